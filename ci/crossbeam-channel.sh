@@ -6,7 +6,29 @@ set -ex
 export RUSTFLAGS="-D warnings"
 
 cargo check --bins --examples --tests
+cargo check --no-default-features
+cargo check --no-default-features --features flavor-array
+cargo check --no-default-features --features flavor-zero
+cargo check --no-default-features --features timers
 cargo test -- --test-threads=1
+cargo test --features fault-injection --test fault
+cargo test --features test-harness --test harness --test wakeup
+cargo test --features debug-waiters --test debug
+cargo test --features debug-peek --test debug_peek
+cargo test --features size-stats --test message_sizes
+cargo test --features head-age --test head_age
+cargo test --features priority-inheritance --test priority
+cargo test --features bench --test bench
+cargo test --features tests-support --test linearizability
+cargo test --features fence-audit --test audit
+cargo test --features strict-ordering --test array --test list --test zero --test select
+
+# These features are exempt from the minimum supported Rust version.
+if [[ "$TRAVIS_RUST_VERSION" != "1.28.0" ]]; then
+    cargo test --features tracing,tracing-core --test traced
+    cargo test --features metrics --test metered
+    cargo test --features boottime --test clock
+fi
 
 if [[ "$TRAVIS_RUST_VERSION" == "nightly" ]]; then
     cd benchmarks
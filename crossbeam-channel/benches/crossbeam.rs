@@ -172,6 +172,43 @@ mod unbounded {
         .unwrap();
     }
 
+    #[bench]
+    fn mpsc_unique(b: &mut Bencher) {
+        let threads = num_cpus::get() - 1;
+        let steps = TOTAL_STEPS / threads;
+        let (s, r) = unbounded::<i32>();
+        let r = r.into_unique().unwrap();
+
+        let (s1, r1) = bounded(0);
+        let (s2, r2) = bounded(0);
+        scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|_| {
+                    while r1.recv().is_ok() {
+                        for i in 0..steps {
+                            s.send(i as i32).unwrap();
+                        }
+                        s2.send(()).unwrap();
+                    }
+                });
+            }
+
+            b.iter(|| {
+                for _ in 0..threads {
+                    s1.send(()).unwrap();
+                }
+                for _ in 0..steps * threads {
+                    r.recv().unwrap();
+                }
+                for _ in 0..threads {
+                    r2.recv().unwrap();
+                }
+            });
+            drop(s1);
+        })
+        .unwrap();
+    }
+
     #[bench]
     fn mpmc(b: &mut Bencher) {
         let threads = num_cpus::get();
@@ -0,0 +1,458 @@
+//! Bounded channels whose capacity adapts to how often their senders and receivers block.
+
+use std::cmp;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use budget::take_unit;
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use err::{SendError, SendTimeoutError, TrySendError};
+use signal::DisconnectedSignal;
+use utils::Spinlock;
+
+/// The number of operations after which the capacity is reconsidered.
+const WINDOW: usize = 64;
+
+/// The capacity grows once more than one in this many sends of a window had to block.
+const GROW_RATIO: usize = 8;
+
+/// The capacity shrinks once more than one in this many receives of a window had to block, while
+/// no send did.
+const SHRINK_RATIO: usize = 2;
+
+/// Creates a bounded channel whose capacity adapts to its load.
+///
+/// **This is experimental.**
+///
+/// The channel starts out holding at most `min_cap` messages. Every few operations, it looks at
+/// how often senders and receivers had to block since the last time. If senders often found the
+/// channel full, the capacity is doubled, up to `max_cap`. If no sender had to wait but receivers
+/// often found the channel empty, the queue is larger than it needs to be, and the capacity is
+/// halved, down to `min_cap`. This suits services where any fixed capacity is either too small
+/// for bursts or wastes memory and latency the rest of the time.
+///
+/// Every change of the capacity is reported as a [`CapacityDecision`] to the receivers returned
+/// by [`AdaptiveReceiver::decisions`].
+///
+/// [`CapacityDecision`]: enum.CapacityDecision.html
+/// [`AdaptiveReceiver::decisions`]: struct.AdaptiveReceiver.html#method.decisions
+///
+/// # Panics
+///
+/// Panics if `min_cap` is zero or greater than `max_cap`.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::adaptive_bounded;
+///
+/// let (s, r) = adaptive_bounded(1, 64);
+/// let decisions = r.decisions();
+/// assert_eq!(s.capacity(), 1);
+///
+/// // A fast producer and a slow consumer.
+/// thread::spawn(move || {
+///     for i in 0..1000 {
+///         s.send(i).unwrap();
+///     }
+/// });
+/// for _ in 0..1000 {
+///     r.recv().unwrap();
+///     thread::yield_now();
+/// }
+///
+/// // Adjustments are reported in the order they were made.
+/// for decision in decisions.try_iter() {
+///     println!("{:?}", decision);
+/// }
+/// ```
+pub fn adaptive_bounded<T>(
+    min_cap: usize,
+    max_cap: usize,
+) -> (AdaptiveSender<T>, AdaptiveReceiver<T>) {
+    assert!(min_cap > 0, "adaptive capacity must be at least one");
+    assert!(
+        min_cap <= max_cap,
+        "minimum capacity must not exceed maximum capacity"
+    );
+
+    // Slots above the current capacity are taken up by reserved units.
+    let (take, give) = bounded(max_cap);
+    for _ in min_cap..max_cap {
+        take.try_send(()).unwrap();
+    }
+
+    let tuner = Arc::new(Tuner {
+        min_cap,
+        max_cap,
+        capacity: AtomicUsize::new(min_cap),
+        take,
+        give,
+        ops: AtomicUsize::new(0),
+        sends: AtomicUsize::new(0),
+        blocked_sends: AtomicUsize::new(0),
+        recvs: AtomicUsize::new(0),
+        blocked_recvs: AtomicUsize::new(0),
+        adjusting: Spinlock::new(()),
+        subscribers: Spinlock::new(Vec::new()),
+    });
+
+    let (s, r) = unbounded();
+    let gone = s.disconnected();
+    let s = AdaptiveSender {
+        sender: s,
+        gone,
+        tuner: tuner.clone(),
+    };
+    (s, AdaptiveReceiver { receiver: r, tuner })
+}
+
+/// A change of the capacity of an adaptive channel.
+///
+/// Received from the channel returned by [`AdaptiveReceiver::decisions`].
+///
+/// [`AdaptiveReceiver::decisions`]: struct.AdaptiveReceiver.html#method.decisions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CapacityDecision {
+    /// The capacity grew because senders often found the channel full.
+    Grew {
+        /// The capacity before the change.
+        from: usize,
+
+        /// The capacity after the change.
+        to: usize,
+    },
+
+    /// The capacity shrank because receivers often found the channel empty.
+    Shrank {
+        /// The capacity before the change.
+        from: usize,
+
+        /// The capacity after the change.
+        to: usize,
+    },
+}
+
+/// Tracks blocking on both sides of an adaptive channel and adjusts its capacity.
+///
+/// Every message in the channel holds a unit in a bounded channel of `max_cap` units, and so does
+/// every slot above the current capacity. Growing gives reserved units back, and shrinking
+/// reserves free ones.
+struct Tuner {
+    /// The lower bound of the capacity.
+    min_cap: usize,
+
+    /// The upper bound of the capacity.
+    max_cap: usize,
+
+    /// The current capacity.
+    capacity: AtomicUsize,
+
+    /// Sending into this channel takes a unit.
+    take: Sender<()>,
+
+    /// Receiving from this channel gives a unit back.
+    give: Receiver<()>,
+
+    /// The number of operations so far.
+    ops: AtomicUsize,
+
+    /// The number of sends in the current window.
+    sends: AtomicUsize,
+
+    /// The number of sends in the current window that had to block.
+    blocked_sends: AtomicUsize,
+
+    /// The number of receives in the current window.
+    recvs: AtomicUsize,
+
+    /// The number of receives in the current window that had to block.
+    blocked_recvs: AtomicUsize,
+
+    /// Held while the capacity is being adjusted.
+    adjusting: Spinlock<()>,
+
+    /// Senders of the channels returned by `decisions`.
+    subscribers: Spinlock<Vec<Sender<CapacityDecision>>>,
+}
+
+impl Tuner {
+    /// Records a send, and adjusts the capacity at the end of a window.
+    fn record_send(&self, blocked: bool) {
+        self.sends.fetch_add(1, Ordering::Relaxed);
+        if blocked {
+            self.blocked_sends.fetch_add(1, Ordering::Relaxed);
+        }
+        self.tick();
+    }
+
+    /// Records a receive, and adjusts the capacity at the end of a window.
+    fn record_recv(&self, blocked: bool) {
+        self.recvs.fetch_add(1, Ordering::Relaxed);
+        if blocked {
+            self.blocked_recvs.fetch_add(1, Ordering::Relaxed);
+        }
+        self.tick();
+    }
+
+    /// Counts an operation, and adjusts the capacity at the end of a window.
+    fn tick(&self) {
+        if self.ops.fetch_add(1, Ordering::Relaxed) % WINDOW == WINDOW - 1 {
+            self.adjust();
+        }
+    }
+
+    /// Adjusts the capacity based on the operations in the window that just ended.
+    fn adjust(&self) {
+        let _guard = self.adjusting.lock();
+
+        let sends = self.sends.swap(0, Ordering::Relaxed);
+        let blocked_sends = self.blocked_sends.swap(0, Ordering::Relaxed);
+        let recvs = self.recvs.swap(0, Ordering::Relaxed);
+        let blocked_recvs = self.blocked_recvs.swap(0, Ordering::Relaxed);
+
+        let from = self.capacity.load(Ordering::Relaxed);
+        let decision = if blocked_sends * GROW_RATIO > sends && from < self.max_cap {
+            let target = cmp::min(from.saturating_mul(2), self.max_cap);
+            let mut to = from;
+            while to < target && self.give.try_recv().is_ok() {
+                to += 1;
+            }
+            CapacityDecision::Grew { from, to }
+        } else if blocked_sends == 0 && blocked_recvs * SHRINK_RATIO > recvs && from > self.min_cap
+        {
+            // Slots still holding messages can't be reserved, so this might shrink less.
+            let target = cmp::max(from / 2, self.min_cap);
+            let mut to = from;
+            while to > target && self.take.try_send(()).is_ok() {
+                to -= 1;
+            }
+            CapacityDecision::Shrank { from, to }
+        } else {
+            return;
+        };
+
+        let to = match decision {
+            CapacityDecision::Grew { to, .. } | CapacityDecision::Shrank { to, .. } => to,
+        };
+        if to != from {
+            self.capacity.store(to, Ordering::Relaxed);
+            self.subscribers.lock().retain(|s| s.send(decision).is_ok());
+        }
+    }
+
+    /// Gives back the unit of a received message.
+    fn release(&self) {
+        // Units are only taken by sends and reservations, so one is always available.
+        let _ = self.give.try_recv();
+    }
+}
+
+/// The sending side of an adaptive channel.
+///
+/// Created by the [`adaptive_bounded`] function.
+///
+/// [`adaptive_bounded`]: fn.adaptive_bounded.html
+pub struct AdaptiveSender<T> {
+    /// The messages.
+    sender: Sender<T>,
+
+    /// Set once all receivers of the channel have been dropped.
+    gone: DisconnectedSignal,
+
+    /// The capacity of the channel.
+    tuner: Arc<Tuner>,
+}
+
+impl<T> AdaptiveSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// A send that fails because the channel is full counts as a blocked send.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.gone.is_set() {
+            return Err(TrySendError::Disconnected(msg));
+        }
+        if self.tuner.take.try_send(()).is_err() {
+            self.tuner.record_send(true);
+            return Err(TrySendError::Full(msg));
+        }
+
+        self.tuner.record_send(false);
+        let res = self.sender.try_send(msg);
+        if res.is_err() {
+            self.tuner.release();
+        }
+        res
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.send_deadline(msg, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_deadline(msg, Some(Instant::now() + timeout))
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the current capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.tuner.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Sends a message, waiting for room in the channel until the deadline.
+    fn send_deadline(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        let blocked = self.tuner.take.try_send(()).is_err();
+        self.tuner.record_send(blocked);
+
+        if blocked {
+            if let Err(err) = take_unit(&self.tuner.take, &self.gone, deadline) {
+                return Err(match err {
+                    SendTimeoutError::Timeout(()) => SendTimeoutError::Timeout(msg),
+                    SendTimeoutError::Disconnected(()) => SendTimeoutError::Disconnected(msg),
+                });
+            }
+        }
+
+        self.sender.send(msg).map_err(|SendError(msg)| {
+            self.tuner.release();
+            SendTimeoutError::Disconnected(msg)
+        })
+    }
+}
+
+impl<T> Clone for AdaptiveSender<T> {
+    fn clone(&self) -> Self {
+        AdaptiveSender {
+            sender: self.sender.clone(),
+            gone: self.gone.clone(),
+            tuner: self.tuner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for AdaptiveSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("AdaptiveSender { .. }")
+    }
+}
+
+/// The receiving side of an adaptive channel.
+///
+/// Created by the [`adaptive_bounded`] function.
+///
+/// [`adaptive_bounded`]: fn.adaptive_bounded.html
+pub struct AdaptiveReceiver<T> {
+    /// The messages.
+    receiver: Receiver<T>,
+
+    /// The capacity of the channel.
+    tuner: Arc<Tuner>,
+}
+
+impl<T> AdaptiveReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// A receive that fails because the channel is empty counts as a blocked receive.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let res = self.receiver.try_recv();
+        match res {
+            Ok(_) => {
+                self.tuner.release();
+                self.tuner.record_recv(false);
+            }
+            Err(TryRecvError::Empty) => self.tuner.record_recv(true),
+            Err(TryRecvError::Disconnected) => {}
+        }
+        res
+    }
+
+    /// Blocks the current thread until a message is received or the channel is disconnected.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_deadline(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Some(Instant::now() + timeout))
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the current capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.tuner.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Returns a receiver that gets a [`CapacityDecision`] every time the capacity changes.
+    ///
+    /// Changes made before this method is called are not reported. The returned receiver gets
+    /// disconnected once the channel is destroyed.
+    ///
+    /// [`CapacityDecision`]: enum.CapacityDecision.html
+    pub fn decisions(&self) -> Receiver<CapacityDecision> {
+        let (s, r) = unbounded();
+        self.tuner.subscribers.lock().push(s);
+        r
+    }
+
+    /// Receives a message, waiting until the deadline while the channel is empty.
+    fn recv_deadline(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let res = match self.receiver.try_recv() {
+            Ok(msg) => {
+                self.tuner.record_recv(false);
+                Ok(msg)
+            }
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => {
+                self.tuner.record_recv(true);
+                self.receiver.recv_deadline(deadline)
+            }
+        };
+
+        if res.is_ok() {
+            self.tuner.release();
+        }
+        res
+    }
+}
+
+impl<T> Clone for AdaptiveReceiver<T> {
+    fn clone(&self) -> Self {
+        AdaptiveReceiver {
+            receiver: self.receiver.clone(),
+            tuner: self.tuner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for AdaptiveReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("AdaptiveReceiver { .. }")
+    }
+}
@@ -0,0 +1,171 @@
+//! Hints for waking up blocked receivers running close to the sender.
+//!
+//! When several receivers are blocked on a channel, a message wakes up only one of them. By
+//! default, that's the one which started waiting first. On big machines, it may be cheaper to wake
+//! up a receiver running on a core close to the senders, so that the message doesn't have to
+//! travel across the interconnect.
+//!
+//! Threads don't know which core they run on, so receivers that care need to declare it with
+//! [`set_current_core`], typically right after pinning themselves to a core. Senders then describe
+//! the cores they'd like to wake up with [`Sender::wake_preference`]. Blocked receivers on those
+//! cores are woken up first, and the others only if there are none.
+//!
+//! Hints never change which messages are received, only which of the blocked receivers gets to
+//! receive them.
+//!
+//! [`set_current_core`]: fn.set_current_core.html
+//! [`Sender::wake_preference`]: ../struct.Sender.html#method.wake_preference
+//!
+//! # Examples
+//!
+//! ```
+//! use std::thread;
+//! use crossbeam_channel::affinity;
+//! use crossbeam_channel::unbounded;
+//!
+//! let (s, r) = unbounded();
+//!
+//! // Only receivers on cores 0 to 3 are close to the sender.
+//! s.wake_preference(Some((0..4).collect()));
+//!
+//! let handles: Vec<_> = (0..8)
+//!     .map(|core| {
+//!         let r = r.clone();
+//!         thread::spawn(move || {
+//!             affinity::set_current_core(Some(core));
+//!             r.recv().unwrap()
+//!         })
+//!     })
+//!     .collect();
+//!
+//! for i in 0..8 {
+//!     s.send(i).unwrap();
+//! }
+//! for h in handles {
+//!     h.join().unwrap();
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::iter::FromIterator;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The number of cores in a word of a `CoreSet`.
+const WORD_BITS: usize = 64;
+
+/// A set of CPU cores, identified by their indices.
+#[derive(Clone, Default)]
+pub struct CoreSet {
+    /// One bit per core.
+    words: Vec<u64>,
+}
+
+impl CoreSet {
+    /// Creates an empty set.
+    pub fn new() -> CoreSet {
+        CoreSet { words: Vec::new() }
+    }
+
+    /// Adds a core to the set.
+    pub fn insert(&mut self, core: usize) {
+        let word = core / WORD_BITS;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1u64 << (core % WORD_BITS);
+    }
+
+    /// Removes a core from the set.
+    pub fn remove(&mut self, core: usize) {
+        if let Some(word) = self.words.get_mut(core / WORD_BITS) {
+            *word &= !(1u64 << (core % WORD_BITS));
+        }
+    }
+
+    /// Returns `true` if the set contains the core.
+    pub fn contains(&self, core: usize) -> bool {
+        match self.words.get(core / WORD_BITS) {
+            Some(word) => word & (1u64 << (core % WORD_BITS)) != 0,
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the set contains no cores.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the word at index `i`, which is zero past the end.
+    fn word(&self, i: usize) -> u64 {
+        self.words.get(i).cloned().unwrap_or(0)
+    }
+
+    /// Returns the cores in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.words.len() * WORD_BITS).filter(move |&core| self.contains(core))
+    }
+}
+
+impl FromIterator<usize> for CoreSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> CoreSet {
+        let mut set = CoreSet::new();
+        for core in iter {
+            set.insert(core);
+        }
+        set
+    }
+}
+
+impl PartialEq for CoreSet {
+    fn eq(&self, other: &CoreSet) -> bool {
+        // Trailing words may be zero after cores get removed.
+        let len = self.words.len().max(other.words.len());
+        (0..len).all(|i| self.word(i) == other.word(i))
+    }
+}
+
+impl Eq for CoreSet {}
+
+impl fmt::Debug for CoreSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+thread_local! {
+    /// The core the current thread has declared to run on.
+    static CURRENT_CORE: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// Set once any thread has declared its core, so that blocking threads don't look up
+/// `CURRENT_CORE` in programs that never do.
+///
+/// Relaxed ordering suffices: a thread only reads its own declaration, and sets the flag itself
+/// before making one.
+static DECLARED: AtomicBool = AtomicBool::new(false);
+
+/// Declares the core the current thread runs on, or clears the declaration.
+///
+/// The declaration is recorded whenever the thread blocks in a receive operation, so that senders
+/// with a [`wake_preference`] can wake it up ahead of other receivers. It isn't checked in any
+/// way, so it's only useful for threads that are pinned to a core.
+///
+/// [`wake_preference`]: ../struct.Sender.html#method.wake_preference
+pub fn set_current_core(core: Option<usize>) {
+    if core.is_some() && !DECLARED.load(Ordering::Relaxed) {
+        DECLARED.store(true, Ordering::Relaxed);
+    }
+    let _ = CURRENT_CORE.try_with(|current| current.set(core));
+}
+
+/// Returns the core the current thread has declared to run on.
+#[inline]
+pub fn current_core() -> Option<usize> {
+    if !DECLARED.load(Ordering::Relaxed) {
+        return None;
+    }
+    CURRENT_CORE
+        .try_with(|current| current.get())
+        .unwrap_or(None)
+}
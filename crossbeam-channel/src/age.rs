@@ -0,0 +1,112 @@
+//! Tracking of how long the oldest message has been waiting in a channel.
+//!
+//! Timestamping every message would make every send slower, so instead a channel keeps a queue of
+//! sparse probes: while tracking is enabled, a send records its position and the current time if
+//! no probe has been recorded during the last millisecond. A message without a probe was
+//! therefore sent at most a millisecond after the closest probe before it.
+//!
+//! This is only compiled with the `head-age` feature. Tracking is then enabled on the first query,
+//! so channels nobody asks about only pay for an atomic load per send.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use utils::Spinlock;
+
+/// The minimum time between two probes, in nanoseconds.
+const PROBE_INTERVAL_NANOS: u32 = 1_000_000;
+
+/// The maximum number of probes kept at once.
+const MAX_PROBES: usize = 1024;
+
+/// Recorded probes.
+struct Probes {
+    /// Positions of probed messages and the instants they were sent at, oldest first.
+    queue: VecDeque<(usize, Instant)>,
+
+    /// The instant of the most recent probe whose message has been received.
+    last_received: Option<Instant>,
+}
+
+/// Tracks the age of the message at the head of a channel.
+pub struct Age {
+    /// Set on the first query.
+    enabled: AtomicBool,
+
+    /// Recorded probes.
+    probes: Spinlock<Probes>,
+}
+
+impl Age {
+    /// Creates a tracker with tracking disabled.
+    pub fn new() -> Age {
+        Age {
+            enabled: AtomicBool::new(false),
+            probes: Spinlock::new(Probes {
+                queue: VecDeque::new(),
+                last_received: None,
+            }),
+        }
+    }
+
+    /// Records that a message is being sent at position `pos`.
+    ///
+    /// Positions must increase with every sent message.
+    #[inline]
+    pub fn record(&self, pos: usize) {
+        if self.enabled.load(Ordering::Relaxed) {
+            self.probe(pos);
+        }
+    }
+
+    /// Records a probe unless one has been recorded recently.
+    #[cold]
+    fn probe(&self, pos: usize) {
+        let now = Instant::now();
+        let mut probes = self.probes.lock();
+
+        if let Some(&(_, last)) = probes.queue.back() {
+            if now < last + Duration::new(0, PROBE_INTERVAL_NANOS) {
+                return;
+            }
+        }
+        if probes.queue.len() < MAX_PROBES {
+            probes.queue.push_back((pos, now));
+        }
+    }
+
+    /// Returns how long the message at position `head` has been waiting, enabling tracking if
+    /// it's not enabled yet.
+    ///
+    /// Returns `None` if the channel is empty or there is nothing known about its messages.
+    pub fn head_age(&self, head: usize, is_empty: bool) -> Option<Duration> {
+        self.enabled.store(true, Ordering::Relaxed);
+        let mut probes = self.probes.lock();
+
+        // Forget probes of received messages.
+        while let Some(&(pos, time)) = probes.queue.front() {
+            if pos >= head {
+                break;
+            }
+            probes.last_received = Some(time);
+            probes.queue.pop_front();
+        }
+
+        if is_empty {
+            return None;
+        }
+
+        match probes.queue.front() {
+            // The head message has been probed.
+            Some(&(pos, time)) if pos == head => Some(time.elapsed()),
+            _ => match probes.last_received {
+                // The head message was sent shortly after the last received probe.
+                Some(time) => Some(time.elapsed()),
+                // The head message was sent before tracking got enabled, so it's at least as old
+                // as the first probe.
+                None => probes.queue.front().map(|&(_, time)| time.elapsed()),
+            },
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Randomized scheduling for auditing the memory orderings of channel protocols.
+//!
+//! The protocols behind every channel flavor contain yield points: places where another thread
+//! interleaving is most likely to expose a missing fence or an ordering that is too weak, like
+//! the window between claiming a slot and publishing a message into it. Code running under
+//! [`with_random_yields`] gets rescheduled at those points at random, which shakes out
+//! interleavings that almost never happen in regular stress tests.
+//!
+//! [`audit_flavors`] runs the protocols of all flavors this way and checks that every message is
+//! delivered exactly once and in order. Combined with the `strict-ordering` feature, it helps tell
+//! whether a failure is caused by a memory ordering or by a logic error.
+//!
+//! This module is only available with the `fence-audit` feature.
+//!
+//! [`with_random_yields`]: fn.with_random_yields.html
+//! [`audit_flavors`]: fn.audit_flavors.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::audit;
+//!
+//! let report = audit::audit_flavors(42, 100);
+//! assert!(report.yields > 0);
+//! ```
+
+use std::cell::Cell;
+use std::thread;
+
+use crossbeam_utils::thread::scope;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use select::Select;
+
+thread_local! {
+    /// State of the random number generator, or zero if yields are not randomized.
+    static RNG: Cell<u64> = Cell::new(0);
+
+    /// The number of yield points the current thread has passed.
+    static POINTS: Cell<u64> = Cell::new(0);
+
+    /// The number of times the current thread yielded at a yield point.
+    static YIELDS: Cell<u64> = Cell::new(0);
+}
+
+/// Called at every yield point when the `fence-audit` feature is enabled.
+pub(crate) fn yield_point() {
+    let _ = POINTS.try_with(|p| p.set(p.get() + 1));
+
+    let yield_now = RNG
+        .try_with(|rng| {
+            let mut x = rng.get();
+            if x == 0 {
+                return false;
+            }
+
+            // Xorshift.
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            rng.set(x);
+
+            // Yield at roughly one in four points.
+            x % 4 == 0
+        })
+        .unwrap_or(false);
+
+    if yield_now {
+        let _ = YIELDS.try_with(|y| y.set(y.get() + 1));
+        thread::yield_now();
+    }
+}
+
+/// Runs a closure on the current thread while yielding at random yield points.
+///
+/// The `seed` determines which yield points the thread yields at. The same seed doesn't make the
+/// interleaving reproducible, since that also depends on the OS scheduler, but it makes
+/// reproducing a failure more likely.
+pub fn with_random_yields<F, R>(seed: u64, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    /// Restores the previous state of the generator, even if the closure panics.
+    struct Restore(u64);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0;
+            let _ = RNG.try_with(|rng| rng.set(prev));
+        }
+    }
+
+    // Zero is the one state xorshift never leaves, so it's reserved for "disabled".
+    let state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let prev = RNG.try_with(|rng| rng.replace(state)).unwrap_or(0);
+    let _restore = Restore(prev);
+    f()
+}
+
+/// Returns the number of yield points the current thread has passed so far.
+pub fn yield_points() -> u64 {
+    POINTS.try_with(|p| p.get()).unwrap_or(0)
+}
+
+/// Returns the number of times the current thread has yielded at a yield point so far.
+pub fn yields() -> u64 {
+    YIELDS.try_with(|y| y.get()).unwrap_or(0)
+}
+
+/// Statistics gathered by [`audit_flavors`].
+///
+/// [`audit_flavors`]: fn.audit_flavors.html
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The number of channel configurations that were exercised.
+    pub configurations: usize,
+
+    /// The number of yield points passed by all threads.
+    pub yield_points: u64,
+
+    /// The number of times threads yielded at yield points.
+    pub yields: u64,
+}
+
+/// A message tagged with the index of its producer and its position among that producer's messages.
+type Message = (usize, usize);
+
+/// The number of producers and consumers in every configuration.
+const THREADS: usize = 2;
+
+/// Runs the protocols of all channel flavors under random yields and checks their results.
+///
+/// Each configuration sends `messages` messages from every one of several producers to several
+/// consumers, through zero-capacity, bounded, and unbounded channels, both with plain receive
+/// operations and with [`Select`]. Every thread runs under [`with_random_yields`] with a seed
+/// derived from `seed`.
+///
+/// [`Select`]: ../struct.Select.html
+/// [`with_random_yields`]: fn.with_random_yields.html
+///
+/// # Panics
+///
+/// Panics if a message is lost, delivered twice, or received out of the order it was sent in by
+/// its producer.
+pub fn audit_flavors(seed: u64, messages: usize) -> AuditReport {
+    let mut report = AuditReport::default();
+    // Capacities of the channels, where `None` stands for an unbounded channel.
+    let caps = [Some(0), Some(1), Some(3), None];
+
+    for (i, &cap) in caps.iter().enumerate() {
+        for &select in &[false, true] {
+            let channel = || match cap {
+                Some(cap) => bounded(cap),
+                None => unbounded(),
+            };
+            let seed = seed ^ ((i as u64) << 1 | select as u64) << 32;
+            let (s, r) = channel();
+            let (_idle_s, idle_r) = channel();
+
+            let (points, ys) = audit(seed, messages, select, s, &r, &idle_r);
+            report.configurations += 1;
+            report.yield_points += points;
+            report.yields += ys;
+        }
+    }
+    report
+}
+
+/// Runs one configuration of the audit and returns the number of yield points passed and yields.
+///
+/// With `select`, consumers select over `r` and `idle`, a receiver that never gets a message.
+fn audit(
+    seed: u64,
+    messages: usize,
+    select: bool,
+    s: Sender<Message>,
+    r: &Receiver<Message>,
+    idle: &Receiver<Message>,
+) -> (u64, u64) {
+    /// Runs `f` under random yields and returns its result with the counters it accumulated.
+    fn counted<F: FnOnce() -> R, R>(seed: u64, f: F) -> (R, u64, u64) {
+        with_random_yields(seed, || {
+            let start = (yield_points(), yields());
+            let res = f();
+            (res, yield_points() - start.0, yields() - start.1)
+        })
+    }
+
+    let results = scope(|scope| {
+        let mut handles = Vec::new();
+
+        for p in 0..THREADS {
+            let s = s.clone();
+            handles.push(scope.spawn(move |_| {
+                counted(seed.wrapping_add(p as u64), || {
+                    for i in 0..messages {
+                        s.send((p, i)).unwrap();
+                    }
+                    0
+                })
+            }));
+        }
+        drop(s);
+
+        for c in 0..THREADS {
+            handles.push(scope.spawn(move |_| {
+                counted(seed.wrapping_add((THREADS + c) as u64), || {
+                    let mut last = [None; THREADS];
+                    let mut count = 0;
+
+                    loop {
+                        let msg = if select {
+                            let mut sel = Select::new();
+                            sel.recv(r);
+                            sel.recv(idle);
+                            let oper = sel.select();
+                            assert_eq!(oper.index(), 0, "selected a receiver without messages");
+                            oper.recv(r)
+                        } else {
+                            r.recv()
+                        };
+
+                        let (p, i) = match msg {
+                            Ok(msg) => msg,
+                            Err(_) => break,
+                        };
+                        if let Some(l) = last[p] {
+                            assert!(l < i, "messages from producer {} out of order", p);
+                        }
+                        last[p] = Some(i);
+                        count += 1;
+                    }
+                    count
+                })
+            }));
+        }
+
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    let mut total = 0;
+    let mut points = 0;
+    let mut ys = 0;
+    for (count, p, y) in results {
+        total += count;
+        points += p;
+        ys += y;
+    }
+    assert_eq!(
+        total,
+        messages * THREADS,
+        "messages were lost or duplicated"
+    );
+    (points, ys)
+}
@@ -0,0 +1,323 @@
+//! Reusable workloads for measuring the performance of channels.
+//!
+//! Each [`Workload`] runs a fixed communication pattern for a given number of iterations and
+//! returns how long that took, not counting the time needed to spawn and join threads. This makes
+//! it possible to reproduce the performance characteristics of the crate on any machine, and to
+//! catch platform-specific regressions by running the same workloads on every release.
+//!
+//! The signature of [`Workload::run`] matches the closure expected by `iter_custom` in
+//! [criterion], so hooking a workload into a criterion benchmark takes one line:
+//!
+//! ```ignore
+//! use criterion::Criterion;
+//! use crossbeam_channel::bench::Workload;
+//!
+//! fn channels(c: &mut Criterion) {
+//!     for w in Workload::suite() {
+//!         c.bench_function(&w.name(), move |b| b.iter_custom(|iters| w.run(iters)));
+//!     }
+//! }
+//! ```
+//!
+//! This module is only available with the `bench` feature.
+//!
+//! [`Workload`]: struct.Workload.html
+//! [`Workload::run`]: struct.Workload.html#method.run
+//! [criterion]: https://crates.io/crates/criterion
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::bench::{Flavor, Workload};
+//!
+//! let w = Workload::spsc_ping_pong(Flavor::Bounded(0));
+//! let elapsed = w.run(1000);
+//! println!("{}: {:?} per round trip", w.name(), elapsed / 1000);
+//! ```
+
+use std::fmt;
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::thread::scope;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use select::Select;
+
+/// The kind of channel a workload communicates through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Flavor {
+    /// A channel created by [`bounded`] with the given capacity.
+    ///
+    /// [`bounded`]: ../fn.bounded.html
+    Bounded(usize),
+
+    /// A channel created by [`unbounded`].
+    ///
+    /// [`unbounded`]: ../fn.unbounded.html
+    Unbounded,
+}
+
+impl Flavor {
+    /// Creates a channel of this flavor.
+    fn channel<T>(self) -> (Sender<T>, Receiver<T>) {
+        match self {
+            Flavor::Bounded(cap) => bounded(cap),
+            Flavor::Unbounded => unbounded(),
+        }
+    }
+}
+
+impl fmt::Display for Flavor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Flavor::Bounded(cap) => write!(f, "bounded({})", cap),
+            Flavor::Unbounded => "unbounded".fmt(f),
+        }
+    }
+}
+
+/// The communication pattern of a workload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    /// A message travels to another thread and back.
+    PingPong,
+
+    /// Several producers send into one channel drained by a single consumer.
+    FanIn { producers: usize },
+
+    /// Producers send into separate channels, which a single consumer selects over.
+    SelectStorm { channels: usize },
+
+    /// Several producers send messages in bursts, yielding between bursts.
+    Bursty { producers: usize, burst: usize },
+}
+
+/// A communication pattern that can be run and timed.
+///
+/// See the [module-level documentation](index.html) for more.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Workload {
+    kind: Kind,
+    flavor: Flavor,
+}
+
+impl Workload {
+    /// A single message bounces between two threads.
+    ///
+    /// Each iteration is one round trip, so this measures latency.
+    pub fn spsc_ping_pong(flavor: Flavor) -> Workload {
+        Workload {
+            kind: Kind::PingPong,
+            flavor,
+        }
+    }
+
+    /// Several producers send into one channel, which a single consumer drains.
+    ///
+    /// Each iteration is one message, so this measures throughput under contention among
+    /// senders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `producers` is zero.
+    pub fn mpmc_fan_in(flavor: Flavor, producers: usize) -> Workload {
+        assert!(producers > 0, "number of producers must be positive");
+        Workload {
+            kind: Kind::FanIn { producers },
+            flavor,
+        }
+    }
+
+    /// Each of several producers sends into its own channel, and a single consumer receives from
+    /// all of them with [`Select`].
+    ///
+    /// Each iteration is one message, so this measures the overhead of selection.
+    ///
+    /// [`Select`]: ../struct.Select.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is zero.
+    pub fn select_storm(flavor: Flavor, channels: usize) -> Workload {
+        assert!(channels > 0, "number of channels must be positive");
+        Workload {
+            kind: Kind::SelectStorm { channels },
+            flavor,
+        }
+    }
+
+    /// Several producers send bursts of `burst` messages into one channel, yielding the processor
+    /// after every burst.
+    ///
+    /// Each iteration is one message. The consumer keeps going to sleep and waking up, so this
+    /// measures the cost of blocking and unblocking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `producers` or `burst` is zero.
+    pub fn bursty_producers(flavor: Flavor, producers: usize, burst: usize) -> Workload {
+        assert!(producers > 0, "number of producers must be positive");
+        assert!(burst > 0, "burst size must be positive");
+        Workload {
+            kind: Kind::Bursty { producers, burst },
+            flavor,
+        }
+    }
+
+    /// Returns a standard set of workloads covering the common channel flavors.
+    pub fn suite() -> Vec<Workload> {
+        let flavors = [Flavor::Bounded(0), Flavor::Bounded(1024), Flavor::Unbounded];
+        let mut suite = Vec::new();
+
+        for &flavor in &flavors {
+            suite.push(Workload::spsc_ping_pong(flavor));
+            suite.push(Workload::mpmc_fan_in(flavor, 4));
+            suite.push(Workload::select_storm(flavor, 4));
+            suite.push(Workload::bursty_producers(flavor, 4, 64));
+        }
+        suite
+    }
+
+    /// Returns the flavor of the channels the workload communicates through.
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Returns a name identifying the workload and its parameters, like
+    /// `"mpmc_fan_in/4/bounded(0)"`.
+    pub fn name(&self) -> String {
+        match self.kind {
+            Kind::PingPong => format!("spsc_ping_pong/{}", self.flavor),
+            Kind::FanIn { producers } => format!("mpmc_fan_in/{}/{}", producers, self.flavor),
+            Kind::SelectStorm { channels } => {
+                format!("select_storm/{}/{}", channels, self.flavor)
+            }
+            Kind::Bursty { producers, burst } => {
+                format!("bursty_producers/{}x{}/{}", producers, burst, self.flavor)
+            }
+        }
+    }
+
+    /// Runs the workload for `iters` iterations and returns the time it took.
+    ///
+    /// Threads are spawned before the clock starts and joined after it stops.
+    pub fn run(&self, iters: u64) -> Duration {
+        match self.kind {
+            Kind::PingPong => ping_pong(self.flavor, iters),
+            Kind::FanIn { producers } => fan_in(self.flavor, producers, 1, iters),
+            Kind::SelectStorm { channels } => select_storm(self.flavor, channels, iters),
+            Kind::Bursty { producers, burst } => fan_in(self.flavor, producers, burst, iters),
+        }
+    }
+}
+
+/// Splits `iters` messages among `n` producers as evenly as possible.
+fn share(iters: u64, n: usize, i: usize) -> u64 {
+    let n = n as u64;
+    let i = i as u64;
+    iters / n + if i < iters % n { 1 } else { 0 }
+}
+
+/// Bounces a message between two threads `iters` times.
+fn ping_pong(flavor: Flavor, iters: u64) -> Duration {
+    let (s1, r1) = flavor.channel::<u64>();
+    let (s2, r2) = flavor.channel::<u64>();
+    let mut elapsed = Duration::from_secs(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            while let Ok(msg) = r1.recv() {
+                s2.send(msg).unwrap();
+            }
+        });
+
+        let start = Instant::now();
+        for i in 0..iters {
+            s1.send(i).unwrap();
+            r2.recv().unwrap();
+        }
+        elapsed = start.elapsed();
+        drop(s1);
+    })
+    .unwrap();
+
+    elapsed
+}
+
+/// Sends `iters` messages from `producers` threads into one channel, yielding after every
+/// `burst` messages when `burst` is greater than one.
+fn fan_in(flavor: Flavor, producers: usize, burst: usize, iters: u64) -> Duration {
+    let (s, r) = flavor.channel::<u64>();
+    let barrier = Barrier::new(producers + 1);
+    let mut elapsed = Duration::from_secs(0);
+
+    scope(|scope| {
+        for i in 0..producers {
+            let s = s.clone();
+            let barrier = &barrier;
+            scope.spawn(move |_| {
+                barrier.wait();
+                for j in 0..share(iters, producers, i) {
+                    s.send(j).unwrap();
+                    if burst > 1 && (j + 1) % burst as u64 == 0 {
+                        thread::yield_now();
+                    }
+                }
+            });
+        }
+
+        barrier.wait();
+        let start = Instant::now();
+        for _ in 0..iters {
+            r.recv().unwrap();
+        }
+        elapsed = start.elapsed();
+    })
+    .unwrap();
+
+    elapsed
+}
+
+/// Sends `iters` messages through `channels` channels, received by selecting over all of them.
+fn select_storm(flavor: Flavor, channels: usize, iters: u64) -> Duration {
+    let (senders, receivers): (Vec<_>, Vec<_>) =
+        (0..channels).map(|_| flavor.channel::<u64>()).unzip();
+    let barrier = Barrier::new(channels + 1);
+    let mut elapsed = Duration::from_secs(0);
+
+    scope(|scope| {
+        for (i, s) in senders.into_iter().enumerate() {
+            let barrier = &barrier;
+            scope.spawn(move |_| {
+                barrier.wait();
+                for j in 0..share(iters, channels, i) {
+                    s.send(j).unwrap();
+                }
+            });
+        }
+
+        let mut sel = Select::new();
+        for r in &receivers {
+            sel.recv(r);
+        }
+
+        barrier.wait();
+        let start = Instant::now();
+        let mut received = 0;
+        while received < iters {
+            let oper = sel.select();
+            let index = oper.index();
+            match oper.recv(&receivers[index]) {
+                Ok(_) => received += 1,
+                // The producer is done, so stop selecting its channel.
+                Err(_) => sel.remove(index),
+            }
+        }
+        elapsed = start.elapsed();
+    })
+    .unwrap();
+
+    elapsed
+}
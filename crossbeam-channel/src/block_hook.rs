@@ -0,0 +1,108 @@
+//! A per-thread hook consulted before a blocking operation parks the thread.
+
+use std::cell::Cell;
+
+/// What a thread should do instead of completing a blocking operation right away.
+///
+/// Returned by hooks installed with [`set_block_hook`].
+///
+/// [`set_block_hook`]: fn.set_block_hook.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockDecision {
+    /// Park the thread until the operation can complete, as if no hook was installed.
+    Park,
+
+    /// Check the operation again, and consult the hook once more if it still can't complete.
+    ///
+    /// The hook should return this after doing some useful work, like running another task.
+    Retry,
+}
+
+thread_local! {
+    /// The hook installed on the current thread.
+    static HOOK: Cell<Option<fn() -> BlockDecision>> = Cell::new(None);
+
+    /// `true` while the hook of the current thread is running.
+    static RUNNING: Cell<bool> = Cell::new(false);
+}
+
+/// Installs a hook consulted whenever a blocking operation is about to park the current thread.
+///
+/// Blocking operations are receives, sends, and selects that have to wait. Before parking, they
+/// call the hook, which can keep the thread busy with other work and ask for the operation to be
+/// checked again by returning [`BlockDecision::Retry`]. This lets executors that run channel
+/// operations on their own worker threads keep those workers running tasks instead of losing them
+/// to the OS.
+///
+/// The hook replaces any previously installed one. Blocking operations started from within the
+/// hook park the thread without consulting it, so the hook never runs recursively.
+///
+/// [`BlockDecision::Retry`]: enum.BlockDecision.html#variant.Retry
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{bounded, set_block_hook, BlockDecision};
+///
+/// thread_local! {
+///     static PENDING: Cell<u32> = Cell::new(3);
+/// }
+///
+/// // Pretend to run a pending task, and park once there are none left.
+/// fn run_pending() -> BlockDecision {
+///     PENDING.with(|p| match p.get() {
+///         0 => BlockDecision::Park,
+///         n => {
+///             p.set(n - 1);
+///             BlockDecision::Retry
+///         }
+///     })
+/// }
+///
+/// let (s, r) = bounded(0);
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     s.send(1).unwrap();
+/// });
+///
+/// set_block_hook(run_pending);
+/// assert_eq!(r.recv(), Ok(1));
+/// assert_eq!(PENDING.with(|p| p.get()), 0);
+/// ```
+pub fn set_block_hook(hook: fn() -> BlockDecision) {
+    let _ = HOOK.try_with(|h| h.set(Some(hook)));
+}
+
+/// Removes the hook installed on the current thread, if any.
+pub fn clear_block_hook() {
+    let _ = HOOK.try_with(|h| h.set(None));
+}
+
+/// Consults the hook of the current thread before parking it.
+///
+/// Returns `BlockDecision::Park` if there is no hook or it's already running.
+pub(crate) fn before_park() -> BlockDecision {
+    let hook = match HOOK.try_with(|h| h.get()) {
+        Ok(Some(hook)) => hook,
+        _ => return BlockDecision::Park,
+    };
+
+    if RUNNING.try_with(|r| r.replace(true)).unwrap_or(true) {
+        return BlockDecision::Park;
+    }
+
+    /// Marks the hook as no longer running, even if it panics.
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            let _ = RUNNING.try_with(|r| r.set(false));
+        }
+    }
+
+    let _reset = Reset;
+    hook()
+}
@@ -0,0 +1,128 @@
+//! Senders that feed channels from the standard library as well as channels from this crate.
+
+use std::fmt;
+use std::sync::mpsc;
+
+use channel::Sender;
+use err::{SendError, TrySendError};
+
+/// A sender for either a channel from this crate or a channel from `std::sync::mpsc`.
+///
+/// Code written against this type can send into both kinds of channels, so a library that exposes
+/// `std::sync::mpsc` endpoints can be fed without spawning an adapter thread per channel. Errors
+/// are always reported with the error types of this crate.
+///
+/// A bridged sender is created from a [`Sender`], a `std::sync::mpsc::Sender`, or a
+/// `std::sync::mpsc::SyncSender` with `From`.
+///
+/// [`Sender`]: struct.Sender.html
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc;
+/// use crossbeam_channel::{unbounded, BridgedSender};
+///
+/// fn produce(s: BridgedSender<i32>) {
+///     s.send(1).unwrap();
+/// }
+///
+/// let (s, r) = mpsc::channel();
+/// produce(s.into());
+/// assert_eq!(r.recv(), Ok(1));
+///
+/// let (s, r) = unbounded();
+/// produce(s.into());
+/// assert_eq!(r.recv(), Ok(1));
+/// ```
+pub struct BridgedSender<T> {
+    flavor: BridgedFlavor<T>,
+}
+
+/// The kind of channel a bridged sender sends into.
+enum BridgedFlavor<T> {
+    /// A channel from this crate.
+    Crossbeam(Sender<T>),
+
+    /// An unbounded channel from `std::sync::mpsc`.
+    Std(mpsc::Sender<T>),
+
+    /// A bounded channel from `std::sync::mpsc`.
+    StdSync(mpsc::SyncSender<T>),
+}
+
+impl<T> BridgedSender<T> {
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        match &self.flavor {
+            BridgedFlavor::Crossbeam(s) => s.send(msg),
+            BridgedFlavor::Std(s) => s.send(msg).map_err(|mpsc::SendError(m)| SendError(m)),
+            BridgedFlavor::StdSync(s) => s.send(msg).map_err(|mpsc::SendError(m)| SendError(m)),
+        }
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// Unbounded channels from `std::sync::mpsc` are never full.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        match &self.flavor {
+            BridgedFlavor::Crossbeam(s) => s.try_send(msg),
+            BridgedFlavor::Std(s) => s
+                .send(msg)
+                .map_err(|mpsc::SendError(m)| TrySendError::Disconnected(m)),
+            BridgedFlavor::StdSync(s) => s.try_send(msg).map_err(|err| match err {
+                mpsc::TrySendError::Full(m) => TrySendError::Full(m),
+                mpsc::TrySendError::Disconnected(m) => TrySendError::Disconnected(m),
+            }),
+        }
+    }
+
+    /// Returns `true` if the sender feeds a channel from `std::sync::mpsc`.
+    pub fn is_std(&self) -> bool {
+        match self.flavor {
+            BridgedFlavor::Crossbeam(_) => false,
+            BridgedFlavor::Std(_) | BridgedFlavor::StdSync(_) => true,
+        }
+    }
+}
+
+impl<T> From<Sender<T>> for BridgedSender<T> {
+    fn from(s: Sender<T>) -> BridgedSender<T> {
+        BridgedSender {
+            flavor: BridgedFlavor::Crossbeam(s),
+        }
+    }
+}
+
+impl<T> From<mpsc::Sender<T>> for BridgedSender<T> {
+    fn from(s: mpsc::Sender<T>) -> BridgedSender<T> {
+        BridgedSender {
+            flavor: BridgedFlavor::Std(s),
+        }
+    }
+}
+
+impl<T> From<mpsc::SyncSender<T>> for BridgedSender<T> {
+    fn from(s: mpsc::SyncSender<T>) -> BridgedSender<T> {
+        BridgedSender {
+            flavor: BridgedFlavor::StdSync(s),
+        }
+    }
+}
+
+impl<T> Clone for BridgedSender<T> {
+    fn clone(&self) -> Self {
+        let flavor = match &self.flavor {
+            BridgedFlavor::Crossbeam(s) => BridgedFlavor::Crossbeam(s.clone()),
+            BridgedFlavor::Std(s) => BridgedFlavor::Std(s.clone()),
+            BridgedFlavor::StdSync(s) => BridgedFlavor::StdSync(s.clone()),
+        };
+        BridgedSender { flavor }
+    }
+}
+
+impl<T> fmt::Debug for BridgedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("BridgedSender { .. }")
+    }
+}
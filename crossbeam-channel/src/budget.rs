@@ -0,0 +1,380 @@
+//! A limit on the total number of messages in a set of channels.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use err::{SendError, SendTimeoutError, TrySendError};
+use select::Select;
+use signal::DisconnectedSignal;
+
+/// A limit on the total number of messages in a set of channels.
+///
+/// Channels created with [`bounded`] and [`unbounded`] share the budget: a message takes up one
+/// unit of it from the moment it's sent until it's received, so all the channels together never
+/// hold more than [`limit`] messages. Once the budget is used up, sends block until a message is
+/// received from any of the channels, even if their own channel has room.
+///
+/// This bounds the memory of pipelines that fan out to many queues by a single figure, rather than
+/// by the capacity of each queue multiplied by the number of queues.
+///
+/// The budget can be cloned, and all clones refer to the same budget.
+///
+/// [`bounded`]: struct.Budget.html#method.bounded
+/// [`unbounded`]: struct.Budget.html#method.unbounded
+/// [`limit`]: struct.Budget.html#method.limit
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{Budget, TrySendError};
+///
+/// let budget = Budget::new(3);
+/// let (s1, r1) = budget.unbounded();
+/// let (s2, _r2) = budget.bounded(10);
+///
+/// s1.send(1).unwrap();
+/// s1.send(2).unwrap();
+/// s2.send(3).unwrap();
+///
+/// // The budget is used up, although neither channel is full.
+/// assert_eq!(s2.try_send(4), Err(TrySendError::Full(4)));
+///
+/// // Receiving from one channel makes room in all of them.
+/// assert_eq!(r1.recv(), Ok(1));
+/// assert_eq!(s2.try_send(4), Ok(()));
+/// ```
+#[derive(Clone)]
+pub struct Budget {
+    inner: Arc<Inner>,
+}
+
+/// The units of a budget, represented as messages in a bounded channel.
+struct Inner {
+    /// Sending into this channel takes a unit.
+    take: Sender<()>,
+
+    /// Receiving from this channel gives a unit back.
+    give: Receiver<()>,
+}
+
+impl Budget {
+    /// Creates a budget of `limit` messages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn new(limit: usize) -> Budget {
+        assert!(limit > 0, "a budget needs a nonzero limit");
+
+        let (take, give) = bounded(limit);
+        Budget {
+            inner: Arc::new(Inner { take, give }),
+        }
+    }
+
+    /// Creates a channel of bounded capacity that draws on the budget.
+    ///
+    /// A send blocks if either the channel is full or the budget is used up.
+    pub fn bounded<T>(&self, cap: usize) -> (BudgetedSender<T>, BudgetedReceiver<T>) {
+        let (s, r) = bounded(cap);
+        self.wrap(s, r)
+    }
+
+    /// Creates a channel of unbounded capacity that draws on the budget.
+    ///
+    /// A send blocks only if the budget is used up.
+    pub fn unbounded<T>(&self) -> (BudgetedSender<T>, BudgetedReceiver<T>) {
+        let (s, r) = unbounded();
+        self.wrap(s, r)
+    }
+
+    /// Returns the maximum number of messages in all channels of the budget.
+    pub fn limit(&self) -> usize {
+        self.inner.take.capacity().unwrap()
+    }
+
+    /// Returns the number of messages currently in the channels of the budget.
+    pub fn in_flight(&self) -> usize {
+        self.inner.take.len()
+    }
+
+    /// Wraps the endpoints of a new channel.
+    fn wrap<T>(
+        &self,
+        sender: Sender<Entry<T>>,
+        receiver: Receiver<Entry<T>>,
+    ) -> (BudgetedSender<T>, BudgetedReceiver<T>) {
+        let gone = sender.disconnected();
+        let s = BudgetedSender {
+            sender,
+            gone,
+            budget: self.inner.clone(),
+        };
+        (s, BudgetedReceiver { receiver })
+    }
+}
+
+impl fmt::Debug for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Budget")
+            .field("limit", &self.limit())
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+/// Takes a unit from a channel of units, waiting until the deadline while there is none.
+///
+/// Fails with `Disconnected` once `gone` is disconnected, which means nobody can receive the
+/// message the unit was meant for anymore.
+pub(crate) fn take_unit(
+    take: &Sender<()>,
+    gone: &Receiver<()>,
+    deadline: Option<Instant>,
+) -> Result<(), SendTimeoutError<()>> {
+    let mut sel = Select::new();
+    let index = sel.send(take);
+    sel.recv(gone);
+
+    let oper = match deadline {
+        None => sel.select(),
+        Some(deadline) => {
+            let now = Instant::now();
+            let timeout = if deadline > now {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+            match sel.select_timeout(timeout) {
+                Ok(oper) => oper,
+                Err(_) => return Err(SendTimeoutError::Timeout(())),
+            }
+        }
+    };
+    if oper.index() != index {
+        let _ = oper.recv(gone);
+        return Err(SendTimeoutError::Disconnected(()));
+    }
+    // The owner of the units keeps both ends of their channel alive.
+    oper.send(take, ()).unwrap();
+    Ok(())
+}
+
+/// A unit of a budget, given back when dropped.
+struct Permit {
+    budget: Arc<Inner>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        // Every permit stands for a completed send, so there is always a unit to receive.
+        let _ = self.budget.give.try_recv();
+    }
+}
+
+/// A message together with the unit of the budget it takes up.
+struct Entry<T> {
+    msg: T,
+    permit: Permit,
+}
+
+impl<T> Entry<T> {
+    /// Returns the message, giving its unit back to the budget.
+    fn into_msg(self) -> T {
+        drop(self.permit);
+        self.msg
+    }
+}
+
+/// The sending side of a channel drawing on a [`Budget`].
+///
+/// Created by [`Budget::bounded`] and [`Budget::unbounded`].
+///
+/// [`Budget`]: struct.Budget.html
+/// [`Budget::bounded`]: struct.Budget.html#method.bounded
+/// [`Budget::unbounded`]: struct.Budget.html#method.unbounded
+pub struct BudgetedSender<T> {
+    /// The channel.
+    sender: Sender<Entry<T>>,
+
+    /// Set once all receivers of the channel have been dropped.
+    gone: DisconnectedSignal,
+
+    /// The budget the channel draws on.
+    budget: Arc<Inner>,
+}
+
+impl<T> BudgetedSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// Fails with [`TrySendError::Full`] if either the channel is full or the budget is used up.
+    ///
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.gone.is_set() {
+            return Err(TrySendError::Disconnected(msg));
+        }
+        if self.budget.take.try_send(()).is_err() {
+            return Err(TrySendError::Full(msg));
+        }
+
+        self.sender
+            .try_send(self.entry(msg))
+            .map_err(|err| match err {
+                TrySendError::Full(entry) => TrySendError::Full(entry.into_msg()),
+                TrySendError::Disconnected(entry) => TrySendError::Disconnected(entry.into_msg()),
+            })
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// The send first waits for a unit of the budget and then for room in the channel. While
+    /// waiting for room in a full channel, the message already takes up its unit.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.send_deadline(msg, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// The timeout covers waiting for both a unit of the budget and room in the channel.
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_deadline(msg, Some(Instant::now() + timeout))
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the capacity of the channel, not taking the budget into account.
+    pub fn capacity(&self) -> Option<usize> {
+        self.sender.capacity()
+    }
+
+    /// Sends a message, waiting for a unit of the budget and room in the channel until the
+    /// deadline.
+    fn send_deadline(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        if let Err(err) = take_unit(&self.budget.take, &self.gone, deadline) {
+            return Err(match err {
+                SendTimeoutError::Timeout(()) => SendTimeoutError::Timeout(msg),
+                SendTimeoutError::Disconnected(()) => SendTimeoutError::Disconnected(msg),
+            });
+        }
+
+        let entry = self.entry(msg);
+        let res = self.sender.send_deadline(entry, deadline);
+        res.map_err(|err| match err {
+            SendTimeoutError::Timeout(entry) => SendTimeoutError::Timeout(entry.into_msg()),
+            SendTimeoutError::Disconnected(entry) => {
+                SendTimeoutError::Disconnected(entry.into_msg())
+            }
+        })
+    }
+
+    /// Pairs a message with a unit of the budget that has already been taken.
+    fn entry(&self, msg: T) -> Entry<T> {
+        Entry {
+            msg,
+            permit: Permit {
+                budget: self.budget.clone(),
+            },
+        }
+    }
+}
+
+impl<T> Clone for BudgetedSender<T> {
+    fn clone(&self) -> Self {
+        BudgetedSender {
+            sender: self.sender.clone(),
+            gone: self.gone.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for BudgetedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("BudgetedSender { .. }")
+    }
+}
+
+/// The receiving side of a channel drawing on a [`Budget`].
+///
+/// Created by [`Budget::bounded`] and [`Budget::unbounded`]. Receiving a message gives its unit
+/// back to the budget. So do messages still in the channel when it's dropped.
+///
+/// [`Budget`]: struct.Budget.html
+/// [`Budget::bounded`]: struct.Budget.html#method.bounded
+/// [`Budget::unbounded`]: struct.Budget.html#method.unbounded
+pub struct BudgetedReceiver<T> {
+    receiver: Receiver<Entry<T>>,
+}
+
+impl<T> BudgetedReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// See [`Receiver::try_recv`] for details.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv().map(Entry::into_msg)
+    }
+
+    /// Blocks the current thread until a message is received or the channel is disconnected.
+    ///
+    /// See [`Receiver::recv`] for details.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv().map(Entry::into_msg)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`] for details.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout).map(Entry::into_msg)
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the capacity of the channel, not taking the budget into account.
+    pub fn capacity(&self) -> Option<usize> {
+        self.receiver.capacity()
+    }
+}
+
+impl<T> Clone for BudgetedReceiver<T> {
+    fn clone(&self) -> Self {
+        BudgetedReceiver {
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for BudgetedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("BudgetedReceiver { .. }")
+    }
+}
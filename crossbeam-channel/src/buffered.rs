@@ -0,0 +1,366 @@
+//! Senders that buffer messages and send them in batches.
+//!
+//! A [`BufferedSender`] collects messages in a buffer of its own and only sends them into the
+//! channel once the buffer fills up or it is flushed explicitly. This takes contention off the
+//! channel when many producers send small messages at a high rate. Every clone of a buffered
+//! sender has its own buffer, so it's meant to be cloned once per producer thread.
+//!
+//! Buffering alone means that messages can wait indefinitely if a producer goes quiet in the
+//! middle of a batch. A [`Flusher`] bounds that latency: it runs a background thread that flushes
+//! the buffers of all senders registered with it once their oldest message has waited for longer
+//! than a configured maximum. A single flusher can be shared by any number of buffered senders.
+//!
+//! [`BufferedSender`]: struct.BufferedSender.html
+//! [`Flusher`]: struct.Flusher.html
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//! use crossbeam_channel::buffered::{BufferedSender, Flusher};
+//! use crossbeam_channel::unbounded;
+//!
+//! let (s, r) = unbounded();
+//! let flusher = Flusher::new(Duration::from_millis(10));
+//! let s = BufferedSender::with_flusher(s, 64, &flusher);
+//!
+//! // The message sits in the buffer until the flusher sends it.
+//! s.send(1).unwrap();
+//! assert_eq!(r.recv_timeout(Duration::from_secs(1)), Ok(1));
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use channel::{unbounded, Sender};
+use err::{RecvTimeoutError, SendError};
+
+/// A buffer of messages waiting to be sent into a channel.
+struct Buffer<T> {
+    /// The channel the messages are sent into.
+    sender: Sender<T>,
+
+    /// The buffered messages.
+    state: Mutex<BufferState<T>>,
+}
+
+/// The contents of a `Buffer`.
+struct BufferState<T> {
+    /// Messages in the order they were sent.
+    messages: VecDeque<T>,
+
+    /// When the oldest buffered message was sent, or `None` if the buffer is empty.
+    oldest: Option<Instant>,
+}
+
+impl<T> Buffer<T> {
+    /// Sends all buffered messages, blocking while the channel is full.
+    fn flush(&self) -> Result<(), SendError<Vec<T>>> {
+        let mut state = self.state.lock().unwrap();
+        state.oldest = None;
+
+        while let Some(msg) = state.messages.pop_front() {
+            if let Err(SendError(msg)) = self.sender.send(msg) {
+                let mut unsent = vec![msg];
+                unsent.extend(state.messages.drain(..));
+                return Err(SendError(unsent));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A buffer that can be flushed by a `Flusher`, regardless of its message type.
+trait Flush: Send + Sync {
+    /// Sends the buffered messages if the oldest one was sent at least `max_latency` ago.
+    ///
+    /// This never blocks: if the buffer is in use or the channel fills up, the remaining messages
+    /// are left for the next round.
+    fn flush_idle(&self, now: Instant, max_latency: Duration);
+}
+
+impl<T: Send> Flush for Buffer<T> {
+    fn flush_idle(&self, now: Instant, max_latency: Duration) {
+        let mut state = match self.state.try_lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        match state.oldest {
+            Some(oldest) if now.duration_since(oldest) >= max_latency => {}
+            _ => return,
+        }
+
+        while let Some(msg) = state.messages.pop_front() {
+            if let Err(err) = self.sender.try_send(msg) {
+                // Leave the rest for the next round, or for the owner to take back if the channel
+                // is disconnected.
+                state.messages.push_front(err.into_inner());
+                return;
+            }
+        }
+        state.oldest = None;
+    }
+}
+
+/// A sender that buffers messages and sends them into a channel in batches.
+///
+/// Messages are sent into the channel once `capacity` of them have been buffered, when [`flush`]
+/// is called, when the sender is dropped, or by a [`Flusher`] the sender is registered with.
+///
+/// Cloning a buffered sender creates a new sender with an empty buffer of its own, registered with
+/// the same flusher.
+///
+/// [`flush`]: struct.BufferedSender.html#method.flush
+/// [`Flusher`]: struct.Flusher.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::buffered::BufferedSender;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+/// let s = BufferedSender::new(s, 2);
+///
+/// s.send(1).unwrap();
+/// assert!(r.is_empty());
+///
+/// // The buffer is full, so both messages are sent.
+/// s.send(2).unwrap();
+/// assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 2]);
+///
+/// s.send(3).unwrap();
+/// drop(s);
+/// assert_eq!(r.recv(), Ok(3));
+/// ```
+pub struct BufferedSender<T> {
+    buffer: Arc<Buffer<T>>,
+    capacity: usize,
+
+    /// The flusher the sender is registered with.
+    flusher: Option<Weak<FlusherShared>>,
+}
+
+impl<T> BufferedSender<T> {
+    /// Creates a sender that sends messages into `sender` in batches of `capacity`.
+    ///
+    /// A capacity of zero or one sends every message right away.
+    pub fn new(sender: Sender<T>, capacity: usize) -> BufferedSender<T> {
+        BufferedSender {
+            buffer: Arc::new(Buffer {
+                sender,
+                state: Mutex::new(BufferState {
+                    messages: VecDeque::with_capacity(capacity),
+                    oldest: None,
+                }),
+            }),
+            capacity,
+            flusher: None,
+        }
+    }
+
+    /// Buffers a message, and sends the whole buffer into the channel if it's full.
+    ///
+    /// Sending the buffer blocks while the channel is full. If the channel is disconnected, the
+    /// error holds all messages that could not be sent, including this one.
+    pub fn send(&self, msg: T) -> Result<(), SendError<Vec<T>>> {
+        {
+            let mut state = self.buffer.state.lock().unwrap();
+            if state.messages.is_empty() {
+                state.oldest = Some(Instant::now());
+            }
+            state.messages.push_back(msg);
+            if state.messages.len() < self.capacity {
+                return Ok(());
+            }
+        }
+        self.flush()
+    }
+
+    /// Sends all buffered messages into the channel.
+    ///
+    /// This blocks while the channel is full. If the channel is disconnected, the error holds the
+    /// messages that could not be sent.
+    pub fn flush(&self) -> Result<(), SendError<Vec<T>>> {
+        self.buffer.flush()
+    }
+
+    /// Returns the number of buffered messages.
+    pub fn len(&self) -> usize {
+        self.buffer.state.lock().unwrap().messages.len()
+    }
+
+    /// Returns `true` if no messages are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of messages that are buffered before they are sent.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T: Send + 'static> BufferedSender<T> {
+    /// Creates a sender like [`new`], whose buffer is also flushed by `flusher` when its oldest
+    /// message has waited for longer than the flusher's maximum latency.
+    ///
+    /// [`new`]: struct.BufferedSender.html#method.new
+    pub fn with_flusher(
+        sender: Sender<T>,
+        capacity: usize,
+        flusher: &Flusher,
+    ) -> BufferedSender<T> {
+        let mut s = BufferedSender::new(sender, capacity);
+        s.register(&flusher.shared);
+        s
+    }
+
+    /// Registers the buffer with the shared state of a flusher.
+    fn register(&mut self, shared: &Arc<FlusherShared>) {
+        let buffer: Arc<dyn Flush> = self.buffer.clone();
+        shared.buffers.lock().unwrap().push(Arc::downgrade(&buffer));
+        self.flusher = Some(Arc::downgrade(shared));
+    }
+}
+
+impl<T> Drop for BufferedSender<T> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl<T: Send + 'static> Clone for BufferedSender<T> {
+    fn clone(&self) -> BufferedSender<T> {
+        let mut s = BufferedSender::new(self.buffer.sender.clone(), self.capacity);
+        if let Some(shared) = self.flusher.as_ref().and_then(|f| f.upgrade()) {
+            s.register(&shared);
+        }
+        s
+    }
+}
+
+impl<T> fmt::Debug for BufferedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BufferedSender")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// State shared between a `Flusher` and its background thread.
+struct FlusherShared {
+    /// The maximum time a message may wait in a buffer.
+    max_latency: Duration,
+
+    /// Buffers of the registered senders.
+    buffers: Mutex<Vec<Weak<dyn Flush>>>,
+}
+
+impl FlusherShared {
+    /// Flushes the buffers that have been idle for too long and forgets dropped ones.
+    fn flush_round(&self) {
+        let now = Instant::now();
+        let mut alive = Vec::new();
+        self.buffers.lock().unwrap().retain(|b| match b.upgrade() {
+            Some(b) => {
+                alive.push(b);
+                true
+            }
+            None => false,
+        });
+
+        for buffer in alive {
+            buffer.flush_idle(now, self.max_latency);
+        }
+    }
+}
+
+/// A background thread that flushes the buffers of [`BufferedSender`]s that have gone idle.
+///
+/// The flusher checks all registered buffers several times per `max_latency`, and sends the
+/// messages of every buffer whose oldest message has waited for `max_latency` or longer. It never
+/// blocks on a full channel: messages that don't fit are left in the buffer for the next check.
+///
+/// Dropping the flusher stops the background thread. Senders registered with it keep working, but
+/// their buffers are no longer flushed in the background.
+///
+/// [`BufferedSender`]: struct.BufferedSender.html
+pub struct Flusher {
+    shared: Arc<FlusherShared>,
+
+    /// Disconnected to stop the background thread.
+    stop: Option<Sender<()>>,
+
+    /// The background thread.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Flusher {
+    /// Starts a flusher that keeps messages from waiting in buffers for longer than
+    /// `max_latency`, give or take the time it takes to check the buffers.
+    pub fn new(max_latency: Duration) -> Flusher {
+        let shared = Arc::new(FlusherShared {
+            max_latency,
+            buffers: Mutex::new(Vec::new()),
+        });
+        let (stop, stopped) = unbounded::<()>();
+
+        let period = max_latency / 4;
+        let period = if period < Duration::from_millis(1) {
+            Duration::from_millis(1)
+        } else {
+            period
+        };
+
+        let thread = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("crossbeam-channel-flusher".to_string())
+                .spawn(move || {
+                    while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(period) {
+                        shared.flush_round();
+                    }
+                })
+                .expect("failed to spawn the flusher thread")
+        };
+
+        Flusher {
+            shared,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the maximum time a message may wait in a buffer.
+    pub fn max_latency(&self) -> Duration {
+        self.shared.max_latency
+    }
+
+    /// Returns the number of senders registered with the flusher that are still alive.
+    pub fn sender_count(&self) -> usize {
+        let buffers = self.shared.buffers.lock().unwrap();
+        buffers.iter().filter(|b| b.upgrade().is_some()).count()
+    }
+}
+
+impl Drop for Flusher {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for Flusher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Flusher")
+            .field("max_latency", &self.shared.max_latency)
+            .finish()
+    }
+}
@@ -0,0 +1,218 @@
+//! Adapters for piping byte streams through channels.
+
+use std::cmp;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::mem;
+
+use channel::{Receiver, Sender};
+use err::SendError;
+
+/// The default number of buffered bytes at which a `ByteSender` sends a chunk.
+const DEFAULT_FLUSH_THRESHOLD: usize = 8 * 1024;
+
+/// The sending side of a byte stream, implementing [`Write`].
+///
+/// Written bytes are buffered and sent as a single `Vec<u8>` chunk once the buffer reaches the
+/// flush threshold, or when [`flush`] is called. Any remaining bytes are sent when the
+/// `ByteSender` is dropped.
+///
+/// If the receiving side is disconnected, writes fail with [`ErrorKind::BrokenPipe`].
+///
+/// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`flush`]: https://doc.rust-lang.org/std/io/trait.Write.html#tymethod.flush
+/// [`ErrorKind::BrokenPipe`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.BrokenPipe
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Write};
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, ByteReceiver, ByteSender};
+///
+/// let (s, r) = unbounded();
+/// let mut writer = ByteSender::new(s);
+/// let mut reader = ByteReceiver::new(r);
+///
+/// thread::spawn(move || {
+///     writer.write_all(b"hello ").unwrap();
+///     writer.write_all(b"world").unwrap();
+/// });
+///
+/// let mut text = String::new();
+/// reader.read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "hello world");
+/// ```
+pub struct ByteSender {
+    sender: Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+    threshold: usize,
+}
+
+impl ByteSender {
+    /// Creates a writer sending chunks of bytes into the channel.
+    ///
+    /// Chunks are sent once at least 8 KiB are buffered.
+    pub fn new(sender: Sender<Vec<u8>>) -> ByteSender {
+        ByteSender::with_flush_threshold(sender, DEFAULT_FLUSH_THRESHOLD)
+    }
+
+    /// Creates a writer that sends a chunk once at least `threshold` bytes are buffered.
+    ///
+    /// A threshold of zero sends every write as a separate chunk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use crossbeam_channel::{unbounded, ByteSender};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut writer = ByteSender::with_flush_threshold(s, 4);
+    ///
+    /// writer.write_all(b"ab").unwrap();
+    /// assert!(r.is_empty());
+    ///
+    /// writer.write_all(b"cd").unwrap();
+    /// assert_eq!(r.try_recv(), Ok(b"abcd".to_vec()));
+    /// ```
+    pub fn with_flush_threshold(sender: Sender<Vec<u8>>, threshold: usize) -> ByteSender {
+        ByteSender {
+            sender,
+            buffer: Vec::new(),
+            threshold,
+        }
+    }
+
+    /// Sends the buffered bytes, if any.
+    fn send_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut chunk = Vec::new();
+        mem::swap(&mut chunk, &mut self.buffer);
+        match self.sender.send(chunk) {
+            Ok(()) => Ok(()),
+            Err(SendError(chunk)) => {
+                // Keep the bytes so that nothing gets lost silently.
+                self.buffer = chunk;
+                Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "receiving side of the channel is disconnected",
+                ))
+            }
+        }
+    }
+}
+
+impl Write for ByteSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.threshold {
+            if let Err(err) = self.send_buffer() {
+                let len = self.buffer.len();
+                self.buffer.truncate(len - buf.len());
+                return Err(err);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+impl Drop for ByteSender {
+    fn drop(&mut self) {
+        let _ = self.send_buffer();
+    }
+}
+
+impl fmt::Debug for ByteSender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ByteSender { .. }")
+    }
+}
+
+/// The receiving side of a byte stream, implementing [`Read`] and [`BufRead`].
+///
+/// Chunks are received from the channel as needed. If a chunk is larger than the buffer passed
+/// to [`read`], the rest of it is kept for subsequent reads. Once the channel is empty and
+/// disconnected, reads return `Ok(0)` to signal the end of the stream.
+///
+/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+/// [`BufRead`]: https://doc.rust-lang.org/std/io/trait.BufRead.html
+/// [`read`]: https://doc.rust-lang.org/std/io/trait.Read.html#tymethod.read
+///
+/// # Examples
+///
+/// ```
+/// use std::io::BufRead;
+/// use crossbeam_channel::{unbounded, ByteReceiver};
+///
+/// let (s, r) = unbounded();
+/// s.send(b"first\nsec".to_vec()).unwrap();
+/// s.send(b"ond\n".to_vec()).unwrap();
+/// drop(s);
+///
+/// let reader = ByteReceiver::new(r);
+/// let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+/// assert_eq!(lines, ["first", "second"]);
+/// ```
+pub struct ByteReceiver {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl ByteReceiver {
+    /// Creates a reader receiving chunks of bytes from the channel.
+    pub fn new(receiver: Receiver<Vec<u8>>) -> ByteReceiver {
+        ByteReceiver {
+            receiver,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for ByteReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = {
+            let available = self.fill_buf()?;
+            let n = cmp::min(available.len(), buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for ByteReceiver {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Skip empty chunks so that they aren't mistaken for the end of the stream.
+        while self.pos == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(&self.chunk[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = cmp::min(self.pos + amt, self.chunk.len());
+    }
+}
+
+impl fmt::Debug for ByteReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ByteReceiver { .. }")
+    }
+}
@@ -1,17 +1,46 @@
 //! The channel interface.
 
+use std::cell::Cell;
 use std::fmt;
 use std::iter::FusedIterator;
+use std::marker::PhantomData;
 use std::mem;
+use std::ops::{Deref, DerefMut};
 use std::panic::{RefUnwindSafe, UnwindSafe};
+#[cfg(feature = "flavor-array")]
+use std::ptr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crossbeam_utils::Backoff;
+
+use affinity::CoreSet;
+#[cfg(feature = "timers")]
+use clock::TimerClock;
 use context::Context;
 use counter;
-use err::{RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError};
-use flavors;
-use select::{Operation, SelectHandle, Token};
+use err::{
+    RecvBatchTimeoutError, RecvError, RecvInterruptibleError, RecvTimeoutError, SendError,
+    SendInterruptibleError, SendTimeoutError, TryRecvError, TrySendError,
+};
+#[cfg(feature = "fault-injection")]
+use fault;
+use fixed_buffer::FixedBuffer;
+#[cfg(feature = "timers")]
+use flavors::tick::Ticks;
+use flavors::{self, Introspect};
+use instrument;
+use interrupt::BlockHandle;
+#[cfg(feature = "metrics")]
+use metered;
+use observer::ChannelObserver;
+#[cfg(feature = "timers")]
+use rate::RateLimitedSender;
+use readiness::ReadyRegistration;
+use select::{Operation, Select, SelectHandle, Token};
+use signal::{DisconnectedSignal, EndpointEvent};
+use stats::OccupancyStats;
+use waiters::{self, OperationKind};
 
 /// Creates a channel of unbounded capacity.
 ///
@@ -58,6 +87,10 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 /// A special case is zero-capacity channel, which cannot hold any messages. Instead, send and
 /// receive operations must appear at the same time in order to pair up and pass the message over.
 ///
+/// Messages sent by the same thread are received in the order they were sent, no matter how many
+/// other threads are sending into the channel at the same time. When several threads receive from
+/// the channel, each of them still observes the messages of any one sender in that order.
+///
 /// # Examples
 ///
 /// A channel of capacity 1:
@@ -101,34 +134,235 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
 /// thread::sleep(Duration::from_secs(1));
 /// assert_eq!(r.recv(), Ok(1));
 /// ```
+///
+/// # Panics
+///
+/// Panics if `cap` is zero and the `flavor-zero` feature is disabled, or if `cap` is nonzero and
+/// the `flavor-array` feature is disabled.
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
 pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
     if cap == 0 {
-        let (s, r) = counter::new(flavors::zero::Channel::new());
-        let s = Sender {
-            flavor: SenderFlavor::Zero(s),
-        };
-        let r = Receiver {
-            flavor: ReceiverFlavor::Zero(r),
-        };
-        (s, r)
+        zero()
     } else {
-        let (s, r) = counter::new(flavors::array::Channel::with_capacity(cap));
-        let s = Sender {
-            flavor: SenderFlavor::Array(s),
-        };
-        let r = Receiver {
-            flavor: ReceiverFlavor::Array(r),
-        };
-        (s, r)
+        array(cap)
     }
 }
 
+/// Creates a zero-capacity channel.
+#[cfg(feature = "flavor-zero")]
+fn zero<T>() -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::zero::Channel::new());
+    let s = Sender {
+        flavor: SenderFlavor::Zero(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Zero(r),
+    };
+    (s, r)
+}
+
+/// Creates a zero-capacity channel.
+#[cfg(all(feature = "flavor-array", not(feature = "flavor-zero")))]
+fn zero<T>() -> (Sender<T>, Receiver<T>) {
+    panic!("zero-capacity channels require the `flavor-zero` feature");
+}
+
+/// Creates a channel of bounded nonzero capacity.
+#[cfg(feature = "flavor-array")]
+fn array<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::array::Channel::with_capacity(cap));
+    let s = Sender {
+        flavor: SenderFlavor::Array(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Array(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded nonzero capacity.
+#[cfg(all(feature = "flavor-zero", not(feature = "flavor-array")))]
+fn array<T>(_cap: usize) -> (Sender<T>, Receiver<T>) {
+    panic!("bounded channels with a nonzero capacity require the `flavor-array` feature");
+}
+
+/// Creates a channel of bounded capacity that records occupancy statistics.
+///
+/// This is like [`bounded`], except that every send records how many messages are in the
+/// channel. Both sides of the channel can then use `take_occupancy_stats` to find out the maximum
+/// number of messages in the channel since the last query, along with a coarse histogram of
+/// occupancy. This makes transient spikes visible, which helps with choosing the capacity.
+///
+/// Recording statistics makes sending slightly slower, so it is not enabled by [`bounded`].
+///
+/// [`bounded`]: fn.bounded.html
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::bounded_with_stats;
+///
+/// let (s, r) = bounded_with_stats(10);
+///
+/// for i in 0..5 {
+///     s.send(i).unwrap();
+/// }
+/// for _ in 0..5 {
+///     r.recv().unwrap();
+/// }
+/// s.send(5).unwrap();
+///
+/// // The spike is visible even though the channel is almost empty now.
+/// let stats = s.take_occupancy_stats().unwrap();
+/// assert_eq!(stats.high_water_mark(), 5);
+/// assert_eq!(r.len(), 1);
+///
+/// // The high-water mark starts over from the current length.
+/// let stats = s.take_occupancy_stats().unwrap();
+/// assert_eq!(stats.high_water_mark(), 1);
+/// ```
+#[cfg(feature = "flavor-array")]
+pub fn bounded_with_stats<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::array::Channel::with_occupancy(cap));
+    let s = Sender {
+        flavor: SenderFlavor::Array(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Array(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity that serves blocked senders and receivers fairly.
+///
+/// This is like [`bounded`], except that send operations are completed in the order they started,
+/// and so are receive operations. When many senders are blocked on a full channel, they are
+/// served first-in, first-out, so no sender can be starved by others that keep overtaking it.
+/// The same goes for receivers blocked on an empty channel.
+///
+/// A sender that gives up waiting with [`send_timeout`] leaves the queue without holding up the
+/// senders behind it. [`try_send`] fails if other senders are already waiting, even if there is
+/// room in the channel. Receivers behave the same way.
+///
+/// Operations started through [`select!`] or [`Select`] don't take part in the queue.
+///
+/// Fairness comes at the cost of throughput, since operations on each side are serialized.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`send_timeout`]: struct.Sender.html#method.send_timeout
+/// [`try_send`]: struct.Sender.html#method.try_send
+/// [`select!`]: macro.select.html
+/// [`Select`]: struct.Select.html
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::bounded_fair;
+///
+/// let (s, r) = bounded_fair(1);
+/// s.send(0).unwrap();
+///
+/// // Block three senders on the full channel, one after another.
+/// let handles: Vec<_> = (1..4)
+///     .map(|i| {
+///         let s = s.clone();
+///         let h = thread::spawn(move || s.send(i).unwrap());
+///         thread::sleep(Duration::from_millis(100));
+///         h
+///     })
+///     .collect();
+///
+/// // The senders are served in the order they blocked.
+/// for i in 0..4 {
+///     assert_eq!(r.recv(), Ok(i));
+/// }
+/// for h in handles {
+///     h.join().unwrap();
+/// }
+/// ```
+#[cfg(feature = "flavor-array")]
+pub fn bounded_fair<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::array::Channel::with_fairness(cap));
+    let s = Sender {
+        flavor: SenderFlavor::Array(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Array(r),
+    };
+    (s, r)
+}
+
+/// Creates an unbounded channel with several lanes of decreasing priority.
+///
+/// Senders are handed out as raw counters because each lane is sent into separately.
+pub(crate) fn lanes<T>(lanes: usize) -> (counter::Sender<flavors::lanes::Channel<T>>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::lanes::Channel::new(lanes));
+    let r = Receiver {
+        flavor: ReceiverFlavor::Lanes(r),
+    };
+    (s, r)
+}
+
+/// Creates an unbounded channel that can merge new messages into queued ones.
+///
+/// Senders are handed out as raw counters because they decide how messages get merged.
+pub(crate) fn coalesce<T>() -> (counter::Sender<flavors::coalesce::Channel<T>>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::coalesce::Channel::new());
+    let r = Receiver {
+        flavor: ReceiverFlavor::Coalesce(r),
+    };
+    (s, r)
+}
+
+/// Creates a bounded channel whose senders may send from signal handlers.
+///
+/// Senders are handed out as raw counters because they only use the async-signal-safe send path.
+#[cfg(feature = "flavor-array")]
+pub(crate) fn signal_safe<T>(
+    cap: usize,
+) -> (counter::Sender<flavors::array::Channel<T>>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::array::Channel::with_signal_safety(cap));
+    let r = Receiver {
+        flavor: ReceiverFlavor::Array(r),
+    };
+    (s, r)
+}
+
+/// Creates an unbounded channel split into shards with separate tails.
+///
+/// Senders are handed out as raw counters because each of them sends into its own shard.
+pub(crate) fn sharded<T>(
+    shards: usize,
+) -> (counter::Sender<flavors::sharded::Channel<T>>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::sharded::Channel::new(shards));
+    let r = Receiver {
+        flavor: ReceiverFlavor::Sharded(r),
+    };
+    (s, r)
+}
+
 /// Creates a receiver that delivers a message after a certain duration of time.
 ///
 /// The channel is bounded with capacity of 1 and never gets disconnected. Exactly one message will
 /// be sent into the channel after `duration` elapses. The message is the instant at which it is
 /// sent.
 ///
+/// The duration is measured by [`ClockSource::Monotonic`]. Use [`after_with_clock`] to choose how
+/// time spent with the system suspended is counted.
+///
+/// [`ClockSource::Monotonic`]: enum.ClockSource.html#variant.Monotonic
+/// [`after_with_clock`]: fn.after_with_clock.html
+///
 /// # Examples
 ///
 /// Using an `after` channel for timeouts:
@@ -172,12 +406,54 @@ pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
 /// assert!(eq(r.recv().unwrap(), start + ms(100)));
 /// assert!(eq(Instant::now(), start + ms(500)));
 /// ```
+#[cfg(feature = "timers")]
 pub fn after(duration: Duration) -> Receiver<Instant> {
     Receiver {
         flavor: ReceiverFlavor::After(Arc::new(flavors::after::Channel::new(duration))),
     }
 }
 
+/// Creates a receiver that delivers a message after a certain duration, as measured by `clock`.
+///
+/// This is like [`after`], except that the duration is measured by the given [`TimerClock`] or
+/// [`ClockSource`]. This decides what happens when the system is suspended before the message is
+/// delivered. With [`ClockSource::Boottime`], suspended time counts, so the message is delivered
+/// right after the system is running again if it became due during the suspension.
+///
+/// [`after`]: fn.after.html
+/// [`TimerClock`]: struct.TimerClock.html
+/// [`ClockSource`]: enum.ClockSource.html
+/// [`ClockSource::Boottime`]: enum.ClockSource.html#variant.Boottime
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::{after_with_clock, unbounded, ClockSource};
+///
+/// let (s, r) = unbounded::<i32>();
+/// let timeout = after_with_clock(Duration::from_millis(100), ClockSource::Boottime);
+///
+/// select! {
+///     recv(r) -> msg => println!("received {:?}", msg),
+///     recv(timeout) -> _ => println!("timed out"),
+/// }
+/// # drop(s);
+/// # }
+/// ```
+#[cfg(feature = "timers")]
+pub fn after_with_clock<C: Into<TimerClock>>(duration: Duration, clock: C) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::After(Arc::new(flavors::after::Channel::with_clock(
+            duration,
+            clock.into(),
+        ))),
+    }
+}
+
 /// Creates a receiver that never delivers messages.
 ///
 /// The channel is bounded with capacity of 0 and never gets disconnected.
@@ -229,6 +505,12 @@ pub fn never<T>() -> Receiver<T> {
 /// sent into the channel in intervals of `duration`. Each message is the instant at which it is
 /// sent.
 ///
+/// The intervals are measured by [`ClockSource::Monotonic`]. Use [`tick_with_clock`] to choose how
+/// time spent with the system suspended is counted.
+///
+/// [`ClockSource::Monotonic`]: enum.ClockSource.html#variant.Monotonic
+/// [`tick_with_clock`]: fn.tick_with_clock.html
+///
 /// # Examples
 ///
 /// Using a `tick` channel to periodically print elapsed time:
@@ -276,12 +558,102 @@ pub fn never<T>() -> Receiver<T> {
 /// assert!(eq(r.recv().unwrap(), start + ms(700)));
 /// assert!(eq(Instant::now(), start + ms(700)));
 /// ```
+#[cfg(feature = "timers")]
 pub fn tick(duration: Duration) -> Receiver<Instant> {
     Receiver {
         flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::new(duration))),
     }
 }
 
+/// Creates a receiver that delivers messages periodically, as measured by `clock`.
+///
+/// This is like [`tick`], except that the intervals are measured by the given [`TimerClock`] or
+/// [`ClockSource`]. With [`ClockSource::Boottime`], a suspension counts towards the pending
+/// interval, so a message is ready right after the system is running again if the interval ended
+/// during the suspension. Like with any tick channel, intervals missed in the meantime are not
+/// made up for.
+///
+/// [`tick`]: fn.tick.html
+/// [`TimerClock`]: struct.TimerClock.html
+/// [`ClockSource`]: enum.ClockSource.html
+/// [`ClockSource::Boottime`]: enum.ClockSource.html#variant.Boottime
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{tick_with_clock, ClockSource};
+///
+/// let ticker = tick_with_clock(Duration::from_millis(50), ClockSource::Boottime);
+///
+/// for _ in 0..3 {
+///     ticker.recv().unwrap();
+/// }
+/// ```
+#[cfg(feature = "timers")]
+pub fn tick_with_clock<C: Into<TimerClock>>(duration: Duration, clock: C) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::with_clock(
+            duration,
+            clock.into(),
+        ))),
+    }
+}
+
+/// Creates a receiver that delivers messages periodically, along with the number of elapsed periods.
+///
+/// This is like [`tick`], except that each message also says how many whole periods of `duration`
+/// elapsed since the previous message was received. A receiver that falls behind doesn't get a
+/// burst of messages to catch up with, as the channel holds at most one. Instead, the next message
+/// reports how far behind it fell, so the consumer can do a proportional amount of catch-up work,
+/// like advancing a simulation by that many steps.
+///
+/// [`tick`]: fn.tick.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::tick_counted;
+///
+/// let r = tick_counted(Duration::from_millis(100));
+///
+/// // Received on time.
+/// assert_eq!(r.recv().unwrap().count, 1);
+///
+/// // Fell behind by three periods.
+/// thread::sleep(Duration::from_millis(350));
+/// assert_eq!(r.recv().unwrap().count, 3);
+/// ```
+#[cfg(feature = "timers")]
+pub fn tick_counted(duration: Duration) -> Receiver<Ticks> {
+    Receiver {
+        flavor: ReceiverFlavor::Tick(Arc::new(flavors::tick::Channel::counted(duration))),
+    }
+}
+
+/// Converts a message received from a tick channel into the message type of its receiver.
+///
+/// Counting tick channels deliver `Ticks` and the others deliver `Instant`s.
+#[cfg(feature = "timers")]
+unsafe fn tick_message<T, E>(chan: &flavors::tick::Channel, msg: Result<Ticks, E>) -> Result<T, E> {
+    if chan.is_counted() {
+        mem::transmute_copy::<Result<Ticks, E>, Result<T, E>>(&msg)
+    } else {
+        let msg = msg.map(|ticks| ticks.instant);
+        mem::transmute_copy::<Result<Instant, E>, Result<T, E>>(&msg)
+    }
+}
+
+/// Creates a receiver for a timer allocated from a timer service.
+#[cfg(feature = "timers")]
+pub(crate) fn timer(timer: flavors::timer::Timer) -> Receiver<Instant> {
+    Receiver {
+        flavor: ReceiverFlavor::Timer(timer),
+    }
+}
+
 /// The sending side of a channel.
 ///
 /// # Examples
@@ -308,12 +680,14 @@ pub struct Sender<T> {
 /// Sender flavors.
 enum SenderFlavor<T> {
     /// Bounded channel based on a preallocated array.
+    #[cfg(feature = "flavor-array")]
     Array(counter::Sender<flavors::array::Channel<T>>),
 
     /// Unbounded channel implemented as a linked list.
     List(counter::Sender<flavors::list::Channel<T>>),
 
     /// Zero-capacity channel.
+    #[cfg(feature = "flavor-zero")]
     Zero(counter::Sender<flavors::zero::Channel<T>>),
 }
 
@@ -346,11 +720,13 @@ impl<T> Sender<T> {
     /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
     /// ```
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
-        match &self.flavor {
+        intercept_send(self.addr(), msg, |msg| match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.try_send(msg),
             SenderFlavor::List(chan) => chan.try_send(msg),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.try_send(msg),
-        }
+        })
     }
 
     /// Blocks the current thread until a message is sent or the channel is disconnected.
@@ -382,12 +758,7 @@ impl<T> Sender<T> {
     /// assert_eq!(s.send(3), Err(SendError(3)));
     /// ```
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.send(msg, None),
-            SenderFlavor::List(chan) => chan.send(msg, None),
-            SenderFlavor::Zero(chan) => chan.send(msg, None),
-        }
-        .map_err(|err| match err {
+        self.send_deadline(msg, None).map_err(|err| match err {
             SendTimeoutError::Disconnected(msg) => SendError(msg),
             SendTimeoutError::Timeout(_) => unreachable!(),
         })
@@ -431,15 +802,88 @@ impl<T> Sender<T> {
     /// );
     /// ```
     pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
-        let deadline = Instant::now() + timeout;
+        self.send_deadline(msg, Some(Instant::now() + timeout))
+    }
 
-        match &self.flavor {
-            SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
-            SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+    /// Blocks the current thread until a message is sent or the operation is interrupted through
+    /// `handle`.
+    ///
+    /// This works like [`send`], except that another thread can make the call return early by
+    /// calling [`BlockHandle::interrupt`] on a clone of `handle`. If the message can be sent right
+    /// away, it is sent even if an interrupt is pending. Otherwise, a pending interrupt is consumed
+    /// and an `Interrupted` error is returned. Either error contains the original message.
+    ///
+    /// [`send`]: struct.Sender.html#method.send
+    /// [`BlockHandle::interrupt`]: struct.BlockHandle.html#method.interrupt
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, BlockHandle, SendInterruptibleError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// s.send(1).unwrap();
+    ///
+    /// let handle = BlockHandle::new();
+    /// let h = handle.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     h.interrupt();
+    /// });
+    ///
+    /// // The channel is full, so the send blocks until interrupted.
+    /// assert_eq!(
+    ///     s.send_interruptible(2, &handle),
+    ///     Err(SendInterruptibleError::Interrupted(2)),
+    /// );
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    pub fn send_interruptible(
+        &self,
+        msg: T,
+        handle: &BlockHandle,
+    ) -> Result<(), SendInterruptibleError<T>> {
+        let msg = match self.try_send(msg) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(msg)) => {
+                return Err(SendInterruptibleError::Disconnected(msg))
+            }
+            Err(TrySendError::Full(msg)) => msg,
+        };
+
+        let mut sel = Select::new();
+        let send = sel.send(self);
+        sel.recv(handle.receiver());
+
+        let oper = sel.select();
+        if oper.index() == send {
+            oper.send(self, msg)
+                .map_err(|SendError(msg)| SendInterruptibleError::Disconnected(msg))
+        } else {
+            let _ = oper.recv(handle.receiver());
+            handle.reset();
+            Err(SendInterruptibleError::Interrupted(msg))
         }
     }
 
+    /// Waits for a message to be sent into the channel until the optional deadline.
+    pub(crate) fn send_deadline(
+        &self,
+        msg: T,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let _desc = waiters::describe(OperationKind::Send, Some(self.addr()));
+        intercept_send(self.addr(), msg, |msg| match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.send(msg, deadline),
+            SenderFlavor::List(chan) => chan.send(msg, deadline),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.send(msg, deadline),
+        })
+    }
+
     /// Returns `true` if the channel is empty.
     ///
     /// Note: Zero-capacity channels are always empty.
@@ -457,8 +901,10 @@ impl<T> Sender<T> {
     /// ```
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.is_empty(),
             SenderFlavor::List(chan) => chan.is_empty(),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.is_empty(),
         }
     }
@@ -480,8 +926,10 @@ impl<T> Sender<T> {
     /// ```
     pub fn is_full(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.is_full(),
             SenderFlavor::List(chan) => chan.is_full(),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.is_full(),
         }
     }
@@ -502,8 +950,10 @@ impl<T> Sender<T> {
     /// ```
     pub fn len(&self) -> usize {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.len(),
             SenderFlavor::List(chan) => chan.len(),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.len(),
         }
     }
@@ -526,64 +976,346 @@ impl<T> Sender<T> {
     /// ```
     pub fn capacity(&self) -> Option<usize> {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.capacity(),
             SenderFlavor::List(chan) => chan.capacity(),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.capacity(),
         }
     }
 
-    /// Returns `true` if senders belong to the same channel.
+    /// Takes a snapshot of occupancy statistics and resets them.
     ///
-    /// # Examples
+    /// Returns `None` if the channel was not created by [`bounded_with_stats`].
     ///
-    /// ```rust
-    /// use crossbeam_channel::unbounded;
+    /// [`bounded_with_stats`]: fn.bounded_with_stats.html
     ///
-    /// let (s, _) = unbounded::<usize>();
+    /// # Examples
     ///
-    /// let s2 = s.clone();
-    /// assert!(s.same_channel(&s2));
+    /// ```
+    /// use crossbeam_channel::{bounded, bounded_with_stats};
     ///
-    /// let (s3, _) = unbounded();
-    /// assert!(!s.same_channel(&s3));
+    /// let (s, _r) = bounded_with_stats(4);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(s.take_occupancy_stats().unwrap().high_water_mark(), 2);
+    ///
+    /// let (s, _r) = bounded::<i32>(4);
+    /// assert!(s.take_occupancy_stats().is_none());
     /// ```
-    pub fn same_channel(&self, other: &Sender<T>) -> bool {
-        match (&self.flavor, &other.flavor) {
-            (SenderFlavor::Array(ref a), SenderFlavor::Array(ref b)) => a == b,
-            (SenderFlavor::List(ref a), SenderFlavor::List(ref b)) => a == b,
-            (SenderFlavor::Zero(ref a), SenderFlavor::Zero(ref b)) => a == b,
-            _ => false,
+    pub fn take_occupancy_stats(&self) -> Option<OccupancyStats> {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.take_occupancy_stats(),
+            _ => None,
         }
     }
-}
 
-impl<T> Drop for Sender<T> {
-    fn drop(&mut self) {
-        unsafe {
-            match &self.flavor {
-                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
-                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
-            }
+    /// Returns how long the oldest message in the channel has been waiting to be received.
+    ///
+    /// Producers can use this to slow down when consumers fall behind. Message ages are only
+    /// tracked after the first call, so until then, and whenever the channel is empty, `None` is
+    /// returned. Ages are sampled rather than recorded for every message, so the result may be off
+    /// by about a millisecond.
+    ///
+    /// Zero-capacity channels never hold messages, so this always returns `None` for them.
+    ///
+    /// This method is only available with the `head-age` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// // Start tracking message ages.
+    /// assert_eq!(s.head_age(), None);
+    ///
+    /// s.send(1).unwrap();
+    /// thread::sleep(Duration::from_millis(100));
+    /// assert!(s.head_age().unwrap() >= Duration::from_millis(100));
+    ///
+    /// r.recv().unwrap();
+    /// assert_eq!(s.head_age(), None);
+    /// ```
+    #[cfg(feature = "head-age")]
+    pub fn head_age(&self) -> Option<Duration> {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.head_age(),
+            SenderFlavor::List(chan) => chan.head_age(),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(_) => None,
         }
     }
-}
-
-impl<T> Clone for Sender<T> {
-    fn clone(&self) -> Self {
-        let flavor = match &self.flavor {
-            SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
-            SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
-            SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
-        };
 
-        Sender { flavor }
+    /// Sets the cores whose blocked receivers are woken up first, or clears the preference.
+    ///
+    /// When a message arrives and several receivers are blocked waiting for one, receivers that
+    /// have declared to run on one of the preferred cores with [`affinity::set_current_core`] are
+    /// woken up first. The other receivers are woken up only if no preferred receiver is blocked.
+    ///
+    /// The preference applies to all senders of the channel. See the [`affinity`] module for more.
+    ///
+    /// [`affinity::set_current_core`]: affinity/fn.set_current_core.html
+    /// [`affinity`]: affinity/index.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::affinity::CoreSet;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut cores = CoreSet::new();
+    /// cores.insert(2);
+    /// cores.insert(3);
+    /// s.wake_preference(Some(cores));
+    /// ```
+    pub fn wake_preference(&self, cores: Option<CoreSet>) {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.set_wake_preference(cores),
+            SenderFlavor::List(chan) => chan.set_wake_preference(cores),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.set_wake_preference(cores),
+        }
+    }
+
+    /// Creates a sender that sends at most `rate` messages per second, in bursts of up to `burst`.
+    ///
+    /// Messages are throttled with a token bucket that holds up to `burst` tokens and refills at
+    /// `rate` tokens per second. Sending through the returned sender blocks while the bucket is
+    /// empty. The bucket starts out full, and is shared by all clones of the returned sender, but
+    /// not by other senders of the channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` or `burst` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = s.rate_limited(10, 2);
+    ///
+    /// let start = Instant::now();
+    /// for i in 0..4 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// // The first two messages were sent right away, and the other two 100 ms apart.
+    /// assert!(start.elapsed() >= Duration::from_millis(200));
+    /// assert_eq!(r.len(), 4);
+    /// ```
+    #[cfg(feature = "timers")]
+    pub fn rate_limited(&self, rate: u32, burst: u32) -> RateLimitedSender<T> {
+        RateLimitedSender::new(self.clone(), rate, burst)
+    }
+
+    /// Returns `true` if senders belong to the same channel.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, _) = unbounded::<usize>();
+    ///
+    /// let s2 = s.clone();
+    /// assert!(s.same_channel(&s2));
+    ///
+    /// let (s3, _) = unbounded();
+    /// assert!(!s.same_channel(&s3));
+    /// ```
+    pub fn same_channel(&self, other: &Sender<T>) -> bool {
+        match (&self.flavor, &other.flavor) {
+            #[cfg(feature = "flavor-array")]
+            (SenderFlavor::Array(ref a), SenderFlavor::Array(ref b)) => a == b,
+            (SenderFlavor::List(ref a), SenderFlavor::List(ref b)) => a == b,
+            #[cfg(feature = "flavor-zero")]
+            (SenderFlavor::Zero(ref a), SenderFlavor::Zero(ref b)) => a == b,
+            #[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+            _ => false,
+        }
+    }
+
+    /// Creates `n` clones of the sender at once.
+    ///
+    /// This is handy when handing out senders to a pool of producer threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// for (i, s) in s.clone_n(4).into_iter().enumerate() {
+    ///     thread::spawn(move || s.send(i).unwrap());
+    /// }
+    /// drop(s);
+    ///
+    /// let mut msgs: Vec<_> = r.iter().collect();
+    /// msgs.sort();
+    /// assert_eq!(msgs, [0, 1, 2, 3]);
+    /// ```
+    pub fn clone_n(&self, n: usize) -> Vec<Sender<T>> {
+        (0..n).map(|_| self.clone()).collect()
+    }
+
+    /// Returns a signal that gets set once all receivers of the channel have been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let gone = s.disconnected();
+    /// assert!(!gone.is_set());
+    ///
+    /// drop(r);
+    /// assert!(gone.is_set());
+    /// ```
+    pub fn disconnected(&self) -> DisconnectedSignal {
+        let signal = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.receivers_gone(),
+            SenderFlavor::List(chan) => chan.receivers_gone(),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.receivers_gone(),
+        };
+        #[cfg(feature = "flavor-zero")]
+        let flavor = ReceiverFlavor::Zero(signal);
+        #[cfg(not(feature = "flavor-zero"))]
+        let flavor = ReceiverFlavor::List(signal);
+        DisconnectedSignal::new(Receiver { flavor })
+    }
+
+    /// Returns `true` if all receivers of the channel have been dropped.
+    pub(crate) fn is_disconnected(&self) -> bool {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.receiver_count() == 0,
+            SenderFlavor::List(chan) => chan.receiver_count() == 0,
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.receiver_count() == 0,
+        }
+    }
+
+    /// Returns the address of the channel, which identifies it while it is alive.
+    pub(crate) fn addr(&self) -> usize {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => &**chan as *const flavors::array::Channel<T> as usize,
+            SenderFlavor::List(chan) => &**chan as *const flavors::list::Channel<T> as usize,
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => &**chan as *const flavors::zero::Channel<T> as usize,
+        }
+    }
+
+    /// Returns a function computing the length of the channel at the address returned by `addr`.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn len_at(&self) -> unsafe fn(usize) -> usize {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(_) => metered::len_at::<flavors::array::Channel<T>>,
+            SenderFlavor::List(_) => metered::len_at::<flavors::list::Channel<T>>,
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(_) => metered::len_at::<flavors::zero::Channel<T>>,
+        }
+    }
+
+    /// Returns a handle that disconnects the channel without keeping it alive.
+    pub(crate) fn closer(&self) -> Arc<counter::Closer> {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.closer(disconnect_array::<T>),
+            SenderFlavor::List(chan) => chan.closer(disconnect_list::<T>),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.closer(disconnect_zero::<T>),
+        }
+    }
+}
+
+/// Disconnects the bounded channel at address `chan`.
+#[cfg(feature = "flavor-array")]
+unsafe fn disconnect_array<T>(chan: usize) -> bool {
+    (*(chan as *const flavors::array::Channel<T>)).disconnect()
+}
+
+/// Disconnects the unbounded channel at address `chan`.
+unsafe fn disconnect_list<T>(chan: usize) -> bool {
+    (*(chan as *const flavors::list::Channel<T>)).disconnect()
+}
+
+/// Disconnects the zero-capacity channel at address `chan`.
+#[cfg(feature = "flavor-zero")]
+unsafe fn disconnect_zero<T>(chan: usize) -> bool {
+    (*(chan as *const flavors::zero::Channel<T>)).disconnect()
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match &self.flavor {
+                #[cfg(feature = "flavor-array")]
+                SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
+                #[cfg(feature = "flavor-zero")]
+                SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        let flavor = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
+            SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
+        };
+
+        Sender { flavor }
     }
 }
 
 impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad("Sender { .. }")
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => debug_endpoint(
+                f,
+                "Sender",
+                &**chan,
+                Some((chan.count(), chan.receiver_count())),
+            ),
+            SenderFlavor::List(chan) => debug_endpoint(
+                f,
+                "Sender",
+                &**chan,
+                Some((chan.count(), chan.receiver_count())),
+            ),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => debug_endpoint(
+                f,
+                "Sender",
+                &**chan,
+                Some((chan.count(), chan.receiver_count())),
+            ),
+        }
     }
 }
 
@@ -614,20 +1346,37 @@ pub struct Receiver<T> {
 /// Receiver flavors.
 enum ReceiverFlavor<T> {
     /// Bounded channel based on a preallocated array.
+    #[cfg(feature = "flavor-array")]
     Array(counter::Receiver<flavors::array::Channel<T>>),
 
     /// Unbounded channel implemented as a linked list.
     List(counter::Receiver<flavors::list::Channel<T>>),
 
     /// Zero-capacity channel.
+    #[cfg(feature = "flavor-zero")]
     Zero(counter::Receiver<flavors::zero::Channel<T>>),
 
+    /// Unbounded channel with several lanes of decreasing priority.
+    Lanes(counter::Receiver<flavors::lanes::Channel<T>>),
+
+    /// Unbounded channel that can merge new messages into queued ones.
+    Coalesce(counter::Receiver<flavors::coalesce::Channel<T>>),
+
+    /// Unbounded channel split into shards with separate tails.
+    Sharded(counter::Receiver<flavors::sharded::Channel<T>>),
+
     /// The after flavor.
+    #[cfg(feature = "timers")]
     After(Arc<flavors::after::Channel>),
 
     /// The tick flavor.
+    #[cfg(feature = "timers")]
     Tick(Arc<flavors::tick::Channel>),
 
+    /// The timer flavor.
+    #[cfg(feature = "timers")]
+    Timer(flavors::timer::Timer),
+
     /// The never flavor.
     Never(flavors::never::Channel<T>),
 }
@@ -662,10 +1411,16 @@ impl<T> Receiver<T> {
     /// assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
     /// ```
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
-        match &self.flavor {
+        intercept_recv(self.addr(), || match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.try_recv(),
             ReceiverFlavor::List(chan) => chan.try_recv(),
+            ReceiverFlavor::Lanes(chan) => chan.try_recv(),
+            ReceiverFlavor::Coalesce(chan) => chan.try_recv(),
+            ReceiverFlavor::Sharded(chan) => chan.try_recv(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.try_recv(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => {
                 let msg = chan.try_recv();
                 unsafe {
@@ -674,7 +1429,10 @@ impl<T> Receiver<T> {
                     )
                 }
             }
-            ReceiverFlavor::Tick(chan) => {
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => unsafe { tick_message(chan, chan.try_recv()) },
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => {
                 let msg = chan.try_recv();
                 unsafe {
                     mem::transmute_copy::<Result<Instant, TryRecvError>, Result<T, TryRecvError>>(
@@ -683,7 +1441,7 @@ impl<T> Receiver<T> {
                 }
             }
             ReceiverFlavor::Never(chan) => chan.try_recv(),
-        }
+        })
     }
 
     /// Blocks the current thread until a message is received or the channel is empty and
@@ -715,31 +1473,55 @@ impl<T> Receiver<T> {
     /// assert_eq!(r.recv(), Err(RecvError));
     /// ```
     pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_deadline(None).map_err(|_| RecvError)
+    }
+
+    /// Blocks the current thread until a message is received, and returns it without moving it
+    /// out of the channel.
+    ///
+    /// The returned guard dereferences to the message. In a bounded channel, the message stays in
+    /// its slot in the channel's buffer until the guard is dropped, which saves moving large
+    /// messages that the consumer only needs to read or modify in place. Messages after it can
+    /// still be received, but senders can't get past the held slot, so the channel fills up for
+    /// them once they wrap around to it. The guard should therefore be dropped soon.
+    ///
+    /// In other kinds of channels, the message is received as usual and held by the guard.
+    ///
+    /// If the channel is empty and disconnected, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded;
+    ///
+    /// let (s, r) = bounded(1);
+    /// s.send([7u8; 4096]).unwrap();
+    ///
+    /// let mut msg = r.recv_in_place().unwrap();
+    /// msg[0] = 1;
+    /// assert_eq!(msg[..2], [1, 7]);
+    ///
+    /// // The slot is released once the guard is dropped.
+    /// assert!(s.try_send([0; 4096]).is_err());
+    /// drop(msg);
+    /// assert!(s.try_send([0; 4096]).is_ok());
+    /// ```
+    pub fn recv_in_place(&self) -> Result<InPlace<'_, T>, RecvError> {
         match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(None),
-            ReceiverFlavor::List(chan) => chan.recv(None),
-            ReceiverFlavor::Zero(chan) => chan.recv(None),
-            ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(None);
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => {
+                let _desc = waiters::describe(OperationKind::Recv, self.addr());
+                let mut token = Token::default();
+                chan.recv_in_place(&mut token, None)
+                    .map_err(|_| RecvError)?;
+                Ok(InPlace {
+                    inner: InPlaceInner::Slot(&**chan, token),
+                })
             }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(None);
-                unsafe {
-                    mem::transmute_copy::<
-                        Result<Instant, RecvTimeoutError>,
-                        Result<T, RecvTimeoutError>,
-                    >(&msg)
-                }
-            }
-            ReceiverFlavor::Never(chan) => chan.recv(None),
+            _ => self.recv().map(|msg| InPlace {
+                inner: InPlaceInner::Owned(msg),
+            }),
         }
-        .map_err(|_| RecvError)
     }
 
     /// Waits for a message to be received from the channel, but only for a limited time.
@@ -780,14 +1562,378 @@ impl<T> Receiver<T> {
     /// );
     /// ```
     pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Some(Instant::now() + timeout))
+    }
+
+    /// Blocks the current thread until a message is received or the operation is interrupted
+    /// through `handle`.
+    ///
+    /// This works like [`recv`], except that another thread can make the call return early by
+    /// calling [`BlockHandle::interrupt`] on a clone of `handle`, which is useful for tearing down
+    /// a worker thread without closing the channel it receives from. If a message can be received
+    /// right away, it is received even if an interrupt is pending. Otherwise, a pending interrupt
+    /// is consumed and an `Interrupted` error is returned.
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    /// [`BlockHandle::interrupt`]: struct.BlockHandle.html#method.interrupt
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, BlockHandle, RecvInterruptibleError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let handle = BlockHandle::new();
+    ///
+    /// s.send(1).unwrap();
+    /// handle.interrupt();
+    ///
+    /// // The message is ready, so it's received despite the interrupt.
+    /// assert_eq!(r.recv_interruptible(&handle), Ok(1));
+    /// assert_eq!(
+    ///     r.recv_interruptible(&handle),
+    ///     Err(RecvInterruptibleError::Interrupted),
+    /// );
+    /// assert!(!handle.is_interrupted());
+    /// ```
+    pub fn recv_interruptible(&self, handle: &BlockHandle) -> Result<T, RecvInterruptibleError> {
+        match self.try_recv() {
+            Ok(msg) => return Ok(msg),
+            Err(TryRecvError::Disconnected) => return Err(RecvInterruptibleError::Disconnected),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        let mut sel = Select::new();
+        let recv = sel.recv(self);
+        sel.recv(handle.receiver());
+
+        let oper = sel.select();
+        if oper.index() == recv {
+            oper.recv(self)
+                .map_err(|_| RecvInterruptibleError::Disconnected)
+        } else {
+            let _ = oper.recv(handle.receiver());
+            handle.reset();
+            Err(RecvInterruptibleError::Interrupted)
+        }
+    }
+
+    /// Receives up to `limit` messages into `buf`, but only for a limited time.
+    ///
+    /// Received messages are appended to `buf` in the order they were received. This call blocks
+    /// until `limit` messages have been received, the operation times out, or the channel becomes
+    /// empty and disconnected.
+    ///
+    /// On success, `Ok(limit)` is returned. If the operation stops early, the returned error
+    /// carries the number of messages that were appended to `buf` before it stopped, so a timeout
+    /// after receiving some of the messages can be told apart from a timeout with nothing
+    /// received.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for send operations to appear
+    /// on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, RecvBatchTimeoutError};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// assert_eq!(r.recv_batch_timeout(&mut buf, 1, Duration::from_millis(100)), Ok(1));
+    /// assert_eq!(
+    ///     r.recv_batch_timeout(&mut buf, 10, Duration::from_millis(100)),
+    ///     Err(RecvBatchTimeoutError::Timeout(1)),
+    /// );
+    /// assert_eq!(buf, [1, 2]);
+    ///
+    /// drop(s);
+    /// assert_eq!(
+    ///     r.recv_batch_timeout(&mut buf, 10, Duration::from_millis(100)),
+    ///     Err(RecvBatchTimeoutError::Disconnected(0)),
+    /// );
+    /// ```
+    pub fn recv_batch_timeout(
+        &self,
+        buf: &mut Vec<T>,
+        limit: usize,
+        timeout: Duration,
+    ) -> Result<usize, RecvBatchTimeoutError> {
         let deadline = Instant::now() + timeout;
+        let mut count = 0;
 
-        match &self.flavor {
-            ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
-            ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
+        while count < limit {
+            match self.recv_deadline(Some(deadline)) {
+                Ok(msg) => {
+                    buf.push(msg);
+                    count += 1;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(RecvBatchTimeoutError::Timeout(count))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(RecvBatchTimeoutError::Disconnected(count))
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Keeps receiving messages into `buf` until `deadline`, and returns how many were received.
+    ///
+    /// Received messages are appended to `buf` in the order they were received. This call blocks
+    /// until the deadline, unless the channel becomes empty and disconnected first, so it's
+    /// convenient for consumers that process messages in batches at a fixed cadence.
+    ///
+    /// If the channel becomes empty and disconnected before any message is received, an error is
+    /// returned. If some messages have been received by then, their number is returned, and the
+    /// next call returns the error.
+    ///
+    /// If the deadline has already passed, at most one message that is ready right away is
+    /// received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// thread::spawn(move || {
+    ///     for i in 0..5 {
+    ///         s.send(i).unwrap();
+    ///         thread::sleep(Duration::from_millis(30));
+    ///     }
+    /// });
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut received = 0;
+    /// loop {
+    ///     // Flush a batch every 100 milliseconds.
+    ///     let deadline = Instant::now() + Duration::from_millis(100);
+    ///     match r.recv_many_until(&mut buf, deadline) {
+    ///         Ok(count) => received += count,
+    ///         Err(RecvError) => break,
+    ///     }
+    ///     buf.clear();
+    /// }
+    /// assert_eq!(received, 5);
+    /// ```
+    pub fn recv_many_until(&self, buf: &mut Vec<T>, deadline: Instant) -> Result<usize, RecvError> {
+        let mut count = 0;
+
+        loop {
+            match self.recv_deadline(Some(deadline)) {
+                Ok(msg) => {
+                    buf.push(msg);
+                    count += 1;
+
+                    // Stop even if messages keep arriving faster than they are received.
+                    if Instant::now() >= deadline {
+                        return Ok(count);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(count),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return if count == 0 {
+                        Err(RecvError)
+                    } else {
+                        Ok(count)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Receives messages that are ready right away into `buf`, and returns how many were received.
+    ///
+    /// This never blocks, and it stops as soon as the channel is empty or `buf` is full, so it
+    /// never grows `buf` either. A `Vec` or `VecDeque` only gets filled up to its current capacity.
+    /// Together with a buffer allocated in advance, this lets real-time consumers like audio
+    /// callbacks drain a channel without allocating or blocking.
+    ///
+    /// Disconnection is not reported. Once this returns fewer messages than `buf` had room for,
+    /// [`try_recv`] tells whether the channel is disconnected.
+    ///
+    /// [`try_recv`]: struct.Receiver.html#method.try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// let mut buf = Vec::with_capacity(3);
+    /// assert_eq!(r.recv_nonblocking_batch_into(&mut buf), 3);
+    /// assert_eq!(buf, [0, 1, 2]);
+    ///
+    /// buf.clear();
+    /// assert_eq!(r.recv_nonblocking_batch_into(&mut buf), 2);
+    /// assert_eq!(buf, [3, 4]);
+    /// assert_eq!(buf.capacity(), 3);
+    /// ```
+    pub fn recv_nonblocking_batch_into<B: FixedBuffer<T>>(&self, buf: &mut B) -> usize {
+        let mut count = 0;
+
+        while buf.remaining() > 0 {
+            match self.try_recv() {
+                Ok(msg) => {
+                    buf.push(msg);
+                    count += 1;
+                }
+                Err(_) => break,
+            }
+        }
+        count
+    }
+
+    /// Blocks until a message is received, and returns the newest message in the channel.
+    ///
+    /// Once a message is received, the messages already queued behind it are received as well,
+    /// and all but the last one are dropped. This suits consumers that only care about the freshest
+    /// value, such as a UI showing the latest state, and would otherwise fall further and further
+    /// behind a fast producer.
+    ///
+    /// Only the messages in the channel at the time the first one is received are drained, so this
+    /// returns even if senders keep sending.
+    ///
+    /// If the channel is empty and disconnected, this call will wake up and return an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(r.recv_latest(), Ok(4));
+    /// assert!(r.is_empty());
+    ///
+    /// drop(s);
+    /// assert_eq!(r.recv_latest(), Err(RecvError));
+    /// ```
+    pub fn recv_latest(&self) -> Result<T, RecvError> {
+        let msg = self.recv()?;
+        Ok(self.drain_behind(msg))
+    }
+
+    /// Attempts to receive the newest message in the channel without blocking.
+    ///
+    /// This is like [`recv_latest`], except that it returns an error right away if the channel is
+    /// empty.
+    ///
+    /// [`recv_latest`]: struct.Receiver.html#method.recv_latest
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, TryRecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// assert_eq!(r.try_recv_latest(), Err(TryRecvError::Empty));
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(r.try_recv_latest(), Ok(2));
+    /// ```
+    pub fn try_recv_latest(&self) -> Result<T, TryRecvError> {
+        let msg = self.try_recv()?;
+        Ok(self.drain_behind(msg))
+    }
+
+    /// Receives the messages currently queued behind `msg` and returns the last of them.
+    fn drain_behind(&self, mut msg: T) -> T {
+        for _ in 0..self.len() {
+            match self.try_recv() {
+                Ok(newer) => msg = newer,
+                Err(_) => break,
+            }
+        }
+        msg
+    }
+
+    /// Receives a message, or produces one with `produce` while the channel is empty.
+    ///
+    /// This is the work-conserving pattern of schedulers: instead of parking as soon as the
+    /// channel is empty, the current thread keeps calling `produce` to find work of its own, such
+    /// as a task in a local queue, checking the channel in between. If `produce` keeps returning
+    /// `None`, the thread backs off for a while and then blocks on the channel like [`recv`]. While
+    /// blocked, `produce` isn't called anymore.
+    ///
+    /// If the channel is empty and disconnected, `produce` is called once more so that remaining
+    /// work can be drained, and an error is returned if it has nothing left either.
+    ///
+    /// [`recv`]: struct.Receiver.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, RecvError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut local = vec![3, 2];
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(1));
+    /// assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(2));
+    ///
+    /// drop(s);
+    /// assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(3));
+    /// assert_eq!(r.recv_or_spawn(|| local.pop()), Err(RecvError));
+    /// ```
+    pub fn recv_or_spawn<F>(&self, mut produce: F) -> Result<T, RecvError>
+    where
+        F: FnMut() -> Option<T>,
+    {
+        let backoff = Backoff::new();
+
+        loop {
+            let disconnected = match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Empty) => false,
+                Err(TryRecvError::Disconnected) => true,
+            };
+
+            if let Some(msg) = produce() {
+                return Ok(msg);
+            }
+            if disconnected {
+                return Err(RecvError);
+            }
+
+            if backoff.is_completed() {
+                return self.recv();
+            }
+            backoff.snooze();
+        }
+    }
+
+    /// Waits for a message to be received from the channel until the optional deadline.
+    pub(crate) fn recv_deadline(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let _desc = waiters::describe(OperationKind::Recv, self.addr());
+        intercept_recv(self.addr(), || match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.recv(deadline),
+            ReceiverFlavor::List(chan) => chan.recv(deadline),
+            ReceiverFlavor::Lanes(chan) => chan.recv(deadline),
+            ReceiverFlavor::Coalesce(chan) => chan.recv(deadline),
+            ReceiverFlavor::Sharded(chan) => chan.recv(deadline),
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.recv(deadline),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => {
-                let msg = chan.recv(Some(deadline));
+                let msg = chan.recv(deadline);
                 unsafe {
                     mem::transmute_copy::<
                         Result<Instant, RecvTimeoutError>,
@@ -795,8 +1941,11 @@ impl<T> Receiver<T> {
                     >(&msg)
                 }
             }
-            ReceiverFlavor::Tick(chan) => {
-                let msg = chan.recv(Some(deadline));
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => unsafe { tick_message(chan, chan.recv(deadline)) },
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => {
+                let msg = chan.recv(deadline);
                 unsafe {
                     mem::transmute_copy::<
                         Result<Instant, RecvTimeoutError>,
@@ -804,8 +1953,8 @@ impl<T> Receiver<T> {
                     >(&msg)
                 }
             }
-            ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
-        }
+            ReceiverFlavor::Never(chan) => chan.recv(deadline),
+        })
     }
 
     /// Returns `true` if the channel is empty.
@@ -825,11 +1974,20 @@ impl<T> Receiver<T> {
     /// ```
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.is_empty(),
             ReceiverFlavor::List(chan) => chan.is_empty(),
+            ReceiverFlavor::Lanes(chan) => chan.is_empty(),
+            ReceiverFlavor::Coalesce(chan) => chan.is_empty(),
+            ReceiverFlavor::Sharded(chan) => chan.is_empty(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.is_empty(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.is_empty(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.is_empty(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.is_empty(),
             ReceiverFlavor::Never(chan) => chan.is_empty(),
         }
     }
@@ -851,11 +2009,20 @@ impl<T> Receiver<T> {
     /// ```
     pub fn is_full(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.is_full(),
             ReceiverFlavor::List(chan) => chan.is_full(),
+            ReceiverFlavor::Lanes(chan) => chan.is_full(),
+            ReceiverFlavor::Coalesce(chan) => chan.is_full(),
+            ReceiverFlavor::Sharded(chan) => chan.is_full(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.is_full(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.is_full(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.is_full(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.is_full(),
             ReceiverFlavor::Never(chan) => chan.is_full(),
         }
     }
@@ -876,11 +2043,20 @@ impl<T> Receiver<T> {
     /// ```
     pub fn len(&self) -> usize {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.len(),
             ReceiverFlavor::List(chan) => chan.len(),
+            ReceiverFlavor::Lanes(chan) => chan.len(),
+            ReceiverFlavor::Coalesce(chan) => chan.len(),
+            ReceiverFlavor::Sharded(chan) => chan.len(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.len(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.len(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.len(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.len(),
             ReceiverFlavor::Never(chan) => chan.len(),
         }
     }
@@ -903,15 +2079,89 @@ impl<T> Receiver<T> {
     /// ```
     pub fn capacity(&self) -> Option<usize> {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.capacity(),
             ReceiverFlavor::List(chan) => chan.capacity(),
+            ReceiverFlavor::Lanes(chan) => chan.capacity(),
+            ReceiverFlavor::Coalesce(chan) => chan.capacity(),
+            ReceiverFlavor::Sharded(chan) => chan.capacity(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.capacity(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.capacity(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.capacity(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.capacity(),
             ReceiverFlavor::Never(chan) => chan.capacity(),
         }
     }
 
+    /// Takes a snapshot of occupancy statistics and resets them.
+    ///
+    /// Returns `None` if the channel was not created by [`bounded_with_stats`].
+    ///
+    /// [`bounded_with_stats`]: fn.bounded_with_stats.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, bounded_with_stats};
+    ///
+    /// let (s, r) = bounded_with_stats(4);
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(r.take_occupancy_stats().unwrap().high_water_mark(), 2);
+    ///
+    /// let (_s, r) = bounded::<i32>(4);
+    /// assert!(r.take_occupancy_stats().is_none());
+    /// ```
+    pub fn take_occupancy_stats(&self) -> Option<OccupancyStats> {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.take_occupancy_stats(),
+            _ => None,
+        }
+    }
+
+    /// Clones up to `limit` messages from the front of the channel without receiving them.
+    ///
+    /// This is meant for debugging, for example to show what's next in a work queue. Receivers
+    /// that try to receive while the messages are being cloned wait until cloning is done.
+    ///
+    /// Returns `None` if the channel was not created by [`unbounded`].
+    ///
+    /// This method is only available with the `debug-peek` feature.
+    ///
+    /// [`unbounded`]: fn.unbounded.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, unbounded};
+    ///
+    /// let (s, r) = unbounded();
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(r.debug_peek(3), Some(vec![0, 1, 2]));
+    /// assert_eq!(r.len(), 5);
+    ///
+    /// let (_s, r) = bounded::<i32>(4);
+    /// assert_eq!(r.debug_peek(3), None);
+    /// ```
+    #[cfg(feature = "debug-peek")]
+    pub fn debug_peek(&self, limit: usize) -> Option<Vec<T>>
+    where
+        T: Clone,
+    {
+        match &self.flavor {
+            ReceiverFlavor::List(chan) => Some(chan.peek(limit)),
+            _ => None,
+        }
+    }
+
     /// A blocking iterator over messages in the channel.
     ///
     /// Each call to [`next`] blocks waiting for the next message and then returns it. However, if
@@ -981,6 +2231,92 @@ impl<T> Receiver<T> {
         TryIter { receiver: self }
     }
 
+    /// A blocking iterator over messages in the channel that ends at a deadline.
+    ///
+    /// Each call to [`next`] blocks waiting for the next message, but not past the deadline. Once
+    /// the deadline is reached or the channel becomes empty and disconnected, the iterator returns
+    /// [`None`] and stays exhausted, even if more messages arrive later.
+    ///
+    /// The deadline is a budget for the whole iteration, no matter how long the gaps between
+    /// messages are. This makes the iterator handy for batch jobs running at a fixed cadence.
+    ///
+    /// [`next`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#tymethod.next
+    /// [`None`]: https://doc.rust-lang.org/std/option/enum.Option.html#variant.None
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0.. {
+    ///         if s.send(i).is_err() {
+    ///             break;
+    ///         }
+    ///         thread::sleep(Duration::from_millis(100));
+    ///     }
+    /// });
+    ///
+    /// // Process whatever arrives within the next 250 milliseconds.
+    /// let deadline = Instant::now() + Duration::from_millis(250);
+    /// let mut batch = Vec::new();
+    /// for msg in r.into_blocking_iter_with_deadline(deadline) {
+    ///     batch.push(msg);
+    /// }
+    ///
+    /// assert_eq!(batch, [0, 1, 2]);
+    /// ```
+    pub fn into_blocking_iter_with_deadline(self, deadline: Instant) -> DeadlineIntoIter<T> {
+        DeadlineIntoIter {
+            receiver: self,
+            deadline,
+            done: false,
+        }
+    }
+
+    /// Registers a callback to be invoked when the receiver becomes ready.
+    ///
+    /// This lets an event loop outside this crate, like one built on epoll, include the channel
+    /// among its sources without blocking a thread on it. The registration fires once, after
+    /// which it has to be rearmed. See [`ReadyRegistration`] for details.
+    ///
+    /// [`ReadyRegistration`]: struct.ReadyRegistration.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let fired = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let f = fired.clone();
+    /// let registration = r.register_ready(move || {
+    ///     f.fetch_add(1, Ordering::SeqCst);
+    /// });
+    /// assert_eq!(fired.load(Ordering::SeqCst), 0);
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    /// assert_eq!(fired.load(Ordering::SeqCst), 1);
+    ///
+    /// // The channel is still ready, so rearming fires right away.
+    /// registration.rearm();
+    /// assert_eq!(fired.load(Ordering::SeqCst), 2);
+    /// ```
+    pub fn register_ready<F>(&self, callback: F) -> ReadyRegistration<T>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        ReadyRegistration::new(self.clone(), callback)
+    }
+
     /// Returns `true` if receivers belong to the same channel.
     ///
     /// # Examples
@@ -988,23 +2324,249 @@ impl<T> Receiver<T> {
     /// ```rust
     /// use crossbeam_channel::unbounded;
     ///
-    /// let (_, r) = unbounded::<usize>();
+    /// let (_, r) = unbounded::<usize>();
+    ///
+    /// let r2 = r.clone();
+    /// assert!(r.same_channel(&r2));
+    ///
+    /// let (_, r3) = unbounded();
+    /// assert!(!r.same_channel(&r3));
+    /// ```
+    pub fn same_channel(&self, other: &Receiver<T>) -> bool {
+        match (&self.flavor, &other.flavor) {
+            #[cfg(feature = "flavor-array")]
+            (ReceiverFlavor::Array(a), ReceiverFlavor::Array(b)) => a == b,
+            (ReceiverFlavor::List(a), ReceiverFlavor::List(b)) => a == b,
+            (ReceiverFlavor::Lanes(a), ReceiverFlavor::Lanes(b)) => a == b,
+            (ReceiverFlavor::Coalesce(a), ReceiverFlavor::Coalesce(b)) => a == b,
+            (ReceiverFlavor::Sharded(a), ReceiverFlavor::Sharded(b)) => a == b,
+            #[cfg(feature = "flavor-zero")]
+            (ReceiverFlavor::Zero(a), ReceiverFlavor::Zero(b)) => a == b,
+            #[cfg(feature = "timers")]
+            (ReceiverFlavor::After(a), ReceiverFlavor::After(b)) => Arc::ptr_eq(a, b),
+            #[cfg(feature = "timers")]
+            (ReceiverFlavor::Tick(a), ReceiverFlavor::Tick(b)) => Arc::ptr_eq(a, b),
+            #[cfg(feature = "timers")]
+            (ReceiverFlavor::Timer(a), ReceiverFlavor::Timer(b)) => a.ptr_eq(b),
+            (ReceiverFlavor::Never(_), ReceiverFlavor::Never(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns a receiver for changes in the number of senders and receivers of the channel.
+    ///
+    /// From now on, every time an endpoint of the channel is cloned or dropped, an
+    /// [`EndpointEvent`] is sent into the returned receiver. Changes that happen while this method
+    /// is running might not be reported. The returned receiver gets disconnected once the channel
+    /// is destroyed.
+    ///
+    /// Events are only recorded for channels that have subscribers, so channels nobody watches
+    /// don't pay for them. For channels created by [`after`], [`tick`], and [`never`], which have
+    /// no senders, the returned receiver is disconnected from the start.
+    ///
+    /// [`EndpointEvent`]: enum.EndpointEvent.html
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    /// [`never`]: fn.never.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, EndpointEvent};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let events = r.endpoint_events();
+    ///
+    /// // A plugin registers a producer and later goes away.
+    /// let plugin = s.clone();
+    /// drop(plugin);
+    ///
+    /// assert_eq!(events.try_recv(), Ok(EndpointEvent::SenderAttached { senders: 2 }));
+    /// assert_eq!(events.try_recv(), Ok(EndpointEvent::SenderDetached { senders: 1 }));
+    /// ```
+    pub fn endpoint_events(&self) -> Receiver<EndpointEvent> {
+        let chan = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.endpoint_events(),
+            ReceiverFlavor::List(chan) => chan.endpoint_events(),
+            ReceiverFlavor::Lanes(chan) => chan.endpoint_events(),
+            ReceiverFlavor::Coalesce(chan) => chan.endpoint_events(),
+            ReceiverFlavor::Sharded(chan) => chan.endpoint_events(),
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.endpoint_events(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(_) | ReceiverFlavor::Tick(_) | ReceiverFlavor::Timer(_) => {
+                return unbounded().1
+            }
+            ReceiverFlavor::Never(_) => return unbounded().1,
+        };
+        Receiver {
+            flavor: ReceiverFlavor::List(chan),
+        }
+    }
+
+    /// Returns the address of the channel, which identifies it while it is alive.
+    ///
+    /// Returns `None` for channels created by `after`, `tick`, and `never`.
+    pub(crate) fn addr(&self) -> Option<usize> {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => {
+                Some(&**chan as *const flavors::array::Channel<T> as usize)
+            }
+            ReceiverFlavor::List(chan) => {
+                Some(&**chan as *const flavors::list::Channel<T> as usize)
+            }
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => {
+                Some(&**chan as *const flavors::zero::Channel<T> as usize)
+            }
+            ReceiverFlavor::Lanes(chan) => {
+                Some(&**chan as *const flavors::lanes::Channel<T> as usize)
+            }
+            ReceiverFlavor::Coalesce(chan) => {
+                Some(&**chan as *const flavors::coalesce::Channel<T> as usize)
+            }
+            ReceiverFlavor::Sharded(chan) => {
+                Some(&**chan as *const flavors::sharded::Channel<T> as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the receiver into a [`UniqueReceiver`] if it is the only receiver of its channel.
+    ///
+    /// If there are other receivers associated with the channel, the receiver is returned back
+    /// in the error.
+    ///
+    /// [`UniqueReceiver`]: struct.UniqueReceiver.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// let r2 = r.clone();
+    /// let r = r.into_unique().unwrap_err();
+    ///
+    /// drop(r2);
+    /// let r = r.into_unique().unwrap();
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    pub fn into_unique(self) -> Result<UniqueReceiver<T>, Receiver<T>> {
+        let is_unique = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.count() == 1,
+            ReceiverFlavor::List(chan) => chan.count() == 1,
+            ReceiverFlavor::Lanes(chan) => chan.count() == 1,
+            ReceiverFlavor::Coalesce(chan) => chan.count() == 1,
+            ReceiverFlavor::Sharded(chan) => chan.count() == 1,
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.count() == 1,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(chan) => Arc::strong_count(chan) == 1,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => Arc::strong_count(chan) == 1,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.is_unique(),
+            ReceiverFlavor::Never(_) => true,
+        };
+
+        if is_unique {
+            Ok(UniqueReceiver {
+                receiver: self,
+                _marker: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Consumes the receiver and returns the messages still buffered in the channel, if it is the
+    /// last endpoint of its channel.
+    ///
+    /// This succeeds only if all senders have been dropped and there are no other receivers, so
+    /// that no message can be sent or received anymore. Otherwise, the receiver is returned back in
+    /// the error.
+    ///
+    /// This is useful on shutdown, when unprocessed messages should be persisted rather than
+    /// dropped together with the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
     ///
-    /// let r2 = r.clone();
-    /// assert!(r.same_channel(&r2));
+    /// // The sender is still alive.
+    /// let r = r.try_into_inner().unwrap_err();
     ///
-    /// let (_, r3) = unbounded();
-    /// assert!(!r.same_channel(&r3));
+    /// drop(s);
+    /// assert_eq!(r.try_into_inner().unwrap(), [1, 2]);
     /// ```
-    pub fn same_channel(&self, other: &Receiver<T>) -> bool {
-        match (&self.flavor, &other.flavor) {
-            (ReceiverFlavor::Array(a), ReceiverFlavor::Array(b)) => a == b,
-            (ReceiverFlavor::List(a), ReceiverFlavor::List(b)) => a == b,
-            (ReceiverFlavor::Zero(a), ReceiverFlavor::Zero(b)) => a == b,
-            (ReceiverFlavor::After(a), ReceiverFlavor::After(b)) => Arc::ptr_eq(a, b),
-            (ReceiverFlavor::Tick(a), ReceiverFlavor::Tick(b)) => Arc::ptr_eq(a, b),
-            (ReceiverFlavor::Never(_), ReceiverFlavor::Never(_)) => true,
-            _ => false,
+    pub fn try_into_inner(self) -> Result<Vec<T>, Receiver<T>> {
+        let is_last = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            ReceiverFlavor::List(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            ReceiverFlavor::Lanes(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            ReceiverFlavor::Coalesce(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            ReceiverFlavor::Sharded(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.count() == 1 && chan.sender_count() == 0,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(chan) => Arc::strong_count(chan) == 1,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => Arc::strong_count(chan) == 1,
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.is_unique(),
+            ReceiverFlavor::Never(_) => true,
+        };
+
+        if is_last {
+            Ok(self.try_iter().collect())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Formats the receiver for `Debug` under the given type name.
+    fn debug_as(&self, f: &mut fmt::Formatter, name: &str) -> fmt::Result {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            ReceiverFlavor::List(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            ReceiverFlavor::Lanes(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            ReceiverFlavor::Coalesce(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            ReceiverFlavor::Sharded(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => {
+                debug_endpoint(f, name, &**chan, Some((chan.sender_count(), chan.count())))
+            }
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(chan) => debug_endpoint(f, name, &**chan, None),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => debug_endpoint(f, name, &**chan, None),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => debug_endpoint(f, name, chan, None),
+            ReceiverFlavor::Never(chan) => debug_endpoint(f, name, chan, None),
         }
     }
 }
@@ -1013,11 +2575,20 @@ impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         unsafe {
             match &self.flavor {
+                #[cfg(feature = "flavor-array")]
                 ReceiverFlavor::Array(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::List(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Lanes(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Coalesce(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Sharded(chan) => chan.release(|c| c.disconnect()),
+                #[cfg(feature = "flavor-zero")]
                 ReceiverFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                #[cfg(feature = "timers")]
                 ReceiverFlavor::After(_) => {}
+                #[cfg(feature = "timers")]
                 ReceiverFlavor::Tick(_) => {}
+                #[cfg(feature = "timers")]
+                ReceiverFlavor::Timer(_) => {}
                 ReceiverFlavor::Never(_) => {}
             }
         }
@@ -1027,11 +2598,20 @@ impl<T> Drop for Receiver<T> {
 impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
         let flavor = match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => ReceiverFlavor::Array(chan.acquire()),
             ReceiverFlavor::List(chan) => ReceiverFlavor::List(chan.acquire()),
+            ReceiverFlavor::Lanes(chan) => ReceiverFlavor::Lanes(chan.acquire()),
+            ReceiverFlavor::Coalesce(chan) => ReceiverFlavor::Coalesce(chan.acquire()),
+            ReceiverFlavor::Sharded(chan) => ReceiverFlavor::Sharded(chan.acquire()),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => ReceiverFlavor::Zero(chan.acquire()),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => ReceiverFlavor::After(chan.clone()),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => ReceiverFlavor::Tick(chan.clone()),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => ReceiverFlavor::Timer(chan.clone()),
             ReceiverFlavor::Never(_) => ReceiverFlavor::Never(flavors::never::Channel::new()),
         };
 
@@ -1041,7 +2621,7 @@ impl<T> Clone for Receiver<T> {
 
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad("Receiver { .. }")
+        self.debug_as(f, "Receiver")
     }
 }
 
@@ -1211,11 +2791,466 @@ impl<T> fmt::Debug for IntoIter<T> {
     }
 }
 
+/// A blocking iterator over messages in a channel that ends at a deadline.
+///
+/// Created by [`Receiver::into_blocking_iter_with_deadline`].
+///
+/// [`Receiver::into_blocking_iter_with_deadline`]: struct.Receiver.html#method.into_blocking_iter_with_deadline
+pub struct DeadlineIntoIter<T> {
+    receiver: Receiver<T>,
+    deadline: Instant,
+    done: bool,
+}
+
+impl<T> DeadlineIntoIter<T> {
+    /// Returns the deadline at which the iterator ends.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns the underlying receiver.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T> FusedIterator for DeadlineIntoIter<T> {}
+
+impl<T> Iterator for DeadlineIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || Instant::now() >= self.deadline {
+            self.done = true;
+            return None;
+        }
+
+        let res = self.receiver.recv_deadline(Some(self.deadline)).ok();
+        self.done = res.is_none();
+        res
+    }
+}
+
+impl<T> fmt::Debug for DeadlineIntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("DeadlineIntoIter { .. }")
+    }
+}
+
+/// The receiving side of a channel that is guaranteed to be its only receiver.
+///
+/// A unique receiver cannot be cloned and cannot be shared between threads, although it can be
+/// sent to another thread. Because there are no competing consumers, it can offer exclusive
+/// operations like [`drain_mut`], and receiving from an unbounded channel skips the
+/// synchronization that is otherwise needed between multiple receivers.
+///
+/// A unique receiver is obtained with [`Receiver::into_unique`] and can be turned back into an
+/// ordinary receiver with [`into_receiver`], e.g. to use it in a [`Select`].
+///
+/// [`drain_mut`]: struct.UniqueReceiver.html#method.drain_mut
+/// [`Receiver::into_unique`]: struct.Receiver.html#method.into_unique
+/// [`into_receiver`]: struct.UniqueReceiver.html#method.into_receiver
+/// [`Select`]: struct.Select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded();
+/// let r = r.into_unique().unwrap();
+///
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         s.send(i).unwrap();
+///     }
+/// });
+///
+/// assert_eq!(r.recv(), Ok(0));
+/// assert_eq!(r.recv(), Ok(1));
+/// assert_eq!(r.recv(), Ok(2));
+/// ```
+pub struct UniqueReceiver<T> {
+    receiver: Receiver<T>,
+    _marker: PhantomData<Cell<()>>,
+}
+
+unsafe impl<T: Send> Send for UniqueReceiver<T> {}
+
+impl<T> UnwindSafe for UniqueReceiver<T> {}
+impl<T> RefUnwindSafe for UniqueReceiver<T> {}
+
+impl<T> UniqueReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// See [`Receiver::try_recv`] for details.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match &self.receiver.flavor {
+            ReceiverFlavor::List(chan) => unsafe { chan.try_recv_unique() },
+            ReceiverFlavor::Lanes(chan) => chan.try_recv(),
+            ReceiverFlavor::Coalesce(chan) => chan.try_recv(),
+            ReceiverFlavor::Sharded(chan) => chan.try_recv(),
+            _ => self.receiver.try_recv(),
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// See [`Receiver::recv`] for details.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_deadline(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`] for details.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Some(Instant::now() + timeout))
+    }
+
+    /// Waits for a message to be received from the channel until the optional deadline.
+    fn recv_deadline(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let _desc = waiters::describe(OperationKind::Recv, self.receiver.addr());
+        match &self.receiver.flavor {
+            ReceiverFlavor::List(chan) => unsafe { chan.recv_unique(deadline) },
+            ReceiverFlavor::Lanes(chan) => chan.recv(deadline),
+            ReceiverFlavor::Coalesce(chan) => chan.recv(deadline),
+            ReceiverFlavor::Sharded(chan) => chan.recv(deadline),
+            _ => self.receiver.recv_deadline(deadline),
+        }
+    }
+
+    /// Receives all messages currently in the channel without blocking.
+    ///
+    /// The returned iterator yields messages until the channel is empty. Since this is the only
+    /// receiver, no other consumer can take messages from the channel while it is being drained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let mut r = r.into_unique().unwrap();
+    ///
+    /// s.send(1).unwrap();
+    /// s.send(2).unwrap();
+    ///
+    /// let v: Vec<_> = r.drain_mut().collect();
+    /// assert_eq!(v, [1, 2]);
+    /// assert!(r.is_empty());
+    /// ```
+    pub fn drain_mut(&mut self) -> Drain<'_, T> {
+        Drain { receiver: self }
+    }
+
+    /// Returns `true` if the channel is empty.
+    ///
+    /// Note: Zero-capacity channels are always empty.
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    ///
+    /// Note: Zero-capacity channels are always full.
+    pub fn is_full(&self) -> bool {
+        self.receiver.is_full()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// If the channel is bounded, returns its capacity.
+    pub fn capacity(&self) -> Option<usize> {
+        self.receiver.capacity()
+    }
+
+    /// Converts the unique receiver back into an ordinary receiver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let r = r.into_unique().unwrap();
+    ///
+    /// let r = r.into_receiver();
+    /// let r2 = r.clone();
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(r2.recv(), Ok(1));
+    /// ```
+    pub fn into_receiver(self) -> Receiver<T> {
+        if let ReceiverFlavor::List(chan) = &self.receiver.flavor {
+            unsafe { chan.end_unique() }
+        }
+        self.receiver
+    }
+}
+
+impl<T> fmt::Debug for UniqueReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.receiver.debug_as(f, "UniqueReceiver")
+    }
+}
+
+/// A draining iterator over messages in a channel.
+///
+/// Each call to [`next`] returns a message if there is one ready to be received. The iterator
+/// never blocks waiting for the next message.
+///
+/// This iterator is created by the [`drain_mut`] method on [`UniqueReceiver`].
+///
+/// [`next`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#tymethod.next
+/// [`drain_mut`]: struct.UniqueReceiver.html#method.drain_mut
+/// [`UniqueReceiver`]: struct.UniqueReceiver.html
+pub struct Drain<'a, T: 'a> {
+    receiver: &'a mut UniqueReceiver<T>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl<'a, T> fmt::Debug for Drain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Drain { .. }")
+    }
+}
+
+/// A message received in place by [`Receiver::recv_in_place`].
+///
+/// The guard dereferences to the message. In a bounded channel, the message is dropped and its slot
+/// is handed back to senders when the guard is dropped. The message can also be moved out with
+/// [`into_inner`].
+///
+/// [`Receiver::recv_in_place`]: struct.Receiver.html#method.recv_in_place
+/// [`into_inner`]: struct.InPlace.html#method.into_inner
+pub struct InPlace<'a, T: 'a> {
+    inner: InPlaceInner<'a, T>,
+}
+
+/// Where an `InPlace` message is stored.
+enum InPlaceInner<'a, T: 'a> {
+    /// In a reserved slot of a bounded channel.
+    #[cfg(feature = "flavor-array")]
+    Slot(&'a flavors::array::Channel<T>, Token),
+
+    /// Received as usual.
+    Owned(T),
+
+    /// Moved out by `into_inner`.
+    Taken(PhantomData<&'a ()>),
+}
+
+impl<'a, T> InPlace<'a, T> {
+    /// Moves the message out, releasing its slot.
+    pub fn into_inner(mut self) -> T {
+        match mem::replace(&mut self.inner, InPlaceInner::Taken(PhantomData)) {
+            #[cfg(feature = "flavor-array")]
+            InPlaceInner::Slot(chan, token) => unsafe {
+                let msg = chan.slot_message(&token).read();
+                chan.release(&token);
+                msg
+            },
+            InPlaceInner::Owned(msg) => msg,
+            InPlaceInner::Taken(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, T> Deref for InPlace<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match &self.inner {
+            #[cfg(feature = "flavor-array")]
+            InPlaceInner::Slot(chan, token) => unsafe { &*chan.slot_message(token) },
+            InPlaceInner::Owned(msg) => msg,
+            InPlaceInner::Taken(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, T> DerefMut for InPlace<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match &mut self.inner {
+            #[cfg(feature = "flavor-array")]
+            InPlaceInner::Slot(chan, token) => unsafe { &mut *chan.slot_message(token) },
+            InPlaceInner::Owned(msg) => msg,
+            InPlaceInner::Taken(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, T> Drop for InPlace<'a, T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "flavor-array")]
+        {
+            if let InPlaceInner::Slot(chan, token) = &self.inner {
+                /// Releases the slot even if dropping the message panics.
+                struct Release<'a, T: 'a>(&'a flavors::array::Channel<T>, &'a Token);
+
+                impl<'a, T> Drop for Release<'a, T> {
+                    fn drop(&mut self) {
+                        unsafe { self.0.release(self.1) }
+                    }
+                }
+
+                let _release = Release(chan, token);
+                unsafe { ptr::drop_in_place(chan.slot_message(token)) }
+            }
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for InPlace<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("InPlace").field(&**self).finish()
+    }
+}
+
+/// Sends a message with `send`, recording its size and publishing metrics of the channel.
+#[inline]
+fn intercept_send<T, E, F>(addr: usize, msg: T, send: F) -> Result<(), E>
+where
+    F: Fn(T) -> Result<(), E>,
+{
+    instrument::record_send::<T>();
+    let res = send_with_faults(addr, msg, send);
+
+    #[cfg(feature = "metrics")]
+    {
+        if res.is_ok() {
+            unsafe { metered::on_send(addr) }
+        }
+    }
+    res
+}
+
+/// Receives a message with `recv`, publishing metrics of the channel.
+#[inline]
+fn intercept_recv<T, E, F>(addr: Option<usize>, recv: F) -> Result<T, E>
+where
+    F: Fn() -> Result<T, E>,
+{
+    let res = recv_with_faults(addr, recv);
+
+    #[cfg(feature = "metrics")]
+    {
+        if let (Ok(_), Some(addr)) = (&res, addr) {
+            unsafe { metered::on_recv(addr) }
+        }
+    }
+    res
+}
+
+/// Sends a message with `send`, applying fault injection hooks installed on the channel.
+#[cfg(feature = "fault-injection")]
+#[inline]
+fn send_with_faults<T, E, F>(addr: usize, msg: T, send: F) -> Result<(), E>
+where
+    F: Fn(T) -> Result<(), E>,
+{
+    fault::on_send(addr, msg, send)
+}
+
+/// Sends a message with `send`.
+#[cfg(not(feature = "fault-injection"))]
+#[inline]
+fn send_with_faults<T, E, F>(_addr: usize, msg: T, send: F) -> Result<(), E>
+where
+    F: Fn(T) -> Result<(), E>,
+{
+    send(msg)
+}
+
+/// Receives a message with `recv`, applying fault injection hooks installed on the channel.
+#[cfg(feature = "fault-injection")]
+#[inline]
+fn recv_with_faults<T, E, F>(addr: Option<usize>, recv: F) -> Result<T, E>
+where
+    F: Fn() -> Result<T, E>,
+{
+    match addr {
+        Some(addr) => fault::on_recv(addr, recv),
+        None => recv(),
+    }
+}
+
+/// Receives a message with `recv`.
+#[cfg(not(feature = "fault-injection"))]
+#[inline]
+fn recv_with_faults<T, E, F>(_addr: Option<usize>, recv: F) -> Result<T, E>
+where
+    F: Fn() -> Result<T, E>,
+{
+    recv()
+}
+
+impl<T> ChannelObserver for Sender<T> {
+    fn version(&self) -> usize {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.send_version(),
+            SenderFlavor::List(chan) => chan.send_version(),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.version(),
+        }
+    }
+}
+
+impl<T> ChannelObserver for Receiver<T> {
+    fn version(&self) -> usize {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.recv_version(),
+            ReceiverFlavor::List(chan) => chan.recv_version(),
+            ReceiverFlavor::Lanes(chan) => chan.recv_version(),
+            ReceiverFlavor::Coalesce(chan) => chan.recv_version(),
+            ReceiverFlavor::Sharded(chan) => chan.recv_version(),
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.version(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(chan) => chan.version(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Tick(chan) => chan.version(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.version(),
+            ReceiverFlavor::Never(chan) => chan.version(),
+        }
+    }
+}
+
+impl<T> ChannelObserver for UniqueReceiver<T> {
+    fn version(&self) -> usize {
+        self.receiver.version()
+    }
+}
+
 impl<T> SelectHandle for Sender<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().try_select(token),
             SenderFlavor::List(chan) => chan.sender().try_select(token),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().try_select(token),
         }
     }
@@ -1226,148 +3261,291 @@ impl<T> SelectHandle for Sender<T> {
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
             SenderFlavor::List(chan) => chan.sender().register(oper, cx),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
         }
     }
 
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().unregister(oper),
             SenderFlavor::List(chan) => chan.sender().unregister(oper),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
         }
     }
 
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
             SenderFlavor::List(chan) => chan.sender().accept(token, cx),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
         }
     }
 
     fn is_ready(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().is_ready(),
             SenderFlavor::List(chan) => chan.sender().is_ready(),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::List(chan) => chan.sender().watch(oper, cx),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().watch(oper, cx),
         }
     }
 
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             SenderFlavor::Array(chan) => chan.sender().unwatch(oper),
             SenderFlavor::List(chan) => chan.sender().unwatch(oper),
+            #[cfg(feature = "flavor-zero")]
             SenderFlavor::Zero(chan) => chan.sender().unwatch(oper),
         }
     }
+
+    fn is_exhausted(&self) -> bool {
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            SenderFlavor::Array(chan) => chan.is_disconnected(),
+            SenderFlavor::List(chan) => chan.is_disconnected(),
+            #[cfg(feature = "flavor-zero")]
+            SenderFlavor::Zero(chan) => chan.is_disconnected(),
+        }
+    }
 }
 
 impl<T> SelectHandle for Receiver<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().try_select(token),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.try_select(token),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.try_select(token),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.try_select(token),
             ReceiverFlavor::Never(chan) => chan.try_select(token),
         }
     }
 
     fn deadline(&self) -> Option<Instant> {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(_) => None,
             ReceiverFlavor::List(_) => None,
+            ReceiverFlavor::Lanes(_) => None,
+            ReceiverFlavor::Coalesce(_) => None,
+            ReceiverFlavor::Sharded(_) => None,
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(_) => None,
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.deadline(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.deadline(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.deadline(),
             ReceiverFlavor::Never(chan) => chan.deadline(),
         }
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().register(oper, cx),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.register(oper, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.register(oper, cx),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.register(oper, cx),
             ReceiverFlavor::Never(chan) => chan.register(oper, cx),
         }
     }
 
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().unregister(oper),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.unregister(oper),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.unregister(oper),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.unregister(oper),
             ReceiverFlavor::Never(chan) => chan.unregister(oper),
         }
     }
 
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().accept(token, cx),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.accept(token, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.accept(token, cx),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.accept(token, cx),
             ReceiverFlavor::Never(chan) => chan.accept(token, cx),
         }
     }
 
     fn is_ready(&self) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::List(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().is_ready(),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().is_ready(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.is_ready(),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.is_ready(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.is_ready(),
             ReceiverFlavor::Never(chan) => chan.is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().watch(oper, cx),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().watch(oper, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.watch(oper, cx),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.watch(oper, cx),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Never(chan) => chan.watch(oper, cx),
         }
     }
 
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
+            #[cfg(feature = "flavor-array")]
             ReceiverFlavor::Array(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Coalesce(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().unwatch(oper),
+            #[cfg(feature = "flavor-zero")]
             ReceiverFlavor::Zero(chan) => chan.receiver().unwatch(oper),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::After(chan) => chan.unwatch(oper),
+            #[cfg(feature = "timers")]
             ReceiverFlavor::Tick(chan) => chan.unwatch(oper),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.unwatch(oper),
             ReceiverFlavor::Never(chan) => chan.unwatch(oper),
         }
     }
+
+    fn never_ready(&self) -> bool {
+        match &self.flavor {
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::After(chan) => chan.never_ready(),
+            #[cfg(feature = "timers")]
+            ReceiverFlavor::Timer(chan) => chan.never_ready(),
+            ReceiverFlavor::Never(chan) => chan.never_ready(),
+            _ => false,
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        // Once a channel is disconnected no more messages arrive, so it stays empty from then on.
+        match &self.flavor {
+            #[cfg(feature = "flavor-array")]
+            ReceiverFlavor::Array(chan) => chan.is_disconnected() && chan.is_empty(),
+            ReceiverFlavor::List(chan) => chan.is_disconnected() && chan.is_empty(),
+            ReceiverFlavor::Lanes(chan) => chan.is_disconnected() && chan.is_empty(),
+            ReceiverFlavor::Coalesce(chan) => chan.is_disconnected() && chan.is_empty(),
+            ReceiverFlavor::Sharded(chan) => chan.is_disconnected() && chan.is_empty(),
+            #[cfg(feature = "flavor-zero")]
+            ReceiverFlavor::Zero(chan) => chan.is_disconnected(),
+            _ => self.never_ready(),
+        }
+    }
+}
+
+/// Formats an endpoint of a channel for `Debug`, showing the state of the channel.
+///
+/// The counts of senders and receivers are only shown for channels that keep track of them.
+pub(crate) fn debug_endpoint(
+    f: &mut fmt::Formatter,
+    name: &str,
+    chan: &dyn Introspect,
+    counts: Option<(usize, usize)>,
+) -> fmt::Result {
+    let mut d = f.debug_struct(name);
+    d.field("flavor", &format_args!("{}", chan.flavor()));
+    d.field("len", &chan.len());
+    d.field("capacity", &chan.capacity());
+    if let Some((senders, receivers)) = counts {
+        d.field("senders", &senders);
+        d.field("receivers", &receivers);
+    }
+    d.field("disconnected", &chan.is_disconnected());
+    d.finish()
 }
 
 /// Writes a message into the channel.
 pub unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T> {
     match &s.flavor {
+        #[cfg(feature = "flavor-array")]
         SenderFlavor::Array(chan) => chan.write(token, msg),
         SenderFlavor::List(chan) => chan.write(token, msg),
+        #[cfg(feature = "flavor-zero")]
         SenderFlavor::Zero(chan) => chan.write(token, msg),
     }
 }
@@ -1375,13 +3553,22 @@ pub unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T
 /// Reads a message from the channel.
 pub unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
     match &r.flavor {
+        #[cfg(feature = "flavor-array")]
         ReceiverFlavor::Array(chan) => chan.read(token),
         ReceiverFlavor::List(chan) => chan.read(token),
+        ReceiverFlavor::Lanes(chan) => chan.read(token),
+        ReceiverFlavor::Coalesce(chan) => chan.read(token),
+        ReceiverFlavor::Sharded(chan) => chan.read(token),
+        #[cfg(feature = "flavor-zero")]
         ReceiverFlavor::Zero(chan) => chan.read(token),
+        #[cfg(feature = "timers")]
         ReceiverFlavor::After(chan) => {
             mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
         }
-        ReceiverFlavor::Tick(chan) => {
+        #[cfg(feature = "timers")]
+        ReceiverFlavor::Tick(chan) => tick_message(chan, chan.read(token)),
+        #[cfg(feature = "timers")]
+        ReceiverFlavor::Timer(chan) => {
             mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
         }
         ReceiverFlavor::Never(chan) => chan.read(token),
@@ -0,0 +1,138 @@
+//! The `channel_set!` macro.
+
+/// Defines a struct of receivers that are selected over as a whole.
+///
+/// The macro takes a struct whose fields are receivers, written as `name: T => Variant` for a
+/// field of type [`Receiver<T>`], and the name of an enum with one variant per field. It defines
+/// both, and gives the struct a method `select_all` that blocks until one of the receivers has a
+/// message or is disconnected, receives from it, and returns the result wrapped in the variant of
+/// that field.
+///
+/// Consumers `match` on the returned enum, so adding a field to the struct makes every consumer
+/// that doesn't handle the new channel fail to compile, rather than silently never receiving from
+/// it.
+///
+/// Fields are always public. The struct, the enum, and `select_all` share the visibility of the
+/// struct.
+///
+/// [`Receiver<T>`]: struct.Receiver.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::unbounded;
+///
+/// channel_set! {
+///     /// Everything a worker listens to.
+///     pub struct Inputs {
+///         jobs: u32 => Job,
+///         shutdown: () => Shutdown,
+///     }
+///
+///     /// A message from one of the inputs.
+///     #[derive(Debug, PartialEq)]
+///     pub enum Input;
+/// }
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// let inputs = Inputs { jobs: r1, shutdown: r2 };
+///
+/// s1.send(7).unwrap();
+/// drop(s2);
+///
+/// let mut done = false;
+/// while !done {
+///     match inputs.select_all() {
+///         Input::Job(job) => assert_eq!(job, Ok(7)),
+///         Input::Shutdown(_) => done = true,
+///     }
+/// }
+/// # drop(s1);
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! channel_set {
+    (
+        $(#[$set_meta:meta])*
+        pub struct $set:ident {
+            $($field:ident : $ty:ty => $variant:ident),+ $(,)*
+        }
+
+        $(#[$event_meta:meta])*
+        pub enum $event:ident;
+    ) => {
+        channel_set!(
+            @define
+            [pub]
+            $(#[$set_meta])* $set
+            ($($field : $ty => $variant),+)
+            $(#[$event_meta])* $event
+        );
+    };
+    (
+        $(#[$set_meta:meta])*
+        struct $set:ident {
+            $($field:ident : $ty:ty => $variant:ident),+ $(,)*
+        }
+
+        $(#[$event_meta:meta])*
+        enum $event:ident;
+    ) => {
+        channel_set!(
+            @define
+            []
+            $(#[$set_meta])* $set
+            ($($field : $ty => $variant),+)
+            $(#[$event_meta])* $event
+        );
+    };
+    (@define
+        [$($vis:tt)*]
+        $(#[$set_meta:meta])* $set:ident
+        ($($field:ident : $ty:ty => $variant:ident),+)
+        $(#[$event_meta:meta])* $event:ident
+    ) => {
+        $(#[$set_meta])*
+        $($vis)* struct $set {
+            $(
+                #[allow(missing_docs)]
+                pub $field: $crate::Receiver<$ty>,
+            )+
+        }
+
+        $(#[$event_meta])*
+        $($vis)* enum $event {
+            $(
+                #[allow(missing_docs)]
+                $variant($crate::internal::Result<$ty, $crate::RecvError>),
+            )+
+        }
+
+        impl $set {
+            /// Blocks until one of the receivers has a message or is disconnected, and receives
+            /// from it.
+            #[allow(unused_assignments)]
+            $($vis)* fn select_all(&self) -> $event {
+                let mut sel = $crate::Select::new();
+                $(
+                    sel.recv(&self.$field);
+                )+
+
+                let oper = sel.select();
+                let index = oper.index();
+                let mut i = 0;
+                $(
+                    if index == i {
+                        return $event::$variant(oper.recv(&self.$field));
+                    }
+                    i += 1;
+                )+
+                crossbeam_channel_delegate!(unreachable())
+            }
+        }
+    };
+}
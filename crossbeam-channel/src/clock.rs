@@ -0,0 +1,260 @@
+//! Clock sources for timer channels and their behavior across system suspend.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::atomic::AtomicCell;
+
+/// How often a blocked timer operation checks whether the system has been suspended, in
+/// milliseconds.
+const RESUME_CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Suspensions shorter than this many milliseconds are not reported, which filters out jitter
+/// between the clocks.
+const MIN_SUSPENSION_MS: u64 = 1;
+
+/// The clock that measures the time until a timer channel delivers a message.
+///
+/// While the system is suspended, some clocks keep counting and others stop. This decides
+/// whether a timer that was pending during a suspension counts the suspended time or not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClockSource {
+    /// The clock behind `Instant`.
+    ///
+    /// Time spent suspended doesn't count on Linux, Android, and macOS: a timer due in 10 minutes
+    /// that sees a one-hour suspension fires 10 minutes after the system is running again. On
+    /// other platforms, `Instant` may count time spent suspended. This is the clock used by
+    /// [`after`] and [`tick`].
+    ///
+    /// [`after`]: fn.after.html
+    /// [`tick`]: fn.tick.html
+    Monotonic,
+
+    /// A clock that keeps counting while the system is suspended.
+    ///
+    /// A timer that became due during a suspension fires as soon as the system is running again,
+    /// and one that is still pending fires when its time is up, suspended time included. This is
+    /// `CLOCK_BOOTTIME` on Linux and Android with the `boottime` feature. Otherwise, it behaves
+    /// like `Monotonic`.
+    Boottime,
+}
+
+impl ClockSource {
+    /// Returns `true` if the clock source is available on the current platform.
+    ///
+    /// `Monotonic` is always available.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            ClockSource::Monotonic => true,
+            ClockSource::Boottime => suspended().is_some(),
+        }
+    }
+}
+
+impl Default for ClockSource {
+    fn default() -> ClockSource {
+        ClockSource::Monotonic
+    }
+}
+
+/// A callback re-deriving the deadline of a timer after a suspension.
+type ResumeHook = Arc<dyn Fn(Duration, Duration) -> Duration + Send + Sync>;
+
+/// The clock of a timer channel, created with [`after_with_clock`] or [`tick_with_clock`].
+///
+/// Besides the [`ClockSource`], a timer clock can have a callback that decides how a pending
+/// deadline changes after the system has been suspended. See [`on_resume`].
+///
+/// [`after_with_clock`]: fn.after_with_clock.html
+/// [`tick_with_clock`]: fn.tick_with_clock.html
+/// [`ClockSource`]: enum.ClockSource.html
+/// [`on_resume`]: struct.TimerClock.html#method.on_resume
+#[derive(Clone)]
+pub struct TimerClock {
+    /// The clock source.
+    source: ClockSource,
+
+    /// The callback re-deriving deadlines after a suspension.
+    on_resume: Option<ResumeHook>,
+}
+
+impl TimerClock {
+    /// Creates a timer clock using `source`.
+    pub fn new(source: ClockSource) -> TimerClock {
+        TimerClock {
+            source,
+            on_resume: None,
+        }
+    }
+
+    /// Returns the clock source.
+    pub fn source(&self) -> ClockSource {
+        self.source
+    }
+
+    /// Sets a callback that re-derives the deadline of a pending timer after a suspension.
+    ///
+    /// The callback gets how long the system was suspended, and how long the timer still had to
+    /// wait as measured without the suspension. It returns how long the timer should wait from
+    /// now on. Without a callback, a `Monotonic` clock keeps the remaining wait and a `Boottime`
+    /// clock subtracts the suspended time from it.
+    ///
+    /// Suspensions are detected by comparing `CLOCK_BOOTTIME` with `CLOCK_MONOTONIC`, so the
+    /// callback is only called on Linux and Android, regardless of the clock source. It's called
+    /// on a thread using the timer channel once it notices the suspension, at most about a second
+    /// after the system is running again. Timers with a message ready to be received are not
+    /// re-derived.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{after_with_clock, ClockSource, TimerClock};
+    ///
+    /// // Fire right after a resume, no matter how long the timer still had to wait.
+    /// let clock = TimerClock::new(ClockSource::Monotonic).on_resume(|_, _| Duration::from_secs(0));
+    /// let timeout = after_with_clock(Duration::from_secs(60), clock);
+    /// # drop(timeout);
+    /// ```
+    pub fn on_resume<F>(mut self, callback: F) -> TimerClock
+    where
+        F: Fn(Duration, Duration) -> Duration + Send + Sync + 'static,
+    {
+        self.on_resume = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl Default for TimerClock {
+    fn default() -> TimerClock {
+        TimerClock::new(ClockSource::default())
+    }
+}
+
+impl From<ClockSource> for TimerClock {
+    fn from(source: ClockSource) -> TimerClock {
+        TimerClock::new(source)
+    }
+}
+
+impl fmt::Debug for TimerClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TimerClock")
+            .field("source", &self.source)
+            .field("on_resume", &self.on_resume.is_some())
+            .finish()
+    }
+}
+
+/// Notices suspensions on behalf of a timer channel and re-derives its deadline.
+pub(crate) struct ResumeWatch {
+    /// The clock of the timer.
+    clock: TimerClock,
+
+    /// The total time the system had spent suspended when the timer last checked.
+    seen: AtomicCell<Duration>,
+}
+
+impl ResumeWatch {
+    /// Creates a watch for a timer using `clock`.
+    ///
+    /// Returns `None` if the timer is not affected by suspensions, or if they can't be detected.
+    pub(crate) fn new(clock: TimerClock) -> Option<ResumeWatch> {
+        if clock.source == ClockSource::Monotonic && clock.on_resume.is_none() {
+            return None;
+        }
+
+        suspended().map(|seen| ResumeWatch {
+            clock,
+            seen: AtomicCell::new(seen),
+        })
+    }
+
+    /// Returns how long the system was suspended since the last call, if it was.
+    ///
+    /// Every suspension is reported to exactly one caller.
+    fn check(&self) -> Option<Duration> {
+        let now = suspended()?;
+        let seen = self.seen.load();
+        if now < seen + Duration::from_millis(MIN_SUSPENSION_MS) {
+            return None;
+        }
+
+        match self.seen.compare_exchange(seen, now) {
+            Ok(_) => Some(now - seen),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns how long a timer that had `remaining` left to wait should wait after being
+    /// suspended for `suspended`.
+    fn rederive(&self, suspended: Duration, remaining: Duration) -> Duration {
+        match self.clock.on_resume {
+            Some(ref callback) => callback(suspended, remaining),
+            None => match self.clock.source {
+                ClockSource::Monotonic => remaining,
+                ClockSource::Boottime => remaining
+                    .checked_sub(suspended)
+                    .unwrap_or_else(|| Duration::from_secs(0)),
+            },
+        }
+    }
+
+    /// Re-derives a pending delivery time if the system has been suspended since the last check.
+    pub(crate) fn update(&self, delivery_time: &AtomicCell<Instant>) {
+        if let Some(suspended) = self.check() {
+            let now = Instant::now();
+            let current = delivery_time.load();
+            if current > now {
+                let remaining = self.rederive(suspended, current - now);
+                let _ = delivery_time.compare_exchange(current, now + remaining);
+            }
+        }
+    }
+
+    /// Limits a wait so that suspensions are noticed soon after the system is running again.
+    pub(crate) fn limit(&self, wait: Duration) -> Duration {
+        wait.min(Duration::from_millis(RESUME_CHECK_INTERVAL_MS))
+    }
+
+    /// Returns when a thread waiting for `delivery_time` should wake up to notice suspensions.
+    pub(crate) fn wake_time(&self, delivery_time: Instant) -> Instant {
+        let now = Instant::now();
+        if delivery_time > now {
+            now + self.limit(delivery_time - now)
+        } else {
+            delivery_time
+        }
+    }
+}
+
+/// Returns the total time the system has spent suspended since it booted, if it can be measured.
+#[cfg(all(feature = "boottime", any(target_os = "linux", target_os = "android")))]
+fn suspended() -> Option<Duration> {
+    fn read(clock: libc::clockid_t) -> Option<Duration> {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        if unsafe { libc::clock_gettime(clock, &mut ts) } == 0 {
+            Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+        } else {
+            None
+        }
+    }
+
+    let monotonic = read(libc::CLOCK_MONOTONIC)?;
+    let boottime = read(libc::CLOCK_BOOTTIME)?;
+    Some(
+        boottime
+            .checked_sub(monotonic)
+            .unwrap_or_else(|| Duration::from_secs(0)),
+    )
+}
+
+/// Returns the total time the system has spent suspended since it booted, if it can be measured.
+#[cfg(not(all(feature = "boottime", any(target_os = "linux", target_os = "android"))))]
+fn suspended() -> Option<Duration> {
+    None
+}
@@ -0,0 +1,225 @@
+//! Channels that coalesce messages with the same key.
+//!
+//! Streams of state updates often only care about the latest state per key: if the consumer falls
+//! behind, older updates are superseded by newer ones anyway. In a coalescing channel, every
+//! message carries a key. If a message with the same key is still waiting to be received, the new
+//! message is merged into it in place instead of being enqueued. The queue therefore never holds
+//! more messages than there are distinct keys, no matter how slow the consumer is.
+//!
+//! By default, the new value simply [`replace`]s the old one. A custom merge function can be
+//! supplied with [`with_merge`]. Merged messages keep their original position in the queue.
+//!
+//! The receiving side is an ordinary [`Receiver`] of key-value pairs.
+//!
+//! [`replace`]: fn.replace.html
+//! [`with_merge`]: fn.with_merge.html
+//! [`Receiver`]: ../struct.Receiver.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::coalesce;
+//!
+//! let (s, r) = coalesce::channel();
+//!
+//! s.send("temperature", 20).unwrap();
+//! s.send("humidity", 40).unwrap();
+//! s.send("temperature", 21).unwrap();
+//!
+//! assert_eq!(r.try_recv(), Ok(("temperature", 21)));
+//! assert_eq!(r.try_recv(), Ok(("humidity", 40)));
+//! assert!(r.try_recv().is_err());
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use channel::{self, Receiver};
+use counter;
+use err::SendError;
+use flavors;
+use utils::Spinlock;
+
+/// Creates a coalescing channel in which new values replace queued ones.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::coalesce;
+///
+/// let (s, r) = coalesce::channel();
+///
+/// // A fast producer reporting progress.
+/// thread::spawn(move || {
+///     for i in 0..=100 {
+///         s.send("progress", i).unwrap();
+///     }
+/// });
+///
+/// // A slow consumer only sees some of the updates, but always ends up with the latest one.
+/// let last = r.iter().map(|(_, progress)| progress).last();
+/// assert_eq!(last, Some(100));
+/// ```
+pub fn channel<K, V>() -> (CoalescingSender<K, V>, Receiver<(K, V)>)
+where
+    K: Eq + Hash + Clone,
+{
+    with_merge(replace)
+}
+
+/// Creates a coalescing channel that merges values with the given function.
+///
+/// The function receives the queued value and the new value, in that order, and returns the
+/// value to keep in the queue. It is called while the queue is locked, so it should be cheap.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::coalesce;
+///
+/// // Count events per key instead of queueing each of them.
+/// let (s, r) = coalesce::with_merge(|old, new| old + new);
+///
+/// s.send("clicks", 1).unwrap();
+/// s.send("clicks", 1).unwrap();
+/// s.send("scrolls", 1).unwrap();
+/// s.send("clicks", 1).unwrap();
+///
+/// assert_eq!(r.try_recv(), Ok(("clicks", 3)));
+/// assert_eq!(r.try_recv(), Ok(("scrolls", 1)));
+/// ```
+pub fn with_merge<K, V>(merge: fn(V, V) -> V) -> (CoalescingSender<K, V>, Receiver<(K, V)>)
+where
+    K: Eq + Hash + Clone,
+{
+    let (s, r) = channel::coalesce();
+    let s = CoalescingSender {
+        chan: s,
+        index: Arc::new(Spinlock::new(HashMap::new())),
+        merge,
+    };
+    (s, r)
+}
+
+/// The default merge policy, which keeps the new value and drops the queued one.
+pub fn replace<V>(_old: V, new: V) -> V {
+    new
+}
+
+/// The sending side of a coalescing channel.
+///
+/// Created by the [`channel`] and [`with_merge`] functions.
+///
+/// [`channel`]: fn.channel.html
+/// [`with_merge`]: fn.with_merge.html
+pub struct CoalescingSender<K, V> {
+    chan: counter::Sender<flavors::coalesce::Channel<(K, V)>>,
+
+    /// Maps keys to the positions of their last pushed messages.
+    ///
+    /// Shared by all senders of the channel. Positions of received messages are stale and get
+    /// pruned once they make up most of the index.
+    index: Arc<Spinlock<HashMap<K, u64>>>,
+
+    /// Merges a new value into a queued one.
+    merge: fn(V, V) -> V,
+}
+
+unsafe impl<K: Send, V: Send> Send for CoalescingSender<K, V> {}
+unsafe impl<K: Send, V: Send> Sync for CoalescingSender<K, V> {}
+
+impl<K, V> CoalescingSender<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Sends a value under the given key.
+    ///
+    /// If a message with the same key is still queued, the value is merged into it. Otherwise, a
+    /// new message is enqueued. This method never blocks.
+    ///
+    /// If the channel is disconnected, the key and the value are returned back in the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{coalesce, SendError};
+    ///
+    /// let (s, r) = coalesce::channel();
+    /// s.send(1, 'a').unwrap();
+    /// assert_eq!(r.recv(), Ok((1, 'a')));
+    ///
+    /// // The previous message has been received, so this one is enqueued.
+    /// s.send(1, 'b').unwrap();
+    /// assert_eq!(s.len(), 1);
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(1, 'c'), Err(SendError((1, 'c'))));
+    /// ```
+    pub fn send(&self, key: K, value: V) -> Result<(), SendError<(K, V)>> {
+        let merge = self.merge;
+        let mut index = self.index.lock();
+
+        self.chan
+            .send_with((key, value), |queue, msg| {
+                let msg = match index.get(&msg.0).cloned() {
+                    Some(pos) => {
+                        match queue.merge(pos, msg, |(k, old), (_, new)| (k, merge(old, new))) {
+                            Ok(()) => return,
+                            Err(msg) => msg,
+                        }
+                    }
+                    None => msg,
+                };
+
+                let key = msg.0.clone();
+                let pos = queue.push(msg);
+                index.insert(key, pos);
+
+                if index.len() > 2 * queue.len() + 16 {
+                    let head = queue.head();
+                    index.retain(|_, pos| *pos >= head);
+                }
+            })
+            .map_err(SendError)
+    }
+}
+
+impl<K, V> CoalescingSender<K, V> {
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+}
+
+impl<K, V> Drop for CoalescingSender<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<K, V> Clone for CoalescingSender<K, V> {
+    fn clone(&self) -> Self {
+        CoalescingSender {
+            chan: self.chan.acquire(),
+            index: self.index.clone(),
+            merge: self.merge,
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for CoalescingSender<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = (self.chan.count(), self.chan.receiver_count());
+        channel::debug_endpoint(f, "CoalescingSender", &*self.chan, Some(counts))
+    }
+}
@@ -1,14 +1,22 @@
 //! Thread-local context used in select.
 
 use std::cell::Cell;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::fmt;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use std::thread::{self, Thread, ThreadId};
 use std::time::Instant;
 
 use crossbeam_utils::Backoff;
 
+use block_hook::{self, BlockDecision};
+#[cfg(feature = "metrics")]
+use metered;
+use ordering::{yield_point, Ordering};
+#[cfg(feature = "priority-inheritance")]
+use priority::Boost;
 use select::Selected;
+use waiters::Parked;
 
 /// Thread-local context used in select.
 #[derive(Debug, Clone)]
@@ -30,6 +38,18 @@ struct Inner {
 
     /// Thread id.
     thread_id: ThreadId,
+
+    /// Invoked instead of unparking the thread, if set.
+    callback: Option<Callback>,
+}
+
+/// A function waking up something other than a parked thread.
+struct Callback(Box<dyn Fn() + Send + Sync>);
+
+impl fmt::Debug for Callback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Callback { .. }")
+    }
 }
 
 impl Context {
@@ -72,13 +92,32 @@ impl Context {
                 packet: AtomicUsize::new(0),
                 thread: thread::current(),
                 thread_id: thread::current().id(),
+                callback: None,
+            }),
+        }
+    }
+
+    /// Creates a `Context` that invokes `callback` instead of unparking a thread.
+    ///
+    /// Such a context can only be used for watching operations, never for blocking.
+    pub fn with_callback<F>(callback: F) -> Context
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Context {
+            inner: Arc::new(Inner {
+                select: AtomicUsize::new(Selected::Waiting.into()),
+                packet: AtomicUsize::new(0),
+                thread: thread::current(),
+                thread_id: thread::current().id(),
+                callback: Some(Callback(Box::new(callback))),
             }),
         }
     }
 
     /// Resets `select` and `packet`.
     #[inline]
-    fn reset(&self) {
+    pub fn reset(&self) {
         self.inner
             .select
             .store(Selected::Waiting.into(), Ordering::Release);
@@ -90,6 +129,7 @@ impl Context {
     /// On failure, the previously selected operation is returned.
     #[inline]
     pub fn try_select(&self, select: Selected) -> Result<(), Selected> {
+        yield_point();
         self.inner
             .select
             .compare_exchange(
@@ -113,6 +153,7 @@ impl Context {
     /// This method must be called after `try_select` succeeds and there is a packet to provide.
     #[inline]
     pub fn store_packet(&self, packet: usize) {
+        yield_point();
         if packet != 0 {
             self.inner.packet.store(packet, Ordering::Release);
         }
@@ -151,6 +192,34 @@ impl Context {
             }
         }
 
+        // Let a runtime embedding this thread run other work before the thread gets parked.
+        while block_hook::before_park() == BlockDecision::Retry {
+            let sel = Selected::from(self.inner.select.load(Ordering::Acquire));
+            if sel != Selected::Waiting {
+                return sel;
+            }
+
+            if let Some(end) = deadline {
+                if Instant::now() >= end {
+                    return match self.try_select(Selected::Aborted) {
+                        Ok(()) => Selected::Aborted,
+                        Err(s) => s,
+                    };
+                }
+            }
+        }
+
+        // Record what this thread waits on for debugging and for test harnesses.
+        let _parked = Parked::enter(&self.inner.thread, deadline);
+
+        // Lend this thread's priority to the consumer it waits on.
+        #[cfg(feature = "priority-inheritance")]
+        let _boost = Boost::enter(&self.inner.thread);
+
+        // Attribute the time spent parked to the channel, if it publishes metrics.
+        #[cfg(feature = "metrics")]
+        let _wait = metered::Wait::enter();
+
         loop {
             // Check whether an operation has been selected.
             let sel = Selected::from(self.inner.select.load(Ordering::Acquire));
@@ -177,10 +246,20 @@ impl Context {
         }
     }
 
-    /// Unparks the thread this context belongs to.
+    /// Returns `true` if the context invokes a callback instead of unparking a thread.
+    #[cfg(feature = "flavor-array")]
+    #[inline]
+    pub fn has_callback(&self) -> bool {
+        self.inner.callback.is_some()
+    }
+
+    /// Unparks the thread this context belongs to, or invokes its callback.
     #[inline]
     pub fn unpark(&self) {
-        self.inner.thread.unpark();
+        match self.inner.callback {
+            None => self.inner.thread.unpark(),
+            Some(ref callback) => (callback.0)(),
+        }
     }
 
     /// Returns the id of the thread this context belongs to.
@@ -3,7 +3,31 @@
 use std::isize;
 use std::ops;
 use std::process;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::Arc;
+
+use err::TrySendError;
+#[cfg(feature = "fault-injection")]
+use fault;
+use flavors::list;
+#[cfg(feature = "flavor-zero")]
+use flavors::zero;
+#[cfg(feature = "metrics")]
+use metered;
+use ordering::Ordering;
+use signal::EndpointEvent;
+use utils::Spinlock;
+
+/// The flavor of the signal channels that get disconnected once all receivers are gone.
+///
+/// Nothing is ever sent into them, so they are zero-capacity channels unless that flavor is
+/// compiled out.
+#[cfg(feature = "flavor-zero")]
+pub type SignalChannel = zero::Channel<()>;
+
+/// The flavor of the signal channels that get disconnected once all receivers are gone.
+#[cfg(not(feature = "flavor-zero"))]
+pub type SignalChannel = list::Channel<()>;
 
 /// Reference counter internals.
 struct Counter<C> {
@@ -16,16 +40,92 @@ struct Counter<C> {
     /// Set to `true` if the last sender or the last receiver reference deallocates the channel.
     destroy: AtomicBool,
 
+    /// Senders of signal channels that get disconnected once the last receiver is released.
+    ///
+    /// Set to `None` when that happens.
+    receivers_gone: Spinlock<Option<Vec<Sender<SignalChannel>>>>,
+
+    /// Set to `true` once someone subscribes to endpoint events.
+    watched: AtomicBool,
+
+    /// Senders of channels that endpoint events are reported to.
+    events: Spinlock<Vec<Sender<list::Channel<EndpointEvent>>>>,
+
+    /// Handles through which the channel can be disconnected from the outside.
+    closers: Spinlock<Vec<Arc<Closer>>>,
+
     /// The internal channel.
     chan: C,
 }
 
+impl<C> Counter<C> {
+    /// Increments or decrements an endpoint count and reports the new count to subscribers.
+    ///
+    /// Returns the previous count.
+    fn transition<F>(&self, count: &AtomicUsize, attach: bool, event: F) -> usize
+    where
+        F: FnOnce(usize) -> EndpointEvent,
+    {
+        let update = || {
+            if attach {
+                count.fetch_add(1, Ordering::Relaxed)
+            } else {
+                count.fetch_sub(1, Ordering::AcqRel)
+            }
+        };
+
+        if !self.watched.load(Ordering::Acquire) {
+            return update();
+        }
+
+        // Updating the count under the lock makes subscribers see counts in order.
+        let mut events = self.events.lock();
+        let prev = update();
+        let event = event(if attach { prev + 1 } else { prev - 1 });
+
+        events.retain(|s| match s.try_send(event) {
+            Err(TrySendError::Disconnected(_)) => {
+                release_events(s);
+                false
+            }
+            _ => true,
+        });
+        prev
+    }
+
+    /// Returns the receiving side of a channel that endpoint events get reported to.
+    fn subscribe(&self) -> Receiver<list::Channel<EndpointEvent>> {
+        let (s, r) = new(list::Channel::new());
+        self.events.lock().push(s);
+        self.watched.store(true, Ordering::Release);
+        r
+    }
+}
+
+impl<C> Drop for Counter<C> {
+    fn drop(&mut self) {
+        // Closers must not touch the channel once it is gone.
+        for closer in self.closers.lock().iter() {
+            *closer.alive.lock() = false;
+        }
+
+        // Subscribers see their event channels disconnected once the channel is gone.
+        for s in self.events.lock().iter() {
+            release_events(s);
+        }
+    }
+}
+
 /// Wraps a channel into the reference counter.
 pub fn new<C>(chan: C) -> (Sender<C>, Receiver<C>) {
     let counter = Box::into_raw(Box::new(Counter {
         senders: AtomicUsize::new(1),
         receivers: AtomicUsize::new(1),
         destroy: AtomicBool::new(false),
+        receivers_gone: Spinlock::new(Some(Vec::new())),
+        watched: AtomicBool::new(false),
+        events: Spinlock::new(Vec::new()),
+        closers: Spinlock::new(Vec::new()),
         chan,
     }));
     let s = Sender { counter };
@@ -46,7 +146,10 @@ impl<C> Sender<C> {
 
     /// Acquires another sender reference.
     pub fn acquire(&self) -> Sender<C> {
-        let count = self.counter().senders.fetch_add(1, Ordering::Relaxed);
+        let counter = self.counter();
+        let count = counter.transition(&counter.senders, true, |senders| {
+            EndpointEvent::SenderAttached { senders }
+        });
 
         // Cloning senders and calling `mem::forget` on the clones could potentially overflow the
         // counter. It's very difficult to recover sensibly from such degenerate scenarios so we
@@ -60,14 +163,71 @@ impl<C> Sender<C> {
         }
     }
 
+    /// Returns the number of sender references.
+    pub fn count(&self) -> usize {
+        self.counter().senders.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of receiver references.
+    pub fn receiver_count(&self) -> usize {
+        self.counter().receivers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the receiving side of a signal channel that gets disconnected once the last
+    /// receiver reference is released.
+    pub fn receivers_gone(&self) -> Receiver<SignalChannel> {
+        let (s, r) = new(SignalChannel::new());
+        let mut signals = self.counter().receivers_gone.lock();
+
+        match signals.as_mut() {
+            Some(signals) => {
+                // Release signals whose receiving sides have been dropped in the meantime.
+                signals.retain(|s| {
+                    if s.receiver_count() == 0 {
+                        unsafe { s.release(|c| c.disconnect()) };
+                        false
+                    } else {
+                        true
+                    }
+                });
+                signals.push(s);
+            }
+            None => unsafe { s.release(|c| c.disconnect()) },
+        }
+        r
+    }
+
+    /// Returns a handle that disconnects the channel by calling `disconnect` with its address.
+    ///
+    /// The handle doesn't keep the channel alive and does nothing once the channel is destroyed.
+    pub fn closer(&self, disconnect: unsafe fn(usize) -> bool) -> Arc<Closer> {
+        let closer = Arc::new(Closer {
+            chan: &self.counter().chan as *const C as usize,
+            disconnect,
+            alive: Spinlock::new(true),
+        });
+        self.counter().closers.lock().push(closer.clone());
+        closer
+    }
+
     /// Releases the sender reference.
     ///
     /// Function `disconnect` will be called if this is the last sender reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
-        if self.counter().senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+        let counter = self.counter();
+        let count = counter.transition(&counter.senders, false, |senders| {
+            EndpointEvent::SenderDetached { senders }
+        });
+
+        if count == 1 {
             disconnect(&self.counter().chan);
 
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
+                #[cfg(feature = "fault-injection")]
+                fault::forget(&self.counter().chan as *const C as usize);
+                #[cfg(feature = "metrics")]
+                metered::forget(&self.counter().chan as *const C as usize);
+
                 drop(Box::from_raw(self.counter));
             }
         }
@@ -101,7 +261,10 @@ impl<C> Receiver<C> {
 
     /// Acquires another receiver reference.
     pub fn acquire(&self) -> Receiver<C> {
-        let count = self.counter().receivers.fetch_add(1, Ordering::Relaxed);
+        let counter = self.counter();
+        let count = counter.transition(&counter.receivers, true, |receivers| {
+            EndpointEvent::ReceiverAttached { receivers }
+        });
 
         // Cloning receivers and calling `mem::forget` on the clones could potentially overflow the
         // counter. It's very difficult to recover sensibly from such degenerate scenarios so we
@@ -115,14 +278,46 @@ impl<C> Receiver<C> {
         }
     }
 
+    /// Returns the number of receiver references.
+    pub fn count(&self) -> usize {
+        self.counter().receivers.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of sender references.
+    pub fn sender_count(&self) -> usize {
+        self.counter().senders.load(Ordering::SeqCst)
+    }
+
+    /// Returns the receiving side of a channel that endpoint events get reported to.
+    pub fn endpoint_events(&self) -> Receiver<list::Channel<EndpointEvent>> {
+        self.counter().subscribe()
+    }
+
     /// Releases the receiver reference.
     ///
     /// Function `disconnect` will be called if this is the last receiver reference.
     pub unsafe fn release<F: FnOnce(&C) -> bool>(&self, disconnect: F) {
-        if self.counter().receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+        let counter = self.counter();
+        let count = counter.transition(&counter.receivers, false, |receivers| {
+            EndpointEvent::ReceiverDetached { receivers }
+        });
+
+        if count == 1 {
             disconnect(&self.counter().chan);
 
+            let signals = self.counter().receivers_gone.lock().take();
+            if let Some(signals) = signals {
+                for s in signals {
+                    s.release(|c| c.disconnect());
+                }
+            }
+
             if self.counter().destroy.swap(true, Ordering::AcqRel) {
+                #[cfg(feature = "fault-injection")]
+                fault::forget(&self.counter().chan as *const C as usize);
+                #[cfg(feature = "metrics")]
+                metered::forget(&self.counter().chan as *const C as usize);
+
                 drop(Box::from_raw(self.counter));
             }
         }
@@ -142,3 +337,39 @@ impl<C> PartialEq for Receiver<C> {
         self.counter == other.counter
     }
 }
+
+/// Releases the sender of a channel that endpoint events get reported to.
+///
+/// Releasing a sender reports an event itself, so doing this in a closure inside `Counter` would
+/// instantiate generic code for ever more closure types, without end.
+fn release_events(s: &Sender<list::Channel<EndpointEvent>>) {
+    unsafe { s.release(|c| c.disconnect()) }
+}
+
+/// A handle through which a channel can be disconnected while it is alive.
+pub struct Closer {
+    /// The address of the channel.
+    chan: usize,
+
+    /// Disconnects the channel at the given address.
+    disconnect: unsafe fn(usize) -> bool,
+
+    /// Equals `true` until the channel is destroyed.
+    alive: Spinlock<bool>,
+}
+
+impl Closer {
+    /// Disconnects the channel if it is still alive.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn close(&self) -> bool {
+        // Holding the lock keeps the channel from being destroyed in the meantime.
+        let alive = self.alive.lock();
+        *alive && unsafe { (self.disconnect)(self.chan) }
+    }
+
+    /// Returns `true` if the channel has not been destroyed yet.
+    pub fn is_alive(&self) -> bool {
+        *self.alive.lock()
+    }
+}
@@ -0,0 +1,271 @@
+//! Channels that drop messages whose key was sent recently.
+//!
+//! Event sources tend to fire in storms: a file watcher reports the same path a dozen times, a
+//! flapping health check reports the same failure over and over. In a deduplicating channel,
+//! every message carries a key, and a message is dropped if a message with the same key went
+//! through within a window. The window is either a number of messages ([`count_window`]) or a
+//! period of time ([`time_window`]).
+//!
+//! Only messages that went through count towards the window. A dropped duplicate doesn't extend
+//! it, so a key that keeps firing still gets through once per window.
+//!
+//! The check happens while the channel's queue is locked for sending, so concurrent senders never
+//! both get the same key through.
+//!
+//! The receiving side is an ordinary [`Receiver`] of key-value pairs.
+//!
+//! [`count_window`]: fn.count_window.html
+//! [`time_window`]: fn.time_window.html
+//! [`Receiver`]: ../struct.Receiver.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::dedup;
+//!
+//! let (s, r) = dedup::count_window(2);
+//!
+//! assert_eq!(s.send("a", 1), Ok(true));
+//! assert_eq!(s.send("a", 2), Ok(false));
+//! assert_eq!(s.send("b", 3), Ok(true));
+//! assert_eq!(s.send("c", 4), Ok(true));
+//!
+//! // "a" has fallen out of the window of the last two messages.
+//! assert_eq!(s.send("a", 5), Ok(true));
+//!
+//! let received: Vec<_> = r.try_iter().collect();
+//! assert_eq!(received, [("a", 1), ("b", 3), ("c", 4), ("a", 5)]);
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::{self, Receiver};
+use counter;
+use err::SendError;
+use flavors;
+use utils::Spinlock;
+
+/// Creates a deduplicating channel whose window spans the last `n` messages that went through.
+///
+/// A message is dropped if its key is among the keys of the last `n` messages sent into the
+/// channel. With `n` set to zero, no message is ever dropped.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::dedup;
+///
+/// let (s, r) = dedup::count_window(1);
+///
+/// // Collapses runs of the same key.
+/// for &key in &["x", "x", "y", "y", "y", "x"] {
+///     s.send(key, ()).unwrap();
+/// }
+///
+/// let keys: Vec<_> = r.try_iter().map(|(key, _)| key).collect();
+/// assert_eq!(keys, ["x", "y", "x"]);
+/// ```
+pub fn count_window<K, V>(n: usize) -> (DedupSender<K, V>, Receiver<(K, V)>)
+where
+    K: Eq + Hash + Clone,
+{
+    with_limit(Limit::Count(n))
+}
+
+/// Creates a deduplicating channel whose window spans the last `duration` of time.
+///
+/// A message is dropped if a message with the same key was sent into the channel less than
+/// `duration` ago.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::dedup;
+///
+/// let (s, r) = dedup::time_window(Duration::from_millis(100));
+///
+/// assert_eq!(s.send("disk full", ()), Ok(true));
+/// assert_eq!(s.send("disk full", ()), Ok(false));
+///
+/// thread::sleep(Duration::from_millis(150));
+/// assert_eq!(s.send("disk full", ()), Ok(true));
+///
+/// assert_eq!(r.len(), 2);
+/// ```
+pub fn time_window<K, V>(duration: Duration) -> (DedupSender<K, V>, Receiver<(K, V)>)
+where
+    K: Eq + Hash + Clone,
+{
+    with_limit(Limit::Time(duration))
+}
+
+/// Creates a deduplicating channel with the given window.
+fn with_limit<K, V>(limit: Limit) -> (DedupSender<K, V>, Receiver<(K, V)>)
+where
+    K: Eq + Hash + Clone,
+{
+    let (s, r) = channel::coalesce();
+    let s = DedupSender {
+        chan: s,
+        window: Arc::new(Spinlock::new(Window {
+            limit,
+            entries: VecDeque::new(),
+            keys: HashSet::new(),
+        })),
+    };
+    (s, r)
+}
+
+/// How far back the window reaches.
+#[derive(Clone, Copy, Debug)]
+enum Limit {
+    /// The last given number of messages.
+    Count(usize),
+
+    /// The last given period of time.
+    Time(Duration),
+}
+
+/// Keys of the messages that went through within the window.
+struct Window<K> {
+    /// How far back the window reaches.
+    limit: Limit,
+
+    /// Keys in the order their messages went through, with the instants at which they did.
+    entries: VecDeque<(Instant, K)>,
+
+    /// The set of keys in `entries`.
+    ///
+    /// Keys in the window are distinct, since duplicates don't go through.
+    keys: HashSet<K>,
+}
+
+impl<K: Eq + Hash + Clone> Window<K> {
+    /// Removes keys that have fallen out of the window.
+    fn expire(&mut self, now: Instant) {
+        while let Some(&(at, _)) = self.entries.front() {
+            let expired = match self.limit {
+                Limit::Count(n) => self.entries.len() > n,
+                Limit::Time(duration) => now.duration_since(at) >= duration,
+            };
+            if !expired {
+                break;
+            }
+
+            if let Some((_, key)) = self.entries.pop_front() {
+                self.keys.remove(&key);
+            }
+        }
+    }
+
+    /// Records a message that went through.
+    fn insert(&mut self, now: Instant, key: K) {
+        self.keys.insert(key.clone());
+        self.entries.push_back((now, key));
+        self.expire(now);
+    }
+}
+
+/// The sending side of a deduplicating channel.
+///
+/// Created by the [`count_window`] and [`time_window`] functions.
+///
+/// [`count_window`]: fn.count_window.html
+/// [`time_window`]: fn.time_window.html
+pub struct DedupSender<K, V> {
+    chan: counter::Sender<flavors::coalesce::Channel<(K, V)>>,
+
+    /// Keys of the messages that went through within the window.
+    ///
+    /// Shared by all senders of the channel.
+    window: Arc<Spinlock<Window<K>>>,
+}
+
+unsafe impl<K: Send, V: Send> Send for DedupSender<K, V> {}
+unsafe impl<K: Send, V: Send> Sync for DedupSender<K, V> {}
+
+impl<K, V> DedupSender<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Sends a value under the given key, unless the key was sent within the window.
+    ///
+    /// Returns `true` if the message was enqueued and `false` if it was dropped as a duplicate.
+    /// This method never blocks.
+    ///
+    /// If the channel is disconnected, the key and the value are returned back in the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{dedup, SendError};
+    ///
+    /// let (s, r) = dedup::count_window(8);
+    /// assert_eq!(s.send(1, 'a'), Ok(true));
+    /// assert_eq!(s.send(1, 'b'), Ok(false));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(2, 'c'), Err(SendError((2, 'c'))));
+    /// ```
+    pub fn send(&self, key: K, value: V) -> Result<bool, SendError<(K, V)>> {
+        let mut window = self.window.lock();
+        let mut sent = false;
+
+        self.chan
+            .send_with((key, value), |queue, msg| {
+                let now = Instant::now();
+                window.expire(now);
+
+                if !window.keys.contains(&msg.0) {
+                    window.insert(now, msg.0.clone());
+                    queue.push(msg);
+                    sent = true;
+                }
+            })
+            .map_err(SendError)?;
+
+        Ok(sent)
+    }
+}
+
+impl<K, V> DedupSender<K, V> {
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+}
+
+impl<K, V> Drop for DedupSender<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<K, V> Clone for DedupSender<K, V> {
+    fn clone(&self) -> Self {
+        DedupSender {
+            chan: self.chan.acquire(),
+            window: self.window.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for DedupSender<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = (self.chan.count(), self.chan.receiver_count());
+        channel::debug_endpoint(f, "DedupSender", &*self.chan, Some(counts))
+    }
+}
@@ -0,0 +1,255 @@
+//! Bounded channels with a soft capacity that signals backpressure before senders block.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use channel::{bounded, Receiver, Sender};
+use err::{SendError, TrySendError};
+
+/// Creates a bounded channel with a soft and a hard capacity.
+///
+/// The channel holds at most `hard_cap` messages, and sending into a full channel blocks just like
+/// with [`bounded`]. Between `soft_cap` and `hard_cap` messages, sends still succeed right away
+/// but report [`Pressure::Backpressured`], so producers can slow down before they stall. A sender
+/// can additionally be configured to sleep for a while after each backpressured send.
+///
+/// Short spikes can be absorbed by borrowing the capacity above the soft cap with
+/// [`borrow_burst`]. While a burst is active, sends above the soft cap report [`Pressure::Burst`]
+/// instead. The burst ends once the channel drains back to the soft cap, after which the soft cap
+/// is honored again.
+///
+/// [`bounded`]: fn.bounded.html
+/// [`Pressure::Backpressured`]: enum.Pressure.html#variant.Backpressured
+/// [`Pressure::Burst`]: enum.Pressure.html#variant.Burst
+/// [`borrow_burst`]: struct.ElasticSender.html#method.borrow_burst
+///
+/// # Panics
+///
+/// Panics if `soft_cap` is greater than `hard_cap`.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{elastic_bounded, Pressure};
+///
+/// let (s, r) = elastic_bounded(1, 3);
+///
+/// assert_eq!(s.send(1), Ok(Pressure::Normal));
+/// assert_eq!(s.send(2), Ok(Pressure::Backpressured));
+///
+/// // Absorb a spike without backpressure.
+/// s.borrow_burst();
+/// assert_eq!(s.send(3), Ok(Pressure::Burst));
+///
+/// // The burst ends once the channel drains to the soft cap.
+/// assert_eq!(r.recv(), Ok(1));
+/// assert_eq!(r.recv(), Ok(2));
+/// assert_eq!(s.send(4), Ok(Pressure::Backpressured));
+/// assert!(!s.is_bursting());
+/// ```
+pub fn elastic_bounded<T>(soft_cap: usize, hard_cap: usize) -> (ElasticSender<T>, Receiver<T>) {
+    assert!(
+        soft_cap <= hard_cap,
+        "soft capacity must not exceed hard capacity"
+    );
+
+    let (s, r) = bounded(hard_cap);
+    let s = ElasticSender {
+        sender: s,
+        shared: Arc::new(Shared {
+            soft_cap,
+            burst: AtomicUsize::new(IDLE),
+        }),
+        slowdown: None,
+    };
+    (s, r)
+}
+
+/// How full an elastic channel was after a successful send.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pressure {
+    /// The channel is within its soft capacity.
+    Normal,
+
+    /// The channel is above its soft capacity, but a burst is absorbing the excess.
+    Burst,
+
+    /// The channel is above its soft capacity and producers should slow down.
+    Backpressured,
+}
+
+/// No burst is active.
+const IDLE: usize = 0;
+
+/// A burst has been borrowed, but the channel hasn't gone above the soft capacity yet.
+const BORROWED: usize = 1;
+
+/// The channel is above the soft capacity thanks to a burst.
+const ABSORBING: usize = 2;
+
+/// State shared by all senders of an elastic channel.
+struct Shared {
+    /// The number of messages above which sends are backpressured.
+    soft_cap: usize,
+
+    /// The state of the burst: `IDLE`, `BORROWED`, or `ABSORBING`.
+    burst: AtomicUsize,
+}
+
+/// The sending side of an elastic channel.
+///
+/// Created by the [`elastic_bounded`] function.
+///
+/// [`elastic_bounded`]: fn.elastic_bounded.html
+pub struct ElasticSender<T> {
+    sender: Sender<T>,
+    shared: Arc<Shared>,
+    slowdown: Option<Duration>,
+}
+
+impl<T> ElasticSender<T> {
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// This method only blocks if the channel has reached its hard capacity. On success, it
+    /// reports how full the channel was after the message was sent.
+    ///
+    /// If a slowdown is configured and the send is backpressured, the current thread sleeps for
+    /// the configured duration before returning.
+    pub fn send(&self, msg: T) -> Result<Pressure, SendError<T>> {
+        self.end_drained_burst();
+        self.sender.send(msg)?;
+
+        let pressure = self.check();
+        if pressure == Pressure::Backpressured {
+            if let Some(slowdown) = self.slowdown {
+                thread::sleep(slowdown);
+            }
+        }
+        Ok(pressure)
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This method fails only if the channel has reached its hard capacity or is disconnected. On
+    /// success, it reports how full the channel was after the message was sent, but never sleeps.
+    pub fn try_send(&self, msg: T) -> Result<Pressure, TrySendError<T>> {
+        self.end_drained_burst();
+        self.sender.try_send(msg)?;
+        Ok(self.check())
+    }
+
+    /// Borrows the capacity above the soft cap until the channel drains back to the soft cap.
+    ///
+    /// The burst can be borrowed ahead of a spike. It ends only after the channel has gone above
+    /// the soft cap and then drained back to it. All senders of the channel share the burst.
+    pub fn borrow_burst(&self) {
+        let _ =
+            self.shared
+                .burst
+                .compare_exchange(IDLE, BORROWED, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if a burst is active.
+    ///
+    /// The end of a burst is detected by the first send after the channel drains to the soft cap.
+    pub fn is_bursting(&self) -> bool {
+        self.shared.burst.load(Ordering::SeqCst) != IDLE
+    }
+
+    /// Sets how long this sender sleeps after each backpressured send.
+    ///
+    /// The slowdown applies to [`send`] only, and is not shared with other senders. Clones start
+    /// out with the same slowdown.
+    ///
+    /// [`send`]: struct.ElasticSender.html#method.send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{elastic_bounded, Pressure};
+    ///
+    /// let (mut s, _r) = elastic_bounded(0, 1);
+    /// s.set_slowdown(Some(Duration::from_millis(100)));
+    ///
+    /// let start = Instant::now();
+    /// assert_eq!(s.send(1), Ok(Pressure::Backpressured));
+    /// assert!(start.elapsed() >= Duration::from_millis(100));
+    /// ```
+    pub fn set_slowdown(&mut self, slowdown: Option<Duration>) {
+        self.slowdown = slowdown;
+    }
+
+    /// Returns the soft capacity of the channel.
+    pub fn soft_capacity(&self) -> usize {
+        self.shared.soft_cap
+    }
+
+    /// Returns the hard capacity of the channel.
+    pub fn hard_capacity(&self) -> usize {
+        self.sender.capacity().unwrap()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    /// Returns the underlying sender, which ignores the soft capacity.
+    pub fn as_sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+
+    /// Ends the burst if the channel has drained back to the soft capacity.
+    fn end_drained_burst(&self) {
+        if self.sender.len() <= self.shared.soft_cap {
+            let _ = self.shared.burst.compare_exchange(
+                ABSORBING,
+                IDLE,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+        }
+    }
+
+    /// Checks how full the channel is after a send.
+    fn check(&self) -> Pressure {
+        if self.sender.len() <= self.shared.soft_cap {
+            Pressure::Normal
+        } else if self.is_bursting() {
+            let _ = self.shared.burst.compare_exchange(
+                BORROWED,
+                ABSORBING,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+            Pressure::Burst
+        } else {
+            Pressure::Backpressured
+        }
+    }
+}
+
+impl<T> Clone for ElasticSender<T> {
+    fn clone(&self) -> Self {
+        ElasticSender {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+            slowdown: self.slowdown,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ElasticSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ElasticSender { .. }")
+    }
+}
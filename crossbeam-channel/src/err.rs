@@ -83,37 +83,161 @@ pub enum RecvTimeoutError {
     Disconnected,
 }
 
+/// An error returned from the [`recv_batch_timeout`] method.
+///
+/// The error contains the number of messages that were received before the operation stopped.
+///
+/// [`recv_batch_timeout`]: struct.Receiver.html#method.recv_batch_timeout
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvBatchTimeoutError {
+    /// The operation timed out before the requested number of messages was received.
+    Timeout(usize),
+
+    /// The channel became empty and disconnected before the requested number of messages was
+    /// received.
+    Disconnected(usize),
+}
+
+/// An error returned from the [`send_interruptible`] method.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`send_interruptible`]: struct.Sender.html#method.send_interruptible
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendInterruptibleError<T> {
+    /// The message could not be sent because the operation was interrupted.
+    Interrupted(T),
+
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+}
+
+/// An error returned from the [`recv_interruptible`] method.
+///
+/// [`recv_interruptible`]: struct.Receiver.html#method.recv_interruptible
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvInterruptibleError {
+    /// A message could not be received because the channel is empty and the operation was
+    /// interrupted.
+    Interrupted,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+}
+
 /// An error returned from the [`try_select`] method.
 ///
 /// Failed because none of the channel operations were ready.
 ///
+/// The error describes the operations in the select, so that a caller retrying in a loop can tell
+/// whether any of them may still become ready.
+///
 /// [`try_select`]: struct.Select.html#method.try_select
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-pub struct TrySelectError;
+pub struct TrySelectError {
+    operations: usize,
+    disconnected: usize,
+}
 
 /// An error returned from the [`select_timeout`] method.
 ///
 /// Failed because none of the channel operations became ready before the timeout.
 ///
+/// The error describes the operations in the select, so that a caller retrying in a loop can tell
+/// whether any of them may still become ready.
+///
 /// [`select_timeout`]: struct.Select.html#method.select_timeout
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-pub struct SelectTimeoutError;
+pub struct SelectTimeoutError {
+    operations: usize,
+    disconnected: usize,
+}
 
 /// An error returned from the [`try_ready`] method.
 ///
 /// Failed because none of the channel operations were ready.
 ///
+/// The error describes the operations in the select, so that a caller retrying in a loop can tell
+/// whether any of them may still become ready.
+///
 /// [`try_ready`]: struct.Select.html#method.try_ready
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-pub struct TryReadyError;
+pub struct TryReadyError {
+    operations: usize,
+    disconnected: usize,
+}
 
 /// An error returned from the [`ready_timeout`] method.
 ///
 /// Failed because none of the channel operations became ready before the timeout.
 ///
+/// The error describes the operations in the select, so that a caller retrying in a loop can tell
+/// whether any of them may still become ready.
+///
 /// [`ready_timeout`]: struct.Select.html#method.ready_timeout
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-pub struct ReadyTimeoutError;
+pub struct ReadyTimeoutError {
+    operations: usize,
+    disconnected: usize,
+}
+
+/// An error returned from the [`select_connected`] method.
+///
+/// [`select_connected`]: struct.Select.html#method.select_connected
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SelectError {
+    /// Every operation in the select is on a disconnected channel, so none of them can succeed.
+    ///
+    /// This is also the case if the select has no operations at all.
+    AllDisconnected,
+}
+
+/// An error returned from the [`select_with_budget`] method.
+///
+/// [`select_with_budget`]: struct.Select.html#method.select_with_budget
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SelectBudgetError {
+    /// The thread was woken up more times than its budget allows, but other threads completed the
+    /// ready operations first every time.
+    Contended,
+}
+
+/// An error returned from the [`try_send`] method of a selected operation.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`try_send`]: struct.SelectedOperation.html#method.try_send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SelectedSendError<T> {
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+
+    /// The passed sender is not the one the operation was selected on, or the operation has
+    /// already been completed.
+    WrongOperand(T),
+}
+
+/// An error returned from the [`try_recv`] method of a selected operation.
+///
+/// [`try_recv`]: struct.SelectedOperation.html#method.try_recv
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SelectedRecvError {
+    /// A message could not be received because the channel is empty and disconnected.
+    Disconnected,
+
+    /// The passed receiver is not the one the operation was selected on, or the operation has
+    /// already been completed.
+    WrongOperand,
+}
+
+/// An error returned from the [`after`] and [`at`] methods of a timer service.
+///
+/// Failed because all timers of the service are in use.
+///
+/// [`after`]: struct.TimerService.html#method.after
+/// [`at`]: struct.TimerService.html#method.at
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct TimerFullError;
 
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -418,6 +542,175 @@ impl RecvTimeoutError {
     }
 }
 
+impl fmt::Display for RecvBatchTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvBatchTimeoutError::Timeout(n) => {
+                write!(
+                    f,
+                    "timed out waiting on batch receive operation after {} messages",
+                    n
+                )
+            }
+            RecvBatchTimeoutError::Disconnected(n) => {
+                write!(f, "channel is empty and disconnected after {} messages", n)
+            }
+        }
+    }
+}
+
+impl error::Error for RecvBatchTimeoutError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvBatchTimeoutError::Timeout(..) => "timed out waiting on batch receive operation",
+            RecvBatchTimeoutError::Disconnected(..) => "channel is empty and disconnected",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl RecvBatchTimeoutError {
+    /// Returns the number of messages received before the operation stopped.
+    pub fn received(&self) -> usize {
+        match *self {
+            RecvBatchTimeoutError::Timeout(n) => n,
+            RecvBatchTimeoutError::Disconnected(n) => n,
+        }
+    }
+
+    /// Returns `true` if the receive operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            RecvBatchTimeoutError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            RecvBatchTimeoutError::Disconnected(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendInterruptibleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendInterruptibleError::Interrupted(..) => "Interrupted(..)".fmt(f),
+            SendInterruptibleError::Disconnected(..) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendInterruptibleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendInterruptibleError::Interrupted(..) => "send operation was interrupted".fmt(f),
+            SendInterruptibleError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for SendInterruptibleError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            SendInterruptibleError::Interrupted(..) => "send operation was interrupted",
+            SendInterruptibleError::Disconnected(..) => "sending on a disconnected channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl<T> From<SendError<T>> for SendInterruptibleError<T> {
+    fn from(err: SendError<T>) -> SendInterruptibleError<T> {
+        match err {
+            SendError(t) => SendInterruptibleError::Disconnected(t),
+        }
+    }
+}
+
+impl<T> SendInterruptibleError<T> {
+    /// Unwraps the message.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendInterruptibleError::Interrupted(v) => v,
+            SendInterruptibleError::Disconnected(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation was interrupted.
+    pub fn is_interrupted(&self) -> bool {
+        match self {
+            SendInterruptibleError::Interrupted(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            SendInterruptibleError::Disconnected(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RecvInterruptibleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvInterruptibleError::Interrupted => "receive operation was interrupted".fmt(f),
+            RecvInterruptibleError::Disconnected => "channel is empty and disconnected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvInterruptibleError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvInterruptibleError::Interrupted => "receive operation was interrupted",
+            RecvInterruptibleError::Disconnected => "channel is empty and disconnected",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl From<RecvError> for RecvInterruptibleError {
+    fn from(err: RecvError) -> RecvInterruptibleError {
+        match err {
+            RecvError => RecvInterruptibleError::Disconnected,
+        }
+    }
+}
+
+impl RecvInterruptibleError {
+    /// Returns `true` if the receive operation was interrupted.
+    pub fn is_interrupted(&self) -> bool {
+        match self {
+            RecvInterruptibleError::Interrupted => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            RecvInterruptibleError::Disconnected => true,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for TrySelectError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "all operations in select would block".fmt(f)
@@ -434,6 +727,42 @@ impl error::Error for TrySelectError {
     }
 }
 
+impl TrySelectError {
+    /// Creates an error for a select with `operations` operations, of which `disconnected` can
+    /// never become ready.
+    pub(crate) fn new(operations: usize, disconnected: usize) -> TrySelectError {
+        TrySelectError {
+            operations,
+            disconnected,
+        }
+    }
+
+    /// Returns the number of operations in the select.
+    pub fn operations(&self) -> usize {
+        self.operations
+    }
+
+    /// Returns the number of operations that are permanently disconnected from any source of
+    /// readiness and will never become ready.
+    ///
+    /// Such operations are receive operations on channels created by [`never`], or on channels
+    /// created by [`after`] or a [`TimerService`] whose message has already been received.
+    ///
+    /// [`never`]: fn.never.html
+    /// [`after`]: fn.after.html
+    /// [`TimerService`]: struct.TimerService.html
+    pub fn disconnected(&self) -> usize {
+        self.disconnected
+    }
+
+    /// Returns `true` if none of the operations can ever become ready, so retrying is pointless.
+    ///
+    /// This is also the case if the select has no operations at all.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected == self.operations
+    }
+}
+
 impl fmt::Display for SelectTimeoutError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         "timed out waiting on select".fmt(f)
@@ -449,3 +778,314 @@ impl error::Error for SelectTimeoutError {
         None
     }
 }
+
+impl SelectTimeoutError {
+    /// Creates an error for a select with `operations` operations, of which `disconnected` can
+    /// never become ready.
+    pub(crate) fn new(operations: usize, disconnected: usize) -> SelectTimeoutError {
+        SelectTimeoutError {
+            operations,
+            disconnected,
+        }
+    }
+
+    /// Returns the number of operations in the select.
+    pub fn operations(&self) -> usize {
+        self.operations
+    }
+
+    /// Returns the number of operations that are permanently disconnected from any source of
+    /// readiness and will never become ready.
+    ///
+    /// Such operations are receive operations on channels created by [`never`], or on channels
+    /// created by [`after`] or a [`TimerService`] whose message has already been received.
+    ///
+    /// [`never`]: fn.never.html
+    /// [`after`]: fn.after.html
+    /// [`TimerService`]: struct.TimerService.html
+    pub fn disconnected(&self) -> usize {
+        self.disconnected
+    }
+
+    /// Returns `true` if none of the operations can ever become ready, so retrying is pointless.
+    ///
+    /// This is also the case if the select has no operations at all.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected == self.operations
+    }
+}
+
+impl fmt::Display for TryReadyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "all operations in select would block".fmt(f)
+    }
+}
+
+impl error::Error for TryReadyError {
+    fn description(&self) -> &str {
+        "all operations in select would block"
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl TryReadyError {
+    /// Creates an error for a select with `operations` operations, of which `disconnected` can
+    /// never become ready.
+    pub(crate) fn new(operations: usize, disconnected: usize) -> TryReadyError {
+        TryReadyError {
+            operations,
+            disconnected,
+        }
+    }
+
+    /// Returns the number of operations in the select.
+    pub fn operations(&self) -> usize {
+        self.operations
+    }
+
+    /// Returns the number of operations that are permanently disconnected from any source of
+    /// readiness and will never become ready.
+    ///
+    /// Such operations are receive operations on channels created by [`never`], or on channels
+    /// created by [`after`] or a [`TimerService`] whose message has already been received.
+    ///
+    /// [`never`]: fn.never.html
+    /// [`after`]: fn.after.html
+    /// [`TimerService`]: struct.TimerService.html
+    pub fn disconnected(&self) -> usize {
+        self.disconnected
+    }
+
+    /// Returns `true` if none of the operations can ever become ready, so retrying is pointless.
+    ///
+    /// This is also the case if the select has no operations at all.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected == self.operations
+    }
+}
+
+impl fmt::Display for ReadyTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "timed out waiting on select".fmt(f)
+    }
+}
+
+impl error::Error for ReadyTimeoutError {
+    fn description(&self) -> &str {
+        "timed out waiting on select"
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl ReadyTimeoutError {
+    /// Creates an error for a select with `operations` operations, of which `disconnected` can
+    /// never become ready.
+    pub(crate) fn new(operations: usize, disconnected: usize) -> ReadyTimeoutError {
+        ReadyTimeoutError {
+            operations,
+            disconnected,
+        }
+    }
+
+    /// Returns the number of operations in the select.
+    pub fn operations(&self) -> usize {
+        self.operations
+    }
+
+    /// Returns the number of operations that are permanently disconnected from any source of
+    /// readiness and will never become ready.
+    ///
+    /// Such operations are receive operations on channels created by [`never`], or on channels
+    /// created by [`after`] or a [`TimerService`] whose message has already been received.
+    ///
+    /// [`never`]: fn.never.html
+    /// [`after`]: fn.after.html
+    /// [`TimerService`]: struct.TimerService.html
+    pub fn disconnected(&self) -> usize {
+        self.disconnected
+    }
+
+    /// Returns `true` if none of the operations can ever become ready, so retrying is pointless.
+    ///
+    /// This is also the case if the select has no operations at all.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected == self.operations
+    }
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectError::AllDisconnected => "all operations in select are disconnected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for SelectError {
+    fn description(&self) -> &str {
+        match *self {
+            SelectError::AllDisconnected => "all operations in select are disconnected",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl fmt::Display for SelectBudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectBudgetError::Contended => "select ran out of retries under contention".fmt(f),
+        }
+    }
+}
+
+impl error::Error for SelectBudgetError {
+    fn description(&self) -> &str {
+        match *self {
+            SelectBudgetError::Contended => "select ran out of retries under contention",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl<T> fmt::Debug for SelectedSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectedSendError::Disconnected(..) => "Disconnected(..)".fmt(f),
+            SelectedSendError::WrongOperand(..) => "WrongOperand(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for SelectedSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectedSendError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+            SelectedSendError::WrongOperand(..) => "passed a sender that wasn't selected".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for SelectedSendError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            SelectedSendError::Disconnected(..) => "sending on a disconnected channel",
+            SelectedSendError::WrongOperand(..) => "passed a sender that wasn't selected",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl<T> From<SendError<T>> for SelectedSendError<T> {
+    fn from(err: SendError<T>) -> SelectedSendError<T> {
+        match err {
+            SendError(t) => SelectedSendError::Disconnected(t),
+        }
+    }
+}
+
+impl<T> SelectedSendError<T> {
+    /// Unwraps the message.
+    pub fn into_inner(self) -> T {
+        match self {
+            SelectedSendError::Disconnected(v) => v,
+            SelectedSendError::WrongOperand(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            SelectedSendError::Disconnected(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the wrong sender was passed.
+    pub fn is_wrong_operand(&self) -> bool {
+        match self {
+            SelectedSendError::WrongOperand(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SelectedRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SelectedRecvError::Disconnected => {
+                "receiving on an empty and disconnected channel".fmt(f)
+            }
+            SelectedRecvError::WrongOperand => "passed a receiver that wasn't selected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for SelectedRecvError {
+    fn description(&self) -> &str {
+        match *self {
+            SelectedRecvError::Disconnected => "receiving on an empty and disconnected channel",
+            SelectedRecvError::WrongOperand => "passed a receiver that wasn't selected",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl From<RecvError> for SelectedRecvError {
+    fn from(err: RecvError) -> SelectedRecvError {
+        match err {
+            RecvError => SelectedRecvError::Disconnected,
+        }
+    }
+}
+
+impl SelectedRecvError {
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            SelectedRecvError::Disconnected => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the wrong receiver was passed.
+    pub fn is_wrong_operand(&self) -> bool {
+        match self {
+            SelectedRecvError::WrongOperand => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TimerFullError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "all timers of the timer service are in use".fmt(f)
+    }
+}
+
+impl error::Error for TimerFullError {
+    fn description(&self) -> &str {
+        "all timers of the timer service are in use"
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
@@ -0,0 +1,464 @@
+//! A small event loop dispatching messages from several receivers to handlers.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "timers")]
+use channel::tick;
+use channel::Receiver;
+use err::TryRecvError;
+use select::Select;
+
+/// An event loop that dispatches messages from several receivers to their handlers.
+///
+/// Handlers are registered together with the receiver they handle with [`on_recv`], or with a
+/// ticker with [`on_tick`]. Then [`run`] waits until one of the receivers has a message and passes
+/// it to the handler, over and over again.
+///
+/// A handler is removed once its receiver is disconnected, and the loop returns once no handlers
+/// are left. It also returns when a message arrives on the receiver passed to [`shutdown_on`], or
+/// when that receiver gets disconnected.
+///
+/// Handlers return a `Result`. What happens when a handler fails is decided by the error policy
+/// set with [`on_error`]. By default, the loop stops and [`run`] returns the error.
+///
+/// [`on_recv`]: struct.EventLoop.html#method.on_recv
+/// [`on_tick`]: struct.EventLoop.html#method.on_tick
+/// [`run`]: struct.EventLoop.html#method.run
+/// [`shutdown_on`]: struct.EventLoop.html#method.shutdown_on
+/// [`on_error`]: struct.EventLoop.html#method.on_error
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{unbounded, EventLoop};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+///
+/// thread::spawn(move || {
+///     s1.send(1).unwrap();
+///     s2.send("two").unwrap();
+/// });
+///
+/// let mut numbers = Vec::new();
+/// let mut strings = Vec::new();
+/// {
+///     let mut event_loop = EventLoop::<()>::new();
+///     event_loop.on_recv(r1, |n| {
+///         numbers.push(n);
+///         Ok(())
+///     });
+///     event_loop.on_recv(r2, |s| {
+///         strings.push(s);
+///         Ok(())
+///     });
+///
+///     // Both senders get dropped, so the loop stops after handling all messages.
+///     event_loop.run().unwrap();
+/// }
+///
+/// assert_eq!(numbers, [1]);
+/// assert_eq!(strings, ["two"]);
+/// ```
+pub struct EventLoop<'a, E> {
+    /// Registered handlers, indexed by their ids. Removed handlers are `None`.
+    handlers: Vec<Option<Entry<'a, E>>>,
+
+    /// Metrics of handlers, indexed by their ids.
+    metrics: Vec<HandlerMetrics>,
+
+    /// The receiver that signals shutdown.
+    shutdown: Option<Box<dyn Source<E> + 'a>>,
+
+    /// The error policy.
+    policy: Policy<'a, E>,
+}
+
+/// A policy deciding what happens when a handler returns an error.
+type Policy<'a, E> = Box<dyn FnMut(HandlerId, &E) -> ErrorAction + 'a>;
+
+/// A registered handler.
+struct Entry<'a, E> {
+    /// The receiver together with its handler.
+    source: Box<dyn Source<E> + 'a>,
+
+    /// Whether queued messages get handled on shutdown.
+    drain: bool,
+}
+
+impl<'a, E> EventLoop<'a, E> {
+    /// Creates an event loop without handlers.
+    pub fn new() -> EventLoop<'a, E> {
+        EventLoop {
+            handlers: Vec::new(),
+            metrics: Vec::new(),
+            shutdown: None,
+            policy: Box::new(|_, _| ErrorAction::Stop),
+        }
+    }
+
+    /// Registers a handler for messages received from `receiver`.
+    ///
+    /// The handler is removed once the receiver is disconnected and empty.
+    pub fn on_recv<T, F>(&mut self, receiver: Receiver<T>, handler: F) -> HandlerId
+    where
+        T: 'a,
+        F: FnMut(T) -> Result<(), E> + 'a,
+    {
+        self.add(Box::new(Handler { receiver, handler }), true)
+    }
+
+    /// Registers a handler called periodically with the instant of each tick.
+    ///
+    /// Ticks are delivered by a receiver created with [`tick`], so ticks missed while other
+    /// handlers are running get dropped. Tickers aren't called during shutdown.
+    ///
+    /// [`tick`]: fn.tick.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, EventLoop};
+    ///
+    /// let (s, r) = bounded(1);
+    /// let mut ticks = 0;
+    /// {
+    ///     let mut event_loop = EventLoop::<()>::new();
+    ///     event_loop.on_tick(Duration::from_millis(10), |_| {
+    ///         ticks += 1;
+    ///         if ticks == 3 {
+    ///             s.send(()).unwrap();
+    ///         }
+    ///         Ok(())
+    ///     });
+    ///     event_loop.shutdown_on(r);
+    ///     event_loop.run().unwrap();
+    /// }
+    /// assert_eq!(ticks, 3);
+    /// ```
+    #[cfg(feature = "timers")]
+    pub fn on_tick<F>(&mut self, period: Duration, handler: F) -> HandlerId
+    where
+        F: FnMut(Instant) -> Result<(), E> + 'a,
+    {
+        self.add(
+            Box::new(Handler {
+                receiver: tick(period),
+                handler,
+            }),
+            false,
+        )
+    }
+
+    /// Makes the loop shut down when a message arrives on `receiver` or it gets disconnected.
+    ///
+    /// On shutdown, messages that are already queued in the receivers get handled before
+    /// [`run`] returns. Messages sent after shutdown has begun are left in their channels.
+    ///
+    /// Replaces the previously set shutdown receiver, if any.
+    ///
+    /// [`run`]: struct.EventLoop.html#method.run
+    pub fn shutdown_on<S>(&mut self, receiver: Receiver<S>)
+    where
+        S: 'a,
+    {
+        self.shutdown = Some(Box::new(Handler {
+            receiver,
+            handler: |_| Ok(()),
+        }));
+    }
+
+    /// Sets the policy deciding what happens when a handler returns an error.
+    ///
+    /// The policy gets the id of the failed handler and its error. The default policy always
+    /// returns [`ErrorAction::Stop`].
+    ///
+    /// [`ErrorAction::Stop`]: enum.ErrorAction.html#variant.Stop
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ErrorAction, EventLoop};
+    ///
+    /// let (s, r) = unbounded();
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    /// drop(s);
+    ///
+    /// let mut event_loop = EventLoop::new();
+    /// let id = event_loop.on_recv(r, |n| if n % 2 == 0 { Ok(()) } else { Err(n) });
+    /// event_loop.on_error(|_, _| ErrorAction::Continue);
+    /// event_loop.run().unwrap();
+    ///
+    /// assert_eq!(event_loop.metrics(id).handled(), 5);
+    /// assert_eq!(event_loop.metrics(id).errors(), 2);
+    /// ```
+    pub fn on_error<P>(&mut self, policy: P)
+    where
+        P: FnMut(HandlerId, &E) -> ErrorAction + 'a,
+    {
+        self.policy = Box::new(policy);
+    }
+
+    /// Returns the metrics of a handler.
+    ///
+    /// Metrics are kept after the handler is removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handler wasn't registered in this event loop.
+    pub fn metrics(&self, id: HandlerId) -> HandlerMetrics {
+        self.metrics[id.0]
+    }
+
+    /// Returns `true` if the handler hasn't been removed.
+    pub fn is_registered(&self, id: HandlerId) -> bool {
+        self.handlers.get(id.0).and_then(|h| h.as_ref()).is_some()
+    }
+
+    /// Runs the loop until shutdown, until no handlers are left, or until the error policy stops
+    /// it.
+    ///
+    /// If the error policy stops the loop, the error is returned. The loop can be run again
+    /// afterwards.
+    pub fn run(&mut self) -> Result<(), E> {
+        loop {
+            let selected = {
+                let mut sel = Select::new();
+                let mut ids = Vec::new();
+                for (id, entry) in self.handlers.iter().enumerate() {
+                    if let Some(ref entry) = *entry {
+                        entry.source.register(&mut sel);
+                        ids.push(id);
+                    }
+                }
+
+                if ids.is_empty() {
+                    return Ok(());
+                }
+
+                let shutdown = self.shutdown.as_ref().map(|s| s.register(&mut sel));
+
+                let index = sel.ready();
+                if Some(index) == shutdown {
+                    None
+                } else {
+                    Some(ids[index])
+                }
+            };
+
+            match selected {
+                Some(id) => {
+                    self.dispatch(id)?;
+                }
+                None => {
+                    let signaled = match self.shutdown.as_mut().unwrap().poll() {
+                        Poll::Empty => false,
+                        Poll::Handled(_) | Poll::Disconnected => true,
+                    };
+                    if signaled {
+                        return self.drain();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Registers a handler and returns its id.
+    fn add(&mut self, source: Box<dyn Source<E> + 'a>, drain: bool) -> HandlerId {
+        self.handlers.push(Some(Entry { source, drain }));
+        self.metrics.push(HandlerMetrics::default());
+        HandlerId(self.handlers.len() - 1)
+    }
+
+    /// Handles a message already queued for handler `id`, if there is one.
+    fn dispatch(&mut self, id: usize) -> Result<Poll<()>, E> {
+        let start = Instant::now();
+        let poll = match self.handlers[id] {
+            Some(ref mut entry) => entry.source.poll(),
+            None => return Ok(Poll::Empty),
+        };
+
+        match poll {
+            Poll::Empty => Ok(Poll::Empty),
+            Poll::Disconnected => {
+                self.handlers[id] = None;
+                Ok(Poll::Disconnected)
+            }
+            Poll::Handled(res) => {
+                let elapsed = start.elapsed();
+                let metrics = &mut self.metrics[id];
+                metrics.handled += 1;
+                metrics.busy += elapsed;
+                if elapsed > metrics.max {
+                    metrics.max = elapsed;
+                }
+
+                if let Err(err) = res {
+                    metrics.errors += 1;
+                    match (self.policy)(HandlerId(id), &err) {
+                        ErrorAction::Continue => {}
+                        ErrorAction::Remove => self.handlers[id] = None,
+                        ErrorAction::Stop => return Err(err),
+                    }
+                }
+                Ok(Poll::Handled(Ok(())))
+            }
+        }
+    }
+
+    /// Handles messages already queued in the receivers of all handlers except tickers.
+    fn drain(&mut self) -> Result<(), E> {
+        for id in 0..self.handlers.len() {
+            let pending = match self.handlers[id] {
+                Some(ref entry) if entry.drain => entry.source.pending(),
+                _ => 0,
+            };
+
+            for _ in 0..pending {
+                match self.dispatch(id)? {
+                    Poll::Handled(_) => {}
+                    Poll::Empty | Poll::Disconnected => break,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, E> Default for EventLoop<'a, E> {
+    fn default() -> EventLoop<'a, E> {
+        EventLoop::new()
+    }
+}
+
+impl<'a, E> fmt::Debug for EventLoop<'a, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("EventLoop { .. }")
+    }
+}
+
+/// An identifier of a handler registered in an [`EventLoop`].
+///
+/// [`EventLoop`]: struct.EventLoop.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HandlerId(usize);
+
+/// What an [`EventLoop`] does after a handler returns an error.
+///
+/// [`EventLoop`]: struct.EventLoop.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Ignores the error and keeps the handler.
+    Continue,
+
+    /// Removes the handler and keeps running the loop.
+    Remove,
+
+    /// Stops the loop and returns the error from [`run`].
+    ///
+    /// [`run`]: struct.EventLoop.html#method.run
+    Stop,
+}
+
+/// Metrics of a handler registered in an [`EventLoop`].
+///
+/// Returned by [`EventLoop::metrics`].
+///
+/// [`EventLoop`]: struct.EventLoop.html
+/// [`EventLoop::metrics`]: struct.EventLoop.html#method.metrics
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandlerMetrics {
+    handled: u64,
+    errors: u64,
+    busy: Duration,
+    max: Duration,
+}
+
+impl HandlerMetrics {
+    /// Returns the number of messages the handler has been called with.
+    pub fn handled(&self) -> u64 {
+        self.handled
+    }
+
+    /// Returns the number of times the handler returned an error.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns the total time spent in the handler.
+    pub fn busy(&self) -> Duration {
+        self.busy
+    }
+
+    /// Returns the longest time a single call of the handler took.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+}
+
+impl fmt::Debug for HandlerMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HandlerMetrics")
+            .field("handled", &self.handled)
+            .field("errors", &self.errors)
+            .field("busy", &self.busy)
+            .field("max", &self.max)
+            .finish()
+    }
+}
+
+/// The result of polling a handler.
+enum Poll<E> {
+    /// A message was passed to the handler, which returned the result.
+    Handled(Result<(), E>),
+
+    /// The receiver has no messages.
+    Empty,
+
+    /// The receiver is disconnected and empty.
+    Disconnected,
+}
+
+/// A receiver together with its handler, with the message type erased.
+trait Source<E> {
+    /// Adds the receiver to `sel` and returns the index of the operation.
+    fn register<'s>(&'s self, sel: &mut Select<'s>) -> usize;
+
+    /// Passes a message to the handler, if the receiver has one.
+    fn poll(&mut self) -> Poll<E>;
+
+    /// Returns the number of messages queued in the receiver.
+    fn pending(&self) -> usize;
+}
+
+/// A receiver together with its handler.
+struct Handler<T, F> {
+    receiver: Receiver<T>,
+    handler: F,
+}
+
+impl<T, F, E> Source<E> for Handler<T, F>
+where
+    F: FnMut(T) -> Result<(), E>,
+{
+    fn register<'s>(&'s self, sel: &mut Select<'s>) -> usize {
+        sel.recv(&self.receiver)
+    }
+
+    fn poll(&mut self) -> Poll<E> {
+        match self.receiver.try_recv() {
+            Ok(msg) => Poll::Handled((self.handler)(msg)),
+            Err(TryRecvError::Empty) => Poll::Empty,
+            Err(TryRecvError::Disconnected) => Poll::Disconnected,
+        }
+    }
+
+    fn pending(&self) -> usize {
+        self.receiver.len()
+    }
+}
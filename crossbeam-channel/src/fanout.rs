@@ -0,0 +1,452 @@
+//! Channels that deliver a copy of every message to each receiver.
+
+use std::fmt;
+use std::ops;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use channel::{unbounded, Receiver, Sender};
+use err::SendError;
+
+/// Creates a channel that delivers a copy of every message to each receiver.
+///
+/// Every clone of the returned [`FanoutReceiver`] gets its own copy of each message sent after the
+/// clone was created. Each receiver has an independent unbounded buffer, so a slow receiver never
+/// stalls the sender or the other receivers.
+///
+/// [`FanoutReceiver`]: struct.FanoutReceiver.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::fanout;
+///
+/// let (s, r1) = fanout();
+/// let r2 = r1.clone();
+///
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+///
+/// assert_eq!(r1.recv(), Ok(1));
+/// assert_eq!(r1.recv(), Ok(2));
+/// assert_eq!(r2.recv(), Ok(1));
+/// assert_eq!(r2.recv(), Ok(2));
+/// ```
+pub fn fanout<T: Clone>() -> (FanoutSender<T>, FanoutReceiver<T>) {
+    new(None)
+}
+
+/// Creates a channel that delivers a copy of every message to each receiver, detaching receivers
+/// that fall too far behind.
+///
+/// This is like [`fanout`], except that a receiver with `limit` undelivered messages in its
+/// buffer is considered to be lagging. Instead of buffering more messages for it, the receiver is
+/// detached from the channel: it can still receive the messages already in its buffer, after
+/// which it becomes disconnected and [`is_lagged`] returns `true`.
+///
+/// [`fanout`]: fn.fanout.html
+/// [`is_lagged`]: struct.FanoutReceiver.html#method.is_lagged
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{fanout_with_lag_limit, RecvError};
+///
+/// let (s, fast) = fanout_with_lag_limit(2);
+/// let slow = fast.clone();
+///
+/// for i in 0..3 {
+///     s.send(i).unwrap();
+///     assert_eq!(fast.recv(), Ok(i));
+/// }
+///
+/// // The slow receiver was detached when its buffer reached the limit.
+/// assert_eq!(slow.recv(), Ok(0));
+/// assert_eq!(slow.recv(), Ok(1));
+/// assert_eq!(slow.recv(), Err(RecvError));
+/// assert!(slow.is_lagged());
+/// assert!(!fast.is_lagged());
+/// ```
+pub fn fanout_with_lag_limit<T: Clone>(limit: usize) -> (FanoutSender<T>, FanoutReceiver<T>) {
+    assert!(limit > 0, "lag limit must be positive");
+    new(Some(limit))
+}
+
+/// Creates a fanout channel with an optional lag limit.
+fn new<T>(lag_limit: Option<usize>) -> (FanoutSender<T>, FanoutReceiver<T>) {
+    let shared = Arc::new(Shared {
+        subscribers: Mutex::new(Vec::new()),
+        senders: AtomicUsize::new(1),
+        lag_limit,
+    });
+    let r = FanoutReceiver::subscribe(&shared, None);
+    let s = FanoutSender { shared };
+    (s, r)
+}
+
+/// State shared by all endpoints of a fanout channel.
+struct Shared<T> {
+    /// Receivers attached to the channel.
+    subscribers: Mutex<Vec<Subscriber<T>>>,
+
+    /// The number of senders associated with the channel.
+    senders: AtomicUsize,
+
+    /// The number of buffered messages at which a receiver is considered to be lagging.
+    lag_limit: Option<usize>,
+}
+
+/// A predicate selecting the messages a receiver is interested in.
+type Filter<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// A receiver to send a message to outside the lock, with its filter.
+type Target<T> = (Sender<T>, Option<Filter<T>>);
+
+/// A receiver attached to a fanout channel.
+struct Subscriber<T> {
+    /// The sending side of the receiver's own buffer.
+    sender: Sender<T>,
+
+    /// Set to `true` if the receiver was detached for lagging behind.
+    lagged: Arc<AtomicBool>,
+
+    /// The predicate messages must satisfy to be delivered to the receiver, if any.
+    filter: Option<Filter<T>>,
+}
+
+impl<T> Subscriber<T> {
+    /// Returns `true` if the message should be delivered to the receiver.
+    fn accepts(&self, msg: &T) -> bool {
+        accepts(&self.filter, msg)
+    }
+}
+
+/// Returns `true` if the message satisfies the optional filter.
+fn accepts<T>(filter: &Option<Filter<T>>, msg: &T) -> bool {
+    match *filter {
+        Some(ref filter) => filter(msg),
+        None => true,
+    }
+}
+
+/// The sending side of a fanout channel.
+///
+/// Created by the [`fanout`] function.
+///
+/// [`fanout`]: fn.fanout.html
+pub struct FanoutSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Clone> FanoutSender<T> {
+    /// Sends a copy of the message to every receiver.
+    ///
+    /// Receivers created by [`subscribe_filtered`] only get the message if it satisfies their
+    /// predicate. Predicates are evaluated here, so receivers are never woken up for messages
+    /// they're not interested in.
+    ///
+    /// This method never blocks. If there are no receivers left, the message is returned back in
+    /// the error. A message that no receiver is interested in is still sent successfully.
+    ///
+    /// [`subscribe_filtered`]: struct.FanoutReceiver.html#method.subscribe_filtered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{fanout, SendError};
+    ///
+    /// let (s, r) = fanout();
+    /// assert_eq!(s.send(1), Ok(()));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(2), Err(SendError(2)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let mut subscribers = self.shared.subscribers.lock().unwrap();
+        let mut msg = Some(msg);
+        let mut i = 0;
+
+        while i < subscribers.len() {
+            // Detach the receiver if it has fallen too far behind.
+            if let Some(limit) = self.shared.lag_limit {
+                if subscribers[i].sender.len() >= limit {
+                    let sub = subscribers.remove(i);
+                    sub.lagged.store(true, Ordering::SeqCst);
+                    continue;
+                }
+            }
+
+            if !subscribers[i].accepts(msg.as_ref().unwrap()) {
+                // Nothing gets sent, so check whether the receiver has been dropped.
+                if subscribers[i].sender.is_disconnected() {
+                    subscribers.remove(i);
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+
+            // The last receiver gets the original message, others get copies.
+            let m = if i + 1 == subscribers.len() {
+                msg.take().unwrap()
+            } else {
+                msg.as_ref().unwrap().clone()
+            };
+
+            match subscribers[i].sender.send(m) {
+                Ok(()) => i += 1,
+                Err(SendError(m)) => {
+                    // The receiver has been dropped.
+                    subscribers.remove(i);
+                    if msg.is_none() {
+                        msg = Some(m);
+                    }
+                }
+            }
+        }
+
+        if subscribers.is_empty() {
+            Err(SendError(msg.unwrap()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T> FanoutSender<Arc<T>> {
+    /// Sends a shared message to every receiver, cloning the `Arc` outside the channel lock.
+    ///
+    /// [`send`] clones the message for every receiver while holding the lock that protects the
+    /// list of receivers, which stalls other senders and new receivers for the whole fan-out. This
+    /// method only takes a snapshot of the receivers while holding the lock. The `Arc` is then
+    /// cloned and handed out to each receiver after the lock has been released.
+    ///
+    /// Because of that, messages sent concurrently from different senders may arrive at different
+    /// receivers in different orders. Messages sent from a single sender always arrive in order.
+    ///
+    /// Predicates of receivers created by [`subscribe_filtered`] are evaluated after the lock has
+    /// been released, too.
+    ///
+    /// If there are no receivers left, a clone of the message is returned back in the error.
+    ///
+    /// [`send`]: struct.FanoutSender.html#method.send
+    /// [`subscribe_filtered`]: struct.FanoutReceiver.html#method.subscribe_filtered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use crossbeam_channel::fanout;
+    ///
+    /// let (s, r1) = fanout();
+    /// let r2 = r1.clone();
+    ///
+    /// let msg = Arc::new(vec![0u8; 1024]);
+    /// s.send_shared(&msg).unwrap();
+    ///
+    /// assert!(Arc::ptr_eq(&r1.recv().unwrap(), &msg));
+    /// assert!(Arc::ptr_eq(&r2.recv().unwrap(), &msg));
+    /// ```
+    pub fn send_shared(&self, msg: &Arc<T>) -> Result<(), SendError<Arc<T>>> {
+        let targets: Vec<Target<Arc<T>>> = {
+            let mut subscribers = self.shared.subscribers.lock().unwrap();
+
+            // Detach receivers that have fallen too far behind.
+            if let Some(limit) = self.shared.lag_limit {
+                subscribers.retain(|sub| {
+                    let lagging = sub.sender.len() >= limit;
+                    if lagging {
+                        sub.lagged.store(true, Ordering::SeqCst);
+                    }
+                    !lagging
+                });
+            }
+
+            subscribers
+                .iter()
+                .map(|sub| (sub.sender.clone(), sub.filter.clone()))
+                .collect()
+        };
+
+        let mut delivered = false;
+        let mut dropped = Vec::new();
+        for (sender, filter) in targets {
+            if !accepts(&filter, msg) {
+                // The receiver only counts as gone if it has been dropped.
+                if sender.is_disconnected() {
+                    dropped.push(sender);
+                } else {
+                    delivered = true;
+                }
+                continue;
+            }
+            match sender.send(msg.clone()) {
+                Ok(()) => delivered = true,
+                Err(_) => dropped.push(sender),
+            }
+        }
+
+        if !dropped.is_empty() {
+            // Remove receivers that have been dropped.
+            let mut subscribers = self.shared.subscribers.lock().unwrap();
+            subscribers.retain(|sub| !dropped.iter().any(|s| s.same_channel(&sub.sender)));
+        }
+
+        if delivered {
+            Ok(())
+        } else {
+            Err(SendError(msg.clone()))
+        }
+    }
+}
+
+impl<T> FanoutSender<T> {
+    /// Returns the number of receivers attached to the channel.
+    ///
+    /// Receivers that have been dropped or detached are removed during the next send.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::fanout;
+    ///
+    /// let (s, r) = fanout::<i32>();
+    /// let r2 = r.clone();
+    /// assert_eq!(s.receiver_count(), 2);
+    /// ```
+    pub fn receiver_count(&self) -> usize {
+        self.shared.subscribers.lock().unwrap().len()
+    }
+}
+
+impl<T> Drop for FanoutSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Disconnect all receivers.
+            self.shared.subscribers.lock().unwrap().clear();
+        }
+    }
+}
+
+impl<T> Clone for FanoutSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::SeqCst);
+        FanoutSender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for FanoutSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("FanoutSender { .. }")
+    }
+}
+
+/// The receiving side of a fanout channel.
+///
+/// Each clone of a fanout receiver gets its own copy of every message sent after the clone was
+/// created, or only of those satisfying its predicate if it was created by [`subscribe_filtered`].
+/// The receiver dereferences to an ordinary [`Receiver`], so it can be used wherever a
+/// receiver is expected, including [`select!`].
+///
+/// [`subscribe_filtered`]: struct.FanoutReceiver.html#method.subscribe_filtered
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+pub struct FanoutReceiver<T> {
+    receiver: Receiver<T>,
+    shared: Arc<Shared<T>>,
+    lagged: Arc<AtomicBool>,
+    filter: Option<Filter<T>>,
+}
+
+impl<T> FanoutReceiver<T> {
+    /// Attaches a new receiver to the channel, optionally only receiving messages that satisfy a
+    /// filter.
+    fn subscribe(shared: &Arc<Shared<T>>, filter: Option<Filter<T>>) -> FanoutReceiver<T> {
+        let (s, r) = unbounded();
+        let lagged = Arc::new(AtomicBool::new(false));
+
+        let mut subscribers = shared.subscribers.lock().unwrap();
+        // If all senders are gone, the new receiver starts out disconnected.
+        if shared.senders.load(Ordering::SeqCst) > 0 {
+            subscribers.push(Subscriber {
+                sender: s,
+                lagged: lagged.clone(),
+                filter: filter.clone(),
+            });
+        }
+
+        FanoutReceiver {
+            receiver: r,
+            shared: shared.clone(),
+            lagged,
+            filter,
+        }
+    }
+
+    /// Attaches a new receiver to the channel that only gets messages satisfying `predicate`.
+    ///
+    /// The predicate is evaluated on the sending side for every message sent after the receiver
+    /// was created, so messages it rejects never reach the receiver's buffer and never wake it up.
+    /// It should be quick, since it runs while sending. Messages are still received in the order
+    /// they were sent.
+    ///
+    /// Clones of the new receiver keep the predicate. The predicate of this receiver, if any,
+    /// doesn't apply to the new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::fanout;
+    ///
+    /// let (s, all) = fanout();
+    /// let even = all.subscribe_filtered(|n: &i32| n % 2 == 0);
+    ///
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(all.try_iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    /// assert_eq!(even.try_iter().collect::<Vec<_>>(), [0, 2, 4]);
+    /// ```
+    pub fn subscribe_filtered<F>(&self, predicate: F) -> FanoutReceiver<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        FanoutReceiver::subscribe(&self.shared, Some(Arc::new(predicate)))
+    }
+
+    /// Returns `true` if the receiver was detached from the channel for lagging behind.
+    ///
+    /// See [`fanout_with_lag_limit`] for details.
+    ///
+    /// [`fanout_with_lag_limit`]: fn.fanout_with_lag_limit.html
+    pub fn is_lagged(&self) -> bool {
+        self.lagged.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> ops::Deref for FanoutReceiver<T> {
+    type Target = Receiver<T>;
+
+    fn deref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+}
+
+impl<T> Clone for FanoutReceiver<T> {
+    fn clone(&self) -> Self {
+        FanoutReceiver::subscribe(&self.shared, self.filter.clone())
+    }
+}
+
+impl<T> fmt::Debug for FanoutReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("FanoutReceiver { .. }")
+    }
+}
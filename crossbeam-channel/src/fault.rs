@@ -0,0 +1,424 @@
+//! Fault injection for testing.
+//!
+//! This module is only available with the `fault-injection` feature.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use channel::{Receiver, Sender};
+
+/// What to do with a message that is being sent.
+///
+/// Returned by hooks installed with [`inject_send`].
+///
+/// [`inject_send`]: fn.inject_send.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendFault {
+    /// Send the message normally.
+    Deliver,
+
+    /// Wait for the given duration, then send the message.
+    Delay(Duration),
+
+    /// Silently discard the message while reporting success to the sender.
+    Drop,
+
+    /// Send the message twice.
+    Duplicate,
+
+    /// Hold the message back and send it right after the next message that gets sent.
+    Hold,
+}
+
+/// What to do with a message that has just been received.
+///
+/// Returned by hooks installed with [`inject_recv`].
+///
+/// [`inject_recv`]: fn.inject_recv.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvFault {
+    /// Return the message normally.
+    Deliver,
+
+    /// Wait for the given duration, then return the message.
+    Delay(Duration),
+
+    /// Silently discard the message and receive the next one instead.
+    Drop,
+}
+
+/// Number of channels with installed hooks.
+///
+/// The registry is only consulted while this is nonzero.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    /// Hooks installed on channels, keyed by channel address.
+    ///
+    /// The registry is never locked while a hook runs, so hooks are free to use faulted channels
+    /// and to install or clear faults themselves.
+    static ref REGISTRY: Mutex<HashMap<usize, Hooks>> = Mutex::new(HashMap::new());
+}
+
+thread_local! {
+    /// Set while the current thread is running a hook.
+    ///
+    /// Channel operations performed by a hook are not intercepted, which would otherwise
+    /// deadlock on the hook's own lock.
+    static IN_HOOK: Cell<bool> = Cell::new(false);
+}
+
+/// Hooks installed on a channel.
+#[derive(Default)]
+struct Hooks {
+    /// The hook intercepting sent messages.
+    send: Option<Arc<SendHook>>,
+
+    /// The hook intercepting received messages.
+    recv: Option<Arc<Mutex<Box<dyn FnMut() -> RecvFault + Send>>>>,
+}
+
+/// A hook intercepting sent messages, along with the messages it is holding back.
+struct SendHook {
+    /// Decides what to do with each message.
+    hook: Mutex<Box<dyn FnMut() -> SendFault + Send>>,
+
+    /// Clones a boxed message of the channel's message type.
+    clone: unsafe fn(*const u8) -> *mut u8,
+
+    /// Drops a boxed message of the channel's message type.
+    drop: unsafe fn(*mut u8),
+
+    /// Boxed messages held back by `SendFault::Hold`.
+    held: Mutex<Vec<*mut u8>>,
+}
+
+unsafe impl Send for SendHook {}
+unsafe impl Sync for SendHook {}
+
+impl SendHook {
+    /// Holds back boxed messages until the next message gets sent.
+    fn hold(&self, msgs: Vec<*mut u8>) {
+        lock(&self.held).extend(msgs);
+    }
+
+    /// Takes the messages held back so far.
+    fn take_held(&self) -> Vec<*mut u8> {
+        let mut held = Vec::new();
+        mem::swap(&mut held, &mut *lock(&self.held));
+        held
+    }
+}
+
+impl Drop for SendHook {
+    fn drop(&mut self) {
+        for &msg in lock(&self.held).iter() {
+            unsafe { (self.drop)(msg) }
+        }
+    }
+}
+
+/// Locks a mutex guarding a hook, ignoring poisoning.
+///
+/// A hook that panicked shouldn't break every later operation on its channel.
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Runs `f` with channel operations on the current thread not being intercepted.
+fn run_hook<R, F: FnOnce() -> R>(f: F) -> R {
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            IN_HOOK.with(|in_hook| in_hook.set(false));
+        }
+    }
+
+    IN_HOOK.with(|in_hook| in_hook.set(true));
+    let _reset = Reset;
+    f()
+}
+
+/// Returns `true` if the current thread is running a hook.
+fn in_hook() -> bool {
+    IN_HOOK.try_with(|in_hook| in_hook.get()).unwrap_or(true)
+}
+
+unsafe fn clone_boxed<T: Clone>(msg: *const u8) -> *mut u8 {
+    Box::into_raw(Box::new((*(msg as *const T)).clone())) as *mut u8
+}
+
+unsafe fn drop_boxed<T>(msg: *mut u8) {
+    drop(Box::from_raw(msg as *mut T));
+}
+
+/// Installs a hook that intercepts messages sent into the channel.
+///
+/// The hook is called for every message sent with [`send`], [`send_timeout`], or [`try_send`] on
+/// any sender of the channel, and decides whether the message gets delivered, delayed, dropped,
+/// duplicated, or held back and reordered. Messages sent by operations in [`select!`] or
+/// [`Select`] are not intercepted.
+///
+/// The hook replaces any previously installed send hook and stays installed until it is removed
+/// by [`clear_faults`] or the channel is destroyed. Messages sent by the hook itself, or while
+/// cloning a duplicated message, are not intercepted.
+///
+/// If a message can't be sent, the messages held back so far stay held. If one of the held
+/// messages can't be sent after the new message, the error carrying it is returned and the rest
+/// of them stay held.
+///
+/// [`send`]: struct.Sender.html#method.send
+/// [`send_timeout`]: struct.Sender.html#method.send_timeout
+/// [`try_send`]: struct.Sender.html#method.try_send
+/// [`select!`]: macro.select.html
+/// [`Select`]: struct.Select.html
+/// [`clear_faults`]: fn.clear_faults.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{inject_send, unbounded, SendFault};
+///
+/// let (s, r) = unbounded();
+///
+/// // Drop every other message.
+/// let mut n = 0;
+/// inject_send(&s, move || {
+///     n += 1;
+///     if n % 2 == 0 {
+///         SendFault::Drop
+///     } else {
+///         SendFault::Deliver
+///     }
+/// });
+///
+/// for i in 0..6 {
+///     s.send(i).unwrap();
+/// }
+/// assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 2, 4]);
+/// ```
+pub fn inject_send<T, F>(s: &Sender<T>, hook: F)
+where
+    T: Clone,
+    F: FnMut() -> SendFault + Send + 'static,
+{
+    let hook = Arc::new(SendHook {
+        hook: Mutex::new(Box::new(hook)),
+        clone: clone_boxed::<T>,
+        drop: drop_boxed::<T>,
+        held: Mutex::new(Vec::new()),
+    });
+
+    let old = {
+        let mut registry = REGISTRY.lock().unwrap();
+        let hooks = registry.entry(s.addr()).or_insert_with(|| {
+            ACTIVE.fetch_add(1, Ordering::SeqCst);
+            Hooks::default()
+        });
+        let old = hooks.send.take();
+        hooks.send = Some(hook);
+        old
+    };
+    drop(old);
+}
+
+/// Installs a hook that intercepts messages received from the channel.
+///
+/// The hook is called for every message received with [`recv`], [`recv_timeout`], or
+/// [`try_recv`] on any receiver of the channel, and decides whether the message gets returned,
+/// delayed, or dropped. Messages received by operations in [`select!`] or [`Select`] are not
+/// intercepted.
+///
+/// The hook replaces any previously installed receive hook and stays installed until it is
+/// removed by [`clear_faults`] or the channel is destroyed. Messages received by the hook itself
+/// are not intercepted.
+///
+/// [`recv`]: struct.Receiver.html#method.recv
+/// [`recv_timeout`]: struct.Receiver.html#method.recv_timeout
+/// [`try_recv`]: struct.Receiver.html#method.try_recv
+/// [`select!`]: macro.select.html
+/// [`Select`]: struct.Select.html
+/// [`clear_faults`]: fn.clear_faults.html
+///
+/// # Panics
+///
+/// Panics if the receiver was created by [`after`], [`tick`], or [`never`].
+///
+/// [`after`]: fn.after.html
+/// [`tick`]: fn.tick.html
+/// [`never`]: fn.never.html
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::{inject_recv, unbounded, RecvFault};
+///
+/// let (s, r) = unbounded();
+///
+/// // Simulate a slow consumer.
+/// inject_recv(&r, || RecvFault::Delay(Duration::from_millis(100)));
+///
+/// s.send(1).unwrap();
+/// let start = Instant::now();
+/// assert_eq!(r.recv(), Ok(1));
+/// assert!(start.elapsed() >= Duration::from_millis(100));
+/// ```
+pub fn inject_recv<T, F>(r: &Receiver<T>, hook: F)
+where
+    F: FnMut() -> RecvFault + Send + 'static,
+{
+    let addr = r
+        .addr()
+        .expect("cannot inject faults into `after`, `tick`, or `never` channels");
+
+    let old = {
+        let mut registry = REGISTRY.lock().unwrap();
+        let hooks = registry.entry(addr).or_insert_with(|| {
+            ACTIVE.fetch_add(1, Ordering::SeqCst);
+            Hooks::default()
+        });
+        let old = hooks.recv.take();
+        hooks.recv = Some(Arc::new(Mutex::new(Box::new(hook))));
+        old
+    };
+    drop(old);
+}
+
+/// Removes all hooks installed on the channel.
+///
+/// Messages that are still being held back by the send hook are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{clear_faults, inject_send, unbounded, SendFault};
+///
+/// let (s, r) = unbounded();
+/// inject_send(&s, || SendFault::Duplicate);
+///
+/// s.send(1).unwrap();
+/// clear_faults(&s);
+/// s.send(2).unwrap();
+///
+/// assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 1, 2]);
+/// ```
+pub fn clear_faults<T>(s: &Sender<T>) {
+    forget(s.addr());
+}
+
+/// Removes hooks installed on the channel at `addr`.
+///
+/// This is called when a channel gets destroyed.
+pub fn forget(addr: usize) {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+
+    let hooks = REGISTRY.lock().unwrap().remove(&addr);
+    if hooks.is_some() {
+        ACTIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+    // Held messages are dropped here, after the registry is unlocked.
+    drop(hooks);
+}
+
+/// Sends `msg` with `send`, applying the send hook installed on the channel at `addr`.
+#[inline]
+pub fn on_send<T, E, F>(addr: usize, msg: T, send: F) -> Result<(), E>
+where
+    F: Fn(T) -> Result<(), E>,
+{
+    if ACTIVE.load(Ordering::SeqCst) == 0 || in_hook() {
+        return send(msg);
+    }
+
+    let hook = REGISTRY
+        .lock()
+        .unwrap()
+        .get(&addr)
+        .and_then(|hooks| hooks.send.clone());
+    let hook = match hook {
+        Some(hook) => hook,
+        None => return send(msg),
+    };
+
+    let fault = run_hook(|| {
+        let mut hook = lock(&hook.hook);
+        (&mut *hook)()
+    });
+    let copy = match fault {
+        SendFault::Hold => {
+            hook.hold(vec![Box::into_raw(Box::new(msg)) as *mut u8]);
+            return Ok(());
+        }
+        SendFault::Drop => return Ok(()),
+        SendFault::Delay(dur) => {
+            thread::sleep(dur);
+            None
+        }
+        SendFault::Duplicate => Some(run_hook(|| unsafe {
+            *Box::from_raw((hook.clone)(&msg as *const T as *const u8) as *mut T)
+        })),
+        SendFault::Deliver => None,
+    };
+
+    // If the new message can't be sent, keep holding the held messages back.
+    if let Some(copy) = copy {
+        send(copy)?;
+    }
+    send(msg)?;
+
+    let mut held = hook.take_held().into_iter();
+    while let Some(p) = held.next() {
+        if let Err(err) = send(unsafe { *Box::from_raw(p as *mut T) }) {
+            hook.hold(held.collect());
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Receives a message with `recv`, applying the receive hook installed on the channel at `addr`.
+#[inline]
+pub fn on_recv<T, E, F>(addr: usize, recv: F) -> Result<T, E>
+where
+    F: Fn() -> Result<T, E>,
+{
+    loop {
+        let msg = recv()?;
+
+        if ACTIVE.load(Ordering::SeqCst) == 0 || in_hook() {
+            return Ok(msg);
+        }
+
+        let hook = REGISTRY
+            .lock()
+            .unwrap()
+            .get(&addr)
+            .and_then(|hooks| hooks.recv.clone());
+        let hook = match hook {
+            Some(hook) => hook,
+            None => return Ok(msg),
+        };
+        let fault = run_hook(|| {
+            let mut hook = lock(&*hook);
+            (&mut *hook)()
+        });
+
+        match fault {
+            RecvFault::Deliver => return Ok(msg),
+            RecvFault::Delay(dur) => {
+                thread::sleep(dur);
+                return Ok(msg);
+            }
+            RecvFault::Drop => drop(msg),
+        }
+    }
+}
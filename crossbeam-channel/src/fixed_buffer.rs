@@ -0,0 +1,77 @@
+//! Fixed-capacity buffers for receiving batches without allocating.
+
+use std::collections::VecDeque;
+
+/// A buffer with a fixed capacity that messages can be pushed into.
+///
+/// This is used by [`Receiver::recv_nonblocking_batch_into`], which never pushes more messages
+/// than the buffer has room for, so consumers that must not allocate, like audio callbacks, can
+/// drain a channel into a buffer they set up in advance.
+///
+/// It is implemented for `Vec<T>` and `VecDeque<T>`, where only the spare capacity is used, and
+/// is easy to implement for array-backed vectors from other crates.
+///
+/// [`Receiver::recv_nonblocking_batch_into`]: struct.Receiver.html#method.recv_nonblocking_batch_into
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{unbounded, FixedBuffer};
+///
+/// // A buffer for at most four messages, stored inline.
+/// struct Frames {
+///     items: [u32; 4],
+///     len: usize,
+/// }
+///
+/// impl FixedBuffer<u32> for Frames {
+///     fn remaining(&self) -> usize {
+///         self.items.len() - self.len
+///     }
+///
+///     fn push(&mut self, item: u32) {
+///         self.items[self.len] = item;
+///         self.len += 1;
+///     }
+/// }
+///
+/// let (s, r) = unbounded();
+/// for i in 0..6 {
+///     s.send(i).unwrap();
+/// }
+///
+/// let mut frames = Frames { items: [0; 4], len: 0 };
+/// assert_eq!(r.recv_nonblocking_batch_into(&mut frames), 4);
+/// assert_eq!(frames.items, [0, 1, 2, 3]);
+/// ```
+pub trait FixedBuffer<T> {
+    /// Returns how many more items fit into the buffer.
+    fn remaining(&self) -> usize;
+
+    /// Pushes an item into the buffer.
+    ///
+    /// This is only called while [`remaining`] is nonzero.
+    ///
+    /// [`remaining`]: trait.FixedBuffer.html#tymethod.remaining
+    fn push(&mut self, item: T);
+}
+
+impl<T> FixedBuffer<T> for Vec<T> {
+    fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    fn push(&mut self, item: T) {
+        Vec::push(self, item);
+    }
+}
+
+impl<T> FixedBuffer<T> for VecDeque<T> {
+    fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    fn push(&mut self, item: T) {
+        self.push_back(item);
+    }
+}
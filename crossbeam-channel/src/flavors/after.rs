@@ -2,12 +2,17 @@
 //!
 //! Messages cannot be sent into this kind of channel; they are materialized on demand.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::AtomicBool;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crossbeam_utils::atomic::AtomicCell;
+
+use clock::{ResumeWatch, TimerClock};
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
+use flavors::Introspect;
+use ordering::Ordering;
 use select::{Operation, SelectHandle, Token};
 use utils;
 
@@ -17,19 +22,48 @@ pub type AfterToken = Option<Instant>;
 /// Channel that delivers a message after a certain amount of time.
 pub struct Channel {
     /// The instant at which the message will be delivered.
-    delivery_time: Instant,
+    ///
+    /// It only changes when the deadline is re-derived after a suspension.
+    delivery_time: AtomicCell<Instant>,
 
     /// `true` if the message has been received.
     received: AtomicBool,
+
+    /// Notices suspensions, if they affect the delivery time.
+    resume: Option<ResumeWatch>,
 }
 
 impl Channel {
     /// Creates a channel that delivers a message after a certain duration of time.
     #[inline]
     pub fn new(dur: Duration) -> Self {
+        Channel::with_clock(dur, TimerClock::default())
+    }
+
+    /// Creates a channel that delivers a message after a certain duration measured by `clock`.
+    pub fn with_clock(dur: Duration, clock: TimerClock) -> Self {
         Channel {
-            delivery_time: Instant::now() + dur,
+            delivery_time: AtomicCell::new(Instant::now() + dur),
             received: AtomicBool::new(false),
+            resume: ResumeWatch::new(clock),
+        }
+    }
+
+    /// Returns the delivery time, re-deriving it first if the system has just been resumed.
+    #[inline]
+    fn delivery_time(&self) -> Instant {
+        if let Some(resume) = &self.resume {
+            resume.update(&self.delivery_time);
+        }
+        self.delivery_time.load()
+    }
+
+    /// Returns how long to sleep while waiting for the message.
+    #[inline]
+    fn sleep_time(&self, wait: Duration) -> Duration {
+        match &self.resume {
+            Some(resume) => resume.limit(wait),
+            None => wait,
         }
     }
 
@@ -42,7 +76,8 @@ impl Channel {
             return Err(TryRecvError::Empty);
         }
 
-        if Instant::now() < self.delivery_time {
+        let delivery_time = self.delivery_time();
+        if Instant::now() < delivery_time {
             // The message was not delivered yet.
             return Err(TryRecvError::Empty);
         }
@@ -50,7 +85,7 @@ impl Channel {
         // Try receiving the message if it is still available.
         if !self.received.swap(true, Ordering::SeqCst) {
             // Success! Return delivery time as the message.
-            Ok(self.delivery_time)
+            Ok(delivery_time)
         } else {
             // The message was already received.
             Err(TryRecvError::Empty)
@@ -68,12 +103,13 @@ impl Channel {
         }
 
         // Wait until the message is received or the deadline is reached.
-        loop {
+        let delivery_time = loop {
+            let delivery_time = self.delivery_time();
             let now = Instant::now();
 
             // Check if we can receive the next message.
-            if now >= self.delivery_time {
-                break;
+            if now >= delivery_time {
+                break delivery_time;
             }
 
             // Check if the deadline has been reached.
@@ -82,16 +118,16 @@ impl Channel {
                     return Err(RecvTimeoutError::Timeout);
                 }
 
-                thread::sleep(self.delivery_time.min(d) - now);
+                thread::sleep(self.sleep_time(delivery_time.min(d) - now));
             } else {
-                thread::sleep(self.delivery_time - now);
+                thread::sleep(self.sleep_time(delivery_time - now));
             }
-        }
+        };
 
         // Try receiving the message if it is still available.
         if !self.received.swap(true, Ordering::SeqCst) {
             // Success! Return the message, which is the instant at which it was delivered.
-            Ok(self.delivery_time)
+            Ok(delivery_time)
         } else {
             // The message was already received. Block forever.
             utils::sleep_until(None);
@@ -114,7 +150,7 @@ impl Channel {
         }
 
         // If the delivery time hasn't been reached yet, the channel is empty.
-        if Instant::now() < self.delivery_time {
+        if Instant::now() < self.delivery_time() {
             return true;
         }
 
@@ -144,6 +180,21 @@ impl Channel {
     pub fn capacity(&self) -> Option<usize> {
         Some(1)
     }
+
+    /// Returns the version of the channel.
+    ///
+    /// The version is 0 before the delivery time, 1 once the message is ready, and 2 after it has
+    /// been received.
+    #[inline]
+    pub fn version(&self) -> usize {
+        if self.received.load(Ordering::SeqCst) {
+            2
+        } else if Instant::now() >= self.delivery_time() {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 impl SelectHandle for Channel {
@@ -168,7 +219,11 @@ impl SelectHandle for Channel {
         if self.received.load(Ordering::Relaxed) {
             None
         } else {
-            Some(self.delivery_time)
+            let delivery_time = self.delivery_time();
+            match &self.resume {
+                Some(resume) => Some(resume.wake_time(delivery_time)),
+                None => Some(delivery_time),
+            }
         }
     }
 
@@ -197,4 +252,28 @@ impl SelectHandle for Channel {
 
     #[inline]
     fn unwatch(&self, _oper: Operation) {}
+
+    #[inline]
+    fn never_ready(&self) -> bool {
+        self.received.load(Ordering::SeqCst)
+    }
+}
+
+impl Introspect for Channel {
+    fn flavor(&self) -> &'static str {
+        "after"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        // This kind of channel never gets disconnected.
+        false
+    }
 }
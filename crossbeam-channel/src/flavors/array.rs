@@ -17,15 +17,23 @@ use std::cell::UnsafeCell;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
-use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::atomic::{self, AtomicUsize};
+#[cfg(feature = "head-age")]
+use std::time::Duration;
 use std::time::Instant;
 
 use crossbeam_utils::{Backoff, CachePadded};
 
+use affinity::CoreSet;
+#[cfg(feature = "head-age")]
+use age::Age;
 use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use flavors::Introspect;
+use ordering::{yield_point, Ordering};
 use select::{Operation, SelectHandle, Selected, Token};
-use waker::SyncWaker;
+use stats::{Occupancy, OccupancyStats};
+use waker::{FairQueue, SyncWaker};
 
 /// A slot in a channel.
 struct Slot<T> {
@@ -56,6 +64,14 @@ impl Default for ArrayToken {
     }
 }
 
+impl ArrayToken {
+    /// Returns `true` if no slot was reserved, which means the channel is disconnected.
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        self.slot.is_null()
+    }
+}
+
 /// Bounded channel based on a preallocated array.
 pub struct Channel<T> {
     /// The head of the channel.
@@ -94,6 +110,16 @@ pub struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Occupancy statistics, if enabled.
+    occupancy: Option<Occupancy>,
+
+    /// Age of the message at the head, tracked on demand.
+    #[cfg(feature = "head-age")]
+    age: Age,
+
+    /// Queues ordering blocked senders and receivers, if fairness is enabled.
+    fair: Option<(FairQueue, FairQueue)>,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -138,10 +164,37 @@ impl<T> Channel<T> {
             tail: CachePadded::new(AtomicUsize::new(tail)),
             senders: SyncWaker::new(),
             receivers: SyncWaker::new(),
+            occupancy: None,
+            #[cfg(feature = "head-age")]
+            age: Age::new(),
+            fair: None,
             _marker: PhantomData,
         }
     }
 
+    /// Creates a bounded channel of capacity `cap` that records occupancy statistics.
+    pub fn with_occupancy(cap: usize) -> Self {
+        let mut chan = Channel::with_capacity(cap);
+        chan.occupancy = Some(Occupancy::new());
+        chan
+    }
+
+    /// Creates a bounded channel of capacity `cap` that serves senders and receivers in FIFO
+    /// order.
+    pub fn with_fairness(cap: usize) -> Self {
+        let mut chan = Channel::with_capacity(cap);
+        chan.fair = Some((FairQueue::new(), FairQueue::new()));
+        chan
+    }
+
+    /// Creates a bounded channel of capacity `cap` whose senders may send from signal handlers
+    /// with `try_send_from_signal`.
+    pub fn with_signal_safety(cap: usize) -> Self {
+        let mut chan = Channel::with_capacity(cap);
+        chan.receivers = SyncWaker::signal_safe();
+        chan
+    }
+
     /// Returns a receiver handle to the channel.
     pub fn receiver(&self) -> Receiver<T> {
         Receiver(self)
@@ -154,6 +207,15 @@ impl<T> Channel<T> {
 
     /// Attempts to reserve a slot for sending a message.
     fn start_send(&self, token: &mut Token) -> bool {
+        self.start_send_with(token, false)
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    ///
+    /// If `from_signal` is `true`, this never waits for another thread, which might be the one
+    /// interrupted by the signal, and skips age tracking, which locks and allocates.
+    #[inline]
+    fn start_send_with(&self, token: &mut Token, from_signal: bool) -> bool {
         let backoff = Backoff::new();
         let mut tail = self.tail.load(Ordering::Relaxed);
 
@@ -185,7 +247,11 @@ impl<T> Channel<T> {
                     lap.wrapping_add(self.one_lap)
                 };
 
-                // Try moving the tail.
+                yield_point();
+
+                // Try moving the tail. A slot is claimed only by a successful exchange, and a
+                // failed one leaves nothing behind, so the messages of each sending thread occupy
+                // increasing positions no matter how many retries or laps happen in between.
                 match self.tail.compare_exchange_weak(
                     tail,
                     new_tail,
@@ -193,6 +259,16 @@ impl<T> Channel<T> {
                     Ordering::Relaxed,
                 ) {
                     Ok(_) => {
+                        if let Some(occupancy) = &self.occupancy {
+                            occupancy.record(self.len(), self.cap);
+                        }
+                        #[cfg(feature = "head-age")]
+                        {
+                            if !from_signal {
+                                self.age.record(tail);
+                            }
+                        }
+
                         // Prepare the token for the follow-up call to `write`.
                         token.array.slot = slot as *const Slot<T> as *const u8;
                         token.array.stamp = tail + 1;
@@ -213,8 +289,26 @@ impl<T> Channel<T> {
                     return false;
                 }
 
+                if from_signal {
+                    // A receiver is still reading from the slot. It might be the thread the
+                    // signal interrupted, so treat the channel as full rather than wait.
+                    return false;
+                }
+
                 backoff.spin();
                 tail = self.tail.load(Ordering::Relaxed);
+            } else if stamp & self.mark_bit != 0 {
+                // The slot is held by `recv_in_place`, so the channel is full until it's released.
+                return false;
+            } else if from_signal {
+                // The stamp hasn't been updated yet, perhaps by the very thread the signal
+                // interrupted, so waiting for it could deadlock. Retry only if the tail has moved
+                // on in the meantime.
+                let t = self.tail.load(Ordering::Relaxed);
+                if t == tail {
+                    return false;
+                }
+                tail = t;
             } else {
                 // Snooze because we need to wait for the stamp to get updated.
                 backoff.snooze();
@@ -230,17 +324,24 @@ impl<T> Channel<T> {
             return Err(msg);
         }
 
-        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
-
-        // Write the message into the slot and update the stamp.
-        slot.msg.get().write(msg);
-        slot.stamp.store(token.array.stamp, Ordering::Release);
+        self.publish(token, msg);
 
         // Wake a sleeping receiver.
         self.receivers.notify();
         Ok(())
     }
 
+    /// Writes a message into the slot reserved in the token and updates the stamp.
+    #[inline]
+    unsafe fn publish(&self, token: &mut Token, msg: T) {
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+
+        slot.msg.get().write(msg);
+        yield_point();
+        slot.stamp.store(token.array.stamp, Ordering::Release);
+        yield_point();
+    }
+
     /// Attempts to reserve a slot for receiving a message.
     fn start_recv(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
@@ -267,6 +368,8 @@ impl<T> Channel<T> {
                     lap.wrapping_add(self.one_lap)
                 };
 
+                yield_point();
+
                 // Try moving the head.
                 match self.head.compare_exchange_weak(
                     head,
@@ -303,6 +406,27 @@ impl<T> Channel<T> {
                     }
                 }
 
+                backoff.spin();
+                head = self.head.load(Ordering::Relaxed);
+            } else if stamp & self.mark_bit != 0 {
+                // The slot is held by `recv_in_place`. Senders can't get past it, so if the head
+                // has caught up with it, the channel is empty.
+                if stamp & !self.mark_bit == head {
+                    atomic::fence(Ordering::SeqCst);
+                    let tail = self.tail.load(Ordering::Relaxed);
+
+                    // If the channel is disconnected...
+                    if tail & self.mark_bit != 0 {
+                        // ...then receive an error.
+                        token.array.slot = ptr::null();
+                        token.array.stamp = 0;
+                        return true;
+                    } else {
+                        // Otherwise, the receive operation is not ready.
+                        return false;
+                    }
+                }
+
                 backoff.spin();
                 head = self.head.load(Ordering::Relaxed);
             } else {
@@ -324,15 +448,51 @@ impl<T> Channel<T> {
 
         // Read the message from the slot and update the stamp.
         let msg = slot.msg.get().read();
+        yield_point();
         slot.stamp.store(token.array.stamp, Ordering::Release);
+        yield_point();
 
         // Wake a sleeping sender.
         self.senders.notify();
         Ok(msg)
     }
 
+    /// Returns a pointer to the message in the slot reserved by `token`.
+    ///
+    /// The token must come from a successful `start_recv` that didn't find the channel
+    /// disconnected.
+    pub unsafe fn slot_message(&self, token: &Token) -> *mut T {
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+        slot.msg.get()
+    }
+
+    /// Makes the slot held by `recv_in_place` available to senders again.
+    ///
+    /// The message in the slot must have been moved out or dropped.
+    pub unsafe fn release(&self, token: &Token) {
+        let slot: &Slot<T> = &*(token.array.slot as *const Slot<T>);
+
+        // The store must be `SeqCst` because senders may have parked after seeing the held stamp
+        // in `is_full`.
+        slot.stamp.store(token.array.stamp, Ordering::SeqCst);
+        yield_point();
+
+        // Wake a sleeping sender.
+        self.senders.notify();
+    }
+
     /// Attempts to send a message into the channel.
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        // If other senders are queued up, don't overtake them.
+        let _turn = match &self.fair {
+            None => None,
+            Some((senders, _)) => match senders.try_enter() {
+                Some(turn) => Some(turn),
+                None if self.is_disconnected() => return Err(TrySendError::Disconnected(msg)),
+                None => return Err(TrySendError::Full(msg)),
+            },
+        };
+
         let token = &mut Token::default();
         if self.start_send(token) {
             unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
@@ -341,8 +501,42 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Attempts to send a message without blocking, in a way that is async-signal-safe.
+    ///
+    /// No locks are taken, no memory is allocated or freed, and the current thread never waits
+    /// for another one. The price is that the message may be rejected as if the channel were
+    /// full while a receiver is still in the middle of freeing up its slot.
+    ///
+    /// Fairness is not supported, since taking a turn requires a lock.
+    pub fn try_send_from_signal(&self, msg: T) -> Result<(), TrySendError<T>> {
+        debug_assert!(self.fair.is_none());
+
+        let token = &mut Token::default();
+        if !self.start_send_with(token, true) {
+            return Err(TrySendError::Full(msg));
+        }
+        if token.array.slot.is_null() {
+            return Err(TrySendError::Disconnected(msg));
+        }
+
+        unsafe {
+            self.publish(token, msg);
+        }
+        self.receivers.notify_from_signal();
+        Ok(())
+    }
+
     /// Sends a message into the channel.
     pub fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        // Wait until all senders queued up earlier are done.
+        let _turn = match &self.fair {
+            None => None,
+            Some((senders, _)) => match senders.enter(deadline) {
+                Some(turn) => Some(turn),
+                None => return Err(SendTimeoutError::Timeout(msg)),
+            },
+        };
+
         let token = &mut Token::default();
         loop {
             // Try sending a message several times.
@@ -392,6 +586,18 @@ impl<T> Channel<T> {
 
     /// Attempts to receive a message without blocking.
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        // If other receivers are queued up, don't overtake them.
+        let _turn = match &self.fair {
+            None => None,
+            Some((_, receivers)) => match receivers.try_enter() {
+                Some(turn) => Some(turn),
+                None if self.is_disconnected() && self.is_empty() => {
+                    return Err(TryRecvError::Disconnected)
+                }
+                None => return Err(TryRecvError::Empty),
+            },
+        };
+
         let token = &mut Token::default();
 
         if self.start_recv(token) {
@@ -404,13 +610,54 @@ impl<T> Channel<T> {
     /// Receives a message from the channel.
     pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
         let token = &mut Token::default();
+        self.start_recv_blocking(token, deadline)?;
+        let res = unsafe { self.read(token) };
+        res.map_err(|_| RecvTimeoutError::Disconnected)
+    }
+
+    /// Receives a message but leaves it in its slot, which stays reserved by `token`.
+    ///
+    /// The slot's stamp gets the mark bit, which stamps otherwise never have, so that senders
+    /// reaching the slot see the channel as full instead of waiting for a receiver to finish
+    /// reading. The message must be moved out or dropped through `slot_message`, and the slot
+    /// handed back with `release`.
+    pub fn recv_in_place(
+        &self,
+        token: &mut Token,
+        deadline: Option<Instant>,
+    ) -> Result<(), RecvTimeoutError> {
+        self.start_recv_blocking(token, deadline)?;
+        if token.array.is_disconnected() {
+            return Err(RecvTimeoutError::Disconnected);
+        }
+
+        let slot = unsafe { &*(token.array.slot as *const Slot<T>) };
+        slot.stamp
+            .store(token.array.stamp | self.mark_bit, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Blocks until a slot is reserved for receiving, or the channel is found disconnected.
+    fn start_recv_blocking(
+        &self,
+        token: &mut Token,
+        deadline: Option<Instant>,
+    ) -> Result<(), RecvTimeoutError> {
+        // Wait until all receivers queued up earlier are done.
+        let _turn = match &self.fair {
+            None => None,
+            Some((_, receivers)) => match receivers.enter(deadline) {
+                Some(turn) => Some(turn),
+                None => return Err(RecvTimeoutError::Timeout),
+            },
+        };
+
         loop {
             // Try receiving a message several times.
             let backoff = Backoff::new();
             loop {
                 if self.start_recv(token) {
-                    let res = unsafe { self.read(token) };
-                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                    return Ok(());
                 }
 
                 if backoff.is_completed() {
@@ -482,6 +729,43 @@ impl<T> Channel<T> {
         Some(self.cap)
     }
 
+    /// Sets the cores whose blocked receivers are woken up first.
+    pub fn set_wake_preference(&self, preference: Option<CoreSet>) {
+        self.receivers.set_preference(preference);
+    }
+
+    /// Returns how long the message at the head of the channel has been waiting.
+    #[cfg(feature = "head-age")]
+    pub fn head_age(&self) -> Option<Duration> {
+        let head = self.head.load(Ordering::SeqCst);
+        self.age.head_age(head, self.is_empty())
+    }
+
+    /// Takes a snapshot of occupancy statistics and resets them, if they are recorded.
+    pub fn take_occupancy_stats(&self) -> Option<OccupancyStats> {
+        self.occupancy
+            .as_ref()
+            .map(|occupancy| occupancy.take(self.len()))
+    }
+
+    /// Returns the version of the receiving side.
+    ///
+    /// The version changes whenever a message is sent or the channel gets disconnected.
+    pub fn recv_version(&self) -> usize {
+        self.tail.load(Ordering::SeqCst)
+    }
+
+    /// Returns the version of the sending side.
+    ///
+    /// The version changes whenever a message is received or the channel gets disconnected.
+    pub fn send_version(&self) -> usize {
+        let tail = self.tail.load(Ordering::SeqCst);
+        let head = self.head.load(Ordering::SeqCst);
+
+        // The mark bit in the head is always zero, so borrow it from the tail.
+        head | (tail & self.mark_bit)
+    }
+
     /// Disconnects the channel and wakes up all blocked senders and receivers.
     ///
     /// Returns `true` if this call disconnected the channel.
@@ -523,7 +807,13 @@ impl<T> Channel<T> {
         //
         // Note: If the tail changes just before we load the head, that means there was a moment
         // when the channel was not full, so it is safe to just return `false`.
-        head.wrapping_add(self.one_lap) == tail & !self.mark_bit
+        if head.wrapping_add(self.one_lap) == tail & !self.mark_bit {
+            return true;
+        }
+
+        // Is the slot at the tail held by `recv_in_place`?
+        let slot = unsafe { &*self.buffer.add(tail & (self.mark_bit - 1)) };
+        slot.stamp.load(Ordering::SeqCst) & self.mark_bit != 0
     }
 }
 
@@ -630,3 +920,21 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
         self.0.senders.unwatch(oper);
     }
 }
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "array"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
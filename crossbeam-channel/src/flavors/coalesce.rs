@@ -0,0 +1,365 @@
+//! Unbounded channel that can merge new messages into queued ones.
+//!
+//! Messages are kept in a queue protected by a lock. Every message pushed into the queue gets a
+//! position, which counts all messages ever pushed. As long as a message hasn't been received, a
+//! sender can find it by its position and merge a new message into it instead of pushing one.
+
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicUsize;
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, TryRecvError};
+use flavors::Introspect;
+use ordering::Ordering;
+use select::{Operation, SelectHandle, Selected, Token};
+use utils::Spinlock;
+use waker::Waker;
+
+/// A pointer to a boxed message, or zero if the channel is disconnected.
+pub type CoalesceToken = usize;
+
+/// Inner representation of a coalescing channel.
+struct Inner<T> {
+    /// Queued messages.
+    ///
+    /// A slot is empty if merging a message into it panicked. Empty slots are skipped by receivers.
+    queue: VecDeque<Option<T>>,
+
+    /// The position of the first message in the queue.
+    head: u64,
+
+    /// Receivers waiting for a message.
+    receivers: Waker,
+
+    /// Equals `true` when the channel is disconnected.
+    is_disconnected: bool,
+}
+
+impl<T> Inner<T> {
+    /// Pops the first message from the queue, skipping empty slots.
+    fn pop(&mut self) -> Option<T> {
+        while let Some(slot) = self.queue.pop_front() {
+            self.head += 1;
+            if slot.is_some() {
+                return slot;
+            }
+        }
+        None
+    }
+}
+
+/// The queue of a coalescing channel, locked for sending.
+pub struct Queue<'a, T: 'a> {
+    inner: &'a mut Inner<T>,
+    pushed: bool,
+}
+
+impl<'a, T> Queue<'a, T> {
+    /// Returns the position of the first queued message.
+    ///
+    /// Messages at lower positions have already been received.
+    pub fn head(&self) -> u64 {
+        self.inner.head
+    }
+
+    /// Returns the number of queued messages.
+    pub fn len(&self) -> usize {
+        self.inner.queue.len()
+    }
+
+    /// Pushes a message and returns its position.
+    pub fn push(&mut self, msg: T) -> u64 {
+        self.inner.queue.push_back(Some(msg));
+        self.pushed = true;
+        self.inner.head + self.inner.queue.len() as u64 - 1
+    }
+
+    /// Merges a message into the one at `pos`, if it's still in the queue.
+    ///
+    /// If the message has already been received, the new message is returned back.
+    pub fn merge<F>(&mut self, pos: u64, msg: T, merge: F) -> Result<(), T>
+    where
+        F: FnOnce(T, T) -> T,
+    {
+        if pos < self.inner.head {
+            return Err(msg);
+        }
+
+        match self.inner.queue.get_mut((pos - self.inner.head) as usize) {
+            Some(slot) => match slot.take() {
+                Some(old) => {
+                    *slot = Some(merge(old, msg));
+                    Ok(())
+                }
+                None => Err(msg),
+            },
+            None => Err(msg),
+        }
+    }
+}
+
+/// Unbounded channel that can merge new messages into queued ones.
+pub struct Channel<T> {
+    /// Inner representation of the channel.
+    inner: Spinlock<Inner<T>>,
+
+    /// Incremented whenever a message is pushed or the channel gets disconnected.
+    version: AtomicUsize,
+}
+
+impl<T> Channel<T> {
+    /// Creates a new coalescing channel.
+    pub fn new() -> Self {
+        Channel {
+            inner: Spinlock::new(Inner {
+                queue: VecDeque::new(),
+                head: 0,
+                receivers: Waker::new(),
+                is_disconnected: false,
+            }),
+            version: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Attempts to reserve a message for receiving.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let mut inner = self.inner.lock();
+
+        if let Some(msg) = inner.pop() {
+            token.coalesce = Box::into_raw(Box::new(msg)) as usize;
+            true
+        } else if inner.is_disconnected {
+            token.coalesce = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reads a message reserved by `start_recv`.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        // If there is no message, the channel is disconnected.
+        if token.coalesce == 0 {
+            return Err(());
+        }
+
+        let msg = Box::from_raw(token.coalesce as *mut T);
+        token.coalesce = 0;
+        Ok(*msg)
+    }
+
+    /// Locks the queue and lets `f` push the message or merge it into a queued one.
+    ///
+    /// If the channel is disconnected, the message is returned back in the error. Otherwise, a
+    /// blocked receiver is woken up if `f` pushed a message.
+    pub fn send_with<F>(&self, msg: T, f: F) -> Result<(), T>
+    where
+        F: FnOnce(&mut Queue<T>, T),
+    {
+        let mut inner = self.inner.lock();
+
+        if inner.is_disconnected {
+            return Err(msg);
+        }
+
+        let pushed = {
+            let mut queue = Queue {
+                inner: &mut inner,
+                pushed: false,
+            };
+            f(&mut queue, msg);
+            queue.pushed
+        };
+
+        if pushed {
+            self.version.fetch_add(1, Ordering::SeqCst);
+            inner.receivers.try_select();
+            inner.receivers.notify();
+        }
+        Ok(())
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut inner = self.inner.lock();
+
+        if let Some(msg) = inner.pop() {
+            Ok(msg)
+        } else if inner.is_disconnected {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                match self.try_recv() {
+                    Ok(msg) => return Ok(msg),
+                    Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                    Err(TryRecvError::Empty) => {}
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            // Prepare for blocking until a sender wakes us up.
+            Context::with(|cx| {
+                let oper = Operation::hook(token);
+                {
+                    let mut inner = self.inner.lock();
+                    inner.receivers.register(oper, cx);
+
+                    // Has the channel become ready just now?
+                    if !inner.queue.is_empty() || inner.is_disconnected {
+                        let _ = cx.try_select(Selected::Aborted);
+                    }
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+
+                match sel {
+                    Selected::Waiting => unreachable!(),
+                    Selected::Aborted | Selected::Disconnected => {
+                        self.inner.lock().receivers.unregister(oper).unwrap();
+                        // If the channel was disconnected, we still have to check for remaining
+                        // messages.
+                    }
+                    Selected::Operation(_) => {}
+                }
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.inner.lock().queue.len()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the version of the receiving side.
+    ///
+    /// The version changes whenever a message is pushed or the channel gets disconnected.
+    pub fn recv_version(&self) -> usize {
+        self.version.load(Ordering::SeqCst)
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut inner = self.inner.lock();
+
+        if !inner.is_disconnected {
+            inner.is_disconnected = true;
+            inner.receivers.disconnect();
+            self.version.fetch_add(1, Ordering::SeqCst);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().queue.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        false
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        let mut inner = self.0.inner.lock();
+        inner.receivers.register(oper, cx);
+        !inner.queue.is_empty() || inner.is_disconnected
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.inner.lock().receivers.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        let inner = self.0.inner.lock();
+        !inner.queue.is_empty() || inner.is_disconnected
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        let mut inner = self.0.inner.lock();
+        inner.receivers.watch(oper, cx);
+        !inner.queue.is_empty() || inner.is_disconnected
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.inner.lock().receivers.unwatch(oper);
+    }
+}
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "coalesce"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
@@ -0,0 +1,246 @@
+//! Unbounded channel with several lanes of decreasing priority.
+//!
+//! Each lane is an unbounded channel implemented as a linked list. Receive operations always take
+//! a message from the first non-empty lane, so messages in earlier lanes preempt messages in later
+//! ones. Messages within a lane are received in the order they were sent.
+
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError};
+use flavors::{list, Introspect};
+use select::{Operation, SelectHandle, Selected, Token};
+
+/// The token type for the lanes flavor.
+///
+/// Holds the index of the lane a message was reserved in. The slot itself is stored in the list
+/// part of the token.
+pub type LanesToken = usize;
+
+/// Unbounded channel with several lanes of decreasing priority.
+pub struct Channel<T> {
+    /// The lanes, in order of decreasing priority.
+    lanes: Vec<list::Channel<T>>,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel with the given number of lanes.
+    pub fn new(lanes: usize) -> Self {
+        assert!(lanes > 0, "a channel needs at least one lane");
+        Channel {
+            lanes: (0..lanes).map(|_| list::Channel::new()).collect(),
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Attempts to reserve a message for receiving, trying lanes in order of priority.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let mut ready = true;
+
+        for (i, lane) in self.lanes.iter().enumerate() {
+            if lane.receiver().try_select(token) {
+                if !token.list.is_disconnected() {
+                    token.lanes = i;
+                    return true;
+                }
+            } else {
+                ready = false;
+            }
+        }
+
+        // If every lane is disconnected and empty, the list part of the token is empty and the
+        // operation receives an error.
+        ready
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.list.is_disconnected() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        self.lanes[token.lanes].read(token)
+    }
+
+    /// Sends a message into the lane.
+    pub fn send(&self, lane: usize, msg: T) -> Result<(), SendTimeoutError<T>> {
+        self.lanes[lane].send(msg, None)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    unsafe {
+                        return self.read(token).map_err(|_| RecvTimeoutError::Disconnected);
+                    }
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            // Prepare for blocking until a sender wakes us up.
+            Context::with(|cx| {
+                let oper = Operation::hook(token);
+                let receiver = self.receiver();
+
+                // Has the channel become ready just now?
+                if receiver.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+                debug_assert!(sel != Selected::Waiting);
+
+                // The lane that selected the operation has already removed it, but the others
+                // haven't.
+                receiver.unregister(oper);
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.lanes.iter().map(|lane| lane.len()).sum()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the version of the receiving side.
+    ///
+    /// The version changes whenever a message is sent or the channel gets disconnected.
+    pub fn recv_version(&self) -> usize {
+        self.lanes
+            .iter()
+            .fold(0, |v, lane| v.wrapping_add(lane.recv_version()))
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut disconnected = false;
+        for lane in &self.lanes {
+            if lane.disconnect() {
+                disconnected = true;
+            }
+        }
+        disconnected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.lanes[0].is_disconnected()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lanes.iter().all(|lane| lane.is_empty())
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        false
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        for lane in &self.0.lanes {
+            lane.receiver().register(oper, cx);
+        }
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        for lane in &self.0.lanes {
+            lane.receiver().unregister(oper);
+        }
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        for lane in &self.0.lanes {
+            lane.receiver().watch(oper, cx);
+        }
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        for lane in &self.0.lanes {
+            lane.receiver().unwatch(oper);
+        }
+    }
+}
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "lanes"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
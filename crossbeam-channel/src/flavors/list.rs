@@ -4,13 +4,20 @@ use std::cell::UnsafeCell;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
 use std::ptr;
-use std::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::atomic::{self, AtomicPtr, AtomicUsize};
+#[cfg(feature = "head-age")]
+use std::time::Duration;
 use std::time::Instant;
 
 use crossbeam_utils::{Backoff, CachePadded};
 
+use affinity::CoreSet;
+#[cfg(feature = "head-age")]
+use age::Age;
 use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use flavors::Introspect;
+use ordering::{yield_point, Ordering};
 use select::{Operation, SelectHandle, Selected, Token};
 use waker::SyncWaker;
 
@@ -138,6 +145,14 @@ impl Default for ListToken {
     }
 }
 
+impl ListToken {
+    /// Returns `true` if no slot was reserved, which means the channel is disconnected.
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        self.block.is_null()
+    }
+}
+
 /// Unbounded channel implemented as a linked list.
 ///
 /// Each message sent into the channel is assigned a sequence number, i.e. an index. Indices are
@@ -155,6 +170,17 @@ pub struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Age of the message at the head, tracked on demand.
+    #[cfg(feature = "head-age")]
+    age: Age,
+
+    /// The number of peeks in progress.
+    ///
+    /// Receivers that reserve a slot while a peek is in progress wait for it to finish before
+    /// reading the message.
+    #[cfg(feature = "debug-peek")]
+    peeks: AtomicUsize,
+
     /// Indicates that dropping a `Channel<T>` may drop messages of type `T`.
     _marker: PhantomData<T>,
 }
@@ -172,6 +198,10 @@ impl<T> Channel<T> {
                 index: AtomicUsize::new(0),
             }),
             receivers: SyncWaker::new(),
+            #[cfg(feature = "head-age")]
+            age: Age::new(),
+            #[cfg(feature = "debug-peek")]
+            peeks: AtomicUsize::new(0),
             _marker: PhantomData,
         }
     }
@@ -239,6 +269,7 @@ impl<T> Channel<T> {
             }
 
             let new_tail = tail + (1 << SHIFT);
+            yield_point();
 
             // Try advancing the tail forward.
             match self.tail.index.compare_exchange_weak(
@@ -251,11 +282,15 @@ impl<T> Channel<T> {
                     // If we've reached the end of the block, install the next one.
                     if offset + 1 == BLOCK_CAP {
                         let next_block = Box::into_raw(next_block.unwrap());
+                        yield_point();
                         self.tail.block.store(next_block, Ordering::Release);
                         self.tail.index.fetch_add(1 << SHIFT, Ordering::Release);
                         (*block).next.store(next_block, Ordering::Release);
                     }
 
+                    #[cfg(feature = "head-age")]
+                    self.age.record(tail >> SHIFT);
+
                     token.list.block = block as *const u8;
                     token.list.offset = offset;
                     return true;
@@ -281,7 +316,9 @@ impl<T> Channel<T> {
         let offset = token.list.offset;
         let slot = (*block).slots.get_unchecked(offset);
         slot.msg.get().write(ManuallyDrop::new(msg));
+        yield_point();
         slot.state.fetch_or(WRITE, Ordering::Release);
+        yield_point();
 
         // Wake a sleeping receiver.
         self.receivers.notify();
@@ -340,6 +377,8 @@ impl<T> Channel<T> {
                 continue;
             }
 
+            yield_point();
+
             // Try moving the head index forward.
             match self.head.index.compare_exchange_weak(
                 head,
@@ -357,9 +396,11 @@ impl<T> Channel<T> {
                         }
 
                         self.head.block.store(next, Ordering::Release);
+                        yield_point();
                         self.head.index.store(next_index, Ordering::Release);
                     }
 
+                    self.wait_for_peeks();
                     token.list.block = block as *const u8;
                     token.list.offset = offset;
                     return true;
@@ -373,6 +414,79 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Waits until no peek is in progress, after a slot for receiving has been reserved.
+    ///
+    /// A peek reads the head index after announcing itself, so any slot reserved without waiting
+    /// lies before the first message the peek clones.
+    #[inline]
+    fn wait_for_peeks(&self) {
+        #[cfg(feature = "debug-peek")]
+        {
+            let backoff = Backoff::new();
+            while self.peeks.load(Ordering::SeqCst) != 0 {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Clones up to `limit` messages from the head of the channel without receiving them.
+    #[cfg(feature = "debug-peek")]
+    pub fn peek(&self, limit: usize) -> Vec<T>
+    where
+        T: Clone,
+    {
+        /// Ends the peek even if cloning a message panics.
+        struct Claim<'a>(&'a AtomicUsize);
+
+        impl<'a> Drop for Claim<'a> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        self.peeks.fetch_add(1, Ordering::SeqCst);
+        let _claim = Claim(&self.peeks);
+
+        // Load a head index and block that belong together. The tail is loaded before the block
+        // so that the first block is installed whenever the channel isn't empty.
+        let backoff = Backoff::new();
+        let (mut head, tail, mut block) = loop {
+            let head = self.head.index.load(Ordering::SeqCst);
+            let tail = self.tail.index.load(Ordering::SeqCst);
+            let block = self.head.block.load(Ordering::Acquire);
+
+            if (head >> SHIFT) % LAP != BLOCK_CAP && self.head.index.load(Ordering::SeqCst) == head
+            {
+                break (head, tail, block);
+            }
+            backoff.snooze();
+        };
+
+        // Erase the lower bits.
+        head &= !((1 << SHIFT) - 1);
+        let tail = tail & !((1 << SHIFT) - 1);
+
+        let mut msgs = Vec::new();
+        unsafe {
+            while head != tail && msgs.len() < limit {
+                let offset = (head >> SHIFT) % LAP;
+
+                if offset < BLOCK_CAP {
+                    // Messages from the head on aren't read until the peek ends, so the slot
+                    // stays initialized and the block stays allocated.
+                    let slot = (*block).slots.get_unchecked(offset);
+                    slot.wait_write();
+                    msgs.push((**slot.msg.get()).clone());
+                } else {
+                    block = (*block).wait_next();
+                }
+
+                head = head.wrapping_add(1 << SHIFT);
+            }
+        }
+        msgs
+    }
+
     /// Reads a message from the channel.
     pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
         if token.list.block.is_null() {
@@ -387,6 +501,7 @@ impl<T> Channel<T> {
         slot.wait_write();
         let m = slot.msg.get().read();
         let msg = ManuallyDrop::into_inner(m);
+        yield_point();
 
         // Destroy the block if we've reached the end, or if another thread wanted to destroy but
         // couldn't because we were busy reading from the slot.
@@ -399,6 +514,115 @@ impl<T> Channel<T> {
         Ok(msg)
     }
 
+    /// Attempts to reserve a slot for receiving a message, assuming there are no other receivers.
+    ///
+    /// Since no other thread moves the head, it is advanced with a plain store instead of a
+    /// compare-and-swap loop.
+    unsafe fn start_recv_unique(&self, token: &mut Token) -> bool {
+        let head = self.head.index.load(Ordering::Acquire);
+        let mut block = self.head.block.load(Ordering::Acquire);
+
+        // Calculate the offset of the index into the block. The head never rests on a block end
+        // because the only receiver moves to the next block right after reading the last slot.
+        let offset = (head >> SHIFT) % LAP;
+        debug_assert!(offset < BLOCK_CAP);
+
+        let mut new_head = head + (1 << SHIFT);
+
+        if new_head & MARK_BIT == 0 {
+            atomic::fence(Ordering::SeqCst);
+            let tail = self.tail.index.load(Ordering::Relaxed);
+
+            // If the tail equals the head, that means the channel is empty.
+            if head >> SHIFT == tail >> SHIFT {
+                // If the channel is disconnected...
+                if tail & MARK_BIT != 0 {
+                    // ...then receive an error.
+                    token.list.block = ptr::null();
+                    return true;
+                } else {
+                    // Otherwise, the receive operation is not ready.
+                    return false;
+                }
+            }
+
+            // If head and tail are not in the same block, set `MARK_BIT` in head.
+            if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                new_head |= MARK_BIT;
+            }
+        }
+
+        // The block can be null here only if the first message is being sent into the channel.
+        // In that case, just wait until it gets initialized.
+        if block.is_null() {
+            let backoff = Backoff::new();
+            while block.is_null() {
+                backoff.snooze();
+                block = self.head.block.load(Ordering::Acquire);
+            }
+        }
+
+        // If we've reached the end of the block, move to the next one.
+        if offset + 1 == BLOCK_CAP {
+            let next = (*block).wait_next();
+            let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
+            if !(*next).next.load(Ordering::Relaxed).is_null() {
+                next_index |= MARK_BIT;
+            }
+
+            self.head.block.store(next, Ordering::Release);
+            self.head.index.store(next_index, Ordering::SeqCst);
+        } else {
+            self.head.index.store(new_head, Ordering::SeqCst);
+        }
+
+        self.wait_for_peeks();
+        token.list.block = block as *const u8;
+        token.list.offset = offset;
+        true
+    }
+
+    /// Reads a message from the channel, assuming there are no other receivers.
+    ///
+    /// Slots are not marked as read, and blocks are destroyed as soon as their last slot is read.
+    unsafe fn read_unique(&self, token: &mut Token) -> Result<T, ()> {
+        if token.list.block.is_null() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        // Read the message.
+        let block = token.list.block as *mut Block<T>;
+        let offset = token.list.offset;
+        let slot = (*block).slots.get_unchecked(offset);
+        slot.wait_write();
+        let m = slot.msg.get().read();
+        let msg = ManuallyDrop::into_inner(m);
+
+        // All other slots in the block have been read, so destroy it if we've reached the end.
+        if offset + 1 == BLOCK_CAP {
+            drop(Box::from_raw(block));
+        }
+
+        Ok(msg)
+    }
+
+    /// Prepares the channel for being used by receivers other than the unique one.
+    ///
+    /// Marks the slots in the head block that were read by the unique receiver as read.
+    pub unsafe fn end_unique(&self) {
+        let head = self.head.index.load(Ordering::Acquire);
+        let block = self.head.block.load(Ordering::Acquire);
+
+        if !block.is_null() {
+            let offset = (head >> SHIFT) % LAP;
+            for i in 0..offset {
+                let slot = (*block).slots.get_unchecked(i);
+                slot.state.fetch_or(READ, Ordering::Release);
+            }
+        }
+    }
+
     /// Attempts to send a message into the channel.
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
         self.send(msg, None).map_err(|err| match err {
@@ -419,10 +643,26 @@ impl<T> Channel<T> {
 
     /// Attempts to receive a message without blocking.
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        unsafe { self.try_recv_with(false) }
+    }
+
+    /// Attempts to receive a message without blocking, assuming there are no other receivers.
+    ///
+    /// The caller must guarantee that no other thread receives from the channel until
+    /// `end_unique` is called.
+    pub unsafe fn try_recv_unique(&self) -> Result<T, TryRecvError> {
+        self.try_recv_with(true)
+    }
+
+    /// Attempts to receive a message without blocking, using the unique receiver protocol if
+    /// `unique` is `true`.
+    #[inline]
+    unsafe fn try_recv_with(&self, unique: bool) -> Result<T, TryRecvError> {
         let token = &mut Token::default();
 
-        if self.start_recv(token) {
-            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        if self.start_recv_with(token, unique) {
+            self.read_with(token, unique)
+                .map_err(|_| TryRecvError::Disconnected)
         } else {
             Err(TryRecvError::Empty)
         }
@@ -430,15 +670,34 @@ impl<T> Channel<T> {
 
     /// Receives a message from the channel.
     pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        unsafe { self.recv_with(deadline, false) }
+    }
+
+    /// Receives a message from the channel, assuming there are no other receivers.
+    ///
+    /// The caller must guarantee that no other thread receives from the channel until
+    /// `end_unique` is called.
+    pub unsafe fn recv_unique(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        self.recv_with(deadline, true)
+    }
+
+    /// Receives a message from the channel, using the unique receiver protocol if `unique` is
+    /// `true`.
+    #[inline]
+    unsafe fn recv_with(
+        &self,
+        deadline: Option<Instant>,
+        unique: bool,
+    ) -> Result<T, RecvTimeoutError> {
         let token = &mut Token::default();
         loop {
             // Try receiving a message several times.
             let backoff = Backoff::new();
             loop {
-                if self.start_recv(token) {
-                    unsafe {
-                        return self.read(token).map_err(|_| RecvTimeoutError::Disconnected);
-                    }
+                if self.start_recv_with(token, unique) {
+                    return self
+                        .read_with(token, unique)
+                        .map_err(|_| RecvTimeoutError::Disconnected);
                 }
 
                 if backoff.is_completed() {
@@ -480,6 +739,26 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Attempts to reserve a slot for receiving a message with the chosen protocol.
+    #[inline]
+    unsafe fn start_recv_with(&self, token: &mut Token, unique: bool) -> bool {
+        if unique {
+            self.start_recv_unique(token)
+        } else {
+            self.start_recv(token)
+        }
+    }
+
+    /// Reads a message from the channel with the chosen protocol.
+    #[inline]
+    unsafe fn read_with(&self, token: &mut Token, unique: bool) -> Result<T, ()> {
+        if unique {
+            self.read_unique(token)
+        } else {
+            self.read(token)
+        }
+    }
+
     /// Returns the current number of messages inside the channel.
     pub fn len(&self) -> usize {
         loop {
@@ -517,11 +796,42 @@ impl<T> Channel<T> {
         }
     }
 
+    /// Sets the cores whose blocked receivers are woken up first.
+    pub fn set_wake_preference(&self, preference: Option<CoreSet>) {
+        self.receivers.set_preference(preference);
+    }
+
+    /// Returns how long the message at the head of the channel has been waiting.
+    #[cfg(feature = "head-age")]
+    pub fn head_age(&self) -> Option<Duration> {
+        let head = self.head.index.load(Ordering::SeqCst);
+        self.age.head_age(head >> SHIFT, self.is_empty())
+    }
+
     /// Returns the capacity of the channel.
     pub fn capacity(&self) -> Option<usize> {
         None
     }
 
+    /// Returns the version of the receiving side.
+    ///
+    /// The version changes whenever a message is sent or the channel gets disconnected.
+    pub fn recv_version(&self) -> usize {
+        self.tail.index.load(Ordering::SeqCst)
+    }
+
+    /// Returns the version of the sending side.
+    ///
+    /// The version changes whenever a message is received or the channel gets disconnected.
+    pub fn send_version(&self) -> usize {
+        let tail = self.tail.index.load(Ordering::SeqCst);
+        let head = self.head.index.load(Ordering::SeqCst);
+
+        // The mark bit in the head has a different meaning, so replace it with the one from the
+        // tail.
+        (head & !MARK_BIT) | (tail & MARK_BIT)
+    }
+
     /// Disconnects the channel and wakes up all blocked receivers.
     ///
     /// Returns `true` if this call disconnected the channel.
@@ -662,3 +972,21 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
 
     fn unwatch(&self, _oper: Operation) {}
 }
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "list"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
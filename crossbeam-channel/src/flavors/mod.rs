@@ -1,17 +1,51 @@
 //! Channel flavors.
 //!
-//! There are six flavors:
+//! There are ten flavors:
 //!
 //! 1. `after` - Channel that delivers a message after a certain amount of time.
 //! 2. `array` - Bounded channel based on a preallocated array.
-//! 3. `list` - Unbounded channel implemented as a linked list.
-//! 4. `never` - Channel that never delivers messages.
-//! 5. `tick` - Channel that delivers messages periodically.
-//! 6. `zero` - Zero-capacity channel.
+//! 3. `coalesce` - Unbounded channel that can merge new messages into queued ones.
+//! 4. `lanes` - Unbounded channel with several lanes of decreasing priority.
+//! 5. `list` - Unbounded channel implemented as a linked list.
+//! 6. `never` - Channel that never delivers messages.
+//! 7. `sharded` - Unbounded channel split into shards with separate tails.
+//! 8. `tick` - Channel that delivers messages periodically.
+//! 9. `timer` - Channel that delivers a message at a certain instant, allocated from a slab.
+//! 10. `zero` - Zero-capacity channel.
+//!
+//! The `array` and `zero` flavors can be compiled out with the `flavor-array` and `flavor-zero`
+//! features, and `after`, `tick`, and `timer` with the `timers` feature.
 
+#[cfg(feature = "timers")]
 pub mod after;
+#[cfg(feature = "flavor-array")]
 pub mod array;
+pub mod coalesce;
+pub mod lanes;
 pub mod list;
 pub mod never;
+pub mod sharded;
+#[cfg(feature = "timers")]
 pub mod tick;
+#[cfg(feature = "timers")]
+pub mod timer;
+#[cfg(feature = "flavor-zero")]
 pub mod zero;
+
+/// Introspection into the state of a channel, shown in the `Debug` output of its endpoints.
+///
+/// Every flavor implements this trait. The returned values are only snapshots and may be out of
+/// date by the time they are shown.
+pub trait Introspect {
+    /// Returns the name of the flavor.
+    fn flavor(&self) -> &'static str;
+
+    /// Returns the current number of messages inside the channel.
+    fn len(&self) -> usize;
+
+    /// Returns the capacity of the channel.
+    fn capacity(&self) -> Option<usize>;
+
+    /// Returns `true` if the channel is disconnected.
+    fn is_disconnected(&self) -> bool;
+}
@@ -7,6 +7,7 @@ use std::time::Instant;
 
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
+use flavors::Introspect;
 use select::{Operation, SelectHandle, Token};
 use utils;
 
@@ -18,6 +19,11 @@ pub struct Channel<T> {
     _marker: PhantomData<T>,
 }
 
+/// A channel that never delivers messages, usable as a placeholder operation in select.
+pub static PLACEHOLDER: Channel<()> = Channel {
+    _marker: PhantomData,
+};
+
 impl<T> Channel<T> {
     /// Creates a channel that never delivers messages.
     #[inline]
@@ -69,6 +75,12 @@ impl<T> Channel<T> {
     pub fn capacity(&self) -> Option<usize> {
         Some(0)
     }
+
+    /// Returns the version of the channel, which never changes.
+    #[inline]
+    pub fn version(&self) -> usize {
+        0
+    }
 }
 
 impl<T> SelectHandle for Channel<T> {
@@ -107,4 +119,28 @@ impl<T> SelectHandle for Channel<T> {
 
     #[inline]
     fn unwatch(&self, _oper: Operation) {}
+
+    #[inline]
+    fn never_ready(&self) -> bool {
+        true
+    }
+}
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "never"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        // This kind of channel never gets disconnected.
+        false
+    }
 }
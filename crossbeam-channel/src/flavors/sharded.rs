@@ -0,0 +1,262 @@
+//! Unbounded channel split into shards with separate tails.
+//!
+//! Each shard is an unbounded channel implemented as a linked list, so senders writing into
+//! different shards never contend on the same tail. Receive operations merge the shards lazily,
+//! starting from the shard after the one that was received from last, so that no shard is starved.
+//! Messages within a shard are received in the order they were sent, but there is no order among
+//! messages in different shards.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use context::Context;
+use err::{RecvTimeoutError, SendTimeoutError, TryRecvError};
+use flavors::{list, Introspect};
+use select::{Operation, SelectHandle, Selected, Token};
+
+/// The token type for the sharded flavor.
+///
+/// Holds the index of the shard a message was reserved in. The slot itself is stored in the list
+/// part of the token.
+pub type ShardedToken = usize;
+
+/// Unbounded channel split into shards with separate tails.
+pub struct Channel<T> {
+    /// The shards.
+    shards: Vec<list::Channel<T>>,
+
+    /// The shard to start the next receive operation at.
+    next: AtomicUsize,
+}
+
+impl<T> Channel<T> {
+    /// Creates a channel with the given number of shards.
+    pub fn new(shards: usize) -> Self {
+        assert!(shards > 0, "a channel needs at least one shard");
+        Channel {
+            shards: (0..shards).map(|_| list::Channel::new()).collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of shards.
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub fn receiver(&self) -> Receiver<T> {
+        Receiver(self)
+    }
+
+    /// Attempts to reserve a message for receiving, trying every shard once.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let n = self.shards.len();
+        let start = self.next.load(Ordering::Relaxed);
+        let mut ready = true;
+
+        for j in 0..n {
+            let i = (start + j) % n;
+            if self.shards[i].receiver().try_select(token) {
+                if !token.list.is_disconnected() {
+                    token.sharded = i;
+                    self.next.store((i + 1) % n, Ordering::Relaxed);
+                    return true;
+                }
+            } else {
+                ready = false;
+            }
+        }
+
+        // If every shard is disconnected and empty, the list part of the token is empty and the
+        // operation receives an error.
+        ready
+    }
+
+    /// Reads a message from the channel.
+    pub unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if token.list.is_disconnected() {
+            // The channel is disconnected.
+            return Err(());
+        }
+
+        self.shards[token.sharded].read(token)
+    }
+
+    /// Sends a message into the shard.
+    pub fn send(&self, shard: usize, msg: T) -> Result<(), SendTimeoutError<T>> {
+        self.shards[shard].send(msg, None)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    unsafe {
+                        return self.read(token).map_err(|_| RecvTimeoutError::Disconnected);
+                    }
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            // Prepare for blocking until a sender wakes us up.
+            Context::with(|cx| {
+                let oper = Operation::hook(token);
+                let receiver = self.receiver();
+
+                // Has the channel become ready just now?
+                if receiver.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                let sel = cx.wait_until(deadline);
+                debug_assert!(sel != Selected::Waiting);
+
+                // The shard that selected the operation has already removed it, but the others
+                // haven't.
+                receiver.unregister(oper);
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.len()).sum()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the version of the receiving side.
+    ///
+    /// The version changes whenever a message is sent or the channel gets disconnected.
+    pub fn recv_version(&self) -> usize {
+        self.shards
+            .iter()
+            .fold(0, |v, shard| v.wrapping_add(shard.recv_version()))
+    }
+
+    /// Disconnects the channel and wakes up all blocked receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub fn disconnect(&self) -> bool {
+        let mut disconnected = false;
+        for shard in &self.shards {
+            if shard.disconnect() {
+                disconnected = true;
+            }
+        }
+        disconnected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.shards[0].is_disconnected()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.is_empty())
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        false
+    }
+}
+
+/// Receiver handle to a channel.
+pub struct Receiver<'a, T: 'a>(&'a Channel<T>);
+
+impl<'a, T> SelectHandle for Receiver<'a, T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        for shard in &self.0.shards {
+            shard.receiver().register(oper, cx);
+        }
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        for shard in &self.0.shards {
+            shard.receiver().unregister(oper);
+        }
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        for shard in &self.0.shards {
+            shard.receiver().watch(oper, cx);
+        }
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        for shard in &self.0.shards {
+            shard.receiver().unwatch(oper);
+        }
+    }
+}
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "sharded"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
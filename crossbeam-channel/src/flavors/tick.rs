@@ -7,12 +7,35 @@ use std::time::{Duration, Instant};
 
 use crossbeam_utils::atomic::AtomicCell;
 
+use clock::{ResumeWatch, TimerClock};
 use context::Context;
 use err::{RecvTimeoutError, TryRecvError};
+use flavors::Introspect;
 use select::{Operation, SelectHandle, Token};
 
 /// Result of a receive operation.
-pub type TickToken = Option<Instant>;
+pub type TickToken = Option<Ticks>;
+
+/// A message received from a counting tick channel.
+///
+/// Received from a receiver created by [`tick_counted`].
+///
+/// [`tick_counted`]: fn.tick_counted.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ticks {
+    /// The instant at which the message was sent.
+    ///
+    /// This is the same instant a [`tick`] channel would have delivered.
+    ///
+    /// [`tick`]: fn.tick.html
+    pub instant: Instant,
+
+    /// The number of whole periods that elapsed since the previous message was received.
+    ///
+    /// For the first message, periods are counted from the creation of the channel. The count is
+    /// 1 when messages are received on time and grows when the receiver falls behind.
+    pub count: u64,
+}
 
 /// Channel that delivers messages periodically.
 pub struct Channel {
@@ -21,24 +44,93 @@ pub struct Channel {
 
     /// The time interval in which messages get delivered.
     duration: Duration,
+
+    /// The instant at which the channel was created.
+    start: Instant,
+
+    /// Whether messages carry the number of elapsed periods.
+    counted: bool,
+
+    /// Notices suspensions, if they affect the delivery time.
+    resume: Option<ResumeWatch>,
 }
 
 impl Channel {
     /// Creates a channel that delivers messages periodically.
     #[inline]
     pub fn new(dur: Duration) -> Self {
+        Channel::build(dur, false, TimerClock::default())
+    }
+
+    /// Creates a channel whose messages carry the number of elapsed periods.
+    #[inline]
+    pub fn counted(dur: Duration) -> Self {
+        Channel::build(dur, true, TimerClock::default())
+    }
+
+    /// Creates a channel that delivers messages periodically, as measured by `clock`.
+    #[inline]
+    pub fn with_clock(dur: Duration, clock: TimerClock) -> Self {
+        Channel::build(dur, false, clock)
+    }
+
+    /// Creates a channel that delivers messages periodically.
+    fn build(dur: Duration, counted: bool, clock: TimerClock) -> Self {
+        let now = Instant::now();
         Channel {
-            delivery_time: AtomicCell::new(Instant::now() + dur),
+            delivery_time: AtomicCell::new(now + dur),
             duration: dur,
+            start: now,
+            counted,
+            resume: ResumeWatch::new(clock),
+        }
+    }
+
+    /// Returns the next delivery time, re-deriving it first if the system has just been resumed.
+    #[inline]
+    fn delivery_time(&self) -> Instant {
+        if let Some(resume) = &self.resume {
+            resume.update(&self.delivery_time);
+        }
+        self.delivery_time.load()
+    }
+
+    /// Returns how long to sleep while waiting for the next message.
+    #[inline]
+    fn sleep_time(&self, wait: Duration) -> Duration {
+        match &self.resume {
+            Some(resume) => resume.limit(wait),
+            None => wait,
+        }
+    }
+
+    /// Returns `true` if messages carry the number of elapsed periods.
+    #[inline]
+    pub fn is_counted(&self) -> bool {
+        self.counted
+    }
+
+    /// Builds the message for a delivery due at `delivery_time` and received at `now`.
+    ///
+    /// The previous message was received at `delivery_time - duration`, since that is when the
+    /// delivery time was last moved forward.
+    fn ticks(&self, delivery_time: Instant, now: Instant) -> Ticks {
+        let nanos = |d: Duration| d.as_secs() as u128 * 1_000_000_000 + d.subsec_nanos() as u128;
+        let period = nanos(self.duration);
+        let late = nanos(now - delivery_time).checked_div(period).unwrap_or(0);
+
+        Ticks {
+            instant: delivery_time,
+            count: (late + 1).min(u128::from(!0u64)) as u64,
         }
     }
 
     /// Attempts to receive a message without blocking.
     #[inline]
-    pub fn try_recv(&self) -> Result<Instant, TryRecvError> {
+    pub fn try_recv(&self) -> Result<Ticks, TryRecvError> {
         loop {
+            let delivery_time = self.delivery_time();
             let now = Instant::now();
-            let delivery_time = self.delivery_time.load();
 
             if now < delivery_time {
                 return Err(TryRecvError::Empty);
@@ -49,18 +141,18 @@ impl Channel {
                 .compare_exchange(delivery_time, now + self.duration)
                 .is_ok()
             {
-                return Ok(delivery_time);
+                return Ok(self.ticks(delivery_time, now));
             }
         }
     }
 
     /// Receives a message from the channel.
     #[inline]
-    pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<Ticks, RecvTimeoutError> {
         loop {
             // Compute the time to sleep until the next message or the deadline.
             let offset = {
-                let delivery_time = self.delivery_time.load();
+                let delivery_time = self.delivery_time();
                 let now = Instant::now();
 
                 // Check if we can receive the next message.
@@ -70,7 +162,7 @@ impl Channel {
                         .compare_exchange(delivery_time, now + self.duration)
                         .is_ok()
                 {
-                    return Ok(delivery_time);
+                    return Ok(self.ticks(delivery_time, now));
                 }
 
                 // Check if the operation deadline has been reached.
@@ -85,20 +177,20 @@ impl Channel {
                 }
             };
 
-            thread::sleep(offset);
+            thread::sleep(self.sleep_time(offset));
         }
     }
 
     /// Reads a message from the channel.
     #[inline]
-    pub unsafe fn read(&self, token: &mut Token) -> Result<Instant, ()> {
+    pub unsafe fn read(&self, token: &mut Token) -> Result<Ticks, ()> {
         token.tick.ok_or(())
     }
 
     /// Returns `true` if the channel is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        Instant::now() < self.delivery_time.load()
+        Instant::now() < self.delivery_time()
     }
 
     /// Returns `true` if the channel is full.
@@ -122,6 +214,22 @@ impl Channel {
     pub fn capacity(&self) -> Option<usize> {
         Some(1)
     }
+
+    /// Returns the version of the channel.
+    ///
+    /// The version is derived from the next delivery time, which moves forward on every received
+    /// message. The lowest bit is set while a message is ready.
+    #[inline]
+    pub fn version(&self) -> usize {
+        let delivery_time = self.delivery_time();
+        let ready = Instant::now() >= delivery_time;
+
+        let offset = delivery_time - self.start;
+        let nanos = (offset.as_secs() as usize)
+            .wrapping_mul(1_000_000_000)
+            .wrapping_add(offset.subsec_nanos() as usize);
+        nanos.wrapping_mul(2).wrapping_add(ready as usize)
+    }
 }
 
 impl SelectHandle for Channel {
@@ -142,7 +250,11 @@ impl SelectHandle for Channel {
 
     #[inline]
     fn deadline(&self) -> Option<Instant> {
-        Some(self.delivery_time.load())
+        let delivery_time = self.delivery_time();
+        match &self.resume {
+            Some(resume) => Some(resume.wake_time(delivery_time)),
+            None => Some(delivery_time),
+        }
     }
 
     #[inline]
@@ -171,3 +283,22 @@ impl SelectHandle for Channel {
     #[inline]
     fn unwatch(&self, _oper: Operation) {}
 }
+
+impl Introspect for Channel {
+    fn flavor(&self) -> &'static str {
+        "tick"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        // This kind of channel never gets disconnected.
+        false
+    }
+}
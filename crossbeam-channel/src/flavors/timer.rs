@@ -0,0 +1,398 @@
+//! Timers that deliver a message at a certain instant, allocated from a shared slab.
+//!
+//! Messages cannot be sent into this kind of channel; they are materialized on demand.
+//!
+//! Each timer behaves just like an `after` channel, except that its state lives in a slot of a
+//! preallocated slab shared by many timers. A slot gets reused once all receivers of its timer are
+//! dropped.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crossbeam_utils::atomic::AtomicCell;
+
+use context::Context;
+use err::{RecvTimeoutError, TryRecvError};
+use flavors::Introspect;
+use ordering::Ordering;
+use select::{Operation, SelectHandle, Token};
+use utils::{self, Spinlock};
+
+/// Result of a receive operation.
+pub type TimerToken = Option<Instant>;
+
+/// A slot holding the state of a single timer.
+struct Slot {
+    /// The instant at which the message will be delivered.
+    delivery_time: AtomicCell<Instant>,
+
+    /// `true` if the message has been received.
+    received: AtomicBool,
+
+    /// The number of receivers referring to this slot.
+    refs: AtomicUsize,
+
+    /// Incremented every time the slot is freed, so that stale heap entries can be told apart.
+    generation: AtomicUsize,
+}
+
+/// A fixed number of timer slots, shared by all timers of a timer service.
+pub struct Slab {
+    /// The slots.
+    slots: Box<[Slot]>,
+
+    /// Indices of the slots that are not in use.
+    free: Spinlock<Vec<usize>>,
+
+    /// Delivery times of the timers, together with their slot indices and generations.
+    ///
+    /// Entries are not removed when timers are received or dropped, but skipped lazily.
+    heap: Spinlock<BinaryHeap<Reverse<(Instant, usize, usize)>>>,
+}
+
+impl Slab {
+    /// Creates a slab with room for `capacity` timers.
+    pub fn new(capacity: usize) -> Slab {
+        let now = Instant::now();
+        let slots: Vec<Slot> = (0..capacity)
+            .map(|_| Slot {
+                delivery_time: AtomicCell::new(now),
+                received: AtomicBool::new(true),
+                refs: AtomicUsize::new(0),
+                generation: AtomicUsize::new(0),
+            })
+            .collect();
+
+        Slab {
+            slots: slots.into_boxed_slice(),
+            free: Spinlock::new((0..capacity).rev().collect()),
+            heap: Spinlock::new(BinaryHeap::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of slots in use.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.lock().len()
+    }
+
+    /// Allocates a timer delivering a message at `delivery_time`, unless all slots are in use.
+    pub fn insert(slab: &Arc<Slab>, delivery_time: Instant) -> Option<Timer> {
+        let index = slab.free.lock().pop()?;
+        let slot = &slab.slots[index];
+
+        // Nobody else refers to a free slot, so it can be set up before it's handed out.
+        slot.delivery_time.store(delivery_time);
+        slot.received.store(false, Ordering::SeqCst);
+        slot.refs.store(1, Ordering::SeqCst);
+        let generation = slot.generation.load(Ordering::SeqCst);
+
+        let mut heap = slab.heap.lock();
+        if heap.len() >= 2 * slab.slots.len() {
+            // Too many stale entries have piled up, so get rid of them.
+            let entries = heap.drain().filter(|e| slab.is_pending(e)).collect();
+            *heap = entries;
+        }
+        heap.push(Reverse((delivery_time, index, generation)));
+
+        Some(Timer {
+            slab: slab.clone(),
+            index,
+        })
+    }
+
+    /// Returns the earliest delivery time among timers whose messages haven't been received.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let mut heap = self.heap.lock();
+        while let Some(entry) = heap.peek().cloned() {
+            if self.is_pending(&entry) {
+                return Some((entry.0).0);
+            }
+            heap.pop();
+        }
+        None
+    }
+
+    /// Returns `true` if the heap entry belongs to a live timer whose message wasn't received.
+    fn is_pending(&self, entry: &Reverse<(Instant, usize, usize)>) -> bool {
+        let (_, index, generation) = entry.0;
+        let slot = &self.slots[index];
+        slot.generation.load(Ordering::SeqCst) == generation
+            && !slot.received.load(Ordering::SeqCst)
+    }
+}
+
+/// A timer occupying a slot in a slab.
+pub struct Timer {
+    /// The slab the slot belongs to.
+    slab: Arc<Slab>,
+
+    /// The index of the slot.
+    index: usize,
+}
+
+impl Timer {
+    /// Returns the slot of this timer.
+    #[inline]
+    fn slot(&self) -> &Slot {
+        &self.slab.slots[self.index]
+    }
+
+    /// Returns `true` if both timers occupy the same slot.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Timer) -> bool {
+        Arc::ptr_eq(&self.slab, &other.slab) && self.index == other.index
+    }
+
+    /// Returns `true` if this is the only reference to the timer.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.slot().refs.load(Ordering::SeqCst) == 1
+    }
+
+    /// Attempts to receive a message without blocking.
+    #[inline]
+    pub fn try_recv(&self) -> Result<Instant, TryRecvError> {
+        let slot = self.slot();
+
+        // We use relaxed ordering because this is just an optional optimistic check.
+        if slot.received.load(Ordering::Relaxed) {
+            // The message has already been received.
+            return Err(TryRecvError::Empty);
+        }
+
+        let delivery_time = slot.delivery_time.load();
+        if Instant::now() < delivery_time {
+            // The message was not delivered yet.
+            return Err(TryRecvError::Empty);
+        }
+
+        // Try receiving the message if it is still available.
+        if !slot.received.swap(true, Ordering::SeqCst) {
+            // Success! Return delivery time as the message.
+            Ok(delivery_time)
+        } else {
+            // The message was already received.
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    #[inline]
+    pub fn recv(&self, deadline: Option<Instant>) -> Result<Instant, RecvTimeoutError> {
+        let slot = self.slot();
+
+        // We use relaxed ordering because this is just an optional optimistic check.
+        if slot.received.load(Ordering::Relaxed) {
+            // The message has already been received.
+            utils::sleep_until(deadline);
+            return Err(RecvTimeoutError::Timeout);
+        }
+
+        let delivery_time = slot.delivery_time.load();
+
+        // Wait until the message is received or the deadline is reached.
+        loop {
+            let now = Instant::now();
+
+            // Check if we can receive the next message.
+            if now >= delivery_time {
+                break;
+            }
+
+            // Check if the deadline has been reached.
+            if let Some(d) = deadline {
+                if now >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+
+                thread::sleep(delivery_time.min(d) - now);
+            } else {
+                thread::sleep(delivery_time - now);
+            }
+        }
+
+        // Try receiving the message if it is still available.
+        if !slot.received.swap(true, Ordering::SeqCst) {
+            // Success! Return the message, which is the instant at which it was delivered.
+            Ok(delivery_time)
+        } else {
+            // The message was already received. Block forever.
+            utils::sleep_until(None);
+            unreachable!()
+        }
+    }
+
+    /// Reads a message from the channel.
+    #[inline]
+    pub unsafe fn read(&self, token: &mut Token) -> Result<Instant, ()> {
+        token.timer.ok_or(())
+    }
+
+    /// Returns `true` if the channel is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let slot = self.slot();
+
+        // We use relaxed ordering because this is just an optional optimistic check.
+        if slot.received.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        // If the delivery time hasn't been reached yet, the channel is empty.
+        if Instant::now() < slot.delivery_time.load() {
+            return true;
+        }
+
+        // The delivery time has been reached. The channel is empty only if the message has already
+        // been received.
+        slot.received.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if the channel is full.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Returns the number of messages in the channel.
+    #[inline]
+    pub fn len(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Returns the capacity of the channel.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    /// Returns the version of the channel.
+    ///
+    /// The version is 0 before the delivery time, 1 once the message is ready, and 2 after it has
+    /// been received.
+    #[inline]
+    pub fn version(&self) -> usize {
+        let slot = self.slot();
+        if slot.received.load(Ordering::SeqCst) {
+            2
+        } else if Instant::now() >= slot.delivery_time.load() {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Clone for Timer {
+    fn clone(&self) -> Timer {
+        self.slot().refs.fetch_add(1, Ordering::Relaxed);
+        Timer {
+            slab: self.slab.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let slot = self.slot();
+        if slot.refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // This was the last receiver, so the slot can be reused.
+            slot.received.store(true, Ordering::SeqCst);
+            slot.generation.fetch_add(1, Ordering::SeqCst);
+            self.slab.free.lock().push(self.index);
+        }
+    }
+}
+
+impl SelectHandle for Timer {
+    #[inline]
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.try_recv() {
+            Ok(msg) => {
+                token.timer = Some(msg);
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                token.timer = None;
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        }
+    }
+
+    #[inline]
+    fn deadline(&self) -> Option<Instant> {
+        let slot = self.slot();
+
+        // We use relaxed ordering because this is just an optional optimistic check.
+        if slot.received.load(Ordering::Relaxed) {
+            None
+        } else {
+            Some(slot.delivery_time.load())
+        }
+    }
+
+    #[inline]
+    fn register(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unregister(&self, _oper: Operation) {}
+
+    #[inline]
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    #[inline]
+    fn is_ready(&self) -> bool {
+        !self.is_empty()
+    }
+
+    #[inline]
+    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+        self.is_ready()
+    }
+
+    #[inline]
+    fn unwatch(&self, _oper: Operation) {}
+
+    #[inline]
+    fn never_ready(&self) -> bool {
+        self.slot().received.load(Ordering::SeqCst)
+    }
+}
+
+impl Introspect for Timer {
+    fn flavor(&self) -> &'static str {
+        "timer"
+    }
+
+    fn len(&self) -> usize {
+        Timer::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Timer::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        // This kind of channel never gets disconnected.
+        false
+    }
+}
@@ -4,13 +4,16 @@
 
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::time::Instant;
 
 use crossbeam_utils::Backoff;
 
+use affinity::CoreSet;
 use context::Context;
 use err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use flavors::Introspect;
+use ordering::{yield_point, Ordering};
 use select::{Operation, SelectHandle, Selected, Token};
 use utils::Spinlock;
 use waker::Waker;
@@ -84,6 +87,9 @@ pub struct Channel<T> {
     /// Inner representation of the channel.
     inner: Spinlock<Inner>,
 
+    /// Incremented whenever an operation starts waiting or the channel gets disconnected.
+    version: AtomicUsize,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -97,6 +103,7 @@ impl<T> Channel<T> {
                 receivers: Waker::new(),
                 is_disconnected: false,
             }),
+            version: AtomicUsize::new(0),
             _marker: PhantomData,
         }
     }
@@ -136,6 +143,7 @@ impl<T> Channel<T> {
 
         let packet = &*(token.zero as *const Packet<T>);
         packet.msg.get().write(Some(msg));
+        yield_point();
         packet.ready.store(true, Ordering::Release);
         Ok(())
     }
@@ -170,6 +178,7 @@ impl<T> Channel<T> {
             // for it. However, after reading the message, we need to set `ready` to `true` in
             // order to signal that the packet can be destroyed.
             let msg = packet.msg.get().replace(None).unwrap();
+            yield_point();
             packet.ready.store(true, Ordering::Release);
             Ok(msg)
         } else {
@@ -229,6 +238,7 @@ impl<T> Channel<T> {
                 .senders
                 .register_with_packet(oper, &packet as *const Packet<T> as usize, cx);
             inner.receivers.notify();
+            self.version.fetch_add(1, Ordering::SeqCst);
             drop(inner);
 
             // Block the current thread.
@@ -237,6 +247,8 @@ impl<T> Channel<T> {
             match sel {
                 Selected::Waiting => unreachable!(),
                 Selected::Aborted => {
+                    // No receiver has paired up with this operation, so the message is still in
+                    // the packet and can be handed back to the caller.
                     self.inner.lock().senders.unregister(oper).unwrap();
                     let msg = unsafe { packet.msg.get().replace(None).unwrap() };
                     Err(SendTimeoutError::Timeout(msg))
@@ -298,6 +310,7 @@ impl<T> Channel<T> {
                 .receivers
                 .register_with_packet(oper, &packet as *const Packet<T> as usize, cx);
             inner.senders.notify();
+            self.version.fetch_add(1, Ordering::SeqCst);
             drop(inner);
 
             // Block the current thread.
@@ -332,22 +345,40 @@ impl<T> Channel<T> {
             inner.is_disconnected = true;
             inner.senders.disconnect();
             inner.receivers.disconnect();
+            self.version.fetch_add(1, Ordering::SeqCst);
             true
         } else {
             false
         }
     }
 
+    /// Returns `true` if the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        self.inner.lock().is_disconnected
+    }
+
     /// Returns the current number of messages inside the channel.
     pub fn len(&self) -> usize {
         0
     }
 
+    /// Sets the cores whose blocked receivers are woken up first.
+    pub fn set_wake_preference(&self, preference: Option<CoreSet>) {
+        self.inner.lock().receivers.set_preference(preference);
+    }
+
     /// Returns the capacity of the channel.
     pub fn capacity(&self) -> Option<usize> {
         Some(0)
     }
 
+    /// Returns the version of the channel.
+    ///
+    /// Both sides of a zero-capacity channel share the same version.
+    pub fn version(&self) -> usize {
+        self.version.load(Ordering::SeqCst)
+    }
+
     /// Returns `true` if the channel is empty.
     pub fn is_empty(&self) -> bool {
         true
@@ -382,6 +413,7 @@ impl<'a, T> SelectHandle for Receiver<'a, T> {
             .receivers
             .register_with_packet(oper, packet as usize, cx);
         inner.senders.notify();
+        self.0.version.fetch_add(1, Ordering::SeqCst);
         inner.senders.can_select() || inner.is_disconnected
     }
 
@@ -432,6 +464,7 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
             .senders
             .register_with_packet(oper, packet as usize, cx);
         inner.receivers.notify();
+        self.0.version.fetch_add(1, Ordering::SeqCst);
         inner.receivers.can_select() || inner.is_disconnected
     }
 
@@ -464,3 +497,21 @@ impl<'a, T> SelectHandle for Sender<'a, T> {
         inner.senders.unwatch(oper);
     }
 }
+
+impl<T> Introspect for Channel<T> {
+    fn flavor(&self) -> &'static str {
+        "zero"
+    }
+
+    fn len(&self) -> usize {
+        Channel::len(self)
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Channel::capacity(self)
+    }
+
+    fn is_disconnected(&self) -> bool {
+        Channel::is_disconnected(self)
+    }
+}
@@ -0,0 +1,177 @@
+//! Groups of channels that can be disconnected all at once.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use channel::Sender;
+use counter::Closer;
+
+/// A group of channels that can be disconnected all at once.
+///
+/// Channels are added to the group with [`register`], and [`close_all`] disconnects all of them,
+/// waking up every thread blocked on them. This gives applications with many channels a single
+/// place to initiate shutdown from, instead of dropping senders in just the right order.
+///
+/// Disconnecting a channel through the group has the same effect as dropping all of its senders:
+/// sends fail right away, while receivers get the messages still in the channel before they see
+/// the disconnection.
+///
+/// The group doesn't keep its channels alive. A channel whose endpoints have all been dropped
+/// simply leaves the group. The group can be cloned, and all clones refer to the same group.
+///
+/// [`register`]: struct.ChannelGroup.html#method.register
+/// [`close_all`]: struct.ChannelGroup.html#method.close_all
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{bounded, unbounded, ChannelGroup};
+///
+/// let group = ChannelGroup::new();
+///
+/// let (s1, r1) = unbounded::<i32>();
+/// let (s2, r2) = bounded::<String>(0);
+/// group.register(&s1);
+/// group.register(&s2);
+///
+/// let worker = thread::spawn(move || {
+///     // Both loops end once the group is closed.
+///     let ints = r1.iter().count();
+///     let strings = r2.iter().count();
+///     (ints, strings)
+/// });
+///
+/// s1.send(1).unwrap();
+/// group.close_all();
+///
+/// assert!(s1.send(2).is_err());
+/// assert_eq!(worker.join().unwrap(), (1, 0));
+/// ```
+#[derive(Clone, Default)]
+pub struct ChannelGroup {
+    inner: Arc<Mutex<Inner>>,
+}
+
+/// Inner representation of a channel group.
+#[derive(Default)]
+struct Inner {
+    /// Handles disconnecting the channels in the group.
+    members: Vec<Arc<Closer>>,
+
+    /// Equals `true` once the group has been closed.
+    closed: bool,
+}
+
+impl ChannelGroup {
+    /// Creates an empty channel group.
+    pub fn new() -> ChannelGroup {
+        ChannelGroup::default()
+    }
+
+    /// Adds the channel `sender` belongs to to the group.
+    ///
+    /// If the group has already been closed, the channel is disconnected right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ChannelGroup};
+    ///
+    /// let group = ChannelGroup::new();
+    /// group.close_all();
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// group.register(&s);
+    /// assert!(s.send(1).is_err());
+    /// assert!(r.recv().is_err());
+    /// ```
+    pub fn register<T>(&self, sender: &Sender<T>) {
+        let closer = sender.closer();
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.closed {
+            drop(inner);
+            closer.close();
+        } else {
+            // Forget about channels that have been destroyed in the meantime.
+            inner.members.retain(|c| c.is_alive());
+            inner.members.push(closer);
+        }
+    }
+
+    /// Disconnects every channel in the group and wakes up all threads blocked on them.
+    ///
+    /// Channels registered after this call get disconnected as soon as they are registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, ChannelGroup};
+    ///
+    /// let group = ChannelGroup::new();
+    /// let (s, r) = bounded(1);
+    /// group.register(&s);
+    ///
+    /// s.send(1).unwrap();
+    /// group.close_all();
+    ///
+    /// assert!(s.send(2).is_err());
+    /// assert_eq!(r.recv(), Ok(1));
+    /// assert!(r.recv().is_err());
+    /// ```
+    pub fn close_all(&self) {
+        let members = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.closed = true;
+            let members = inner.members.drain(..).collect::<Vec<_>>();
+            members
+        };
+
+        for closer in members {
+            closer.close();
+        }
+    }
+
+    /// Returns `true` if [`close_all`] has been called.
+    ///
+    /// [`close_all`]: struct.ChannelGroup.html#method.close_all
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().unwrap().closed
+    }
+
+    /// Returns the number of channels in the group that are still alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, ChannelGroup};
+    ///
+    /// let group = ChannelGroup::new();
+    /// let (s, r) = unbounded::<i32>();
+    /// group.register(&s);
+    /// assert_eq!(group.len(), 1);
+    ///
+    /// drop(s);
+    /// drop(r);
+    /// assert_eq!(group.len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.members.iter().filter(|c| c.is_alive()).count()
+    }
+
+    /// Returns `true` if the group has no channels that are still alive.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for ChannelGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ChannelGroup")
+            .field("len", &self.len())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
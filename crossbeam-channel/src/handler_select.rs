@@ -0,0 +1,216 @@
+//! Selection with handlers attached to the operations.
+
+use std::fmt;
+use std::time::Duration;
+
+use channel::{Receiver, Sender};
+use err::{RecvError, SendError};
+use select::{Select, SelectedOperation};
+
+/// A selection over a set of operations, each with its own handler.
+///
+/// This is a runtime counterpart to [`select!`]: every operation is registered together with the
+/// code that handles its outcome, and the handler of the selected operation is called
+/// automatically. The result of the handler is the result of the selection. This way, selections
+/// can be assembled entirely at runtime, for example out of cases contributed by plugins, without
+/// matching on operation indices.
+///
+/// As in [`select!`], the message of a send operation is only produced once that operation has
+/// been selected, so messages for the operations that don't get selected are never created.
+///
+/// Handlers are called at most once, so selecting consumes the `HandlerSelect`. If no operation
+/// becomes ready in [`try_select`] or [`select_timeout`], the `HandlerSelect` is given back so that
+/// the selection can be retried.
+///
+/// [`select!`]: macro.select.html
+/// [`try_select`]: struct.HandlerSelect.html#method.try_select
+/// [`select_timeout`]: struct.HandlerSelect.html#method.select_timeout
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{unbounded, HandlerSelect};
+///
+/// let (s1, r1) = unbounded::<i32>();
+/// let (s2, r2) = unbounded::<&str>();
+/// s2.send("hello").unwrap();
+///
+/// let mut sel = HandlerSelect::new();
+/// sel.recv(&r1, |msg| format!("number {:?}", msg));
+/// sel.recv(&r2, |msg| format!("string {:?}", msg));
+///
+/// assert_eq!(sel.select(), "string Ok(\"hello\")");
+/// # drop(s1);
+/// ```
+pub struct HandlerSelect<'a, R> {
+    /// The operations.
+    sel: Select<'a>,
+
+    /// Handlers indexed by the indices of their operations.
+    cases: Vec<Option<Box<dyn Case<'a, R> + 'a>>>,
+}
+
+impl<'a, R> HandlerSelect<'a, R> {
+    /// Creates an empty list of operations.
+    pub fn new() -> HandlerSelect<'a, R> {
+        HandlerSelect {
+            sel: Select::new(),
+            cases: Vec::new(),
+        }
+    }
+
+    /// Adds a receive operation, handled by `handler`.
+    ///
+    /// The handler gets the result of the receive operation once it's selected. Returns the index
+    /// of the added operation.
+    pub fn recv<T, F>(&mut self, r: &'a Receiver<T>, handler: F) -> usize
+    where
+        T: 'a,
+        F: FnOnce(Result<T, RecvError>) -> R + 'a,
+    {
+        let index = self.sel.recv(r);
+        self.insert(index, Box::new(RecvCase { r, handler }));
+        index
+    }
+
+    /// Adds a send operation, handled by `handler`.
+    ///
+    /// Once the operation is selected, `msg` is called to produce the message, which is then sent.
+    /// The handler gets the result of the send operation. Returns the index of the added
+    /// operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, HandlerSelect};
+    ///
+    /// let (s1, r1) = bounded(0);
+    /// let (s2, r2) = bounded(1);
+    ///
+    /// let mut sel = HandlerSelect::new();
+    /// sel.send(&s1, || panic!("no receiver is waiting"), |_| 1);
+    /// sel.send(&s2, || "message", |res| {
+    ///     assert!(res.is_ok());
+    ///     2
+    /// });
+    ///
+    /// assert_eq!(sel.select(), 2);
+    /// assert_eq!(r2.try_recv(), Ok("message"));
+    /// # drop(r1);
+    /// ```
+    pub fn send<T, M, F>(&mut self, s: &'a Sender<T>, msg: M, handler: F) -> usize
+    where
+        T: 'a,
+        M: FnOnce() -> T + 'a,
+        F: FnOnce(Result<(), SendError<T>>) -> R + 'a,
+    {
+        let index = self.sel.send(s);
+        self.insert(index, Box::new(SendCase { s, msg, handler }));
+        index
+    }
+
+    /// Returns `true` if no operations have been added.
+    pub fn is_empty(&self) -> bool {
+        self.sel.is_empty()
+    }
+
+    /// Blocks until one of the operations becomes ready, completes it, and returns what its
+    /// handler returns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added.
+    pub fn select(mut self) -> R {
+        let oper = self.sel.select();
+        self.dispatch(oper)
+    }
+
+    /// Completes one of the operations if any is ready, and returns what its handler returns.
+    ///
+    /// If none of the operations are ready, the `HandlerSelect` is returned in the error.
+    pub fn try_select(mut self) -> Result<R, HandlerSelect<'a, R>> {
+        match self.sel.try_select() {
+            Ok(oper) => Ok(self.dispatch(oper)),
+            Err(_) => Err(self),
+        }
+    }
+
+    /// Waits until one of the operations becomes ready, but only for a limited time, completes
+    /// it, and returns what its handler returns.
+    ///
+    /// If the timeout elapses first, the `HandlerSelect` is returned in the error.
+    pub fn select_timeout(mut self, timeout: Duration) -> Result<R, HandlerSelect<'a, R>> {
+        match self.sel.select_timeout(timeout) {
+            Ok(oper) => Ok(self.dispatch(oper)),
+            Err(_) => Err(self),
+        }
+    }
+
+    /// Stores the handler of the operation at `index`.
+    fn insert(&mut self, index: usize, case: Box<dyn Case<'a, R> + 'a>) {
+        while self.cases.len() <= index {
+            self.cases.push(None);
+        }
+        self.cases[index] = Some(case);
+    }
+
+    /// Completes the selected operation with its handler.
+    fn dispatch(mut self, oper: SelectedOperation<'a>) -> R {
+        let case = self.cases[oper.index()]
+            .take()
+            .expect("selected an operation without a handler");
+        case.complete(oper)
+    }
+}
+
+impl<'a, R> Default for HandlerSelect<'a, R> {
+    fn default() -> HandlerSelect<'a, R> {
+        HandlerSelect::new()
+    }
+}
+
+impl<'a, R> fmt::Debug for HandlerSelect<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("HandlerSelect { .. }")
+    }
+}
+
+/// An operation in a `HandlerSelect` together with its handler.
+trait Case<'a, R> {
+    /// Completes the selected operation and calls the handler.
+    fn complete(self: Box<Self>, oper: SelectedOperation<'a>) -> R;
+}
+
+/// A receive operation and its handler.
+struct RecvCase<'a, T: 'a, F> {
+    r: &'a Receiver<T>,
+    handler: F,
+}
+
+impl<'a, T, F, R> Case<'a, R> for RecvCase<'a, T, F>
+where
+    F: FnOnce(Result<T, RecvError>) -> R,
+{
+    fn complete(self: Box<Self>, oper: SelectedOperation<'a>) -> R {
+        let this = *self;
+        (this.handler)(oper.recv(this.r))
+    }
+}
+
+/// A send operation, its lazily produced message, and its handler.
+struct SendCase<'a, T: 'a, M, F> {
+    s: &'a Sender<T>,
+    msg: M,
+    handler: F,
+}
+
+impl<'a, T, M, F, R> Case<'a, R> for SendCase<'a, T, M, F>
+where
+    M: FnOnce() -> T,
+    F: FnOnce(Result<(), SendError<T>>) -> R,
+{
+    fn complete(self: Box<Self>, oper: SelectedOperation<'a>) -> R {
+        let this = *self;
+        (this.handler)(oper.send(this.s, (this.msg)()))
+    }
+}
@@ -0,0 +1,285 @@
+//! Utilities for observing and controlling blocked channel operations in tests.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::MutexGuard;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+use waiters::{self, REGISTRY};
+
+/// Number of live harnesses.
+///
+/// Blocked threads are only recorded while at least one harness exists.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// The identifier of the next harness to be created.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The bookkeeping of test harnesses, kept in the registry of parked threads.
+pub struct State {
+    /// Threads held at their blocking points, with the number of blocking points each of them
+    /// may still pass.
+    holds: HashMap<ThreadId, usize>,
+
+    /// The number of times each thread got blocked, separately for each live harness.
+    counts: HashMap<usize, HashMap<ThreadId, usize>>,
+}
+
+impl State {
+    /// Creates empty bookkeeping.
+    pub fn new() -> State {
+        State {
+            holds: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+}
+
+/// Creates a harness for observing and controlling threads blocked in channel operations.
+///
+/// While the harness is alive, every thread that parks inside a blocking operation (a send,
+/// receive, or select) is recorded until it wakes up again. Tests can use it to wait until a
+/// thread has actually reached its blocking point instead of sleeping and hoping it did.
+///
+/// Threads can also be [held] at their blocking points. A held thread stops right before parking
+/// and doesn't look at the outcome of its operation until the test [steps] or [releases] it, which
+/// makes it possible to reproduce a specific interleaving of a blocked operation with the
+/// operations of other threads.
+///
+/// The harness must be created before the observed threads start blocking.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{bounded, test_harness};
+///
+/// let harness = test_harness();
+/// let (s, r) = bounded(0);
+///
+/// let t = thread::spawn(move || s.send(7).unwrap());
+///
+/// // Wait until the sender is parked waiting for a receiver.
+/// harness.wait_until_blocked(t.thread().id());
+/// assert_eq!(r.try_recv(), Ok(7));
+/// t.join().unwrap();
+/// ```
+///
+/// [held]: struct.TestHarness.html#method.hold
+/// [steps]: struct.TestHarness.html#method.step
+/// [releases]: struct.TestHarness.html#method.release
+pub fn test_harness() -> TestHarness {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    REGISTRY
+        .state
+        .lock()
+        .unwrap()
+        .harness
+        .counts
+        .insert(id, HashMap::new());
+
+    ACTIVE.fetch_add(1, Ordering::SeqCst);
+    TestHarness { id }
+}
+
+/// A harness for observing threads blocked in channel operations.
+///
+/// Created by the [`test_harness`] function.
+///
+/// [`test_harness`]: fn.test_harness.html
+pub struct TestHarness {
+    /// Identifies the block counts recorded for this harness.
+    id: usize,
+}
+
+impl TestHarness {
+    /// Returns `true` if the thread is currently blocked in a channel operation.
+    pub fn is_blocked(&self, thread_id: ThreadId) -> bool {
+        let state = REGISTRY.state.lock().unwrap();
+        state.parked.contains_key(&thread_id)
+    }
+
+    /// Blocks until the thread is blocked in a channel operation.
+    pub fn wait_until_blocked(&self, thread_id: ThreadId) {
+        let mut state = REGISTRY.state.lock().unwrap();
+        while !state.parked.contains_key(&thread_id) {
+            state = REGISTRY.cond.wait(state).unwrap();
+        }
+    }
+
+    /// Blocks until the thread is blocked in a channel operation, but only for a limited time.
+    ///
+    /// Returns `true` if the thread got blocked before the timeout.
+    pub fn wait_until_blocked_timeout(&self, thread_id: ThreadId, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = REGISTRY.state.lock().unwrap();
+
+        while !state.parked.contains_key(&thread_id) {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = REGISTRY.cond.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        true
+    }
+
+    /// Holds the thread at its blocking points until it is stepped or released.
+    ///
+    /// When a held thread reaches a blocking point, it gets recorded as blocked and then waits
+    /// for [`step`] or [`release`] instead of parking. Meanwhile, its operation stays registered
+    /// with the channel and can be completed by other threads, but the held thread only finds
+    /// out once it continues. Deadlines of held operations are not enforced while they are held.
+    ///
+    /// Holding a thread that is already blocked takes effect at its next blocking point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Barrier};
+    /// use std::thread;
+    /// use crossbeam_channel::{test_harness, unbounded};
+    ///
+    /// let harness = test_harness();
+    /// let (s, r) = unbounded();
+    /// let start = Arc::new(Barrier::new(2));
+    ///
+    /// let t = thread::spawn({
+    ///     let start = start.clone();
+    ///     move || {
+    ///         start.wait();
+    ///         r.recv().unwrap()
+    ///     }
+    /// });
+    ///
+    /// // Hold the receiver before it gets a chance to block.
+    /// harness.hold(t.thread().id());
+    /// start.wait();
+    /// harness.wait_until_blocked(t.thread().id());
+    ///
+    /// // The message is handed over, but the receiver stays at its blocking point.
+    /// s.send(1).unwrap();
+    /// assert!(harness.is_blocked(t.thread().id()));
+    ///
+    /// harness.release(t.thread().id());
+    /// assert_eq!(t.join().unwrap(), 1);
+    /// ```
+    ///
+    /// [`step`]: struct.TestHarness.html#method.step
+    /// [`release`]: struct.TestHarness.html#method.release
+    pub fn hold(&self, thread_id: ThreadId) {
+        let mut state = REGISTRY.state.lock().unwrap();
+        state.harness.holds.entry(thread_id).or_insert(0);
+    }
+
+    /// Lets a held thread pass one blocking point, and keeps holding it at the next one.
+    ///
+    /// If the thread is not held, this method does nothing.
+    pub fn step(&self, thread_id: ThreadId) {
+        let mut state = REGISTRY.state.lock().unwrap();
+        if let Some(passes) = state.harness.holds.get_mut(&thread_id) {
+            *passes += 1;
+            REGISTRY.cond.notify_all();
+        }
+    }
+
+    /// Stops holding the thread, letting it continue from its current blocking point.
+    ///
+    /// If the thread is not held, this method does nothing.
+    pub fn release(&self, thread_id: ThreadId) {
+        let mut state = REGISTRY.state.lock().unwrap();
+        if state.harness.holds.remove(&thread_id).is_some() {
+            REGISTRY.cond.notify_all();
+        }
+    }
+
+    /// Returns the number of times the thread got blocked in channel operations since this
+    /// harness was created.
+    ///
+    /// A blocking operation normally blocks at most once. If a thread gets woken up but then
+    /// fails to complete its operation, it has to block again, so this count can be used to
+    /// detect spurious wakeups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{test_harness, unbounded};
+    ///
+    /// let harness = test_harness();
+    /// let (s, r) = unbounded();
+    ///
+    /// let t = thread::spawn(move || r.recv().unwrap());
+    /// harness.wait_until_blocked(t.thread().id());
+    ///
+    /// let id = t.thread().id();
+    /// s.send(1).unwrap();
+    /// assert_eq!(t.join().unwrap(), 1);
+    /// assert_eq!(harness.times_blocked(id), 1);
+    /// ```
+    pub fn times_blocked(&self, thread_id: ThreadId) -> usize {
+        let state = REGISTRY.state.lock().unwrap();
+        state.harness.counts[&self.id]
+            .get(&thread_id)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of threads currently blocked in channel operations.
+    pub fn blocked_count(&self) -> usize {
+        REGISTRY.state.lock().unwrap().parked.len()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        let mut state = REGISTRY.state.lock().unwrap();
+        state.harness.counts.remove(&self.id);
+
+        if ACTIVE.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Without a harness nobody could release held threads anymore.
+            state.harness.holds.clear();
+            REGISTRY.cond.notify_all();
+        }
+    }
+}
+
+impl fmt::Debug for TestHarness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TestHarness")
+            .field("blocked_count", &self.blocked_count())
+            .finish()
+    }
+}
+
+/// Returns `true` if a harness is alive, in which case parked threads are recorded.
+#[inline]
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed) != 0
+}
+
+/// Counts a thread that has just been recorded as parked, and waits while it is held.
+#[cold]
+pub fn on_park(mut state: MutexGuard<waiters::State>, thread_id: ThreadId) {
+    for counts in state.harness.counts.values_mut() {
+        *counts.entry(thread_id).or_insert(0) += 1;
+    }
+    REGISTRY.cond.notify_all();
+
+    loop {
+        {
+            match state.harness.holds.get_mut(&thread_id) {
+                None => break,
+                Some(passes) => {
+                    if *passes > 0 {
+                        *passes -= 1;
+                        break;
+                    }
+                }
+            }
+        }
+        state = REGISTRY.cond.wait(state).unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+//! A hook for profilers and tracing tools observing select operations, and statistics of message
+//! sizes.
+//!
+//! Once a hook is installed with [`set_select_hook`], every selection made by [`Select`] or
+//! [`select!`] reports its progress to it: when the thread blocks, when it wakes up, and which
+//! operation got selected in the end. Operations are identified by their index, so the hook can
+//! attribute wait time to specific channels.
+//!
+//! While no hook is installed, the only cost is a single atomic load per selection.
+//!
+//! [`set_select_hook`]: fn.set_select_hook.html
+//! [`Select`]: ../struct.Select.html
+//! [`select!`]: ../macro.select.html
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use crossbeam_channel::instrument::{self, SelectEvent};
+//! use crossbeam_channel::{unbounded, Select};
+//!
+//! let fired = Arc::new(AtomicUsize::new(0));
+//! let counter = fired.clone();
+//! instrument::set_select_hook(move |event| {
+//!     if let SelectEvent::Fired { .. } = *event {
+//!         counter.fetch_add(1, Ordering::SeqCst);
+//!     }
+//! });
+//!
+//! let (s, r) = unbounded();
+//! s.send(1).unwrap();
+//!
+//! let mut sel = Select::new();
+//! sel.recv(&r);
+//! let oper = sel.select();
+//! assert_eq!(oper.recv(&r), Ok(1));
+//!
+//! instrument::clear_select_hook();
+//! assert!(fired.load(Ordering::SeqCst) >= 1);
+//! ```
+//!
+//! # Message sizes
+//!
+//! Every message occupies `size_of::<T>()` bytes in a channel, so a single large variant of an
+//! enum makes all messages of that type as large as the variant. With the `size-stats` feature,
+//! sent messages are counted by size class, and [`message_sizes`] shows how much traffic goes
+//! through channels of large message types. The first time a message larger than the threshold
+//! set with [`set_large_message_threshold`] is sent, a warning suggesting boxing is printed to
+//! standard error.
+//!
+//! Without the `size-stats` feature, nothing is recorded and sending pays nothing.
+//!
+//! [`message_sizes`]: fn.message_sizes.html
+//! [`set_large_message_threshold`]: fn.set_large_message_threshold.html
+
+use std::cell::Cell;
+#[cfg(feature = "size-stats")]
+use std::mem;
+#[cfg(feature = "size-stats")]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// An event reported to the select hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectEvent {
+    /// The thread is about to block, waiting on `operations` operations.
+    BlockBegin {
+        /// The number of operations the thread is waiting on.
+        operations: usize,
+    },
+
+    /// The thread woke up after blocking.
+    BlockEnd {
+        /// How long the thread was blocked.
+        waited: Duration,
+    },
+
+    /// An operation was selected, or became ready.
+    Fired {
+        /// The index of the operation.
+        index: usize,
+
+        /// How long the whole selection took, including time spent blocked.
+        elapsed: Duration,
+    },
+}
+
+/// A hook receiving select events.
+type Hook = Arc<dyn Fn(&SelectEvent) + Send + Sync>;
+
+/// Set to `true` while a hook is installed.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// The installed hook.
+    static ref HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+}
+
+thread_local! {
+    /// Set to `true` while the current thread is running the hook.
+    static IN_HOOK: Cell<bool> = Cell::new(false);
+}
+
+/// Installs a hook that gets called on every select event, replacing the previous one.
+///
+/// The hook is called on the thread performing the selection, so it should be quick and must not
+/// block. Selections made by the hook itself are not reported.
+pub fn set_select_hook<F>(hook: F)
+where
+    F: Fn(&SelectEvent) + Send + Sync + 'static,
+{
+    *HOOK.write().unwrap() = Some(Arc::new(hook));
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Removes the installed hook, if any.
+///
+/// Selections that are already in progress may still report events to the removed hook.
+pub fn clear_select_hook() {
+    ENABLED.store(false, Ordering::SeqCst);
+    *HOOK.write().unwrap() = None;
+}
+
+/// Reports the events of a single selection to the hook.
+pub(crate) struct Probe {
+    /// The hook installed when the selection started, together with the instant it started at.
+    ///
+    /// This is `None` if no hook was installed.
+    hook: Option<(Hook, Instant)>,
+
+    /// The instant at which the thread started blocking.
+    blocked: Option<Instant>,
+}
+
+impl Probe {
+    /// Starts observing a selection.
+    #[inline]
+    pub fn new() -> Probe {
+        let mut hook = None;
+        if ENABLED.load(Ordering::Relaxed) && !IN_HOOK.with(|h| h.get()) {
+            if let Some(h) = HOOK.read().unwrap().clone() {
+                hook = Some((h, Instant::now()));
+            }
+        }
+
+        Probe {
+            hook,
+            blocked: None,
+        }
+    }
+
+    /// Reports that the thread is about to block.
+    #[inline]
+    pub fn block_begin(&mut self, operations: usize) {
+        if self.hook.is_some() {
+            self.emit(&SelectEvent::BlockBegin { operations });
+            self.blocked = Some(Instant::now());
+        }
+    }
+
+    /// Reports that the thread woke up.
+    #[inline]
+    pub fn block_end(&mut self) {
+        if let Some(blocked) = self.blocked.take() {
+            let waited = blocked.elapsed();
+            self.emit(&SelectEvent::BlockEnd { waited });
+        }
+    }
+
+    /// Reports that operation `index` was selected.
+    #[inline]
+    pub fn fired(&self, index: usize) {
+        if let Some((_, start)) = self.hook {
+            let elapsed = start.elapsed();
+            self.emit(&SelectEvent::Fired { index, elapsed });
+        }
+    }
+
+    /// Calls the hook with an event.
+    fn emit(&self, event: &SelectEvent) {
+        if let Some((ref hook, _)) = self.hook {
+            IN_HOOK.with(|h| h.set(true));
+            hook(event);
+            IN_HOOK.with(|h| h.set(false));
+        }
+    }
+}
+
+/// The number of size classes: powers of two from 8 to 4096 bytes, and everything larger.
+#[cfg(feature = "size-stats")]
+const SIZE_CLASSES: usize = 11;
+
+/// The default threshold for large messages, in bytes.
+#[cfg(feature = "size-stats")]
+const DEFAULT_THRESHOLD: usize = 1024;
+
+#[cfg(feature = "size-stats")]
+lazy_static! {
+    /// Number of sent messages in each size class.
+    static ref SIZE_COUNTS: Vec<AtomicUsize> = (0..SIZE_CLASSES).map(|_| AtomicUsize::new(0)).collect();
+}
+
+/// Total size of sent messages, in bytes.
+#[cfg(feature = "size-stats")]
+static SIZE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Messages larger than this many bytes trigger the warning.
+#[cfg(feature = "size-stats")]
+static THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_THRESHOLD);
+
+/// Set to `true` once the warning has been printed.
+#[cfg(feature = "size-stats")]
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Returns the size class of messages of `size` bytes.
+#[cfg(feature = "size-stats")]
+fn size_class(size: usize) -> usize {
+    let mut class = 0;
+    while class < SIZE_CLASSES - 1 && size > 8 << class {
+        class += 1;
+    }
+    class
+}
+
+/// Records a message of type `T` being sent.
+#[cfg(feature = "size-stats")]
+#[inline]
+pub(crate) fn record_send<T>() {
+    let size = mem::size_of::<T>();
+    SIZE_COUNTS[size_class(size)].fetch_add(1, Ordering::Relaxed);
+    SIZE_BYTES.fetch_add(size, Ordering::Relaxed);
+
+    let threshold = THRESHOLD.load(Ordering::Relaxed);
+    if size > threshold && !WARNED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "crossbeam-channel: sending a message of {} bytes, over the threshold of {} bytes; \
+             consider boxing large enum variants",
+            size, threshold,
+        );
+    }
+}
+
+/// Records a message of type `T` being sent.
+#[cfg(not(feature = "size-stats"))]
+#[inline]
+pub(crate) fn record_send<T>() {}
+
+/// Sets the size in bytes above which sending a message prints a warning.
+///
+/// The warning is printed at most once per process. The default threshold is 1024 bytes.
+#[cfg(feature = "size-stats")]
+pub fn set_large_message_threshold(bytes: usize) {
+    THRESHOLD.store(bytes, Ordering::Relaxed);
+}
+
+/// Returns statistics of the sizes of messages sent so far.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::instrument;
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, _r) = unbounded();
+/// s.send([0u8; 100]).unwrap();
+///
+/// let sizes = instrument::message_sizes();
+/// assert!(sizes.messages() >= 1);
+/// assert!(sizes.class_counts().iter().any(|&(max, n)| max == Some(128) && n >= 1));
+/// ```
+#[cfg(feature = "size-stats")]
+pub fn message_sizes() -> MessageSizes {
+    let mut counts = [0; SIZE_CLASSES];
+    for (count, c) in counts.iter_mut().zip(SIZE_COUNTS.iter()) {
+        *count = c.load(Ordering::Relaxed);
+    }
+    MessageSizes {
+        counts,
+        bytes: SIZE_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+/// Statistics of the sizes of sent messages.
+///
+/// Returned by [`message_sizes`].
+///
+/// [`message_sizes`]: fn.message_sizes.html
+#[cfg(feature = "size-stats")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageSizes {
+    /// Number of sent messages in each size class.
+    counts: [usize; SIZE_CLASSES],
+
+    /// Total size of sent messages, in bytes.
+    bytes: usize,
+}
+
+#[cfg(feature = "size-stats")]
+impl MessageSizes {
+    /// Returns the number of sent messages.
+    pub fn messages(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the total size of sent messages, in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of sent messages in each size class.
+    ///
+    /// Each class is given by the largest message size it covers, in bytes: 8, 16, 32, and so on
+    /// up to 4096. The last class, covering everything larger, is given as `None`.
+    pub fn class_counts(&self) -> Vec<(Option<usize>, usize)> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(class, &count)| {
+                let max = if class < SIZE_CLASSES - 1 {
+                    Some(8 << class)
+                } else {
+                    None
+                };
+                (max, count)
+            })
+            .collect()
+    }
+}
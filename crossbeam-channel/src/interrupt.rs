@@ -0,0 +1,100 @@
+//! Handles for interrupting blocked operations from other threads.
+
+use std::fmt;
+
+use channel::{unbounded, Receiver, Sender};
+
+/// A handle for interrupting a blocked send or receive operation from another thread.
+///
+/// A thread that may get stuck waiting on a channel creates a handle before blocking, hands a
+/// clone of it to whoever might need to wake it up, and then blocks with
+/// [`Receiver::recv_interruptible`] or [`Sender::send_interruptible`]. Calling [`interrupt`] on
+/// any clone of the handle makes that operation return an `Interrupted` error, without closing
+/// the channel for anybody else.
+///
+/// An interrupt stays pending until it's consumed by the operation it interrupts, so it isn't
+/// lost if it arrives just before the operation starts blocking. Operations that can complete
+/// right away complete as usual, even with an interrupt pending. Interrupting several times before
+/// an operation consumes the interrupt has the same effect as interrupting once.
+///
+/// [`Receiver::recv_interruptible`]: struct.Receiver.html#method.recv_interruptible
+/// [`Sender::send_interruptible`]: struct.Sender.html#method.send_interruptible
+/// [`interrupt`]: struct.BlockHandle.html#method.interrupt
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, BlockHandle, RecvInterruptibleError};
+///
+/// let (s, r) = unbounded::<i32>();
+/// let handle = BlockHandle::new();
+///
+/// let worker = {
+///     let handle = handle.clone();
+///     thread::spawn(move || (r.recv_interruptible(&handle), r))
+/// };
+///
+/// thread::sleep(Duration::from_millis(100));
+/// handle.interrupt();
+///
+/// let (res, r) = worker.join().unwrap();
+/// assert_eq!(res, Err(RecvInterruptibleError::Interrupted));
+///
+/// // The channel is still connected.
+/// s.send(1).unwrap();
+/// assert_eq!(r.recv(), Ok(1));
+/// ```
+#[derive(Clone)]
+pub struct BlockHandle {
+    /// Sends a message to interrupt.
+    sender: Sender<()>,
+
+    /// Ready while an interrupt is pending.
+    receiver: Receiver<()>,
+}
+
+impl BlockHandle {
+    /// Creates a handle without a pending interrupt.
+    pub fn new() -> BlockHandle {
+        let (sender, receiver) = unbounded();
+        BlockHandle { sender, receiver }
+    }
+
+    /// Interrupts the operation blocked with this handle, or the next one to block with it.
+    pub fn interrupt(&self) {
+        if self.receiver.is_empty() {
+            let _ = self.sender.send(());
+        }
+    }
+
+    /// Returns `true` if an interrupt is pending.
+    pub fn is_interrupted(&self) -> bool {
+        !self.receiver.is_empty()
+    }
+
+    /// Discards the pending interrupt, if any.
+    pub fn reset(&self) {
+        while self.receiver.try_recv().is_ok() {}
+    }
+
+    /// Returns the receiver that is ready while an interrupt is pending.
+    pub(crate) fn receiver(&self) -> &Receiver<()> {
+        &self.receiver
+    }
+}
+
+impl Default for BlockHandle {
+    fn default() -> BlockHandle {
+        BlockHandle::new()
+    }
+}
+
+impl fmt::Debug for BlockHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BlockHandle")
+            .field("interrupted", &self.is_interrupted())
+            .finish()
+    }
+}
@@ -0,0 +1,108 @@
+//! Threads whose completion can be selected on.
+
+use std::fmt;
+use std::ops;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread::{self, Thread};
+
+use channel::{bounded, Receiver};
+
+/// Spawns a thread whose result is delivered through a channel.
+///
+/// The returned [`JoinReceiver`] receives a single message once the thread finishes: `Ok` with
+/// the value returned by `f`, or `Err` with the panic payload if `f` panicked. The receiver
+/// dereferences to an ordinary [`Receiver`], so a supervisor can wait for workers to finish in
+/// the same [`select!`] as its work queues and timers.
+///
+/// [`JoinReceiver`]: struct.JoinReceiver.html
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::{spawn_selectable, tick};
+///
+/// let worker = spawn_selectable(|| 1 + 2);
+/// let ticker = tick(Duration::from_millis(100));
+///
+/// loop {
+///     select! {
+///         recv(worker) -> res => {
+///             assert_eq!(res.unwrap().unwrap(), 3);
+///             break;
+///         }
+///         recv(ticker) -> _ => println!("still waiting"),
+///     }
+/// }
+/// # }
+/// ```
+pub fn spawn_selectable<F, T>(f: F) -> JoinReceiver<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (s, r) = bounded(1);
+
+    let handle = thread::spawn(move || {
+        let res = panic::catch_unwind(AssertUnwindSafe(f));
+        // The receiver might be gone, in which case nobody is interested in the result.
+        let _ = s.send(res);
+    });
+
+    JoinReceiver {
+        receiver: r,
+        thread: handle.thread().clone(),
+    }
+}
+
+/// A receiver for the result of a thread spawned by [`spawn_selectable`].
+///
+/// [`spawn_selectable`]: fn.spawn_selectable.html
+pub struct JoinReceiver<T> {
+    receiver: Receiver<thread::Result<T>>,
+    thread: Thread,
+}
+
+impl<T> JoinReceiver<T> {
+    /// Blocks until the thread finishes and returns its result.
+    ///
+    /// If the result has already been received through the channel, this method panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::spawn_selectable;
+    ///
+    /// let worker = spawn_selectable(|| panic!("oops"));
+    /// assert!(worker.join().is_err());
+    /// ```
+    pub fn join(self) -> thread::Result<T> {
+        self.receiver
+            .recv()
+            .expect("the result of the thread has already been received")
+    }
+
+    /// Returns a handle to the underlying thread.
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+}
+
+impl<T> ops::Deref for JoinReceiver<T> {
+    type Target = Receiver<thread::Result<T>>;
+
+    fn deref(&self) -> &Receiver<thread::Result<T>> {
+        &self.receiver
+    }
+}
+
+impl<T> fmt::Debug for JoinReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("JoinReceiver { .. }")
+    }
+}
@@ -0,0 +1,177 @@
+//! Channels with a control lane whose messages preempt data messages.
+//!
+//! A common pattern is to have a queue of data messages next to a queue of urgent control
+//! messages, and to always check the control queue first. A lanes channel offers this as a single
+//! channel: the sending side has separate methods for the two lanes, while the receiving side is
+//! an ordinary [`Receiver`] that always returns control messages before data messages. Since it is
+//! a single receiver, it takes up a single case in [`select!`].
+//!
+//! Messages within a lane are received in the order they were sent. Both lanes are unbounded.
+//!
+//! [`Receiver`]: ../struct.Receiver.html
+//! [`select!`]: ../macro.select.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::lanes;
+//!
+//! let (s, r) = lanes::channel();
+//!
+//! s.send_data(1).unwrap();
+//! s.send_data(2).unwrap();
+//! s.send_control(100).unwrap();
+//!
+//! assert_eq!(r.recv(), Ok(100));
+//! assert_eq!(r.recv(), Ok(1));
+//! assert_eq!(r.recv(), Ok(2));
+//! ```
+
+use std::fmt;
+
+use channel::{self, Receiver};
+use counter;
+use err::{SendError, SendTimeoutError};
+use flavors;
+
+/// The lane for control messages.
+const CONTROL: usize = 0;
+
+/// The lane for data messages.
+const DATA: usize = 1;
+
+/// Creates a channel with a control lane and a data lane.
+///
+/// Receive operations return control messages before data messages.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::{after, lanes};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Message {
+///     Stop,
+///     Data(i32),
+/// }
+///
+/// let (s, r) = lanes::channel();
+/// for i in 0..3 {
+///     s.send_data(Message::Data(i)).unwrap();
+/// }
+/// s.send_control(Message::Stop).unwrap();
+///
+/// // The stop message is received first even though it was sent last.
+/// let timeout = after(Duration::from_secs(1));
+/// select! {
+///     recv(r) -> msg => assert_eq!(msg, Ok(Message::Stop)),
+///     recv(timeout) -> _ => panic!("timed out"),
+/// }
+/// # }
+/// ```
+pub fn channel<T>() -> (LaneSender<T>, Receiver<T>) {
+    let (s, r) = channel::lanes(2);
+    (LaneSender { chan: s }, r)
+}
+
+/// The sending side of a lanes channel.
+///
+/// Created by the [`channel`] function.
+///
+/// [`channel`]: fn.channel.html
+pub struct LaneSender<T> {
+    chan: counter::Sender<flavors::lanes::Channel<T>>,
+}
+
+unsafe impl<T: Send> Send for LaneSender<T> {}
+unsafe impl<T: Send> Sync for LaneSender<T> {}
+
+impl<T> LaneSender<T> {
+    /// Sends a control message, which is received before any data messages.
+    ///
+    /// This method never blocks. If the channel is disconnected, the message is returned back in
+    /// the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{lanes, SendError};
+    ///
+    /// let (s, r) = lanes::channel();
+    /// s.send_data(1).unwrap();
+    /// s.send_control(2).unwrap();
+    /// assert_eq!(r.recv(), Ok(2));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send_control(3), Err(SendError(3)));
+    /// ```
+    pub fn send_control(&self, msg: T) -> Result<(), SendError<T>> {
+        self.send(CONTROL, msg)
+    }
+
+    /// Sends a data message, which is received after all control messages.
+    ///
+    /// This method never blocks. If the channel is disconnected, the message is returned back in
+    /// the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{lanes, SendError};
+    ///
+    /// let (s, r) = lanes::channel();
+    /// s.send_data(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send_data(2), Err(SendError(2)));
+    /// ```
+    pub fn send_data(&self, msg: T) -> Result<(), SendError<T>> {
+        self.send(DATA, msg)
+    }
+
+    /// Sends a message into the lane.
+    fn send(&self, lane: usize, msg: T) -> Result<(), SendError<T>> {
+        self.chan.send(lane, msg).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Returns the number of messages in the channel, across both lanes.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if both lanes are empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+}
+
+impl<T> Drop for LaneSender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<T> Clone for LaneSender<T> {
+    fn clone(&self) -> Self {
+        LaneSender {
+            chan: self.chan.acquire(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LaneSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = (self.chan.count(), self.chan.receiver_count());
+        channel::debug_endpoint(f, "LaneSender", &*self.chan, Some(counts))
+    }
+}
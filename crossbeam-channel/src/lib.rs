@@ -145,6 +145,55 @@
 //! # }
 //! ```
 //!
+//! Messages don't have to be `'static` either. Channels created inside a scope can carry
+//! references into the enclosing stack frame, so fork/join code can hand out pieces of a buffer
+//! without wrapping it into an `Arc`:
+//!
+//! ```
+//! # extern crate crossbeam_channel;
+//! # extern crate crossbeam_utils;
+//! # fn main() {
+//! use crossbeam_channel::unbounded;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let buffer = vec![1u64; 1 << 16];
+//! let (jobs_s, jobs_r) = unbounded::<&[u64]>();
+//! let (sums_s, sums_r) = unbounded();
+//!
+//! scope(|scope| {
+//!     for _ in 0..4 {
+//!         let (jobs_r, sums_s) = (jobs_r.clone(), sums_s.clone());
+//!         scope.spawn(move |_| {
+//!             for chunk in jobs_r {
+//!                 sums_s.send(chunk.iter().sum::<u64>()).unwrap();
+//!             }
+//!         });
+//!     }
+//!
+//!     for chunk in buffer.chunks(1024) {
+//!         jobs_s.send(chunk).unwrap();
+//!     }
+//!     drop(jobs_s);
+//! }).unwrap();
+//!
+//! drop(sums_s);
+//! assert_eq!(sums_r.iter().sum::<u64>(), 1 << 16);
+//! # }
+//! ```
+//!
+//! The borrow checker makes sure that such messages don't outlive the data they borrow:
+//!
+//! ```compile_fail
+//! use crossbeam_channel::unbounded;
+//!
+//! let (s, r) = unbounded();
+//! {
+//!     let buffer = vec![1, 2, 3];
+//!     s.send(&buffer[..]).unwrap();
+//! }
+//! r.recv().unwrap();
+//! ```
+//!
 //! # Disconnection
 //!
 //! When all senders or all receivers associated with a channel get dropped, the channel becomes
@@ -348,31 +397,213 @@
 #![warn(missing_debug_implementations)]
 
 extern crate crossbeam_utils;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(all(feature = "boottime", any(target_os = "linux", target_os = "android")))]
+extern crate libc;
+#[cfg(feature = "metrics")]
+extern crate metrics;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
+#[cfg(feature = "flavor-array")]
+mod adaptive;
+pub mod affinity;
+#[cfg(feature = "head-age")]
+mod age;
+#[cfg(all(
+    feature = "fence-audit",
+    feature = "flavor-array",
+    feature = "flavor-zero"
+))]
+pub mod audit;
+#[cfg(all(feature = "bench", feature = "flavor-array", feature = "flavor-zero"))]
+pub mod bench;
+mod block_hook;
+mod bridge;
+#[cfg(feature = "flavor-array")]
+mod budget;
+pub mod buffered;
+mod bytes;
 mod channel;
+mod channel_set;
+#[cfg(feature = "timers")]
+mod clock;
+pub mod coalesce;
 mod context;
 mod counter;
+pub mod dedup;
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+mod elastic;
 mod err;
+mod event_loop;
+mod fanout;
+#[cfg(feature = "fault-injection")]
+mod fault;
+mod fixed_buffer;
 mod flavors;
+mod group;
+mod handler_select;
+#[cfg(feature = "test-harness")]
+mod harness;
+pub mod instrument;
+mod interrupt;
+#[cfg(feature = "flavor-array")]
+mod join;
+pub mod lanes;
+#[cfg(all(
+    feature = "tests-support",
+    feature = "flavor-array",
+    feature = "flavor-zero"
+))]
+pub mod linearizability;
+#[cfg(feature = "metrics")]
+pub mod metered;
+mod nursery;
+mod observer;
+pub mod oneshot;
+mod ordering;
+pub mod poison;
+mod pollable;
+#[cfg(feature = "priority-inheritance")]
+pub mod priority;
+mod race;
+#[cfg(feature = "timers")]
+mod rate;
+mod readiness;
 mod select;
 mod select_macro;
+#[cfg(feature = "flavor-zero")]
+pub mod session;
+mod shard;
+pub mod sharded;
+mod signal;
+#[cfg(feature = "flavor-array")]
+pub mod signal_safe;
+mod stats;
+#[cfg(feature = "flavor-array")]
+pub mod sync;
+mod timed;
+#[cfg(feature = "timers")]
+mod timer;
+#[cfg(feature = "tracing")]
+pub mod traced;
+#[cfg(all(feature = "flavor-array", feature = "flavor-zero"))]
+mod upgrade;
 mod utils;
+mod waiters;
 mod waker;
 
 /// Crate internals used by the `select!` macro.
 #[doc(hidden)]
 pub mod internal {
+    pub use std::result::Result;
+
     pub use select::SelectHandle;
     pub use select::{select, select_timeout, try_select};
 }
 
-pub use channel::{after, never, tick};
-pub use channel::{bounded, unbounded};
-pub use channel::{IntoIter, Iter, TryIter};
-pub use channel::{Receiver, Sender};
+/// Debugging helpers showing what blocked threads wait on.
+///
+/// Every thread that blocks in a channel operation records which operation it is blocked in, on
+/// which channel, and until what deadline. [`dump_waiters`] prints all of them, which helps with
+/// finding out why an application got stuck.
+///
+/// This module is only available with the `debug-waiters` feature, since the bookkeeping adds
+/// some overhead to blocking operations.
+///
+/// [`dump_waiters`]: fn.dump_waiters.html
+#[cfg(feature = "debug-waiters")]
+pub mod debug {
+    pub use waiters::{dump_waiters, waiters, OperationKind, Waiter};
+}
+
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub use channel::bounded;
+#[cfg(feature = "timers")]
+pub use channel::{after, after_with_clock, tick, tick_counted, tick_with_clock};
+#[cfg(feature = "flavor-array")]
+pub use channel::{bounded_fair, bounded_with_stats};
+pub use channel::{never, unbounded};
+pub use channel::{DeadlineIntoIter, Drain, InPlace, IntoIter, Iter, TryIter};
+pub use channel::{Receiver, Sender, UniqueReceiver};
+#[cfg(feature = "timers")]
+pub use flavors::tick::Ticks;
+
+pub use select::{InFlight, Select, SelectedOperation};
+
+#[cfg(feature = "flavor-array")]
+pub use adaptive::{adaptive_bounded, AdaptiveReceiver, AdaptiveSender, CapacityDecision};
+
+#[cfg(feature = "timers")]
+pub use clock::{ClockSource, TimerClock};
+
+pub use handler_select::HandlerSelect;
+
+pub use block_hook::{clear_block_hook, set_block_hook, BlockDecision};
+
+pub use bridge::BridgedSender;
+
+#[cfg(feature = "flavor-array")]
+pub use budget::{Budget, BudgetedReceiver, BudgetedSender};
+
+pub use bytes::{ByteReceiver, ByteSender};
+
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub use elastic::{elastic_bounded, ElasticSender, Pressure};
+
+pub use event_loop::{ErrorAction, EventLoop, HandlerId, HandlerMetrics};
+
+pub use fixed_buffer::FixedBuffer;
+
+pub use fanout::{fanout, fanout_with_lag_limit, FanoutReceiver, FanoutSender};
+
+pub use group::ChannelGroup;
+
+#[cfg(feature = "test-harness")]
+pub use harness::{test_harness, TestHarness};
+
+pub use interrupt::BlockHandle;
+
+#[cfg(feature = "flavor-array")]
+pub use join::{spawn_selectable, JoinReceiver};
+
+pub use nursery::{nursery, CancelToken, Nursery};
+
+pub use pollable::{Pollable, PollableSource, ReadyNotifier};
+
+#[cfg(feature = "fault-injection")]
+pub use fault::{clear_faults, inject_recv, inject_send, RecvFault, SendFault};
+
+pub use observer::ChannelObserver;
+
+pub use race::{race, race_slice, race_slice_timeout, race_timeout, Either};
+
+#[cfg(feature = "timers")]
+pub use rate::RateLimitedSender;
+
+pub use readiness::ReadyRegistration;
+
+pub use shard::{shard, Sequenced, Shard, ShardIter};
+
+pub use signal::{DisconnectedSignal, EndpointEvent};
+
+pub use stats::OccupancyStats;
+
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub use timed::timed_bounded;
+pub use timed::{timed_unbounded, TimedReceiver, TimedSender};
+
+#[cfg(feature = "timers")]
+pub use timer::TimerService;
 
-pub use select::{Select, SelectedOperation};
+#[cfg(all(feature = "flavor-array", feature = "flavor-zero"))]
+pub use upgrade::{upgradable_bounded, UpgradableIter, UpgradableReceiver, UpgradableSender};
 
-pub use err::{ReadyTimeoutError, SelectTimeoutError, TryReadyError, TrySelectError};
-pub use err::{RecvError, RecvTimeoutError, TryRecvError};
+pub use err::TimerFullError;
+pub use err::{ReadyTimeoutError, SelectBudgetError, SelectError, SelectTimeoutError};
+pub use err::{RecvBatchTimeoutError, RecvError, RecvTimeoutError, TryRecvError};
+pub use err::{RecvInterruptibleError, SendInterruptibleError};
+pub use err::{SelectedRecvError, SelectedSendError};
 pub use err::{SendError, SendTimeoutError, TrySendError};
+pub use err::{TryReadyError, TrySelectError};
@@ -0,0 +1,422 @@
+//! Linearizability checking for channels and other queues.
+//!
+//! A concurrent queue is linearizable if every history of concurrent operations on it can be
+//! explained by executing the operations one at a time, in an order that respects real time, on
+//! a plain sequential FIFO queue. This module records such histories and checks them.
+//!
+//! [`record`] runs random non-blocking operations ([`try_send`], [`try_recv`], and [`len`]) on a
+//! [`Queue`] from several threads and records when each one was invoked and when it returned.
+//! [`check`] searches for a sequential order that explains the history. [`check_queue`] does both
+//! for many histories, and [`check_flavors`] runs it against every channel flavor, both with
+//! direct operations and through [`Select`].
+//!
+//! Implementing [`Queue`] for a type is all it takes to check it, which is also the way to
+//! validate a custom [`SelectHandle`]: implement the operations by selecting over the handle.
+//!
+//! This module is only available with the `tests-support` feature.
+//!
+//! [`record`]: fn.record.html
+//! [`check`]: fn.check.html
+//! [`check_queue`]: fn.check_queue.html
+//! [`check_flavors`]: fn.check_flavors.html
+//! [`Queue`]: trait.Queue.html
+//! [`try_send`]: trait.Queue.html#tymethod.try_send
+//! [`try_recv`]: trait.Queue.html#tymethod.try_recv
+//! [`len`]: trait.Queue.html#tymethod.len
+//! [`Select`]: ../struct.Select.html
+//! [`SelectHandle`]: ../internal/trait.SelectHandle.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::bounded;
+//! use crossbeam_channel::linearizability::check_queue;
+//!
+//! check_queue(|| bounded(2), Some(2), 7, 50).unwrap();
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+use std::error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_utils::thread::scope;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use select::Select;
+
+/// The number of threads operating on the queue in each history checked by `check_queue`.
+const THREADS: usize = 3;
+
+/// The number of operations each thread performs in each history checked by `check_queue`.
+const OPS: usize = 6;
+
+/// A queue whose non-blocking operations can be checked for linearizability.
+pub trait Queue: Sync {
+    /// Attempts to push `value` and returns `true` if it was pushed, or `false` if the queue is
+    /// full.
+    fn try_send(&self, value: u64) -> bool;
+
+    /// Attempts to pop a value, or returns `None` if the queue is empty.
+    fn try_recv(&self) -> Option<u64>;
+
+    /// Returns the number of values in the queue.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Queue for (Sender<u64>, Receiver<u64>) {
+    fn try_send(&self, value: u64) -> bool {
+        self.0.try_send(value).is_ok()
+    }
+
+    fn try_recv(&self) -> Option<u64> {
+        self.1.try_recv().ok()
+    }
+
+    fn len(&self) -> usize {
+        self.1.len()
+    }
+}
+
+/// A channel whose operations go through [`Select`] instead of the direct methods.
+///
+/// Checking this exercises the selection protocol of the channel's flavor.
+///
+/// [`Select`]: ../struct.Select.html
+#[derive(Clone, Debug)]
+pub struct ViaSelect(pub Sender<u64>, pub Receiver<u64>);
+
+impl Queue for ViaSelect {
+    fn try_send(&self, value: u64) -> bool {
+        let mut sel = Select::new();
+        sel.send(&self.0);
+        match sel.try_select() {
+            Ok(oper) => oper.send(&self.0, value).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn try_recv(&self) -> Option<u64> {
+        let mut sel = Select::new();
+        sel.recv(&self.1);
+        match sel.try_select() {
+            Ok(oper) => oper.recv(&self.1).ok(),
+            Err(_) => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.1.len()
+    }
+}
+
+/// An operation on a queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// `try_send` with the given value.
+    TrySend(u64),
+
+    /// `try_recv`.
+    TryRecv,
+
+    /// `len`.
+    Len,
+}
+
+/// The result of an operation on a queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// `try_send` returned whether the value was pushed.
+    Sent(bool),
+
+    /// `try_recv` returned a value, or `None`.
+    Received(Option<u64>),
+
+    /// `len` returned the length.
+    Len(usize),
+}
+
+/// An operation in a recorded history.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The index of the thread that performed the operation.
+    pub thread: usize,
+
+    /// The operation.
+    pub op: Op,
+
+    /// What the operation returned.
+    pub outcome: Outcome,
+
+    /// The logical time just before the operation was invoked.
+    pub invoked: usize,
+
+    /// The logical time just after the operation returned.
+    pub returned: usize,
+}
+
+/// A history that can't be explained by any sequential order of its operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The capacity of the sequential queue the history was checked against.
+    pub capacity: Option<usize>,
+
+    /// The events of the history, ordered by invocation time.
+    pub history: Vec<Event>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "history is not linearizable:")?;
+        for e in &self.history {
+            writeln!(
+                f,
+                "  [{}..{}] thread {}: {:?} -> {:?}",
+                e.invoked, e.returned, e.thread, e.op, e.outcome
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Violation {
+    fn description(&self) -> &str {
+        "history is not linearizable"
+    }
+}
+
+/// A sequential FIFO queue with an optional capacity.
+struct Model {
+    queue: VecDeque<u64>,
+    capacity: Option<usize>,
+}
+
+impl Model {
+    /// Applies an operation and returns its outcome.
+    fn apply(&mut self, op: Op) -> Outcome {
+        match op {
+            Op::TrySend(v) => match self.capacity {
+                Some(cap) if self.queue.len() >= cap => Outcome::Sent(false),
+                _ => {
+                    self.queue.push_back(v);
+                    Outcome::Sent(true)
+                }
+            },
+            Op::TryRecv => Outcome::Received(self.queue.pop_front()),
+            Op::Len => Outcome::Len(self.queue.len()),
+        }
+    }
+}
+
+/// Runs random operations on `queue` from `threads` threads, `ops` operations per thread, and
+/// returns the recorded history ordered by invocation time.
+///
+/// The `seed` determines which operations every thread performs. Sent values are unique across
+/// the history. The queue should be empty when this is called.
+pub fn record<Q: Queue>(queue: &Q, seed: u64, threads: usize, ops: usize) -> Vec<Event> {
+    let clock = AtomicUsize::new(0);
+
+    let mut history = scope(|scope| {
+        let handles = (0..threads)
+            .map(|t| {
+                let clock = &clock;
+                scope.spawn(move |_| {
+                    // Zero is the one state xorshift never leaves.
+                    let mut rng = (seed ^ t as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+                    let mut events = Vec::with_capacity(ops);
+
+                    for i in 0..ops {
+                        rng ^= rng << 13;
+                        rng ^= rng >> 7;
+                        rng ^= rng << 17;
+
+                        let op = match rng % 5 {
+                            0 | 1 => Op::TrySend((t * ops + i + 1) as u64),
+                            2 | 3 => Op::TryRecv,
+                            _ => Op::Len,
+                        };
+
+                        let invoked = clock.fetch_add(1, Ordering::SeqCst);
+                        let outcome = match op {
+                            Op::TrySend(v) => Outcome::Sent(queue.try_send(v)),
+                            Op::TryRecv => Outcome::Received(queue.try_recv()),
+                            Op::Len => Outcome::Len(queue.len()),
+                        };
+                        let returned = clock.fetch_add(1, Ordering::SeqCst);
+
+                        events.push(Event {
+                            thread: t,
+                            op,
+                            outcome,
+                            invoked,
+                            returned,
+                        });
+                    }
+                    events
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    history.sort_by_key(|e| e.invoked);
+    history
+}
+
+/// Checks whether a history is linearizable with respect to a FIFO queue of the given capacity,
+/// where `None` stands for an unbounded queue.
+///
+/// A zero capacity models a zero-capacity channel, on which non-blocking operations never
+/// succeed unless they meet a blocking operation on the other side.
+///
+/// # Panics
+///
+/// Panics if the history has more than 64 events.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::linearizability::{check, Event, Op, Outcome};
+///
+/// // A receive that returned after a send completed must not find the queue empty.
+/// let history = vec![
+///     Event { thread: 0, op: Op::TrySend(1), outcome: Outcome::Sent(true), invoked: 0, returned: 1 },
+///     Event { thread: 1, op: Op::TryRecv, outcome: Outcome::Received(None), invoked: 2, returned: 3 },
+/// ];
+/// assert!(check(None, &history).is_err());
+/// ```
+pub fn check(capacity: Option<usize>, history: &[Event]) -> Result<(), Violation> {
+    assert!(history.len() <= 64, "history has more than 64 events");
+
+    let mut search = Search {
+        history,
+        capacity,
+        visited: HashSet::new(),
+    };
+    let model = Model {
+        queue: VecDeque::new(),
+        capacity,
+    };
+
+    if search.linearize(0, model) {
+        Ok(())
+    } else {
+        let mut history = history.to_vec();
+        history.sort_by_key(|e| e.invoked);
+        Err(Violation { capacity, history })
+    }
+}
+
+/// A depth-first search for a linearization of a history.
+struct Search<'a> {
+    history: &'a [Event],
+    capacity: Option<usize>,
+
+    /// Sets of linearized events and resulting queue contents that have already been explored.
+    visited: HashSet<(u64, VecDeque<u64>)>,
+}
+
+impl<'a> Search<'a> {
+    /// Returns `true` if the events not in `done` can be linearized starting from `model`.
+    fn linearize(&mut self, done: u64, model: Model) -> bool {
+        let n = self.history.len();
+        if done.count_ones() as usize == n {
+            return true;
+        }
+        if !self.visited.insert((done, model.queue.clone())) {
+            return false;
+        }
+
+        let pending = || (0..n).filter(move |&i| done & (1 << i) == 0);
+
+        // Only events invoked before every pending event has returned can go next.
+        let first_return = pending().map(|i| self.history[i].returned).min().unwrap();
+
+        for i in pending() {
+            let e = self.history[i];
+            if e.invoked > first_return {
+                continue;
+            }
+
+            let mut next = Model {
+                queue: model.queue.clone(),
+                capacity: self.capacity,
+            };
+            if next.apply(e.op) == e.outcome && self.linearize(done | 1 << i, next) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Records `histories` histories of queues created by `new` and checks each of them against a
+/// FIFO queue of the given capacity.
+///
+/// Every history has 3 threads performing 6 operations each. The `seed` determines the
+/// operations.
+///
+/// Returns the first history that is not linearizable.
+pub fn check_queue<Q, F>(
+    mut new: F,
+    capacity: Option<usize>,
+    seed: u64,
+    histories: usize,
+) -> Result<(), Violation>
+where
+    Q: Queue,
+    F: FnMut() -> Q,
+{
+    for h in 0..histories {
+        let queue = new();
+        let history = record(&queue, seed.wrapping_add(h as u64), THREADS, OPS);
+        check(capacity, &history)?;
+    }
+    Ok(())
+}
+
+/// Checks every channel flavor with [`check_queue`], both with direct operations and through
+/// [`ViaSelect`].
+///
+/// [`check_queue`]: fn.check_queue.html
+/// [`ViaSelect`]: struct.ViaSelect.html
+///
+/// # Panics
+///
+/// Panics with the offending history if one of the flavors is not linearizable.
+pub fn check_flavors(seed: u64, histories: usize) {
+    // Capacities of the channels, where `None` stands for an unbounded channel.
+    let caps = [Some(0), Some(1), Some(3), None];
+
+    for &cap in &caps {
+        let channel = || match cap {
+            Some(cap) => bounded(cap),
+            None => unbounded(),
+        };
+
+        if let Err(err) = check_queue(channel, cap, seed, histories) {
+            panic!("{}", err);
+        }
+        if let Err(err) = check_queue(
+            || {
+                let (s, r) = channel();
+                ViaSelect(s, r)
+            },
+            cap,
+            seed,
+            histories,
+        ) {
+            panic!("{} (through `Select`)", err);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Publishing channel metrics through the [`metrics`] facade.
+//!
+//! A channel registered with [`register`] reports how many messages it holds, how many messages
+//! go through it, and how long threads stay blocked on it to the installed `metrics` recorder.
+//! Every metric is labeled with `channel` set to the name the channel was registered under:
+//!
+//! * `crossbeam_channel_length`: a gauge of the number of messages in the channel.
+//! * `crossbeam_channel_sent`: a counter of sent messages.
+//! * `crossbeam_channel_received`: a counter of received messages.
+//! * `crossbeam_channel_blocked_seconds`: a histogram of how long threads were blocked in sends
+//!   and receives on the channel.
+//!
+//! The length is updated whenever a message is sent or received. Messages sent or received by
+//! operations in [`select!`] or [`Select`] are not counted, and time spent blocked in them is not
+//! attributed to any channel.
+//!
+//! Metric handles are obtained from the recorder when the channel is registered, so the recorder
+//! has to be installed first. Channels that aren't registered pay a single atomic load per
+//! operation.
+//!
+//! This module is only available with the `metrics` feature.
+//!
+//! [`metrics`]: https://docs.rs/metrics
+//! [`register`]: fn.register.html
+//! [`select!`]: ../macro.select.html
+//! [`Select`]: ../struct.Select.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::{metered, unbounded};
+//!
+//! let (s, r) = unbounded();
+//! metered::register("jobs", &s);
+//!
+//! s.send(1).unwrap();
+//! assert_eq!(r.recv(), Ok(1));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use metrics::{self, Counter, Gauge, Histogram};
+
+use channel::Sender;
+use flavors::Introspect;
+use waiters::{self, OperationKind};
+
+/// Number of registered channels.
+///
+/// The registry is only consulted while this is nonzero.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    /// Metrics of registered channels, keyed by channel address.
+    static ref REGISTRY: RwLock<HashMap<usize, Arc<Meter>>> = RwLock::new(HashMap::new());
+}
+
+/// Metric handles of a registered channel.
+struct Meter {
+    /// The number of messages in the channel.
+    length: Gauge,
+
+    /// The number of sent messages.
+    sent: Counter,
+
+    /// The number of received messages.
+    received: Counter,
+
+    /// How long threads were blocked on the channel, in seconds.
+    blocked: Histogram,
+
+    /// Computes the length of the channel from its address.
+    len_at: unsafe fn(usize) -> usize,
+}
+
+/// Returns the length of the channel of flavor `C` at `addr`.
+pub(crate) unsafe fn len_at<C: Introspect>(addr: usize) -> usize {
+    (*(addr as *const C)).len()
+}
+
+/// Registers the channel under a name and starts publishing its metrics.
+///
+/// Registering the channel again replaces the name.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{bounded, metered};
+///
+/// let (s, r) = bounded::<i32>(16);
+/// metered::register("requests", &s);
+/// # drop(r);
+/// ```
+pub fn register<T>(name: &str, s: &Sender<T>) {
+    let labels = [("channel", name.to_string())];
+    let meter = Arc::new(Meter {
+        length: metrics::gauge!("crossbeam_channel_length", &labels),
+        sent: metrics::counter!("crossbeam_channel_sent", &labels),
+        received: metrics::counter!("crossbeam_channel_received", &labels),
+        blocked: metrics::histogram!("crossbeam_channel_blocked_seconds", &labels),
+        len_at: s.len_at(),
+    });
+    meter.length.set(s.len() as f64);
+
+    let old = REGISTRY.write().unwrap().insert(s.addr(), meter);
+    if old.is_none() {
+        ACTIVE.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Stops publishing metrics of the channel.
+///
+/// Channels are unregistered automatically when they get destroyed.
+pub fn unregister<T>(s: &Sender<T>) {
+    forget(s.addr());
+}
+
+/// Unregisters the channel at `addr`.
+///
+/// This is called when a channel gets destroyed.
+pub(crate) fn forget(addr: usize) {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return;
+    }
+
+    if REGISTRY.write().unwrap().remove(&addr).is_some() {
+        ACTIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns the metrics of the channel at `addr`, if it is registered.
+#[inline]
+fn meter(addr: usize) -> Option<Arc<Meter>> {
+    if ACTIVE.load(Ordering::SeqCst) == 0 {
+        return None;
+    }
+    REGISTRY.read().unwrap().get(&addr).cloned()
+}
+
+/// Records that a message was sent into the channel at `addr`.
+///
+/// The channel must stay alive for the duration of the call.
+#[inline]
+pub(crate) unsafe fn on_send(addr: usize) {
+    if let Some(meter) = meter(addr) {
+        meter.sent.increment(1);
+        meter.length.set((meter.len_at)(addr) as f64);
+    }
+}
+
+/// Records that a message was received from the channel at `addr`.
+///
+/// The channel must stay alive for the duration of the call.
+#[inline]
+pub(crate) unsafe fn on_recv(addr: usize) {
+    if let Some(meter) = meter(addr) {
+        meter.received.increment(1);
+        meter.length.set((meter.len_at)(addr) as f64);
+    }
+}
+
+/// Time the current thread spends parked in a send or receive, recorded when dropped.
+pub(crate) struct Wait {
+    /// The metrics of the channel and the time the thread parked.
+    started: Option<(Arc<Meter>, Instant)>,
+}
+
+impl Wait {
+    /// Starts timing if the current thread is about to park on a registered channel.
+    pub(crate) fn enter() -> Wait {
+        let meter = match waiters::current() {
+            Some((OperationKind::Send, Some(channel)))
+            | Some((OperationKind::Recv, Some(channel))) => meter(channel),
+            _ => None,
+        };
+        Wait {
+            started: meter.map(|meter| (meter, Instant::now())),
+        }
+    }
+}
+
+impl Drop for Wait {
+    fn drop(&mut self) {
+        if let Some((ref meter, start)) = self.started {
+            let waited = start.elapsed();
+            let secs = waited.as_secs() as f64 + f64::from(waited.subsec_nanos()) / 1e9;
+            meter.blocked.record(secs);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Scoped workers whose results and failures are collected in one place.
+
+use std::cell::Cell;
+use std::fmt;
+use std::ops;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_utils::thread::{scope, Scope};
+
+use channel::{unbounded, Receiver, Sender};
+use err::TryRecvError;
+use utils::Spinlock;
+
+/// Runs a group of scoped workers and collects their results.
+///
+/// The closure spawns workers with [`Nursery::spawn`]. Each worker returns a `Result`. Once the
+/// closure returns, this function waits for all workers to finish and returns their values in the
+/// order they were spawned.
+///
+/// If a worker fails, the [`CancelToken`] shared by all workers is cancelled, and this function
+/// returns the error of the first worker that failed once all workers have finished. Cancellation
+/// is cooperative: workers should check [`is_cancelled`] or select over the token, and finish
+/// early when it's cancelled. If a worker panics, the token is cancelled as well, and the panic is
+/// resumed once all workers have finished. Panics take precedence over errors.
+///
+/// Like threads spawned with [`crossbeam_utils::thread::scope`], workers can borrow from the
+/// enclosing stack frame.
+///
+/// [`Nursery::spawn`]: struct.Nursery.html#method.spawn
+/// [`CancelToken`]: struct.CancelToken.html
+/// [`is_cancelled`]: struct.CancelToken.html#method.is_cancelled
+/// [`crossbeam_utils::thread::scope`]: https://docs.rs/crossbeam-utils/*/crossbeam_utils/thread/fn.scope.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::{nursery, unbounded};
+///
+/// let (s, r) = unbounded::<u32>();
+/// let inputs = vec!["1", "2", "x"];
+///
+/// let res = nursery(|n| {
+///     for input in &inputs {
+///         n.spawn(move |_| input.parse::<u32>().map_err(|_| format!("bad input: {}", input)));
+///     }
+///
+///     // A worker that runs until it's cancelled.
+///     n.spawn(|cancel| loop {
+///         select! {
+///             recv(r) -> msg => drop(msg),
+///             recv(cancel) -> _ => return Ok(0),
+///         }
+///     });
+/// });
+///
+/// assert_eq!(res, Err("bad input: x".to_string()));
+/// # drop(s);
+/// # }
+/// ```
+pub fn nursery<'env, T, E, F>(f: F) -> Result<Vec<T>, E>
+where
+    F: FnOnce(&Nursery<'_, 'env, T, E>),
+    T: Send + 'env,
+    E: Send + 'env,
+{
+    let res = scope(|scope| {
+        let (s, r) = unbounded();
+        let n = Nursery {
+            scope,
+            results: s,
+            cancel: CancelToken::new(),
+            spawned: Cell::new(0),
+        };
+
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(&n))) {
+            // Stop the workers before the scope waits for them.
+            n.cancel.cancel();
+            panic::resume_unwind(payload);
+        }
+        collect(n.spawned.get(), &r, &n.cancel)
+    });
+
+    // Workers catch their own panics, so the scope can't fail.
+    res.unwrap()
+}
+
+/// Receives the outcomes of `count` workers.
+fn collect<T, E>(
+    count: usize,
+    results: &Receiver<Outcome<T, E>>,
+    cancel: &CancelToken,
+) -> Result<Vec<T>, E> {
+    let mut values = (0..count).map(|_| None).collect::<Vec<_>>();
+    let mut error = None;
+    let mut payload = None;
+
+    for _ in 0..count {
+        // The nursery holds a sender, so the channel can't be disconnected.
+        let (index, outcome) = results.recv().unwrap();
+        match outcome {
+            Ok(Ok(value)) => values[index] = Some(value),
+            Ok(Err(err)) => {
+                cancel.cancel();
+                if error.is_none() {
+                    error = Some(err);
+                }
+            }
+            Err(p) => {
+                cancel.cancel();
+                if payload.is_none() {
+                    payload = Some(p);
+                }
+            }
+        }
+    }
+
+    if let Some(payload) = payload {
+        panic::resume_unwind(payload);
+    }
+    match error {
+        Some(err) => Err(err),
+        None => Ok(values.into_iter().map(Option::unwrap).collect()),
+    }
+}
+
+/// The index of a worker and what it returned.
+type Outcome<T, E> = (usize, thread::Result<Result<T, E>>);
+
+/// A scope for spawning workers, created by [`nursery`].
+///
+/// [`nursery`]: fn.nursery.html
+pub struct Nursery<'scope, 'env: 'scope, T, E> {
+    /// The scope the workers run in.
+    scope: &'scope Scope<'env>,
+
+    /// Receives the outcome of every worker.
+    results: Sender<Outcome<T, E>>,
+
+    /// Cancelled once a worker fails.
+    cancel: CancelToken,
+
+    /// The number of spawned workers.
+    spawned: Cell<usize>,
+}
+
+impl<'scope, 'env, T, E> Nursery<'scope, 'env, T, E>
+where
+    T: Send + 'env,
+    E: Send + 'env,
+{
+    /// Spawns a worker.
+    ///
+    /// The worker gets the cancellation token of the nursery. Returns the index of the worker's
+    /// value in the results.
+    pub fn spawn<F>(&self, f: F) -> usize
+    where
+        F: FnOnce(&CancelToken) -> Result<T, E> + Send + 'env,
+    {
+        let index = self.spawned.get();
+        self.spawned.set(index + 1);
+
+        let results = self.results.clone();
+        let cancel = self.cancel.clone();
+        self.scope.spawn(move |_| {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| f(&cancel)));
+            let failed = match outcome {
+                Ok(ref res) => res.is_err(),
+                Err(_) => true,
+            };
+            if failed {
+                // Let the other workers know right away rather than once the failure is collected.
+                cancel.cancel();
+            }
+            let _ = results.send((index, outcome));
+        });
+        index
+    }
+
+    /// Returns the cancellation token shared by the workers.
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+}
+
+impl<'scope, 'env, T, E> fmt::Debug for Nursery<'scope, 'env, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Nursery { .. }")
+    }
+}
+
+/// A token that tells workers to stop.
+///
+/// Once cancelled, a token stays cancelled. The token dereferences to a [`Receiver`] that becomes
+/// disconnected when the token is cancelled, so it can be waited on in [`select!`] alongside other
+/// channels. The token can be cloned, and all clones refer to the same token.
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::CancelToken;
+///
+/// let token = CancelToken::new();
+/// let worker = {
+///     let token = token.clone();
+///     thread::spawn(move || {
+///         let _ = token.recv();
+///         assert!(token.is_cancelled());
+///     })
+/// };
+///
+/// token.cancel();
+/// worker.join().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct CancelToken {
+    /// Disconnected once the token is cancelled.
+    receiver: Receiver<()>,
+
+    /// The only sender of the channel, dropped on cancellation.
+    sender: Arc<Spinlock<Option<Sender<()>>>>,
+}
+
+impl CancelToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> CancelToken {
+        let (s, r) = unbounded();
+        CancelToken {
+            receiver: r,
+            sender: Arc::new(Spinlock::new(Some(s))),
+        }
+    }
+
+    /// Cancels the token.
+    ///
+    /// Cancelling a token that is already cancelled has no effect.
+    pub fn cancel(&self) {
+        let sender = self.sender.lock().take();
+        drop(sender);
+    }
+
+    /// Returns `true` if the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.receiver.try_recv() == Err(TryRecvError::Disconnected)
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> CancelToken {
+        CancelToken::new()
+    }
+}
+
+impl ops::Deref for CancelToken {
+    type Target = Receiver<()>;
+
+    fn deref(&self) -> &Receiver<()> {
+        &self.receiver
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
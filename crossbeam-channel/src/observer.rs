@@ -0,0 +1,48 @@
+//! Cheap change detection for channel endpoints.
+
+/// Observes changes on one side of a channel without blocking or registering anything.
+///
+/// Every endpoint has a version number that changes whenever an operation on that endpoint may
+/// have become possible. For a [`Receiver`], that means a message was sent or the channel got
+/// disconnected. For a [`Sender`], it means a message was received or the channel got
+/// disconnected.
+///
+/// This is useful in poll loops that periodically check many channels, for example once per
+/// frame: remember the version after draining a channel, and only touch the channel again once
+/// [`has_changed`] reports a change. Checking the version is much cheaper than building a
+/// [`Select`].
+///
+/// Versions increase over time, but may wrap around on overflow. A version might change even
+/// though no operation has become possible, but a change never goes unreported.
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`Sender`]: struct.Sender.html
+/// [`has_changed`]: trait.ChannelObserver.html#method.has_changed
+/// [`Select`]: struct.Select.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{unbounded, ChannelObserver};
+///
+/// let (s, r) = unbounded();
+/// let version = r.version();
+/// assert!(!r.has_changed(version));
+///
+/// s.send(1).unwrap();
+/// assert!(r.has_changed(version));
+///
+/// // Drain the channel and remember the new version.
+/// let version = r.version();
+/// assert_eq!(r.try_iter().collect::<Vec<_>>(), [1]);
+/// assert!(!r.has_changed(version));
+/// ```
+pub trait ChannelObserver {
+    /// Returns the current version of the endpoint.
+    fn version(&self) -> usize;
+
+    /// Returns `true` if the version has changed since it was observed as `since`.
+    fn has_changed(&self, since: usize) -> bool {
+        self.version() != since
+    }
+}
@@ -0,0 +1,306 @@
+//! Oneshot channels whose shared state lives on the stack.
+//!
+//! A [`Slot`] holds the state of a channel that carries a single message. It is created by the
+//! coordinating thread, typically as a local variable, and [`split`] into a [`Sender`] and a
+//! [`Receiver`] that borrow it. The endpoints can be handed to scoped threads, just like
+//! references to any other local variable, and the borrow checker makes sure the slot outlives
+//! them.
+//!
+//! Since nothing is reference counted, a request/response round trip doesn't allocate on the
+//! heap. Blocked threads are woken up by unparking, which doesn't allocate either. After both
+//! endpoints are dropped, the same slot can be split again for the next round trip.
+//!
+//! The sender either deposits the message and moves on with [`send`], like a oneshot channel, or
+//! waits until the receiver takes it with [`handoff`], like a zero-capacity channel.
+//!
+//! [`Slot`]: struct.Slot.html
+//! [`split`]: struct.Slot.html#method.split
+//! [`Sender`]: struct.Sender.html
+//! [`Receiver`]: struct.Receiver.html
+//! [`send`]: struct.Sender.html#method.send
+//! [`handoff`]: struct.Sender.html#method.handoff
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate crossbeam_channel;
+//! extern crate crossbeam_utils;
+//! # fn main() {
+//! use crossbeam_channel::oneshot::Slot;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let mut slot = Slot::new();
+//!
+//! for request in 0..3 {
+//!     let (s, r) = slot.split();
+//!
+//!     scope(|scope| {
+//!         scope.spawn(move |_| s.send(request * 10).unwrap());
+//!         assert_eq!(r.recv(), Ok(request * 10));
+//!     })
+//!     .unwrap();
+//! }
+//! # }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use err::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use utils::Spinlock;
+
+/// Shared state of a oneshot channel.
+struct Inner<T> {
+    /// The message, if it has been sent but not received yet.
+    msg: Option<T>,
+
+    /// `true` if a message has been sent.
+    sent: bool,
+
+    /// `true` if the sender has been dropped or has sent its message.
+    sender_gone: bool,
+
+    /// `true` if the receiver has been dropped.
+    receiver_gone: bool,
+
+    /// The thread blocked on the other side, if any.
+    ///
+    /// Only one side blocks at a time: the receiver while waiting for the message, or the sender
+    /// while waiting for the message to be taken.
+    waiter: Option<Thread>,
+}
+
+impl<T> Inner<T> {
+    /// Returns the state of a channel on which nothing has happened yet.
+    fn new() -> Inner<T> {
+        Inner {
+            msg: None,
+            sent: false,
+            sender_gone: false,
+            receiver_gone: false,
+            waiter: None,
+        }
+    }
+
+    /// Wakes up the thread blocked on the other side.
+    fn wake(&mut self) {
+        if let Some(thread) = self.waiter.take() {
+            thread.unpark();
+        }
+    }
+}
+
+/// Storage for a oneshot channel, meant to be placed on the stack.
+///
+/// See the [module-level documentation](index.html) for more.
+pub struct Slot<T> {
+    inner: Spinlock<Inner<T>>,
+}
+
+impl<T> Slot<T> {
+    /// Creates storage for a oneshot channel.
+    pub fn new() -> Slot<T> {
+        Slot {
+            inner: Spinlock::new(Inner::new()),
+        }
+    }
+
+    /// Creates a fresh channel in this slot and returns its two endpoints.
+    ///
+    /// A message left over from a previous use of the slot is dropped.
+    pub fn split(&mut self) -> (Sender<T>, Receiver<T>) {
+        *self.inner.lock() = Inner::new();
+
+        let s = Sender {
+            slot: self,
+            _marker: PhantomData,
+        };
+        let r = Receiver {
+            slot: self,
+            _marker: PhantomData,
+        };
+        (s, r)
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Slot<T> {
+        Slot::new()
+    }
+}
+
+impl<T> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Slot { .. }")
+    }
+}
+
+/// The sending side of a oneshot channel.
+///
+/// Created by [`Slot::split`].
+///
+/// [`Slot::split`]: struct.Slot.html#method.split
+pub struct Sender<'a, T: 'a> {
+    slot: &'a Slot<T>,
+
+    /// Endpoints are moved between threads, but not shared.
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<'a, T: Send> Send for Sender<'a, T> {}
+
+impl<'a, T> Sender<'a, T> {
+    /// Sends the message without waiting for it to be received.
+    ///
+    /// If the receiver has been dropped, the message is returned back in the error.
+    pub fn send(self, msg: T) -> Result<(), SendError<T>> {
+        let mut inner = self.slot.inner.lock();
+        if inner.receiver_gone {
+            return Err(SendError(msg));
+        }
+
+        inner.msg = Some(msg);
+        inner.sent = true;
+        inner.wake();
+        Ok(())
+    }
+
+    /// Sends the message and blocks the current thread until the receiver takes it.
+    ///
+    /// If the receiver is dropped before taking the message, the message is returned back in the
+    /// error.
+    pub fn handoff(self, msg: T) -> Result<(), SendError<T>> {
+        {
+            let mut inner = self.slot.inner.lock();
+            if inner.receiver_gone {
+                return Err(SendError(msg));
+            }
+
+            inner.msg = Some(msg);
+            inner.sent = true;
+            inner.wake();
+        }
+
+        loop {
+            {
+                let mut inner = self.slot.inner.lock();
+                if inner.msg.is_none() {
+                    return Ok(());
+                }
+                if inner.receiver_gone {
+                    let msg = inner.msg.take().unwrap();
+                    return Err(SendError(msg));
+                }
+                inner.waiter = Some(thread::current());
+            }
+            thread::park();
+        }
+    }
+}
+
+impl<'a, T> Drop for Sender<'a, T> {
+    fn drop(&mut self) {
+        let mut inner = self.slot.inner.lock();
+        inner.sender_gone = true;
+        inner.wake();
+    }
+}
+
+impl<'a, T> fmt::Debug for Sender<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+/// The receiving side of a oneshot channel.
+///
+/// Created by [`Slot::split`].
+///
+/// [`Slot::split`]: struct.Slot.html#method.split
+pub struct Receiver<'a, T: 'a> {
+    slot: &'a Slot<T>,
+
+    /// Endpoints are moved between threads, but not shared.
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<'a, T: Send> Send for Receiver<'a, T> {}
+
+impl<'a, T> Receiver<'a, T> {
+    /// Attempts to receive the message without blocking.
+    ///
+    /// Once the message has been received, or the sender has been dropped without sending it,
+    /// the channel is disconnected.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut inner = self.slot.inner.lock();
+        match inner.msg.take() {
+            Some(msg) => {
+                // Let a sender blocked in `handoff` know that the message was taken.
+                inner.wake();
+                Ok(msg)
+            }
+            None if inner.sent || inner.sender_gone => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Blocks the current thread until the message is received or the sender is dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for the message to be received, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_until(Some(Instant::now() + timeout))
+    }
+
+    /// Waits for the message until the optional deadline.
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        loop {
+            {
+                let mut inner = self.slot.inner.lock();
+                if let Some(msg) = inner.msg.take() {
+                    inner.wake();
+                    return Ok(msg);
+                }
+                if inner.sent || inner.sender_gone {
+                    return Err(RecvTimeoutError::Disconnected);
+                }
+                if let Some(d) = deadline {
+                    if Instant::now() >= d {
+                        inner.waiter = None;
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                }
+                inner.waiter = Some(thread::current());
+            }
+
+            // Spurious wakeups are fine because the state is checked again.
+            match deadline {
+                None => thread::park(),
+                Some(d) => {
+                    let now = Instant::now();
+                    if now < d {
+                        thread::park_timeout(d - now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T> Drop for Receiver<'a, T> {
+    fn drop(&mut self) {
+        let mut inner = self.slot.inner.lock();
+        inner.receiver_gone = true;
+        inner.wake();
+    }
+}
+
+impl<'a, T> fmt::Debug for Receiver<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
@@ -0,0 +1,36 @@
+//! Memory orderings and scheduling points used by the channel protocols.
+//!
+//! The flavors, wakers, and contexts import `Ordering` from this module rather than from
+//! `std::sync::atomic`. Normally it's the standard type itself, but with the `strict-ordering`
+//! feature every ordering becomes `SeqCst`. If a bug goes away with the feature enabled, it's
+//! likely caused by an ordering that is too weak, which helps narrow down bugs that only show up
+//! on weakly ordered architectures like ARM.
+
+#[cfg(not(feature = "strict-ordering"))]
+pub use std::sync::atomic::Ordering;
+
+/// Stand-in for `std::sync::atomic::Ordering` that makes every ordering sequentially consistent.
+#[cfg(feature = "strict-ordering")]
+pub struct Ordering;
+
+#[cfg(feature = "strict-ordering")]
+#[allow(non_upper_case_globals)]
+impl Ordering {
+    pub const Relaxed: ::std::sync::atomic::Ordering = ::std::sync::atomic::Ordering::SeqCst;
+    pub const Release: ::std::sync::atomic::Ordering = ::std::sync::atomic::Ordering::SeqCst;
+    pub const Acquire: ::std::sync::atomic::Ordering = ::std::sync::atomic::Ordering::SeqCst;
+    pub const AcqRel: ::std::sync::atomic::Ordering = ::std::sync::atomic::Ordering::SeqCst;
+    pub const SeqCst: ::std::sync::atomic::Ordering = ::std::sync::atomic::Ordering::SeqCst;
+}
+
+/// Marks a point in a protocol where another thread may interleave.
+///
+/// With the `fence-audit` feature, the current thread may get rescheduled here while running
+/// under [`audit::with_random_yields`]. Otherwise, this does nothing.
+///
+/// [`audit::with_random_yields`]: ../audit/fn.with_random_yields.html
+#[inline(always)]
+pub fn yield_point() {
+    #[cfg(feature = "fence-audit")]
+    ::audit::yield_point();
+}
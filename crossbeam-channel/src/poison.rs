@@ -0,0 +1,543 @@
+//! Channels that get poisoned when a consumer panics while handling a message.
+//!
+//! This mirrors mutex poisoning. A message received from a [`PoisonReceiver`] comes wrapped in a
+//! [`Delivery`] guard. If the guard is dropped while its thread is panicking, the consumer failed
+//! halfway through handling the message and the channel gets poisoned. From then on, sending and
+//! receiving fail with a `Poisoned` error until the poison is cleared with [`clear_poison`], so a
+//! data pipeline doesn't silently carry on after one of its workers lost a message.
+//!
+//! Handling is complete when the guard is dropped normally or unwrapped with
+//! [`Delivery::into_inner`]. A consumer that unwraps the message right away gives up the
+//! protection.
+//!
+//! Operations that are already blocked when the channel gets poisoned are not interrupted.
+//!
+//! [`PoisonReceiver`]: struct.PoisonReceiver.html
+//! [`Delivery`]: struct.Delivery.html
+//! [`clear_poison`]: struct.PoisonReceiver.html#method.clear_poison
+//! [`Delivery::into_inner`]: struct.Delivery.html#method.into_inner
+//!
+//! # Examples
+//!
+//! ```
+//! use std::thread;
+//! use crossbeam_channel::poison::{self, RecvError};
+//!
+//! let (s, r) = poison::unbounded();
+//! s.send(1).unwrap();
+//! s.send(2).unwrap();
+//!
+//! let worker = r.clone();
+//! let result = thread::spawn(move || {
+//!     let msg = worker.recv().unwrap();
+//!     if *msg == 1 {
+//!         panic!("failed to handle {}", *msg);
+//!     }
+//! })
+//! .join();
+//! assert!(result.is_err());
+//!
+//! // The second message is held back until somebody looks into the failure.
+//! assert!(r.is_poisoned());
+//! assert_eq!(r.recv().unwrap_err(), RecvError::Poisoned);
+//!
+//! r.clear_poison();
+//! assert_eq!(*r.recv().unwrap(), 2);
+//! ```
+
+use std::error;
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use channel::{self, Receiver, Sender};
+use err;
+
+/// Creates a poisoning channel of bounded capacity.
+///
+/// See [`bounded`] for the behavior of the channel.
+///
+/// [`bounded`]: ../fn.bounded.html
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub fn bounded<T>(cap: usize) -> (PoisonSender<T>, PoisonReceiver<T>) {
+    let (s, r) = channel::bounded(cap);
+    wrap(s, r)
+}
+
+/// Creates a poisoning channel of unbounded capacity.
+///
+/// See [`unbounded`] for the behavior of the channel.
+///
+/// [`unbounded`]: ../fn.unbounded.html
+pub fn unbounded<T>() -> (PoisonSender<T>, PoisonReceiver<T>) {
+    let (s, r) = channel::unbounded();
+    wrap(s, r)
+}
+
+/// Wraps both sides of a channel, sharing one poison flag.
+fn wrap<T>(sender: Sender<T>, receiver: Receiver<T>) -> (PoisonSender<T>, PoisonReceiver<T>) {
+    let poisoned = Arc::new(AtomicBool::new(false));
+    let s = PoisonSender {
+        sender,
+        poisoned: poisoned.clone(),
+    };
+    let r = PoisonReceiver { receiver, poisoned };
+    (s, r)
+}
+
+/// The sending side of a poisoning channel.
+pub struct PoisonSender<T> {
+    sender: Sender<T>,
+    poisoned: Arc<AtomicBool>,
+}
+
+impl<T> PoisonSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// See [`Sender::try_send`].
+    ///
+    /// [`Sender::try_send`]: ../struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.is_poisoned() {
+            return Err(TrySendError::Poisoned(msg));
+        }
+        self.sender.try_send(msg).map_err(|err| match err {
+            err::TrySendError::Full(msg) => TrySendError::Full(msg),
+            err::TrySendError::Disconnected(msg) => TrySendError::Disconnected(msg),
+        })
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// See [`Sender::send`].
+    ///
+    /// [`Sender::send`]: ../struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        if self.is_poisoned() {
+            return Err(SendError::Poisoned(msg));
+        }
+        self.sender
+            .send(msg)
+            .map_err(|err::SendError(msg)| SendError::Disconnected(msg))
+    }
+
+    /// Returns `true` if the channel is poisoned.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    /// Clears the poison, so that operations on the channel succeed again.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::SeqCst);
+    }
+}
+
+impl<T> Clone for PoisonSender<T> {
+    fn clone(&self) -> PoisonSender<T> {
+        PoisonSender {
+            sender: self.sender.clone(),
+            poisoned: self.poisoned.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PoisonSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PoisonSender")
+            .field("poisoned", &self.is_poisoned())
+            .finish()
+    }
+}
+
+/// The receiving side of a poisoning channel.
+pub struct PoisonReceiver<T> {
+    receiver: Receiver<T>,
+    poisoned: Arc<AtomicBool>,
+}
+
+impl<T> PoisonReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// See [`Receiver::try_recv`].
+    ///
+    /// [`Receiver::try_recv`]: ../struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<Delivery<T>, TryRecvError> {
+        if self.is_poisoned() {
+            return Err(TryRecvError::Poisoned);
+        }
+        match self.receiver.try_recv() {
+            Ok(msg) => Ok(self.deliver(msg)),
+            Err(err::TryRecvError::Empty) => Err(TryRecvError::Empty),
+            Err(err::TryRecvError::Disconnected) => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    ///
+    /// See [`Receiver::recv`].
+    ///
+    /// [`Receiver::recv`]: ../struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<Delivery<T>, RecvError> {
+        if self.is_poisoned() {
+            return Err(RecvError::Poisoned);
+        }
+        match self.receiver.recv() {
+            Ok(msg) => Ok(self.deliver(msg)),
+            Err(err::RecvError) => Err(RecvError::Disconnected),
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`].
+    ///
+    /// [`Receiver::recv_timeout`]: ../struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Delivery<T>, RecvTimeoutError> {
+        if self.is_poisoned() {
+            return Err(RecvTimeoutError::Poisoned);
+        }
+        match self.receiver.recv_timeout(timeout) {
+            Ok(msg) => Ok(self.deliver(msg)),
+            Err(err::RecvTimeoutError::Timeout) => Err(RecvTimeoutError::Timeout),
+            Err(err::RecvTimeoutError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+        }
+    }
+
+    /// Returns `true` if the channel is poisoned.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Ordering::SeqCst)
+    }
+
+    /// Clears the poison, so that operations on the channel succeed again.
+    pub fn clear_poison(&self) {
+        self.poisoned.store(false, Ordering::SeqCst);
+    }
+
+    /// Wraps a received message into a guard.
+    fn deliver(&self, msg: T) -> Delivery<T> {
+        Delivery {
+            msg: ManuallyDrop::new(msg),
+            poisoned: self.poisoned.clone(),
+        }
+    }
+}
+
+impl<T> Clone for PoisonReceiver<T> {
+    fn clone(&self) -> PoisonReceiver<T> {
+        PoisonReceiver {
+            receiver: self.receiver.clone(),
+            poisoned: self.poisoned.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PoisonReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PoisonReceiver")
+            .field("poisoned", &self.is_poisoned())
+            .finish()
+    }
+}
+
+/// A message received from a poisoning channel.
+///
+/// The message is being handled for as long as the guard is alive. If the guard is dropped while
+/// the thread is panicking, the channel gets poisoned.
+pub struct Delivery<T> {
+    msg: ManuallyDrop<T>,
+    poisoned: Arc<AtomicBool>,
+}
+
+impl<T> Delivery<T> {
+    /// Unwraps the message, marking its handling as complete.
+    ///
+    /// A panic after this point no longer poisons the channel.
+    pub fn into_inner(self) -> T {
+        // The guard implements `Drop`, so its fields have to be moved out by hand.
+        let delivery = ManuallyDrop::new(self);
+        unsafe {
+            drop(ptr::read(&delivery.poisoned));
+            ptr::read(&*delivery.msg)
+        }
+    }
+}
+
+impl<T> Deref for Delivery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.msg
+    }
+}
+
+impl<T> DerefMut for Delivery<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.msg
+    }
+}
+
+impl<T> Drop for Delivery<T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.poisoned.store(true, Ordering::SeqCst);
+        }
+        unsafe { ManuallyDrop::drop(&mut self.msg) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Delivery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Delivery").field("msg", &*self.msg).finish()
+    }
+}
+
+/// An error returned from the [`send`] method of a poisoning channel.
+///
+/// The error contains the message so it can be recovered.
+///
+/// [`send`]: struct.PoisonSender.html#method.send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendError<T> {
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+
+    /// The message could not be sent because the channel is poisoned.
+    Poisoned(T),
+}
+
+/// An error returned from the [`try_send`] method of a poisoning channel.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`try_send`]: struct.PoisonSender.html#method.try_send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The message could not be sent because the channel is full.
+    Full(T),
+
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+
+    /// The message could not be sent because the channel is poisoned.
+    Poisoned(T),
+}
+
+/// An error returned from the [`recv`] method of a poisoning channel.
+///
+/// [`recv`]: struct.PoisonReceiver.html#method.recv
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvError {
+    /// A message could not be received because the channel is empty and disconnected.
+    Disconnected,
+
+    /// A message could not be received because the channel is poisoned.
+    Poisoned,
+}
+
+/// An error returned from the [`try_recv`] method of a poisoning channel.
+///
+/// [`try_recv`]: struct.PoisonReceiver.html#method.try_recv
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryRecvError {
+    /// A message could not be received because the channel is empty.
+    Empty,
+
+    /// A message could not be received because the channel is empty and disconnected.
+    Disconnected,
+
+    /// A message could not be received because the channel is poisoned.
+    Poisoned,
+}
+
+/// An error returned from the [`recv_timeout`] method of a poisoning channel.
+///
+/// [`recv_timeout`]: struct.PoisonReceiver.html#method.recv_timeout
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvTimeoutError {
+    /// A message could not be received because the channel is empty and the operation timed out.
+    Timeout,
+
+    /// A message could not be received because the channel is empty and disconnected.
+    Disconnected,
+
+    /// A message could not be received because the channel is poisoned.
+    Poisoned,
+}
+
+impl<T> SendError<T> {
+    /// Unwraps the message.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Disconnected(v) => v,
+            SendError::Poisoned(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is poisoned.
+    pub fn is_poisoned(&self) -> bool {
+        match self {
+            SendError::Poisoned(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendError::Disconnected(..) => "Disconnected(..)".fmt(f),
+            SendError::Poisoned(..) => "Poisoned(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SendError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+            SendError::Poisoned(..) => "sending on a poisoned channel".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for SendError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            SendError::Disconnected(..) => "sending on a disconnected channel",
+            SendError::Poisoned(..) => "sending on a poisoned channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl<T> TrySendError<T> {
+    /// Unwraps the message.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(v) => v,
+            TrySendError::Disconnected(v) => v,
+            TrySendError::Poisoned(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is poisoned.
+    pub fn is_poisoned(&self) -> bool {
+        match self {
+            TrySendError::Poisoned(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrySendError::Full(..) => "Full(..)".fmt(f),
+            TrySendError::Disconnected(..) => "Disconnected(..)".fmt(f),
+            TrySendError::Poisoned(..) => "Poisoned(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TrySendError::Full(..) => "sending on a full channel".fmt(f),
+            TrySendError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+            TrySendError::Poisoned(..) => "sending on a poisoned channel".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for TrySendError<T> {
+    fn description(&self) -> &str {
+        match *self {
+            TrySendError::Full(..) => "sending on a full channel",
+            TrySendError::Disconnected(..) => "sending on a disconnected channel",
+            TrySendError::Poisoned(..) => "sending on a poisoned channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvError::Disconnected => "receiving on an empty and disconnected channel".fmt(f),
+            RecvError::Poisoned => "receiving on a poisoned channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvError::Disconnected => "receiving on an empty and disconnected channel",
+            RecvError::Poisoned => "receiving on a poisoned channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TryRecvError::Empty => "receiving on an empty channel".fmt(f),
+            TryRecvError::Disconnected => "receiving on an empty and disconnected channel".fmt(f),
+            TryRecvError::Poisoned => "receiving on a poisoned channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for TryRecvError {
+    fn description(&self) -> &str {
+        match *self {
+            TryRecvError::Empty => "receiving on an empty channel",
+            TryRecvError::Disconnected => "receiving on an empty and disconnected channel",
+            TryRecvError::Poisoned => "receiving on a poisoned channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecvTimeoutError::Timeout => "timed out waiting on receive operation".fmt(f),
+            RecvTimeoutError::Disconnected => "channel is empty and disconnected".fmt(f),
+            RecvTimeoutError::Poisoned => "receiving on a poisoned channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvTimeoutError {
+    fn description(&self) -> &str {
+        match *self {
+            RecvTimeoutError::Timeout => "timed out waiting on receive operation",
+            RecvTimeoutError::Disconnected => "channel is empty and disconnected",
+            RecvTimeoutError::Poisoned => "receiving on a poisoned channel",
+        }
+    }
+
+    fn cause(&self) -> Option<&dyn error::Error> {
+        None
+    }
+}
@@ -0,0 +1,199 @@
+//! Adapter for waiting on third-party readiness sources in a select.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use context::Context;
+use select::{Operation, SelectHandle, Token};
+use waker::SyncWaker;
+
+/// A source of readiness events from outside this crate, like a socket registered with an event
+/// loop.
+///
+/// A source must be able to tell whether it is ready right now, and to invoke a callback
+/// whenever it becomes ready. The callback is edge-triggered: it only has to be invoked when the
+/// source goes from not ready to ready, and it may be invoked spuriously.
+///
+/// Wrapping a source into a [`Pollable`] makes it possible to wait for it with [`Select`],
+/// alongside channel operations.
+///
+/// [`Pollable`]: struct.Pollable.html
+/// [`Select`]: struct.Select.html
+pub trait PollableSource {
+    /// Returns `true` if the source is ready.
+    fn is_ready(&self) -> bool;
+
+    /// Installs the notifier that must be invoked whenever the source becomes ready.
+    ///
+    /// This method is called once, when the source is wrapped into a [`Pollable`].
+    ///
+    /// [`Pollable`]: struct.Pollable.html
+    fn set_notifier(&self, notifier: ReadyNotifier);
+}
+
+/// Wakes up threads waiting for a [`Pollable`] when its source becomes ready.
+///
+/// Handed to the source by [`PollableSource::set_notifier`].
+///
+/// [`Pollable`]: struct.Pollable.html
+/// [`PollableSource::set_notifier`]: trait.PollableSource.html#tymethod.set_notifier
+#[derive(Clone)]
+pub struct ReadyNotifier {
+    waker: Arc<SyncWaker>,
+}
+
+impl ReadyNotifier {
+    /// Notifies waiting threads that the source has become ready.
+    ///
+    /// This is cheap if no thread is waiting.
+    pub fn notify(&self) {
+        self.waker.notify();
+    }
+}
+
+impl fmt::Debug for ReadyNotifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ReadyNotifier { .. }")
+    }
+}
+
+/// A readiness source that can be waited on with [`Select`].
+///
+/// Operations on pollable sources are added with [`Select::poll`]. When one is selected, it must
+/// be completed with [`SelectedOperation::poll`], after which the source itself can be used, for
+/// example to read from a socket without blocking.
+///
+/// [`Select`]: struct.Select.html
+/// [`Select::poll`]: struct.Select.html#method.poll
+/// [`SelectedOperation::poll`]: struct.SelectedOperation.html#method.poll
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::{Arc, Mutex};
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, Pollable, PollableSource, ReadyNotifier, Select};
+///
+/// // A flag that gets raised by another thread.
+/// #[derive(Default)]
+/// struct Flag {
+///     raised: AtomicBool,
+///     notifier: Mutex<Option<ReadyNotifier>>,
+/// }
+///
+/// impl Flag {
+///     fn raise(&self) {
+///         self.raised.store(true, Ordering::SeqCst);
+///         if let Some(notifier) = &*self.notifier.lock().unwrap() {
+///             notifier.notify();
+///         }
+///     }
+/// }
+///
+/// impl<'a> PollableSource for &'a Flag {
+///     fn is_ready(&self) -> bool {
+///         self.raised.load(Ordering::SeqCst)
+///     }
+///
+///     fn set_notifier(&self, notifier: ReadyNotifier) {
+///         *self.notifier.lock().unwrap() = Some(notifier);
+///     }
+/// }
+///
+/// let flag = Arc::new(Flag::default());
+/// let pollable = Pollable::new(&*flag);
+/// let (_s, r) = unbounded::<i32>();
+///
+/// let flag = flag.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     flag.raise();
+/// });
+///
+/// let mut sel = Select::new();
+/// sel.recv(&r);
+/// let index = sel.poll(&pollable);
+///
+/// let oper = sel.select();
+/// assert_eq!(oper.index(), index);
+/// oper.poll(&pollable);
+/// ```
+pub struct Pollable<S> {
+    /// The wrapped source.
+    source: S,
+
+    /// Threads waiting for the source to become ready.
+    waker: Arc<SyncWaker>,
+}
+
+impl<S: PollableSource> Pollable<S> {
+    /// Wraps a readiness source and installs its notifier.
+    pub fn new(source: S) -> Pollable<S> {
+        let waker = Arc::new(SyncWaker::new());
+        source.set_notifier(ReadyNotifier {
+            waker: waker.clone(),
+        });
+        Pollable { source, waker }
+    }
+
+    /// Returns a reference to the wrapped source.
+    pub fn get_ref(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns `true` if the source is ready.
+    pub fn is_ready(&self) -> bool {
+        self.source.is_ready()
+    }
+}
+
+impl<S> fmt::Debug for Pollable<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Pollable { .. }")
+    }
+}
+
+impl<S: PollableSource> SelectHandle for Pollable<S> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        if self.source.is_ready() {
+            token.pollable = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // Register before checking, so that a notification in between isn't missed.
+        self.waker.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.waker.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.source.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.waker.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.waker.unwatch(oper);
+    }
+}
@@ -0,0 +1,201 @@
+//! Priority inheritance from blocked senders to the consumer of a channel.
+//!
+//! When a high-priority thread blocks sending into a full channel that is drained by a
+//! low-priority consumer, the sender effectively runs at the consumer's priority, which is a
+//! priority inversion. With this module, a consumer thread registers itself as the consumer of a
+//! channel with [`register_consumer`], and a [`PriorityHook`] installed with [`set_priority_hook`]
+//! is told whenever a sender parks waiting on that channel, and again when it wakes up. The hook
+//! can then temporarily raise the consumer's OS priority to that of the sender.
+//!
+//! Adjusting thread priorities is platform-specific and usually needs privileges, so the crate
+//! leaves it to the hook, which typically keeps a map from `ThreadId` to a native thread handle
+//! or ID recorded when the consumer registers.
+//!
+//! Only blocking [`Sender::send`] and [`Sender::send_timeout`] calls are reported, not sends in
+//! a [`Select`]. This module is only available with the `priority-inheritance` feature.
+//!
+//! [`register_consumer`]: fn.register_consumer.html
+//! [`PriorityHook`]: trait.PriorityHook.html
+//! [`set_priority_hook`]: fn.set_priority_hook.html
+//! [`Sender::send`]: ../struct.Sender.html#method.send
+//! [`Sender::send_timeout`]: ../struct.Sender.html#method.send_timeout
+//! [`Select`]: ../struct.Select.html
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::thread::{self, Thread};
+//! use std::time::Duration;
+//! use crossbeam_channel::bounded;
+//! use crossbeam_channel::priority::{self, PriorityHook};
+//!
+//! static BOOSTS: AtomicUsize = AtomicUsize::new(0);
+//!
+//! struct CountBoosts;
+//!
+//! impl PriorityHook for CountBoosts {
+//!     fn boost(&self, _consumer: &Thread, _sender: &Thread) {
+//!         // A real hook would raise the consumer's OS priority here.
+//!         BOOSTS.fetch_add(1, Ordering::SeqCst);
+//!     }
+//!
+//!     fn restore(&self, _consumer: &Thread, _sender: &Thread) {}
+//! }
+//!
+//! priority::set_priority_hook(CountBoosts);
+//!
+//! let (s, r) = bounded(1);
+//! let consumer = thread::spawn(move || {
+//!     let _registration = priority::register_consumer(&r);
+//!     thread::sleep(Duration::from_millis(500));
+//!     r.iter().count()
+//! });
+//!
+//! thread::sleep(Duration::from_millis(100));
+//! s.send(1).unwrap();
+//! // The channel is full, so this blocks and boosts the consumer.
+//! s.send(2).unwrap();
+//! drop(s);
+//!
+//! assert_eq!(consumer.join().unwrap(), 2);
+//! assert!(BOOSTS.load(Ordering::SeqCst) >= 1);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, Thread};
+
+use channel::Receiver;
+use waiters::{self, OperationKind};
+
+/// A hook that adjusts the priority of consumer threads while senders wait on them.
+///
+/// Installed with [`set_priority_hook`]. Every call to [`boost`] is followed by exactly one call to
+/// [`restore`] with the same threads, once the sender wakes up. Several senders can wait on the
+/// same consumer at the same time, so a hook that changes priorities should count boosts per
+/// consumer, or remember the original priority only on the first one.
+///
+/// Both methods are called on the sender's thread, just before it parks and just after it wakes
+/// up, so they should be quick.
+///
+/// [`set_priority_hook`]: fn.set_priority_hook.html
+/// [`boost`]: trait.PriorityHook.html#tymethod.boost
+/// [`restore`]: trait.PriorityHook.html#tymethod.restore
+pub trait PriorityHook: Send + Sync {
+    /// Called when `sender` parks waiting for `consumer` to make room in a channel.
+    fn boost(&self, consumer: &Thread, sender: &Thread);
+
+    /// Called when `sender` wakes up again, undoing the matching call to `boost`.
+    fn restore(&self, consumer: &Thread, sender: &Thread);
+}
+
+lazy_static! {
+    /// The installed hook.
+    static ref HOOK: RwLock<Option<Arc<dyn PriorityHook>>> = RwLock::new(None);
+
+    /// Maps channel addresses to their registered consumer threads.
+    static ref CONSUMERS: Mutex<HashMap<usize, Thread>> = Mutex::new(HashMap::new());
+}
+
+/// Installs the hook told about senders waiting on registered consumers.
+///
+/// The hook replaces any previously installed one. Senders that are already waiting still call
+/// `restore` on the hook that boosted them.
+pub fn set_priority_hook<H: PriorityHook + 'static>(hook: H) {
+    *HOOK.write().unwrap() = Some(Arc::new(hook));
+}
+
+/// Removes the installed hook, if any.
+pub fn clear_priority_hook() {
+    *HOOK.write().unwrap() = None;
+}
+
+/// Registers the current thread as the consumer of the channel `r` belongs to.
+///
+/// The registration lasts until the returned guard is dropped. Registering another consumer
+/// replaces the previous one. Channels created by [`never`] have no consumer to register, so the
+/// registration does nothing for them.
+///
+/// [`never`]: ../fn.never.html
+pub fn register_consumer<T>(r: &Receiver<T>) -> ConsumerRegistration {
+    let channel = r.addr();
+    if let Some(channel) = channel {
+        CONSUMERS.lock().unwrap().insert(channel, thread::current());
+    }
+    ConsumerRegistration {
+        channel,
+        thread: thread::current(),
+    }
+}
+
+/// Returns the thread registered as the consumer of the channel `r` belongs to, if any.
+pub fn consumer<T>(r: &Receiver<T>) -> Option<Thread> {
+    let channel = r.addr()?;
+    CONSUMERS.lock().unwrap().get(&channel).cloned()
+}
+
+/// A registration of the current thread as the consumer of a channel.
+///
+/// Created by [`register_consumer`]. Dropping it removes the registration, unless another thread
+/// has registered as the consumer since.
+///
+/// [`register_consumer`]: fn.register_consumer.html
+pub struct ConsumerRegistration {
+    channel: Option<usize>,
+    thread: Thread,
+}
+
+impl Drop for ConsumerRegistration {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel {
+            let mut consumers = CONSUMERS.lock().unwrap();
+            if consumers.get(&channel).map(|t| t.id()) == Some(self.thread.id()) {
+                consumers.remove(&channel);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ConsumerRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ConsumerRegistration { .. }")
+    }
+}
+
+/// A boost of a consumer on behalf of the current thread, restored when dropped.
+pub(crate) struct Boost {
+    boosted: Option<(Arc<dyn PriorityHook>, Thread, Thread)>,
+}
+
+impl Boost {
+    /// Boosts the consumer of the channel the current thread is about to park on in a send.
+    pub(crate) fn enter(sender: &Thread) -> Boost {
+        let channel = match waiters::current() {
+            Some((OperationKind::Send, Some(channel))) => channel,
+            _ => return Boost { boosted: None },
+        };
+        let hook = match *HOOK.read().unwrap() {
+            Some(ref hook) => hook.clone(),
+            None => return Boost { boosted: None },
+        };
+        let consumer = match CONSUMERS.lock().unwrap().get(&channel) {
+            Some(consumer) if consumer.id() != sender.id() => consumer.clone(),
+            _ => return Boost { boosted: None },
+        };
+
+        hook.boost(&consumer, sender);
+        Boost {
+            boosted: Some((hook, consumer, sender.clone())),
+        }
+    }
+}
+
+impl Drop for Boost {
+    fn drop(&mut self) {
+        if let Some((hook, consumer, sender)) = self.boosted.take() {
+            hook.restore(&consumer, &sender);
+        }
+    }
+}
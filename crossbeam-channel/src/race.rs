@@ -0,0 +1,195 @@
+//! Receiving from whichever of several receivers is ready first.
+
+use std::time::Duration;
+
+use channel::Receiver;
+use err::{RecvError, SelectTimeoutError};
+use select::{Select, SelectedOperation};
+
+/// One of two values, returned by [`race`] and [`race_timeout`].
+///
+/// [`race`]: fn.race.html
+/// [`race_timeout`]: fn.race_timeout.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// A value from the first receiver.
+    Left(L),
+
+    /// A value from the second receiver.
+    Right(R),
+}
+
+impl<L, R> Either<L, R> {
+    /// Returns `true` if this is a `Left` value.
+    pub fn is_left(&self) -> bool {
+        match self {
+            Either::Left(_) => true,
+            Either::Right(_) => false,
+        }
+    }
+
+    /// Returns `true` if this is a `Right` value.
+    pub fn is_right(&self) -> bool {
+        !self.is_left()
+    }
+
+    /// Returns the `Left` value, if this is one.
+    pub fn left(self) -> Option<L> {
+        match self {
+            Either::Left(l) => Some(l),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Returns the `Right` value, if this is one.
+    pub fn right(self) -> Option<R> {
+        match self {
+            Either::Left(_) => None,
+            Either::Right(r) => Some(r),
+        }
+    }
+}
+
+/// Blocks until one of two receivers is ready and receives from it.
+///
+/// This is a shorthand for a [`select!`] with two `recv` cases, for receivers that may carry
+/// different message types. As in [`select!`], a receiver is ready if it has a message or its
+/// channel is disconnected, and if both are ready, a random one is chosen.
+///
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{race, unbounded, Either};
+///
+/// let (s1, r1) = unbounded::<i32>();
+/// let (s2, r2) = unbounded::<&str>();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     s2.send("done").unwrap();
+/// });
+///
+/// assert_eq!(race(&r1, &r2), Either::Right(Ok("done")));
+/// # drop(s1);
+/// ```
+pub fn race<T, U>(
+    r1: &Receiver<T>,
+    r2: &Receiver<U>,
+) -> Either<Result<T, RecvError>, Result<U, RecvError>> {
+    let mut sel = Select::new();
+    let left = sel.recv(r1);
+    sel.recv(r2);
+
+    let oper = sel.select();
+    complete(oper, left, r1, r2)
+}
+
+/// Waits until one of two receivers is ready and receives from it, but only for a limited time.
+///
+/// This is like [`race`], except that an error is returned if neither receiver becomes ready
+/// before the timeout.
+///
+/// [`race`]: fn.race.html
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::{race_timeout, unbounded};
+///
+/// let (s1, r1) = unbounded::<i32>();
+/// let (s2, r2) = unbounded::<String>();
+///
+/// assert!(race_timeout(&r1, &r2, Duration::from_millis(100)).is_err());
+///
+/// s1.send(1).unwrap();
+/// let res = race_timeout(&r1, &r2, Duration::from_millis(100)).unwrap();
+/// assert_eq!(res.left(), Some(Ok(1)));
+/// # drop(s2);
+/// ```
+pub fn race_timeout<T, U>(
+    r1: &Receiver<T>,
+    r2: &Receiver<U>,
+    timeout: Duration,
+) -> Result<Either<Result<T, RecvError>, Result<U, RecvError>>, SelectTimeoutError> {
+    let mut sel = Select::new();
+    let left = sel.recv(r1);
+    sel.recv(r2);
+
+    let oper = sel.select_timeout(timeout)?;
+    Ok(complete(oper, left, r1, r2))
+}
+
+/// Receives from whichever of the two receivers was selected.
+fn complete<'a, T, U>(
+    oper: SelectedOperation<'a>,
+    left: usize,
+    r1: &Receiver<T>,
+    r2: &Receiver<U>,
+) -> Either<Result<T, RecvError>, Result<U, RecvError>> {
+    if oper.index() == left {
+        Either::Left(oper.recv(r1))
+    } else {
+        Either::Right(oper.recv(r2))
+    }
+}
+
+/// Blocks until one of the receivers in a slice is ready and receives from it.
+///
+/// Returns the index of the receiver in the slice along with the result of receiving from it.
+/// See [`race`] for details.
+///
+/// [`race`]: fn.race.html
+///
+/// # Panics
+///
+/// Panics if the slice is empty.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{race_slice, unbounded};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// let rs = [r1, r2];
+///
+/// s2.send(20).unwrap();
+/// assert_eq!(race_slice(&rs), (1, Ok(20)));
+/// # drop(s1);
+/// ```
+pub fn race_slice<T>(receivers: &[Receiver<T>]) -> (usize, Result<T, RecvError>) {
+    let mut sel = Select::new();
+    for r in receivers {
+        sel.recv(r);
+    }
+
+    let oper = sel.select();
+    let index = oper.index();
+    (index, oper.recv(&receivers[index]))
+}
+
+/// Waits until one of the receivers in a slice is ready and receives from it, but only for a
+/// limited time.
+///
+/// This is like [`race_slice`], except that an error is returned if no receiver becomes ready
+/// before the timeout. An empty slice always times out.
+///
+/// [`race_slice`]: fn.race_slice.html
+pub fn race_slice_timeout<T>(
+    receivers: &[Receiver<T>],
+    timeout: Duration,
+) -> Result<(usize, Result<T, RecvError>), SelectTimeoutError> {
+    let mut sel = Select::new();
+    for r in receivers {
+        sel.recv(r);
+    }
+
+    let oper = sel.select_timeout(timeout)?;
+    let index = oper.index();
+    Ok((index, oper.recv(&receivers[index])))
+}
@@ -0,0 +1,226 @@
+//! Senders that throttle messages with a token bucket.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::{after, Receiver, Sender};
+use err::{SendError, SendTimeoutError, TrySendError};
+use utils::Spinlock;
+
+/// A token bucket shared by all clones of a rate-limited sender.
+///
+/// Instead of counting tokens, the bucket tracks the instant at which it will be full again
+/// (theoretical arrival time). Every sent message pushes that instant forward by one interval, and
+/// a message may be sent as long as the instant is at most `burst - 1` intervals in the future.
+struct Bucket {
+    /// The time it takes to replenish one token.
+    interval: Duration,
+
+    /// How far in the future the bucket may be full again, which is `burst - 1` intervals.
+    tolerance: Duration,
+
+    /// The instant at which the bucket will be full again, if it's in the future.
+    tat: Spinlock<Instant>,
+}
+
+impl Bucket {
+    /// Takes a token, or returns the instant at which one will be available.
+    fn acquire(&self) -> Result<(), Instant> {
+        let now = Instant::now();
+        let mut tat = self.tat.lock();
+
+        let start = if *tat > now { *tat } else { now };
+        if start > now + self.tolerance {
+            return Err(start - self.tolerance);
+        }
+
+        *tat = start + self.interval;
+        Ok(())
+    }
+
+    /// Returns a token taken by `acquire` that ended up unused.
+    fn release(&self) {
+        let mut tat = self.tat.lock();
+        *tat -= self.interval;
+    }
+
+    /// Returns the instant at which a token will be available.
+    fn available_at(&self) -> Instant {
+        let now = Instant::now();
+        let tat = *self.tat.lock();
+
+        if tat > now + self.tolerance {
+            tat - self.tolerance
+        } else {
+            now
+        }
+    }
+}
+
+/// A sender that limits the rate at which messages are sent.
+///
+/// Created by [`Sender::rate_limited`].
+///
+/// Messages are throttled with a token bucket holding up to `burst` tokens, which refills at
+/// `rate` tokens per second. Every sent message takes a token, and sending blocks while the bucket
+/// is empty. The bucket is shared by all clones of the sender.
+///
+/// [`Sender::rate_limited`]: struct.Sender.html#method.rate_limited
+pub struct RateLimitedSender<T> {
+    sender: Sender<T>,
+    bucket: Arc<Bucket>,
+}
+
+impl<T> RateLimitedSender<T> {
+    /// Creates a sender that sends at most `rate` messages per second, in bursts of up to `burst`.
+    pub(crate) fn new(sender: Sender<T>, rate: u32, burst: u32) -> RateLimitedSender<T> {
+        assert!(rate > 0, "rate must be positive");
+        assert!(burst > 0, "burst must be positive");
+
+        let interval = Duration::from_secs(1) / rate;
+        RateLimitedSender {
+            sender,
+            bucket: Arc::new(Bucket {
+                interval,
+                tolerance: interval * (burst - 1),
+                tat: Spinlock::new(Instant::now()),
+            }),
+        }
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// If the bucket is empty, the message is returned back in [`TrySendError::Full`], just as
+    /// if the channel was full. A token is only taken if the message is sent.
+    ///
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.bucket.acquire().is_err() {
+            return Err(TrySendError::Full(msg));
+        }
+
+        let res = self.sender.try_send(msg);
+        if res.is_err() {
+            self.bucket.release();
+        }
+        res
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// The current thread first waits for a token, and then for room in the channel.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        while self.bucket.acquire().is_err() {
+            let _ = self.permit().recv();
+        }
+
+        let res = self.sender.send(msg);
+        if res.is_err() {
+            self.bucket.release();
+        }
+        res
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// If no token will become available before the timeout, this method gives up right away
+    /// without waiting.
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.bucket.acquire() {
+                Ok(()) => break,
+                Err(at) if at > deadline => return Err(SendTimeoutError::Timeout(msg)),
+                Err(_) => {
+                    let _ = self.permit().recv();
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let timeout = if deadline > now {
+            deadline - now
+        } else {
+            Duration::from_secs(0)
+        };
+
+        let res = self.sender.send_timeout(msg, timeout);
+        if res.is_err() {
+            self.bucket.release();
+        }
+        res
+    }
+
+    /// Returns a receiver that delivers a message once a token is available.
+    ///
+    /// The receiver is created by [`after`], so it can be used in [`select!`] to wait for a token
+    /// alongside other operations. The token isn't reserved, so a clone of this sender might take
+    /// it first, in which case [`try_send`] fails and a new permit has to be awaited.
+    ///
+    /// [`after`]: fn.after.html
+    /// [`select!`]: macro.select.html
+    /// [`try_send`]: struct.RateLimitedSender.html#method.try_send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use crossbeam_channel::{unbounded, TrySendError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let (stop_s, stop_r) = unbounded::<()>();
+    /// let s = s.rate_limited(20, 1);
+    ///
+    /// let mut sent = 0;
+    /// while sent < 3 {
+    ///     select! {
+    ///         recv(s.permit()) -> _ => {
+    ///             match s.try_send(sent) {
+    ///                 Ok(()) => sent += 1,
+    ///                 Err(TrySendError::Full(_)) => {}
+    ///                 Err(TrySendError::Disconnected(_)) => break,
+    ///             }
+    ///         }
+    ///         recv(stop_r) -> _ => break,
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1, 2]);
+    /// # drop(stop_s);
+    /// # }
+    /// ```
+    pub fn permit(&self) -> Receiver<Instant> {
+        let at = self.bucket.available_at();
+        let now = Instant::now();
+
+        if at > now {
+            after(at - now)
+        } else {
+            after(Duration::from_secs(0))
+        }
+    }
+
+    /// Returns the underlying sender, which isn't rate-limited.
+    pub fn as_sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+}
+
+impl<T> Clone for RateLimitedSender<T> {
+    fn clone(&self) -> Self {
+        RateLimitedSender {
+            sender: self.sender.clone(),
+            bucket: self.bucket.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RateLimitedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RateLimitedSender { .. }")
+    }
+}
@@ -0,0 +1,147 @@
+//! Readiness notifications for event loops outside this crate.
+
+use std::fmt;
+
+use channel::Receiver;
+use context::Context;
+use select::{Operation, SelectHandle, Selected};
+
+/// A registration that invokes a callback when a receiver becomes ready.
+///
+/// Created by [`Receiver::register_ready`]. This is the reverse of [`Pollable`]: instead of
+/// waiting for an outside source in a select, an outside event loop gets told when a channel can
+/// be received from, without any thread blocking on the channel. The callback typically writes to
+/// an eventfd or a pipe registered with epoll, or wakes up a task.
+///
+/// A receiver is ready when a message can be received from it or when it is disconnected.
+///
+/// The registration is edge-triggered and fires at most once per arming. It is armed when created,
+/// and after the callback fires, the event loop receives whatever is in the channel and then calls
+/// [`rearm`] to get notified again. If the receiver is already ready when the registration is
+/// armed, the callback is invoked right away. The callback may occasionally be invoked
+/// spuriously.
+///
+/// The callback runs on the thread that made the receiver ready, while the channel's internal
+/// locks are held. It must be quick and must not use the channel.
+///
+/// Only receivers of channels created by [`bounded`] and [`unbounded`] or one of their variants
+/// notify registrations. Receivers created by [`after`], [`tick`], and [`never`] do not, since no
+/// thread makes them ready.
+///
+/// [`Receiver::register_ready`]: struct.Receiver.html#method.register_ready
+/// [`Pollable`]: struct.Pollable.html
+/// [`rearm`]: struct.ReadyRegistration.html#method.rearm
+/// [`bounded`]: fn.bounded.html
+/// [`unbounded`]: fn.unbounded.html
+/// [`after`]: fn.after.html
+/// [`tick`]: fn.tick.html
+/// [`never`]: fn.never.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::unbounded;
+///
+/// // Stands in for an eventfd registered with an event loop.
+/// let (event_s, event_r) = unbounded();
+///
+/// let (s, r) = unbounded();
+/// let registration = r.register_ready(move || {
+///     let _ = event_s.send(());
+/// });
+///
+/// thread::spawn(move || {
+///     for i in 0..3 {
+///         s.send(i).unwrap();
+///     }
+/// });
+///
+/// // The event loop.
+/// let mut received = Vec::new();
+/// while received.len() < 3 {
+///     event_r.recv().unwrap();
+///     received.extend(r.try_iter());
+///     registration.rearm();
+/// }
+///
+/// assert_eq!(received, [0, 1, 2]);
+/// ```
+pub struct ReadyRegistration<T> {
+    /// The receiver being watched.
+    receiver: Receiver<T>,
+
+    /// Invokes the callback when the receiver's channel notifies it.
+    cx: Context,
+
+    /// Identifies the registration in the channel's list of watchers.
+    oper: Operation,
+
+    /// Gives the registration a stable address for `oper`.
+    _slot: Box<u8>,
+}
+
+impl<T> ReadyRegistration<T> {
+    /// Registers `callback` to be invoked when `receiver` becomes ready.
+    pub(crate) fn new<F>(receiver: Receiver<T>, callback: F) -> ReadyRegistration<T>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut slot = Box::new(0);
+        let registration = ReadyRegistration {
+            receiver,
+            cx: Context::with_callback(callback),
+            oper: Operation::hook(&mut *slot),
+            _slot: slot,
+        };
+        registration.arm();
+        registration
+    }
+
+    /// Starts watching the receiver.
+    fn arm(&self) {
+        let oper = self.oper;
+        if self.receiver.watch(oper, &self.cx) {
+            // Already ready, so fire now, unless a notification beat us to it.
+            if self.cx.try_select(Selected::Operation(oper)).is_ok() {
+                self.receiver.unwatch(oper);
+                self.cx.unpark();
+            }
+        }
+    }
+
+    /// Arms the registration again after the callback has fired.
+    ///
+    /// If the callback hasn't fired since the registration was last armed, this does nothing.
+    pub fn rearm(&self) {
+        if self.cx.selected() != Selected::Waiting {
+            self.receiver.unwatch(self.oper);
+            self.cx.reset();
+            self.arm();
+        }
+    }
+
+    /// Returns `true` if the callback has fired since the registration was last armed.
+    pub fn has_fired(&self) -> bool {
+        self.cx.selected() != Selected::Waiting
+    }
+
+    /// Returns the receiver being watched.
+    pub fn receiver(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+}
+
+impl<T> Drop for ReadyRegistration<T> {
+    fn drop(&mut self) {
+        self.receiver.unwatch(self.oper);
+    }
+}
+
+impl<T> fmt::Debug for ReadyRegistration<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadyRegistration")
+            .field("fired", &self.has_fired())
+            .finish()
+    }
+}
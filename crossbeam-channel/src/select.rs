@@ -3,17 +3,23 @@
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_utils::Backoff;
+use crossbeam_utils::{shuffle, Backoff};
 
 use channel::{self, Receiver, Sender};
 use context::Context;
 use err::{ReadyTimeoutError, TryReadyError};
-use err::{RecvError, SendError};
-use err::{SelectTimeoutError, TrySelectError};
+use err::{RecvError, SelectedRecvError, SelectedSendError, SendError};
+use err::{SelectBudgetError, SelectError, SelectTimeoutError, TrySelectError};
 use flavors;
+use instrument::Probe;
+use pollable::{Pollable, PollableSource};
 use utils;
+use waiters::{self, OperationKind};
 
 /// Temporary data that gets initialized during select or a blocking operation, and is consumed by
 /// `read` or `write`.
@@ -21,14 +27,54 @@ use utils;
 /// Each field contains data associated with a specific channel flavor.
 #[derive(Debug, Default)]
 pub struct Token {
+    #[cfg(feature = "timers")]
     pub after: flavors::after::AfterToken,
+    #[cfg(feature = "flavor-array")]
     pub array: flavors::array::ArrayToken,
+    pub coalesce: flavors::coalesce::CoalesceToken,
+    pub lanes: flavors::lanes::LanesToken,
     pub list: flavors::list::ListToken,
     pub never: flavors::never::NeverToken,
+    pub pollable: bool,
+    pub sharded: flavors::sharded::ShardedToken,
+    #[cfg(feature = "timers")]
     pub tick: flavors::tick::TickToken,
+    #[cfg(feature = "timers")]
+    pub timer: flavors::timer::TimerToken,
+    #[cfg(feature = "flavor-zero")]
     pub zero: flavors::zero::ZeroToken,
 }
 
+impl Token {
+    /// Returns `true` if the selected operation found its channel disconnected.
+    ///
+    /// Every flavor leaves its part of the token empty when the channel is disconnected, and in
+    /// that case there is nothing to clean up if the operation doesn't get completed.
+    #[inline]
+    pub fn is_disconnected(&self) -> bool {
+        #[cfg(feature = "flavor-array")]
+        {
+            if !self.array.is_disconnected() {
+                return false;
+            }
+        }
+        #[cfg(feature = "flavor-zero")]
+        {
+            if self.zero != 0 {
+                return false;
+            }
+        }
+        #[cfg(feature = "timers")]
+        {
+            if self.after.is_some() || self.tick.is_some() || self.timer.is_some() {
+                return false;
+            }
+        }
+
+        self.coalesce == 0 && self.list.is_disconnected() && !self.pollable
+    }
+}
+
 /// Identifier associated with an operation by a specific thread on a specific channel.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Operation(usize);
@@ -117,6 +163,16 @@ pub trait SelectHandle {
 
     /// Unregisters an operation for readiness notification.
     fn unwatch(&self, oper: Operation);
+
+    /// Returns `true` if the operation can never become ready.
+    fn never_ready(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the operation can only ever fail because its channel is disconnected.
+    fn is_exhausted(&self) -> bool {
+        self.never_ready()
+    }
 }
 
 impl<'a, T: SelectHandle> SelectHandle for &'a T {
@@ -151,6 +207,14 @@ impl<'a, T: SelectHandle> SelectHandle for &'a T {
     fn unwatch(&self, oper: Operation) {
         (**self).unwatch(oper)
     }
+
+    fn never_ready(&self) -> bool {
+        (**self).never_ready()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        (**self).is_exhausted()
+    }
 }
 
 /// Determines when a select operation should time out.
@@ -166,14 +230,66 @@ enum Timeout {
     At(Instant),
 }
 
+/// Shuffles operations within each priority tier, keeping the tiers in order.
+fn shuffle_tiers(handles: &mut [(&dyn SelectHandle, usize, *const u8)], tiers: &[usize]) {
+    if tiers.is_empty() {
+        shuffle(handles);
+        return;
+    }
+
+    let mut start = 0;
+    while start < handles.len() {
+        let len = tiers[start..]
+            .iter()
+            .take_while(|&&t| t == tiers[start])
+            .count();
+        shuffle(&mut handles[start..start + len]);
+        start += len;
+    }
+}
+
+/// Returns the number of operations that can never become ready.
+fn never_ready(handles: &[(&dyn SelectHandle, usize, *const u8)]) -> usize {
+    handles.iter().filter(|&&(h, _, _)| h.never_ready()).count()
+}
+
 /// Runs until one of the operations is selected, potentially blocking the current thread.
 ///
 /// Successful receive operations will have to be followed up by `channel::read()` and successful
 /// send operations by `channel::write()`.
+///
+/// `tiers` holds the priority tier of each operation, in increasing order, or is empty if all
+/// operations are in the same tier. If `biased` is set, operations within a tier are tried in the
+/// order they're listed in instead of a random one.
+///
+/// If `budget` is set, the thread gives up and returns `None` once it has woken up without
+/// managing to select an operation more than `budget` times.
 fn run_select(
     handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
+    timeout: Timeout,
+    budget: Option<usize>,
+) -> Option<(Token, usize, *const u8)> {
+    let mut probe = Probe::new();
+    let res = run_select_probed(handles, tiers, biased, timeout, budget, &mut probe);
+    if let Some((_, i, _)) = res {
+        probe.fired(i);
+    }
+    res
+}
+
+/// Implements `run_select`, reporting blocking to `probe`.
+fn run_select_probed(
+    handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
     timeout: Timeout,
+    budget: Option<usize>,
+    probe: &mut Probe,
 ) -> Option<(Token, usize, *const u8)> {
+    let _desc = waiters::describe(OperationKind::Select, None);
+
     if handles.is_empty() {
         // Wait until the timeout and return.
         match timeout {
@@ -190,7 +306,9 @@ fn run_select(
     }
 
     // Shuffle the operations for fairness.
-    utils::shuffle(handles);
+    if !biased {
+        shuffle_tiers(handles, tiers);
+    }
 
     // Create a token, which serves as a temporary variable that gets initialized in this function
     // and is later used by a call to `channel::read()` or `channel::write()` that completes the
@@ -204,6 +322,9 @@ fn run_select(
         }
     }
 
+    // The number of times the thread has woken up without selecting an operation.
+    let mut retries = 0;
+
     loop {
         // Prepare for blocking.
         let res = Context::with(|cx| {
@@ -254,7 +375,9 @@ fn run_select(
                 }
 
                 // Block the current thread.
+                probe.block_begin(handles.len());
                 sel = cx.wait_until(deadline);
+                probe.block_end();
             }
 
             // Unregister all registered operations.
@@ -314,14 +437,46 @@ fn run_select(
                 }
             }
         }
+
+        // Give up if other threads keep winning the race for the operations.
+        if let Some(budget) = budget {
+            if retries == budget {
+                return None;
+            }
+            retries += 1;
+        }
     }
 }
 
 /// Runs until one of the operations becomes ready, potentially blocking the current thread.
+///
+/// `tiers` holds the priority tier of each operation, in increasing order, or is empty if all
+/// operations are in the same tier. If `biased` is set, operations within a tier are checked in the
+/// order they're listed in.
 fn run_ready(
     handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
+    timeout: Timeout,
+) -> Option<usize> {
+    let mut probe = Probe::new();
+    let res = run_ready_probed(handles, tiers, biased, timeout, &mut probe);
+    if let Some(i) = res {
+        probe.fired(i);
+    }
+    res
+}
+
+/// Implements `run_ready`, reporting blocking to `probe`.
+fn run_ready_probed(
+    handles: &mut [(&dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
     timeout: Timeout,
+    probe: &mut Probe,
 ) -> Option<usize> {
+    let _desc = waiters::describe(OperationKind::Ready, None);
+
     if handles.is_empty() {
         // Wait until the timeout and return.
         match timeout {
@@ -338,7 +493,9 @@ fn run_ready(
     }
 
     // Shuffle the operations for fairness.
-    utils::shuffle(handles);
+    if !biased {
+        shuffle_tiers(handles, tiers);
+    }
 
     loop {
         let backoff = Backoff::new();
@@ -409,7 +566,9 @@ fn run_ready(
                 }
 
                 // Block the current thread.
+                probe.block_begin(handles.len());
                 sel = cx.wait_until(deadline);
+                probe.block_end();
             }
 
             // Unwatch all operations.
@@ -445,13 +604,17 @@ fn run_ready(
 #[inline]
 pub fn try_select<'a>(
     handles: &mut [(&'a dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
 ) -> Result<SelectedOperation<'a>, TrySelectError> {
-    match run_select(handles, Timeout::Now) {
-        None => Err(TrySelectError),
+    match run_select(handles, tiers, biased, Timeout::Now, None) {
+        None => Err(TrySelectError::new(handles.len(), never_ready(handles))),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
             index,
             ptr,
+            tag: None,
+            completed: false,
             _marker: PhantomData,
         }),
     }
@@ -461,34 +624,68 @@ pub fn try_select<'a>(
 #[inline]
 pub fn select<'a>(
     handles: &mut [(&'a dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
 ) -> SelectedOperation<'a> {
     if handles.is_empty() {
         panic!("no operations have been added to `Select`");
     }
 
-    let (token, index, ptr) = run_select(handles, Timeout::Never).unwrap();
+    let (token, index, ptr) = run_select(handles, tiers, biased, Timeout::Never, None).unwrap();
     SelectedOperation {
         token,
         index,
         ptr,
+        tag: None,
+        completed: false,
         _marker: PhantomData,
     }
 }
 
+/// Blocks until one of the operations becomes ready and selects it, giving up after `retries`
+/// wakeups that didn't select anything.
+#[inline]
+pub fn select_with_budget<'a>(
+    handles: &mut [(&'a dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
+    retries: usize,
+) -> Result<SelectedOperation<'a>, SelectBudgetError> {
+    if handles.is_empty() {
+        panic!("no operations have been added to `Select`");
+    }
+
+    match run_select(handles, tiers, biased, Timeout::Never, Some(retries)) {
+        None => Err(SelectBudgetError::Contended),
+        Some((token, index, ptr)) => Ok(SelectedOperation {
+            token,
+            index,
+            ptr,
+            tag: None,
+            completed: false,
+            _marker: PhantomData,
+        }),
+    }
+}
+
 /// Blocks for a limited time until one of the operations becomes ready and selects it.
 #[inline]
 pub fn select_timeout<'a>(
     handles: &mut [(&'a dyn SelectHandle, usize, *const u8)],
+    tiers: &[usize],
+    biased: bool,
     timeout: Duration,
 ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
     let timeout = Timeout::At(Instant::now() + timeout);
 
-    match run_select(handles, timeout) {
-        None => Err(SelectTimeoutError),
+    match run_select(handles, tiers, biased, timeout, None) {
+        None => Err(SelectTimeoutError::new(handles.len(), never_ready(handles))),
         Some((token, index, ptr)) => Ok(SelectedOperation {
             token,
             index,
             ptr,
+            tag: None,
+            completed: false,
             _marker: PhantomData,
         }),
     }
@@ -498,7 +695,7 @@ pub fn select_timeout<'a>(
 ///
 /// `Select` allows you to define a set of channel operations, wait until any one of them becomes
 /// ready, and finally execute it. If multiple operations are ready at the same time, a random one
-/// among them is selected.
+/// among them is selected, unless the `Select` was created with [`new_biased`].
 ///
 /// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
 /// when it will simply return an error because the channel is disconnected.
@@ -519,6 +716,20 @@ pub fn select_timeout<'a>(
 ///   so it's wise to use a retry loop. However, note that these methods might return with success
 ///   spuriously, so it's a good idea to always double check if the operation is really ready.
 ///
+/// A `Select` is meant to be long-lived. The list of operations is kept between calls, so the
+/// same `Select` can be used for selection repeatedly, typically in a loop, without rebuilding
+/// it every time. Operations that are no longer needed can be dropped from the list with
+/// [`remove`].
+///
+/// Cloning a `Select` creates an independent copy of the list. The copy refers to the same senders
+/// and receivers and assigns the same indices to them, so an index returned by one copy can be
+/// used to look up the operation in the other. Adding or removing operations later only affects
+/// the copy it's done on, and indices assigned afterwards are the same in both copies only if the
+/// operations are added in the same order. Each copy registers itself with the channels on every
+/// selection, so copies can select at the same time on different threads, and each selected
+/// operation is completed on the same senders and receivers it was added with. The senders and
+/// receivers are borrowed by all copies, so they can't be moved or dropped while any copy exists.
+///
 /// # Examples
 ///
 /// Use [`select`] to receive a message from a list of receivers:
@@ -570,7 +781,82 @@ pub fn select_timeout<'a>(
 /// }
 /// ```
 ///
+/// Reuse the same `Select` to receive messages until all channels are disconnected:
+///
+/// ```
+/// use crossbeam_channel::{unbounded, Select};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = unbounded();
+/// let rs = [r1, r2];
+///
+/// s1.send(1).unwrap();
+/// s2.send(2).unwrap();
+/// drop(s1);
+/// drop(s2);
+///
+/// let mut sel = Select::new();
+/// for r in &rs {
+///     sel.recv(r);
+/// }
+///
+/// let mut sum = 0;
+/// let mut remaining = rs.len();
+///
+/// while remaining > 0 {
+///     let oper = sel.select();
+///     let index = oper.index();
+///
+///     match oper.recv(&rs[index]) {
+///         Ok(msg) => sum += msg,
+///         Err(_) => {
+///             // The channel is disconnected, so stop selecting on it.
+///             sel.remove(index);
+///             remaining -= 1;
+///         }
+///     }
+/// }
+///
+/// assert_eq!(sum, 3);
+/// ```
+///
+/// Share a list of operations among worker threads by cloning it:
+///
+/// ```
+/// # extern crate crossbeam_channel;
+/// # extern crate crossbeam_utils;
+/// # fn main() {
+/// use crossbeam_channel::{bounded, Select};
+/// use crossbeam_utils::thread::scope;
+///
+/// let (s, r) = bounded(0);
+///
+/// let mut sel = Select::new();
+/// let recv = sel.recv(&r);
+///
+/// scope(|scope| {
+///     for _ in 0..4 {
+///         let mut sel = sel.clone();
+///         let r = &r;
+///         scope.spawn(move |_| {
+///             // Indices assigned before cloning are the same in every copy.
+///             let oper = sel.select();
+///             assert_eq!(oper.index(), recv);
+///             oper.recv(r).unwrap()
+///         });
+///     }
+///
+///     for i in 0..4 {
+///         s.send(i).unwrap();
+///     }
+/// })
+/// .unwrap();
+/// # }
+/// ```
+///
 /// [`select!`]: macro.select.html
+/// [`new_biased`]: struct.Select.html#method.new_biased
+/// [`remove`]: struct.Select.html#method.remove
 /// [`try_select`]: struct.Select.html#method.try_select
 /// [`select`]: struct.Select.html#method.select
 /// [`select_timeout`]: struct.Select.html#method.select_timeout
@@ -578,11 +864,23 @@ pub fn select_timeout<'a>(
 /// [`ready`]: struct.Select.html#method.ready
 /// [`ready_timeout`]: struct.Select.html#method.ready_timeout
 pub struct Select<'a> {
-    /// A list of senders and receivers participating in selection.
+    /// A list of senders and receivers participating in selection, ordered by tier.
     handles: Vec<(&'a dyn SelectHandle, usize, *const u8)>,
 
+    /// The priority tier of each operation in `handles`.
+    tiers: Vec<usize>,
+
+    /// The tags attached to operations, as `(index, tag)` pairs sorted by index.
+    tags: Vec<(usize, usize)>,
+
     /// The next index to assign to an operation.
     next_index: usize,
+
+    /// Whether operations on disconnected channels are removed instead of being selected.
+    remove_disconnected: bool,
+
+    /// Whether ready operations are selected in the order they were added instead of randomly.
+    biased: bool,
 }
 
 unsafe impl<'a> Send for Select<'a> {}
@@ -604,7 +902,49 @@ impl<'a> Select<'a> {
     pub fn new() -> Select<'a> {
         Select {
             handles: Vec::with_capacity(4),
+            tiers: Vec::with_capacity(4),
+            tags: Vec::new(),
             next_index: 0,
+            remove_disconnected: false,
+            biased: false,
+        }
+    }
+
+    /// Creates an empty list of channel operations that are selected in the order they were added.
+    ///
+    /// When several operations are ready, the one added first is selected instead of a random
+    /// one. Operations in different priority tiers are still ordered by tier first. Blocking
+    /// operations still wait on all operations at once, and select whichever becomes ready first.
+    ///
+    /// This also applies to [`ready`] and its variants.
+    ///
+    /// Note that an operation that is always ready will starve all operations added after it.
+    ///
+    /// [`ready`]: struct.Select.html#method.ready
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new_biased();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are ready, so the first one is selected.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(1));
+    /// ```
+    pub fn new_biased() -> Select<'a> {
+        Select {
+            biased: true,
+            ..Select::new()
         }
     }
 
@@ -626,7 +966,7 @@ impl<'a> Select<'a> {
     pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
         let i = self.next_index;
         let ptr = s as *const Sender<_> as *const u8;
-        self.handles.push((s, i, ptr));
+        self.push((s, i, ptr));
         self.next_index += 1;
         i
     }
@@ -649,192 +989,722 @@ impl<'a> Select<'a> {
     pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
         let i = self.next_index;
         let ptr = r as *const Receiver<_> as *const u8;
-        self.handles.push((r, i, ptr));
+        self.push((r, i, ptr));
         self.next_index += 1;
         i
     }
 
-    /// Removes a previously added operation.
-    ///
-    /// This is useful when an operation is selected because the channel got disconnected and we
-    /// want to try again to select a different operation instead.
-    ///
-    /// If new operations are added after removing some, the indices of removed operations will not
-    /// be reused.
+    /// Adds a send operation if the sender exists.
     ///
-    /// # Panics
+    /// If `s` is `None`, a placeholder operation that never becomes ready is added instead, so
+    /// the index can be handled the same way in both cases.
     ///
-    /// An attempt to remove a non-existing or already removed operation will panic.
+    /// Returns the index of the added operation.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use crossbeam_channel::{unbounded, Select};
+    /// use crossbeam_channel::{unbounded, Select, Sender};
     ///
-    /// let (s1, r1) = unbounded::<i32>();
-    /// let (_, r2) = unbounded::<i32>();
+    /// let (s, r) = unbounded::<i32>();
+    /// let missing: Option<&Sender<i32>> = None;
     ///
     /// let mut sel = Select::new();
-    /// let oper1 = sel.recv(&r1);
-    /// let oper2 = sel.recv(&r2);
+    /// let oper1 = sel.send_opt(missing);
+    /// let oper2 = sel.send_opt(Some(&s));
     ///
-    /// // Both operations are initially ready, so a random one will be executed.
     /// let oper = sel.select();
     /// assert_eq!(oper.index(), oper2);
-    /// assert!(oper.recv(&r2).is_err());
-    /// sel.remove(oper2);
-    ///
-    /// s1.send(10).unwrap();
-    ///
-    /// let oper = sel.select();
-    /// assert_eq!(oper.index(), oper1);
-    /// assert_eq!(oper.recv(&r1), Ok(10));
+    /// assert_eq!(oper.send(&s, 10), Ok(()));
+    /// assert_eq!(r.recv(), Ok(10));
     /// ```
-    pub fn remove(&mut self, index: usize) {
-        assert!(
-            index < self.next_index,
-            "index out of bounds; {} >= {}",
-            index,
-            self.next_index,
-        );
-
-        let i = self
-            .handles
-            .iter()
-            .enumerate()
-            .find(|(_, (_, i, _))| *i == index)
-            .expect("no operation with this index")
-            .0;
-
-        self.handles.swap_remove(i);
+    pub fn send_opt<T>(&mut self, s: Option<&'a Sender<T>>) -> usize {
+        match s {
+            Some(s) => self.send(s),
+            None => self.placeholder(),
+        }
     }
 
-    /// Attempts to select one of the operations without blocking.
-    ///
-    /// If an operation is ready, it is selected and returned. If multiple operations are ready at
-    /// the same time, a random one among them is selected. If none of the operations are ready, an
-    /// error is returned.
-    ///
-    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
-    /// even when it will simply return an error because the channel is disconnected.
+    /// Adds a receive operation if the receiver exists.
     ///
-    /// The selected operation must be completed with [`SelectedOperation::send`]
-    /// or [`SelectedOperation::recv`].
+    /// If `r` is `None`, a placeholder operation that never becomes ready is added instead, so
+    /// conditional operations don't require building the selection differently.
     ///
-    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
-    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    /// Returns the index of the added operation.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use crossbeam_channel::{unbounded, Select};
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Receiver, Select};
     ///
-    /// let (s1, r1) = unbounded();
-    /// let (s2, r2) = unbounded();
+    /// let (s, r) = unbounded();
+    /// let missing: Option<&Receiver<i32>> = None;
     ///
-    /// s1.send(10).unwrap();
-    /// s2.send(20).unwrap();
+    /// s.send(10).unwrap();
     ///
     /// let mut sel = Select::new();
-    /// let oper1 = sel.recv(&r1);
-    /// let oper2 = sel.recv(&r2);
+    /// let oper1 = sel.recv_opt(missing);
+    /// let oper2 = sel.recv_opt(Some(&r));
     ///
-    /// // Both operations are initially ready, so a random one will be executed.
-    /// let oper = sel.try_select();
-    /// match oper {
-    ///     Err(_) => panic!("both operations should be ready"),
-    ///     Ok(oper) => match oper.index() {
-    ///         i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
-    ///         i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
-    ///         _ => unreachable!(),
-    ///     }
-    /// }
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert_eq!(oper.recv(&r), Ok(10));
+    ///
+    /// // The placeholder operation never becomes ready.
+    /// let mut sel = Select::new();
+    /// sel.recv_opt(missing);
+    /// assert!(sel.select_timeout(Duration::from_millis(100)).is_err());
     /// ```
-    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
-        try_select(&mut self.handles)
+    pub fn recv_opt<T>(&mut self, r: Option<&'a Receiver<T>>) -> usize {
+        match r {
+            Some(r) => self.recv(r),
+            None => self.placeholder(),
+        }
     }
 
-    /// Blocks until one of the operations becomes ready and selects it.
-    ///
-    /// Once an operation becomes ready, it is selected and returned. If multiple operations are
-    /// ready at the same time, a random one among them is selected.
-    ///
-    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
-    /// even when it will simply return an error because the channel is disconnected.
-    ///
-    /// The selected operation must be completed with [`SelectedOperation::send`]
-    /// or [`SelectedOperation::recv`].
+    /// Adds a send operation with a tag attached.
     ///
-    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
-    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    /// The tag is returned by [`SelectedOperation::tag`] when the operation is selected, which
+    /// saves keeping a separate map from operation indices to whatever the operation belongs to.
     ///
-    /// # Panics
+    /// Returns the index of the added operation.
     ///
-    /// Panics if no operations have been added to `Select`.
+    /// [`SelectedOperation::tag`]: struct.SelectedOperation.html#method.tag
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use std::time::Duration;
     /// use crossbeam_channel::{unbounded, Select};
     ///
-    /// let (s1, r1) = unbounded();
-    /// let (s2, r2) = unbounded();
-    ///
-    /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s1.send(10).unwrap();
-    /// });
-    /// thread::spawn(move || s2.send(20).unwrap());
+    /// let (s, r) = unbounded();
     ///
     /// let mut sel = Select::new();
-    /// let oper1 = sel.recv(&r1);
-    /// let oper2 = sel.recv(&r2);
+    /// sel.send_tagged(&s, 7);
     ///
-    /// // The second operation will be selected because it becomes ready first.
     /// let oper = sel.select();
-    /// match oper.index() {
-    ///     i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
-    ///     i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
-    ///     _ => unreachable!(),
-    /// }
+    /// assert_eq!(oper.tag(), Some(7));
+    /// assert_eq!(oper.send(&s, 10), Ok(()));
+    /// assert_eq!(r.recv(), Ok(10));
     /// ```
-    pub fn select(&mut self) -> SelectedOperation<'a> {
-        select(&mut self.handles)
+    pub fn send_tagged<T>(&mut self, s: &'a Sender<T>, tag: usize) -> usize {
+        let i = self.send(s);
+        self.tags.push((i, tag));
+        i
     }
 
-    /// Blocks for a limited time until one of the operations becomes ready and selects it.
-    ///
-    /// If an operation becomes ready, it is selected and returned. If multiple operations are
-    /// ready at the same time, a random one among them is selected. If none of the operations
-    /// become ready for the specified duration, an error is returned.
+    /// Adds a receive operation with a tag attached.
     ///
-    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
-    /// even when it will simply return an error because the channel is disconnected.
+    /// The tag is returned by [`SelectedOperation::tag`] when the operation is selected, which
+    /// saves keeping a separate map from operation indices to whatever the operation belongs to.
     ///
-    /// The selected operation must be completed with [`SelectedOperation::send`]
-    /// or [`SelectedOperation::recv`].
+    /// Returns the index of the added operation.
     ///
-    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
-    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    /// [`SelectedOperation::tag`]: struct.SelectedOperation.html#method.tag
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::thread;
-    /// use std::time::Duration;
-    /// use crossbeam_channel::{unbounded, Select};
+    /// use crossbeam_channel::{unbounded, Receiver, Select};
+    ///
+    /// struct Connection {
+    ///     name: &'static str,
+    ///     incoming: Receiver<i32>,
+    /// }
     ///
     /// let (s1, r1) = unbounded();
-    /// let (s2, r2) = unbounded();
+    /// let (_s2, r2) = unbounded();
+    /// let conns = vec![
+    ///     Connection { name: "first", incoming: r1 },
+    ///     Connection { name: "second", incoming: r2 },
+    /// ];
     ///
-    /// thread::spawn(move || {
-    ///     thread::sleep(Duration::from_secs(1));
-    ///     s1.send(10).unwrap();
-    /// });
+    /// let mut sel = Select::new();
+    /// for (i, conn) in conns.iter().enumerate() {
+    ///     sel.recv_tagged(&conn.incoming, i);
+    /// }
+    ///
+    /// s1.send(10).unwrap();
+    ///
+    /// let oper = sel.select();
+    /// let conn = &conns[oper.tag().unwrap()];
+    /// assert_eq!(conn.name, "first");
+    /// assert_eq!(oper.recv(&conn.incoming), Ok(10));
+    /// ```
+    pub fn recv_tagged<T>(&mut self, r: &'a Receiver<T>, tag: usize) -> usize {
+        let i = self.recv(r);
+        self.tags.push((i, tag));
+        i
+    }
+
+    /// Returns the tag attached to the operation with the given index, if any.
+    fn tag(&self, index: usize) -> Option<usize> {
+        self.tags
+            .binary_search_by_key(&index, |&(i, _)| i)
+            .ok()
+            .map(|i| self.tags[i].1)
+    }
+
+    /// Adds an operation waiting for a pollable readiness source.
+    ///
+    /// Returns the index of the added operation.
+    ///
+    /// # Examples
+    ///
+    /// See [`Pollable`] for an example.
+    ///
+    /// [`Pollable`]: struct.Pollable.html
+    pub fn poll<S: PollableSource>(&mut self, p: &'a Pollable<S>) -> usize {
+        let i = self.next_index;
+        let ptr = p as *const Pollable<S> as *const u8;
+        self.push((p, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds an operation that never becomes ready.
+    fn placeholder(&mut self) -> usize {
+        let i = self.next_index;
+        self.push((&flavors::never::PLACEHOLDER, i, ptr::null()));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds an operation to the first tier.
+    fn push(&mut self, handle: (&'a dyn SelectHandle, usize, *const u8)) {
+        self.insert(handle, 0);
+    }
+
+    /// Inserts an operation at the end of its tier.
+    fn insert(&mut self, handle: (&'a dyn SelectHandle, usize, *const u8), tier: usize) {
+        let pos = self.tiers.iter().take_while(|&&t| t <= tier).count();
+        self.handles.insert(pos, handle);
+        self.tiers.insert(pos, tier);
+    }
+
+    /// Moves an operation into a priority tier.
+    ///
+    /// Operations start out in tier 0. When several operations are ready, one in the lowest tier
+    /// is selected, with ties within a tier broken randomly. Blocking operations still wait on all
+    /// tiers at once, and select whichever operation becomes ready first.
+    ///
+    /// This also applies to [`ready`] and its variants.
+    ///
+    /// [`ready`]: struct.Select.html#method.ready
+    ///
+    /// # Panics
+    ///
+    /// An attempt to move a non-existing or removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let (s3, r3) = unbounded();
+    /// s1.send("control").unwrap();
+    /// s2.send("data").unwrap();
+    /// s3.send("data").unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let control = sel.recv(&r1);
+    /// let data1 = sel.recv(&r2);
+    /// let data2 = sel.recv(&r3);
+    /// sel.set_tier(data1, 1);
+    /// sel.set_tier(data2, 1);
+    ///
+    /// // The control channel is always preferred over the data channels.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), control);
+    /// assert_eq!(oper.recv(&r1), Ok("control"));
+    /// ```
+    pub fn set_tier(&mut self, index: usize, tier: usize) {
+        let i = self.position(index);
+        let handle = self.handles.remove(i);
+        self.tiers.remove(i);
+        self.insert(handle, tier);
+    }
+
+    /// Returns the position of the operation with the given index in `handles`.
+    fn position(&self, index: usize) -> usize {
+        assert!(
+            index < self.next_index,
+            "index out of bounds; {} >= {}",
+            index,
+            self.next_index,
+        );
+
+        self.handles
+            .iter()
+            .position(|&(_, i, _)| i == index)
+            .expect("no operation with this index")
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// This is useful when an operation is selected because the channel got disconnected and we
+    /// want to try again to select a different operation instead.
+    ///
+    /// If new operations are added after removing some, the indices of removed operations will not
+    /// be reused.
+    ///
+    /// # Panics
+    ///
+    /// An attempt to remove a non-existing or already removed operation will panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (_, r2) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are initially ready, so a random one will be executed.
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert!(oper.recv(&r2).is_err());
+    /// sel.remove(oper2);
+    ///
+    /// s1.send(10).unwrap();
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper1);
+    /// assert_eq!(oper.recv(&r1), Ok(10));
+    /// ```
+    pub fn remove(&mut self, index: usize) {
+        let i = self.position(index);
+        self.handles.remove(i);
+        self.tiers.remove(i);
+
+        if let Ok(i) = self.tags.binary_search_by_key(&index, |&(i, _)| i) {
+            self.tags.remove(i);
+        }
+    }
+
+    /// Removes all operations.
+    ///
+    /// This makes it possible to reuse the `Select` for a different set of operations without
+    /// allocating again. Indices of removed operations will not be reused, and settings like
+    /// [`set_remove_disconnected`] are kept.
+    ///
+    /// [`set_remove_disconnected`]: struct.Select.html#method.set_remove_disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// sel.clear();
+    /// assert!(sel.is_empty());
+    ///
+    /// let oper2 = sel.recv(&r2);
+    /// assert_ne!(oper1, oper2);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.index(), oper2);
+    /// assert_eq!(oper.recv(&r2), Ok(2));
+    /// ```
+    pub fn clear(&mut self) {
+        self.handles.clear();
+        self.tiers.clear();
+        self.tags.clear();
+    }
+
+    /// Returns `true` if there are no operations left to select.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (_s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// assert!(sel.is_empty());
+    ///
+    /// let oper = sel.recv(&r);
+    /// assert!(!sel.is_empty());
+    ///
+    /// sel.remove(oper);
+    /// assert!(sel.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Sets whether operations on disconnected channels are removed automatically.
+    ///
+    /// By default, an operation on a disconnected channel is always ready, so it keeps getting
+    /// selected and completing with an error. When removal is enabled, [`try_select`],
+    /// [`select`], and [`select_timeout`] instead remove such operations as if by calling
+    /// [`remove`], and keep selecting among the remaining ones. Readiness methods like [`ready`]
+    /// are not affected.
+    ///
+    /// The last remaining operation is removed too, but it is still returned so that the caller
+    /// can observe that all channels are disconnected. After that, [`is_empty`] returns `true`.
+    ///
+    /// [`try_select`]: struct.Select.html#method.try_select
+    /// [`select`]: struct.Select.html#method.select
+    /// [`select_timeout`]: struct.Select.html#method.select_timeout
+    /// [`remove`]: struct.Select.html#method.remove
+    /// [`ready`]: struct.Select.html#method.ready
+    /// [`is_empty`]: struct.Select.html#method.is_empty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let rs = [r1, r2];
+    ///
+    /// s1.send(1).unwrap();
+    /// s2.send(2).unwrap();
+    /// drop(s1);
+    /// drop(s2);
+    ///
+    /// let mut sel = Select::new();
+    /// sel.set_remove_disconnected(true);
+    /// for r in &rs {
+    ///     sel.recv(r);
+    /// }
+    ///
+    /// let mut sum = 0;
+    /// while !sel.is_empty() {
+    ///     let oper = sel.select();
+    ///     let index = oper.index();
+    ///     if let Ok(msg) = oper.recv(&rs[index]) {
+    ///         sum += msg;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn set_remove_disconnected(&mut self, remove: bool) {
+        self.remove_disconnected = remove;
+    }
+
+    /// Returns `true` if every operation is on a disconnected channel.
+    ///
+    /// A send operation is exhausted once its channel is disconnected, and a receive operation
+    /// once its channel is disconnected and empty. Receive operations on channels that will never
+    /// deliver another message, like the ones created by [`never`], are exhausted too. Such
+    /// operations can only ever complete with an error, so there is no point in selecting them.
+    ///
+    /// A `Select` without operations is exhausted as well.
+    ///
+    /// [`never`]: fn.never.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r1);
+    /// sel.send(&s2);
+    /// assert!(!sel.is_exhausted());
+    ///
+    /// drop(s1);
+    /// assert!(!sel.is_exhausted());
+    ///
+    /// drop(r2);
+    /// assert!(sel.is_exhausted());
+    /// ```
+    pub fn is_exhausted(&self) -> bool {
+        self.handles.iter().all(|&(h, _, _)| h.is_exhausted())
+    }
+
+    /// Removes the selected operation if it is on a disconnected channel and removal is enabled.
+    ///
+    /// Returns the operation back if it should be completed by the caller.
+    fn filter(&mut self, mut oper: SelectedOperation<'a>) -> Option<SelectedOperation<'a>> {
+        oper.tag = self.tag(oper.index);
+
+        if !self.remove_disconnected || !oper.is_disconnected() {
+            return Some(oper);
+        }
+
+        self.remove(oper.index);
+        if self.handles.is_empty() {
+            Some(oper)
+        } else {
+            // The operation has nothing to clean up, so it doesn't need to be completed.
+            mem::forget(oper);
+            None
+        }
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// If an operation is ready, it is selected and returned. If multiple operations are ready at
+    /// the same time, a random one among them is selected. If none of the operations are ready, an
+    /// error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// s1.send(10).unwrap();
+    /// s2.send(20).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // Both operations are initially ready, so a random one will be executed.
+    /// let oper = sel.try_select();
+    /// match oper {
+    ///     Err(_) => panic!("both operations should be ready"),
+    ///     Ok(oper) => match oper.index() {
+    ///         i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
+    ///         i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        loop {
+            let oper = try_select(&mut self.handles, tiers(&self.tiers), self.biased)?;
+            if let Some(oper) = self.filter(oper) {
+                return Ok(oper);
+            }
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// Once an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added to `Select`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s1.send(10).unwrap();
+    /// });
+    /// thread::spawn(move || s2.send(20).unwrap());
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// // The second operation will be selected because it becomes ready first.
+    /// let oper = sel.select();
+    /// match oper.index() {
+    ///     i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
+    ///     i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        loop {
+            let oper = select(&mut self.handles, tiers(&self.tiers), self.biased);
+            if let Some(oper) = self.filter(oper) {
+                return oper;
+            }
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it, unless all operations
+    /// are on disconnected channels.
+    ///
+    /// This is like [`select`], except that an error is returned instead of selecting an
+    /// operation once the `Select` is [exhausted]. An event loop can call this method until it
+    /// fails, rather than completing dead operations one by one with errors.
+    ///
+    /// Operations that get disconnected while the thread is blocked are selected as usual, and the
+    /// error is returned by the next call.
+    ///
+    /// [`select`]: struct.Select.html#method.select
+    /// [exhausted]: struct.Select.html#method.is_exhausted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select, SelectError};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let rs = [r1, r2];
+    ///
+    /// thread::spawn(move || {
+    ///     s1.send(1).unwrap();
+    ///     s2.send(2).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// for r in &rs {
+    ///     sel.recv(r);
+    /// }
+    ///
+    /// let mut sum = 0;
+    /// loop {
+    ///     match sel.select_connected() {
+    ///         Ok(oper) => {
+    ///             let index = oper.index();
+    ///             if let Ok(msg) = oper.recv(&rs[index]) {
+    ///                 sum += msg;
+    ///             }
+    ///         }
+    ///         Err(SelectError::AllDisconnected) => break,
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn select_connected(&mut self) -> Result<SelectedOperation<'a>, SelectError> {
+        loop {
+            if self.is_exhausted() {
+                return Err(SelectError::AllDisconnected);
+            }
+
+            let oper = select(&mut self.handles, tiers(&self.tiers), self.biased);
+            if let Some(oper) = self.filter(oper) {
+                return Ok(oper);
+            }
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it, unless the thread keeps
+    /// losing the race for it.
+    ///
+    /// This is like [`select`], except for how often the thread retries. A blocked select is woken
+    /// up when one of its operations becomes ready, but under heavy contention another thread may
+    /// complete that operation first, and the select goes back to sleep. Once that has happened
+    /// more than `retries` times, an error is returned instead, so the caller can back off or
+    /// escalate rather than suffer an unbounded delay.
+    ///
+    /// [`select`]: struct.Select.html#method.select
+    ///
+    /// # Panics
+    ///
+    /// Panics if no operations have been added to `Select`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select, SelectBudgetError};
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     s.send(1).unwrap();
+    /// });
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r);
+    ///
+    /// match sel.select_with_budget(10) {
+    ///     Ok(oper) => {
+    ///         assert_eq!(oper.index(), oper1);
+    ///         assert_eq!(oper.recv(&r), Ok(1));
+    ///     }
+    ///     Err(SelectBudgetError::Contended) => {
+    ///         // Other threads kept taking the messages; back off and try again later.
+    ///     }
+    /// };
+    /// ```
+    pub fn select_with_budget(
+        &mut self,
+        retries: usize,
+    ) -> Result<SelectedOperation<'a>, SelectBudgetError> {
+        loop {
+            let oper =
+                select_with_budget(&mut self.handles, tiers(&self.tiers), self.biased, retries)?;
+            if let Some(oper) = self.filter(oper) {
+                return Ok(oper);
+            }
+        }
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and selects it.
+    ///
+    /// If an operation becomes ready, it is selected and returned. If multiple operations are
+    /// ready at the same time, a random one among them is selected. If none of the operations
+    /// become ready for the specified duration, an error is returned.
+    ///
+    /// An operation is considered to be ready if it doesn't have to block. Note that it is ready
+    /// even when it will simply return an error because the channel is disconnected.
+    ///
+    /// The selected operation must be completed with [`SelectedOperation::send`]
+    /// or [`SelectedOperation::recv`].
+    ///
+    /// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+    /// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(1));
+    ///     s1.send(10).unwrap();
+    /// });
     /// thread::spawn(move || s2.send(20).unwrap());
     ///
     /// let mut sel = Select::new();
@@ -856,7 +1726,21 @@ impl<'a> Select<'a> {
         &mut self,
         timeout: Duration,
     ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
-        select_timeout(&mut self.handles, timeout)
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let now = Instant::now();
+            let timeout = if now < deadline {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+
+            let oper = select_timeout(&mut self.handles, tiers(&self.tiers), self.biased, timeout)?;
+            if let Some(oper) = self.filter(oper) {
+                return Ok(oper);
+            }
+        }
     }
 
     /// Attempts to find a ready operation without blocking.
@@ -896,8 +1780,16 @@ impl<'a> Select<'a> {
     /// }
     /// ```
     pub fn try_ready(&mut self) -> Result<usize, TryReadyError> {
-        match run_ready(&mut self.handles, Timeout::Now) {
-            None => Err(TryReadyError),
+        match run_ready(
+            &mut self.handles,
+            tiers(&self.tiers),
+            self.biased,
+            Timeout::Now,
+        ) {
+            None => Err(TryReadyError::new(
+                self.handles.len(),
+                never_ready(&self.handles),
+            )),
             Some(index) => Ok(index),
         }
     }
@@ -949,7 +1841,13 @@ impl<'a> Select<'a> {
             panic!("no operations have been added to `Select`");
         }
 
-        run_ready(&mut self.handles, Timeout::Never).unwrap()
+        run_ready(
+            &mut self.handles,
+            tiers(&self.tiers),
+            self.biased,
+            Timeout::Never,
+        )
+        .unwrap()
     }
 
     /// Blocks for a limited time until one of the operations becomes ready.
@@ -995,8 +1893,11 @@ impl<'a> Select<'a> {
     pub fn ready_timeout(&mut self, timeout: Duration) -> Result<usize, ReadyTimeoutError> {
         let timeout = Timeout::At(Instant::now() + timeout);
 
-        match run_ready(&mut self.handles, timeout) {
-            None => Err(ReadyTimeoutError),
+        match run_ready(&mut self.handles, tiers(&self.tiers), self.biased, timeout) {
+            None => Err(ReadyTimeoutError::new(
+                self.handles.len(),
+                never_ready(&self.handles),
+            )),
             Some(index) => Ok(index),
         }
     }
@@ -1006,7 +1907,11 @@ impl<'a> Clone for Select<'a> {
     fn clone(&self) -> Select<'a> {
         Select {
             handles: self.handles.clone(),
+            tiers: self.tiers.clone(),
+            tags: self.tags.clone(),
             next_index: self.next_index,
+            remove_disconnected: self.remove_disconnected,
+            biased: self.biased,
         }
     }
 }
@@ -1023,9 +1928,21 @@ impl<'a> fmt::Debug for Select<'a> {
     }
 }
 
+/// Returns the tiers to pass to `run_select` and `run_ready`.
+///
+/// If all operations are in tier 0, the tiers are omitted.
+fn tiers(tiers: &[usize]) -> &[usize] {
+    match tiers.last() {
+        Some(&t) if t > 0 => tiers,
+        _ => &[],
+    }
+}
+
 /// A selected operation that needs to be completed.
 ///
-/// To complete the operation, call [`send`] or [`recv`].
+/// To complete the operation, call [`send`] or [`recv`]. If the mapping from operations to
+/// senders and receivers is assembled at runtime, [`try_send`] and [`try_recv`] report passing
+/// the wrong one as an error instead of panicking.
 ///
 /// # Panics
 ///
@@ -1034,6 +1951,8 @@ impl<'a> fmt::Debug for Select<'a> {
 ///
 /// [`send`]: struct.SelectedOperation.html#method.send
 /// [`recv`]: struct.SelectedOperation.html#method.recv
+/// [`try_send`]: struct.SelectedOperation.html#method.try_send
+/// [`try_recv`]: struct.SelectedOperation.html#method.try_recv
 #[must_use]
 pub struct SelectedOperation<'a> {
     /// Token needed to complete the operation.
@@ -1045,6 +1964,12 @@ pub struct SelectedOperation<'a> {
     /// The address of the selected `Sender` or `Receiver`.
     ptr: *const u8,
 
+    /// The tag attached to the selected operation, if any.
+    tag: Option<usize>,
+
+    /// Set once the operation has been completed by `try_send` or `try_recv`.
+    completed: bool,
+
     /// Indicates that `Sender`s and `Receiver`s are borrowed.
     _marker: PhantomData<&'a ()>,
 }
@@ -1078,6 +2003,60 @@ impl<'a> SelectedOperation<'a> {
         self.index
     }
 
+    /// Returns the tag attached to the selected operation.
+    ///
+    /// Returns `None` if the operation was added without a tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv_tagged(&r, 42);
+    ///
+    /// let oper = sel.select();
+    /// assert_eq!(oper.tag(), Some(42));
+    /// assert_eq!(oper.recv(&r), Ok(1));
+    /// ```
+    pub fn tag(&self) -> Option<usize> {
+        self.tag
+    }
+
+    /// Returns `true` if the operation was selected because its channel is disconnected.
+    ///
+    /// Completing such an operation always returns an error. Checking this first makes it
+    /// possible to tell a dead channel apart from one that delivered a message, for example to
+    /// [`remove`] it from the `Select`.
+    ///
+    /// The operation still has to be completed with [`send`] or [`recv`].
+    ///
+    /// [`remove`]: struct.Select.html#method.remove
+    /// [`send`]: struct.SelectedOperation.html#method.send
+    /// [`recv`]: struct.SelectedOperation.html#method.recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select, RecvError};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// drop(s);
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r);
+    ///
+    /// let oper = sel.select();
+    /// assert!(oper.is_disconnected());
+    /// assert_eq!(oper.recv(&r), Err(RecvError));
+    /// ```
+    pub fn is_disconnected(&self) -> bool {
+        self.token.is_disconnected()
+    }
+
     /// Completes the send operation.
     ///
     /// The passed [`Sender`] reference must be the same one that was used in [`Select::send`]
@@ -1085,7 +2064,8 @@ impl<'a> SelectedOperation<'a> {
     ///
     /// # Panics
     ///
-    /// Panics if an incorrect [`Sender`] reference is passed.
+    /// Panics if an incorrect [`Sender`] reference is passed, or if the operation has already been
+    /// completed.
     ///
     /// # Examples
     ///
@@ -1106,6 +2086,7 @@ impl<'a> SelectedOperation<'a> {
     /// [`Sender`]: struct.Sender.html
     /// [`Select::send`]: struct.Select.html#method.send
     pub fn send<T>(mut self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(!self.completed, "the operation has already been completed");
         assert!(
             s as *const Sender<T> as *const u8 == self.ptr,
             "passed a sender that wasn't selected",
@@ -1122,7 +2103,8 @@ impl<'a> SelectedOperation<'a> {
     ///
     /// # Panics
     ///
-    /// Panics if an incorrect [`Receiver`] reference is passed.
+    /// Panics if an incorrect [`Receiver`] reference is passed, or if the operation has already
+    /// been completed.
     ///
     /// # Examples
     ///
@@ -1143,6 +2125,7 @@ impl<'a> SelectedOperation<'a> {
     /// [`Receiver`]: struct.Receiver.html
     /// [`Select::recv`]: struct.Select.html#method.recv
     pub fn recv<T>(mut self, r: &Receiver<T>) -> Result<T, RecvError> {
+        assert!(!self.completed, "the operation has already been completed");
         assert!(
             r as *const Receiver<T> as *const u8 == self.ptr,
             "passed a receiver that wasn't selected",
@@ -1151,6 +2134,150 @@ impl<'a> SelectedOperation<'a> {
         mem::forget(self);
         res.map_err(|_| RecvError)
     }
+
+    /// Attempts to complete the send operation.
+    ///
+    /// This is like [`send`], except that passing a [`Sender`] reference other than the one used
+    /// in [`Select::send`] returns [`SelectedSendError::WrongOperand`] with the message instead of
+    /// panicking. The operation is then left as it was, and still has to be completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select, SelectedSendError};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, _r2) = unbounded();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.send(&s1);
+    ///
+    /// let mut oper = sel.select();
+    /// assert_eq!(oper.try_send(&s2, 10), Err(SelectedSendError::WrongOperand(10)));
+    /// assert_eq!(oper.try_send(&s1, 10), Ok(()));
+    /// assert_eq!(r1.recv(), Ok(10));
+    /// ```
+    ///
+    /// [`send`]: struct.SelectedOperation.html#method.send
+    /// [`Sender`]: struct.Sender.html
+    /// [`Select::send`]: struct.Select.html#method.send
+    /// [`SelectedSendError::WrongOperand`]: enum.SelectedSendError.html#variant.WrongOperand
+    pub fn try_send<T>(&mut self, s: &Sender<T>, msg: T) -> Result<(), SelectedSendError<T>> {
+        if self.completed || s as *const Sender<T> as *const u8 != self.ptr {
+            return Err(SelectedSendError::WrongOperand(msg));
+        }
+        self.completed = true;
+        let res = unsafe { channel::write(s, &mut self.token, msg) };
+        res.map_err(SelectedSendError::Disconnected)
+    }
+
+    /// Attempts to complete the receive operation.
+    ///
+    /// This is like [`recv`], except that passing a [`Receiver`] reference other than the one
+    /// used in [`Select::recv`] returns [`SelectedRecvError::WrongOperand`] instead of panicking.
+    /// The operation is then left as it was, and still has to be completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select, SelectedRecvError};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (_s2, r2) = unbounded::<i32>();
+    /// s1.send(7).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r1);
+    ///
+    /// let mut oper = sel.select();
+    /// assert_eq!(oper.try_recv(&r2), Err(SelectedRecvError::WrongOperand));
+    /// assert_eq!(oper.try_recv(&r1), Ok(7));
+    /// ```
+    ///
+    /// [`recv`]: struct.SelectedOperation.html#method.recv
+    /// [`Receiver`]: struct.Receiver.html
+    /// [`Select::recv`]: struct.Select.html#method.recv
+    /// [`SelectedRecvError::WrongOperand`]: enum.SelectedRecvError.html#variant.WrongOperand
+    pub fn try_recv<T>(&mut self, r: &Receiver<T>) -> Result<T, SelectedRecvError> {
+        if self.completed || r as *const Receiver<T> as *const u8 != self.ptr {
+            return Err(SelectedRecvError::WrongOperand);
+        }
+        self.completed = true;
+        let res = unsafe { channel::read(r, &mut self.token) };
+        res.map_err(|_| SelectedRecvError::Disconnected)
+    }
+
+    /// Completes the receive operation, guarding the message until it is handled.
+    ///
+    /// This is like [`recv`], except that the message comes wrapped in an [`InFlight`]
+    /// guard. If the guard is dropped while its thread is panicking, the message is handed to
+    /// `fallback` instead of being lost. Passing a sender of the same channel puts the message
+    /// back into it, while passing a sender of another channel dead-letters it there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed, or if the operation has already
+    /// been completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::panic;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded();
+    /// s.send(1).unwrap();
+    ///
+    /// let result = panic::catch_unwind(|| {
+    ///     let mut sel = Select::new();
+    ///     sel.recv(&r);
+    ///
+    ///     let msg = sel.select().recv_guarded(&r, &s).unwrap();
+    ///     if *msg == 1 {
+    ///         panic!("failed to handle {}", *msg);
+    ///     }
+    /// });
+    /// assert!(result.is_err());
+    ///
+    /// // The message went back into the channel.
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// ```
+    ///
+    /// [`recv`]: struct.SelectedOperation.html#method.recv
+    /// [`InFlight`]: struct.InFlight.html
+    /// [`Receiver`]: struct.Receiver.html
+    pub fn recv_guarded<'s, T>(
+        self,
+        r: &Receiver<T>,
+        fallback: &'s Sender<T>,
+    ) -> Result<InFlight<'s, T>, RecvError> {
+        self.recv(r).map(|msg| InFlight {
+            msg: Some(msg),
+            fallback,
+        })
+    }
+
+    /// Completes the operation waiting for a pollable readiness source.
+    ///
+    /// The passed [`Pollable`] reference must be the same one that was used in [`Select::poll`]
+    /// when the operation was added. The source was ready when the operation was selected, so it
+    /// can now be used without blocking, unless another thread got to it first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Pollable`] reference is passed, or if the operation has already
+    /// been completed.
+    ///
+    /// [`Pollable`]: struct.Pollable.html
+    /// [`Select::poll`]: struct.Select.html#method.poll
+    pub fn poll<S>(self, p: &Pollable<S>) {
+        assert!(!self.completed, "the operation has already been completed");
+        assert!(
+            p as *const Pollable<S> as *const u8 == self.ptr,
+            "passed a pollable source that wasn't selected",
+        );
+        mem::forget(self);
+    }
 }
 
 impl<'a> fmt::Debug for SelectedOperation<'a> {
@@ -1161,6 +2288,66 @@ impl<'a> fmt::Debug for SelectedOperation<'a> {
 
 impl<'a> Drop for SelectedOperation<'a> {
     fn drop(&mut self) {
-        panic!("dropped `SelectedOperation` without completing the operation");
+        if !self.completed {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
+
+/// A message received by [`SelectedOperation::recv_guarded`].
+///
+/// The guard dereferences to the message. If it is dropped while its thread is panicking, the
+/// handler failed halfway through and the message is sent to the fallback channel with
+/// [`Sender::try_send`]. The message is dropped only if that channel is full or disconnected.
+///
+/// Handling is complete when the guard is dropped normally or unwrapped with [`into_inner`].
+///
+/// [`SelectedOperation::recv_guarded`]: struct.SelectedOperation.html#method.recv_guarded
+/// [`Sender::try_send`]: struct.Sender.html#method.try_send
+/// [`into_inner`]: struct.InFlight.html#method.into_inner
+pub struct InFlight<'a, T: 'a> {
+    /// The message, taken out by `into_inner`.
+    msg: Option<T>,
+
+    /// Where the message goes if handling it panics.
+    fallback: &'a Sender<T>,
+}
+
+impl<'a, T> InFlight<'a, T> {
+    /// Unwraps the message.
+    ///
+    /// A panic after this point no longer hands the message to the fallback channel.
+    pub fn into_inner(mut self) -> T {
+        self.msg.take().unwrap()
+    }
+}
+
+impl<'a, T> Deref for InFlight<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.msg.as_ref().unwrap()
+    }
+}
+
+impl<'a, T> DerefMut for InFlight<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.msg.as_mut().unwrap()
+    }
+}
+
+impl<'a, T> Drop for InFlight<'a, T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            if let Some(msg) = self.msg.take() {
+                let _ = self.fallback.try_send(msg);
+            }
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for InFlight<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InFlight").field("msg", &**self).finish()
     }
 }
@@ -38,17 +38,33 @@ macro_rules! crossbeam_channel_delegate {
 /// 3. `@case`: Parses a single case and verifies its argument list.
 ///
 /// The codegen stage consists of these subparts:
-/// 1. `@init`: Attempts to optimize `select!` away and initializes the list of handles.
+/// 1. `@init`: Attempts to optimize `select!` away and starts binding operands.
 /// 1. `@count`: Counts the listed cases.
-/// 3. `@add`: Adds send/receive operations to the list of handles and starts selection.
+/// 3. `@add`: Binds the senders/receivers, creates the list of handles, and starts selection.
 /// 4. `@complete`: Completes the selected send/receive operation.
 ///
+/// Every sender/receiver expression is bound with a `match` before the list of handles is
+/// created, so temporaries produced by those expressions outlive the list and the selection.
+///
 /// If the parsing stage encounters a syntax error or the codegen stage ends up with too many
 /// cases to process, the macro fails with a compile-time error.
+#[cfg(feature = "select-macro")]
 #[doc(hidden)]
 #[macro_export(local_inner_macros)]
 macro_rules! crossbeam_channel_internal {
-    // The list is empty. Now check the arguments of each processed case.
+    // The list is empty. Now check the arguments of each processed case, keeping the marker of
+    // `select_biased!` in front.
+    (@list
+        ()
+        (@biased, $($head:tt)*)
+    ) => {
+        crossbeam_channel_internal!(
+            @case
+            ($($head)*)
+            (@biased,)
+            ()
+        )
+    };
     (@list
         ()
         ($($head:tt)*)
@@ -609,7 +625,7 @@ macro_rules! crossbeam_channel_internal {
             ref _r => {
                 let _r: &$crate::Receiver<_> = _r;
                 match _r.try_recv() {
-                    ::std::result::Result::Err($crate::TryRecvError::Empty) => {
+                    $crate::internal::Result::Err($crate::TryRecvError::Empty) => {
                         $default_body
                     }
                     _res => {
@@ -644,7 +660,7 @@ macro_rules! crossbeam_channel_internal {
             ref _r => {
                 let _r: &$crate::Receiver<_> = _r;
                 match _r.recv_timeout($timeout) {
-                    ::std::result::Result::Err($crate::RecvTimeoutError::Timeout) => {
+                    $crate::internal::Result::Err($crate::RecvTimeoutError::Timeout) => {
                         $default_body
                     }
                     _res => {
@@ -755,19 +771,41 @@ macro_rules! crossbeam_channel_internal {
     //     }
     // }};
 
-    // Create the list of handles and add operations to it.
+    // Select cases in the order they're listed in.
+    (@init
+        (@biased, $($cases:tt)*)
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @init
+            true
+            ($($cases)*)
+            $default
+        )
+    };
+    // Select a random ready case.
+    (@init
+        $cases:tt
+        $default:tt
+    ) => {
+        crossbeam_channel_internal!(
+            @init
+            false
+            $cases
+            $default
+        )
+    };
+    // Bind the operands and add operations to the list of handles.
     (@init
+        $biased:tt
         ($($cases:tt)*)
         $default:tt
     ) => {{
         const _LEN: usize = crossbeam_channel_internal!(@count ($($cases)*));
-        let _handle: &$crate::internal::SelectHandle = &$crate::never::<()>();
-
-        #[allow(unused_mut)]
-        let mut _sel = [(_handle, 0, ::std::ptr::null()); _LEN];
 
         crossbeam_channel_internal!(
             @add
+            $biased
             _sel
             ($($cases)*)
             $default
@@ -806,6 +844,7 @@ macro_rules! crossbeam_channel_internal {
                 (31usize _oper31)
             )
             ()
+            ()
         )
     }};
 
@@ -819,19 +858,19 @@ macro_rules! crossbeam_channel_internal {
 
     // Run blocking selection.
     (@add
+        $biased:tt
         $sel:ident
         ()
         ()
         $labels:tt
         $cases:tt
+        $handles:tt
     ) => {{
-        let _oper: $crate::SelectedOperation<'_> = {
-            let _oper = $crate::internal::select(&mut $sel);
+        crossbeam_channel_internal!(@handles $sel $handles);
 
-            // Erase the lifetime so that `sel` can be dropped early even without NLL.
-            #[allow(unsafe_code)]
-            unsafe { ::std::mem::transmute(_oper) }
-        };
+        // The selected operation borrows the senders and receivers, not `sel`, so `sel` can
+        // still be dropped before the operation is completed.
+        let _oper = $crate::internal::select(&mut $sel, &[], $biased);
 
         crossbeam_channel_internal! {
             @complete
@@ -842,26 +881,24 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Run non-blocking selection.
     (@add
+        $biased:tt
         $sel:ident
         ()
         (default() => $body:tt,)
         $labels:tt
         $cases:tt
+        $handles:tt
     ) => {{
-        let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::try_select(&mut $sel);
+        crossbeam_channel_internal!(@handles $sel $handles);
 
-            // Erase the lifetime so that `sel` can be dropped early even without NLL.
-            #[allow(unsafe_code)]
-            unsafe { ::std::mem::transmute(_oper) }
-        };
+        let _oper = $crate::internal::try_select(&mut $sel, &[], $biased);
 
         match _oper {
-            None => {
+            $crate::internal::Result::Err(_) => {
                 { $sel };
                 $body
             }
-            Some(_oper) => {
+            $crate::internal::Result::Ok(_oper) => {
                 crossbeam_channel_internal! {
                     @complete
                     $sel
@@ -873,26 +910,24 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Run selection with a timeout.
     (@add
+        $biased:tt
         $sel:ident
         ()
         (default($timeout:expr) => $body:tt,)
         $labels:tt
         $cases:tt
+        $handles:tt
     ) => {{
-        let _oper: ::std::option::Option<$crate::SelectedOperation<'_>> = {
-            let _oper = $crate::internal::select_timeout(&mut $sel, $timeout);
+        crossbeam_channel_internal!(@handles $sel $handles);
 
-            // Erase the lifetime so that `sel` can be dropped early even without NLL.
-            #[allow(unsafe_code)]
-            unsafe { ::std::mem::transmute(_oper) }
-        };
+        let _oper = $crate::internal::select_timeout(&mut $sel, &[], $biased, $timeout);
 
         match _oper {
-            ::std::option::Option::None => {
+            $crate::internal::Result::Err(_) => {
                 { $sel };
                 $body
             }
-            ::std::option::Option::Some(_oper) => {
+            $crate::internal::Result::Ok(_oper) => {
                 crossbeam_channel_internal! {
                     @complete
                     $sel
@@ -904,81 +939,81 @@ macro_rules! crossbeam_channel_internal {
     }};
     // Have we used up all labels?
     (@add
+        $biased:tt
         $sel:ident
         $input:tt
         $default:tt
         ()
         $cases:tt
+        $handles:tt
     ) => {
         crossbeam_channel_delegate!(compile_error("too many operations in a `select!` block"))
     };
     // Add a receive operation to `sel`.
     (@add
+        $biased:tt
         $sel:ident
         (recv($r:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
         (($i:tt $var:ident) $($labels:tt)*)
         ($($cases:tt)*)
+        ($($handles:tt)*)
     ) => {{
         match $r {
             ref _r => {
-                #[allow(unsafe_code)]
-                let $var: &$crate::Receiver<_> = unsafe {
-                    let _r: &$crate::Receiver<_> = _r;
-
-                    // Erase the lifetime so that `sel` can be dropped early even without NLL.
-                    unsafe fn unbind<'a, T>(x: &T) -> &'a T {
-                        ::std::mem::transmute(x)
-                    }
-                    unbind(_r)
-                };
-                $sel[$i] = ($var, $i, $var as *const $crate::Receiver<_> as *const u8);
+                let $var: &$crate::Receiver<_> = _r;
 
                 crossbeam_channel_internal!(
                     @add
+                    $biased
                     $sel
                     ($($tail)*)
                     $default
                     ($($labels)*)
                     ($($cases)* [$i] recv($var) -> $res => $body,)
+                    ($($handles)* ($var, $i, $var as *const $crate::Receiver<_> as *const u8),)
                 )
             }
         }
     }};
     // Add a send operation to `sel`.
     (@add
+        $biased:tt
         $sel:ident
         (send($s:expr, $m:expr) -> $res:pat => $body:tt, $($tail:tt)*)
         $default:tt
         (($i:tt $var:ident) $($labels:tt)*)
         ($($cases:tt)*)
+        ($($handles:tt)*)
     ) => {{
         match $s {
             ref _s => {
-                #[allow(unsafe_code)]
-                let $var: &$crate::Sender<_> = unsafe {
-                    let _s: &$crate::Sender<_> = _s;
-
-                    // Erase the lifetime so that `sel` can be dropped early even without NLL.
-                    unsafe fn unbind<'a, T>(x: &T) -> &'a T {
-                        ::std::mem::transmute(x)
-                    }
-                    unbind(_s)
-                };
-                $sel[$i] = ($var, $i, $var as *const $crate::Sender<_> as *const u8);
+                let $var: &$crate::Sender<_> = _s;
 
                 crossbeam_channel_internal!(
                     @add
+                    $biased
                     $sel
                     ($($tail)*)
                     $default
                     ($($labels)*)
                     ($($cases)* [$i] send($var, $m) -> $res => $body,)
+                    ($($handles)* ($var, $i, $var as *const $crate::Sender<_> as *const u8),)
                 )
             }
         }
     }};
 
+    // Create the list of handles once all operands are bound.
+    (@handles
+        $sel:ident
+        ($(($var:ident, $i:tt, $ptr:expr),)*)
+    ) => {
+        #[allow(unused_mut)]
+        let mut $sel: [(&$crate::internal::SelectHandle, usize, *const u8); _LEN] =
+            [$(($var, $i, $ptr),)*];
+    };
+
     // Complete a receive operation.
     (@complete
         $sel:ident
@@ -1032,6 +1067,25 @@ macro_rules! crossbeam_channel_internal {
         ))
     }};
 
+    // The entry points of `select_biased!`.
+    (@biased) => {
+        crossbeam_channel_delegate!(compile_error("empty `select_biased!` block"))
+    };
+    (@biased $($case:ident $(($($args:tt)*))* => $body:expr $(,)*)*) => {
+        crossbeam_channel_internal!(
+            @list
+            ($($case $(($($args)*))* => { $body },)*)
+            (@biased,)
+        )
+    };
+    (@biased $($tokens:tt)*) => {
+        crossbeam_channel_internal!(
+            @list
+            ($($tokens)*)
+            (@biased,)
+        )
+    };
+
     // Catches a bug within this macro (should not happen).
     (@$($tokens:tt)*) => {
         crossbeam_channel_delegate!(compile_error(
@@ -1066,7 +1120,7 @@ macro_rules! crossbeam_channel_internal {
 ///
 /// This macro allows you to define a set of channel operations, wait until any one of them becomes
 /// ready, and finally execute it. If multiple operations are ready at the same time, a random one
-/// among them is selected.
+/// among them is selected. Use [`select_biased!`] to prefer the operations listed first instead.
 ///
 /// It is also possible to define a `default` case that gets executed if none of the operations are
 /// ready, either right away or for a certain duration of time.
@@ -1077,6 +1131,7 @@ macro_rules! crossbeam_channel_internal {
 /// The `select` macro is a convenience wrapper around [`Select`]. However, it cannot select over a
 /// dynamically created list of channel operations.
 ///
+/// [`select_biased!`]: macro.select_biased.html
 /// [`Select`]: struct.Select.html
 ///
 /// # Examples
@@ -1204,6 +1259,7 @@ macro_rules! crossbeam_channel_internal {
 ///
 /// [`never`]: fn.never.html
 /// [example]: fn.never.html#examples
+#[cfg(feature = "select-macro")]
 #[macro_export(local_inner_macros)]
 macro_rules! select {
     ($($tokens:tt)*) => {
@@ -1212,3 +1268,49 @@ macro_rules! select {
         )
     };
 }
+
+/// Selects from a set of channel operations, preferring the ones listed first.
+///
+/// This macro is like [`select!`], except that if multiple operations are ready at the same time,
+/// the one listed first is selected instead of a random one. Blocking still waits on all
+/// operations at once, and selects whichever becomes ready first.
+///
+/// Note that an operation that is always ready will starve all operations listed after it.
+///
+/// This is a convenience wrapper around [`Select::new_biased`].
+///
+/// [`select!`]: macro.select.html
+/// [`Select::new_biased`]: struct.Select.html#method.new_biased
+///
+/// # Examples
+///
+/// Handle control messages before any pending work:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use crossbeam_channel::unbounded;
+///
+/// let (control_s, control_r) = unbounded();
+/// let (work_s, work_r) = unbounded();
+///
+/// work_s.send("job").unwrap();
+/// control_s.send("stop").unwrap();
+///
+/// select_biased! {
+///     recv(control_r) -> msg => assert_eq!(msg, Ok("stop")),
+///     recv(work_r) -> _ => panic!("control messages go first"),
+/// }
+/// # }
+/// ```
+#[cfg(feature = "select-macro")]
+#[macro_export(local_inner_macros)]
+macro_rules! select_biased {
+    ($($tokens:tt)*) => {
+        crossbeam_channel_internal!(
+            @biased
+            $($tokens)*
+        )
+    };
+}
@@ -0,0 +1,265 @@
+//! Channels that enforce a communication protocol at compile time.
+//!
+//! A protocol is a type built out of [`Send`], [`Recv`], [`Choose`], [`Offer`], and [`End`]. A
+//! [`Chan`] is parameterized by the protocol its endpoint has yet to follow, and every operation
+//! consumes the endpoint and returns a new one for the rest of the protocol. Sequencing the
+//! operations incorrectly, like receiving a response before sending the request, is therefore a
+//! type error.
+//!
+//! The two endpoints created by [`channel`] follow dual protocols: whenever one side sends, the
+//! other side receives. Under the hood, messages are exchanged through a pair of zero-capacity
+//! channels, so every send waits for the other side to receive it.
+//!
+//! If the other side is dropped before the protocol is finished, operations return an error
+//! instead of blocking forever.
+//!
+//! [`Send`]: struct.Send.html
+//! [`Recv`]: struct.Recv.html
+//! [`Choose`]: struct.Choose.html
+//! [`Offer`]: struct.Offer.html
+//! [`End`]: struct.End.html
+//! [`Chan`]: struct.Chan.html
+//! [`channel`]: fn.channel.html
+//!
+//! # Examples
+//!
+//! A request/response handshake:
+//!
+//! ```
+//! use std::thread;
+//! use crossbeam_channel::session::{self, End, Recv, Send};
+//!
+//! // The client sends a number and receives its square.
+//! type Client = Send<u64, Recv<u64, End>>;
+//!
+//! let (client, server) = session::channel::<Client>();
+//!
+//! thread::spawn(move || {
+//!     let (n, server) = server.recv().unwrap();
+//!     server.send(n * n).unwrap().close();
+//! });
+//!
+//! let client = client.send(7).unwrap();
+//! let (square, client) = client.recv().unwrap();
+//! client.close();
+//!
+//! assert_eq!(square, 49);
+//! ```
+//!
+//! Receiving the response before sending the request doesn't compile:
+//!
+//! ```compile_fail
+//! use crossbeam_channel::session::{self, End, Recv, Send};
+//!
+//! let (client, _server) = session::channel::<Send<u64, Recv<u64, End>>>();
+//! let (square, client) = client.recv().unwrap();
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::marker::{self, PhantomData};
+
+use channel::{bounded, Receiver, Sender};
+use err::{RecvError, SendError};
+
+/// A protocol step that sends a `T` and continues with `P`.
+#[derive(Debug)]
+pub struct Send<T, P>(PhantomData<(T, P)>);
+
+/// A protocol step that receives a `T` and continues with `P`.
+#[derive(Debug)]
+pub struct Recv<T, P>(PhantomData<(T, P)>);
+
+/// A protocol step that picks one of two continuations, `L` or `R`.
+#[derive(Debug)]
+pub struct Choose<L, R>(PhantomData<(L, R)>);
+
+/// A protocol step that lets the other side pick one of two continuations, `L` or `R`.
+#[derive(Debug)]
+pub struct Offer<L, R>(PhantomData<(L, R)>);
+
+/// The end of a protocol.
+#[derive(Debug)]
+pub struct End(());
+
+/// A protocol with a dual, which is followed by the other side of the channel.
+pub trait Protocol: Sized {
+    /// The protocol followed by the other side.
+    type Dual: Protocol<Dual = Self>;
+}
+
+impl Protocol for End {
+    type Dual = End;
+}
+
+impl<T, P: Protocol> Protocol for Send<T, P> {
+    type Dual = Recv<T, P::Dual>;
+}
+
+impl<T, P: Protocol> Protocol for Recv<T, P> {
+    type Dual = Send<T, P::Dual>;
+}
+
+impl<L: Protocol, R: Protocol> Protocol for Choose<L, R> {
+    type Dual = Offer<L::Dual, R::Dual>;
+}
+
+impl<L: Protocol, R: Protocol> Protocol for Offer<L, R> {
+    type Dual = Choose<L::Dual, R::Dual>;
+}
+
+/// Creates a pair of endpoints following protocol `P` and its dual.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::session::{self, Branch, Choose, End, Recv, Send};
+///
+/// // The client either asks for a greeting or hangs up.
+/// type Client = Choose<Send<String, Recv<String, End>>, End>;
+///
+/// let (client, server) = session::channel::<Client>();
+///
+/// let t = thread::spawn(move || match server.offer().unwrap() {
+///     Branch::Left(server) => {
+///         let (name, server) = server.recv().unwrap();
+///         server.send(format!("Hello, {}!", name)).unwrap().close();
+///     }
+///     Branch::Right(server) => server.close(),
+/// });
+///
+/// let client = client.left().unwrap();
+/// let (greeting, client) = client.send("world".to_string()).unwrap().recv().unwrap();
+/// client.close();
+///
+/// assert_eq!(greeting, "Hello, world!");
+/// t.join().unwrap();
+/// ```
+pub fn channel<P: Protocol>() -> (Chan<P>, Chan<P::Dual>) {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(0);
+    (Chan::new(s1, r2), Chan::new(s2, r1))
+}
+
+/// A message of any type exchanged by the endpoints.
+type Message = Box<dyn Any + marker::Send>;
+
+/// An endpoint that has yet to follow protocol `P`.
+///
+/// Created by the [`channel`] function.
+///
+/// [`channel`]: fn.channel.html
+pub struct Chan<P> {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    _protocol: PhantomData<fn() -> P>,
+}
+
+impl<P> Chan<P> {
+    /// Creates an endpoint from the underlying channels.
+    fn new(sender: Sender<Message>, receiver: Receiver<Message>) -> Chan<P> {
+        Chan {
+            sender,
+            receiver,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Converts the endpoint to the next step of the protocol.
+    fn advance<Q>(self) -> Chan<Q> {
+        Chan::new(self.sender, self.receiver)
+    }
+
+    /// Sends a message to the other side.
+    fn write<T: marker::Send + 'static>(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender
+            .send(Box::new(msg))
+            .map_err(|SendError(msg)| SendError(*msg.downcast::<T>().unwrap()))
+    }
+
+    /// Receives a message from the other side.
+    fn read<T: 'static>(&self) -> Result<T, RecvError> {
+        let msg = self.receiver.recv()?;
+        // Dual protocols guarantee that the other side sent a message of the expected type.
+        Ok(*msg
+            .downcast::<T>()
+            .expect("received a message of unexpected type"))
+    }
+}
+
+impl<T: marker::Send + 'static, P> Chan<Send<T, P>> {
+    /// Sends a message and continues with the rest of the protocol.
+    ///
+    /// If the other side has been dropped, the message is returned back in the error.
+    pub fn send(self, msg: T) -> Result<Chan<P>, SendError<T>> {
+        self.write(msg)?;
+        Ok(self.advance())
+    }
+}
+
+impl<T: marker::Send + 'static, P> Chan<Recv<T, P>> {
+    /// Receives a message and continues with the rest of the protocol.
+    ///
+    /// If the other side has been dropped, an error is returned.
+    pub fn recv(self) -> Result<(T, Chan<P>), RecvError> {
+        let msg = self.read()?;
+        Ok((msg, self.advance()))
+    }
+}
+
+impl<L, R> Chan<Choose<L, R>> {
+    /// Picks the left continuation.
+    ///
+    /// If the other side has been dropped, an error is returned.
+    pub fn left(self) -> Result<Chan<L>, SendError<()>> {
+        self.write(true).map_err(|_| SendError(()))?;
+        Ok(self.advance())
+    }
+
+    /// Picks the right continuation.
+    ///
+    /// If the other side has been dropped, an error is returned.
+    pub fn right(self) -> Result<Chan<R>, SendError<()>> {
+        self.write(false).map_err(|_| SendError(()))?;
+        Ok(self.advance())
+    }
+}
+
+impl<L, R> Chan<Offer<L, R>> {
+    /// Waits for the other side to pick a continuation.
+    ///
+    /// If the other side has been dropped, an error is returned.
+    pub fn offer(self) -> Result<Branch<Chan<L>, Chan<R>>, RecvError> {
+        if self.read()? {
+            Ok(Branch::Left(self.advance()))
+        } else {
+            Ok(Branch::Right(self.advance()))
+        }
+    }
+}
+
+impl Chan<End> {
+    /// Closes the endpoint after finishing the protocol.
+    ///
+    /// This is the same as dropping it, but makes it explicit that the protocol is complete.
+    pub fn close(self) {}
+}
+
+impl<P> fmt::Debug for Chan<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Chan { .. }")
+    }
+}
+
+/// The continuation picked by the other side of an [`Offer`].
+///
+/// [`Offer`]: struct.Offer.html
+#[derive(Debug)]
+pub enum Branch<L, R> {
+    /// The left continuation.
+    Left(L),
+
+    /// The right continuation.
+    Right(R),
+}
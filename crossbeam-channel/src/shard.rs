@@ -0,0 +1,202 @@
+//! Receivers that split a channel among several consumers while numbering its messages.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::Receiver;
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use select::Select;
+use utils::Spinlock;
+
+/// Splits a receiver into `n` shards that collectively drain it.
+///
+/// Every message is received by exactly one of the shards, together with its sequence number: the
+/// position of the message in the order it was received from the original channel, starting from
+/// zero. Messages processed in parallel by several shards can later be put back in the original
+/// order by sorting on the sequence number.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::{shard, unbounded};
+///
+/// let (s, r) = unbounded();
+/// for i in 0..100 {
+///     s.send(i).unwrap();
+/// }
+/// drop(s);
+///
+/// let handles: Vec<_> = shard(r, 4)
+///     .into_iter()
+///     .map(|shard| thread::spawn(move || shard.iter().collect::<Vec<_>>()))
+///     .collect();
+///
+/// let mut results: Vec<_> = handles
+///     .into_iter()
+///     .flat_map(|h| h.join().unwrap())
+///     .map(|m| (m.seq, m.value * 2))
+///     .collect();
+///
+/// // Restore the original order.
+/// results.sort();
+/// assert!(results.iter().enumerate().all(|(i, &(seq, v))| seq == i as u64 && v == 2 * i));
+/// ```
+pub fn shard<T>(receiver: Receiver<T>, n: usize) -> Vec<Shard<T>> {
+    assert!(n > 0, "number of shards must be positive");
+
+    let shared = Arc::new(Shared {
+        receiver,
+        next: Spinlock::new(0),
+    });
+    (0..n)
+        .map(|index| Shard {
+            shared: shared.clone(),
+            index,
+        })
+        .collect()
+}
+
+/// A message together with its sequence number.
+///
+/// Received from a [`Shard`].
+///
+/// [`Shard`]: struct.Shard.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sequenced<T> {
+    /// The position of the message in the original channel, starting from zero.
+    pub seq: u64,
+
+    /// The message.
+    pub value: T,
+}
+
+/// State shared by all shards of a receiver.
+struct Shared<T> {
+    /// The original receiver.
+    receiver: Receiver<T>,
+
+    /// The sequence number of the next message.
+    ///
+    /// Receiving a message and numbering it happen under this lock, so numbers follow the order of
+    /// the original channel.
+    next: Spinlock<u64>,
+}
+
+/// One of the receivers created by [`shard`].
+///
+/// [`shard`]: fn.shard.html
+pub struct Shard<T> {
+    shared: Arc<Shared<T>>,
+    index: usize,
+}
+
+impl<T> Shard<T> {
+    /// Returns the index of this shard, between zero and the number of shards.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub fn try_recv(&self) -> Result<Sequenced<T>, TryRecvError> {
+        let mut next = self.shared.next.lock();
+        let value = self.shared.receiver.try_recv()?;
+
+        let seq = *next;
+        *next += 1;
+        Ok(Sequenced { seq, value })
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    pub fn recv(&self) -> Result<Sequenced<T>, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            // Another shard may take the message first, in which case we wait again.
+            let mut sel = Select::new();
+            sel.recv(&self.shared.receiver);
+            sel.ready();
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Sequenced<T>, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+
+            let mut sel = Select::new();
+            sel.recv(&self.shared.receiver);
+            let _ = sel.ready_timeout(deadline - now);
+        }
+    }
+
+    /// A blocking iterator over messages received by this shard.
+    ///
+    /// The iterator ends once the channel is empty and disconnected.
+    pub fn iter(&self) -> ShardIter<T> {
+        ShardIter { shard: self }
+    }
+
+    /// Returns the original receiver, which becomes ready whenever a message is available.
+    ///
+    /// This can be used to wait for a message in [`select!`], and then take it with
+    /// [`try_recv`]. Another shard might take the message first, in which case [`try_recv`] fails.
+    /// Messages received from the original receiver directly don't get sequence numbers.
+    ///
+    /// [`select!`]: macro.select.html
+    /// [`try_recv`]: struct.Shard.html#method.try_recv
+    pub fn source(&self) -> &Receiver<T> {
+        &self.shared.receiver
+    }
+}
+
+impl<T> fmt::Debug for Shard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Shard { .. }")
+    }
+}
+
+/// A blocking iterator over messages received by a [`Shard`].
+///
+/// Created by [`Shard::iter`].
+///
+/// [`Shard`]: struct.Shard.html
+/// [`Shard::iter`]: struct.Shard.html#method.iter
+pub struct ShardIter<'a, T: 'a> {
+    shard: &'a Shard<T>,
+}
+
+impl<'a, T> Iterator for ShardIter<'a, T> {
+    type Item = Sequenced<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.shard.recv().ok()
+    }
+}
+
+impl<'a, T> fmt::Debug for ShardIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ShardIter { .. }")
+    }
+}
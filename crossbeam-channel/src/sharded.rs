@@ -0,0 +1,188 @@
+//! Channels whose senders write into separate shards, for fan-in from many producers.
+//!
+//! In an ordinary unbounded channel, all senders append messages to the same tail, which becomes
+//! a point of contention once many producer threads send at a high rate. A sharded channel gives
+//! each group of senders its own tail, and the receiving side merges the shards lazily as it
+//! receives. The receiving side is an ordinary [`Receiver`], so it can be used in [`select!`] like
+//! any other.
+//!
+//! The price is ordering: messages sent through the same shard are received in the order they
+//! were sent, but there is no order among messages sent through different shards. Senders are
+//! [`ShardedSender`]s rather than [`Sender`]s to make this explicit. All clones of a sender send
+//! into the same shard.
+//!
+//! All shards are unbounded.
+//!
+//! [`Receiver`]: ../struct.Receiver.html
+//! [`Sender`]: ../struct.Sender.html
+//! [`ShardedSender`]: struct.ShardedSender.html
+//! [`select!`]: ../macro.select.html
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate crossbeam_channel;
+//! # extern crate crossbeam_utils;
+//! # fn main() {
+//! use crossbeam_channel::sharded;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let (senders, r) = sharded::channel(4);
+//!
+//! scope(|scope| {
+//!     for s in senders {
+//!         scope.spawn(move |_| {
+//!             for i in 0..100 {
+//!                 s.send((s.shard(), i)).unwrap();
+//!             }
+//!         });
+//!     }
+//! })
+//! .unwrap();
+//!
+//! // Messages from different shards are interleaved, but each shard keeps its own order.
+//! let mut last = [None; 4];
+//! for (shard, i) in r.iter() {
+//!     assert!(last[shard] < Some(i));
+//!     last[shard] = Some(i);
+//! }
+//! assert_eq!(last, [Some(99); 4]);
+//! # }
+//! ```
+
+use std::fmt;
+
+use channel::{self, Receiver};
+use counter;
+use err::{SendError, SendTimeoutError};
+use flavors;
+
+/// Creates a channel with the given number of shards.
+///
+/// Returns one sender for each shard, and the receiver that merges them.
+///
+/// # Panics
+///
+/// Panics if `shards` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::sharded;
+///
+/// let (senders, r) = sharded::channel(2);
+/// senders[0].send(1).unwrap();
+/// senders[1].send(2).unwrap();
+/// senders[0].send(3).unwrap();
+///
+/// let mut msgs: Vec<_> = r.try_iter().collect();
+/// msgs.sort();
+/// assert_eq!(msgs, [1, 2, 3]);
+/// ```
+pub fn channel<T>(shards: usize) -> (Vec<ShardedSender<T>>, Receiver<T>) {
+    assert!(shards > 0, "a sharded channel needs at least one shard");
+
+    let (s, r) = channel::sharded(shards);
+    let mut senders = (1..shards)
+        .map(|shard| ShardedSender {
+            chan: s.acquire(),
+            shard,
+        })
+        .collect::<Vec<_>>();
+    senders.insert(0, ShardedSender { chan: s, shard: 0 });
+    (senders, r)
+}
+
+/// The sending side of a sharded channel.
+///
+/// Messages are received in the order they were sent only relative to other messages sent into
+/// the same shard. Clones of a sender send into the same shard.
+///
+/// Created by the [`channel`] function.
+///
+/// [`channel`]: fn.channel.html
+pub struct ShardedSender<T> {
+    /// The channel.
+    chan: counter::Sender<flavors::sharded::Channel<T>>,
+
+    /// The shard this sender sends into.
+    shard: usize,
+}
+
+unsafe impl<T: Send> Send for ShardedSender<T> {}
+unsafe impl<T: Send> Sync for ShardedSender<T> {}
+
+impl<T> ShardedSender<T> {
+    /// Sends a message into the shard of this sender.
+    ///
+    /// This method never blocks. If the channel is disconnected, the message is returned back in
+    /// the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{sharded, SendError};
+    ///
+    /// let (senders, r) = sharded::channel(1);
+    /// senders[0].send(1).unwrap();
+    /// assert_eq!(r.recv(), Ok(1));
+    ///
+    /// drop(r);
+    /// assert_eq!(senders[0].send(2), Err(SendError(2)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.chan.send(self.shard, msg).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Returns the index of the shard this sender sends into.
+    pub fn shard(&self) -> usize {
+        self.shard
+    }
+
+    /// Returns the number of shards in the channel.
+    pub fn shards(&self) -> usize {
+        self.chan.shards()
+    }
+
+    /// Creates `n` clones of the sender at once, all sending into the same shard.
+    pub fn clone_n(&self, n: usize) -> Vec<ShardedSender<T>> {
+        (0..n).map(|_| self.clone()).collect()
+    }
+
+    /// Returns the number of messages in the channel, across all shards.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if all shards are empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+}
+
+impl<T> Drop for ShardedSender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<T> Clone for ShardedSender<T> {
+    fn clone(&self) -> Self {
+        ShardedSender {
+            chan: self.chan.acquire(),
+            shard: self.shard,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ShardedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = (self.chan.count(), self.chan.receiver_count());
+        channel::debug_endpoint(f, "ShardedSender", &*self.chan, Some(counts))
+    }
+}
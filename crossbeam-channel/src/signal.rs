@@ -0,0 +1,131 @@
+//! Signals about the endpoints of a channel.
+
+use std::fmt;
+use std::ops;
+use std::time::Duration;
+
+use channel::Receiver;
+use err::{RecvTimeoutError, TryRecvError};
+
+/// A signal that gets set once all receivers of a channel have been dropped.
+///
+/// Created by [`Sender::disconnected`]. A producer that spends a long time preparing messages
+/// can check the signal or wait for it in a select, and abort early instead of finding out about
+/// the disconnection on the next send.
+///
+/// The signal dereferences to a [`Receiver`] that never receives any messages. Once the signal is
+/// set, receive operations on it fail right away, so it can be used in [`select!`] like any other
+/// receiver.
+///
+/// [`Sender::disconnected`]: struct.Sender.html#method.disconnected
+/// [`Receiver`]: struct.Receiver.html
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{bounded, tick};
+///
+/// let (s, r) = bounded::<i32>(1);
+/// let gone = s.disconnected();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     drop(r);
+/// });
+///
+/// let ticker = tick(Duration::from_millis(10));
+/// let mut batches = 0;
+/// loop {
+///     select! {
+///         recv(gone) -> _ => break,
+///         recv(ticker) -> _ => batches += 1,
+///     }
+/// }
+///
+/// assert!(gone.is_set());
+/// assert!(batches > 0);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DisconnectedSignal {
+    receiver: Receiver<()>,
+}
+
+impl DisconnectedSignal {
+    /// Wraps the receiving side of a signal channel.
+    pub(crate) fn new(receiver: Receiver<()>) -> DisconnectedSignal {
+        DisconnectedSignal { receiver }
+    }
+
+    /// Returns `true` if all receivers of the channel have been dropped.
+    pub fn is_set(&self) -> bool {
+        self.receiver.try_recv() == Err(TryRecvError::Disconnected)
+    }
+
+    /// Blocks the current thread until all receivers of the channel have been dropped.
+    pub fn wait(&self) {
+        let _ = self.receiver.recv();
+    }
+
+    /// Waits until all receivers of the channel have been dropped, but only for a limited time.
+    ///
+    /// Returns `true` if the signal got set before the timeout.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.receiver.recv_timeout(timeout) == Err(RecvTimeoutError::Disconnected)
+    }
+}
+
+impl ops::Deref for DisconnectedSignal {
+    type Target = Receiver<()>;
+
+    fn deref(&self) -> &Receiver<()> {
+        &self.receiver
+    }
+}
+
+impl fmt::Debug for DisconnectedSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DisconnectedSignal")
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
+
+/// A change in the number of endpoints of a channel.
+///
+/// Received from the channel returned by [`Receiver::endpoint_events`]. Every event carries the
+/// number of endpoints of its kind right after the change.
+///
+/// [`Receiver::endpoint_events`]: struct.Receiver.html#method.endpoint_events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EndpointEvent {
+    /// A sender was cloned.
+    SenderAttached {
+        /// The number of senders after the clone.
+        senders: usize,
+    },
+
+    /// A sender was dropped.
+    SenderDetached {
+        /// The number of remaining senders.
+        senders: usize,
+    },
+
+    /// A receiver was cloned.
+    ReceiverAttached {
+        /// The number of receivers after the clone.
+        receivers: usize,
+    },
+
+    /// A receiver was dropped.
+    ReceiverDetached {
+        /// The number of remaining receivers.
+        receivers: usize,
+    },
+}
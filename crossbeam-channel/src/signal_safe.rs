@@ -0,0 +1,174 @@
+//! Bounded channels that can be sent into from signal handlers.
+//!
+//! Only a few functions may be called from a signal handler: the signal can arrive while the
+//! interrupted thread holds a lock or is in the middle of allocating memory, and a handler that
+//! waits for the same lock or allocates deadlocks or corrupts the heap. Regular senders don't
+//! qualify, since waking a blocked receiver takes a lock.
+//!
+//! The [`try_send`] method of a [`SignalSender`] is async-signal-safe: it takes no locks, doesn't
+//! allocate or free memory, and never waits for another thread. This makes it possible to hand
+//! log records or other events from a signal handler to a regular thread. A receiver blocked on
+//! the channel is woken up right away, unless the signal interrupted a thread in the middle of
+//! registering with or waking up receivers, in which case that thread wakes it up once it's done.
+//!
+//! The receiving side is an ordinary [`Receiver`] and has no such restrictions.
+//!
+//! The guarantee doesn't hold with the `fence-audit` feature, which adds thread-local state to
+//! the channel protocols.
+//!
+//! [`try_send`]: struct.SignalSender.html#method.try_send
+//! [`SignalSender`]: struct.SignalSender.html
+//! [`Receiver`]: ../struct.Receiver.html
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::signal_safe;
+//!
+//! let (s, r) = signal_safe::channel(16);
+//!
+//! // In a signal handler:
+//! let _ = s.try_send("interrupted");
+//!
+//! assert_eq!(r.recv(), Ok("interrupted"));
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use channel::{self, Receiver};
+use counter;
+use err::{SendError, SendTimeoutError, TrySendError};
+use flavors;
+
+/// Creates a bounded channel whose sender can send from signal handlers.
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{signal_safe, TrySendError};
+///
+/// let (s, r) = signal_safe::channel(1);
+/// assert_eq!(s.try_send(1), Ok(()));
+/// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+/// assert_eq!(r.recv(), Ok(1));
+/// ```
+pub fn channel<T>(cap: usize) -> (SignalSender<T>, Receiver<T>) {
+    let (s, r) = channel::signal_safe(cap);
+    (SignalSender { chan: s }, r)
+}
+
+/// The sending side of a channel that can be sent into from signal handlers.
+///
+/// Only [`try_send`], [`len`], [`is_empty`], [`is_full`], and [`capacity`] are async-signal-safe.
+/// In particular, clones of the sender have to be created and dropped outside of signal handlers.
+///
+/// Created by the [`channel`] function.
+///
+/// [`try_send`]: struct.SignalSender.html#method.try_send
+/// [`len`]: struct.SignalSender.html#method.len
+/// [`is_empty`]: struct.SignalSender.html#method.is_empty
+/// [`is_full`]: struct.SignalSender.html#method.is_full
+/// [`capacity`]: struct.SignalSender.html#method.capacity
+/// [`channel`]: fn.channel.html
+pub struct SignalSender<T> {
+    chan: counter::Sender<flavors::array::Channel<T>>,
+}
+
+unsafe impl<T: Send> Send for SignalSender<T> {}
+unsafe impl<T: Send> Sync for SignalSender<T> {}
+
+impl<T> SignalSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This method is async-signal-safe. Besides failing when the channel is full or
+    /// disconnected, it may also fail with [`TrySendError::Full`] when a receiver is still freeing
+    /// up the slot the message would go into, since waiting for the receiver could deadlock if
+    /// the signal interrupted it.
+    ///
+    /// Dropping the message when the send fails is up to the caller, and is only
+    /// async-signal-safe if dropping a `T` is.
+    ///
+    /// [`TrySendError::Full`]: ../enum.TrySendError.html#variant.Full
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{signal_safe, TrySendError};
+    ///
+    /// let (s, r) = signal_safe::channel(1);
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+    /// ```
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.chan.try_send_from_signal(msg)
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// This method is not async-signal-safe.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.chan.send(msg, None).map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// This method is not async-signal-safe.
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        let deadline = Instant::now() + timeout;
+        self.chan.send(msg, Some(deadline))
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chan.is_empty()
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.chan.is_full()
+    }
+
+    /// Returns the capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.chan.capacity().unwrap()
+    }
+}
+
+impl<T> Drop for SignalSender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chan.release(|c| c.disconnect());
+        }
+    }
+}
+
+impl<T> Clone for SignalSender<T> {
+    fn clone(&self) -> Self {
+        SignalSender {
+            chan: self.chan.acquire(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SignalSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = (self.chan.count(), self.chan.receiver_count());
+        channel::debug_endpoint(f, "SignalSender", &*self.chan, Some(counts))
+    }
+}
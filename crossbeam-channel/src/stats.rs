@@ -0,0 +1,129 @@
+//! Occupancy statistics for bounded channels.
+
+use std::fmt;
+#[cfg(feature = "flavor-array")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of buckets in an occupancy histogram.
+const BUCKETS: usize = 8;
+
+/// Occupancy of a channel, recorded on every send.
+#[cfg(feature = "flavor-array")]
+pub struct Occupancy {
+    /// The maximum number of messages in the channel since the last query.
+    high_water: AtomicUsize,
+
+    /// Number of sends that left the channel with occupancy in each bucket.
+    histogram: [AtomicUsize; BUCKETS],
+}
+
+#[cfg(feature = "flavor-array")]
+impl Occupancy {
+    /// Creates empty occupancy statistics.
+    pub fn new() -> Occupancy {
+        Occupancy {
+            high_water: AtomicUsize::new(0),
+            histogram: Default::default(),
+        }
+    }
+
+    /// Records that a send left `len` messages in a channel of capacity `cap`.
+    #[inline]
+    pub fn record(&self, len: usize, cap: usize) {
+        let mut high_water = self.high_water.load(Ordering::Relaxed);
+        while len > high_water {
+            match self.high_water.compare_exchange_weak(
+                high_water,
+                len,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(h) => high_water = h,
+            }
+        }
+
+        let bucket = bucket(len, cap);
+        self.histogram[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Takes a snapshot of the statistics and resets them.
+    ///
+    /// The high-water mark is reset to `len`, the current number of messages in the channel.
+    pub fn take(&self, len: usize) -> OccupancyStats {
+        let mut histogram = [0; BUCKETS];
+        for (h, count) in histogram.iter_mut().zip(self.histogram.iter()) {
+            *h = count.swap(0, Ordering::Relaxed);
+        }
+
+        OccupancyStats {
+            high_water_mark: self.high_water.swap(len, Ordering::Relaxed),
+            histogram,
+        }
+    }
+}
+
+/// Returns the histogram bucket for occupancy `len` in a channel of capacity `cap`.
+#[cfg(feature = "flavor-array")]
+fn bucket(len: usize, cap: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        ((len - 1) * BUCKETS / cap).min(BUCKETS - 1)
+    }
+}
+
+/// A snapshot of occupancy statistics of a bounded channel.
+///
+/// Returned by [`Sender::take_occupancy_stats`] and [`Receiver::take_occupancy_stats`] on
+/// channels created by [`bounded_with_stats`].
+///
+/// [`Sender::take_occupancy_stats`]: struct.Sender.html#method.take_occupancy_stats
+/// [`Receiver::take_occupancy_stats`]: struct.Receiver.html#method.take_occupancy_stats
+/// [`bounded_with_stats`]: fn.bounded_with_stats.html
+#[derive(Clone, PartialEq, Eq)]
+pub struct OccupancyStats {
+    high_water_mark: usize,
+    histogram: [usize; BUCKETS],
+}
+
+impl OccupancyStats {
+    /// Returns the maximum number of messages that were in the channel during the period covered
+    /// by the snapshot.
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    /// Returns the coarse occupancy histogram.
+    ///
+    /// The capacity of the channel is split into eight equal ranges, and each bucket counts the
+    /// sends that left the channel with occupancy in the corresponding range. The first bucket
+    /// covers the lowest eighth of the capacity, and the last bucket covers the highest eighth,
+    /// including a full channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::bounded_with_stats;
+    ///
+    /// let (s, r) = bounded_with_stats(16);
+    /// for i in 0..16 {
+    ///     s.send(i).unwrap();
+    /// }
+    ///
+    /// let stats = r.take_occupancy_stats().unwrap();
+    /// assert_eq!(stats.histogram(), &[2; 8]);
+    /// ```
+    pub fn histogram(&self) -> &[usize] {
+        &self.histogram
+    }
+}
+
+impl fmt::Debug for OccupancyStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OccupancyStats")
+            .field("high_water_mark", &self.high_water_mark)
+            .field("histogram", &self.histogram)
+            .finish()
+    }
+}
@@ -0,0 +1,1449 @@
+//! Synchronization primitives that can be waited on in [`select!`].
+//!
+//! [`select!`]: ../macro.select.html
+
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_utils::Backoff;
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvTimeoutError, TryRecvError};
+use utils::Spinlock;
+
+/// The promise has not been completed yet.
+const EMPTY: usize = 0;
+
+/// A thread is writing the value into the promise.
+const WRITING: usize = 1;
+
+/// The promise holds its value.
+const DONE: usize = 2;
+
+/// A value that gets set exactly once and can be waited on by any number of threads.
+///
+/// Many threads may attempt to [`complete`] the promise, but only the first one succeeds. The
+/// others get their value back, so nothing is lost in a race.
+///
+/// Waiting threads either block in [`wait`], or wait for the receiver returned by [`ready`], which
+/// can be used in [`select!`] alongside other operations. Clones of a promise share the value.
+///
+/// [`complete`]: struct.Promise.html#method.complete
+/// [`wait`]: struct.Promise.html#method.wait
+/// [`ready`]: struct.Promise.html#method.ready
+/// [`select!`]: ../macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::sync::Promise;
+///
+/// let promise = Promise::new();
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let promise = promise.clone();
+///         thread::spawn(move || promise.complete(i).is_ok())
+///     })
+///     .collect();
+///
+/// let winners = handles
+///     .into_iter()
+///     .map(|h| h.join().unwrap())
+///     .filter(|&won| won)
+///     .count();
+///
+/// assert_eq!(winners, 1);
+/// assert!(*promise.wait() < 4);
+/// ```
+pub struct Promise<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// State shared by all clones of a promise.
+struct Inner<T> {
+    /// One of `EMPTY`, `WRITING`, or `DONE`.
+    state: AtomicUsize,
+
+    /// The value, which is set once the state is `DONE`.
+    value: UnsafeCell<Option<T>>,
+
+    /// Dropped on completion, which disconnects `ready`.
+    signal: Spinlock<Option<Sender<()>>>,
+
+    /// Becomes ready once the promise is completed.
+    ready: Receiver<()>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send + Sync> Sync for Inner<T> {}
+
+impl<T> Promise<T> {
+    /// Creates a promise that hasn't been completed yet.
+    pub fn new() -> Promise<T> {
+        let (s, r) = unbounded();
+        Promise {
+            inner: Arc::new(Inner {
+                state: AtomicUsize::new(EMPTY),
+                value: UnsafeCell::new(None),
+                signal: Spinlock::new(Some(s)),
+                ready: r,
+            }),
+        }
+    }
+
+    /// Completes the promise with `value`, waking up all waiting threads.
+    ///
+    /// If the promise has already been completed, the value is returned back in the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::sync::Promise;
+    ///
+    /// let promise = Promise::new();
+    /// assert_eq!(promise.complete(1), Ok(()));
+    /// assert_eq!(promise.complete(2), Err(2));
+    /// assert_eq!(promise.get(), Some(&1));
+    /// ```
+    pub fn complete(&self, value: T) -> Result<(), T> {
+        if self
+            .inner
+            .state
+            .compare_exchange(EMPTY, WRITING, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(value);
+        }
+
+        // Only the thread that moved the state to `WRITING` can get here, and nobody reads the
+        // value before the state is `DONE`.
+        unsafe {
+            *self.inner.value.get() = Some(value);
+        }
+        self.inner.state.store(DONE, Ordering::Release);
+
+        // Disconnect the channel to wake up waiting threads.
+        let signal = self.inner.signal.lock().take();
+        drop(signal);
+        Ok(())
+    }
+
+    /// Returns the value if the promise has been completed.
+    pub fn get(&self) -> Option<&T> {
+        if self.inner.state.load(Ordering::Acquire) == DONE {
+            // The value never changes once the state is `DONE`.
+            unsafe { (*self.inner.value.get()).as_ref() }
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the promise has been completed.
+    pub fn is_complete(&self) -> bool {
+        self.inner.state.load(Ordering::Acquire) == DONE
+    }
+
+    /// Blocks the current thread until the promise is completed and returns the value.
+    pub fn wait(&self) -> &T {
+        loop {
+            if let Some(value) = self.get() {
+                return value;
+            }
+            let _ = self.inner.ready.recv();
+        }
+    }
+
+    /// Waits for the promise to be completed, but only for a limited time.
+    ///
+    /// If the promise doesn't get completed before the timeout, `None` is returned.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<&T> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(value) = self.get() {
+                return Some(value);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            let _ = self.inner.ready.recv_timeout(deadline - now);
+        }
+    }
+
+    /// Returns a receiver that becomes ready once the promise is completed.
+    ///
+    /// The receiver never delivers messages. It gets disconnected when the promise is completed,
+    /// so that receive operations on it return an error from then on. This makes it possible to
+    /// wait for the promise in [`select!`].
+    ///
+    /// [`select!`]: ../macro.select.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::sync::Promise;
+    ///
+    /// let promise = Promise::new();
+    /// let p = promise.clone();
+    /// thread::spawn(move || p.complete("done").unwrap());
+    ///
+    /// select! {
+    ///     recv(promise.ready()) -> _ => assert_eq!(promise.get(), Some(&"done")),
+    ///     default(Duration::from_secs(1)) => panic!("timed out"),
+    /// }
+    /// # }
+    /// ```
+    pub fn ready(&self) -> &Receiver<()> {
+        &self.inner.ready
+    }
+}
+
+impl<T> Clone for Promise<T> {
+    fn clone(&self) -> Promise<T> {
+        Promise {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Default for Promise<T> {
+    fn default() -> Promise<T> {
+        Promise::new()
+    }
+}
+
+impl<T> fmt::Debug for Promise<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Promise { .. }")
+    }
+}
+
+/// A mutual exclusion lock whose acquisition can be waited on in [`select!`].
+///
+/// The lock is a single token passed around through a channel. Acquiring the lock means
+/// receiving the token from the receiver returned by [`acquire`], so waiting for the lock can be
+/// combined with other operations in [`select!`]. The received [`LockToken`] is then turned into
+/// a guard with [`guard`]. Dropping the guard puts the token back and releases the lock.
+///
+/// Threads waiting for the lock are served in the same order as receivers waiting on a channel.
+///
+/// [`select!`]: ../macro.select.html
+/// [`acquire`]: struct.SelectMutex.html#method.acquire
+/// [`LockToken`]: struct.LockToken.html
+/// [`guard`]: struct.SelectMutex.html#method.guard
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::sync::SelectMutex;
+/// use crossbeam_channel::unbounded;
+///
+/// let state = Arc::new(SelectMutex::new(Vec::new()));
+/// let (s, r) = unbounded();
+///
+/// let st = state.clone();
+/// let worker = thread::spawn(move || {
+///     let mut pending = Vec::new();
+///     loop {
+///         select! {
+///             recv(r) -> job => match job {
+///                 Ok(job) => pending.push(job),
+///                 Err(_) => break,
+///             },
+///             recv(st.acquire()) -> token => {
+///                 // The shared state is ours until the guard is dropped.
+///                 let mut state = st.guard(token.unwrap());
+///                 state.extend(pending.drain(..));
+///             }
+///         }
+///     }
+///     st.lock().extend(pending);
+/// });
+///
+/// for i in 0..10 {
+///     s.send(i).unwrap();
+/// }
+/// drop(s);
+/// worker.join().unwrap();
+///
+/// let mut v = state.lock().clone();
+/// v.sort();
+/// assert_eq!(v, (0..10).collect::<Vec<_>>());
+/// # }
+/// ```
+pub struct SelectMutex<T> {
+    /// The protected value.
+    value: UnsafeCell<T>,
+
+    /// Holds the token while the lock is free.
+    tokens: Receiver<LockToken>,
+}
+
+unsafe impl<T: Send> Send for SelectMutex<T> {}
+unsafe impl<T: Send> Sync for SelectMutex<T> {}
+
+impl<T> SelectMutex<T> {
+    /// Creates a new unlocked mutex holding `value`.
+    pub fn new(value: T) -> SelectMutex<T> {
+        let (s, r) = bounded(1);
+        s.send(LockToken {
+            home: Some(s.clone()),
+        })
+        .unwrap();
+
+        SelectMutex {
+            value: UnsafeCell::new(value),
+            tokens: r,
+        }
+    }
+
+    /// Blocks the current thread until the lock is acquired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::sync::SelectMutex;
+    ///
+    /// let m = SelectMutex::new(1);
+    /// *m.lock() += 1;
+    /// assert_eq!(*m.lock(), 2);
+    /// ```
+    pub fn lock(&self) -> SelectMutexGuard<'_, T> {
+        // The token holds a sender, so the channel can't get disconnected while we're alive.
+        self.guard(self.tokens.recv().unwrap())
+    }
+
+    /// Attempts to acquire the lock without blocking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::sync::SelectMutex;
+    ///
+    /// let m = SelectMutex::new(());
+    /// let guard = m.try_lock().unwrap();
+    /// assert!(m.try_lock().is_none());
+    ///
+    /// drop(guard);
+    /// assert!(m.try_lock().is_some());
+    /// ```
+    pub fn try_lock(&self) -> Option<SelectMutexGuard<'_, T>> {
+        self.tokens.try_recv().ok().map(|token| self.guard(token))
+    }
+
+    /// Waits for the lock, but only for a limited time.
+    ///
+    /// If the lock doesn't get acquired before the timeout, `None` is returned.
+    pub fn lock_timeout(&self, timeout: Duration) -> Option<SelectMutexGuard<'_, T>> {
+        self.tokens
+            .recv_timeout(timeout)
+            .ok()
+            .map(|token| self.guard(token))
+    }
+
+    /// Returns a receiver that delivers the lock token whenever the lock is free.
+    ///
+    /// Receiving the token acquires the lock, which must then be turned into a guard with
+    /// [`guard`]. Dropping the token instead releases the lock again. This makes it possible to
+    /// wait for the lock in [`select!`].
+    ///
+    /// [`guard`]: struct.SelectMutex.html#method.guard
+    /// [`select!`]: ../macro.select.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use std::time::Duration;
+    /// use crossbeam_channel::sync::SelectMutex;
+    ///
+    /// let m = SelectMutex::new(0);
+    ///
+    /// select! {
+    ///     recv(m.acquire()) -> token => *m.guard(token.unwrap()) += 1,
+    ///     default(Duration::from_secs(1)) => panic!("timed out"),
+    /// }
+    ///
+    /// assert_eq!(*m.lock(), 1);
+    /// # }
+    /// ```
+    pub fn acquire(&self) -> &Receiver<LockToken> {
+        &self.tokens
+    }
+
+    /// Turns a token received from [`acquire`] into a guard.
+    ///
+    /// [`acquire`]: struct.SelectMutex.html#method.acquire
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token belongs to a different mutex.
+    pub fn guard(&self, token: LockToken) -> SelectMutexGuard<'_, T> {
+        assert!(
+            token.home.as_ref().map(|s| s.addr()) == self.tokens.addr(),
+            "lock token belongs to a different mutex"
+        );
+
+        SelectMutexGuard {
+            mutex: self,
+            _token: token,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// No locking is needed because the mutex is borrowed mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Consumes the mutex and returns the value.
+    pub fn into_inner(self) -> T {
+        // The mutex implements `Drop`, so its fields have to be moved out by hand.
+        let mutex = ManuallyDrop::new(self);
+        release_tokens(&mutex.tokens);
+        unsafe {
+            drop(ptr::read(&mutex.tokens));
+            ptr::read(mutex.value.get())
+        }
+    }
+}
+
+impl<T> Drop for SelectMutex<T> {
+    fn drop(&mut self) {
+        release_tokens(&self.tokens);
+    }
+}
+
+/// Breaks the cycle formed by a token sitting in its own channel.
+fn release_tokens(tokens: &Receiver<LockToken>) {
+    if let Ok(mut token) = tokens.try_recv() {
+        token.home = None;
+    }
+}
+
+impl<T: Default> Default for SelectMutex<T> {
+    fn default() -> SelectMutex<T> {
+        SelectMutex::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for SelectMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SelectMutex { .. }")
+    }
+}
+
+/// Proof of holding the lock of a [`SelectMutex`], received from [`SelectMutex::acquire`].
+///
+/// Dropping the token releases the lock.
+///
+/// [`SelectMutex`]: struct.SelectMutex.html
+/// [`SelectMutex::acquire`]: struct.SelectMutex.html#method.acquire
+pub struct LockToken {
+    /// The channel the token returns to when dropped.
+    ///
+    /// Set to `None` once the mutex is gone.
+    home: Option<Sender<LockToken>>,
+}
+
+impl Drop for LockToken {
+    fn drop(&mut self) {
+        if let Some(home) = self.home.take() {
+            let token = LockToken {
+                home: Some(home.clone()),
+            };
+            // The send fails only if the mutex is gone, in which case the token is discarded.
+            if let Err(err) = home.try_send(token) {
+                err.into_inner().home = None;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LockToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("LockToken { .. }")
+    }
+}
+
+/// A guard that releases the lock of a [`SelectMutex`] when dropped.
+///
+/// [`SelectMutex`]: struct.SelectMutex.html
+pub struct SelectMutexGuard<'a, T: 'a> {
+    mutex: &'a SelectMutex<T>,
+    _token: LockToken,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Deref for SelectMutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for SelectMutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for SelectMutexGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A notification primitive for telling waiting threads that something has changed.
+///
+/// [`notify_one`] wakes up one waiting thread, or stores a permit if nobody is waiting, so that
+/// the next thread to wait returns right away. At most one permit is stored. [`notify_all`] wakes
+/// up all waiting threads, but doesn't store a permit.
+///
+/// Waiting starts with [`notified`], which registers the current thread and returns a
+/// [`Notified`] handle. A notification sent after the handle is created is never missed, so the
+/// usual pattern is to create the handle, check the shared state, and then wait on the handle.
+/// The handle can also be waited on in [`select!`].
+///
+/// [`notify_one`]: struct.Notify.html#method.notify_one
+/// [`notify_all`]: struct.Notify.html#method.notify_all
+/// [`notified`]: struct.Notify.html#method.notified
+/// [`Notified`]: struct.Notified.html
+/// [`select!`]: ../macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::sync::Notify;
+///
+/// let notify = Notify::new();
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let (n, r) = (notify.clone(), ready.clone());
+/// thread::spawn(move || {
+///     r.store(true, Ordering::SeqCst);
+///     n.notify_all();
+/// });
+///
+/// loop {
+///     let notified = notify.notified();
+///     if ready.load(Ordering::SeqCst) {
+///         break;
+///     }
+///     notified.wait();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Notify {
+    state: Arc<Spinlock<NotifyState>>,
+}
+
+/// State shared by all clones of a `Notify`.
+struct NotifyState {
+    /// Set when `notify_one` found nobody waiting.
+    permit: bool,
+
+    /// Registered waiters, in the order they were registered.
+    waiters: VecDeque<Arc<Waiter>>,
+}
+
+impl NotifyState {
+    /// Wakes up the first registered waiter, or stores a permit if there are none.
+    fn notify_one(&mut self) {
+        match self.waiters.pop_front() {
+            Some(waiter) => {
+                waiter.one.store(true, Ordering::Relaxed);
+                let _ = waiter.sender.try_send(());
+            }
+            None => self.permit = true,
+        }
+    }
+}
+
+/// A waiter registered with a `Notify`.
+struct Waiter {
+    /// Delivers the notification.
+    sender: Sender<()>,
+
+    /// Set if the notification came from `notify_one`.
+    one: AtomicBool,
+}
+
+impl Notify {
+    /// Creates a new `Notify` with no stored permit.
+    pub fn new() -> Notify {
+        Notify {
+            state: Arc::new(Spinlock::new(NotifyState {
+                permit: false,
+                waiters: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Wakes up one waiting thread, or stores a permit if there are none.
+    ///
+    /// Waiters are woken up in the order they called [`notified`].
+    ///
+    /// [`notified`]: struct.Notify.html#method.notified
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::sync::Notify;
+    ///
+    /// let notify = Notify::new();
+    /// notify.notify_one();
+    ///
+    /// // The stored permit is consumed by the next waiter.
+    /// let notified = notify.notified();
+    /// assert!(notified.is_notified());
+    /// assert!(!notify.notified().is_notified());
+    /// ```
+    pub fn notify_one(&self) {
+        self.state.lock().notify_one();
+    }
+
+    /// Wakes up all waiting threads.
+    ///
+    /// Unlike [`notify_one`], this doesn't store a permit if nobody is waiting.
+    ///
+    /// [`notify_one`]: struct.Notify.html#method.notify_one
+    pub fn notify_all(&self) {
+        let waiters = {
+            let mut state = self.state.lock();
+            let waiters = state.waiters.drain(..).collect::<Vec<_>>();
+            waiters
+        };
+        for waiter in waiters {
+            let _ = waiter.sender.try_send(());
+        }
+    }
+
+    /// Registers the current thread as a waiter and returns a handle for waiting.
+    ///
+    /// If a permit is stored, it is consumed and the handle is notified right away.
+    pub fn notified(&self) -> Notified {
+        let (s, r) = bounded(1);
+        let waiter = Arc::new(Waiter {
+            sender: s,
+            one: AtomicBool::new(false),
+        });
+
+        let mut state = self.state.lock();
+        if state.permit {
+            state.permit = false;
+            waiter.one.store(true, Ordering::Relaxed);
+            let _ = waiter.sender.try_send(());
+        } else {
+            state.waiters.push_back(waiter.clone());
+        }
+        drop(state);
+
+        Notified {
+            receiver: r,
+            waiter,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Blocks the current thread until it gets notified.
+    pub fn wait(&self) {
+        self.notified().wait();
+    }
+
+    /// Waits until the current thread gets notified, but only for a limited time.
+    ///
+    /// Returns `true` if the thread got notified before the timeout.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.notified().wait_timeout(timeout)
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Notify {
+        Notify::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Notify { .. }")
+    }
+}
+
+/// A registration for a single notification from a [`Notify`].
+///
+/// Created by [`Notify::notified`]. The handle dereferences to a [`Receiver`] that delivers one
+/// message once the handle is notified, so it can be used in [`select!`] like any other receiver.
+///
+/// If the handle is dropped after being woken up by [`notify_one`] without its message having
+/// been received, the notification is passed on to the next waiter.
+///
+/// [`Notify`]: struct.Notify.html
+/// [`Notify::notified`]: struct.Notify.html#method.notified
+/// [`Receiver`]: ../struct.Receiver.html
+/// [`select!`]: ../macro.select.html
+/// [`notify_one`]: struct.Notify.html#method.notify_one
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::thread;
+/// use crossbeam_channel::sync::Notify;
+/// use crossbeam_channel::unbounded;
+///
+/// let notify = Notify::new();
+/// let (s, r) = unbounded::<i32>();
+///
+/// let n = notify.clone();
+/// thread::spawn(move || n.notify_one());
+///
+/// let notified = notify.notified();
+/// select! {
+///     recv(r) -> _ => panic!(),
+///     recv(notified) -> _ => {}
+/// }
+/// # drop(s);
+/// # }
+/// ```
+pub struct Notified {
+    receiver: Receiver<()>,
+    waiter: Arc<Waiter>,
+    state: Arc<Spinlock<NotifyState>>,
+}
+
+impl Notified {
+    /// Returns `true` if the handle has been notified and the notification hasn't been received.
+    pub fn is_notified(&self) -> bool {
+        !self.receiver.is_empty()
+    }
+
+    /// Blocks the current thread until the handle is notified.
+    pub fn wait(&self) {
+        let _ = self.receiver.recv();
+    }
+
+    /// Waits until the handle is notified, but only for a limited time.
+    ///
+    /// Returns `true` if the handle got notified before the timeout.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.receiver.recv_timeout(timeout).is_ok()
+    }
+}
+
+impl Deref for Notified {
+    type Target = Receiver<()>;
+
+    fn deref(&self) -> &Receiver<()> {
+        &self.receiver
+    }
+}
+
+impl Drop for Notified {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        let waiter = &self.waiter;
+        state.waiters.retain(|w| !Arc::ptr_eq(w, waiter));
+
+        // Pass on an unreceived notification from `notify_one` so that it doesn't get lost.
+        if self.receiver.try_recv().is_ok() && waiter.one.load(Ordering::Relaxed) {
+            state.notify_one();
+        }
+    }
+}
+
+impl fmt::Debug for Notified {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Notified")
+            .field("notified", &self.is_notified())
+            .finish()
+    }
+}
+
+/// A reusable barrier whose completion can be waited on in [`select!`].
+///
+/// A barrier for `n` threads completes a generation once `n` threads have arrived at it, and then
+/// starts over for the next generation. Blocking in [`wait`] works like with
+/// [`std::sync::Barrier`]. Alternatively, [`arrive`] counts the current thread as arrived and
+/// returns a [`BarrierWait`] handle that can be waited on in [`select!`], for example alongside a
+/// shutdown channel.
+///
+/// If the handle is dropped before the generation completes, the arrival is withdrawn.
+///
+/// [`select!`]: ../macro.select.html
+/// [`wait`]: struct.Barrier.html#method.wait
+/// [`std::sync::Barrier`]: https://doc.rust-lang.org/std/sync/struct.Barrier.html
+/// [`arrive`]: struct.Barrier.html#method.arrive
+/// [`BarrierWait`]: struct.BarrierWait.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::sync::Barrier;
+/// use crossbeam_channel::unbounded;
+///
+/// let barrier = Arc::new(Barrier::new(3));
+/// let (shutdown_s, shutdown_r) = unbounded::<()>();
+///
+/// let handles: Vec<_> = (0..3)
+///     .map(|_| {
+///         let barrier = barrier.clone();
+///         let shutdown = shutdown_r.clone();
+///         thread::spawn(move || {
+///             let mut stages = 0;
+///             loop {
+///                 let arrival = barrier.arrive();
+///                 select! {
+///                     recv(arrival) -> _ => stages += 1,
+///                     recv(shutdown) -> _ => return stages,
+///                 }
+///                 if stages == 5 {
+///                     return stages;
+///                 }
+///             }
+///         })
+///     })
+///     .collect();
+///
+/// for h in handles {
+///     assert_eq!(h.join().unwrap(), 5);
+/// }
+/// # drop(shutdown_s);
+/// # }
+/// ```
+pub struct Barrier {
+    /// The number of threads completing a generation.
+    n: usize,
+
+    /// The state of the current generation.
+    state: Spinlock<BarrierState>,
+}
+
+/// The state of a barrier's current generation.
+struct BarrierState {
+    /// The number of threads that have arrived.
+    count: usize,
+
+    /// Incremented whenever a generation completes.
+    generation: usize,
+
+    /// Dropped when the generation completes, which disconnects `ready`.
+    signal: Sender<()>,
+
+    /// Becomes ready once the generation completes.
+    ready: Receiver<()>,
+}
+
+impl Barrier {
+    /// Creates a barrier for `n` threads.
+    ///
+    /// If `n` is zero or one, every arrival completes a generation right away.
+    pub fn new(n: usize) -> Barrier {
+        let (s, r) = unbounded();
+        Barrier {
+            n,
+            state: Spinlock::new(BarrierState {
+                count: 0,
+                generation: 0,
+                signal: s,
+                ready: r,
+            }),
+        }
+    }
+
+    /// Blocks the current thread until `n` threads have arrived at the barrier.
+    ///
+    /// Returns `true` for exactly one thread per generation, the leader, which is the thread that
+    /// arrived last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use crossbeam_channel::sync::Barrier;
+    ///
+    /// let barrier = Arc::new(Barrier::new(4));
+    /// let handles: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let barrier = barrier.clone();
+    ///         thread::spawn(move || barrier.wait())
+    ///     })
+    ///     .collect();
+    ///
+    /// let leaders = handles
+    ///     .into_iter()
+    ///     .map(|h| h.join().unwrap())
+    ///     .filter(|&leader| leader)
+    ///     .count();
+    /// assert_eq!(leaders, 1);
+    /// ```
+    pub fn wait(&self) -> bool {
+        let arrival = self.arrive();
+        arrival.wait();
+        arrival.is_leader()
+    }
+
+    /// Counts the current thread as arrived and returns a handle for waiting.
+    ///
+    /// The handle becomes ready once the current generation completes. If it is dropped before
+    /// that, the arrival is withdrawn.
+    pub fn arrive(&self) -> BarrierWait<'_> {
+        let mut state = self.state.lock();
+        let generation = state.generation;
+        let ready = state.ready.clone();
+
+        state.count += 1;
+        let leader = state.count >= self.n;
+        if leader {
+            // Complete the generation, disconnecting the channel and waking up everyone.
+            let (s, r) = unbounded();
+            state.count = 0;
+            state.generation = state.generation.wrapping_add(1);
+            state.signal = s;
+            state.ready = r;
+        }
+
+        BarrierWait {
+            barrier: self,
+            generation,
+            leader,
+            ready,
+        }
+    }
+
+    /// Returns the number of threads completing a generation.
+    pub fn parties(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the number of threads that have arrived in the current generation.
+    pub fn arrived(&self) -> usize {
+        self.state.lock().count
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("parties", &self.n)
+            .field("arrived", &self.arrived())
+            .finish()
+    }
+}
+
+/// An arrival at a [`Barrier`], waiting for its generation to complete.
+///
+/// Created by [`Barrier::arrive`]. The handle dereferences to a [`Receiver`] that never receives
+/// any messages. Once the generation completes, receive operations on it fail right away, so it
+/// can be used in [`select!`] like any other receiver.
+///
+/// [`Barrier`]: struct.Barrier.html
+/// [`Barrier::arrive`]: struct.Barrier.html#method.arrive
+/// [`Receiver`]: ../struct.Receiver.html
+/// [`select!`]: ../macro.select.html
+pub struct BarrierWait<'a> {
+    barrier: &'a Barrier,
+    generation: usize,
+    leader: bool,
+    ready: Receiver<()>,
+}
+
+impl<'a> BarrierWait<'a> {
+    /// Returns `true` if this arrival completed the generation.
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+
+    /// Returns `true` if the generation has completed.
+    pub fn is_complete(&self) -> bool {
+        self.ready.try_recv() == Err(TryRecvError::Disconnected)
+    }
+
+    /// Blocks the current thread until the generation completes.
+    pub fn wait(&self) {
+        let _ = self.ready.recv();
+    }
+
+    /// Waits until the generation completes, but only for a limited time.
+    ///
+    /// Returns `true` if the generation completed before the timeout.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.ready.recv_timeout(timeout) == Err(RecvTimeoutError::Disconnected)
+    }
+}
+
+impl<'a> Deref for BarrierWait<'a> {
+    type Target = Receiver<()>;
+
+    fn deref(&self) -> &Receiver<()> {
+        &self.ready
+    }
+}
+
+impl<'a> Drop for BarrierWait<'a> {
+    fn drop(&mut self) {
+        let mut state = self.barrier.state.lock();
+        if state.generation == self.generation {
+            // The generation hasn't completed, so withdraw the arrival.
+            state.count -= 1;
+        }
+    }
+}
+
+impl<'a> fmt::Debug for BarrierWait<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BarrierWait")
+            .field("leader", &self.leader)
+            .field("complete", &self.is_complete())
+            .finish()
+    }
+}
+
+/// A closure submitted to a flat combiner.
+type Job<T> = Box<dyn FnMut(&mut T) + Send>;
+
+/// Shared state whose mutations are executed in batches by one thread at a time.
+///
+/// Instead of every thread taking a lock, threads [`submit`] closures into a queue. Whichever
+/// thread finds the state free becomes the combiner and executes all queued closures against the
+/// state in one go, while the others return right away. Under contention, the state and the
+/// closures stay in the cache of a single core, which makes hot shared state cheaper to mutate
+/// than with a mutex.
+///
+/// Every submission returns a [`Promise`] that gets completed with the closure's result. It can be
+/// ignored, waited on, or waited on in [`select!`] through [`Promise::ready`].
+///
+/// A thread may end up executing many batches submitted by other threads while it is the
+/// combiner. If a closure panics, the panic propagates to the combining thread, and the promise of
+/// that closure is never completed.
+///
+/// [`submit`]: struct.FlatCombiner.html#method.submit
+/// [`Promise`]: struct.Promise.html
+/// [`select!`]: ../macro.select.html
+/// [`Promise::ready`]: struct.Promise.html#method.ready
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::sync::FlatCombiner;
+///
+/// let counter = Arc::new(FlatCombiner::new(0));
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let counter = counter.clone();
+///         thread::spawn(move || {
+///             for _ in 0..100 {
+///                 counter.submit(|n| *n += 1);
+///             }
+///         })
+///     })
+///     .collect();
+/// for h in handles {
+///     h.join().unwrap();
+/// }
+///
+/// // Waiting on a submission also waits for everything submitted before it.
+/// assert_eq!(*counter.submit(|n| *n).wait(), 400);
+/// ```
+pub struct FlatCombiner<T> {
+    /// The protected state.
+    state: UnsafeCell<T>,
+
+    /// Set to `true` while a thread is combining.
+    combining: AtomicBool,
+
+    /// The sending side of the queue of submitted closures.
+    jobs: Sender<Job<T>>,
+
+    /// The receiving side of the queue of submitted closures.
+    queue: Receiver<Job<T>>,
+}
+
+unsafe impl<T: Send> Send for FlatCombiner<T> {}
+unsafe impl<T: Send> Sync for FlatCombiner<T> {}
+
+impl<T> FlatCombiner<T> {
+    /// Creates a flat combiner protecting `state`.
+    pub fn new(state: T) -> FlatCombiner<T> {
+        let (s, r) = unbounded();
+        FlatCombiner {
+            state: UnsafeCell::new(state),
+            combining: AtomicBool::new(false),
+            jobs: s,
+            queue: r,
+        }
+    }
+
+    /// Submits a closure to be executed against the state, and returns a promise of its result.
+    ///
+    /// Closures are executed in the order they were submitted. If no other thread is combining,
+    /// the current thread executes the queued closures, including this one, before returning.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::sync::FlatCombiner;
+    ///
+    /// let log = FlatCombiner::new(Vec::new());
+    /// log.submit(|v| v.push("a"));
+    /// let len = log.submit(|v| {
+    ///     v.push("b");
+    ///     v.len()
+    /// });
+    /// assert_eq!(*len.wait(), 2);
+    /// ```
+    pub fn submit<F, R>(&self, f: F) -> Promise<R>
+    where
+        F: FnOnce(&mut T) -> R + Send + 'static,
+        R: Send + Sync + 'static,
+    {
+        let promise = Promise::new();
+        let p = promise.clone();
+        let mut f = Some(f);
+        let job: Job<T> = Box::new(move |state| {
+            if let Some(f) = f.take() {
+                let _ = p.complete(f(state));
+            }
+        });
+
+        // The receiving side lives as long as `self`, so this never fails.
+        let _ = self.jobs.send(job);
+        self.combine();
+        promise
+    }
+
+    /// Executes the queued closures if no other thread is combining, and returns how many were
+    /// executed.
+    ///
+    /// Submitting already combines, so this only needs to be called by a thread dedicated to
+    /// combining, or to flush closures left behind by a panic.
+    pub fn combine(&self) -> usize {
+        let mut count = 0;
+
+        loop {
+            if self
+                .combining
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+            {
+                // The other combiner will check the queue again after it's done.
+                return count;
+            }
+
+            {
+                let _done = Combining(&self.combining);
+                while let Ok(mut job) = self.queue.try_recv() {
+                    // Only the combiner accesses the state.
+                    job(unsafe { &mut *self.state.get() });
+                    count += 1;
+                }
+            }
+
+            // A closure submitted just before the flag was cleared might have missed its turn.
+            if self.queue.is_empty() {
+                return count;
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the state.
+    ///
+    /// Closures that are still queued are not executed.
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.state.get() }
+    }
+
+    /// Executes the queued closures and returns the state.
+    pub fn into_inner(self) -> T {
+        self.combine();
+        self.state.into_inner()
+    }
+}
+
+impl<T: Default> Default for FlatCombiner<T> {
+    fn default() -> FlatCombiner<T> {
+        FlatCombiner::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for FlatCombiner<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FlatCombiner")
+            .field("queued", &self.queue.len())
+            .finish()
+    }
+}
+
+/// Clears the combining flag when dropped, even if a closure panics.
+struct Combining<'a>(&'a AtomicBool);
+
+impl<'a> Drop for Combining<'a> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// A cell for small `Copy` values that are read often and written rarely.
+///
+/// The cell is a sequence lock: a writer bumps a sequence number before and after writing, and a
+/// reader copies the value out and retries if the sequence number changed in the meantime.
+/// Readers never block writers or each other and don't write to shared memory, so [`load`] stays
+/// cheap even with many readers on different cores. Writers are serialized among themselves.
+///
+/// Changes can be waited on with [`watch`], which returns a [`Notified`] handle that fires on the
+/// next [`store`] and can be used in [`select!`]. Stores don't send any messages unless someone is
+/// watching.
+///
+/// This is meant for telemetry and similar state shared between a producer and many readers,
+/// where sending every update through a channel would be wasteful.
+///
+/// [`load`]: struct.SnapshotCell.html#method.load
+/// [`store`]: struct.SnapshotCell.html#method.store
+/// [`watch`]: struct.SnapshotCell.html#method.watch
+/// [`Notified`]: struct.Notified.html
+/// [`select!`]: ../macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use crossbeam_channel::sync::SnapshotCell;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Stats {
+///     sent: u64,
+///     dropped: u64,
+/// }
+///
+/// let stats = Arc::new(SnapshotCell::new(Stats { sent: 0, dropped: 0 }));
+///
+/// let s = stats.clone();
+/// thread::spawn(move || {
+///     for i in 1..=100 {
+///         s.store(Stats { sent: i, dropped: i / 10 });
+///     }
+/// });
+///
+/// loop {
+///     let watch = stats.watch();
+///     let snapshot = stats.load();
+///     assert_eq!(snapshot.dropped, snapshot.sent / 10);
+///     if snapshot.sent == 100 {
+///         break;
+///     }
+///     watch.wait();
+/// }
+/// ```
+pub struct SnapshotCell<T: Copy> {
+    /// The sequence number, which is odd while a write is in progress.
+    seq: AtomicUsize,
+
+    /// The value.
+    value: UnsafeCell<T>,
+
+    /// Wakes up watchers after each store.
+    notify: Notify,
+}
+
+unsafe impl<T: Copy + Send> Send for SnapshotCell<T> {}
+unsafe impl<T: Copy + Send> Sync for SnapshotCell<T> {}
+
+impl<T: Copy> SnapshotCell<T> {
+    /// Creates a new cell holding `value`.
+    pub fn new(value: T) -> SnapshotCell<T> {
+        SnapshotCell {
+            seq: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Returns a copy of the current value.
+    ///
+    /// The copy is always a value that was stored as a whole, never a mix of two stores. If a
+    /// store is in progress, this spins until it completes.
+    pub fn load(&self) -> T {
+        let backoff = Backoff::new();
+        loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq & 1 == 0 {
+                // The read may race with a writer, in which case the copy is discarded below.
+                let value = unsafe { ptr::read_volatile(self.value.get()) };
+                atomic::fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) == seq {
+                    return value;
+                }
+            }
+            backoff.snooze();
+        }
+    }
+
+    /// Replaces the value and wakes up everyone waiting in [`watch`].
+    ///
+    /// [`watch`]: struct.SnapshotCell.html#method.watch
+    pub fn store(&self, value: T) {
+        self.update(|_| value);
+    }
+
+    /// Replaces the value with the result of `f` applied to the current value, and wakes up
+    /// everyone waiting in [`watch`].
+    ///
+    /// Other writers are blocked while `f` runs, so it should be quick. Readers are not blocked,
+    /// but spin until the write completes.
+    ///
+    /// [`watch`]: struct.SnapshotCell.html#method.watch
+    pub fn update<F: FnOnce(T) -> T>(&self, f: F) {
+        let backoff = Backoff::new();
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq & 1 == 0 {
+                match self.seq.compare_exchange_weak(
+                    seq,
+                    seq.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(s) => seq = s,
+                }
+            } else {
+                backoff.snooze();
+                seq = self.seq.load(Ordering::Relaxed);
+            }
+        }
+        atomic::fence(Ordering::Release);
+
+        // Release the write lock even if `f` panics, leaving the old value in place.
+        let guard = SeqWrite {
+            seq: &self.seq,
+            next: seq.wrapping_add(2),
+        };
+        unsafe {
+            let value = f(*self.value.get());
+            ptr::write_volatile(self.value.get(), value);
+        }
+        drop(guard);
+
+        self.notify.notify_all();
+    }
+
+    /// Returns the number of stores so far.
+    ///
+    /// Comparing versions is a cheap way for readers to tell whether the value has changed since
+    /// they last looked at it. The count wraps around on overflow.
+    pub fn version(&self) -> usize {
+        self.seq.load(Ordering::Acquire) / 2
+    }
+
+    /// Returns a handle that gets notified by the next store.
+    ///
+    /// A store that happens after the handle is created is never missed, so the usual pattern is
+    /// to create the handle, [`load`] the value, and then wait on the handle if the value isn't
+    /// interesting yet.
+    ///
+    /// [`load`]: struct.SnapshotCell.html#method.load
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use std::time::Duration;
+    /// use crossbeam_channel::sync::SnapshotCell;
+    /// use crossbeam_channel::after;
+    ///
+    /// let level = SnapshotCell::new(0.5f64);
+    ///
+    /// let watch = level.watch();
+    /// level.store(0.9);
+    ///
+    /// select! {
+    ///     recv(watch) -> _ => assert_eq!(level.load(), 0.9),
+    ///     recv(after(Duration::from_secs(1))) -> _ => panic!(),
+    /// }
+    /// # }
+    /// ```
+    pub fn watch(&self) -> Notified {
+        self.notify.notified()
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Consumes the cell and returns the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Copy + Default> Default for SnapshotCell<T> {
+    fn default() -> SnapshotCell<T> {
+        SnapshotCell::new(T::default())
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SnapshotCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SnapshotCell")
+            .field("value", &self.load())
+            .finish()
+    }
+}
+
+/// Ends a write to a `SnapshotCell` when dropped, even if the new value couldn't be computed.
+struct SeqWrite<'a> {
+    seq: &'a AtomicUsize,
+    next: usize,
+}
+
+impl<'a> Drop for SeqWrite<'a> {
+    fn drop(&mut self) {
+        self.seq.store(self.next, Ordering::Release);
+    }
+}
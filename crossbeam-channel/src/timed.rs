@@ -0,0 +1,300 @@
+//! Channels that record how long each message spent in the channel.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+use channel::bounded;
+use channel::{unbounded, Receiver, Sender};
+use err::{RecvError, RecvTimeoutError, TryRecvError};
+use err::{SendError, SendTimeoutError, TrySendError};
+
+/// Creates a timestamping channel of bounded capacity.
+///
+/// This is like [`bounded`], except that every message is stamped with the instant at which it
+/// was sent. The receiving side can then find out how long each message waited in the channel,
+/// which is useful for monitoring queueing latency.
+///
+/// [`bounded`]: fn.bounded.html
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::timed_bounded;
+///
+/// let (s, r) = timed_bounded(1);
+/// s.send("hello").unwrap();
+///
+/// thread::sleep(Duration::from_millis(100));
+///
+/// let (msg, waited) = r.recv_timed().unwrap();
+/// assert_eq!(msg, "hello");
+/// assert!(waited >= Duration::from_millis(100));
+/// ```
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub fn timed_bounded<T>(cap: usize) -> (TimedSender<T>, TimedReceiver<T>) {
+    let (s, r) = bounded(cap);
+    (TimedSender { sender: s }, TimedReceiver { receiver: r })
+}
+
+/// Creates a timestamping channel of unbounded capacity.
+///
+/// This is like [`unbounded`], except that every message is stamped with the instant at which it
+/// was sent. See [`timed_bounded`] for more details.
+///
+/// [`unbounded`]: fn.unbounded.html
+/// [`timed_bounded`]: fn.timed_bounded.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::timed_unbounded;
+///
+/// let (s, r) = timed_unbounded();
+/// s.send(1).unwrap();
+/// s.send(2).unwrap();
+///
+/// assert_eq!(r.recv(), Ok(1));
+/// let (msg, _waited) = r.recv_timed().unwrap();
+/// assert_eq!(msg, 2);
+/// ```
+pub fn timed_unbounded<T>() -> (TimedSender<T>, TimedReceiver<T>) {
+    let (s, r) = unbounded();
+    (TimedSender { sender: s }, TimedReceiver { receiver: r })
+}
+
+/// Returns the message along with the time elapsed since it was sent.
+fn elapsed<T>((msg, sent): (T, Instant)) -> (T, Duration) {
+    let now = Instant::now();
+    let waited = if now > sent {
+        now - sent
+    } else {
+        Duration::from_secs(0)
+    };
+    (msg, waited)
+}
+
+/// The sending side of a timestamping channel.
+///
+/// Created by the [`timed_bounded`] and [`timed_unbounded`] functions.
+///
+/// [`timed_bounded`]: fn.timed_bounded.html
+/// [`timed_unbounded`]: fn.timed_unbounded.html
+pub struct TimedSender<T> {
+    sender: Sender<(T, Instant)>,
+}
+
+impl<T> TimedSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// See [`Sender::try_send`] for details.
+    ///
+    /// [`Sender::try_send`]: struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.sender
+            .try_send((msg, Instant::now()))
+            .map_err(|err| match err {
+                TrySendError::Full((msg, _)) => TrySendError::Full(msg),
+                TrySendError::Disconnected((msg, _)) => TrySendError::Disconnected(msg),
+            })
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// The message is stamped with the instant at which this method was called, so time spent
+    /// waiting for room in a full channel counts towards the time the message waited.
+    ///
+    /// See [`Sender::send`] for details.
+    ///
+    /// [`Sender::send`]: struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender
+            .send((msg, Instant::now()))
+            .map_err(|SendError((msg, _))| SendError(msg))
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// See [`Sender::send_timeout`] for details.
+    ///
+    /// [`Sender::send_timeout`]: struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.sender
+            .send_timeout((msg, Instant::now()), timeout)
+            .map_err(|err| match err {
+                SendTimeoutError::Timeout((msg, _)) => SendTimeoutError::Timeout(msg),
+                SendTimeoutError::Disconnected((msg, _)) => SendTimeoutError::Disconnected(msg),
+            })
+    }
+
+    /// Returns the underlying sender, which sends messages along with their timestamps.
+    pub fn as_sender(&self) -> &Sender<(T, Instant)> {
+        &self.sender
+    }
+}
+
+impl<T> Clone for TimedSender<T> {
+    fn clone(&self) -> Self {
+        TimedSender {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TimedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("TimedSender { .. }")
+    }
+}
+
+/// The receiving side of a timestamping channel.
+///
+/// Created by the [`timed_bounded`] and [`timed_unbounded`] functions.
+///
+/// Messages can be received either on their own, or along with the time they spent in the
+/// channel. To use the channel in [`select!`], select over [`as_receiver`], which yields messages
+/// along with their send instants.
+///
+/// [`timed_bounded`]: fn.timed_bounded.html
+/// [`timed_unbounded`]: fn.timed_unbounded.html
+/// [`select!`]: macro.select.html
+/// [`as_receiver`]: struct.TimedReceiver.html#method.as_receiver
+pub struct TimedReceiver<T> {
+    receiver: Receiver<(T, Instant)>,
+}
+
+impl<T> TimedReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// See [`Receiver::try_recv`] for details.
+    ///
+    /// [`Receiver::try_recv`]: struct.Receiver.html#method.try_recv
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv().map(|(msg, _)| msg)
+    }
+
+    /// Blocks the current thread until a message is received or the channel is disconnected.
+    ///
+    /// See [`Receiver::recv`] for details.
+    ///
+    /// [`Receiver::recv`]: struct.Receiver.html#method.recv
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv().map(|(msg, _)| msg)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    ///
+    /// See [`Receiver::recv_timeout`] for details.
+    ///
+    /// [`Receiver::recv_timeout`]: struct.Receiver.html#method.recv_timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout).map(|(msg, _)| msg)
+    }
+
+    /// Attempts to receive a message without blocking, along with the time it spent in the
+    /// channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{timed_unbounded, TryRecvError};
+    ///
+    /// let (s, r) = timed_unbounded();
+    /// assert_eq!(r.try_recv_timed(), Err(TryRecvError::Empty));
+    ///
+    /// s.send(5).unwrap();
+    /// assert_eq!(r.try_recv_timed().unwrap().0, 5);
+    /// ```
+    pub fn try_recv_timed(&self) -> Result<(T, Duration), TryRecvError> {
+        self.receiver.try_recv().map(elapsed)
+    }
+
+    /// Blocks until a message is received, and returns it along with the time it spent in the
+    /// channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{timed_unbounded, RecvError};
+    ///
+    /// let (s, r) = timed_unbounded();
+    ///
+    /// thread::spawn(move || {
+    ///     s.send(5).unwrap();
+    ///     drop(s);
+    /// });
+    ///
+    /// let (msg, waited) = r.recv_timed().unwrap();
+    /// assert_eq!(msg, 5);
+    /// assert!(waited < Duration::from_secs(10));
+    /// assert_eq!(r.recv_timed(), Err(RecvError));
+    /// ```
+    pub fn recv_timed(&self) -> Result<(T, Duration), RecvError> {
+        self.receiver.recv().map(elapsed)
+    }
+
+    /// Waits for a message for a limited time, and returns it along with the time it spent in
+    /// the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{timed_unbounded, RecvTimeoutError};
+    ///
+    /// let (s, r) = timed_unbounded::<i32>();
+    /// assert_eq!(
+    ///     r.recv_timeout_timed(Duration::from_millis(100)),
+    ///     Err(RecvTimeoutError::Timeout),
+    /// );
+    /// ```
+    pub fn recv_timeout_timed(&self, timeout: Duration) -> Result<(T, Duration), RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout).map(elapsed)
+    }
+
+    /// Returns the underlying receiver, which yields messages along with their send instants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate crossbeam_channel;
+    /// # fn main() {
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timed_unbounded;
+    ///
+    /// let (s, r) = timed_unbounded();
+    /// s.send(1).unwrap();
+    ///
+    /// select! {
+    ///     recv(r.as_receiver()) -> msg => {
+    ///         let (msg, sent) = msg.unwrap();
+    ///         assert_eq!(msg, 1);
+    ///         println!("waited for {:?}", sent.elapsed());
+    ///     }
+    ///     default(Duration::from_secs(1)) => panic!(),
+    /// }
+    /// # }
+    /// ```
+    pub fn as_receiver(&self) -> &Receiver<(T, Instant)> {
+        &self.receiver
+    }
+}
+
+impl<T> Clone for TimedReceiver<T> {
+    fn clone(&self) -> Self {
+        TimedReceiver {
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TimedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("TimedReceiver { .. }")
+    }
+}
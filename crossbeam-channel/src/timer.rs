@@ -0,0 +1,142 @@
+//! A service issuing many lightweight timers that share their resources.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use channel::{self, Receiver};
+use err::TimerFullError;
+use flavors::timer::Slab;
+
+/// A service issuing timers from a fixed number of preallocated slots.
+///
+/// Every call to [`after`] allocates a new channel. A timer service instead keeps the state of
+/// up to `capacity` timers in a single slab, and its [`after`][`TimerService::after`] and [`at`]
+/// methods only claim a free slot. A slot is released once all receivers of its timer are dropped.
+///
+/// Timer receivers behave just like those returned by [`after`]: each delivers a single message,
+/// which is the instant it was due, and can be used in [`select!`]. They never register wakers, so
+/// a thread blocked on any number of them simply parks until the earliest deadline.
+///
+/// The service is cheap to clone, and clones share the same slots.
+///
+/// [`after`]: fn.after.html
+/// [`TimerService::after`]: struct.TimerService.html#method.after
+/// [`at`]: struct.TimerService.html#method.at
+/// [`select!`]: macro.select.html
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate crossbeam_channel;
+/// # fn main() {
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, TimerService};
+///
+/// let timers = TimerService::new(64);
+/// let (s, r) = unbounded::<i32>();
+///
+/// let timeout = timers.after(Duration::from_millis(50)).unwrap();
+/// assert_eq!(timers.len(), 1);
+///
+/// select! {
+///     recv(r) -> msg => panic!("unexpected message: {:?}", msg),
+///     recv(timeout) -> _ => println!("timed out"),
+/// }
+///
+/// // Dropping the receiver frees its slot.
+/// drop(timeout);
+/// assert_eq!(timers.len(), 0);
+/// # drop(s);
+/// # }
+/// ```
+pub struct TimerService {
+    slab: Arc<Slab>,
+}
+
+impl TimerService {
+    /// Creates a service with room for `capacity` timers at a time.
+    pub fn new(capacity: usize) -> TimerService {
+        TimerService {
+            slab: Arc::new(Slab::new(capacity)),
+        }
+    }
+
+    /// Creates a timer that delivers a message after `duration`.
+    ///
+    /// If all timers are in use, an error is returned.
+    pub fn after(&self, duration: Duration) -> Result<Receiver<Instant>, TimerFullError> {
+        self.at(Instant::now() + duration)
+    }
+
+    /// Creates a timer that delivers a message at `instant`.
+    ///
+    /// If all timers are in use, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{TimerFullError, TimerService};
+    ///
+    /// let timers = TimerService::new(1);
+    /// let at = Instant::now() + Duration::from_millis(10);
+    ///
+    /// let r = timers.at(at).unwrap();
+    /// assert_eq!(timers.at(at).unwrap_err(), TimerFullError);
+    ///
+    /// assert_eq!(r.recv(), Ok(at));
+    /// ```
+    pub fn at(&self, instant: Instant) -> Result<Receiver<Instant>, TimerFullError> {
+        match Slab::insert(&self.slab, instant) {
+            Some(timer) => Ok(channel::timer(timer)),
+            None => Err(TimerFullError),
+        }
+    }
+
+    /// Returns the maximum number of timers in use at a time.
+    pub fn capacity(&self) -> usize {
+        self.slab.capacity()
+    }
+
+    /// Returns the number of timers in use.
+    ///
+    /// A timer stays in use until all of its receivers are dropped, even after its message has
+    /// been received.
+    pub fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    /// Returns `true` if no timers are in use.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if all timers are in use.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the earliest instant at which one of the timers delivers its message.
+    ///
+    /// Timers whose messages have already been received are not taken into account. If there are
+    /// no such timers, `None` is returned.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.slab.next_deadline()
+    }
+}
+
+impl Clone for TimerService {
+    fn clone(&self) -> TimerService {
+        TimerService {
+            slab: self.slab.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for TimerService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("TimerService { .. }")
+    }
+}
@@ -0,0 +1,199 @@
+//! Channels that carry the tracing span of the sender along with each message.
+//!
+//! A message sent through a [`TracedSender`] is wrapped into a [`Traced`] together with the span
+//! that was current at the time of sending. The receiving thread enters that span while handling
+//! the message, so the causality between the two threads shows up in traces, without every
+//! message type having to carry a span by hand.
+//!
+//! The receiving side is an ordinary [`Receiver`] of [`Traced`] messages, which works with
+//! [`select!`] and everything else receivers support.
+//!
+//! This module is only available with the `tracing` feature.
+//!
+//! [`TracedSender`]: struct.TracedSender.html
+//! [`Traced`]: struct.Traced.html
+//! [`Receiver`]: ../struct.Receiver.html
+//! [`select!`]: ../macro.select.html
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate crossbeam_channel;
+//! # extern crate tracing;
+//! # fn main() {
+//! use std::thread;
+//! use crossbeam_channel::traced;
+//! use tracing::info_span;
+//!
+//! let (s, r) = traced::unbounded();
+//!
+//! thread::spawn(move || {
+//!     let span = info_span!("request", id = 7);
+//!     let _enter = span.enter();
+//!     s.send("payload").unwrap();
+//! });
+//!
+//! let msg = r.recv().unwrap();
+//! msg.in_span(|payload| {
+//!     // Events recorded here belong to the `request` span of the sender.
+//!     assert_eq!(payload, "payload");
+//! });
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use tracing::span::EnteredSpan;
+use tracing::Span;
+
+use channel::{self, Receiver, Sender};
+use err::{SendError, SendTimeoutError, TrySendError};
+
+/// Creates a channel of unbounded capacity that carries tracing spans with its messages.
+///
+/// See [`unbounded`] for the behavior of the channel.
+///
+/// [`unbounded`]: ../fn.unbounded.html
+pub fn unbounded<T>() -> (TracedSender<T>, Receiver<Traced<T>>) {
+    let (s, r) = channel::unbounded();
+    (TracedSender { sender: s }, r)
+}
+
+/// Creates a channel of bounded capacity that carries tracing spans with its messages.
+///
+/// See [`bounded`] for the behavior of the channel.
+///
+/// [`bounded`]: ../fn.bounded.html
+#[cfg(any(feature = "flavor-array", feature = "flavor-zero"))]
+pub fn bounded<T>(cap: usize) -> (TracedSender<T>, Receiver<Traced<T>>) {
+    let (s, r) = channel::bounded(cap);
+    (TracedSender { sender: s }, r)
+}
+
+/// A message together with the span that was current when it was sent.
+pub struct Traced<T> {
+    span: Span,
+    msg: T,
+}
+
+impl<T> Traced<T> {
+    /// Wraps a message together with the current span.
+    pub fn new(msg: T) -> Traced<T> {
+        Traced::with_span(msg, Span::current())
+    }
+
+    /// Wraps a message together with the given span.
+    pub fn with_span(msg: T, span: Span) -> Traced<T> {
+        Traced { span, msg }
+    }
+
+    /// Returns the span the message was sent in.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Returns a reference to the message.
+    pub fn get(&self) -> &T {
+        &self.msg
+    }
+
+    /// Unwraps the message, discarding the span.
+    pub fn into_inner(self) -> T {
+        self.msg
+    }
+
+    /// Unwraps the message and the span.
+    pub fn into_parts(self) -> (T, Span) {
+        (self.msg, self.span)
+    }
+
+    /// Handles the message with the span it was sent in entered.
+    pub fn in_span<R, F: FnOnce(T) -> R>(self, f: F) -> R {
+        let Traced { span, msg } = self;
+        span.in_scope(|| f(msg))
+    }
+
+    /// Enters the span the message was sent in and unwraps the message.
+    ///
+    /// The span stays entered until the returned guard is dropped.
+    pub fn enter(self) -> (T, EnteredSpan) {
+        (self.msg, self.span.entered())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Traced<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Traced")
+            .field("span", &self.span)
+            .field("msg", &self.msg)
+            .finish()
+    }
+}
+
+/// The sending side of a channel that carries tracing spans with its messages.
+///
+/// Every message is sent together with the span that is current at the time of sending.
+pub struct TracedSender<T> {
+    sender: Sender<Traced<T>>,
+}
+
+impl<T> TracedSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// See [`Sender::try_send`].
+    ///
+    /// [`Sender::try_send`]: ../struct.Sender.html#method.try_send
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.sender
+            .try_send(Traced::new(msg))
+            .map_err(|err| match err {
+                TrySendError::Full(t) => TrySendError::Full(t.msg),
+                TrySendError::Disconnected(t) => TrySendError::Disconnected(t.msg),
+            })
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// See [`Sender::send`].
+    ///
+    /// [`Sender::send`]: ../struct.Sender.html#method.send
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender
+            .send(Traced::new(msg))
+            .map_err(|SendError(t)| SendError(t.msg))
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// See [`Sender::send_timeout`].
+    ///
+    /// [`Sender::send_timeout`]: ../struct.Sender.html#method.send_timeout
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.sender
+            .send_timeout(Traced::new(msg), timeout)
+            .map_err(|err| match err {
+                SendTimeoutError::Timeout(t) => SendTimeoutError::Timeout(t.msg),
+                SendTimeoutError::Disconnected(t) => SendTimeoutError::Disconnected(t.msg),
+            })
+    }
+
+    /// Returns the underlying sender, for sending messages with explicitly chosen spans.
+    pub fn as_sender(&self) -> &Sender<Traced<T>> {
+        &self.sender
+    }
+}
+
+impl<T> Clone for TracedSender<T> {
+    fn clone(&self) -> TracedSender<T> {
+        TracedSender {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TracedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("TracedSender { .. }")
+    }
+}
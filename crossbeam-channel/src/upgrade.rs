@@ -0,0 +1,414 @@
+//! Bounded channels that can be turned into unbounded channels while in use.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use channel::{bounded, unbounded, Receiver, Sender};
+use err::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use select::Select;
+
+/// Creates a bounded channel that can later be upgraded to an unbounded one.
+///
+/// Until [`upgrade`] is called, the channel behaves like one created by [`bounded`]. After that,
+/// sends never block, like with [`unbounded`]. Senders blocked on a full channel at the time of
+/// the upgrade complete their sends right away. All endpoints stay valid, so a channel whose
+/// buffer keeps filling up can be switched over without handing new endpoints to everyone holding
+/// the old ones.
+///
+/// Messages sent before the upgrade are received before messages sent after it. Messages sent by
+/// the same thread are received in the order they were sent.
+///
+/// [`upgrade`]: struct.UpgradableSender.html#method.upgrade
+/// [`bounded`]: fn.bounded.html
+/// [`unbounded`]: fn.unbounded.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{upgradable_bounded, TrySendError};
+///
+/// let (s, r) = upgradable_bounded(1);
+/// s.send(1).unwrap();
+/// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+///
+/// // The channel keeps saturating, so make it unbounded.
+/// r.upgrade();
+/// s.try_send(2).unwrap();
+/// s.try_send(3).unwrap();
+/// assert_eq!(s.capacity(), None);
+///
+/// assert_eq!(r.iter().take(3).collect::<Vec<_>>(), [1, 2, 3]);
+/// ```
+pub fn upgradable_bounded<T>(cap: usize) -> (UpgradableSender<T>, UpgradableReceiver<T>) {
+    let (bs, br) = bounded(cap);
+    let (us, ur) = unbounded();
+    let (signal_s, signal_r) = bounded(0);
+
+    let shared = Arc::new(Shared {
+        cap,
+        upgraded: AtomicBool::new(false),
+        in_flight: AtomicUsize::new(0),
+        retired: AtomicBool::new(false),
+        signal: Mutex::new(Some(signal_s)),
+    });
+
+    let s = UpgradableSender {
+        bounded: bs,
+        unbounded: us,
+        signal: signal_r.clone(),
+        shared: shared.clone(),
+    };
+    let r = UpgradableReceiver {
+        bounded: br,
+        unbounded: ur,
+        signal: signal_r,
+        shared,
+    };
+    (s, r)
+}
+
+/// State shared by all endpoints of an upgradable channel.
+struct Shared {
+    /// The capacity of the bounded channel.
+    cap: usize,
+
+    /// Set to `true` once the channel is upgraded.
+    ///
+    /// From then on, senders send into the unbounded channel.
+    upgraded: AtomicBool,
+
+    /// The number of sends into the bounded channel that are in progress.
+    in_flight: AtomicUsize,
+
+    /// Set to `true` once no more messages can arrive through the bounded channel.
+    ///
+    /// From then on, receivers receive from the unbounded channel.
+    retired: AtomicBool,
+
+    /// A channel that gets disconnected on upgrade, waking up blocked senders.
+    signal: Mutex<Option<Sender<()>>>,
+}
+
+impl Shared {
+    /// Upgrades the channel.
+    fn upgrade(&self) {
+        self.upgraded.store(true, Ordering::SeqCst);
+        self.signal.lock().unwrap().take();
+    }
+
+    /// Returns `true` if the channel has been upgraded.
+    fn is_upgraded(&self) -> bool {
+        self.upgraded.load(Ordering::SeqCst)
+    }
+
+    /// Returns the capacity of the channel, or `None` if it has been upgraded.
+    fn capacity(&self) -> Option<usize> {
+        if self.is_upgraded() {
+            None
+        } else {
+            Some(self.cap)
+        }
+    }
+
+    /// Runs a send into the bounded channel, unless the channel has been upgraded.
+    ///
+    /// Returns `None` if the message should go to the unbounded channel instead.
+    fn send_bounded<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce() -> Option<R>,
+    {
+        if self.is_upgraded() {
+            return None;
+        }
+
+        // Receivers switch to the unbounded channel only after seeing no sends in progress, and
+        // every send that starts after that sees the upgrade.
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let res = if self.is_upgraded() { None } else { f() };
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        res
+    }
+}
+
+/// The sending side of an upgradable channel.
+///
+/// Created by the [`upgradable_bounded`] function.
+///
+/// [`upgradable_bounded`]: fn.upgradable_bounded.html
+pub struct UpgradableSender<T> {
+    bounded: Sender<T>,
+    unbounded: Sender<T>,
+    signal: Receiver<()>,
+    shared: Arc<Shared>,
+}
+
+impl<T> UpgradableSender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// Sends fail with [`TrySendError::Full`] only before the channel is upgraded.
+    ///
+    /// [`TrySendError::Full`]: enum.TrySendError.html#variant.Full
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let mut msg = Some(msg);
+        let res = self
+            .shared
+            .send_bounded(|| Some(self.bounded.try_send(msg.take().unwrap())));
+
+        match res {
+            Some(res) => res,
+            None => self.unbounded.try_send(msg.unwrap()),
+        }
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// If the channel gets upgraded while the current thread is blocked, the message is sent
+    /// right away.
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let mut msg = Some(msg);
+        let res = self.shared.send_bounded(|| {
+            let mut sel = Select::new();
+            let send = sel.send(&self.bounded);
+            sel.recv(&self.signal);
+
+            let oper = sel.select();
+            if oper.index() == send {
+                Some(oper.send(&self.bounded, msg.take().unwrap()))
+            } else {
+                // The channel has been upgraded.
+                let _ = oper.recv(&self.signal);
+                None
+            }
+        });
+
+        match res {
+            Some(res) => res,
+            None => self.unbounded.send(msg.unwrap()),
+        }
+    }
+
+    /// Upgrades the channel to an unbounded one.
+    ///
+    /// Does nothing if the channel has already been upgraded.
+    pub fn upgrade(&self) {
+        self.shared.upgrade();
+    }
+
+    /// Returns `true` if the channel has been upgraded.
+    pub fn is_upgraded(&self) -> bool {
+        self.shared.is_upgraded()
+    }
+
+    /// Returns the capacity of the channel, or `None` if it has been upgraded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.bounded.len() + self.unbounded.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for UpgradableSender<T> {
+    fn clone(&self) -> Self {
+        UpgradableSender {
+            bounded: self.bounded.clone(),
+            unbounded: self.unbounded.clone(),
+            signal: self.signal.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for UpgradableSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("UpgradableSender { .. }")
+    }
+}
+
+/// The receiving side of an upgradable channel.
+///
+/// Created by the [`upgradable_bounded`] function.
+///
+/// [`upgradable_bounded`]: fn.upgradable_bounded.html
+pub struct UpgradableReceiver<T> {
+    bounded: Receiver<T>,
+    unbounded: Receiver<T>,
+    signal: Receiver<()>,
+    shared: Arc<Shared>,
+}
+
+impl<T> UpgradableReceiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match self.recv_until(Some(Instant::now())) {
+            Ok(msg) => Ok(msg),
+            Err(RecvTimeoutError::Timeout) => Err(TryRecvError::Empty),
+            Err(RecvTimeoutError::Disconnected) => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel is empty and
+    /// disconnected.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a limited time.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_until(Some(Instant::now() + timeout))
+    }
+
+    /// A blocking iterator over messages in the channel.
+    pub fn iter(&self) -> UpgradableIter<T> {
+        UpgradableIter { receiver: self }
+    }
+
+    /// Upgrades the channel to an unbounded one.
+    ///
+    /// Does nothing if the channel has already been upgraded.
+    pub fn upgrade(&self) {
+        self.shared.upgrade();
+    }
+
+    /// Returns `true` if the channel has been upgraded.
+    pub fn is_upgraded(&self) -> bool {
+        self.shared.is_upgraded()
+    }
+
+    /// Returns the capacity of the channel, or `None` if it has been upgraded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.shared.capacity()
+    }
+
+    /// Returns the number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.bounded.len() + self.unbounded.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Receives a message, waiting until the optional deadline.
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        while !self.shared.retired.load(Ordering::SeqCst) {
+            match self.bounded.try_recv() {
+                Ok(msg) => return Ok(msg),
+                Err(TryRecvError::Disconnected) => {
+                    // All senders are gone, so nothing else can arrive through either channel.
+                    self.shared.retired.store(true, Ordering::SeqCst);
+                    break;
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+
+            if self.shared.is_upgraded() {
+                if self.shared.in_flight.load(Ordering::SeqCst) == 0 {
+                    // A message might have arrived right before the last send finished.
+                    if let Ok(msg) = self.bounded.try_recv() {
+                        return Ok(msg);
+                    }
+                    self.shared.retired.store(true, Ordering::SeqCst);
+                    break;
+                }
+
+                if let Some(d) = deadline {
+                    if Instant::now() >= d {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                }
+
+                // Senders that were blocked when the channel got upgraded are being woken up, so
+                // this won't take long.
+                thread::yield_now();
+            } else {
+                let mut sel = Select::new();
+                sel.recv(&self.bounded);
+                sel.recv(&self.signal);
+
+                let res = match deadline {
+                    None => Ok(sel.ready()),
+                    Some(d) => {
+                        let now = Instant::now();
+                        if now >= d {
+                            return Err(RecvTimeoutError::Timeout);
+                        }
+                        sel.ready_timeout(d - now)
+                    }
+                };
+                if res.is_err() {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+        }
+
+        match deadline {
+            None => self
+                .unbounded
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+            Some(d) => {
+                let now = Instant::now();
+                if now >= d {
+                    self.unbounded.try_recv().map_err(|err| match err {
+                        TryRecvError::Empty => RecvTimeoutError::Timeout,
+                        TryRecvError::Disconnected => RecvTimeoutError::Disconnected,
+                    })
+                } else {
+                    self.unbounded.recv_timeout(d - now)
+                }
+            }
+        }
+    }
+}
+
+impl<T> Clone for UpgradableReceiver<T> {
+    fn clone(&self) -> Self {
+        UpgradableReceiver {
+            bounded: self.bounded.clone(),
+            unbounded: self.unbounded.clone(),
+            signal: self.signal.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for UpgradableReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("UpgradableReceiver { .. }")
+    }
+}
+
+/// A blocking iterator over messages in an upgradable channel.
+///
+/// Created by [`UpgradableReceiver::iter`].
+///
+/// [`UpgradableReceiver::iter`]: struct.UpgradableReceiver.html#method.iter
+pub struct UpgradableIter<'a, T: 'a> {
+    receiver: &'a UpgradableReceiver<T>,
+}
+
+impl<'a, T> Iterator for UpgradableIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<'a, T> fmt::Debug for UpgradableIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("UpgradableIter { .. }")
+    }
+}
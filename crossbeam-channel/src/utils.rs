@@ -1,7 +1,6 @@
 //! Miscellaneous utilities.
 
-use std::cell::{Cell, UnsafeCell};
-use std::num::Wrapping;
+use std::cell::UnsafeCell;
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
@@ -9,44 +8,8 @@ use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
 
-/// Randomly shuffles a slice.
-pub fn shuffle<T>(v: &mut [T]) {
-    let len = v.len();
-    if len <= 1 {
-        return;
-    }
-
-    thread_local! {
-        static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
-    }
-
-    let _ = RNG.try_with(|rng| {
-        for i in 1..len {
-            // This is the 32-bit variant of Xorshift.
-            //
-            // Source: https://en.wikipedia.org/wiki/Xorshift
-            let mut x = rng.get();
-            x ^= x << 13;
-            x ^= x >> 17;
-            x ^= x << 5;
-            rng.set(x);
-
-            let x = x.0;
-            let n = i + 1;
-
-            // This is a fast alternative to `let j = x % n`.
-            //
-            // Author: Daniel Lemire
-            // Source: https://lemire.me/blog/2016/06/27/a-fast-alternative-to-the-modulo-reduction/
-            let j = ((x as u64).wrapping_mul(n as u64) >> 32) as u32 as usize;
-
-            v.swap(i, j);
-        }
-    });
-}
-
 /// Sleeps until the deadline, or forever if the deadline isn't specified.
-pub fn sleep_until(deadline: Option<Instant>) {
+pub(crate) fn sleep_until(deadline: Option<Instant>) {
     loop {
         match deadline {
             None => thread::sleep(Duration::from_secs(1000)),
@@ -62,14 +25,17 @@ pub fn sleep_until(deadline: Option<Instant>) {
 }
 
 /// A simple spinlock.
-pub struct Spinlock<T> {
+pub(crate) struct Spinlock<T> {
     flag: AtomicBool,
     value: UnsafeCell<T>,
 }
 
+unsafe impl<T: Send> Send for Spinlock<T> {}
+unsafe impl<T: Send> Sync for Spinlock<T> {}
+
 impl<T> Spinlock<T> {
     /// Returns a new spinlock initialized with `value`.
-    pub fn new(value: T) -> Spinlock<T> {
+    pub(crate) fn new(value: T) -> Spinlock<T> {
         Spinlock {
             flag: AtomicBool::new(false),
             value: UnsafeCell::new(value),
@@ -77,17 +43,27 @@ impl<T> Spinlock<T> {
     }
 
     /// Locks the spinlock.
-    pub fn lock(&self) -> SpinlockGuard<'_, T> {
+    pub(crate) fn lock(&self) -> SpinlockGuard<'_, T> {
         let backoff = Backoff::new();
         while self.flag.swap(true, Ordering::Acquire) {
             backoff.snooze();
         }
         SpinlockGuard { parent: self }
     }
+
+    /// Attempts to lock the spinlock without spinning.
+    #[cfg(feature = "flavor-array")]
+    pub(crate) fn try_lock(&self) -> Option<SpinlockGuard<'_, T>> {
+        if self.flag.swap(true, Ordering::Acquire) {
+            None
+        } else {
+            Some(SpinlockGuard { parent: self })
+        }
+    }
 }
 
 /// A guard holding a spinlock locked.
-pub struct SpinlockGuard<'a, T: 'a> {
+pub(crate) struct SpinlockGuard<'a, T: 'a> {
     parent: &'a Spinlock<T>,
 }
 
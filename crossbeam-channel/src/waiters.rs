@@ -0,0 +1,380 @@
+//! A registry of threads blocked in channel operations, for debugging.
+//!
+//! Blocking operations describe themselves with [`describe`] before they start. If the thread
+//! then parks, the description is recorded in a global registry until the thread wakes up, and
+//! [`dump_waiters`] prints what every parked thread waits on.
+//!
+//! Recording only happens with the `debug-waiters` feature, or with the `test-harness` feature
+//! while a test harness is alive. The harness reads the same registry, and keeps its own
+//! bookkeeping in it. Descriptions are also kept with the `priority-inheritance` feature, which
+//! looks up the channel a blocked sender waits on, and with the `metrics` feature, which
+//! attributes blocked time to channels. Without any of these features, everything in this module
+//! compiles down to nothing.
+//!
+//! [`describe`]: fn.describe.html
+//! [`dump_waiters`]: fn.dump_waiters.html
+
+#[cfg(any(
+    feature = "debug-waiters",
+    feature = "priority-inheritance",
+    feature = "metrics"
+))]
+use std::cell::Cell;
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+use std::sync::{Condvar, Mutex};
+use std::thread::Thread;
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+use std::thread::ThreadId;
+use std::time::Instant;
+
+#[cfg(feature = "test-harness")]
+use harness;
+
+/// The kind of operation a thread is blocked in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperationKind {
+    /// A send operation.
+    Send,
+
+    /// A receive operation.
+    Recv,
+
+    /// A select over several operations.
+    Select,
+
+    /// Waiting until one of several operations becomes ready.
+    Ready,
+}
+
+impl fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OperationKind::Send => "send".fmt(f),
+            OperationKind::Recv => "recv".fmt(f),
+            OperationKind::Select => "select".fmt(f),
+            OperationKind::Ready => "ready".fmt(f),
+        }
+    }
+}
+
+/// A description of what a blocked thread waits on.
+///
+/// Returned by [`waiters`].
+///
+/// [`waiters`]: fn.waiters.html
+#[cfg(feature = "debug-waiters")]
+#[derive(Clone, Debug)]
+pub struct Waiter {
+    thread: Thread,
+    kind: OperationKind,
+    channel: Option<usize>,
+    deadline: Option<Instant>,
+    since: Instant,
+}
+
+#[cfg(feature = "debug-waiters")]
+impl Waiter {
+    /// Returns the blocked thread.
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Returns the kind of operation the thread is blocked in.
+    pub fn kind(&self) -> OperationKind {
+        self.kind
+    }
+
+    /// Returns an identifier of the channel the thread is blocked on.
+    ///
+    /// Two waiters blocked on the same channel have the same identifier. Selects wait on several
+    /// channels at once and don't have one.
+    pub fn channel(&self) -> Option<usize> {
+        self.channel
+    }
+
+    /// Returns the deadline of the operation, if it has one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the instant at which the thread got blocked.
+    pub fn since(&self) -> Instant {
+        self.since
+    }
+}
+
+#[cfg(feature = "debug-waiters")]
+impl fmt::Display for Waiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.thread.name() {
+            Some(name) => write!(f, "thread '{}'", name)?,
+            None => write!(f, "thread {:?}", self.thread.id())?,
+        }
+        write!(f, ": {}", self.kind)?;
+        if let Some(channel) = self.channel {
+            write!(f, " on channel {:#x}", channel)?;
+        }
+        write!(f, ", blocked for {:?}", self.since.elapsed())?;
+
+        if let Some(deadline) = self.deadline {
+            let now = Instant::now();
+            if deadline > now {
+                write!(f, ", deadline in {:?}", deadline - now)?;
+            } else {
+                write!(f, ", deadline passed")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(
+    feature = "debug-waiters",
+    feature = "priority-inheritance",
+    feature = "metrics"
+))]
+thread_local! {
+    /// The operation the current thread is performing.
+    static CURRENT: Cell<Option<(OperationKind, Option<usize>)>> = Cell::new(None);
+}
+
+/// What is recorded about a parked thread.
+///
+/// Operations that haven't been described are only recorded for test harnesses.
+#[cfg(feature = "debug-waiters")]
+type Record = Option<Waiter>;
+#[cfg(all(feature = "test-harness", not(feature = "debug-waiters")))]
+type Record = ();
+
+/// The registry of parked threads.
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+pub struct Registry {
+    /// Parked threads, along with the bookkeeping of test harnesses.
+    pub state: Mutex<State>,
+
+    /// Signaled whenever a thread gets parked while a test harness is alive, or a test harness
+    /// lets a held thread continue.
+    pub cond: Condvar,
+}
+
+/// The state guarded by the registry.
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+pub struct State {
+    /// Parked threads.
+    pub parked: HashMap<ThreadId, Record>,
+
+    /// Threads held and blocks counted by test harnesses.
+    #[cfg(feature = "test-harness")]
+    pub harness: harness::State,
+}
+
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+lazy_static! {
+    /// The registry of parked threads.
+    pub static ref REGISTRY: Registry = Registry {
+        state: Mutex::new(State {
+            parked: HashMap::new(),
+            #[cfg(feature = "test-harness")]
+            harness: harness::State::new(),
+        }),
+        cond: Condvar::new(),
+    };
+}
+
+/// Describes the operation the current thread is about to perform.
+///
+/// The description applies until the returned guard is dropped.
+#[inline]
+pub fn describe(kind: OperationKind, channel: Option<usize>) -> Description {
+    #[cfg(any(
+        feature = "debug-waiters",
+        feature = "priority-inheritance",
+        feature = "metrics"
+    ))]
+    {
+        let prev = CURRENT
+            .try_with(|current| current.replace(Some((kind, channel))))
+            .unwrap_or(None);
+        Description { prev }
+    }
+    #[cfg(not(any(
+        feature = "debug-waiters",
+        feature = "priority-inheritance",
+        feature = "metrics"
+    )))]
+    {
+        let _ = (kind, channel);
+        Description {}
+    }
+}
+
+/// Returns the operation the current thread is performing, if it has been described.
+#[cfg(any(feature = "priority-inheritance", feature = "metrics"))]
+pub fn current() -> Option<(OperationKind, Option<usize>)> {
+    CURRENT.try_with(|current| current.get()).unwrap_or(None)
+}
+
+/// A guard restoring the previous description of the current operation when dropped.
+pub struct Description {
+    #[cfg(any(
+        feature = "debug-waiters",
+        feature = "priority-inheritance",
+        feature = "metrics"
+    ))]
+    prev: Option<(OperationKind, Option<usize>)>,
+}
+
+#[cfg(any(
+    feature = "debug-waiters",
+    feature = "priority-inheritance",
+    feature = "metrics"
+))]
+impl Drop for Description {
+    fn drop(&mut self) {
+        let prev = self.prev;
+        let _ = CURRENT.try_with(|current| current.set(prev));
+    }
+}
+
+/// A record of a parked thread.
+///
+/// When dropped, the thread is removed from the registry.
+pub struct Parked {
+    #[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+    thread_id: Option<ThreadId>,
+}
+
+impl Parked {
+    /// Records the thread as parked, if its current operation has been described or a test
+    /// harness is alive.
+    ///
+    /// If a test harness holds the thread, this waits until the harness lets it continue.
+    #[inline]
+    pub fn enter(thread: &Thread, deadline: Option<Instant>) -> Parked {
+        #[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+        {
+            #[cfg(feature = "debug-waiters")]
+            let record = {
+                let current = CURRENT.try_with(|current| current.get()).unwrap_or(None);
+                current.map(|(kind, channel)| Waiter {
+                    thread: thread.clone(),
+                    kind,
+                    channel,
+                    deadline,
+                    since: Instant::now(),
+                })
+            };
+            #[cfg(feature = "debug-waiters")]
+            let described = record.is_some();
+            #[cfg(not(feature = "debug-waiters"))]
+            let (record, described) = {
+                let _ = deadline;
+                ((), false)
+            };
+
+            #[cfg(feature = "test-harness")]
+            let observed = harness::is_active();
+            #[cfg(not(feature = "test-harness"))]
+            let observed = false;
+
+            if described || observed {
+                Parked::register(thread.id(), record, observed)
+            } else {
+                Parked { thread_id: None }
+            }
+        }
+        #[cfg(not(any(feature = "debug-waiters", feature = "test-harness")))]
+        {
+            let _ = (thread, deadline);
+            Parked {}
+        }
+    }
+
+    #[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+    fn register(thread_id: ThreadId, record: Record, observed: bool) -> Parked {
+        let mut state = REGISTRY.state.lock().unwrap();
+        state.parked.insert(thread_id, record);
+
+        #[cfg(feature = "test-harness")]
+        {
+            if observed {
+                harness::on_park(state, thread_id);
+            }
+        }
+        #[cfg(not(feature = "test-harness"))]
+        {
+            let _ = observed;
+        }
+
+        Parked {
+            thread_id: Some(thread_id),
+        }
+    }
+}
+
+#[cfg(any(feature = "debug-waiters", feature = "test-harness"))]
+impl Drop for Parked {
+    fn drop(&mut self) {
+        if let Some(thread_id) = self.thread_id {
+            REGISTRY.state.lock().unwrap().parked.remove(&thread_id);
+        }
+    }
+}
+
+/// Returns descriptions of all threads currently blocked in channel operations.
+///
+/// The descriptions are sorted by how long the threads have been blocked, longest first.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::debug::{self, OperationKind};
+/// use crossbeam_channel::unbounded;
+///
+/// let (s, r) = unbounded::<i32>();
+/// let t = thread::Builder::new()
+///     .name("consumer".to_string())
+///     .spawn(move || r.recv())
+///     .unwrap();
+///
+/// thread::sleep(Duration::from_millis(500));
+/// let waiters = debug::waiters();
+/// let waiter = waiters
+///     .iter()
+///     .find(|w| w.thread().name() == Some("consumer"))
+///     .unwrap();
+/// assert_eq!(waiter.kind(), OperationKind::Recv);
+///
+/// drop(s);
+/// t.join().unwrap().unwrap_err();
+/// ```
+#[cfg(feature = "debug-waiters")]
+pub fn waiters() -> Vec<Waiter> {
+    let mut waiters: Vec<Waiter> = REGISTRY
+        .state
+        .lock()
+        .unwrap()
+        .parked
+        .values()
+        .filter_map(|record| record.clone())
+        .collect();
+    waiters.sort_by_key(|w| w.since);
+    waiters
+}
+
+/// Prints what every thread blocked in a channel operation waits on to standard error.
+///
+/// Each blocked thread is printed on its own line, longest blocked first.
+#[cfg(feature = "debug-waiters")]
+pub fn dump_waiters() {
+    let waiters = waiters();
+    eprintln!("{} thread(s) blocked in channel operations", waiters.len());
+    for waiter in &waiters {
+        eprintln!("  {}", waiter);
+    }
+}
@@ -1,9 +1,15 @@
 //! Waking mechanism for threads blocked on channel operations.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{self, AtomicBool};
+#[cfg(feature = "flavor-array")]
+use std::sync::{Condvar, Mutex};
 use std::thread::{self, ThreadId};
+#[cfg(feature = "flavor-array")]
+use std::time::Instant;
 
+use affinity::{self, CoreSet};
 use context::Context;
+use ordering::{yield_point, Ordering};
 use select::{Operation, Selected};
 use utils::Spinlock;
 
@@ -17,6 +23,9 @@ pub struct Entry {
 
     /// Context associated with the thread owning this operation.
     pub cx: Context,
+
+    /// The core the thread owning this operation declared to run on.
+    pub core: Option<usize>,
 }
 
 /// A queue of threads blocked on channel operations.
@@ -29,6 +38,12 @@ pub struct Waker {
 
     /// A list of operations waiting to be ready.
     observers: Vec<Entry>,
+
+    /// Cores whose threads are woken up first.
+    preference: Option<CoreSet>,
+
+    /// Entries woken up from a signal handler, which are dropped later outside of it.
+    retired: Vec<Entry>,
 }
 
 impl Waker {
@@ -38,6 +53,8 @@ impl Waker {
         Waker {
             selectors: Vec::new(),
             observers: Vec::new(),
+            preference: None,
+            retired: Vec::new(),
         }
     }
 
@@ -54,6 +71,7 @@ impl Waker {
             oper,
             packet,
             cx: cx.clone(),
+            core: affinity::current_core(),
         });
     }
 
@@ -74,39 +92,52 @@ impl Waker {
     }
 
     /// Attempts to find another thread's entry, select the operation, and wake it up.
+    ///
+    /// At most one thread is woken up. Entries whose threads have already been selected by
+    /// another operation (for example, by a different channel in the same select) are skipped
+    /// rather than woken up, so a single message never wakes up more than one blocked thread.
+    ///
+    /// If a preference is set, threads on the preferred cores are tried first.
     #[inline]
     pub fn try_select(&mut self) -> Option<Entry> {
-        let mut entry = None;
+        if self.selectors.is_empty() {
+            return None;
+        }
 
-        if !self.selectors.is_empty() {
-            let thread_id = current_thread_id();
+        let thread_id = current_thread_id();
 
+        if let Some(ref preference) = self.preference {
             for i in 0..self.selectors.len() {
-                // Does the entry belong to a different thread?
-                if self.selectors[i].cx.thread_id() != thread_id {
-                    // Try selecting this operation.
-                    let sel = Selected::Operation(self.selectors[i].oper);
-                    let res = self.selectors[i].cx.try_select(sel);
-
-                    if res.is_ok() {
-                        // Provide the packet.
-                        self.selectors[i].cx.store_packet(self.selectors[i].packet);
-                        // Wake the thread up.
-                        self.selectors[i].cx.unpark();
-
-                        // Remove the entry from the queue to keep it clean and improve
-                        // performance.
-                        entry = Some(self.selectors.remove(i));
-                        break;
+                if let Some(core) = self.selectors[i].core {
+                    if preference.contains(core) && self.selectors[i].cx.thread_id() != thread_id {
+                        if let Some(entry) = select_entry(&mut self.selectors, i) {
+                            return Some(entry);
+                        }
                     }
                 }
             }
         }
 
-        entry
+        for i in 0..self.selectors.len() {
+            // Does the entry belong to a different thread?
+            if self.selectors[i].cx.thread_id() != thread_id {
+                if let Some(entry) = select_entry(&mut self.selectors, i) {
+                    return Some(entry);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Sets the cores whose threads are woken up first by `try_select`.
+    #[inline]
+    pub fn set_preference(&mut self, preference: Option<CoreSet>) {
+        self.preference = preference;
     }
 
     /// Returns `true` if there is an entry which can be selected by the current thread.
+    #[cfg(feature = "flavor-zero")]
     #[inline]
     pub fn can_select(&self) -> bool {
         if self.selectors.is_empty() {
@@ -127,6 +158,7 @@ impl Waker {
             oper,
             packet: 0,
             cx: cx.clone(),
+            core: None,
         });
     }
 
@@ -137,6 +169,9 @@ impl Waker {
     }
 
     /// Notifies all operations waiting to be ready.
+    ///
+    /// Unlike selectors, observers don't consume anything when woken up, so every one of them
+    /// gets notified.
     #[inline]
     pub fn notify(&mut self) {
         for entry in self.observers.drain(..) {
@@ -146,6 +181,53 @@ impl Waker {
         }
     }
 
+    /// Wakes up one selector and all observers, like `try_select` followed by `notify`, but in a
+    /// way that is async-signal-safe.
+    ///
+    /// Nothing is allocated or freed: woken entries are moved into `retired`, which must have
+    /// enough spare capacity (see `reserve_retired`). The current thread isn't skipped because
+    /// looking it up isn't async-signal-safe. Observers with callbacks are left alone since their
+    /// callbacks may do anything.
+    ///
+    /// Returns `false` if some observers were left alone.
+    #[cfg(feature = "flavor-array")]
+    pub fn notify_from_signal(&mut self) -> bool {
+        for i in 0..self.selectors.len() {
+            let sel = Selected::Operation(self.selectors[i].oper);
+            if self.selectors[i].cx.try_select(sel).is_ok() {
+                self.selectors[i].cx.store_packet(self.selectors[i].packet);
+                self.selectors[i].cx.unpark();
+                let entry = self.selectors.remove(i);
+                self.retired.push(entry);
+                break;
+            }
+        }
+
+        let mut done = true;
+        let mut i = 0;
+        while i < self.observers.len() {
+            if self.observers[i].cx.has_callback() {
+                done = false;
+                i += 1;
+            } else {
+                let entry = self.observers.remove(i);
+                if entry.cx.try_select(Selected::Operation(entry.oper)).is_ok() {
+                    entry.cx.unpark();
+                }
+                self.retired.push(entry);
+            }
+        }
+        done
+    }
+
+    /// Drops the retired entries and makes room for retiring every registered entry.
+    #[inline]
+    pub fn reserve_retired(&mut self) {
+        self.retired.clear();
+        self.retired
+            .reserve(self.selectors.len() + self.observers.len());
+    }
+
     /// Notifies all registered operations that the channel is disconnected.
     #[inline]
     pub fn disconnect(&mut self) {
@@ -164,6 +246,26 @@ impl Waker {
     }
 }
 
+/// Tries selecting the operation of the entry at index `i`, and wakes its thread up on success.
+///
+/// The selected entry is removed from the queue.
+#[inline]
+fn select_entry(selectors: &mut Vec<Entry>, i: usize) -> Option<Entry> {
+    let sel = Selected::Operation(selectors[i].oper);
+
+    if selectors[i].cx.try_select(sel).is_ok() {
+        // Provide the packet.
+        selectors[i].cx.store_packet(selectors[i].packet);
+        // Wake the thread up.
+        selectors[i].cx.unpark();
+
+        // Remove the entry from the queue to keep it clean and improve performance.
+        Some(selectors.remove(i))
+    } else {
+        None
+    }
+}
+
 impl Drop for Waker {
     #[inline]
     fn drop(&mut self) {
@@ -181,6 +283,13 @@ pub struct SyncWaker {
 
     /// `true` if the waker is empty.
     is_empty: AtomicBool,
+
+    /// `true` if a notification from a signal handler couldn't get the lock and was left to the
+    /// thread holding it.
+    deferred: AtomicBool,
+
+    /// `true` if signal handlers may notify the waker with `notify_from_signal`.
+    signal_safe: bool,
 }
 
 impl SyncWaker {
@@ -190,39 +299,104 @@ impl SyncWaker {
         SyncWaker {
             inner: Spinlock::new(Waker::new()),
             is_empty: AtomicBool::new(true),
+            deferred: AtomicBool::new(false),
+            signal_safe: false,
         }
     }
 
+    /// Creates a new `SyncWaker` that signal handlers may notify with `notify_from_signal`.
+    ///
+    /// Every operation on it then keeps room for retiring entries and catches up on deferred
+    /// notifications, which ordinary wakers don't pay for.
+    #[cfg(feature = "flavor-array")]
+    pub fn signal_safe() -> Self {
+        SyncWaker {
+            inner: Spinlock::new(Waker::new()),
+            is_empty: AtomicBool::new(true),
+            deferred: AtomicBool::new(false),
+            signal_safe: true,
+        }
+    }
+
+    /// Runs `f` with the inner `Waker` locked.
+    ///
+    /// If the waker is signal-safe, afterwards catches up on a notification that a signal handler
+    /// left behind while the lock was held.
+    #[inline]
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Waker) -> R,
+    {
+        let res = {
+            let mut inner = self.inner.lock();
+            let res = f(&mut inner);
+            if self.signal_safe {
+                inner.reserve_retired();
+            }
+            self.is_empty.store(
+                inner.selectors.is_empty() && inner.observers.is_empty(),
+                Ordering::SeqCst,
+            );
+            res
+        };
+
+        if self.signal_safe {
+            // Pairs with the fence in `notify_from_signal`: either the signal handler gets the
+            // lock, or this thread sees the flag.
+            atomic::fence(Ordering::SeqCst);
+            if self.deferred.load(Ordering::Relaxed) && self.deferred.swap(false, Ordering::SeqCst)
+            {
+                self.notify();
+            }
+        }
+        res
+    }
+
     /// Registers the current thread with an operation.
     #[inline]
     pub fn register(&self, oper: Operation, cx: &Context) {
-        let mut inner = self.inner.lock();
-        inner.register(oper, cx);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.with(|inner| inner.register(oper, cx));
+        yield_point();
     }
 
     /// Unregisters an operation previously registered by the current thread.
     #[inline]
     pub fn unregister(&self, oper: Operation) -> Option<Entry> {
-        let mut inner = self.inner.lock();
-        let entry = inner.unregister(oper);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
-        entry
+        self.with(|inner| inner.unregister(oper))
     }
 
     /// Attempts to find one thread (not the current one), select its operation, and wake it up.
     #[inline]
     pub fn notify(&self) {
+        yield_point();
         if !self.is_empty.load(Ordering::SeqCst) {
-            let mut inner = self.inner.lock();
-            inner.try_select();
-            inner.notify();
+            self.with(|inner| {
+                inner.try_select();
+                inner.notify();
+            });
+        }
+    }
+
+    /// Like `notify`, but async-signal-safe.
+    ///
+    /// Never waits for the lock, since the thread holding it might be the one the signal
+    /// interrupted. If the lock is held, the notification is left to the thread holding it.
+    ///
+    /// The waker must have been created with `signal_safe`.
+    #[cfg(feature = "flavor-array")]
+    pub fn notify_from_signal(&self) {
+        debug_assert!(self.signal_safe);
+        if self.is_empty.load(Ordering::SeqCst) {
+            return;
+        }
+
+        self.deferred.store(true, Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+        if let Some(mut inner) = self.inner.try_lock() {
+            if self.deferred.swap(false, Ordering::SeqCst) && !inner.notify_from_signal() {
+                // Observers with callbacks get notified by the next regular operation.
+                self.deferred.store(true, Ordering::SeqCst);
+            }
             self.is_empty.store(
                 inner.selectors.is_empty() && inner.observers.is_empty(),
                 Ordering::SeqCst,
@@ -233,34 +407,25 @@ impl SyncWaker {
     /// Registers an operation waiting to be ready.
     #[inline]
     pub fn watch(&self, oper: Operation, cx: &Context) {
-        let mut inner = self.inner.lock();
-        inner.watch(oper, cx);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.with(|inner| inner.watch(oper, cx));
     }
 
     /// Unregisters an operation waiting to be ready.
     #[inline]
     pub fn unwatch(&self, oper: Operation) {
-        let mut inner = self.inner.lock();
-        inner.unwatch(oper);
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.with(|inner| inner.unwatch(oper));
+    }
+
+    /// Sets the cores whose threads are woken up first.
+    #[inline]
+    pub fn set_preference(&self, preference: Option<CoreSet>) {
+        self.with(|inner| inner.set_preference(preference));
     }
 
     /// Notifies all threads that the channel is disconnected.
     #[inline]
     pub fn disconnect(&self) {
-        let mut inner = self.inner.lock();
-        inner.disconnect();
-        self.is_empty.store(
-            inner.selectors.is_empty() && inner.observers.is_empty(),
-            Ordering::SeqCst,
-        );
+        self.with(|inner| inner.disconnect());
     }
 }
 
@@ -271,6 +436,112 @@ impl Drop for SyncWaker {
     }
 }
 
+/// A queue of threads taking turns in first-in, first-out order.
+///
+/// Each thread takes a ticket and waits until its ticket is served. Only one thread holds the
+/// turn at a time, and turns are handed out in the order tickets were taken.
+#[cfg(feature = "flavor-array")]
+pub struct FairQueue {
+    /// The state of the queue.
+    state: Mutex<FairState>,
+
+    /// Signaled whenever the turn moves on.
+    cond: Condvar,
+}
+
+/// The state of a `FairQueue`.
+#[cfg(feature = "flavor-array")]
+struct FairState {
+    /// The ticket that will be taken next.
+    next: usize,
+
+    /// The ticket currently holding the turn.
+    serving: usize,
+
+    /// Tickets whose owners gave up waiting.
+    abandoned: Vec<usize>,
+}
+
+#[cfg(feature = "flavor-array")]
+impl FairQueue {
+    /// Creates a new `FairQueue`.
+    pub fn new() -> Self {
+        FairQueue {
+            state: Mutex::new(FairState {
+                next: 0,
+                serving: 0,
+                abandoned: Vec::new(),
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// Waits for the current thread's turn until the optional deadline.
+    ///
+    /// Returns `None` if the deadline was reached first.
+    pub fn enter(&self, deadline: Option<Instant>) -> Option<Turn<'_>> {
+        let mut state = self.state.lock().unwrap();
+        let ticket = state.next;
+        state.next = state.next.wrapping_add(1);
+
+        while state.serving != ticket {
+            state = match deadline {
+                None => self.cond.wait(state).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        // Give up, letting the turn skip this ticket.
+                        state.abandoned.push(ticket);
+                        return None;
+                    }
+                    self.cond.wait_timeout(state, deadline - now).unwrap().0
+                }
+            };
+        }
+
+        Some(Turn { queue: self })
+    }
+
+    /// Takes the turn without waiting if no other thread holds it or is waiting for it.
+    pub fn try_enter(&self) -> Option<Turn<'_>> {
+        let mut state = self.state.lock().unwrap();
+        if state.next == state.serving {
+            state.next = state.next.wrapping_add(1);
+            Some(Turn { queue: self })
+        } else {
+            None
+        }
+    }
+}
+
+/// The turn in a `FairQueue`, passed on to the next thread when dropped.
+#[cfg(feature = "flavor-array")]
+pub struct Turn<'a> {
+    queue: &'a FairQueue,
+}
+
+#[cfg(feature = "flavor-array")]
+impl<'a> Drop for Turn<'a> {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.serving = state.serving.wrapping_add(1);
+
+        // Skip tickets of threads that gave up waiting.
+        loop {
+            let serving = state.serving;
+            match state.abandoned.iter().position(|&t| t == serving) {
+                Some(i) => {
+                    state.abandoned.swap_remove(i);
+                    state.serving = state.serving.wrapping_add(1);
+                }
+                None => break,
+            }
+        }
+
+        self.queue.cond.notify_all();
+    }
+}
+
 /// Returns the id of the current thread.
 #[inline]
 fn current_thread_id() -> ThreadId {
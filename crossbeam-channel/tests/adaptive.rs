@@ -0,0 +1,159 @@
+//! Tests for adaptive channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{adaptive_bounded, CapacityDecision};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, SendTimeoutError};
+use crossbeam_channel::{TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+/// The number of operations after which the capacity is reconsidered.
+const WINDOW: usize = 64;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = adaptive_bounded(2, 8);
+    assert_eq!(s.capacity(), 2);
+    assert_eq!(r.capacity(), 2);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(s.send_timeout(3, ms(50)), Err(SendTimeoutError::Timeout(3)));
+    assert_eq!(s.len(), 2);
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    assert!(r.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "adaptive capacity must be at least one")]
+fn zero_capacity() {
+    adaptive_bounded::<i32>(0, 1);
+}
+
+#[test]
+#[should_panic(expected = "minimum capacity must not exceed maximum capacity")]
+fn min_above_max() {
+    adaptive_bounded::<i32>(2, 1);
+}
+
+#[test]
+fn grows_and_shrinks() {
+    let (s, r) = adaptive_bounded(1, 4);
+    let decisions = r.decisions();
+
+    // Senders keep finding the channel full.
+    for i in 0..WINDOW {
+        let _ = s.try_send(i);
+    }
+    assert_eq!(
+        decisions.try_recv(),
+        Ok(CapacityDecision::Grew { from: 1, to: 2 })
+    );
+    assert_eq!(s.capacity(), 2);
+
+    // Receivers keep finding the channel empty.
+    for _ in 0..WINDOW {
+        let _ = r.try_recv();
+    }
+    assert_eq!(
+        decisions.try_recv(),
+        Ok(CapacityDecision::Shrank { from: 2, to: 1 })
+    );
+    assert_eq!(r.capacity(), 1);
+    assert_eq!(decisions.try_recv(), Err(TryRecvError::Empty));
+
+    assert_eq!(s.try_send(100), Ok(()));
+    assert_eq!(s.try_send(101), Err(TrySendError::Full(101)));
+}
+
+#[test]
+fn bounds() {
+    let (s, r) = adaptive_bounded(2, 3);
+    let decisions = r.decisions();
+
+    for _ in 0..4 {
+        for i in 0..WINDOW {
+            let _ = s.try_send(i);
+        }
+    }
+    assert_eq!(s.capacity(), 3);
+    assert_eq!(
+        decisions.try_iter().collect::<Vec<_>>(),
+        [CapacityDecision::Grew { from: 2, to: 3 }]
+    );
+
+    while r.try_recv().is_ok() {}
+    for _ in 0..4 * WINDOW {
+        let _ = r.try_recv();
+    }
+    assert_eq!(r.capacity(), 2);
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = adaptive_bounded(1, 1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(200));
+            drop(r);
+        });
+
+        // A sender waiting for room notices that the receivers are gone.
+        assert_eq!(s.send(2), Err(SendError(2)));
+    })
+    .unwrap();
+
+    let (s, r) = adaptive_bounded::<i32>(1, 1);
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 20_000;
+
+    let (s, r) = adaptive_bounded(1, 32);
+    let decisions = r.decisions();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+                assert!(s.len() <= 32);
+            }
+        });
+
+        for i in 0..COUNT {
+            assert_eq!(r.recv(), Ok(i));
+            if i % 100 == 0 {
+                thread::sleep(ms(1));
+            }
+        }
+    })
+    .unwrap();
+
+    // The capacity reported by every decision stays within the bounds.
+    for decision in decisions.try_iter() {
+        let (from, to) = match decision {
+            CapacityDecision::Grew { from, to } => (from, to),
+            CapacityDecision::Shrank { from, to } => (from, to),
+        };
+        assert!(from >= 1 && to >= 1);
+        assert!(from <= 32 && to <= 32);
+    }
+}
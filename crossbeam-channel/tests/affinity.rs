@@ -0,0 +1,123 @@
+//! Tests for affinity hints.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::affinity::{self, CoreSet};
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn core_set() {
+    let mut set = CoreSet::new();
+    assert!(set.is_empty());
+
+    set.insert(3);
+    set.insert(200);
+    assert!(set.contains(3));
+    assert!(set.contains(200));
+    assert!(!set.contains(4));
+    assert!(!set.contains(1000));
+    assert_eq!(set.iter().collect::<Vec<_>>(), [3, 200]);
+
+    set.remove(3);
+    set.remove(1000);
+    assert_eq!(set.iter().collect::<Vec<_>>(), [200]);
+    assert_eq!(format!("{:?}", set), "{200}");
+
+    set.remove(200);
+    assert!(set.is_empty());
+    assert_eq!(set, (0..0).collect());
+}
+
+#[test]
+fn current_core() {
+    assert_eq!(affinity::current_core(), None);
+    affinity::set_current_core(Some(5));
+    assert_eq!(affinity::current_core(), Some(5));
+
+    thread::spawn(|| assert_eq!(affinity::current_core(), None))
+        .join()
+        .unwrap();
+
+    affinity::set_current_core(None);
+    assert_eq!(affinity::current_core(), None);
+}
+
+/// Blocks one receiver per core, in order, then sends a single message and returns the core of
+/// the receiver that got it.
+fn first_woken(s: &Sender<i32>, r: &Receiver<i32>, cores: &[usize]) -> usize {
+    let (done_s, done_r) = unbounded();
+
+    scope(|scope| {
+        for &core in cores {
+            let done_s = done_s.clone();
+            scope.spawn(move |_| {
+                affinity::set_current_core(Some(core));
+                let msg = r.recv().unwrap();
+                done_s.send((core, msg)).unwrap();
+            });
+            thread::sleep(ms(100));
+        }
+
+        s.send(0).unwrap();
+        let (core, _) = done_r.recv().unwrap();
+
+        // Release the remaining receivers.
+        for i in 1..cores.len() {
+            s.send(i as i32).unwrap();
+        }
+        core
+    })
+    .unwrap()
+}
+
+#[test]
+fn without_preference() {
+    let (s, r) = bounded(0);
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 0);
+
+    let (s, r) = bounded(1);
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 0);
+
+    let (s, r) = unbounded();
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 0);
+}
+
+#[test]
+fn preferred() {
+    let (s, r) = bounded(0);
+    s.wake_preference(Some(vec![2].into_iter().collect()));
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 2);
+
+    let (s, r) = bounded(1);
+    s.wake_preference(Some(vec![1, 2].into_iter().collect()));
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 1);
+
+    let (s, r) = unbounded();
+    s.clone()
+        .wake_preference(Some(vec![2].into_iter().collect()));
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 2);
+}
+
+#[test]
+fn no_preferred_waiting() {
+    let (s, r) = unbounded();
+    s.wake_preference(Some(vec![7].into_iter().collect()));
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 0);
+}
+
+#[test]
+fn cleared_preference() {
+    let (s, r) = unbounded();
+    s.wake_preference(Some(vec![2].into_iter().collect()));
+    s.wake_preference(None);
+    assert_eq!(first_woken(&s, &r, &[0, 1, 2]), 0);
+}
@@ -11,7 +11,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{bounded, Receiver};
+use crossbeam_channel::{bounded, bounded_fair, bounded_with_stats, Receiver};
 use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
@@ -657,3 +657,369 @@ fn channel_through_channel() {
     })
     .unwrap();
 }
+
+#[test]
+fn occupancy_stats() {
+    let (s, r) = bounded_with_stats(8);
+
+    for i in 0..6 {
+        s.send(i).unwrap();
+    }
+    for _ in 0..6 {
+        r.recv().unwrap();
+    }
+
+    let stats = r.take_occupancy_stats().unwrap();
+    assert_eq!(stats.high_water_mark(), 6);
+    assert_eq!(stats.histogram(), &[1, 1, 1, 1, 1, 1, 0, 0]);
+
+    let stats = s.take_occupancy_stats().unwrap();
+    assert_eq!(stats.high_water_mark(), 0);
+    assert_eq!(stats.histogram(), &[0; 8]);
+
+    for i in 0..8 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(s.try_send(8), Err(TrySendError::Full(8)));
+    assert_eq!(s.take_occupancy_stats().unwrap().high_water_mark(), 8);
+
+    let (s, r) = bounded(8);
+    s.send(1).unwrap();
+    assert!(s.take_occupancy_stats().is_none());
+    assert!(r.take_occupancy_stats().is_none());
+}
+
+#[test]
+fn occupancy_stats_concurrent() {
+    const COUNT: usize = 10_000;
+    const CAP: usize = 10;
+
+    let (s, r) = bounded_with_stats(CAP);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                assert_eq!(r.recv(), Ok(i));
+            }
+        });
+    })
+    .unwrap();
+
+    let stats = s.take_occupancy_stats().unwrap();
+    assert!(stats.high_water_mark() <= CAP);
+    assert_eq!(stats.histogram().iter().sum::<usize>(), COUNT);
+}
+
+#[test]
+fn fair_senders() {
+    const THREADS: usize = 8;
+
+    let (s, r) = bounded_fair(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        for i in 1..=THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| s.send(i).unwrap());
+            thread::sleep(ms(50));
+        }
+
+        for i in 0..=THREADS {
+            assert_eq!(r.recv(), Ok(i));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn fair_receivers() {
+    const THREADS: usize = 8;
+
+    let (s, r) = bounded_fair(1);
+    let (done_s, done_r) = bounded(THREADS);
+
+    scope(|scope| {
+        for i in 0..THREADS {
+            let r = r.clone();
+            let done_s = done_s.clone();
+            scope.spawn(move |_| {
+                let msg = r.recv().unwrap();
+                done_s.send((i, msg)).unwrap();
+            });
+            thread::sleep(ms(50));
+        }
+
+        for i in 0..THREADS {
+            s.send(i).unwrap();
+            assert_eq!(done_r.recv(), Ok((i, i)));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn fair_timeout() {
+    let (s, r) = bounded_fair(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+        thread::sleep(ms(100));
+
+        // This sender gives up while queued behind the first one.
+        scope.spawn(|_| {
+            assert_eq!(
+                s.send_timeout(2, ms(100)),
+                Err(SendTimeoutError::Timeout(2))
+            )
+        });
+        thread::sleep(ms(100));
+        scope.spawn(|_| s.send(3).unwrap());
+        thread::sleep(ms(300));
+
+        // Queued senders block `try_send` even though there is room afterwards.
+        assert_eq!(s.try_send(4), Err(TrySendError::Full(4)));
+
+        assert_eq!(r.recv(), Ok(0));
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(3));
+    })
+    .unwrap();
+
+    assert_eq!(s.try_send(5), Ok(()));
+    assert_eq!(r.try_recv(), Ok(5));
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn fair_mpmc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded_fair::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn per_producer_fifo() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    for &cap in &[1, 2, 7] {
+        let (s, r) = bounded::<(usize, usize)>(cap);
+
+        scope(|scope| {
+            for t in 0..THREADS {
+                let s = s.clone();
+                scope.spawn(move |_| {
+                    for i in 0..COUNT {
+                        s.send((t, i)).unwrap();
+                    }
+                });
+            }
+
+            let mut next = [0; THREADS];
+            for _ in 0..COUNT * THREADS {
+                let (t, i) = r.recv().unwrap();
+                assert_eq!(i, next[t]);
+                next[t] += 1;
+            }
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn per_producer_fifo_mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    for &fair in &[false, true] {
+        let (s, r) = if fair {
+            bounded_fair::<(usize, usize)>(3)
+        } else {
+            bounded::<(usize, usize)>(3)
+        };
+
+        scope(|scope| {
+            for t in 0..THREADS {
+                let s = s.clone();
+                scope.spawn(move |_| {
+                    for i in 0..COUNT {
+                        s.send((t, i)).unwrap();
+                    }
+                });
+            }
+            drop(s);
+
+            for _ in 0..THREADS {
+                let r = r.clone();
+                scope.spawn(move |_| {
+                    // Each receiver sees the messages of every sender in increasing order, with
+                    // gaps where other receivers took some of them.
+                    let mut last = [None; THREADS];
+                    for (t, i) in r {
+                        if let Some(l) = last[t] {
+                            assert!(l < i);
+                        }
+                        last[t] = Some(i);
+                    }
+                });
+            }
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn recv_in_place() {
+    let (s, r) = bounded(2);
+    s.send(vec![1]).unwrap();
+    s.send(vec![2]).unwrap();
+
+    let mut first = r.recv_in_place().unwrap();
+    first.push(10);
+    assert_eq!(*first, [1, 10]);
+
+    // Later messages can be received while the first slot is held, but senders can't get past
+    // the held slot.
+    assert_eq!(r.recv(), Ok(vec![2]));
+    assert_eq!(s.try_send(vec![3]), Err(TrySendError::Full(vec![3])));
+
+    drop(first);
+    s.send(vec![3]).unwrap();
+    s.send(vec![4]).unwrap();
+
+    assert_eq!(r.recv_in_place().unwrap().into_inner(), [3]);
+    assert_eq!(r.recv(), Ok(vec![4]));
+
+    drop(s);
+    assert!(r.recv_in_place().is_err());
+}
+
+#[test]
+fn recv_in_place_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded(1);
+    s.send(DropCounter).unwrap();
+    let msg = r.recv_in_place().unwrap();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    drop(msg);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    s.send(DropCounter).unwrap();
+    let msg = r.recv_in_place().unwrap().into_inner();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    drop(msg);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    // Nothing is left for the channel to drop.
+    drop((s, r));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn recv_in_place_blocks_sender() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        let msg = r.recv_in_place().unwrap();
+        scope.spawn(|_| s.send(2).unwrap());
+
+        thread::sleep(ms(200));
+        assert_eq!(*msg, 1);
+        assert!(r.is_empty());
+        drop(msg);
+
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_while_held() {
+    let (s, r) = bounded(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    let msg = r.recv_in_place().unwrap();
+    assert_eq!(r.recv(), Ok(2));
+
+    // The head has wrapped around to the held slot.
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Disconnected));
+    assert_eq!(*msg, 1);
+}
+
+#[test]
+fn recv_latest() {
+    let (s, r) = bounded(3);
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv_latest(), Ok(2));
+    assert!(r.is_empty());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // Draining makes room for all of these.
+            for i in 3..9 {
+                s.send(i).unwrap();
+            }
+        });
+
+        let mut last = 0;
+        while last < 8 {
+            let msg = r.recv_latest().unwrap();
+            assert!(msg > last);
+            last = msg;
+        }
+    })
+    .unwrap();
+    assert_eq!(r.try_recv_latest(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,60 @@
+//! Tests for the fence audit.
+
+#![cfg(feature = "fence-audit")]
+
+extern crate crossbeam_channel;
+
+use crossbeam_channel::audit::{self, audit_flavors, with_random_yields};
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+
+#[test]
+fn yield_points_in_every_flavor() {
+    let channels: Vec<(Sender<i32>, Receiver<i32>)> = vec![bounded(1), unbounded()];
+
+    for (s, r) in channels {
+        let start = audit::yield_points();
+        s.send(1).unwrap();
+        assert_eq!(r.recv(), Ok(1));
+        assert!(audit::yield_points() > start);
+    }
+}
+
+#[test]
+fn no_yields_outside() {
+    let (s, r) = unbounded();
+    let start = audit::yields();
+    for i in 0..1000 {
+        s.send(i).unwrap();
+        r.recv().unwrap();
+    }
+    assert_eq!(audit::yields(), start);
+}
+
+#[test]
+fn random_yields() {
+    let (s, r) = unbounded();
+    let start = audit::yields();
+    with_random_yields(7, || {
+        for i in 0..1000 {
+            s.send(i).unwrap();
+            r.recv().unwrap();
+        }
+    });
+    let yields = audit::yields() - start;
+    assert!(yields > 0);
+
+    // Yields stop once the closure returns.
+    s.send(0).unwrap();
+    r.recv().unwrap();
+    assert_eq!(audit::yields() - start, yields);
+}
+
+#[test]
+fn flavors() {
+    for seed in 0..2 {
+        let report = audit_flavors(seed, 200);
+        assert_eq!(report.configurations, 8);
+        assert!(report.yields > 0);
+        assert!(report.yield_points > report.yields);
+    }
+}
@@ -0,0 +1,141 @@
+//! Tests for selectable barriers.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::sync::Barrier;
+use crossbeam_channel::unbounded;
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let barrier = Barrier::new(2);
+    assert_eq!(barrier.parties(), 2);
+
+    let first = barrier.arrive();
+    assert_eq!(barrier.arrived(), 1);
+    assert!(!first.is_complete());
+    assert!(!first.wait_timeout(ms(50)));
+
+    let second = barrier.arrive();
+    assert_eq!(barrier.arrived(), 0);
+    assert!(first.is_complete());
+    assert!(second.is_complete());
+    assert!(!first.is_leader());
+    assert!(second.is_leader());
+}
+
+#[test]
+fn single() {
+    for &n in &[0, 1] {
+        let barrier = Barrier::new(n);
+        assert!(barrier.wait());
+        assert!(barrier.wait());
+    }
+}
+
+#[test]
+fn wait() {
+    const THREADS: usize = 4;
+    const GENERATIONS: usize = 100;
+
+    let barrier = Barrier::new(THREADS);
+    let leaders = AtomicUsize::new(0);
+    let arrived = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..GENERATIONS {
+                    arrived.fetch_add(1, Ordering::SeqCst);
+                    if barrier.wait() {
+                        leaders.fetch_add(1, Ordering::SeqCst);
+                    }
+                    // Nobody can be past the barrier before everyone has arrived.
+                    assert!(arrived.load(Ordering::SeqCst) >= (i + 1) * THREADS);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(leaders.load(Ordering::SeqCst), GENERATIONS);
+}
+
+#[test]
+fn withdraw() {
+    let barrier = Barrier::new(2);
+
+    let arrival = barrier.arrive();
+    assert_eq!(barrier.arrived(), 1);
+    drop(arrival);
+    assert_eq!(barrier.arrived(), 0);
+
+    // A completed arrival isn't withdrawn from the next generation.
+    let first = barrier.arrive();
+    let second = barrier.arrive();
+    let third = barrier.arrive();
+    drop(first);
+    drop(second);
+    assert_eq!(barrier.arrived(), 1);
+    assert!(!third.is_complete());
+}
+
+#[test]
+fn select_shutdown() {
+    let barrier = Barrier::new(3);
+    let (s, r) = unbounded::<()>();
+
+    scope(|scope| {
+        for _ in 0..2 {
+            scope.spawn(|_| {
+                let arrival = barrier.arrive();
+                select! {
+                    recv(arrival) -> _ => panic!(),
+                    recv(r) -> _ => {}
+                }
+            });
+        }
+
+        thread::sleep(ms(300));
+        assert_eq!(barrier.arrived(), 2);
+        drop(s);
+    })
+    .unwrap();
+
+    // The arrivals of the threads that shut down were withdrawn.
+    assert_eq!(barrier.arrived(), 0);
+}
+
+#[test]
+fn select_complete() {
+    let barrier = Barrier::new(2);
+    let (s, r) = unbounded::<()>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let now = Instant::now();
+            let arrival = barrier.arrive();
+            select! {
+                recv(arrival) -> msg => assert!(msg.is_err()),
+                recv(r) -> _ => panic!(),
+            }
+            assert!(now.elapsed() >= ms(200));
+        });
+
+        thread::sleep(ms(300));
+        assert!(barrier.wait());
+    })
+    .unwrap();
+
+    drop(s);
+}
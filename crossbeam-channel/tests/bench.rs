@@ -0,0 +1,57 @@
+//! Tests for the benchmark workloads.
+
+#![cfg(feature = "bench")]
+
+extern crate crossbeam_channel;
+
+use std::time::Duration;
+
+use crossbeam_channel::bench::{Flavor, Workload};
+
+#[test]
+fn names() {
+    assert_eq!(
+        Workload::spsc_ping_pong(Flavor::Bounded(0)).name(),
+        "spsc_ping_pong/bounded(0)"
+    );
+    assert_eq!(
+        Workload::mpmc_fan_in(Flavor::Unbounded, 4).name(),
+        "mpmc_fan_in/4/unbounded"
+    );
+    assert_eq!(
+        Workload::select_storm(Flavor::Bounded(16), 3).name(),
+        "select_storm/3/bounded(16)"
+    );
+    assert_eq!(
+        Workload::bursty_producers(Flavor::Unbounded, 2, 8).name(),
+        "bursty_producers/2x8/unbounded"
+    );
+}
+
+#[test]
+fn suite() {
+    let suite = Workload::suite();
+    assert!(!suite.is_empty());
+
+    let mut names: Vec<_> = suite.iter().map(|w| w.name()).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), suite.len());
+}
+
+#[test]
+fn run_all() {
+    for w in Workload::suite() {
+        // Iteration counts that don't divide evenly among the threads.
+        for &iters in &[0, 1, 7, 1001] {
+            let elapsed = w.run(iters);
+            assert!(elapsed < Duration::from_secs(10), "{}", w.name());
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "number of producers must be positive")]
+fn no_producers() {
+    Workload::mpmc_fan_in(Flavor::Unbounded, 0);
+}
@@ -0,0 +1,162 @@
+//! Tests for the hook consulted before blocking.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::cell::Cell;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, clear_block_hook, set_block_hook, unbounded, BlockDecision};
+use crossbeam_channel::{RecvTimeoutError, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+thread_local! {
+    static CALLS: Cell<usize> = Cell::new(0);
+}
+
+fn calls() -> usize {
+    CALLS.with(|c| c.get())
+}
+
+fn count_and_park() -> BlockDecision {
+    CALLS.with(|c| c.set(c.get() + 1));
+    BlockDecision::Park
+}
+
+fn count_and_retry() -> BlockDecision {
+    CALLS.with(|c| c.set(c.get() + 1));
+    thread::sleep(ms(1));
+    BlockDecision::Retry
+}
+
+#[test]
+fn not_consulted_when_ready() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    set_block_hook(count_and_park);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(calls(), 0);
+}
+
+#[test]
+fn park() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        set_block_hook(count_and_park);
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(calls(), 1);
+    })
+    .unwrap();
+}
+
+#[test]
+fn retry() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        set_block_hook(count_and_retry);
+        assert_eq!(r.recv(), Ok(7));
+        assert!(calls() > 1);
+    })
+    .unwrap();
+}
+
+#[test]
+fn retry_timeout() {
+    let (_s, r) = unbounded::<i32>();
+
+    set_block_hook(count_and_retry);
+    let start = Instant::now();
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+    assert!(start.elapsed() >= ms(100));
+    assert!(calls() > 1);
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s1.send(1).unwrap();
+        });
+
+        set_block_hook(count_and_retry);
+        let mut sel = Select::new();
+        sel.recv(&r1);
+        sel.recv(&r2);
+        let oper = sel.select();
+        assert_eq!(oper.index(), 0);
+        assert_eq!(oper.recv(&r1), Ok(1));
+        assert!(calls() > 1);
+    })
+    .unwrap();
+}
+
+#[test]
+fn clear() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        set_block_hook(count_and_park);
+        clear_block_hook();
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(calls(), 0);
+    })
+    .unwrap();
+}
+
+#[test]
+fn not_recursive() {
+    thread_local! {
+        static INNER: Cell<Option<bool>> = Cell::new(None);
+    }
+
+    // Blocks inside the hook, which must park instead of consulting the hook again.
+    fn nested() -> BlockDecision {
+        CALLS.with(|c| c.set(c.get() + 1));
+        let (_s, r) = unbounded::<i32>();
+        let res = r.recv_timeout(ms(10));
+        INNER.with(|i| i.set(Some(res.is_err())));
+        BlockDecision::Park
+    }
+
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        set_block_hook(nested);
+        assert_eq!(r.recv(), Ok(7));
+        assert_eq!(calls(), 1);
+        assert_eq!(INNER.with(|i| i.get()), Some(true));
+    })
+    .unwrap();
+}
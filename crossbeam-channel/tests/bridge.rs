@@ -0,0 +1,88 @@
+//! Tests for bridged senders.
+
+extern crate crossbeam_channel;
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, BridgedSender};
+use crossbeam_channel::{SendError, TrySendError};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn crossbeam() {
+    let (s, r) = bounded(1);
+    let s = BridgedSender::from(s);
+    assert!(!s.is_std());
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.recv(), Ok(1));
+
+    drop(r);
+    assert_eq!(s.send(3), Err(SendError(3)));
+}
+
+#[test]
+fn std_unbounded() {
+    let (s, r) = mpsc::channel();
+    let s = BridgedSender::from(s);
+    assert!(s.is_std());
+
+    for i in 0..10 {
+        s.try_send(i).unwrap();
+    }
+    s.send(10).unwrap();
+    assert_eq!(
+        r.try_iter().collect::<Vec<_>>(),
+        (0..11).collect::<Vec<_>>()
+    );
+
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn std_bounded() {
+    let (s, r) = mpsc::sync_channel(1);
+    let s = BridgedSender::from(s);
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+
+    let h = thread::spawn(move || {
+        thread::sleep(ms(100));
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(2));
+    });
+    s.send(2).unwrap();
+    h.join().unwrap();
+
+    assert_eq!(s.try_send(3), Err(TrySendError::Disconnected(3)));
+}
+
+#[test]
+fn clones() {
+    let (s, r) = mpsc::channel();
+    let s1 = BridgedSender::from(s);
+    let s2 = s1.clone();
+
+    thread::spawn(move || s2.send(2).unwrap()).join().unwrap();
+    s1.send(1).unwrap();
+    drop(s1);
+
+    assert_eq!(r.iter().collect::<Vec<_>>(), [2, 1]);
+
+    let (s, r) = unbounded();
+    let s1: BridgedSender<i32> = s.into();
+    let s2 = s1.clone();
+    s2.send(1).unwrap();
+    drop(s1);
+    drop(s2);
+    assert_eq!(r.iter().collect::<Vec<_>>(), [1]);
+}
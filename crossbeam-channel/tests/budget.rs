@@ -0,0 +1,164 @@
+//! Tests for channels sharing a budget.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Budget, RecvTimeoutError};
+use crossbeam_channel::{SendError, SendTimeoutError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let budget = Budget::new(2);
+    assert_eq!(budget.limit(), 2);
+    assert_eq!(budget.in_flight(), 0);
+
+    let (s, r) = budget.unbounded();
+    s.send(1).unwrap();
+    assert_eq!(budget.in_flight(), 1);
+    assert_eq!(s.len(), 1);
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(budget.in_flight(), 0);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+#[should_panic(expected = "a budget needs a nonzero limit")]
+fn zero_limit() {
+    Budget::new(0);
+}
+
+#[test]
+fn shared_limit() {
+    let budget = Budget::new(3);
+    let (s1, r1) = budget.unbounded();
+    let (s2, r2) = budget.bounded(2);
+
+    s1.try_send(1).unwrap();
+    s2.try_send(2).unwrap();
+    s2.try_send(3).unwrap();
+    assert_eq!(s1.try_send(4), Err(TrySendError::Full(4)));
+    assert_eq!(
+        s2.send_timeout(5, ms(50)),
+        Err(SendTimeoutError::Timeout(5))
+    );
+    assert_eq!(budget.in_flight(), 3);
+
+    assert_eq!(r2.recv(), Ok(2));
+    assert_eq!(s1.try_send(4), Ok(()));
+    assert_eq!(r1.recv(), Ok(1));
+    assert_eq!(r1.recv(), Ok(4));
+    assert_eq!(r2.recv(), Ok(3));
+    assert_eq!(budget.in_flight(), 0);
+}
+
+#[test]
+fn channel_full() {
+    let budget = Budget::new(10);
+    let (s, r) = budget.bounded(1);
+
+    s.send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    // A failed send gives its unit back.
+    assert_eq!(budget.in_flight(), 1);
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn send_blocks_on_budget() {
+    let budget = Budget::new(1);
+    let (s1, r1) = budget.unbounded();
+    let (s2, r2) = budget.unbounded();
+    s1.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            assert_eq!(r1.recv(), Ok(1));
+        });
+
+        let start = Instant::now();
+        s2.send(2).unwrap();
+        assert!(start.elapsed() >= ms(100));
+    })
+    .unwrap();
+
+    assert_eq!(r2.recv(), Ok(2));
+}
+
+#[test]
+fn disconnected() {
+    let budget = Budget::new(1);
+    let (s1, _r1) = budget.unbounded();
+    let (s2, r2) = budget.unbounded();
+    s1.send(1).unwrap();
+
+    // A sender waiting for the budget notices that its receivers are gone.
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(200));
+            drop(r2);
+        });
+
+        assert_eq!(s2.send(2), Err(SendError(2)));
+    })
+    .unwrap();
+    assert_eq!(s2.try_send(3), Err(TrySendError::Disconnected(3)));
+    assert_eq!(budget.in_flight(), 1);
+}
+
+#[test]
+fn dropped_messages_give_back() {
+    let budget = Budget::new(2);
+    let (s, r) = budget.unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(budget.in_flight(), 2);
+
+    drop(s);
+    drop(r);
+    assert_eq!(budget.in_flight(), 0);
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+    const CHANNELS: usize = 4;
+    const LIMIT: usize = 5;
+
+    let budget = Budget::new(LIMIT);
+    let channels = (0..CHANNELS).map(|i| budget.bounded(i)).collect::<Vec<_>>();
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for (s, r) in &channels {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+            let received = &received;
+            let budget = &budget;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    assert!(budget.in_flight() <= LIMIT);
+                    assert_eq!(r.recv(), Ok(i));
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), COUNT * CHANNELS);
+    assert_eq!(budget.in_flight(), 0);
+}
@@ -0,0 +1,155 @@
+//! Tests for buffered senders and flushers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::buffered::{BufferedSender, Flusher};
+use crossbeam_channel::{bounded, unbounded, SendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let s = BufferedSender::new(s, 3);
+    assert_eq!(s.capacity(), 3);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(s.len(), 2);
+    assert!(r.is_empty());
+
+    s.send(3).unwrap();
+    assert!(s.is_empty());
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+    s.send(4).unwrap();
+    s.flush().unwrap();
+    assert_eq!(r.try_recv(), Ok(4));
+}
+
+#[test]
+fn zero_capacity() {
+    let (s, r) = unbounded();
+    let s = BufferedSender::new(s, 0);
+    s.send(1).unwrap();
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn flush_on_drop() {
+    let (s, r) = unbounded();
+    let s = BufferedSender::new(s, 10);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded();
+    let s = BufferedSender::new(s, 3);
+    s.send(1).unwrap();
+    drop(r);
+
+    s.send(2).unwrap();
+    assert_eq!(s.send(3), Err(SendError(vec![1, 2, 3])));
+    assert!(s.is_empty());
+}
+
+#[test]
+fn clones_have_own_buffers() {
+    let (s, r) = unbounded();
+    let s1 = BufferedSender::new(s, 2);
+    let s2 = s1.clone();
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    assert_eq!((s1.len(), s2.len()), (1, 1));
+
+    s2.send(3).unwrap();
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn flusher() {
+    let (s, r) = unbounded();
+    let flusher = Flusher::new(ms(50));
+    assert_eq!(flusher.max_latency(), ms(50));
+
+    let s = BufferedSender::with_flusher(s, 100, &flusher);
+    let start = Instant::now();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(1));
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(2));
+    assert!(start.elapsed() >= ms(50));
+    assert!(s.is_empty());
+}
+
+#[test]
+fn flusher_shared() {
+    let (s, r) = unbounded();
+    let flusher = Flusher::new(ms(20));
+
+    scope(|scope| {
+        for t in 0..4 {
+            let s = BufferedSender::with_flusher(s.clone(), 1000, &flusher);
+            scope.spawn(move |_| {
+                s.send(t).unwrap();
+                // Go quiet in the middle of a batch.
+                thread::sleep(ms(300));
+                assert!(s.is_empty());
+            });
+        }
+    })
+    .unwrap();
+
+    let mut received = r.try_iter().collect::<Vec<_>>();
+    received.sort();
+    assert_eq!(received, [0, 1, 2, 3]);
+}
+
+#[test]
+fn flusher_full_channel() {
+    let (s, r) = bounded(1);
+    let flusher = Flusher::new(ms(10));
+    let s = BufferedSender::with_flusher(s, 100, &flusher);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    // The flusher doesn't block, so the second message stays buffered until there's room.
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(1));
+    thread::sleep(ms(100));
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(2));
+}
+
+#[test]
+fn sender_count() {
+    let (s, _r) = unbounded::<i32>();
+    let flusher = Flusher::new(ms(10));
+
+    let s1 = BufferedSender::with_flusher(s.clone(), 10, &flusher);
+    let s2 = s1.clone();
+    assert_eq!(flusher.sender_count(), 2);
+
+    drop(s1);
+    assert_eq!(flusher.sender_count(), 1);
+
+    // Senders keep working after the flusher is gone.
+    drop(flusher);
+    s2.send(1).unwrap();
+    assert_eq!(s2.len(), 1);
+}
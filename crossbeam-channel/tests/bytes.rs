@@ -0,0 +1,131 @@
+//! Tests for byte stream adapters.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::io::{BufRead, ErrorKind, Read, Write};
+
+use crossbeam_channel::{bounded, unbounded, ByteReceiver, ByteSender};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let mut writer = ByteSender::new(s);
+    let mut reader = ByteReceiver::new(r);
+
+    writer.write_all(b"hello").unwrap();
+    writer.flush().unwrap();
+
+    let mut buf = [0; 5];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    drop(writer);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn flush_threshold() {
+    let (s, r) = unbounded();
+    let mut writer = ByteSender::with_flush_threshold(s, 4);
+
+    writer.write_all(b"abc").unwrap();
+    assert!(r.is_empty());
+    writer.write_all(b"defgh").unwrap();
+    assert_eq!(r.try_recv(), Ok(b"abcdefgh".to_vec()));
+
+    writer.write_all(b"ij").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(r.try_recv(), Ok(b"ij".to_vec()));
+
+    // Flushing an empty buffer doesn't send anything.
+    writer.flush().unwrap();
+    assert!(r.is_empty());
+
+    // Remaining bytes are sent on drop.
+    writer.write_all(b"k").unwrap();
+    drop(writer);
+    assert_eq!(r.try_recv(), Ok(b"k".to_vec()));
+}
+
+#[test]
+fn zero_threshold() {
+    let (s, r) = unbounded();
+    let mut writer = ByteSender::with_flush_threshold(s, 0);
+
+    writer.write_all(b"a").unwrap();
+    writer.write_all(b"bc").unwrap();
+    assert_eq!(r.try_recv(), Ok(b"a".to_vec()));
+    assert_eq!(r.try_recv(), Ok(b"bc".to_vec()));
+}
+
+#[test]
+fn carry_over() {
+    let (s, r) = unbounded();
+    s.send(b"abcdef".to_vec()).unwrap();
+    s.send(Vec::new()).unwrap();
+    s.send(b"gh".to_vec()).unwrap();
+    drop(s);
+
+    let mut reader = ByteReceiver::new(r);
+    let mut buf = [0; 4];
+    assert_eq!(reader.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"abcd");
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"ef");
+    assert_eq!(reader.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"gh");
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn buf_read() {
+    let (s, r) = unbounded();
+    s.send(b"one\ntw".to_vec()).unwrap();
+    s.send(b"o\nthree".to_vec()).unwrap();
+    drop(s);
+
+    let reader = ByteReceiver::new(r);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+    assert_eq!(lines, ["one", "two", "three"]);
+}
+
+#[test]
+fn broken_pipe() {
+    let (s, r) = unbounded();
+    let mut writer = ByteSender::with_flush_threshold(s, 4);
+    drop(r);
+
+    writer.write_all(b"ab").unwrap();
+    assert_eq!(
+        writer.write(b"cd").unwrap_err().kind(),
+        ErrorKind::BrokenPipe
+    );
+    assert_eq!(writer.flush().unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn pipe() {
+    const COUNT: usize = 100_000;
+
+    let (s, r) = bounded(4);
+    let mut writer = ByteSender::with_flush_threshold(s, 1000);
+    let mut reader = ByteReceiver::new(r);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                writer.write_all(&[i as u8]).unwrap();
+            }
+        });
+
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data.len(), COUNT);
+        for (i, &b) in data.iter().enumerate() {
+            assert_eq!(b, i as u8);
+        }
+    })
+    .unwrap();
+}
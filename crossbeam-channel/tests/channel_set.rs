@@ -0,0 +1,102 @@
+//! Tests for the `channel_set!` macro.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, RecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+channel_set! {
+    /// Three inputs of different message types.
+    struct Inputs {
+        numbers: i32 => Number,
+        words: &'static str => Word,
+        stop: () => Stop,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Input;
+}
+
+channel_set! {
+    pub struct Single {
+        only: u8 => Only,
+    }
+
+    pub enum SingleInput;
+}
+
+#[test]
+fn ready() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (_s3, r3) = unbounded();
+    let inputs = Inputs {
+        numbers: r1,
+        words: r2,
+        stop: r3,
+    };
+
+    s2.send("hello").unwrap();
+    assert_eq!(inputs.select_all(), Input::Word(Ok("hello")));
+
+    s1.send(1).unwrap();
+    assert_eq!(inputs.select_all(), Input::Number(Ok(1)));
+}
+
+#[test]
+fn disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+    let inputs = Inputs {
+        numbers: r1,
+        words: r2,
+        stop: r3,
+    };
+
+    drop(s3);
+    assert_eq!(inputs.select_all(), Input::Stop(Err(RecvError)));
+    drop(s1);
+}
+
+#[test]
+fn blocking() {
+    let (s1, r1) = bounded(0);
+    let (_s2, r2) = unbounded();
+    let (_s3, r3) = unbounded();
+    let inputs = Inputs {
+        numbers: r1,
+        words: r2,
+        stop: r3,
+    };
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s1.send(5).unwrap();
+        });
+
+        assert_eq!(inputs.select_all(), Input::Number(Ok(5)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn single() {
+    let (s, r) = unbounded();
+    let single = Single { only: r };
+    s.send(3).unwrap();
+
+    match single.select_all() {
+        SingleInput::Only(msg) => assert_eq!(msg, Ok(3)),
+    }
+}
@@ -0,0 +1,94 @@
+//! Tests for timer channels with a chosen clock.
+
+#[macro_use]
+extern crate crossbeam_channel;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after_with_clock, tick_with_clock, ClockSource, TimerClock};
+use crossbeam_channel::{RecvTimeoutError, TryRecvError};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn supported() {
+    assert!(ClockSource::Monotonic.is_supported());
+    if cfg!(all(
+        feature = "boottime",
+        any(target_os = "linux", target_os = "android")
+    )) {
+        assert!(ClockSource::Boottime.is_supported());
+    }
+    assert_eq!(ClockSource::default(), ClockSource::Monotonic);
+    assert_eq!(TimerClock::default().source(), ClockSource::Monotonic);
+}
+
+#[test]
+fn after_fires() {
+    for &source in &[ClockSource::Monotonic, ClockSource::Boottime] {
+        let start = Instant::now();
+        let r = after_with_clock(ms(50), source);
+
+        assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+        let fired = r.recv().unwrap();
+        assert!(fired - start >= ms(50));
+        assert!(Instant::now() - start < ms(1000));
+
+        assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    }
+}
+
+#[test]
+fn after_in_select() {
+    let start = Instant::now();
+    let r = after_with_clock(ms(1500), ClockSource::Boottime);
+
+    // Waits longer than the interval at which suspensions are checked.
+    select! {
+        recv(r) -> msg => assert!(msg.is_ok()),
+    }
+
+    let elapsed = Instant::now() - start;
+    assert!(elapsed >= ms(1500));
+    assert!(elapsed < ms(2500));
+}
+
+#[test]
+fn tick_fires() {
+    let start = Instant::now();
+    let r = tick_with_clock(ms(50), ClockSource::Boottime);
+
+    for i in 1..4 {
+        r.recv().unwrap();
+        assert!(Instant::now() - start >= ms(50 * i));
+    }
+    assert!(Instant::now() - start < ms(1000));
+}
+
+#[test]
+fn no_resume_without_suspend() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let clock = {
+        let calls = calls.clone();
+        TimerClock::new(ClockSource::Monotonic).on_resume(move |_, remaining| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            remaining
+        })
+    };
+    assert_eq!(clock.source(), ClockSource::Monotonic);
+
+    let r = after_with_clock(ms(100), clock.clone());
+    let t = tick_with_clock(ms(20), clock);
+    for _ in 0..3 {
+        t.recv().unwrap();
+    }
+    r.recv().unwrap();
+    thread::sleep(ms(10));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
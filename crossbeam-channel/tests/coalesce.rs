@@ -0,0 +1,244 @@
+//! Tests for coalescing channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{coalesce, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = coalesce::channel();
+    s.send(1, 'a').unwrap();
+    assert_eq!(r.try_recv(), Ok((1, 'a')));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn capacity() {
+    let (s, r) = coalesce::channel::<i32, ()>();
+    assert_eq!(r.capacity(), None);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn replace() {
+    let (s, r) = coalesce::channel();
+    for i in 0..10 {
+        s.send("a", i).unwrap();
+        s.send("b", i * 10).unwrap();
+    }
+    assert_eq!(s.len(), 2);
+    assert_eq!(r.len(), 2);
+
+    assert_eq!(r.recv(), Ok(("a", 9)));
+    assert_eq!(r.recv(), Ok(("b", 90)));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn keeps_position() {
+    let (s, r) = coalesce::channel();
+    s.send(1, 0).unwrap();
+    s.send(2, 0).unwrap();
+    s.send(3, 0).unwrap();
+    s.send(1, 1).unwrap();
+    s.send(3, 1).unwrap();
+
+    assert_eq!(r.recv(), Ok((1, 1)));
+    assert_eq!(r.recv(), Ok((2, 0)));
+    assert_eq!(r.recv(), Ok((3, 1)));
+}
+
+#[test]
+fn received_are_not_merged() {
+    let (s, r) = coalesce::channel();
+    s.send(1, 'a').unwrap();
+    s.send(2, 'b').unwrap();
+    assert_eq!(r.recv(), Ok((1, 'a')));
+
+    s.send(1, 'c').unwrap();
+    s.send(2, 'd').unwrap();
+    assert_eq!(r.recv(), Ok((2, 'd')));
+    assert_eq!(r.recv(), Ok((1, 'c')));
+}
+
+#[test]
+fn custom_merge() {
+    let (s, r) = coalesce::with_merge(|mut old: Vec<i32>, new| {
+        old.extend(new);
+        old
+    });
+    s.send('a', vec![1]).unwrap();
+    s.send('a', vec![2, 3]).unwrap();
+    s.send('b', vec![4]).unwrap();
+    assert_eq!(r.recv(), Ok(('a', vec![1, 2, 3])));
+
+    s.send('a', vec![5]).unwrap();
+    s.send('b', vec![6]).unwrap();
+    assert_eq!(r.recv(), Ok(('b', vec![4, 6])));
+    assert_eq!(r.recv(), Ok(('a', vec![5])));
+}
+
+#[test]
+fn merge_keeps_original_key() {
+    #[derive(Clone, Debug)]
+    struct Key(i32, &'static str);
+
+    impl PartialEq for Key {
+        fn eq(&self, other: &Key) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for Key {}
+    impl std::hash::Hash for Key {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    let (s, r) = coalesce::channel();
+    s.send(Key(1, "first"), 1).unwrap();
+    s.send(Key(1, "second"), 2).unwrap();
+
+    let (key, value) = r.recv().unwrap();
+    assert_eq!(key.1, "first");
+    assert_eq!(value, 2);
+}
+
+#[test]
+fn many_keys() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = coalesce::channel();
+    for round in 0..3 {
+        for i in 0..COUNT {
+            s.send(i, round).unwrap();
+        }
+        assert_eq!(s.len(), COUNT);
+        for i in 0..COUNT / 2 {
+            assert_eq!(r.recv(), Ok((i, round)));
+        }
+        for i in COUNT / 2..COUNT {
+            assert_eq!(r.recv(), Ok((i, round)));
+        }
+    }
+}
+
+#[test]
+fn recv() {
+    let (s, r) = coalesce::channel();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Ok((1, 7)));
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok((2, 9)));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(1, 7).unwrap();
+            s.send(2, 8).unwrap();
+            s.send(2, 9).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = coalesce::channel();
+    let s2 = s.clone();
+    s.send(1, 'a').unwrap();
+    drop(s);
+    s2.send(1, 'b').unwrap();
+    drop(s2);
+
+    assert_eq!(r.recv(), Ok((1, 'b')));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (s, r) = coalesce::channel();
+    drop(r);
+    assert_eq!(s.send(1, 'a'), Err(SendError((1, 'a'))));
+}
+
+#[test]
+fn select() {
+    let (s, r) = coalesce::channel();
+    let (_s2, r2) = crossbeam_channel::unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send("x", 1).unwrap();
+            s.send("x", 2).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => {
+                let msg = msg.unwrap();
+                assert_eq!(msg.0, "x");
+            }
+            recv(r2) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+
+    // The second message may or may not have been merged into the first one.
+    while r.try_recv().is_ok() {}
+
+    let mut sel = Select::new();
+    let i = sel.recv(&r);
+    s.send("y", 3).unwrap();
+    s.send("y", 4).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), i);
+    assert_eq!(oper.recv(&r), Ok(("y", 4)));
+
+    drop(s);
+    let oper = sel.select();
+    assert_eq!(oper.recv(&r), Err(RecvError));
+}
+
+#[test]
+fn stress() {
+    const COUNT: usize = 10_000;
+    const KEYS: usize = 10;
+
+    let (s, r) = coalesce::with_merge(|old: usize, new| old + new);
+
+    scope(|scope| {
+        for _ in 0..4 {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i % KEYS, 1).unwrap();
+                }
+            });
+        }
+        drop(s);
+
+        scope.spawn(move |_| {
+            let mut total = 0;
+            for (key, count) in r.iter() {
+                assert!(key < KEYS);
+                assert!(r.len() <= KEYS);
+                total += count;
+            }
+            assert_eq!(total, 4 * COUNT);
+        });
+    })
+    .unwrap();
+}
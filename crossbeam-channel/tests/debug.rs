@@ -0,0 +1,136 @@
+//! Tests for the registry of blocked threads.
+
+#![cfg(feature = "debug-waiters")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::debug::{self, OperationKind, Waiter};
+use crossbeam_channel::{bounded, unbounded, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Returns the description of the named thread, if it is blocked.
+fn find(name: &str) -> Option<Waiter> {
+    debug::waiters()
+        .into_iter()
+        .find(|w| w.thread().name() == Some(name))
+}
+
+#[test]
+fn recv() {
+    let (s, r) = unbounded::<i32>();
+
+    let t = thread::Builder::new()
+        .name("debug::recv".to_string())
+        .spawn(move || r.recv().unwrap())
+        .unwrap();
+
+    thread::sleep(ms(500));
+    let waiter = find("debug::recv").unwrap();
+    assert_eq!(waiter.kind(), OperationKind::Recv);
+    assert!(waiter.channel().is_some());
+    assert_eq!(waiter.deadline(), None);
+    assert!(waiter.since() <= Instant::now());
+
+    s.send(1).unwrap();
+    assert_eq!(t.join().unwrap(), 1);
+    assert!(find("debug::recv").is_none());
+}
+
+#[test]
+fn send_with_deadline() {
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope
+            .builder()
+            .name("debug::send".to_string())
+            .spawn(|_| s.send_timeout(1, ms(5000)).unwrap())
+            .unwrap();
+
+        thread::sleep(ms(500));
+        let waiter = find("debug::send").unwrap();
+        assert_eq!(waiter.kind(), OperationKind::Send);
+        assert!(waiter.deadline().is_some());
+
+        let text = waiter.to_string();
+        assert!(text.starts_with("thread 'debug::send': send on channel 0x"));
+        assert!(text.contains("deadline in"));
+
+        assert_eq!(r.recv(), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn same_channel() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        for name in &["debug::same1", "debug::same2"] {
+            scope
+                .builder()
+                .name(name.to_string())
+                .spawn(|_| r.recv().unwrap())
+                .unwrap();
+        }
+
+        thread::sleep(ms(500));
+        let a = find("debug::same1").unwrap();
+        let b = find("debug::same2").unwrap();
+        assert_eq!(a.channel(), b.channel());
+        assert!(a.channel().is_some());
+
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope
+            .builder()
+            .name("debug::select".to_string())
+            .spawn(|_| {
+                let mut sel = Select::new();
+                sel.recv(&r1);
+                sel.recv(&r2);
+                let oper = sel.select();
+                assert_eq!(oper.recv(&r1), Ok(1));
+            })
+            .unwrap();
+
+        scope
+            .builder()
+            .name("debug::ready".to_string())
+            .spawn(|_| {
+                let mut sel = Select::new();
+                sel.recv(&r2);
+                sel.ready_timeout(ms(1000)).unwrap_err();
+            })
+            .unwrap();
+
+        thread::sleep(ms(500));
+        let waiter = find("debug::select").unwrap();
+        assert_eq!(waiter.kind(), OperationKind::Select);
+        assert_eq!(waiter.channel(), None);
+        let waiter = find("debug::ready").unwrap();
+        assert_eq!(waiter.kind(), OperationKind::Ready);
+
+        debug::dump_waiters();
+        s1.send(1).unwrap();
+    })
+    .unwrap();
+}
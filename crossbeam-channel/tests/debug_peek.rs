@@ -0,0 +1,113 @@
+//! Tests for peeking into unbounded channels.
+
+#![cfg(feature = "debug-peek")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crossbeam_channel::{bounded, never, unbounded};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    assert_eq!(r.debug_peek(10), Some(vec![]));
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.debug_peek(0), Some(vec![]));
+    assert_eq!(r.debug_peek(1), Some(vec![1]));
+    assert_eq!(r.debug_peek(10), Some(vec![1, 2]));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.debug_peek(10), Some(vec![2]));
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.debug_peek(10), Some(vec![]));
+}
+
+#[test]
+fn other_flavors() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+    assert_eq!(r.debug_peek(1), None);
+
+    let (_s, r) = bounded::<i32>(0);
+    assert_eq!(r.debug_peek(1), None);
+
+    assert_eq!(never::<i32>().debug_peek(1), None);
+}
+
+#[test]
+fn across_blocks() {
+    let (s, r) = unbounded();
+    for i in 0..100 {
+        s.send(i.to_string()).unwrap();
+    }
+
+    for start in 0..100 {
+        let expected = (start..100.min(start + 40))
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(r.debug_peek(40), Some(expected));
+        assert_eq!(r.recv(), Ok(start.to_string()));
+    }
+    assert_eq!(r.debug_peek(40), Some(vec![]));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    assert_eq!(r.debug_peek(10), Some(vec![1, 2]));
+    assert_eq!(r.iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(r.debug_peek(10), Some(vec![]));
+}
+
+#[test]
+fn concurrent_receivers() {
+    const COUNT: usize = 50_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = unbounded();
+    let received = AtomicUsize::new(0);
+    let done = AtomicBool::new(false);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                // Boxed so that a message dropped while being cloned would be noticed.
+                s.send(Box::new(i)).unwrap();
+            }
+        });
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let mut last = None;
+                while received.load(Ordering::SeqCst) < COUNT {
+                    if let Ok(msg) = r.try_recv() {
+                        assert!(last < Some(*msg));
+                        last = Some(*msg);
+                        received.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+                done.store(true, Ordering::SeqCst);
+            });
+        }
+
+        while !done.load(Ordering::SeqCst) {
+            let msgs = r.debug_peek(100).unwrap();
+            for w in msgs.windows(2) {
+                assert_eq!(*w[0] + 1, *w[1]);
+            }
+        }
+    })
+    .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), COUNT);
+    assert_eq!(r.debug_peek(10), Some(vec![]));
+}
@@ -0,0 +1,131 @@
+//! Tests for deduplicating channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::dedup;
+use crossbeam_channel::{RecvError, SendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn count_window() {
+    let (s, r) = dedup::count_window(3);
+
+    for &key in &[1, 2, 1, 3, 2, 4, 1, 1] {
+        s.send(key, ()).unwrap();
+    }
+
+    let keys: Vec<_> = r.try_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, [1, 2, 3, 4, 1]);
+}
+
+#[test]
+fn zero_window() {
+    let (s, r) = dedup::count_window(0);
+    for _ in 0..5 {
+        assert_eq!(s.send("k", ()), Ok(true));
+    }
+    assert_eq!(r.len(), 5);
+}
+
+#[test]
+fn time_window() {
+    let (s, r) = dedup::time_window(ms(200));
+
+    assert_eq!(s.send("a", 1), Ok(true));
+    thread::sleep(ms(100));
+    assert_eq!(s.send("a", 2), Ok(false));
+    assert_eq!(s.send("b", 3), Ok(true));
+
+    // Dropped duplicates don't extend the window.
+    thread::sleep(ms(150));
+    assert_eq!(s.send("a", 4), Ok(true));
+    assert_eq!(s.send("b", 5), Ok(false));
+
+    let received: Vec<_> = r.try_iter().collect();
+    assert_eq!(received, [("a", 1), ("b", 3), ("a", 4)]);
+}
+
+#[test]
+fn received_messages_still_count() {
+    let (s, r) = dedup::count_window(4);
+    s.send(1, 'a').unwrap();
+    assert_eq!(r.recv(), Ok((1, 'a')));
+    assert_eq!(s.send(1, 'b'), Ok(false));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = dedup::count_window(4);
+    s.send(1, 'a').unwrap();
+    drop(s);
+    assert_eq!(r.recv(), Ok((1, 'a')));
+    assert_eq!(r.recv(), Err(RecvError));
+
+    let (s, r) = dedup::count_window(4);
+    drop(r);
+    assert_eq!(s.send(1, 'a'), Err(SendError((1, 'a'))));
+
+    // A message that wasn't sent doesn't make its key a duplicate.
+    let (s, r) = dedup::count_window(4);
+    let s2 = s.clone();
+    drop(r);
+    assert!(s.send(1, 'a').is_err());
+    assert!(s2.send(1, 'a').is_err());
+}
+
+#[test]
+fn select() {
+    let (s, r) = dedup::count_window(4);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send("ping", 1).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(("ping", 1))),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn concurrent_senders() {
+    const THREADS: usize = 4;
+    const KEYS: usize = 100;
+
+    let (s, r) = dedup::count_window(KEYS);
+    let sent = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for key in 0..KEYS {
+                    if s.send(key, ()).unwrap() {
+                        sent.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // Every key got through exactly once.
+    let keys: HashSet<_> = r.try_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys.len(), KEYS);
+    assert_eq!(sent.load(Ordering::SeqCst), KEYS);
+}
@@ -0,0 +1,119 @@
+//! Tests for signals about disconnected receivers.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded, RecvError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    for &cap in &[0, 1, 5] {
+        let (s, r) = bounded::<i32>(cap);
+        let gone = s.disconnected();
+        assert!(!gone.is_set());
+
+        drop(r);
+        assert!(gone.is_set());
+        assert_eq!(gone.recv(), Err(RecvError));
+    }
+}
+
+#[test]
+fn already_disconnected() {
+    let (s, r) = unbounded::<i32>();
+    drop(r);
+
+    let gone = s.disconnected();
+    assert!(gone.is_set());
+    assert!(gone.wait_timeout(ms(0)));
+}
+
+#[test]
+fn last_receiver() {
+    let (s, r1) = unbounded::<i32>();
+    let r2 = r1.clone();
+    let gone = s.disconnected();
+
+    drop(r1);
+    assert!(!gone.is_set());
+    drop(r2);
+    assert!(gone.is_set());
+}
+
+#[test]
+fn multiple_signals() {
+    let (s, r) = unbounded::<i32>();
+    let s2 = s.clone();
+    let signals = [s.disconnected(), s.disconnected(), s2.disconnected()];
+    let clone = signals[0].clone();
+
+    // Dropped signals don't interfere with the others.
+    drop(s.disconnected());
+
+    drop(r);
+    assert!(signals.iter().all(|g| g.is_set()));
+    assert!(clone.is_set());
+}
+
+#[test]
+fn senders_dropped_first() {
+    // Signals don't keep the channel connected for receivers.
+    let (s, r) = unbounded::<i32>();
+    let gone = s.disconnected();
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert!(!gone.is_set());
+
+    drop(r);
+    assert!(gone.is_set());
+}
+
+#[test]
+fn wait() {
+    let (s, r) = bounded::<i32>(0);
+    let gone = s.disconnected();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(r);
+        });
+
+        let start = Instant::now();
+        assert!(!gone.wait_timeout(ms(50)));
+        gone.wait();
+        assert!(start.elapsed() >= ms(100));
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s, r) = unbounded::<i32>();
+    let (_s2, r2) = unbounded::<i32>();
+    let gone = s.disconnected();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(r);
+        });
+
+        select! {
+            recv(gone) -> res => assert_eq!(res, Err(RecvError)),
+            recv(r2) -> _ => panic!(),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+}
@@ -0,0 +1,184 @@
+//! Tests for elastic bounded channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{elastic_bounded, Pressure};
+use crossbeam_channel::{SendError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = elastic_bounded(1, 2);
+    assert_eq!(s.send(7), Ok(Pressure::Normal));
+    assert_eq!(r.try_recv(), Ok(7));
+    assert!(s.is_empty());
+}
+
+#[test]
+fn capacities() {
+    for i in 1..10 {
+        let (s, r) = elastic_bounded::<()>(i / 2, i);
+        assert_eq!(s.soft_capacity(), i / 2);
+        assert_eq!(s.hard_capacity(), i);
+        assert_eq!(r.capacity(), Some(i));
+    }
+}
+
+#[test]
+#[should_panic]
+fn soft_above_hard() {
+    elastic_bounded::<()>(3, 2);
+}
+
+#[test]
+fn soft_and_hard() {
+    let (s, r) = elastic_bounded(2, 4);
+    assert_eq!(s.try_send(1), Ok(Pressure::Normal));
+    assert_eq!(s.try_send(2), Ok(Pressure::Normal));
+    assert_eq!(s.try_send(3), Ok(Pressure::Backpressured));
+    assert_eq!(s.try_send(4), Ok(Pressure::Backpressured));
+    assert_eq!(s.try_send(5), Err(TrySendError::Full(5)));
+    assert_eq!(s.len(), 4);
+
+    for i in 1..=3 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert_eq!(s.try_send(5), Ok(Pressure::Normal));
+}
+
+#[test]
+fn blocks_at_hard_cap() {
+    let (s, r) = elastic_bounded(0, 1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(s.send(1), Ok(Pressure::Backpressured));
+            let now = Instant::now();
+            assert_eq!(s.send(2), Ok(Pressure::Backpressured));
+            assert!(now.elapsed() >= ms(500));
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(1000));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn burst() {
+    let (s, r) = elastic_bounded(1, 4);
+    assert!(!s.is_bursting());
+
+    s.borrow_burst();
+    assert!(s.is_bursting());
+    assert_eq!(s.send(1), Ok(Pressure::Normal));
+    assert_eq!(s.send(2), Ok(Pressure::Burst));
+    assert_eq!(s.send(3), Ok(Pressure::Burst));
+    assert!(s.is_bursting());
+
+    // Partially draining the channel doesn't end the burst.
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s.send(4), Ok(Pressure::Burst));
+    assert!(s.is_bursting());
+
+    // Draining down to the soft cap does.
+    for i in 2..=4 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert_eq!(s.send(5), Ok(Pressure::Normal));
+    assert!(!s.is_bursting());
+    assert_eq!(s.send(6), Ok(Pressure::Backpressured));
+}
+
+#[test]
+fn burst_still_blocks_at_hard_cap() {
+    let (s, _r) = elastic_bounded(0, 2);
+    s.borrow_burst();
+    assert_eq!(s.try_send(1), Ok(Pressure::Burst));
+    assert_eq!(s.try_send(2), Ok(Pressure::Burst));
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+}
+
+#[test]
+fn burst_is_shared() {
+    let (s1, r) = elastic_bounded(0, 4);
+    let s2 = s1.clone();
+
+    s1.borrow_burst();
+    assert!(s2.is_bursting());
+    assert_eq!(s2.send(1), Ok(Pressure::Burst));
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s2.send(2), Ok(Pressure::Backpressured));
+    assert!(!s1.is_bursting());
+}
+
+#[test]
+fn slowdown() {
+    let (mut s, r) = elastic_bounded(1, 4);
+    s.set_slowdown(Some(ms(500)));
+
+    let now = Instant::now();
+    assert_eq!(s.send(1), Ok(Pressure::Normal));
+    assert!(now.elapsed() < ms(500));
+
+    let now = Instant::now();
+    assert_eq!(s.send(2), Ok(Pressure::Backpressured));
+    assert!(now.elapsed() >= ms(500));
+
+    // Non-blocking sends never sleep.
+    let now = Instant::now();
+    assert_eq!(s.try_send(3), Ok(Pressure::Backpressured));
+    assert!(now.elapsed() < ms(500));
+
+    // Bursts aren't slowed down.
+    s.borrow_burst();
+    let now = Instant::now();
+    assert_eq!(s.send(4), Ok(Pressure::Burst));
+    assert!(now.elapsed() < ms(500));
+
+    // Clones inherit the slowdown, but changing it only affects one sender.
+    let mut s2 = s.clone();
+    s2.set_slowdown(None);
+    for i in 1..=4 {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert_eq!(s2.send(5), Ok(Pressure::Normal));
+    let now = Instant::now();
+    assert_eq!(s2.send(6), Ok(Pressure::Backpressured));
+    assert!(now.elapsed() < ms(500));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = elastic_bounded(1, 2);
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+
+    let (s, r) = elastic_bounded(1, 2);
+    let s2 = s.clone();
+    s.send(1).unwrap();
+    drop(s);
+    assert_eq!(r.recv(), Ok(1));
+    drop(s2);
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn as_sender() {
+    let (s, r) = elastic_bounded(0, 1);
+    s.as_sender().send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.recv(), Ok(1));
+}
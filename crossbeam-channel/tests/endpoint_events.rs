@@ -0,0 +1,131 @@
+//! Tests for endpoint events.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use crossbeam_channel::{bounded, never, tick, unbounded, EndpointEvent, TryRecvError};
+use crossbeam_utils::thread::scope;
+use std::time::Duration;
+
+use EndpointEvent::*;
+
+#[test]
+fn senders() {
+    let (s, r) = bounded::<i32>(1);
+    let events = r.endpoint_events();
+
+    let s2 = s.clone();
+    let s3 = s2.clone();
+    drop(s);
+    drop(s3);
+    drop(s2);
+
+    let got: Vec<_> = events.try_iter().collect();
+    assert_eq!(
+        got,
+        [
+            SenderAttached { senders: 2 },
+            SenderAttached { senders: 3 },
+            SenderDetached { senders: 2 },
+            SenderDetached { senders: 1 },
+            SenderDetached { senders: 0 },
+        ]
+    );
+}
+
+#[test]
+fn receivers() {
+    let (_s, r) = unbounded::<i32>();
+    let events = r.endpoint_events();
+
+    let r2 = r.clone();
+    drop(r);
+    assert_eq!(events.try_recv(), Ok(ReceiverAttached { receivers: 2 }));
+    assert_eq!(events.try_recv(), Ok(ReceiverDetached { receivers: 1 }));
+
+    drop(r2);
+    assert_eq!(events.try_recv(), Ok(ReceiverDetached { receivers: 0 }));
+}
+
+#[test]
+fn disconnected_when_destroyed() {
+    let (s, r) = bounded::<i32>(0);
+    let events = r.endpoint_events();
+
+    drop(s);
+    assert_eq!(events.try_recv(), Ok(SenderDetached { senders: 0 }));
+    assert_eq!(events.try_recv(), Err(TryRecvError::Empty));
+
+    drop(r);
+    assert_eq!(events.try_recv(), Ok(ReceiverDetached { receivers: 0 }));
+    assert_eq!(events.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn multiple_subscribers() {
+    let (s, r) = unbounded::<i32>();
+    let e1 = r.endpoint_events();
+    let e2 = r.endpoint_events();
+
+    let s2 = s.clone();
+    assert_eq!(e1.try_recv(), Ok(SenderAttached { senders: 2 }));
+    assert_eq!(e2.try_recv(), Ok(SenderAttached { senders: 2 }));
+
+    // A dropped subscriber doesn't affect the others.
+    drop(e1);
+    drop(s2);
+    assert_eq!(e2.try_recv(), Ok(SenderDetached { senders: 1 }));
+}
+
+#[test]
+fn no_senders() {
+    let r = never::<i32>();
+    assert_eq!(
+        r.endpoint_events().try_recv(),
+        Err(TryRecvError::Disconnected)
+    );
+
+    let r = tick(Duration::from_secs(1));
+    assert_eq!(
+        r.endpoint_events().try_recv(),
+        Err(TryRecvError::Disconnected)
+    );
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let (s, r) = unbounded::<i32>();
+    let events = r.endpoint_events();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    drop(s.clone());
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // Every event is reported, and each one is consistent with the one before it.
+    let mut senders = 1;
+    let mut total = 0;
+    for event in events.try_iter() {
+        match event {
+            SenderAttached { senders: n } => assert_eq!(n, senders + 1),
+            SenderDetached { senders: n } => assert_eq!(n, senders - 1),
+            _ => panic!(),
+        }
+        senders = match event {
+            SenderAttached { senders } | SenderDetached { senders } => senders,
+            _ => unreachable!(),
+        };
+        total += 1;
+    }
+    assert_eq!(senders, 1);
+    assert_eq!(total, 2 * THREADS * COUNT);
+}
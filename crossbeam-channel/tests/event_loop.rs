@@ -0,0 +1,265 @@
+//! Tests for the event loop.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded};
+use crossbeam_channel::{ErrorAction, EventLoop};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    s.send(7).unwrap();
+    drop(s);
+
+    let mut received = Vec::new();
+    {
+        let mut event_loop = EventLoop::<()>::new();
+        event_loop.on_recv(r, |msg| {
+            received.push(msg);
+            Ok(())
+        });
+        event_loop.run().unwrap();
+    }
+    assert_eq!(received, [7]);
+}
+
+#[test]
+fn no_handlers() {
+    let mut event_loop = EventLoop::<()>::new();
+    event_loop.run().unwrap();
+}
+
+#[test]
+fn removes_disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    let mut event_loop = EventLoop::<()>::new();
+    let id1 = event_loop.on_recv(r1, |_| Ok(()));
+    let id2 = event_loop.on_recv(r2, |_| Ok(()));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            drop(s1);
+            thread::sleep(ms(100));
+            s2.send(1).unwrap();
+            drop(s2);
+        });
+
+        event_loop.run().unwrap();
+    })
+    .unwrap();
+
+    assert!(!event_loop.is_registered(id1));
+    assert!(!event_loop.is_registered(id2));
+    assert_eq!(event_loop.metrics(id1).handled(), 0);
+    assert_eq!(event_loop.metrics(id2).handled(), 1);
+}
+
+#[test]
+fn many_handlers() {
+    const COUNT: usize = 1000;
+
+    let (s1, r1) = bounded(5);
+    let (s2, r2) = unbounded();
+
+    let mut sum1 = 0;
+    let mut sum2 = 0;
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s1.send(i).unwrap();
+            }
+        });
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s2.send(i * 2).unwrap();
+            }
+        });
+
+        let mut event_loop = EventLoop::<()>::new();
+        event_loop.on_recv(r1, |i| {
+            sum1 += i;
+            Ok(())
+        });
+        event_loop.on_recv(r2, |i| {
+            sum2 += i;
+            Ok(())
+        });
+        event_loop.run().unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(sum1, COUNT * (COUNT - 1) / 2);
+    assert_eq!(sum2, COUNT * (COUNT - 1));
+}
+
+#[test]
+fn ticker() {
+    let (s, r) = bounded(1);
+    let mut ticks = Vec::new();
+
+    let start = Instant::now();
+    {
+        let mut event_loop = EventLoop::<()>::new();
+        event_loop.on_tick(ms(50), |instant| {
+            ticks.push(instant);
+            if ticks.len() == 3 {
+                s.send(()).unwrap();
+            }
+            Ok(())
+        });
+        event_loop.shutdown_on(r);
+        event_loop.run().unwrap();
+    }
+
+    assert_eq!(ticks.len(), 3);
+    assert!(start.elapsed() >= ms(150));
+    for w in ticks.windows(2) {
+        assert!(w[1] - w[0] >= ms(50));
+    }
+}
+
+#[test]
+fn shutdown_drains_queued() {
+    let (s, r) = unbounded();
+    let (shutdown_s, shutdown_r) = bounded(1);
+
+    let mut received = Vec::new();
+    {
+        let mut event_loop = EventLoop::<()>::new();
+        event_loop.on_recv(r, |msg| {
+            received.push(msg);
+            Ok(())
+        });
+        event_loop.shutdown_on(shutdown_r);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                s.send(1).unwrap();
+                thread::sleep(ms(100));
+
+                // The loop is waiting, so these are queued together with the shutdown signal.
+                s.send(2).unwrap();
+                s.send(3).unwrap();
+                shutdown_s.send(()).unwrap();
+            });
+
+            event_loop.run().unwrap();
+        })
+        .unwrap();
+    }
+
+    // The sender is still alive, but everything queued before shutdown was handled.
+    assert_eq!(received, [1, 2, 3]);
+    drop(s);
+}
+
+#[test]
+fn shutdown_on_disconnect() {
+    let (s, r) = unbounded::<i32>();
+    let (shutdown_s, shutdown_r) = bounded::<()>(0);
+
+    let mut event_loop = EventLoop::<()>::new();
+    let id = event_loop.on_recv(r, |_| Ok(()));
+    event_loop.shutdown_on(shutdown_r);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            drop(shutdown_s);
+        });
+
+        let start = Instant::now();
+        event_loop.run().unwrap();
+        assert!(start.elapsed() >= ms(100));
+    })
+    .unwrap();
+
+    assert!(event_loop.is_registered(id));
+    drop(s);
+}
+
+#[test]
+fn stop_on_error() {
+    let (s, r) = unbounded();
+    for i in 0..5 {
+        s.send(i).unwrap();
+    }
+
+    let mut event_loop = EventLoop::new();
+    let id = event_loop.on_recv(r, |i| if i == 2 { Err("two") } else { Ok(()) });
+
+    assert_eq!(event_loop.run(), Err("two"));
+    assert_eq!(event_loop.metrics(id).handled(), 3);
+    assert_eq!(event_loop.metrics(id).errors(), 1);
+
+    // The loop can be resumed.
+    drop(s);
+    assert_eq!(event_loop.run(), Ok(()));
+    assert_eq!(event_loop.metrics(id).handled(), 5);
+}
+
+#[test]
+fn remove_on_error() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    for i in 0..3 {
+        s1.send(i).unwrap();
+        s2.send(i).unwrap();
+    }
+    drop(s1);
+    drop(s2);
+
+    let mut failed = Vec::new();
+    let failing = {
+        let mut event_loop = EventLoop::new();
+        let failing = event_loop.on_recv(r1, |_| Err(()));
+        let working = event_loop.on_recv(r2, |_| Ok(()));
+
+        event_loop.on_error(|id, _| {
+            failed.push(id);
+            ErrorAction::Remove
+        });
+        event_loop.run().unwrap();
+
+        assert!(!event_loop.is_registered(failing));
+        assert_eq!(event_loop.metrics(failing).handled(), 1);
+        assert_eq!(event_loop.metrics(working).handled(), 3);
+        failing
+    };
+    assert_eq!(failed, [failing]);
+}
+
+#[test]
+fn metrics() {
+    let (s, r) = unbounded();
+    s.send(ms(50)).unwrap();
+    s.send(ms(10)).unwrap();
+    drop(s);
+
+    let mut event_loop = EventLoop::<()>::new();
+    let id = event_loop.on_recv(r, |d| {
+        thread::sleep(d);
+        Ok(())
+    });
+    event_loop.run().unwrap();
+
+    let metrics = event_loop.metrics(id);
+    assert_eq!(metrics.handled(), 2);
+    assert_eq!(metrics.errors(), 0);
+    assert!(metrics.busy() >= ms(60));
+    assert!(metrics.max() >= ms(50));
+    assert!(metrics.max() < metrics.busy());
+}
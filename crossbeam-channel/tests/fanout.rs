@@ -0,0 +1,279 @@
+//! Tests for fanout channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_channel::{fanout, fanout_with_lag_limit};
+use crossbeam_channel::{RecvError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = fanout();
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn every_receiver_gets_a_copy() {
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+    let r3 = r2.clone();
+
+    for i in 0..10 {
+        s.send(i.to_string()).unwrap();
+    }
+    drop(s);
+
+    for r in &[r1, r2, r3] {
+        let v: Vec<String> = r.iter().collect();
+        assert_eq!(v, (0..10).map(|i| i.to_string()).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn late_clone() {
+    let (s, r1) = fanout();
+    s.send(1).unwrap();
+
+    let r2 = r1.clone();
+    s.send(2).unwrap();
+
+    assert_eq!(r1.try_iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(r2.try_iter().collect::<Vec<_>>(), [2]);
+}
+
+#[test]
+fn clone_after_disconnect() {
+    let (s, r1) = fanout::<i32>();
+    drop(s);
+
+    let r2 = r1.clone();
+    assert_eq!(r2.recv(), Err(RecvError));
+}
+
+#[test]
+fn dropped_receivers() {
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+    assert_eq!(s.receiver_count(), 2);
+
+    drop(r1);
+    s.send(1).unwrap();
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r2.recv(), Ok(1));
+
+    drop(r2);
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(s.receiver_count(), 0);
+}
+
+#[test]
+fn cloned_senders() {
+    let (s1, r) = fanout();
+    let s2 = s1.clone();
+
+    s1.send(1).unwrap();
+    drop(s1);
+    s2.send(2).unwrap();
+    drop(s2);
+
+    assert_eq!(r.iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn lag_limit() {
+    let (s, fast) = fanout_with_lag_limit(10);
+    let slow = fast.clone();
+
+    for i in 0..100 {
+        s.send(i).unwrap();
+        assert_eq!(fast.recv(), Ok(i));
+    }
+
+    assert!(slow.is_lagged());
+    assert!(!fast.is_lagged());
+    assert_eq!(slow.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    assert_eq!(s.receiver_count(), 1);
+}
+
+#[test]
+fn slow_receiver_does_not_stall() {
+    const COUNT: usize = 10_000;
+
+    let (s, fast) = fanout();
+    let slow = fast.clone();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                assert_eq!(fast.recv(), Ok(i));
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(slow.len(), COUNT);
+}
+
+#[test]
+fn select() {
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+
+    s.send(1).unwrap();
+
+    for r in &[r1, r2] {
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(1)),
+            default(ms(1000)) => panic!(),
+        }
+    }
+}
+
+#[test]
+fn send_shared() {
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+
+    let msg = Arc::new(String::from("hello"));
+    s.send_shared(&msg).unwrap();
+    assert_eq!(Arc::strong_count(&msg), 3);
+
+    for r in &[r1, r2] {
+        assert!(Arc::ptr_eq(&r.recv().unwrap(), &msg));
+    }
+    assert_eq!(Arc::strong_count(&msg), 1);
+}
+
+#[test]
+fn send_shared_dropped_receivers() {
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+
+    drop(r1);
+    s.send_shared(&Arc::new(1)).unwrap();
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(*r2.recv().unwrap(), 1);
+
+    drop(r2);
+    let msg = Arc::new(2);
+    let SendError(back) = s.send_shared(&msg).unwrap_err();
+    assert!(Arc::ptr_eq(&back, &msg));
+    assert_eq!(s.receiver_count(), 0);
+}
+
+#[test]
+fn send_shared_lag_limit() {
+    let (s, fast) = fanout_with_lag_limit(2);
+    let slow = fast.clone();
+
+    for i in 0..3 {
+        s.send_shared(&Arc::new(i)).unwrap();
+        assert_eq!(*fast.recv().unwrap(), i);
+    }
+
+    assert_eq!(*slow.recv().unwrap(), 0);
+    assert_eq!(*slow.recv().unwrap(), 1);
+    assert_eq!(slow.recv(), Err(RecvError));
+    assert!(slow.is_lagged());
+}
+
+#[test]
+fn send_shared_in_order() {
+    const COUNT: usize = 10_000;
+
+    let (s, r1) = fanout();
+    let r2 = r1.clone();
+    let receivers = [r1, r2];
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send_shared(&Arc::new(i)).unwrap();
+            }
+        });
+
+        for r in &receivers {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    assert_eq!(*r.recv().unwrap(), i);
+                }
+                assert_eq!(r.recv(), Err(RecvError));
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn subscribe_filtered() {
+    let (s, all) = fanout();
+    let small = all.subscribe_filtered(|n: &i32| *n < 10);
+    let large = small.subscribe_filtered(|n: &i32| *n >= 10);
+
+    for &i in &[1, 20, 3, 40] {
+        s.send(i).unwrap();
+    }
+
+    assert_eq!(all.try_iter().collect::<Vec<_>>(), [1, 20, 3, 40]);
+    assert_eq!(small.try_iter().collect::<Vec<_>>(), [1, 3]);
+    assert_eq!(large.try_iter().collect::<Vec<_>>(), [20, 40]);
+}
+
+#[test]
+fn subscribe_filtered_clone() {
+    let (s, r) = fanout();
+    let odd = r.subscribe_filtered(|n: &i32| n % 2 == 1);
+    let odd2 = odd.clone();
+    drop(r);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(odd.try_recv(), Ok(1));
+    assert_eq!(odd2.try_recv(), Ok(1));
+    assert_eq!(odd2.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn subscribe_filtered_rejects_all() {
+    let (s, r) = fanout();
+    let none = r.subscribe_filtered(|_: &i32| false);
+    drop(r);
+
+    // The message is sent even though nobody is interested in it.
+    assert_eq!(s.send(1), Ok(()));
+    assert_eq!(none.try_recv(), Err(TryRecvError::Empty));
+
+    drop(none);
+    assert_eq!(s.send(3), Err(SendError(3)));
+}
+
+#[test]
+fn send_shared_filtered() {
+    let (s, r) = fanout();
+    let big = r.subscribe_filtered(|n: &Arc<i32>| **n > 100);
+
+    s.send_shared(&Arc::new(1)).unwrap();
+    s.send_shared(&Arc::new(200)).unwrap();
+
+    assert_eq!(r.try_iter().map(|n| *n).collect::<Vec<_>>(), [1, 200]);
+    assert_eq!(big.try_iter().map(|n| *n).collect::<Vec<_>>(), [200]);
+}
@@ -0,0 +1,291 @@
+//! Tests for fault injection.
+
+#![cfg(feature = "fault-injection")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, bounded, unbounded};
+use crossbeam_channel::{clear_faults, inject_recv, inject_send, RecvFault, SendFault};
+use crossbeam_channel::{RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn drop_messages() {
+    let (s, r) = unbounded();
+    inject_send(&s, || SendFault::Drop);
+
+    assert_eq!(s.send(1), Ok(()));
+    assert_eq!(s.try_send(2), Ok(()));
+    assert_eq!(s.send_timeout(3, ms(100)), Ok(()));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    clear_faults(&s);
+    s.send(4).unwrap();
+    assert_eq!(r.try_recv(), Ok(4));
+}
+
+#[test]
+fn duplicate() {
+    let (s, r) = unbounded();
+    inject_send(&s, || SendFault::Duplicate);
+
+    s.send("a".to_string()).unwrap();
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), ["a", "a"]);
+}
+
+#[test]
+fn reorder() {
+    let (s, r) = unbounded();
+
+    // Hold back every even message until the next one is sent.
+    let mut n = 0;
+    inject_send(&s, move || {
+        n += 1;
+        if n % 2 == 1 {
+            SendFault::Hold
+        } else {
+            SendFault::Deliver
+        }
+    });
+
+    for i in 0..6 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 0, 3, 2, 5, 4]);
+}
+
+#[test]
+fn delay_send() {
+    let (s, r) = bounded(1);
+    inject_send(&s, || SendFault::Delay(ms(200)));
+
+    let start = Instant::now();
+    s.send(1).unwrap();
+    assert!(start.elapsed() >= ms(200));
+    assert_eq!(r.try_recv(), Ok(1));
+}
+
+#[test]
+fn recv_faults() {
+    let (s, r) = unbounded();
+
+    let mut n = 0;
+    inject_recv(&r, move || {
+        n += 1;
+        if n % 2 == 0 {
+            RecvFault::Drop
+        } else {
+            RecvFault::Deliver
+        }
+    });
+
+    for i in 0..6 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.recv_timeout(ms(100)), Ok(4));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    inject_recv(&r, || RecvFault::Delay(ms(200)));
+    s.send(6).unwrap();
+    let start = Instant::now();
+    assert_eq!(r.recv(), Ok(6));
+    assert!(start.elapsed() >= ms(200));
+}
+
+#[test]
+fn errors() {
+    let (s, r) = bounded(1);
+    inject_send(&s, || SendFault::Duplicate);
+
+    assert_eq!(s.try_send(1), Err(TrySendError::Full(1)));
+    assert_eq!(r.try_recv(), Ok(1));
+
+    drop(r);
+    assert_eq!(s.send(2), Err(SendError(2)));
+}
+
+#[test]
+fn held_messages_kept_on_error() {
+    let (s, r) = bounded(1);
+
+    let mut n = 0;
+    inject_send(&s, move || {
+        n += 1;
+        if n <= 2 {
+            SendFault::Hold
+        } else {
+            SendFault::Deliver
+        }
+    });
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Ok(()));
+
+    // The first held message doesn't fit, so it is returned and the second one stays held.
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(1)));
+
+    // The new message doesn't fit either, so the second one still stays held.
+    assert_eq!(s.try_send(4), Err(TrySendError::Full(4)));
+    assert_eq!(r.try_recv(), Ok(3));
+
+    assert_eq!(s.try_send(5), Err(TrySendError::Full(2)));
+    assert_eq!(r.try_recv(), Ok(5));
+
+    s.send(6).unwrap();
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [6]);
+}
+
+#[test]
+fn hook_uses_faulted_channels() {
+    let (s, r) = unbounded();
+    let (log_s, log_r) = unbounded();
+
+    // The hook sends on faulted channels, including its own.
+    inject_send(&log_s, || SendFault::Duplicate);
+    inject_send(&s, {
+        let s = s.clone();
+        let log_s = log_s.clone();
+        move || {
+            s.send(0).unwrap();
+            log_s.send("hook").unwrap();
+            SendFault::Deliver
+        }
+    });
+
+    s.send(1).unwrap();
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(log_r.try_iter().collect::<Vec<_>>(), ["hook"]);
+
+    // The hook clears its own faults.
+    inject_send(&s, {
+        let s = s.clone();
+        move || {
+            clear_faults(&s);
+            SendFault::Drop
+        }
+    });
+    s.send(2).unwrap();
+    s.send(3).unwrap();
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn panicking_hook() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    inject_send(&s1, || panic!());
+    inject_send(&s2, || SendFault::Duplicate);
+
+    // The hook panics while its lock is held.
+    let s = s1.clone();
+    assert!(thread::spawn(move || s.send(1)).join().is_err());
+
+    s2.send(2).unwrap();
+    assert_eq!(r2.try_iter().collect::<Vec<_>>(), [2, 2]);
+
+    inject_send(&s1, || SendFault::Deliver);
+    s1.send(3).unwrap();
+    assert_eq!(r1.try_iter().collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn held_messages_dropped() {
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Clone for DropCounter {
+        fn clone(&self) -> DropCounter {
+            DropCounter(self.0.clone())
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+
+    let (s, r) = unbounded();
+    inject_send(&s, || SendFault::Hold);
+    s.send(DropCounter(drops.clone())).unwrap();
+    s.send(DropCounter(drops.clone())).unwrap();
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    // Destroying the channel drops held messages.
+    drop(s);
+    drop(r);
+    assert_eq!(drops.load(Ordering::SeqCst), 2);
+
+    let (s, _r) = unbounded();
+    inject_send(&s, || SendFault::Hold);
+    s.send(DropCounter(drops.clone())).unwrap();
+    clear_faults(&s);
+    assert_eq!(drops.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn other_channels_unaffected() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    inject_send(&s1, || SendFault::Drop);
+
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    assert_eq!(r1.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r2.try_recv(), Ok(2));
+}
+
+#[test]
+#[should_panic]
+fn timer_receiver() {
+    inject_recv(&after(ms(100)), || RecvFault::Drop);
+}
+
+#[test]
+fn concurrent() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded(10);
+
+    let mut n = 0;
+    inject_send(&s, move || {
+        n += 1;
+        if n % 10 == 0 {
+            SendFault::Drop
+        } else {
+            SendFault::Deliver
+        }
+    });
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        scope.spawn(|_| {
+            for _ in 0..THREADS * COUNT * 9 / 10 {
+                r.recv().unwrap();
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,109 @@
+//! Tests for flat combiners.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::sync::FlatCombiner;
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let fc = FlatCombiner::new(Vec::new());
+    let a = fc.submit(|v| {
+        v.push(1);
+        v.len()
+    });
+    let b = fc.submit(|v| {
+        v.push(2);
+        v.len()
+    });
+
+    // Without contention, submitting executes right away.
+    assert_eq!(a.get(), Some(&1));
+    assert_eq!(b.get(), Some(&2));
+    assert_eq!(fc.combine(), 0);
+    assert_eq!(fc.into_inner(), [1, 2]);
+}
+
+#[test]
+fn order_per_thread() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let fc = FlatCombiner::new(Vec::new());
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let fc = &fc;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    fc.submit(move |v: &mut Vec<(usize, usize)>| v.push((t, i)));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let v = fc.into_inner();
+    assert_eq!(v.len(), THREADS * COUNT);
+    for t in 0..THREADS {
+        let seq: Vec<_> = v.iter().filter(|e| e.0 == t).map(|e| e.1).collect();
+        assert_eq!(seq, (0..COUNT).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn wait_in_select() {
+    let fc = Arc::new(FlatCombiner::new(0));
+    let slow = fc.clone();
+    let entered = Arc::new(AtomicUsize::new(0));
+    let e = entered.clone();
+
+    scope(|scope| {
+        // Keep the combiner busy so that the next submission only gets queued.
+        scope.spawn(move |_| {
+            slow.submit(move |n| {
+                e.store(1, Ordering::SeqCst);
+                thread::sleep(ms(300));
+                *n += 1;
+            });
+        });
+        while entered.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+
+        let done = fc.submit(|n| *n * 10);
+        assert!(done.get().is_none());
+
+        select! {
+            recv(done.ready()) -> _ => assert_eq!(done.get(), Some(&10)),
+            default(ms(1000)) => panic!("timed out"),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn panic_in_closure() {
+    let fc = Arc::new(FlatCombiner::new(0));
+
+    let f = fc.clone();
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        f.submit(|_: &mut i32| panic!("boom"));
+    }));
+    assert!(res.is_err());
+
+    // The combiner is released, so later submissions still go through.
+    assert_eq!(*fc.submit(|n| *n + 1).wait(), 1);
+}
@@ -0,0 +1,172 @@
+//! Tests for channel groups.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, ChannelGroup, RecvError, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let group = ChannelGroup::new();
+    assert!(group.is_empty());
+    assert!(!group.is_closed());
+
+    let (s, r) = unbounded();
+    group.register(&s);
+    assert_eq!(group.len(), 1);
+
+    s.send(1).unwrap();
+    group.close_all();
+    assert!(group.is_closed());
+    assert!(group.is_empty());
+
+    assert!(s.send(2).is_err());
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn wakes_blocked() {
+    let group = ChannelGroup::new();
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(1);
+    let (s3, r3) = bounded::<i32>(0);
+    let (s4, r4) = bounded::<i32>(0);
+    group.register(&s1);
+    group.register(&s2);
+    group.register(&s3);
+    group.register(&s4);
+    s2.send(0).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| assert!(r1.recv().is_err()));
+        scope.spawn(|_| assert!(s2.send(1).is_err()));
+        scope.spawn(|_| assert!(r3.recv().is_err()));
+        scope.spawn(|_| assert!(s4.send(1).is_err()));
+
+        thread::sleep(ms(500));
+        group.close_all();
+    })
+    .unwrap();
+
+    assert_eq!(r2.recv(), Ok(0));
+    assert!(r2.recv().is_err());
+    drop((s1, s3, r4));
+}
+
+#[test]
+fn wakes_select() {
+    let group = ChannelGroup::new();
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    group.register(&s1);
+    group.register(&s2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut sel = Select::new();
+            sel.recv(&r1);
+            sel.recv(&r2);
+            let oper = sel.select();
+            match oper.index() {
+                0 => assert!(oper.recv(&r1).is_err()),
+                _ => assert!(oper.recv(&r2).is_err()),
+            }
+        });
+
+        thread::sleep(ms(500));
+        group.close_all();
+    })
+    .unwrap();
+
+    drop((s1, s2));
+}
+
+#[test]
+fn register_after_close() {
+    let group = ChannelGroup::new();
+    group.close_all();
+
+    let (s, r) = bounded::<i32>(5);
+    group.register(&s);
+    assert!(s.try_send(1).is_err());
+    assert!(r.try_recv().is_err());
+    assert!(group.is_empty());
+}
+
+#[test]
+fn does_not_keep_alive() {
+    let group = ChannelGroup::new();
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    group.register(&s1);
+    group.register(&s2);
+    assert_eq!(group.len(), 2);
+
+    // Dropping the senders still disconnects the channel as usual.
+    drop(s1);
+    assert!(r1.recv().is_err());
+    assert_eq!(group.len(), 2);
+
+    drop(r1);
+    assert_eq!(group.len(), 1);
+
+    drop(s2);
+    drop(r2);
+    assert_eq!(group.len(), 0);
+
+    // Closing a group whose channels are gone does nothing.
+    group.close_all();
+}
+
+#[test]
+fn clones() {
+    let group = ChannelGroup::new();
+    let clone = group.clone();
+
+    let (s, r) = unbounded::<i32>();
+    clone.register(&s);
+    assert_eq!(group.len(), 1);
+
+    group.close_all();
+    assert!(clone.is_closed());
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn stress_close_while_dropping() {
+    const COUNT: usize = 1000;
+
+    for _ in 0..COUNT / 100 {
+        let group = ChannelGroup::new();
+        let mut endpoints = Vec::new();
+
+        for i in 0..100 {
+            let (s, r) = if i % 2 == 0 { bounded(1) } else { unbounded() };
+            group.register(&s);
+            endpoints.push((s, r));
+        }
+
+        scope(|scope| {
+            scope.spawn(|_| group.close_all());
+            scope.spawn(move |_| {
+                for (s, r) in endpoints {
+                    let _ = s.send(0);
+                    drop(s);
+                    let _ = r.recv();
+                }
+            });
+        })
+        .unwrap();
+
+        assert!(group.is_empty());
+    }
+}
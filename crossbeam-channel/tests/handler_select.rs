@@ -0,0 +1,153 @@
+//! Tests for selection with handlers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, HandlerSelect, Receiver, RecvError, SendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+
+    s1.send(1).unwrap();
+    let mut sel = HandlerSelect::new();
+    sel.recv(&r1, |msg| msg.map(|n| n * 10));
+    sel.recv(&r2, |msg| msg.map(|n| n * 100));
+    assert_eq!(sel.select(), Ok(10));
+
+    s2.send(2).unwrap();
+    let mut sel = HandlerSelect::new();
+    sel.recv(&r1, |msg| msg.map(|n| n * 10));
+    sel.recv(&r2, |msg| msg.map(|n| n * 100));
+    assert_eq!(sel.select(), Ok(200));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded::<i32>();
+    drop(s);
+
+    let mut sel = HandlerSelect::new();
+    sel.recv(&r, |msg| msg);
+    assert_eq!(sel.select(), Err(RecvError));
+
+    let (s, r) = bounded::<i32>(0);
+    drop(r);
+
+    let mut sel = HandlerSelect::new();
+    sel.send(&s, || 7, |res| res);
+    assert_eq!(sel.select(), Err(SendError(7)));
+}
+
+#[test]
+fn lazy_send() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(1);
+    let created = Cell::new(0);
+
+    let mut sel = HandlerSelect::new();
+    sel.send(
+        &s1,
+        || {
+            created.set(created.get() + 1);
+            1
+        },
+        |_| "first",
+    );
+    sel.send(
+        &s2,
+        || {
+            created.set(created.get() + 10);
+            2
+        },
+        |res| {
+            res.unwrap();
+            "second"
+        },
+    );
+
+    assert_eq!(sel.select(), "second");
+    assert_eq!(created.get(), 10);
+    assert_eq!(r2.try_recv(), Ok(2));
+    drop(r1);
+}
+
+#[test]
+fn try_select() {
+    let (s, r) = unbounded();
+
+    let mut sel = HandlerSelect::new();
+    sel.recv(&r, |msg| msg.unwrap());
+
+    // Nothing is ready, so the selection is handed back.
+    let sel = sel.try_select().unwrap_err();
+    s.send(5).unwrap();
+    assert_eq!(sel.try_select().unwrap(), 5);
+}
+
+#[test]
+fn select_timeout() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(3).unwrap();
+        });
+
+        let mut sel = HandlerSelect::new();
+        sel.recv(&r, |msg| msg.unwrap());
+
+        let sel = sel.select_timeout(ms(100)).unwrap_err();
+        assert_eq!(sel.select_timeout(ms(1000)).unwrap(), 3);
+    })
+    .unwrap();
+}
+
+#[test]
+fn runtime_cases() {
+    const COUNT: usize = 10;
+
+    let channels = (0..COUNT).map(|_| unbounded()).collect::<Vec<_>>();
+    let receivers = channels
+        .iter()
+        .map(|c| &c.1)
+        .collect::<Vec<&Receiver<usize>>>();
+    channels[7].0.send(70).unwrap();
+
+    let mut sel = HandlerSelect::new();
+    for (i, r) in receivers.iter().enumerate() {
+        sel.recv(r, move |msg| (i, msg.unwrap()));
+    }
+    assert_eq!(sel.select(), (7, 70));
+}
+
+#[test]
+fn send_and_recv() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            assert_eq!(r1.recv(), Ok("sent"));
+        });
+
+        let mut sel = HandlerSelect::new();
+        sel.send(&s1, || "sent", |res| res.is_ok());
+        sel.recv(&r2, |_| false);
+        assert!(sel.select());
+    })
+    .unwrap();
+    drop(s2);
+}
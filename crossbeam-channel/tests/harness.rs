@@ -0,0 +1,209 @@
+//! Tests for the test harness.
+
+#![cfg(feature = "test-harness")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::Barrier;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, test_harness, unbounded, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn zero_rendezvous() {
+    let harness = test_harness();
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        let t = scope.spawn(move |_| s.send(7).unwrap());
+
+        harness.wait_until_blocked(t.thread().id());
+        assert!(harness.is_blocked(t.thread().id()));
+        assert_eq!(r.try_recv(), Ok(7));
+
+        t.join().unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn full_buffer() {
+    let harness = test_harness();
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        let t = scope.spawn(move |_| s.send(2).unwrap());
+
+        harness.wait_until_blocked(t.thread().id());
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(2));
+
+        t.join().unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn empty_receiver() {
+    let harness = test_harness();
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        let t = scope.spawn(move |_| r.recv());
+
+        harness.wait_until_blocked(t.thread().id());
+        drop(s);
+
+        assert!(t.join().unwrap().is_err());
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let harness = test_harness();
+    let (s1, r1) = unbounded::<i32>();
+    let (_s2, r2) = unbounded::<i32>();
+
+    scope(|scope| {
+        let t = scope.spawn(|_| {
+            let mut sel = Select::new();
+            sel.recv(&r1);
+            sel.recv(&r2);
+            let oper = sel.select();
+            assert_eq!(oper.index(), 0);
+            oper.recv(&r1).unwrap()
+        });
+
+        harness.wait_until_blocked(t.thread().id());
+        s1.send(5).unwrap();
+
+        assert_eq!(t.join().unwrap(), 5);
+    })
+    .unwrap();
+}
+
+#[test]
+fn unblocked() {
+    let harness = test_harness();
+    let (s, r) = bounded::<i32>(0);
+
+    scope(|scope| {
+        let t = scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.send(1).unwrap();
+        });
+
+        assert!(!harness.wait_until_blocked_timeout(thread::current().id(), ms(100)));
+        assert_eq!(r.recv(), Ok(1));
+        assert!(!harness.is_blocked(thread::current().id()));
+        assert!(!harness.is_blocked(t.thread().id()));
+
+        t.join().unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn hold_and_release() {
+    let harness = test_harness();
+    let (s, r) = bounded(0);
+    let start = Barrier::new(2);
+
+    scope(|scope| {
+        let t = scope.spawn(|_| {
+            start.wait();
+            s.send(1).unwrap();
+        });
+
+        harness.hold(t.thread().id());
+        start.wait();
+        harness.wait_until_blocked(t.thread().id());
+
+        // The receiver completes the rendezvous, but the sender stays held.
+        assert_eq!(r.recv(), Ok(1));
+        thread::sleep(ms(100));
+        assert!(harness.is_blocked(t.thread().id()));
+
+        let id = t.thread().id();
+        harness.release(id);
+        t.join().unwrap();
+        assert!(!harness.is_blocked(id));
+    })
+    .unwrap();
+}
+
+#[test]
+fn step() {
+    let harness = test_harness();
+    let (s, r) = unbounded();
+    let start = Barrier::new(2);
+
+    scope(|scope| {
+        let t = scope.spawn(|_| {
+            start.wait();
+            let a = r.recv().unwrap();
+            let b = r.recv().unwrap();
+            a + b
+        });
+
+        harness.hold(t.thread().id());
+        start.wait();
+
+        for i in 1..3 {
+            // Wait until the receiver reaches its `i`-th blocking point.
+            while harness.times_blocked(t.thread().id()) < i {
+                thread::yield_now();
+            }
+            s.send(i).unwrap();
+            thread::sleep(ms(100));
+            assert!(harness.is_blocked(t.thread().id()));
+            harness.step(t.thread().id());
+        }
+
+        let id = t.thread().id();
+        assert_eq!(t.join().unwrap(), 3);
+        assert_eq!(harness.times_blocked(id), 2);
+    })
+    .unwrap();
+}
+
+#[test]
+fn counts_per_harness() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        let old = test_harness();
+        let t = scope.spawn(|_| {
+            r.recv().unwrap();
+            r.recv().unwrap();
+        });
+        let id = t.thread().id();
+
+        old.wait_until_blocked(id);
+        let new = test_harness();
+        assert_eq!(old.times_blocked(id), 1);
+        assert_eq!(new.times_blocked(id), 0);
+
+        // Only blocks since the new harness was created are counted by it.
+        s.send(1).unwrap();
+        while old.times_blocked(id) < 2 {
+            thread::yield_now();
+        }
+        assert_eq!(new.times_blocked(id), 1);
+        drop(old);
+
+        s.send(2).unwrap();
+        t.join().unwrap();
+        assert_eq!(new.times_blocked(id), 1);
+    })
+    .unwrap();
+}
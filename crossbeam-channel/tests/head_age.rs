@@ -0,0 +1,98 @@
+//! Tests for tracking how long the oldest message has been waiting.
+
+#![cfg(feature = "head-age")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array() {
+    let (s, r) = bounded(4);
+    assert_eq!(s.head_age(), None);
+
+    // Go around the buffer several times.
+    for _ in 0..5 {
+        s.send(1).unwrap();
+        thread::sleep(ms(200));
+        s.send(2).unwrap();
+        s.send(3).unwrap();
+        assert!(s.head_age().unwrap() >= ms(200));
+
+        r.recv().unwrap();
+        assert!(s.head_age().unwrap() < ms(200));
+        r.recv().unwrap();
+        assert!(s.head_age().unwrap() < ms(200));
+        r.recv().unwrap();
+        assert_eq!(s.head_age(), None);
+    }
+}
+
+#[test]
+fn array_before_tracking() {
+    let (s, r) = bounded(4);
+    s.send(1).unwrap();
+    assert_eq!(s.head_age(), None);
+
+    thread::sleep(ms(100));
+    s.send(2).unwrap();
+    thread::sleep(ms(100));
+
+    // The first message is at least as old as the first tracked one.
+    assert!(s.head_age().unwrap() >= ms(100));
+    r.recv().unwrap();
+    assert!(s.head_age().unwrap() >= ms(100));
+}
+
+#[test]
+fn list() {
+    let (s, r) = unbounded();
+    assert_eq!(s.head_age(), None);
+
+    // Cross several blocks.
+    for _ in 0..5 {
+        for i in 0..20 {
+            s.send(i).unwrap();
+        }
+        thread::sleep(ms(200));
+        s.send(20).unwrap();
+        assert!(s.head_age().unwrap() >= ms(200));
+
+        for _ in 0..19 {
+            r.recv().unwrap();
+            assert!(s.head_age().unwrap() >= ms(200));
+        }
+        r.recv().unwrap();
+        assert!(s.head_age().unwrap() < ms(200));
+        r.recv().unwrap();
+        assert_eq!(s.head_age(), None);
+    }
+
+    s.send(0).unwrap();
+    thread::sleep(ms(100));
+    let age = s.head_age().unwrap();
+    assert!(age >= ms(100) && age < ms(200));
+}
+
+#[test]
+fn zero() {
+    let (s, r) = bounded(0);
+    assert_eq!(s.head_age(), None);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+        thread::sleep(ms(100));
+        assert_eq!(s.head_age(), None);
+        r.recv().unwrap();
+    })
+    .unwrap();
+}
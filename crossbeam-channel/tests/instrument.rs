@@ -0,0 +1,160 @@
+//! Tests for the select hook.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::cell::RefCell;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::instrument::{self, SelectEvent};
+use crossbeam_channel::{bounded, unbounded, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+thread_local! {
+    static EVENTS: RefCell<Vec<SelectEvent>> = RefCell::new(Vec::new());
+}
+
+/// Installs a hook recording events into a thread-local list, so that tests don't interfere.
+///
+/// Every test installs the same hook again, which is harmless.
+fn record() {
+    instrument::set_select_hook(|event| EVENTS.with(|events| events.borrow_mut().push(*event)));
+    EVENTS.with(|events| events.borrow_mut().clear());
+}
+
+/// Returns the events recorded on the current thread.
+fn recorded() -> Vec<SelectEvent> {
+    EVENTS.with(|events| {
+        let mut events = events.borrow_mut();
+        let recorded = events.drain(..).collect();
+        recorded
+    })
+}
+
+#[test]
+fn fired_without_blocking() {
+    record();
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded();
+    s2.send(2).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    sel.recv(&r2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r2), Ok(2));
+
+    match recorded()[..] {
+        [SelectEvent::Fired { index: 1, .. }] => {}
+        ref events => panic!("{:?}", events),
+    }
+    drop(s1);
+}
+
+#[test]
+fn blocking() {
+    record();
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+        });
+
+        select! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert_eq!(msg, Ok(2)),
+        }
+    })
+    .unwrap();
+
+    let events = recorded();
+    assert_eq!(events[0], SelectEvent::BlockBegin { operations: 2 });
+    match events[events.len() - 2] {
+        SelectEvent::BlockEnd { waited } => assert!(waited >= ms(80)),
+        ref event => panic!("{:?}", event),
+    }
+    match events[events.len() - 1] {
+        SelectEvent::Fired { index, elapsed } => {
+            assert_eq!(index, 1);
+            assert!(elapsed >= ms(100));
+        }
+        ref event => panic!("{:?}", event),
+    }
+    drop(s1);
+}
+
+#[test]
+fn ready() {
+    record();
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(1).unwrap();
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r);
+        assert_eq!(sel.ready(), 0);
+    })
+    .unwrap();
+
+    let events = recorded();
+    assert_eq!(events[0], SelectEvent::BlockBegin { operations: 1 });
+    match events[events.len() - 1] {
+        SelectEvent::Fired { index: 0, elapsed } => assert!(elapsed >= ms(100)),
+        ref event => panic!("{:?}", event),
+    }
+}
+
+#[test]
+fn timeout() {
+    record();
+    let (s, r) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    assert!(sel.select_timeout(ms(50)).is_err());
+    assert!(sel.try_select().is_err());
+
+    // Nothing was fired, and the thread may have woken up more than once.
+    let events = recorded();
+    assert!(!events.is_empty());
+    for pair in events.chunks(2) {
+        assert_eq!(pair[0], SelectEvent::BlockBegin { operations: 1 });
+        match pair[1] {
+            SelectEvent::BlockEnd { .. } => {}
+            ref event => panic!("{:?}", event),
+        }
+    }
+    drop(s);
+}
+
+#[test]
+fn unrelated_threads() {
+    record();
+    let (s, r) = unbounded();
+
+    thread::spawn(move || {
+        s.send(1).unwrap();
+        let mut sel = Select::new();
+        sel.send(&s);
+        sel.select().send(&s, 2).unwrap();
+    })
+    .join()
+    .unwrap();
+
+    assert!(recorded().is_empty());
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 2]);
+}
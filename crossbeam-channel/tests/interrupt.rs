@@ -0,0 +1,140 @@
+//! Tests for interrupting blocked operations.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded, BlockHandle};
+use crossbeam_channel::{RecvInterruptibleError, SendInterruptibleError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn interrupt_recv() {
+    let (s, r) = unbounded::<i32>();
+    let handle = BlockHandle::new();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            handle.interrupt();
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            r.recv_interruptible(&handle),
+            Err(RecvInterruptibleError::Interrupted)
+        );
+        assert!(start.elapsed() >= ms(100));
+    })
+    .unwrap();
+
+    // The interrupt was consumed and the channel still works.
+    assert!(!handle.is_interrupted());
+    s.send(1).unwrap();
+    assert_eq!(r.recv_interruptible(&handle), Ok(1));
+}
+
+#[test]
+fn interrupt_send() {
+    for cap in 0..2 {
+        let (s, r) = bounded(cap);
+        for i in 0..cap {
+            s.send(i).unwrap();
+        }
+        let handle = BlockHandle::new();
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                thread::sleep(ms(200));
+                handle.interrupt();
+            });
+
+            assert_eq!(
+                s.send_interruptible(10, &handle),
+                Err(SendInterruptibleError::Interrupted(10))
+            );
+        })
+        .unwrap();
+
+        assert_eq!(
+            r.try_iter().collect::<Vec<_>>(),
+            (0..cap).collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn pending_interrupt() {
+    let (s, r) = bounded(1);
+    let handle = BlockHandle::new();
+
+    // Interrupting several times has the same effect as interrupting once.
+    handle.interrupt();
+    handle.interrupt();
+    assert!(handle.is_interrupted());
+
+    // Operations that don't have to block ignore the interrupt.
+    assert_eq!(s.send_interruptible(1, &handle), Ok(()));
+    assert!(handle.is_interrupted());
+
+    assert_eq!(
+        s.send_interruptible(2, &handle),
+        Err(SendInterruptibleError::Interrupted(2))
+    );
+    assert!(!handle.is_interrupted());
+
+    assert_eq!(r.recv_interruptible(&handle), Ok(1));
+    handle.interrupt();
+    handle.reset();
+    assert!(!handle.is_interrupted());
+}
+
+#[test]
+fn disconnected() {
+    let handle = BlockHandle::new();
+
+    let (s, r) = unbounded::<i32>();
+    drop(s);
+    assert_eq!(
+        r.recv_interruptible(&handle),
+        Err(RecvInterruptibleError::Disconnected)
+    );
+
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(200));
+            drop(r);
+        });
+
+        assert_eq!(
+            s.send_interruptible(2, &handle),
+            Err(SendInterruptibleError::Disconnected(2))
+        );
+    })
+    .unwrap();
+}
+
+#[test]
+fn message_wins_over_blocking() {
+    let (s, r) = unbounded();
+    let handle = BlockHandle::new();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s.send(7).unwrap();
+        });
+
+        assert_eq!(r.recv_interruptible(&handle), Ok(7));
+    })
+    .unwrap();
+    assert!(!handle.is_interrupted());
+}
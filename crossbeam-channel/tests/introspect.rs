@@ -0,0 +1,85 @@
+//! Tests for the `Debug` output of channel endpoints.
+
+extern crate crossbeam_channel;
+
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, coalesce, lanes, never, tick, unbounded};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn bounded_channel() {
+    let (s, r) = bounded::<i32>(4);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    let _s2 = s.clone();
+
+    assert_eq!(
+        format!("{:?}", s),
+        "Sender { flavor: array, len: 2, capacity: Some(4), senders: 2, receivers: 1, \
+         disconnected: false }"
+    );
+    assert_eq!(
+        format!("{:?}", r),
+        "Receiver { flavor: array, len: 2, capacity: Some(4), senders: 2, receivers: 1, \
+         disconnected: false }"
+    );
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded::<i32>();
+    s.send(1).unwrap();
+    drop(s);
+
+    assert_eq!(
+        format!("{:?}", r),
+        "Receiver { flavor: list, len: 1, capacity: None, senders: 0, receivers: 1, \
+         disconnected: true }"
+    );
+
+    let (s, r) = bounded::<i32>(0);
+    drop(r);
+    assert_eq!(
+        format!("{:?}", s),
+        "Sender { flavor: zero, len: 0, capacity: Some(0), senders: 1, receivers: 0, \
+         disconnected: true }"
+    );
+}
+
+#[test]
+fn unique() {
+    let (_s, r) = unbounded::<i32>();
+    let r = r.into_unique().unwrap();
+    assert!(format!("{:?}", r).starts_with("UniqueReceiver { flavor: list, len: 0,"));
+}
+
+#[test]
+fn special_flavors() {
+    assert_eq!(
+        format!("{:?}", never::<i32>()),
+        "Receiver { flavor: never, len: 0, capacity: Some(0), disconnected: false }"
+    );
+    assert_eq!(
+        format!("{:?}", after(ms(1000))),
+        "Receiver { flavor: after, len: 0, capacity: Some(1), disconnected: false }"
+    );
+    assert!(format!("{:?}", tick(ms(1000))).starts_with("Receiver { flavor: tick,"));
+}
+
+#[test]
+fn other_senders() {
+    let (s, r) = lanes::channel::<i32>();
+    s.send_data(1).unwrap();
+    assert!(format!("{:?}", s).starts_with("LaneSender { flavor: lanes, len: 1,"));
+    assert!(format!("{:?}", r).starts_with("Receiver { flavor: lanes, len: 1,"));
+
+    let (s, r) = coalesce::channel::<i32, i32>();
+    s.send(1, 1).unwrap();
+    s.send(1, 2).unwrap();
+    assert!(format!("{:?}", s).starts_with("CoalescingSender { flavor: coalesce, len: 1,"));
+    drop(r);
+}
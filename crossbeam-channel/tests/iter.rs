@@ -3,9 +3,16 @@
 extern crate crossbeam_channel;
 extern crate crossbeam_utils;
 
+use std::thread;
+use std::time::{Duration, Instant};
+
 use crossbeam_channel::unbounded;
 use crossbeam_utils::thread::scope;
 
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
 #[test]
 fn nested_recv_iter() {
     let (s, r) = unbounded::<i32>();
@@ -111,3 +118,65 @@ fn recv_into_iter_borrowed() {
     assert_eq!(iter.next().unwrap(), 2);
     assert_eq!(iter.next().is_none(), true);
 }
+
+#[test]
+fn recv_deadline_iter() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..10 {
+                if s.send(i).is_err() {
+                    break;
+                }
+                thread::sleep(ms(100));
+            }
+        });
+
+        let deadline = Instant::now() + ms(250);
+        let v: Vec<i32> = r.into_blocking_iter_with_deadline(deadline).collect();
+        assert!(Instant::now() >= deadline);
+        assert_eq!(v, [0, 1, 2]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_deadline_iter_disconnected() {
+    let (s, r) = unbounded::<i32>();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    drop(s);
+
+    let start = Instant::now();
+    let mut iter = r.into_blocking_iter_with_deadline(start + ms(1000));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next(), Some(2));
+    assert_eq!(iter.next(), None);
+    assert!(start.elapsed() < ms(500));
+}
+
+#[test]
+fn recv_deadline_iter_fused() {
+    let (s, r) = unbounded::<i32>();
+    s.send(1).unwrap();
+
+    let mut iter = r.into_blocking_iter_with_deadline(Instant::now() + ms(100));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next(), None);
+
+    // Messages arriving after the deadline are left in the channel.
+    s.send(2).unwrap();
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.into_inner().try_recv(), Ok(2));
+}
+
+#[test]
+fn recv_deadline_iter_past() {
+    let (s, r) = unbounded::<i32>();
+    s.send(1).unwrap();
+
+    let mut iter = r.into_blocking_iter_with_deadline(Instant::now());
+    assert_eq!(iter.next(), None);
+    drop(s);
+}
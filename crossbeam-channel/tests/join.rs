@@ -0,0 +1,102 @@
+//! Tests for selectable thread completion.
+
+#[macro_use]
+extern crate crossbeam_channel;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{never, spawn_selectable, unbounded, Select};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let worker = spawn_selectable(|| 7);
+    assert_eq!(worker.join().unwrap(), 7);
+}
+
+#[test]
+fn panic_payload() {
+    let worker = spawn_selectable(|| -> i32 { panic!("boom") });
+    let err = worker.recv().unwrap().unwrap_err();
+    assert_eq!(*err.downcast::<&str>().unwrap(), "boom");
+}
+
+#[test]
+fn thread() {
+    let worker = spawn_selectable(|| thread::current().id());
+    let id = worker.thread().id();
+    assert_eq!(worker.join().unwrap(), id);
+}
+
+#[test]
+fn select_workers() {
+    let (s, r) = unbounded();
+
+    let slow = spawn_selectable(|| {
+        thread::sleep(ms(500));
+        "slow"
+    });
+    let fast = spawn_selectable(move || {
+        s.send(1).unwrap();
+        thread::sleep(ms(100));
+        "fast"
+    });
+
+    let mut slow = Some(&*slow);
+    let mut fast = Some(&*fast);
+    let mut finished = Vec::new();
+    let mut messages = 0;
+
+    while finished.len() < 2 {
+        select! {
+            recv(r) -> msg => {
+                if msg.is_ok() {
+                    messages += 1;
+                }
+            }
+            recv(slow.unwrap_or(&never())) -> res => {
+                finished.push(res.unwrap().unwrap());
+                slow = None;
+            }
+            recv(fast.unwrap_or(&never())) -> res => {
+                finished.push(res.unwrap().unwrap());
+                fast = None;
+            }
+            default(ms(1000)) => panic!(),
+        }
+    }
+
+    assert_eq!(finished, ["fast", "slow"]);
+    assert_eq!(messages, 1);
+}
+
+#[test]
+fn dynamic_select() {
+    let workers: Vec<_> = (0..4)
+        .map(|i| {
+            spawn_selectable(move || {
+                thread::sleep(ms(100 * i));
+                i
+            })
+        })
+        .collect();
+
+    let mut sel = Select::new();
+    for w in &workers {
+        sel.recv(w);
+    }
+
+    let mut results = Vec::new();
+    for _ in 0..workers.len() {
+        let oper = sel.select();
+        let index = oper.index();
+        results.push(oper.recv(&workers[index]).unwrap().unwrap());
+        sel.remove(index);
+    }
+
+    assert_eq!(results, [0, 1, 2, 3]);
+}
@@ -0,0 +1,185 @@
+//! Tests for lanes channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{lanes, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = lanes::channel();
+    s.send_data(1).unwrap();
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    s.send_control(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn control_first() {
+    let (s, r) = lanes::channel();
+
+    for i in 0..10 {
+        s.send_data(i).unwrap();
+        s.send_control(100 + i).unwrap();
+    }
+    assert_eq!(r.len(), 20);
+    assert_eq!(s.len(), 20);
+
+    let v: Vec<i32> = r.try_iter().collect();
+    let expected: Vec<i32> = (100..110).chain(0..10).collect();
+    assert_eq!(v, expected);
+    assert!(r.is_empty());
+    assert!(s.is_empty());
+}
+
+#[test]
+fn disconnect() {
+    let (s, r) = lanes::channel();
+    let s2 = s.clone();
+
+    s.send_data(1).unwrap();
+    s2.send_control(2).unwrap();
+    drop(s);
+    drop(s2);
+
+    // Remaining messages are received before the disconnection.
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (s, r) = lanes::channel();
+    drop(r);
+    assert_eq!(s.send_data(1), Err(SendError(1)));
+    assert_eq!(s.send_control(2), Err(SendError(2)));
+}
+
+#[test]
+fn wake_on_either_lane() {
+    let (s, r) = lanes::channel();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+
+        thread::sleep(ms(100));
+        s.send_data(1).unwrap();
+        thread::sleep(ms(100));
+        s.send_control(2).unwrap();
+        thread::sleep(ms(100));
+        drop(s);
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s, r) = lanes::channel();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send_control(1).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(1)),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+
+    s.send_data(2).unwrap();
+    s.send_control(3).unwrap();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r), Ok(3));
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = lanes::channel::<usize>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    if i % 2 == 0 {
+                        s.send_data(i).unwrap();
+                    } else {
+                        s.send_control(i).unwrap();
+                    }
+                }
+            });
+        }
+
+        let mut handles = Vec::new();
+        for _ in 0..THREADS {
+            handles.push(scope.spawn(|_| {
+                let mut sum = 0;
+                for _ in 0..COUNT {
+                    sum += r.recv().unwrap();
+                }
+                sum
+            }));
+        }
+
+        let sum: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(sum, THREADS * COUNT * (COUNT - 1) / 2);
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn drops() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = lanes::channel();
+    for _ in 0..100 {
+        s.send_data(DropCounter).unwrap();
+        s.send_control(DropCounter).unwrap();
+    }
+    for _ in 0..50 {
+        r.recv().unwrap();
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 50);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 200);
+}
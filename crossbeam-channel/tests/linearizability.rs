@@ -0,0 +1,120 @@
+//! Tests for the linearizability checker.
+
+#![cfg(feature = "tests-support")]
+
+extern crate crossbeam_channel;
+
+use std::sync::Mutex;
+
+use crossbeam_channel::linearizability::{
+    check, check_flavors, check_queue, record, Event, Op, Outcome, Queue,
+};
+use crossbeam_channel::{bounded, unbounded};
+
+fn event(thread: usize, op: Op, outcome: Outcome, invoked: usize, returned: usize) -> Event {
+    Event {
+        thread,
+        op,
+        outcome,
+        invoked,
+        returned,
+    }
+}
+
+#[test]
+fn sequential() {
+    let history = vec![
+        event(0, Op::TrySend(1), Outcome::Sent(true), 0, 1),
+        event(0, Op::TrySend(2), Outcome::Sent(false), 2, 3),
+        event(0, Op::Len, Outcome::Len(1), 4, 5),
+        event(0, Op::TryRecv, Outcome::Received(Some(1)), 6, 7),
+    ];
+    assert!(check(Some(1), &history).is_ok());
+    assert!(check(None, &history).is_err());
+}
+
+#[test]
+fn overlapping() {
+    // The sends are concurrent, so either value may come out first.
+    let history = vec![
+        event(0, Op::TrySend(1), Outcome::Sent(true), 0, 3),
+        event(1, Op::TryRecv, Outcome::Received(Some(2)), 1, 6),
+        event(2, Op::TrySend(2), Outcome::Sent(true), 2, 4),
+    ];
+    assert!(check(None, &history).is_ok());
+
+    // The first send completed before the second one started, so its value comes out first.
+    let history = vec![
+        event(0, Op::TrySend(1), Outcome::Sent(true), 0, 1),
+        event(2, Op::TrySend(2), Outcome::Sent(true), 2, 4),
+        event(1, Op::TryRecv, Outcome::Received(Some(2)), 3, 6),
+    ];
+    assert!(check(None, &history).is_err());
+
+    let history = vec![
+        event(0, Op::TrySend(1), Outcome::Sent(true), 0, 3),
+        event(1, Op::TryRecv, Outcome::Received(Some(1)), 1, 6),
+        event(2, Op::TrySend(2), Outcome::Sent(true), 2, 4),
+        event(2, Op::Len, Outcome::Len(1), 7, 8),
+    ];
+    assert!(check(None, &history).is_ok());
+}
+
+#[test]
+fn zero_capacity() {
+    let history = vec![event(0, Op::TrySend(1), Outcome::Sent(true), 0, 1)];
+    assert!(check(Some(0), &history).is_err());
+
+    let history = vec![
+        event(0, Op::TrySend(1), Outcome::Sent(false), 0, 1),
+        event(1, Op::TryRecv, Outcome::Received(None), 0, 1),
+    ];
+    assert!(check(Some(0), &history).is_ok());
+}
+
+#[test]
+fn record_history() {
+    let queue = unbounded();
+    let history = record(&queue, 1, 4, 10);
+
+    assert_eq!(history.len(), 40);
+    assert!(history.windows(2).all(|w| w[0].invoked < w[1].invoked));
+    assert!(history.iter().all(|e| e.invoked < e.returned));
+    assert!(check(None, &history).is_ok());
+}
+
+/// A stack, which is not a FIFO queue.
+struct Stack(Mutex<Vec<u64>>);
+
+impl Queue for Stack {
+    fn try_send(&self, value: u64) -> bool {
+        self.0.lock().unwrap().push(value);
+        true
+    }
+
+    fn try_recv(&self) -> Option<u64> {
+        self.0.lock().unwrap().pop()
+    }
+
+    fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+}
+
+#[test]
+fn detects_lifo() {
+    let res = check_queue(|| Stack(Mutex::new(Vec::new())), None, 0, 200);
+    let violation = res.unwrap_err();
+    assert_eq!(violation.capacity, None);
+    assert!(violation.to_string().contains("not linearizable"));
+}
+
+#[test]
+fn detects_wrong_capacity() {
+    assert!(check_queue(|| bounded(2), Some(1), 0, 200).is_err());
+}
+
+#[test]
+fn flavors() {
+    check_flavors(0, 200);
+}
@@ -6,13 +6,14 @@ extern crate crossbeam_utils;
 extern crate rand;
 
 use std::any::Any;
-use std::sync::atomic::AtomicUsize;
+use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::{unbounded, Receiver};
-use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{RecvBatchTimeoutError, RecvError, RecvTimeoutError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
 use rand::{thread_rng, Rng};
@@ -135,6 +136,108 @@ fn recv_timeout() {
     .unwrap();
 }
 
+#[test]
+fn recv_batch_timeout() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut buf = Vec::new();
+            assert_eq!(
+                r.recv_batch_timeout(&mut buf, 3, ms(1000)),
+                Err(RecvBatchTimeoutError::Timeout(2))
+            );
+            assert_eq!(buf, [1, 2]);
+            assert_eq!(r.recv_batch_timeout(&mut buf, 2, ms(1000)), Ok(2));
+            assert_eq!(buf, [1, 2, 3, 4]);
+            assert_eq!(
+                r.recv_batch_timeout(&mut buf, 3, ms(1000)),
+                Err(RecvBatchTimeoutError::Disconnected(1))
+            );
+            assert_eq!(buf, [1, 2, 3, 4, 5]);
+            assert_eq!(r.recv_batch_timeout(&mut buf, 0, ms(1000)), Ok(0));
+        });
+        scope.spawn(move |_| {
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+            thread::sleep(ms(1500));
+            s.send(3).unwrap();
+            s.send(4).unwrap();
+            s.send(5).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_many_until() {
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut buf = Vec::new();
+            let start = Instant::now();
+            assert_eq!(r.recv_many_until(&mut buf, start + ms(500)), Ok(2));
+            assert!(start.elapsed() >= ms(500));
+            assert_eq!(buf, [1, 2]);
+
+            assert_eq!(r.recv_many_until(&mut buf, start + ms(2000)), Ok(3));
+            assert!(start.elapsed() < ms(2000));
+            assert_eq!(buf, [1, 2, 3, 4, 5]);
+            assert_eq!(
+                r.recv_many_until(&mut buf, start + ms(2000)),
+                Err(RecvError)
+            );
+        });
+        scope.spawn(move |_| {
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+            thread::sleep(ms(1000));
+            s.send(3).unwrap();
+            s.send(4).unwrap();
+            s.send(5).unwrap();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_many_until_passed_deadline() {
+    let (s, r) = unbounded();
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+
+    let mut buf = Vec::new();
+    let deadline = Instant::now();
+    assert_eq!(r.recv_many_until(&mut buf, deadline), Ok(1));
+    assert_eq!(buf, [0]);
+}
+
+#[test]
+fn recv_many_until_busy() {
+    let (s, r) = unbounded();
+    let done = &AtomicBool::new(false);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let mut i = 0;
+            while !done.load(Ordering::SeqCst) {
+                s.send(i).unwrap();
+                i += 1;
+            }
+        });
+
+        // Messages keep arriving, but the call still returns at the deadline.
+        let mut buf = Vec::new();
+        let start = Instant::now();
+        assert!(r.recv_many_until(&mut buf, start + ms(100)).unwrap() > 0);
+        assert!(start.elapsed() < ms(1000));
+        done.store(true, Ordering::SeqCst);
+    })
+    .unwrap();
+}
+
 #[test]
 fn try_send() {
     let (s, r) = unbounded();
@@ -536,3 +639,150 @@ fn channel_through_channel() {
     })
     .unwrap();
 }
+
+#[test]
+fn recv_or_spawn() {
+    let (s, r) = unbounded::<i32>();
+
+    // Local work is produced while the channel is empty, and messages come first otherwise.
+    let mut local = vec![20, 10];
+    assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(10));
+    s.send(1).unwrap();
+    assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(1));
+    assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(20));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // Without local work, the thread eventually blocks until a message arrives.
+            let mut calls = 0;
+            assert_eq!(
+                r.recv_or_spawn(|| {
+                    calls += 1;
+                    None
+                }),
+                Ok(2)
+            );
+            assert!(calls > 0);
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(2).unwrap();
+        });
+    })
+    .unwrap();
+
+    drop(s);
+    let mut local = vec![30];
+    assert_eq!(r.recv_or_spawn(|| local.pop()), Ok(30));
+    assert_eq!(r.recv_or_spawn(|| local.pop()), Err(RecvError));
+}
+
+#[test]
+fn recv_nonblocking_batch_into() {
+    let (s, r) = unbounded();
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+
+    // Only the spare capacity is used.
+    let mut buf = Vec::with_capacity(4);
+    buf.push(-1);
+    let cap = buf.capacity();
+    assert_eq!(r.recv_nonblocking_batch_into(&mut buf), cap - 1);
+    assert_eq!(buf.capacity(), cap);
+    assert_eq!(buf[..4], [-1, 0, 1, 2]);
+
+    // A full buffer receives nothing.
+    assert_eq!(r.recv_nonblocking_batch_into(&mut buf), 0);
+
+    let mut deque = VecDeque::with_capacity(100);
+    let n = r.recv_nonblocking_batch_into(&mut deque);
+    assert_eq!(n, 10 - (cap - 1));
+    assert_eq!(deque.back(), Some(&9));
+
+    drop(s);
+    assert_eq!(r.recv_nonblocking_batch_into(&mut deque), 0);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn recv_in_place() {
+    let (s, r) = unbounded();
+    s.send(String::from("a")).unwrap();
+
+    let mut msg = r.recv_in_place().unwrap();
+    msg.push('b');
+    assert_eq!(*msg, "ab");
+    assert_eq!(msg.into_inner(), "ab");
+
+    drop(s);
+    assert!(r.recv_in_place().is_err());
+}
+
+#[test]
+fn recv_latest() {
+    let (s, r) = unbounded();
+    assert_eq!(r.try_recv_latest(), Err(TryRecvError::Empty));
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv_latest(), Ok(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s.send(10).unwrap();
+        });
+
+        // Blocks until a message arrives.
+        assert_eq!(r.recv_latest(), Ok(10));
+    })
+    .unwrap();
+
+    s.send(11).unwrap();
+    drop(s);
+    assert_eq!(r.try_recv_latest(), Ok(11));
+    assert_eq!(r.recv_latest(), Err(RecvError));
+    assert_eq!(r.try_recv_latest(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn recv_latest_drops_older() {
+    const COUNT: usize = 100;
+
+    let dropped = AtomicUsize::new(0);
+    let (s, r) = unbounded::<DropCounter>();
+    for i in 0..COUNT {
+        s.send(DropCounter(i, &dropped)).unwrap();
+    }
+
+    let latest = r.recv_latest().unwrap();
+    assert_eq!(latest.0, COUNT - 1);
+    assert_eq!(dropped.load(Ordering::SeqCst), COUNT - 1);
+}
+
+#[test]
+fn clone_n() {
+    let (s, r) = unbounded();
+    let senders = s.clone_n(3);
+    assert_eq!(senders.len(), 3);
+    assert!(senders.iter().all(|x| x.same_channel(&s)));
+    drop(s);
+
+    for (i, s) in senders.iter().enumerate() {
+        s.send(i).unwrap();
+    }
+    drop(senders);
+
+    assert_eq!(r.iter().collect::<Vec<_>>(), [0, 1, 2]);
+}
+
+struct DropCounter<'a>(usize, &'a AtomicUsize);
+
+impl<'a> Drop for DropCounter<'a> {
+    fn drop(&mut self) {
+        self.1.fetch_add(1, Ordering::SeqCst);
+    }
+}
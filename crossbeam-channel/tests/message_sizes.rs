@@ -0,0 +1,82 @@
+//! Tests for message size statistics.
+
+#![cfg(feature = "size-stats")]
+
+extern crate crossbeam_channel;
+
+use crossbeam_channel::instrument;
+use crossbeam_channel::{bounded, unbounded};
+
+/// Returns the number of messages sent so far in the class covering sizes up to `max`.
+fn class_count(max: Option<usize>) -> usize {
+    instrument::message_sizes()
+        .class_counts()
+        .into_iter()
+        .find(|&(m, _)| m == max)
+        .unwrap()
+        .1
+}
+
+#[test]
+fn size_classes() {
+    // Other tests don't send messages of these sizes.
+    let before_2048 = class_count(Some(2048));
+    let before_large = class_count(None);
+
+    let (s, r) = unbounded();
+    for _ in 0..3 {
+        s.send([0u8; 2000]).unwrap();
+    }
+    let (s2, r2) = bounded(1);
+    s2.send([0u8; 5000]).unwrap();
+    drop((r, r2));
+
+    assert_eq!(class_count(Some(2048)), before_2048 + 3);
+    assert_eq!(class_count(None), before_large + 1);
+}
+
+#[test]
+fn totals() {
+    let before = instrument::message_sizes();
+
+    let (s, r) = unbounded();
+    s.send(1u64).unwrap();
+    s.try_send(2u64).unwrap();
+    drop(r);
+
+    let after = instrument::message_sizes();
+    assert!(after.messages() >= before.messages() + 2);
+    assert!(after.bytes() >= before.bytes() + 16);
+}
+
+#[test]
+fn class_bounds() {
+    let classes = instrument::message_sizes().class_counts();
+    let bounds: Vec<_> = classes.iter().map(|&(max, _)| max).collect();
+    assert_eq!(
+        bounds,
+        [
+            Some(8),
+            Some(16),
+            Some(32),
+            Some(64),
+            Some(128),
+            Some(256),
+            Some(512),
+            Some(1024),
+            Some(2048),
+            Some(4096),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn large_message_threshold() {
+    // Only prints a warning, so this just checks that sending goes through.
+    instrument::set_large_message_threshold(16);
+    let (s, r) = unbounded();
+    s.send([0u8; 32]).unwrap();
+    assert_eq!(r.recv().unwrap().len(), 32);
+    instrument::set_large_message_threshold(1024);
+}
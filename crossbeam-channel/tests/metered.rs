@@ -0,0 +1,180 @@
+//! Tests for publishing channel metrics.
+
+#![cfg(feature = "metrics")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate metrics;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, metered, unbounded, Sender};
+use crossbeam_utils::thread::scope;
+use metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Samples recorded into a histogram.
+#[derive(Default)]
+struct Samples(Mutex<Vec<f64>>);
+
+impl HistogramFn for Samples {
+    fn record(&self, value: f64) {
+        self.0.lock().unwrap().push(value);
+    }
+}
+
+/// A recorder keeping metrics in memory, keyed by name and `channel` label.
+#[derive(Default)]
+struct TestRecorder {
+    values: Mutex<HashMap<String, Arc<AtomicU64>>>,
+    samples: Mutex<HashMap<String, Arc<Samples>>>,
+}
+
+impl TestRecorder {
+    fn value(&self, key: &Key) -> Arc<AtomicU64> {
+        let mut values = self.values.lock().unwrap();
+        values.entry(name(key)).or_default().clone()
+    }
+
+    fn counter(&self, name: &str, channel: &str) -> u64 {
+        let values = self.values.lock().unwrap();
+        values[&format!("{}/{}", name, channel)].load(Ordering::SeqCst)
+    }
+
+    fn gauge(&self, name: &str, channel: &str) -> f64 {
+        f64::from_bits(self.counter(name, channel))
+    }
+
+    fn samples(&self, name: &str, channel: &str) -> Vec<f64> {
+        let samples = self.samples.lock().unwrap();
+        let samples = samples[&format!("{}/{}", name, channel)].0.lock().unwrap();
+        samples.clone()
+    }
+}
+
+fn name(key: &Key) -> String {
+    let channel = key.labels().find(|l| l.key() == "channel").unwrap();
+    format!("{}/{}", key.name(), channel.value())
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata) -> Counter {
+        Counter::from_arc(self.value(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata) -> Gauge {
+        Gauge::from_arc(self.value(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata) -> Histogram {
+        let mut samples = self.samples.lock().unwrap();
+        Histogram::from_arc(samples.entry(name(key)).or_default().clone())
+    }
+}
+
+/// Registers the channel with a fresh recorder.
+fn register<T>(name: &str, s: &Sender<T>) -> TestRecorder {
+    let recorder = TestRecorder::default();
+    metrics::with_local_recorder(&recorder, || metered::register(name, s));
+    recorder
+}
+
+#[test]
+fn counts() {
+    let (s, r) = unbounded();
+    s.send(0).unwrap();
+
+    let rec = register("counts", &s);
+    assert_eq!(rec.gauge("crossbeam_channel_length", "counts"), 1.0);
+
+    s.send(1).unwrap();
+    s.try_send(2).unwrap();
+    s.send_timeout(3, ms(100)).unwrap();
+    assert_eq!(rec.counter("crossbeam_channel_sent", "counts"), 3);
+    assert_eq!(rec.gauge("crossbeam_channel_length", "counts"), 4.0);
+
+    assert_eq!(r.recv(), Ok(0));
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(rec.counter("crossbeam_channel_received", "counts"), 2);
+    assert_eq!(rec.gauge("crossbeam_channel_length", "counts"), 2.0);
+
+    // Failed operations are not counted.
+    drop(r);
+    assert!(s.send(4).is_err());
+    assert_eq!(rec.counter("crossbeam_channel_sent", "counts"), 3);
+}
+
+#[test]
+fn failed_recv() {
+    let (s, r) = bounded::<i32>(1);
+    let rec = register("failed_recv", &s);
+
+    assert!(r.try_recv().is_err());
+    assert!(r.recv_timeout(ms(50)).is_err());
+    assert_eq!(rec.counter("crossbeam_channel_received", "failed_recv"), 0);
+    assert_eq!(rec.gauge("crossbeam_channel_length", "failed_recv"), 0.0);
+}
+
+#[test]
+fn blocked() {
+    let (s, r) = bounded(0);
+    let rec = register("blocked", &s);
+
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Ok(1)));
+        thread::sleep(ms(500));
+        s.send(1).unwrap();
+    })
+    .unwrap();
+
+    let samples = rec.samples("crossbeam_channel_blocked_seconds", "blocked");
+    assert!(samples.iter().any(|&secs| secs >= 0.4));
+    assert_eq!(rec.counter("crossbeam_channel_sent", "blocked"), 1);
+    assert_eq!(rec.counter("crossbeam_channel_received", "blocked"), 1);
+}
+
+#[test]
+fn unregister() {
+    let (s, r) = unbounded();
+    let rec = register("unregister", &s);
+
+    s.send(1).unwrap();
+    metered::unregister(&s);
+    s.send(2).unwrap();
+    r.recv().unwrap();
+
+    assert_eq!(rec.counter("crossbeam_channel_sent", "unregister"), 1);
+    assert_eq!(rec.counter("crossbeam_channel_received", "unregister"), 0);
+    assert_eq!(rec.gauge("crossbeam_channel_length", "unregister"), 1.0);
+}
+
+#[test]
+fn separate_channels() {
+    let (s1, r1) = unbounded();
+    let (s2, _r2) = unbounded();
+    let rec = register("first", &s1);
+
+    s2.send(1).unwrap();
+    s1.send(1).unwrap();
+    assert_eq!(rec.counter("crossbeam_channel_sent", "first"), 1);
+
+    // Destroying a channel unregisters it, even if a new one takes its place in memory.
+    drop(s1);
+    drop(r1);
+    let (s3, _r3) = unbounded();
+    s3.send(1).unwrap();
+    assert_eq!(rec.counter("crossbeam_channel_sent", "first"), 1);
+}
@@ -0,0 +1,175 @@
+//! Tests for `Notify`.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::sync::Notify;
+use crossbeam_channel::unbounded;
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn permit() {
+    let notify = Notify::new();
+    assert!(!notify.wait_timeout(ms(50)));
+
+    // Only one permit is stored.
+    notify.notify_one();
+    notify.notify_one();
+    assert!(notify.wait_timeout(ms(50)));
+    assert!(!notify.wait_timeout(ms(50)));
+}
+
+#[test]
+fn notify_all_stores_no_permit() {
+    let notify = Notify::new();
+    notify.notify_all();
+    assert!(!notify.notified().is_notified());
+}
+
+#[test]
+fn notify_one_wakes_one() {
+    let notify = Notify::new();
+    let woken = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..3 {
+            scope.spawn(|_| {
+                if notify.wait_timeout(ms(1000)) {
+                    woken.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+
+        thread::sleep(ms(300));
+        notify.notify_one();
+        thread::sleep(ms(300));
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+    })
+    .unwrap();
+
+    assert_eq!(woken.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn notify_all_wakes_all() {
+    let notify = Notify::new();
+
+    scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| {
+                let now = Instant::now();
+                notify.wait();
+                assert!(now.elapsed() >= ms(200));
+            });
+        }
+
+        thread::sleep(ms(300));
+        notify.notify_all();
+    })
+    .unwrap();
+}
+
+#[test]
+fn registered_before_notify() {
+    let notify = Notify::new();
+    let notified = notify.notified();
+    assert!(!notified.is_notified());
+
+    notify.notify_all();
+    assert!(notified.is_notified());
+    notified.wait();
+    assert!(!notified.is_notified());
+}
+
+#[test]
+fn fifo() {
+    let notify = Notify::new();
+    let first = notify.notified();
+    let second = notify.notified();
+
+    notify.notify_one();
+    assert!(first.is_notified());
+    assert!(!second.is_notified());
+}
+
+#[test]
+fn dropped_notification_passed_on() {
+    let notify = Notify::new();
+    let first = notify.notified();
+    let second = notify.notified();
+
+    notify.notify_one();
+    drop(first);
+    assert!(second.is_notified());
+
+    // With nobody else waiting, it becomes a permit.
+    let only = notify.notified();
+    notify.notify_one();
+    drop(only);
+    assert!(notify.wait_timeout(ms(0)));
+
+    // Notifications from `notify_all` are not passed on.
+    let (a, b) = (notify.notified(), notify.notified());
+    notify.notify_all();
+    drop(a);
+    drop(b);
+    assert!(!notify.notified().is_notified());
+}
+
+#[test]
+fn select() {
+    let notify = Notify::new();
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let notified = notify.notified();
+            select! {
+                recv(r) -> _ => panic!(),
+                recv(notified) -> msg => assert_eq!(msg, Ok(())),
+            }
+        });
+
+        thread::sleep(ms(300));
+        notify.notify_one();
+    })
+    .unwrap();
+
+    drop(s);
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let notify = Notify::new();
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    notify.wait();
+                    received.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+
+        // Every notification either wakes a waiter or becomes a permit, so none are lost.
+        while received.load(Ordering::SeqCst) < THREADS * COUNT {
+            notify.notify_one();
+            thread::yield_now();
+        }
+    })
+    .unwrap();
+}
@@ -0,0 +1,123 @@
+//! Tests for nurseries of scoped workers.
+
+#[macro_use]
+extern crate crossbeam_channel;
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{nursery, unbounded, CancelToken};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn empty() {
+    let res: Result<Vec<i32>, ()> = nursery(|_| {});
+    assert_eq!(res, Ok(vec![]));
+}
+
+#[test]
+fn results_in_spawn_order() {
+    let res: Result<Vec<usize>, ()> = nursery(|n| {
+        for i in 0..10 {
+            let index = n.spawn(move |_| {
+                thread::sleep(ms(10 * (10 - i as u64)));
+                Ok(i * 10)
+            });
+            assert_eq!(index, i);
+        }
+    });
+    assert_eq!(res, Ok((0..10).map(|i| i * 10).collect()));
+}
+
+#[test]
+fn borrows() {
+    let data = vec![1, 2, 3];
+    let sum = AtomicUsize::new(0);
+
+    let res: Result<Vec<()>, ()> = nursery(|n| {
+        for &x in &data {
+            let sum = &sum;
+            n.spawn(move |_| {
+                sum.fetch_add(x, Ordering::SeqCst);
+                Ok(())
+            });
+        }
+    });
+    assert!(res.is_ok());
+    assert_eq!(sum.load(Ordering::SeqCst), 6);
+}
+
+#[test]
+fn error_cancels() {
+    let (s, r) = unbounded::<i32>();
+
+    let res = nursery(|n| {
+        for _ in 0..4 {
+            let r = &r;
+            n.spawn(move |cancel| loop {
+                select! {
+                    recv(r) -> _ => {}
+                    recv(cancel) -> _ => return Ok(()),
+                }
+            });
+        }
+        n.spawn(|_| {
+            thread::sleep(ms(100));
+            Err("first")
+        });
+        n.spawn(|cancel| {
+            let _ = cancel.recv();
+            Err("second")
+        });
+    });
+
+    assert_eq!(res, Err("first"));
+    drop(s);
+}
+
+#[test]
+fn panic_cancels() {
+    let res = panic::catch_unwind(|| {
+        let _: Result<Vec<()>, ()> = nursery(|n| {
+            n.spawn(|cancel| {
+                let _ = cancel.recv();
+                Err(())
+            });
+            n.spawn(|_| panic!("worker failed"));
+        });
+    });
+
+    let payload = res.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"worker failed"));
+}
+
+#[test]
+fn manual_cancel() {
+    let res: Result<Vec<i32>, ()> = nursery(|n| {
+        n.spawn(|cancel| {
+            let _ = cancel.recv();
+            Ok(1)
+        });
+        assert!(!n.cancel_token().is_cancelled());
+        n.cancel_token().cancel();
+    });
+    assert_eq!(res, Ok(vec![1]));
+}
+
+#[test]
+fn cancel_token() {
+    let token = CancelToken::new();
+    assert!(!token.is_cancelled());
+
+    let clone = token.clone();
+    token.cancel();
+    token.cancel();
+    assert!(clone.is_cancelled());
+    assert!(clone.recv().is_err());
+    assert_eq!(format!("{:?}", clone), "CancelToken { cancelled: true }");
+}
@@ -0,0 +1,134 @@
+//! Tests for observing channel versions.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, tick, unbounded, ChannelObserver};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn array() {
+    let (s, r) = bounded(2);
+    let sv = s.version();
+    let rv = r.version();
+
+    s.send(1).unwrap();
+    assert!(r.has_changed(rv));
+    assert!(!s.has_changed(sv));
+
+    let rv = r.version();
+    assert_eq!(r.recv(), Ok(1));
+    assert!(s.has_changed(sv));
+    assert!(!r.has_changed(rv));
+
+    // Wrap around the buffer a few times.
+    for i in 0..10 {
+        let v = r.version();
+        s.send(i).unwrap();
+        assert!(r.version() > v);
+        let v = s.version();
+        r.recv().unwrap();
+        assert!(s.version() > v);
+    }
+
+    let rv = r.version();
+    drop(s);
+    assert!(r.has_changed(rv));
+
+    let (s, r) = bounded::<i32>(1);
+    let sv = s.version();
+    drop(r);
+    assert!(s.has_changed(sv));
+}
+
+#[test]
+fn list() {
+    let (s, r) = unbounded();
+    let sv = s.version();
+    let rv = r.version();
+
+    for i in 0..100 {
+        let v = r.version();
+        s.send(i).unwrap();
+        assert!(r.version() > v);
+    }
+    assert!(r.has_changed(rv));
+    assert!(!s.has_changed(sv));
+
+    for _ in 0..100 {
+        let v = s.version();
+        r.recv().unwrap();
+        assert!(s.version() > v);
+    }
+
+    let rv = r.version();
+    drop(s);
+    assert!(r.has_changed(rv));
+
+    let (s, r) = unbounded::<i32>();
+    let sv = s.version();
+    drop(r);
+    assert!(s.has_changed(sv));
+}
+
+#[test]
+fn zero() {
+    let (s, r) = bounded(0);
+    let rv = r.version();
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(7).unwrap());
+
+        while !r.has_changed(rv) {
+            thread::yield_now();
+        }
+        assert_eq!(r.recv(), Ok(7));
+    })
+    .unwrap();
+
+    let sv = s.version();
+    drop(r);
+    assert!(s.has_changed(sv));
+}
+
+#[test]
+fn after_tick_never() {
+    let r = after(ms(100));
+    let v = r.version();
+    thread::sleep(ms(200));
+    assert!(r.has_changed(v));
+    let v = r.version();
+    r.recv().unwrap();
+    assert!(r.has_changed(v));
+
+    let r = tick(ms(100));
+    let v = r.version();
+    thread::sleep(ms(150));
+    assert!(r.has_changed(v));
+    let v = r.version();
+    r.recv().unwrap();
+    assert!(r.has_changed(v));
+    assert!(!r.has_changed(r.version()));
+
+    let r = never::<i32>();
+    let v = r.version();
+    thread::sleep(ms(100));
+    assert!(!r.has_changed(v));
+}
+
+#[test]
+fn unique() {
+    let (s, r) = unbounded();
+    let r = r.into_unique().unwrap();
+    let v = r.version();
+
+    s.send(1).unwrap();
+    assert!(r.has_changed(v));
+}
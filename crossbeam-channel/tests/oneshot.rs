@@ -0,0 +1,151 @@
+//! Tests for oneshot channels placed on the stack.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::oneshot::Slot;
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let mut slot = Slot::new();
+    let (s, r) = slot.split();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn disconnected() {
+    let mut slot = Slot::<i32>::new();
+
+    {
+        let (s, r) = slot.split();
+        drop(s);
+        assert_eq!(r.recv(), Err(RecvError));
+    }
+
+    let (s, r) = slot.split();
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+}
+
+#[test]
+fn recv_timeout() {
+    let mut slot = Slot::new();
+    let (s, r) = slot.split();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            s.send(1).unwrap();
+        });
+
+        assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+        assert_eq!(r.recv_timeout(ms(500)), Ok(1));
+        assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Disconnected));
+    })
+    .unwrap();
+}
+
+#[test]
+fn wakes_receiver_on_drop() {
+    let mut slot = Slot::<i32>::new();
+    let (s, r) = slot.split();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(s);
+        });
+
+        let start = Instant::now();
+        assert_eq!(r.recv(), Err(RecvError));
+        assert!(start.elapsed() >= ms(80));
+    })
+    .unwrap();
+}
+
+#[test]
+fn handoff() {
+    let mut slot = Slot::new();
+
+    {
+        let (s, r) = slot.split();
+        scope(|scope| {
+            scope.spawn(move |_| {
+                thread::sleep(ms(100));
+                assert_eq!(r.recv(), Ok(5));
+            });
+
+            let start = Instant::now();
+            assert_eq!(s.handoff(5), Ok(()));
+            assert!(start.elapsed() >= ms(80));
+        })
+        .unwrap();
+    }
+
+    let (s, r) = slot.split();
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(100));
+            drop(r);
+        });
+
+        assert_eq!(s.handoff(6), Err(SendError(6)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn reuse() {
+    const ROUNDS: usize = 1000;
+
+    let mut request = Slot::new();
+    let mut response = Slot::new();
+
+    for i in 0..ROUNDS {
+        let (req_s, req_r) = request.split();
+        let (resp_s, resp_r) = response.split();
+
+        scope(|scope| {
+            scope.spawn(move |_| {
+                let n = req_r.recv().unwrap();
+                resp_s.send(n * 2).unwrap();
+            });
+
+            req_s.send(i).unwrap();
+            assert_eq!(resp_r.recv(), Ok(i * 2));
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn leftover_dropped() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let mut slot = Slot::new();
+
+    {
+        let (s, r) = slot.split();
+        s.send(rc.clone()).unwrap();
+        drop(r);
+        assert_eq!(Rc::strong_count(&rc), 2);
+    }
+
+    slot.split();
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
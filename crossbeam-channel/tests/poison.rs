@@ -0,0 +1,166 @@
+//! Tests for poisoning channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::poison::{self, PoisonReceiver};
+use crossbeam_channel::poison::{
+    RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError,
+};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Receives a message and panics while handling it.
+fn fail<T>(r: &PoisonReceiver<T>) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _msg = r.recv().unwrap();
+        panic!("handling failed");
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = poison::unbounded();
+    s.send(7).unwrap();
+
+    let mut msg = r.recv().unwrap();
+    assert_eq!(*msg, 7);
+    *msg += 1;
+    assert_eq!(msg.into_inner(), 8);
+    assert!(!r.is_poisoned());
+}
+
+#[test]
+fn poisons_on_panic() {
+    let (s, r) = poison::bounded(2);
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    fail(&r);
+    assert!(r.is_poisoned());
+    assert!(s.is_poisoned());
+
+    assert_eq!(s.send(3), Err(SendError::Poisoned(3)));
+    assert_eq!(s.try_send(3), Err(TrySendError::Poisoned(3)));
+    assert_eq!(r.recv().unwrap_err(), RecvError::Poisoned);
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Poisoned);
+    assert_eq!(
+        r.recv_timeout(ms(50)).unwrap_err(),
+        RecvTimeoutError::Poisoned
+    );
+
+    s.clear_poison();
+    assert!(!r.is_poisoned());
+    assert_eq!(*r.recv().unwrap(), 2);
+}
+
+#[test]
+fn completed_handling() {
+    let (s, r) = poison::unbounded();
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+
+    // A message that was handled doesn't poison the channel.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        drop(r.recv().unwrap());
+        panic!("unrelated failure");
+    }));
+    assert!(result.is_err());
+    assert!(!r.is_poisoned());
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let msg = r.recv().unwrap().into_inner();
+        panic!("failed after unwrapping {}", msg);
+    }));
+    assert!(result.is_err());
+    assert!(!r.is_poisoned());
+}
+
+#[test]
+fn errors() {
+    let (s, r) = poison::bounded(1);
+    s.try_send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.try_recv().unwrap().into_inner(), 1);
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Empty);
+    assert_eq!(
+        r.recv_timeout(ms(50)).unwrap_err(),
+        RecvTimeoutError::Timeout
+    );
+
+    drop(s);
+    assert_eq!(r.recv().unwrap_err(), RecvError::Disconnected);
+    assert_eq!(r.try_recv().unwrap_err(), TryRecvError::Disconnected);
+
+    let (s, r) = poison::unbounded::<i32>();
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError::Disconnected(1)));
+    assert_eq!(s.send(1).unwrap_err().into_inner(), 1);
+}
+
+#[test]
+fn drops_messages() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = poison::unbounded();
+    s.send(DropCounter).unwrap();
+    s.send(DropCounter).unwrap();
+
+    drop(r.recv().unwrap());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    let msg = r.recv().unwrap().into_inner();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    drop(msg);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn worker_pool() {
+    const COUNT: usize = 100;
+
+    let (s, r) = poison::unbounded();
+    let handled = AtomicUsize::new(0);
+
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        for _ in 0..4 {
+            let r = r.clone();
+            let handled = &handled;
+            scope.spawn(move |_| {
+                while let Ok(msg) = r.try_recv() {
+                    if *msg == COUNT / 2 {
+                        panic!("bad message");
+                    }
+                    handled.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(ms(1));
+                }
+            });
+        }
+    })
+    .unwrap_err();
+
+    // Workers stopped once one of them failed.
+    assert!(r.is_poisoned());
+    assert!(handled.load(Ordering::SeqCst) < COUNT - 1);
+}
@@ -0,0 +1,146 @@
+//! Tests for waiting on pollable readiness sources.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{unbounded, Pollable, PollableSource, ReadyNotifier, Select};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A source that is ready while its flag is raised.
+#[derive(Default)]
+struct Flag {
+    raised: AtomicBool,
+    notifier: Mutex<Option<ReadyNotifier>>,
+    installed: AtomicUsize,
+}
+
+impl Flag {
+    fn raise(&self) {
+        self.raised.store(true, Ordering::SeqCst);
+        if let Some(notifier) = &*self.notifier.lock().unwrap() {
+            notifier.notify();
+        }
+    }
+
+    fn lower(&self) {
+        self.raised.store(false, Ordering::SeqCst);
+    }
+}
+
+impl<'a> PollableSource for &'a Flag {
+    fn is_ready(&self) -> bool {
+        self.raised.load(Ordering::SeqCst)
+    }
+
+    fn set_notifier(&self, notifier: ReadyNotifier) {
+        self.installed.fetch_add(1, Ordering::SeqCst);
+        *self.notifier.lock().unwrap() = Some(notifier);
+    }
+}
+
+#[test]
+fn smoke() {
+    let flag = Flag::default();
+    let p = Pollable::new(&flag);
+    assert_eq!(flag.installed.load(Ordering::SeqCst), 1);
+    assert!(!p.is_ready());
+
+    let mut sel = Select::new();
+    let index = sel.poll(&p);
+    assert!(sel.try_select().is_err());
+
+    flag.raise();
+    assert!(p.is_ready());
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), index);
+    assert!(!oper.is_disconnected());
+    oper.poll(&p);
+}
+
+#[test]
+fn wakes_select() {
+    let flag = Flag::default();
+    let p = Pollable::new(&flag);
+    let (_s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            flag.raise();
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r);
+        let index = sel.poll(&p);
+
+        let start = Instant::now();
+        let oper = sel.select();
+        assert_eq!(oper.index(), index);
+        oper.poll(&p);
+        assert!(start.elapsed() >= ms(80));
+    })
+    .unwrap();
+}
+
+#[test]
+fn wakes_ready() {
+    let flag = Flag::default();
+    let p = Pollable::new(&flag);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            flag.raise();
+        });
+
+        let mut sel = Select::new();
+        sel.poll(&p);
+        assert_eq!(sel.ready_timeout(ms(1000)), Ok(0));
+    })
+    .unwrap();
+}
+
+#[test]
+fn channel_first() {
+    let flag = Flag::default();
+    let p = Pollable::new(&flag);
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.poll(&p);
+    sel.recv(&r);
+
+    let oper = sel.select_timeout(ms(1000)).unwrap();
+    assert_eq!(oper.index(), 1);
+    assert_eq!(oper.recv(&r), Ok(1));
+
+    // The source stops being ready once it's lowered again.
+    flag.raise();
+    flag.lower();
+    assert!(sel.select_timeout(ms(50)).is_err());
+}
+
+#[test]
+fn remove_disconnected_keeps_pollable() {
+    let flag = Flag::default();
+    let p = Pollable::new(&flag);
+    flag.raise();
+
+    let mut sel = Select::new();
+    sel.set_remove_disconnected(true);
+    sel.poll(&p);
+
+    let oper = sel.select();
+    oper.poll(&p);
+    assert!(!sel.is_empty());
+}
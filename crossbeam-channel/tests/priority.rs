@@ -0,0 +1,155 @@
+//! Tests for priority inheritance.
+
+#![cfg(feature = "priority-inheritance")]
+
+#[macro_use]
+extern crate lazy_static;
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::Mutex;
+use std::thread::{self, Thread, ThreadId};
+use std::time::Duration;
+
+use crossbeam_channel::priority::{self, PriorityHook};
+use crossbeam_channel::{bounded, never, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+lazy_static! {
+    /// Serializes tests, since the hook is global.
+    static ref LOCK: Mutex<()> = Mutex::new(());
+
+    /// Calls made to the hook, as `(boost, consumer, sender)`.
+    static ref CALLS: Mutex<Vec<(bool, ThreadId, ThreadId)>> = Mutex::new(Vec::new());
+}
+
+struct Record;
+
+impl PriorityHook for Record {
+    fn boost(&self, consumer: &Thread, sender: &Thread) {
+        CALLS
+            .lock()
+            .unwrap()
+            .push((true, consumer.id(), sender.id()));
+    }
+
+    fn restore(&self, consumer: &Thread, sender: &Thread) {
+        CALLS
+            .lock()
+            .unwrap()
+            .push((false, consumer.id(), sender.id()));
+    }
+}
+
+fn take_calls() -> Vec<(bool, ThreadId, ThreadId)> {
+    let mut calls = CALLS.lock().unwrap();
+    let taken = calls.drain(..).collect();
+    taken
+}
+
+#[test]
+fn boost_and_restore() {
+    let _lock = LOCK.lock().unwrap();
+    priority::set_priority_hook(Record);
+    take_calls();
+
+    let (s, r) = bounded(1);
+    let (registered_s, registered_r) = bounded(0);
+
+    scope(|scope| {
+        let consumer = scope.spawn(|_| {
+            let _registration = priority::register_consumer(&r);
+            registered_s.send(()).unwrap();
+            thread::sleep(ms(300));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+            thread::current().id()
+        });
+
+        registered_r.recv().unwrap();
+        assert!(priority::consumer(&r).is_some());
+
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+
+        let consumer = consumer.join().unwrap();
+        let sender = thread::current().id();
+        assert_eq!(
+            take_calls(),
+            [(true, consumer, sender), (false, consumer, sender)]
+        );
+    })
+    .unwrap();
+
+    // The registration ended with the consumer thread.
+    assert!(priority::consumer(&r).is_none());
+    priority::clear_priority_hook();
+}
+
+#[test]
+fn no_boost_without_registration() {
+    let _lock = LOCK.lock().unwrap();
+    priority::set_priority_hook(Record);
+    take_calls();
+
+    let (s, r) = bounded(1);
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            r.recv().unwrap();
+        });
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+    })
+    .unwrap();
+
+    assert!(take_calls().is_empty());
+    priority::clear_priority_hook();
+}
+
+#[test]
+fn no_boost_without_hook() {
+    let _lock = LOCK.lock().unwrap();
+    priority::clear_priority_hook();
+    take_calls();
+
+    let (s, r) = bounded(1);
+    let _registration = priority::register_consumer(&r);
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            r.recv().unwrap();
+        });
+        s.send(1).unwrap();
+        s.send(2).unwrap();
+    })
+    .unwrap();
+
+    assert!(take_calls().is_empty());
+}
+
+#[test]
+fn registration() {
+    let (_s, r) = unbounded::<i32>();
+    assert!(priority::consumer(&r).is_none());
+
+    let registration = priority::register_consumer(&r);
+    assert_eq!(
+        priority::consumer(&r).map(|t| t.id()),
+        Some(thread::current().id())
+    );
+
+    // A clone of the receiver belongs to the same channel.
+    assert!(priority::consumer(&r.clone()).is_some());
+
+    drop(registration);
+    assert!(priority::consumer(&r).is_none());
+
+    let r = never::<i32>();
+    let _registration = priority::register_consumer(&r);
+    assert!(priority::consumer(&r).is_none());
+}
@@ -0,0 +1,151 @@
+//! Tests for promises.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::sync::Promise;
+use crossbeam_channel::unbounded;
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let promise = Promise::new();
+    assert!(!promise.is_complete());
+    assert_eq!(promise.get(), None);
+
+    assert_eq!(promise.complete(7), Ok(()));
+    assert!(promise.is_complete());
+    assert_eq!(promise.get(), Some(&7));
+    assert_eq!(promise.wait(), &7);
+
+    assert_eq!(promise.complete(8), Err(8));
+    assert_eq!(promise.get(), Some(&7));
+}
+
+#[test]
+fn wait() {
+    let promise = Promise::new();
+
+    scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| assert_eq!(promise.wait(), "done"));
+        }
+
+        thread::sleep(ms(100));
+        promise.complete(String::from("done")).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_timeout() {
+    let promise = Promise::new();
+
+    let start = Instant::now();
+    assert_eq!(promise.wait_timeout(ms(100)), None);
+    assert!(start.elapsed() >= ms(100));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            promise.complete(1).unwrap();
+        });
+        assert_eq!(promise.wait_timeout(ms(1000)), Some(&1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn exactly_once() {
+    const THREADS: usize = 8;
+
+    for _ in 0..100 {
+        let promise = Promise::new();
+        let winners = AtomicUsize::new(0);
+        let returned = AtomicUsize::new(0);
+
+        scope(|scope| {
+            for i in 0..THREADS {
+                let promise = promise.clone();
+                let winners = &winners;
+                let returned = &returned;
+                scope.spawn(move |_| match promise.complete(i) {
+                    Ok(()) => {
+                        winners.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Err(v) => {
+                        assert_eq!(v, i);
+                        returned.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        assert_eq!(winners.load(Ordering::SeqCst), 1);
+        assert_eq!(returned.load(Ordering::SeqCst), THREADS - 1);
+        assert!(*promise.wait() < THREADS);
+    }
+}
+
+#[test]
+fn select() {
+    let (s, r) = unbounded::<i32>();
+    let promise = Promise::new();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            promise.complete(5).unwrap();
+        });
+
+        select! {
+            recv(r) -> _ => panic!(),
+            recv(promise.ready()) -> msg => {
+                assert!(msg.is_err());
+                assert_eq!(promise.get(), Some(&5));
+            }
+        }
+    })
+    .unwrap();
+
+    // The receiver stays ready.
+    select! {
+        recv(promise.ready()) -> _ => {}
+        default => panic!(),
+    }
+    drop(s);
+}
+
+#[test]
+fn drops_value() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let promise = Promise::new();
+    let clone = promise.clone();
+    assert!(promise.complete(DropCounter).is_ok());
+    assert!(promise.complete(DropCounter).is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(promise);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    drop(clone);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,125 @@
+//! Tests for racing receivers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, never, unbounded, Receiver, RecvError};
+use crossbeam_channel::{race, race_slice, race_slice_timeout, race_timeout, Either};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<String>();
+
+    s1.send(1).unwrap();
+    assert_eq!(race(&r1, &r2), Either::Left(Ok(1)));
+
+    s2.send("two".to_string()).unwrap();
+    assert_eq!(race(&r1, &r2), Either::Right(Ok("two".to_string())));
+}
+
+#[test]
+fn disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    drop(s2);
+    assert_eq!(race(&r1, &r2), Either::Right(Err(RecvError)));
+    drop(s1);
+}
+
+#[test]
+fn blocks() {
+    let (s1, r1) = bounded::<i32>(0);
+    let (s2, r2) = bounded::<char>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(200));
+            s2.send('x').unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(race(&r1, &r2), Either::Right(Ok('x')));
+        assert!(start.elapsed() >= ms(100));
+    })
+    .unwrap();
+    drop(s1);
+}
+
+#[test]
+fn timeout() {
+    let r1 = never::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    let start = Instant::now();
+    assert!(race_timeout(&r1, &r2, ms(100)).is_err());
+    assert!(start.elapsed() >= ms(100));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+        });
+
+        let res = race_timeout(&r1, &r2, ms(1000)).unwrap();
+        assert_eq!(res.right(), Some(Ok(2)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn either() {
+    let l: Either<i32, &str> = Either::Left(1);
+    let r: Either<i32, &str> = Either::Right("r");
+
+    assert!(l.is_left());
+    assert!(!l.is_right());
+    assert!(r.is_right());
+    assert_eq!(l.left(), Some(1));
+    assert_eq!(l.right(), None);
+    assert_eq!(r.left(), None);
+    assert_eq!(r.right(), Some("r"));
+}
+
+#[test]
+fn slice() {
+    let channels = (0..5).map(|_| unbounded()).collect::<Vec<_>>();
+    let receivers = channels
+        .iter()
+        .map(|c| c.1.clone())
+        .collect::<Vec<Receiver<usize>>>();
+
+    for i in (0..5).rev() {
+        channels[i].0.send(i * 10).unwrap();
+        assert_eq!(race_slice(&receivers), (i, Ok(i * 10)));
+    }
+
+    assert!(race_slice_timeout(&receivers, ms(50)).is_err());
+    channels[3].0.send(30).unwrap();
+    assert_eq!(race_slice_timeout(&receivers, ms(50)), Ok((3, Ok(30))));
+}
+
+#[test]
+fn empty_slice() {
+    let receivers: Vec<Receiver<i32>> = Vec::new();
+
+    let start = Instant::now();
+    assert!(race_slice_timeout(&receivers, ms(100)).is_err());
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+#[should_panic]
+fn empty_slice_panics() {
+    let receivers: Vec<Receiver<i32>> = Vec::new();
+    let _ = race_slice(&receivers);
+}
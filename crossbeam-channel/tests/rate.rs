@@ -0,0 +1,207 @@
+//! Tests for rate-limited senders.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, unbounded};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let s = s.rate_limited(10, 1);
+
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    let start = Instant::now();
+    s.send(8).unwrap();
+    assert!(start.elapsed() >= ms(80));
+    assert_eq!(r.try_recv(), Ok(8));
+}
+
+#[test]
+fn burst() {
+    let (s, r) = unbounded();
+    let s = s.rate_limited(10, 3);
+
+    let start = Instant::now();
+    for i in 0..3 {
+        s.send(i).unwrap();
+    }
+    assert!(start.elapsed() < ms(50));
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+
+    s.send(3).unwrap();
+    assert!(start.elapsed() >= ms(80));
+    assert_eq!(r.len(), 4);
+}
+
+#[test]
+fn refill() {
+    let (s, r) = unbounded();
+    let s = s.rate_limited(20, 2);
+
+    s.send(0).unwrap();
+    s.send(1).unwrap();
+    assert!(s.try_send(2).is_err());
+
+    // The bucket refills, but never above the burst size.
+    thread::sleep(ms(300));
+    s.try_send(2).unwrap();
+    s.try_send(3).unwrap();
+    assert_eq!(s.try_send(4), Err(TrySendError::Full(4)));
+    assert_eq!(r.len(), 4);
+}
+
+#[test]
+fn rate() {
+    const COUNT: usize = 10;
+
+    let (s, r) = unbounded();
+    let s = s.rate_limited(50, 1);
+
+    let start = Instant::now();
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+    }
+
+    // The first message goes through right away.
+    assert!(start.elapsed() >= ms(20 * (COUNT as u64 - 1) - 10));
+    assert!(start.elapsed() < ms(20 * COUNT as u64 + 500));
+    assert_eq!(
+        r.try_iter().collect::<Vec<_>>(),
+        (0..COUNT).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn shared_by_clones() {
+    let (s, r) = unbounded();
+    let s1 = s.rate_limited(10, 2);
+    let s2 = s1.clone();
+
+    s1.try_send(1).unwrap();
+    s2.try_send(2).unwrap();
+    assert!(s1.try_send(3).is_err());
+    assert!(s2.try_send(3).is_err());
+
+    // Other senders are not limited.
+    s.send(3).unwrap();
+    s1.as_sender().send(4).unwrap();
+    assert_eq!(r.len(), 4);
+}
+
+#[test]
+fn channel_full() {
+    let (s, r) = bounded(1);
+    let s = s.rate_limited(10, 2);
+
+    s.try_send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+
+    // The failed send didn't take a token.
+    r.recv().unwrap();
+    s.try_send(3).unwrap();
+    assert_eq!(r.recv(), Ok(3));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded();
+    let s = s.rate_limited(10, 1);
+    drop(r);
+
+    assert_eq!(s.try_send(1), Err(TrySendError::Disconnected(1)));
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(10)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+}
+
+#[test]
+fn send_timeout() {
+    let (s, r) = unbounded();
+    let s = s.rate_limited(5, 1);
+
+    s.send(1).unwrap();
+
+    // No token becomes available in time.
+    let start = Instant::now();
+    assert_eq!(s.send_timeout(2, ms(50)), Err(SendTimeoutError::Timeout(2)));
+    assert!(start.elapsed() < ms(50));
+
+    // A token becomes available after about 200 ms.
+    assert_eq!(s.send_timeout(3, ms(1000)), Ok(()));
+    assert!(start.elapsed() >= ms(150));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 3]);
+}
+
+#[test]
+fn permit_select() {
+    let (s, r) = unbounded();
+    let (stop_s, stop_r) = bounded::<()>(0);
+    let s = s.rate_limited(20, 1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(300));
+            drop(stop_s);
+        });
+
+        let start = Instant::now();
+        let mut sent = 0;
+        loop {
+            select! {
+                recv(s.permit()) -> _ => {
+                    if s.try_send(sent).is_ok() {
+                        sent += 1;
+                    }
+                }
+                recv(stop_r) -> _ => break,
+            }
+        }
+
+        // Roughly one message every 50 ms, plus the initial token.
+        assert!(start.elapsed() >= ms(300));
+        assert!(sent >= 3, "sent {} messages", sent);
+        assert!(sent <= 8, "sent {} messages", sent);
+    })
+    .unwrap();
+
+    assert!(r.len() >= 3);
+}
+
+#[test]
+fn threads() {
+    const THREADS: usize = 4;
+    const PER_THREAD: usize = 5;
+
+    let (s, r) = unbounded();
+    let s = s.rate_limited(100, 1);
+
+    let start = Instant::now();
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..PER_THREAD {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(start.elapsed() >= ms(10 * (THREADS * PER_THREAD) as u64 - 20));
+    assert_eq!(r.len(), THREADS * PER_THREAD);
+}
@@ -0,0 +1,135 @@
+//! Tests for readiness registrations.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, ReadyRegistration, Receiver};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Registers a callback counting how many times it was invoked.
+fn counting<T>(r: &Receiver<T>) -> (ReadyRegistration<T>, Arc<AtomicUsize>) {
+    let fired = Arc::new(AtomicUsize::new(0));
+    let f = fired.clone();
+    let registration = r.register_ready(move || {
+        f.fetch_add(1, Ordering::SeqCst);
+    });
+    (registration, fired)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let (registration, fired) = counting(&r);
+    assert!(!registration.has_fired());
+
+    s.send(1).unwrap();
+    assert!(registration.has_fired());
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    // Fires only once per arming.
+    s.send(2).unwrap();
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    assert_eq!(registration.receiver().try_iter().count(), 2);
+    registration.rearm();
+    assert!(!registration.has_fired());
+    s.send(3).unwrap();
+    assert_eq!(fired.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn already_ready() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    let (registration, fired) = counting(&r);
+    assert!(registration.has_fired());
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+    // Still ready, so rearming fires again.
+    registration.rearm();
+    assert_eq!(fired.load(Ordering::SeqCst), 2);
+
+    // Rearming without the callback having fired does nothing.
+    assert_eq!(r.recv(), Ok(1));
+    registration.rearm();
+    assert!(!registration.has_fired());
+    registration.rearm();
+    assert_eq!(fired.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn disconnect() {
+    let (s, r) = unbounded::<i32>();
+    let (registration, fired) = counting(&r);
+
+    drop(s);
+    assert_eq!(fired.load(Ordering::SeqCst), 1);
+    assert!(registration.receiver().try_recv().is_err());
+}
+
+#[test]
+fn zero_capacity() {
+    let (s, r) = bounded(0);
+    let (registration, fired) = counting(&r);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(7).unwrap());
+
+        while fired.load(Ordering::SeqCst) == 0 {
+            thread::sleep(ms(10));
+        }
+        assert_eq!(registration.receiver().recv(), Ok(7));
+    })
+    .unwrap();
+}
+
+#[test]
+fn dropped_registration() {
+    let (s, r) = unbounded();
+    let (registration, fired) = counting(&r);
+    drop(registration);
+
+    s.send(1).unwrap();
+    assert_eq!(fired.load(Ordering::SeqCst), 0);
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn event_loop() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded(16);
+    let (event_s, event_r) = unbounded();
+    let registration = r.register_ready(move || {
+        let _ = event_s.send(());
+    });
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        let mut next = 0;
+        while next < COUNT {
+            event_r.recv_timeout(ms(5000)).unwrap();
+            for msg in r.try_iter() {
+                assert_eq!(msg, next);
+                next += 1;
+            }
+            registration.rearm();
+        }
+    })
+    .unwrap();
+}
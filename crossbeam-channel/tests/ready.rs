@@ -8,7 +8,7 @@ use std::cell::Cell;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{after, bounded, tick, unbounded};
+use crossbeam_channel::{after, bounded, never, tick, unbounded};
 use crossbeam_channel::{Receiver, Select, TryRecvError, TrySendError};
 use crossbeam_utils::thread::scope;
 
@@ -835,3 +835,26 @@ fn fairness2() {
     })
     .unwrap();
 }
+
+#[test]
+fn error_context() {
+    let (s, r) = unbounded::<i32>();
+    let n = never::<i32>();
+    let a = after(ms(1000));
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    sel.recv(&n);
+    sel.recv(&a);
+
+    let err = sel.try_ready().unwrap_err();
+    assert_eq!(err.operations(), 3);
+    assert_eq!(err.disconnected(), 1);
+    assert!(!err.is_disconnected());
+
+    let err = sel.ready_timeout(ms(50)).unwrap_err();
+    assert_eq!(err.operations(), 3);
+    assert_eq!(err.disconnected(), 1);
+    assert_eq!(err.to_string(), "timed out waiting on select");
+    drop(s);
+}
@@ -5,10 +5,13 @@ extern crate crossbeam_utils;
 
 use std::any::Any;
 use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{after, bounded, tick, unbounded, Receiver, Select, TryRecvError};
+use crossbeam_channel::{after, bounded, never, tick, unbounded};
+use crossbeam_channel::{Receiver, Select, SelectBudgetError, SelectError, Sender, TryRecvError};
+use crossbeam_channel::{SelectedOperation, SelectedRecvError, SelectedSendError};
 use crossbeam_utils::thread::scope;
 
 fn ms(ms: u64) -> Duration {
@@ -382,6 +385,60 @@ fn unblocks() {
     .unwrap();
 }
 
+#[test]
+fn optional_operations() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    let no_receiver: Option<&Receiver<i32>> = None;
+    let no_sender: Option<&Sender<i32>> = None;
+
+    s1.send(1).unwrap();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv_opt(no_receiver);
+    let oper2 = sel.send_opt(no_sender);
+    let oper3 = sel.recv_opt(Some(&r1));
+    let oper = sel.select_timeout(ms(1000));
+    match oper {
+        Err(_) => panic!(),
+        Ok(oper) => match oper.index() {
+            i if i == oper1 => panic!(),
+            i if i == oper2 => panic!(),
+            i if i == oper3 => assert_eq!(oper.recv(&r1), Ok(1)),
+            _ => unreachable!(),
+        },
+    }
+
+    let mut sel = Select::new();
+    sel.recv_opt(no_receiver);
+    sel.send_opt(no_sender);
+    let start = Instant::now();
+    assert!(sel.select_timeout(ms(300)).is_err());
+    assert!(start.elapsed() >= ms(300));
+    assert!(sel.try_select().is_err());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r2.recv(), Ok(2));
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv_opt(no_receiver);
+        let oper2 = sel.send_opt(Some(&s2));
+        let oper = sel.select_timeout(ms(1000));
+        match oper {
+            Err(_) => panic!(),
+            Ok(oper) => match oper.index() {
+                i if i == oper1 => panic!(),
+                i if i == oper2 => assert_eq!(oper.send(&s2, 2), Ok(())),
+                _ => unreachable!(),
+            },
+        }
+    })
+    .unwrap();
+}
+
 #[test]
 fn both_ready() {
     let (s1, r1) = bounded(0);
@@ -1267,6 +1324,40 @@ fn send_and_clone() {
     assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
 }
 
+#[test]
+fn clone_independent() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+
+    let mut copy = sel.clone();
+
+    // Removing from the original doesn't affect the copy, and indices stay the same.
+    sel.remove(oper1);
+    s1.send(1).unwrap();
+    assert!(sel.try_select().is_err());
+    let oper = copy.try_select().unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    // Operations added to the copy are not in the original.
+    let oper3 = copy.recv(&r3);
+    s3.send(3).unwrap();
+    assert!(sel.try_select().is_err());
+    let oper = copy.try_select().unwrap();
+    assert_eq!(oper.index(), oper3);
+    assert_eq!(oper.recv(&r3), Ok(3));
+
+    s2.send(2).unwrap();
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&r2), Ok(2));
+}
+
 #[test]
 fn reuse() {
     const COUNT: usize = 10_000;
@@ -1302,3 +1393,725 @@ fn reuse() {
     })
     .unwrap();
 }
+
+#[test]
+fn reuse_and_remove() {
+    const COUNT: usize = 1000;
+    const THREADS: usize = 4;
+
+    let mut senders = Vec::new();
+    let mut receivers = Vec::new();
+    for _ in 0..THREADS {
+        let (s, r) = bounded(1);
+        senders.push(s);
+        receivers.push(r);
+    }
+
+    scope(|scope| {
+        for s in senders {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut sel = Select::new();
+        for r in &receivers {
+            sel.recv(r);
+        }
+
+        let mut next = [0; THREADS];
+        let mut remaining = THREADS;
+
+        while remaining > 0 {
+            let oper = sel.select();
+            let index = oper.index();
+            match oper.recv(&receivers[index]) {
+                Ok(i) => {
+                    assert_eq!(i, next[index]);
+                    next[index] += 1;
+                }
+                Err(_) => {
+                    assert_eq!(next[index], COUNT);
+                    sel.remove(index);
+                    remaining -= 1;
+                }
+            }
+        }
+
+        assert!(sel.try_select().is_err());
+    })
+    .unwrap();
+}
+
+#[test]
+fn is_disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    let (s3, r3) = bounded::<i32>(1);
+    let r4 = after(ms(0));
+
+    s1.send(1).unwrap();
+    drop(s1);
+
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    let oper = sel.select();
+    assert!(!oper.is_disconnected());
+    assert_eq!(oper.recv(&r1), Ok(1));
+    let oper = sel.select();
+    assert!(oper.is_disconnected());
+    assert!(oper.recv(&r1).is_err());
+
+    drop(r2);
+    let mut sel = Select::new();
+    sel.send(&s2);
+    let oper = sel.select();
+    assert!(oper.is_disconnected());
+    assert!(oper.send(&s2, 2).is_err());
+
+    drop(s3);
+    let mut sel = Select::new();
+    sel.recv(&r3);
+    let oper = sel.select();
+    assert!(oper.is_disconnected());
+    assert!(oper.recv(&r3).is_err());
+
+    let mut sel = Select::new();
+    sel.recv(&r4);
+    let oper = sel.select();
+    assert!(!oper.is_disconnected());
+    assert!(oper.recv(&r4).is_ok());
+}
+
+#[test]
+fn remove_disconnected() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+    let (s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    sel.set_remove_disconnected(true);
+    let oper1 = sel.recv(&r1);
+    sel.recv(&r2);
+    sel.recv(&r3);
+
+    s1.send(1).unwrap();
+    drop(s1);
+    drop(s2);
+
+    // The message is still delivered even though the channel is disconnected.
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    // Disconnected operations are removed rather than selected.
+    assert!(sel.try_select().is_err());
+    assert!(sel.select_timeout(ms(100)).is_err());
+    assert!(!sel.is_empty());
+
+    sel.set_remove_disconnected(false);
+    drop(s3);
+    let oper = sel.select();
+    assert!(oper.is_disconnected());
+    assert!(oper.recv(&r3).is_err());
+
+    // The last operation is removed, but still returned.
+    sel.set_remove_disconnected(true);
+    let oper = sel.try_select().unwrap();
+    assert!(oper.is_disconnected());
+    assert!(oper.recv(&r3).is_err());
+    assert!(sel.is_empty());
+    assert!(sel.try_select().is_err());
+}
+
+#[test]
+fn remove_disconnected_threads() {
+    const COUNT: usize = 1000;
+    const THREADS: usize = 4;
+
+    let mut senders = Vec::new();
+    let mut receivers = Vec::new();
+    for _ in 0..THREADS {
+        let (s, r) = bounded(1);
+        senders.push(s);
+        receivers.push(r);
+    }
+
+    scope(|scope| {
+        for s in senders {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut sel = Select::new();
+        sel.set_remove_disconnected(true);
+        for r in &receivers {
+            sel.recv(r);
+        }
+
+        let mut next = [0; THREADS];
+        while !sel.is_empty() {
+            let oper = sel.select();
+            let index = oper.index();
+            if oper.is_disconnected() {
+                assert!(sel.is_empty());
+                assert!(oper.recv(&receivers[index]).is_err());
+            } else {
+                assert_eq!(oper.recv(&receivers[index]), Ok(next[index]));
+                next[index] += 1;
+            }
+        }
+
+        assert_eq!(next, [COUNT; THREADS]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn tiers() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+    sel.set_tier(oper1, 2);
+    sel.set_tier(oper3, 1);
+
+    for i in 0..10 {
+        s1.send(i).unwrap();
+        s2.send(i).unwrap();
+        s3.send(i).unwrap();
+    }
+
+    for i in 0..10 {
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv(&r2), Ok(i));
+    }
+    for i in 0..10 {
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), oper3);
+        assert_eq!(oper.recv(&r3), Ok(i));
+    }
+    for i in 0..10 {
+        assert_eq!(sel.ready(), oper1);
+        assert_eq!(r1.try_recv(), Ok(i));
+    }
+    assert!(sel.try_ready().is_err());
+}
+
+#[test]
+fn tiers_fairness_within_tier() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = unbounded::<()>();
+    let (s2, r2) = unbounded::<()>();
+    let (s3, r3) = unbounded::<()>();
+
+    for _ in 0..COUNT {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+    s3.send(()).unwrap();
+
+    let mut sel = Select::new();
+    let oper3 = sel.recv(&r3);
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    sel.set_tier(oper3, 1);
+
+    let mut hits = [0usize; 2];
+    for _ in 0..COUNT {
+        let oper = sel.select();
+        let index = oper.index();
+        if index == oper1 {
+            oper.recv(&r1).unwrap();
+            hits[0] += 1;
+        } else {
+            assert_eq!(index, oper2);
+            oper.recv(&r2).unwrap();
+            hits[1] += 1;
+        }
+    }
+    assert!(hits.iter().all(|x| *x >= COUNT / 4));
+    assert_eq!(r3.len(), 1);
+}
+
+#[test]
+fn tiers_blocking() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<i32>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+            thread::sleep(ms(100));
+            s1.send(1).unwrap();
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        sel.set_tier(oper2, 1);
+
+        // A lower priority operation is selected if it becomes ready first.
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper2);
+        assert_eq!(oper.recv(&r2), Ok(2));
+
+        let oper = sel.select_timeout(ms(1000)).unwrap();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&r1), Ok(1));
+    })
+    .unwrap();
+}
+
+#[test]
+fn tiers_remove() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+    s1.send(1).unwrap();
+    s2.send(2).unwrap();
+    s3.send(3).unwrap();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+    sel.set_tier(oper2, 1);
+    sel.set_tier(oper3, 2);
+    sel.remove(oper1);
+
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&r2), Ok(2));
+
+    // Moving an operation back to tier 0.
+    sel.set_tier(oper3, 0);
+    s2.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper3);
+    assert_eq!(oper.recv(&r3), Ok(3));
+}
+
+#[test]
+fn error_context() {
+    let (s, r) = unbounded::<i32>();
+    let n = never::<i32>();
+    let a = after(ms(0));
+    thread::sleep(ms(10));
+    a.recv().unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    sel.recv(&n);
+    sel.recv(&a);
+
+    let err = sel.try_select().unwrap_err();
+    assert_eq!(err.operations(), 3);
+    assert_eq!(err.disconnected(), 2);
+    assert!(!err.is_disconnected());
+
+    let err = sel.select_timeout(ms(50)).unwrap_err();
+    assert_eq!(err.operations(), 3);
+    assert_eq!(err.disconnected(), 2);
+
+    sel.remove(0);
+    let err = sel.try_select().unwrap_err();
+    assert_eq!(err.operations(), 2);
+    assert!(err.is_disconnected());
+    assert_eq!(err.to_string(), "all operations in select would block");
+
+    let err = Select::new().try_select().unwrap_err();
+    assert_eq!(err.operations(), 0);
+    assert!(err.is_disconnected());
+    drop(s);
+}
+
+#[test]
+fn exhausted() {
+    let (s1, r1) = bounded::<i32>(1);
+    let (s2, r2) = unbounded::<i32>();
+    let (s3, r3) = bounded::<i32>(0);
+    let n = never::<i32>();
+    s1.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    sel.recv(&n);
+    sel.send(&s2);
+    sel.send(&s3);
+    assert!(!sel.is_exhausted());
+
+    drop(s1);
+    drop(r2);
+    drop(r3);
+    assert!(!sel.is_exhausted());
+
+    assert_eq!(r1.recv(), Ok(1));
+    assert!(sel.is_exhausted());
+    assert!(Select::new().is_exhausted());
+}
+
+#[test]
+fn select_connected() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s1.send(1).unwrap();
+            drop(s1);
+            thread::sleep(ms(100));
+            s2.send(2).unwrap();
+            drop(s2);
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r1);
+        sel.recv(&r2);
+
+        sel.set_remove_disconnected(true);
+
+        let mut msgs = Vec::new();
+        loop {
+            match sel.select_connected() {
+                Ok(oper) => {
+                    let res = match oper.index() {
+                        0 => oper.recv(&r1),
+                        1 => oper.recv(&r2),
+                        _ => unreachable!(),
+                    };
+                    msgs.extend(res);
+                }
+                Err(err) => {
+                    assert_eq!(err, SelectError::AllDisconnected);
+                    break;
+                }
+            }
+        }
+
+        assert_eq!(msgs, [1, 2]);
+        assert!(sel.is_exhausted());
+        assert_eq!(
+            sel.select_connected().unwrap_err(),
+            SelectError::AllDisconnected
+        );
+    })
+    .unwrap();
+
+    assert_eq!(
+        Select::new().select_connected().unwrap_err().to_string(),
+        "all operations in select are disconnected"
+    );
+}
+
+#[test]
+fn select_with_budget() {
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r);
+
+        s.send(1).unwrap();
+        let oper = sel.select_with_budget(0).unwrap();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&r), Ok(1));
+
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(2).unwrap();
+        });
+
+        let oper = sel.select_with_budget(0).unwrap();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&r), Ok(2));
+    })
+    .unwrap();
+
+    assert_eq!(
+        SelectBudgetError::Contended.to_string(),
+        "select ran out of retries under contention"
+    );
+}
+
+#[test]
+fn select_with_budget_contended() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded(1);
+    let received = AtomicUsize::new(0);
+
+    scope(|scope| {
+        // Threads that race the selects for every message.
+        for _ in 0..THREADS {
+            scope.spawn(|_| loop {
+                match r.try_recv() {
+                    Ok(_) => {
+                        received.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Err(TryRecvError::Empty) => thread::yield_now(),
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            });
+        }
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let mut sel = Select::new();
+                sel.recv(&r);
+
+                loop {
+                    match sel.select_with_budget(0) {
+                        Ok(oper) => match oper.recv(&r) {
+                            Ok(_) => {
+                                received.fetch_add(1, Ordering::SeqCst);
+                            }
+                            Err(_) => break,
+                        },
+                        // Lost the race too many times, so try again.
+                        Err(SelectBudgetError::Contended) => {}
+                    }
+                }
+            });
+        }
+
+        for i in 0..COUNT {
+            s.send(i).unwrap();
+        }
+        drop(s);
+    })
+    .unwrap();
+
+    // Giving up never loses a message.
+    assert_eq!(received.load(Ordering::SeqCst), COUNT);
+}
+
+#[test]
+fn wrong_operand() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded::<i32>();
+    s1.send(1).unwrap();
+
+    fn complete(oper: &mut SelectedOperation, r: &Receiver<i32>) -> Result<i32, SelectedRecvError> {
+        let msg = oper.try_recv(r)?;
+        Ok(msg)
+    }
+
+    // The operation survives being completed with the wrong receiver, and the error can be
+    // propagated and dropped.
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    let mut oper = sel.select();
+    assert!(complete(&mut oper, &r2).unwrap_err().is_wrong_operand());
+    assert_eq!(oper.try_recv(&r2).ok(), None);
+    assert_eq!(complete(&mut oper, &r1), Ok(1));
+
+    // A completed operation can't be completed again, and can be dropped.
+    assert_eq!(oper.try_recv(&r1), Err(SelectedRecvError::WrongOperand));
+    drop(oper);
+
+    let mut sel = Select::new();
+    sel.send(&s2);
+    let mut oper = sel.select();
+    assert_eq!(
+        oper.try_send(&s1, 2),
+        Err(SelectedSendError::WrongOperand(2))
+    );
+    assert_eq!(oper.try_send(&s2, 3), Ok(()));
+    assert_eq!(r2.try_recv(), Ok(3));
+    assert_eq!(
+        oper.try_send(&s2, 4),
+        Err(SelectedSendError::WrongOperand(4))
+    );
+    drop(oper);
+
+    drop(r2);
+    let mut sel = Select::new();
+    sel.send(&s2);
+    let mut oper = sel.select();
+    let err = oper.try_send(&s2, 5).unwrap_err();
+    assert!(err.is_disconnected());
+    assert_eq!(err.into_inner(), 5);
+}
+
+#[test]
+#[should_panic(expected = "the operation has already been completed")]
+fn complete_twice() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    let mut oper = sel.select();
+    assert_eq!(oper.try_recv(&r), Ok(1));
+    let _ = oper.recv(&r);
+}
+
+#[test]
+fn tagged() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv_tagged(&r1, 10);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.send_tagged(&s3, 30);
+    sel.set_tier(oper3, 1);
+
+    // Tags follow operations across tiers.
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper3);
+    assert_eq!(oper.tag(), Some(30));
+    assert_eq!(oper.send(&s3, 3), Ok(()));
+    assert_eq!(r3.try_recv(), Ok(3));
+    sel.remove(oper3);
+
+    s2.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.tag(), None);
+    assert_eq!(oper.recv(&r2), Ok(2));
+
+    s1.send(1).unwrap();
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.tag(), Some(10));
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    // Tags are kept by clones and dropped along with removed operations.
+    let mut sel2 = sel.clone();
+    sel.remove(oper1);
+    drop(s1);
+    let oper = sel2.select_timeout(ms(1000)).unwrap();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.tag(), Some(10));
+    assert!(oper.recv(&r1).is_err());
+
+    let oper4 = sel.recv_tagged(&r1, 40);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper4);
+    assert_eq!(oper.tag(), Some(40));
+    assert!(oper.recv(&r1).is_err());
+}
+
+#[test]
+fn clear() {
+    let (s1, r1) = unbounded();
+    let (s2, _r2) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv_tagged(&r1, 1);
+    sel.send(&s2);
+    sel.clear();
+    assert!(sel.is_empty());
+    assert!(sel.try_select().is_err());
+
+    // Indices keep growing, and tags of cleared operations are gone.
+    let oper3 = sel.recv(&r1);
+    assert!(oper3 > oper1);
+    s1.send(10).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper3);
+    assert_eq!(oper.tag(), None);
+    assert_eq!(oper.recv(&r1), Ok(10));
+}
+
+#[test]
+fn recv_guarded() {
+    let (s1, r1) = bounded(1);
+    let (dead_s, dead_r) = unbounded();
+
+    // Handled messages are not handed to the fallback channel.
+    s1.send(1).unwrap();
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    let mut msg = sel.select().recv_guarded(&r1, &dead_s).unwrap();
+    *msg += 1;
+    assert_eq!(*msg, 2);
+    drop(msg);
+    assert!(dead_r.is_empty());
+
+    // A panicking handler dead-letters the message.
+    s1.send(3).unwrap();
+    let res = std::panic::catch_unwind(|| {
+        let mut sel = Select::new();
+        sel.recv(&r1);
+        let msg = sel.select().recv_guarded(&r1, &dead_s).unwrap();
+        panic!("failed to handle {}", *msg);
+    });
+    assert!(res.is_err());
+    assert_eq!(dead_r.try_recv(), Ok(3));
+    assert!(r1.is_empty());
+
+    // Unwrapped messages are no longer guarded.
+    s1.send(4).unwrap();
+    let res = std::panic::catch_unwind(|| {
+        let mut sel = Select::new();
+        sel.recv(&r1);
+        let msg = sel.select().recv_guarded(&r1, &dead_s).unwrap();
+        let msg = msg.into_inner();
+        panic!("failed to handle {}", msg);
+    });
+    assert!(res.is_err());
+    assert!(dead_r.is_empty());
+
+    // Disconnection is reported as usual.
+    drop(s1);
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    assert!(sel.select().recv_guarded(&r1, &dead_s).is_err());
+}
+
+#[test]
+fn biased() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+
+    for i in 0..10 {
+        s1.send(i).unwrap();
+        s2.send(i).unwrap();
+        s3.send(i).unwrap();
+    }
+
+    let mut sel = Select::new_biased();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper3 = sel.recv(&r3);
+
+    // Ready operations are selected in the order they were added.
+    for i in 0..10 {
+        assert_eq!(sel.ready(), oper1);
+        let oper = sel.select();
+        assert_eq!(oper.index(), oper1);
+        assert_eq!(oper.recv(&r1), Ok(i));
+    }
+
+    // Tiers take precedence over the order operations were added in.
+    sel.set_tier(oper2, 1);
+    for i in 0..10 {
+        let oper = sel.try_select().unwrap();
+        assert_eq!(oper.index(), oper3);
+        assert_eq!(oper.recv(&r3), Ok(i));
+    }
+
+    // Clones keep selecting in order.
+    let mut sel2 = sel.clone();
+    let oper = sel2.select_timeout(ms(100)).unwrap();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&r2), Ok(0));
+}
@@ -1,6 +1,6 @@
 //! Tests for the `select!` macro.
 
-#![deny(unsafe_code)]
+#![forbid(unsafe_code)]
 
 #[macro_use]
 extern crate crossbeam_channel;
@@ -1177,6 +1177,46 @@ fn deref() {
     }
 }
 
+#[test]
+fn temporaries() {
+    fn ready(msg: i32) -> Receiver<i32> {
+        let (s, r) = unbounded();
+        s.send(msg).unwrap();
+        r
+    }
+    fn receivers() -> Vec<Receiver<i32>> {
+        vec![ready(1), ready(2)]
+    }
+
+    select! {
+        recv(ready(1)) -> v => assert_eq!(v, Ok(1)),
+    }
+    select! {
+        recv(&ready(1)) -> v => assert_eq!(v, Ok(1)),
+        default => panic!(),
+    }
+    select! {
+        recv(receivers()[1]) -> v => assert_eq!(v, Ok(2)),
+        default(ms(1000)) => panic!(),
+    }
+
+    let (s, r) = bounded::<i32>(0);
+    select! {
+        recv(r) -> _ => panic!(),
+        recv(receivers().first().unwrap()) -> v => assert_eq!(v, Ok(1)),
+    }
+    select! {
+        recv(never::<i32>()) -> _ => panic!(),
+        recv(receivers().into_iter().last().unwrap()) -> v => assert_eq!(v, Ok(2)),
+        default => panic!(),
+    }
+    select! {
+        send(unbounded().0, 0) -> v => assert_eq!(v, Ok(())),
+        send(s, 0) -> _ => panic!(),
+        default(ms(1000)) => panic!(),
+    }
+}
+
 #[test]
 fn result_types() {
     let (s, _) = bounded::<i32>(0);
@@ -1438,3 +1478,100 @@ fn disconnect_wakes_receiver() {
     })
     .unwrap();
 }
+
+mod shadowed_prelude {
+    #![allow(dead_code, unused_imports)]
+
+    use crossbeam_channel::unbounded;
+    use std::time::Duration;
+
+    // The macro must not rely on these names from the prelude.
+    enum Option {
+        None,
+        Some,
+    }
+    enum Result {
+        Ok,
+        Err,
+    }
+    use self::Option::{None, Some};
+    use self::Result::{Err, Ok};
+
+    #[test]
+    fn expands() {
+        let (s, r) = unbounded::<i32>();
+        let (_s2, r2) = unbounded::<i32>();
+
+        select! {
+            recv(r) -> _ => panic!(),
+            default => {}
+        }
+        select! {
+            recv(r) -> _ => panic!(),
+            default(Duration::from_millis(10)) => {}
+        }
+        select! {
+            recv(r) -> _ => panic!(),
+            recv(r2) -> _ => panic!(),
+            default => {}
+        }
+        select! {
+            recv(r) -> _ => panic!(),
+            recv(r2) -> _ => panic!(),
+            default(Duration::from_millis(10)) => {}
+        }
+
+        s.send(1).unwrap();
+        select! {
+            recv(r) -> msg => assert_eq!(msg.unwrap(), 1),
+            recv(r2) -> _ => panic!(),
+        }
+    }
+}
+
+#[test]
+fn biased() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+    let (s3, r3) = bounded(1);
+
+    for i in 0..10 {
+        s1.send(i).unwrap();
+        s2.send(i).unwrap();
+    }
+
+    // Ready cases are selected in the order they're listed in.
+    for i in 0..10 {
+        select_biased! {
+            recv(r1) -> v => assert_eq!(v, Ok(i)),
+            recv(r2) -> _ => panic!(),
+            send(s3, 0) -> _ => panic!(),
+        }
+    }
+    for i in 0..10 {
+        select_biased! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> v => assert_eq!(v, Ok(i)),
+            default => panic!(),
+        }
+    }
+    select_biased! {
+        recv(r1) -> _ => panic!(),
+        send(s3, 1) -> res => assert_eq!(res, Ok(())),
+        default(ms(100)) => panic!(),
+    }
+    assert_eq!(r3.try_recv(), Ok(1));
+
+    // Blocking still waits on all cases.
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s2.send(10).unwrap();
+        });
+        select_biased! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> v => assert_eq!(v, Ok(10)),
+        }
+    })
+    .unwrap();
+}
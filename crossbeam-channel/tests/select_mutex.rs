@@ -0,0 +1,185 @@
+//! Tests for select-aware mutexes.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::sync::SelectMutex;
+use crossbeam_channel::{never, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let m = SelectMutex::new(1);
+    {
+        let mut guard = m.lock();
+        *guard += 1;
+        assert!(m.try_lock().is_none());
+        assert!(m.acquire().try_recv().is_err());
+    }
+    assert_eq!(*m.try_lock().unwrap(), 2);
+    assert_eq!(m.into_inner(), 2);
+}
+
+#[test]
+fn get_mut() {
+    let mut m = SelectMutex::new(vec![1]);
+    m.get_mut().push(2);
+    assert_eq!(*m.lock(), [1, 2]);
+}
+
+#[test]
+fn lock_timeout() {
+    let m = SelectMutex::new(());
+
+    scope(|scope| {
+        let guard = m.lock();
+        scope.spawn(|_| {
+            let now = Instant::now();
+            assert!(m.lock_timeout(ms(100)).is_none());
+            assert!(now.elapsed() >= ms(100));
+            assert!(m.lock_timeout(ms(1000)).is_some());
+        });
+        thread::sleep(ms(300));
+        drop(guard);
+    })
+    .unwrap();
+}
+
+#[test]
+fn dropped_token_releases() {
+    let m = SelectMutex::new(0);
+
+    let token = m.acquire().recv().unwrap();
+    assert!(m.try_lock().is_none());
+    drop(token);
+    assert!(m.try_lock().is_some());
+}
+
+#[test]
+#[should_panic(expected = "different mutex")]
+fn foreign_token() {
+    let m1 = SelectMutex::new(0);
+    let m2 = SelectMutex::new(0);
+
+    let token = m1.acquire().recv().unwrap();
+    let _guard = m2.guard(token);
+}
+
+#[test]
+fn token_outlives_mutex() {
+    let m = SelectMutex::new(String::from("value"));
+    let token = m.acquire().recv().unwrap();
+    assert_eq!(m.into_inner(), "value");
+    drop(token);
+
+    let m = SelectMutex::new(0);
+    let token = m.acquire().recv().unwrap();
+    drop(m);
+    drop(token);
+}
+
+#[test]
+fn select_wakes_on_unlock() {
+    let m = SelectMutex::new(0);
+    let (s, r) = unbounded::<i32>();
+
+    scope(|scope| {
+        let guard = m.lock();
+        scope.spawn(|_| {
+            let now = Instant::now();
+            select! {
+                recv(r) -> _ => panic!(),
+                recv(m.acquire()) -> token => *m.guard(token.unwrap()) += 1,
+            }
+            assert!(now.elapsed() >= ms(200));
+        });
+        thread::sleep(ms(300));
+        drop(guard);
+    })
+    .unwrap();
+
+    assert_eq!(*m.lock(), 1);
+    drop(s);
+}
+
+#[test]
+fn select_with_work() {
+    const COUNT: usize = 1000;
+
+    let m = SelectMutex::new(Vec::new());
+    let (s, r) = unbounded();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut pending = Vec::new();
+            let mut done = false;
+            while !done || !pending.is_empty() {
+                let r = if done { None } else { Some(&r) };
+                select! {
+                    recv(r.unwrap_or(&never())) -> msg => match msg {
+                        Ok(msg) => pending.push(msg),
+                        Err(_) => done = true,
+                    },
+                    recv(m.acquire()) -> token => {
+                        m.guard(token.unwrap()).extend(pending.drain(..));
+                    }
+                }
+            }
+        });
+
+        for i in 0..COUNT {
+            s.send(i).unwrap();
+            if i % 100 == 0 {
+                let guard = m.lock();
+                thread::sleep(ms(1));
+                drop(guard);
+            }
+        }
+        drop(s);
+    })
+    .unwrap();
+
+    assert_eq!(m.into_inner(), (0..COUNT).collect::<Vec<_>>());
+}
+
+#[test]
+fn mutual_exclusion() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let m = SelectMutex::new(0);
+    let inside = AtomicUsize::new(0);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let m = &m;
+            let inside = &inside;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    let mut guard = if (t + i) % 2 == 0 {
+                        m.lock()
+                    } else {
+                        select! {
+                            recv(m.acquire()) -> token => m.guard(token.unwrap()),
+                        }
+                    };
+                    assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                    *guard += 1;
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(m.into_inner(), THREADS * COUNT);
+}
@@ -0,0 +1,104 @@
+//! Tests for session-typed channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use crossbeam_channel::session::{self, Branch, Choose, End, Offer, Recv, Send};
+use crossbeam_channel::{RecvError, SendError};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    type Client = Send<i32, Recv<String, End>>;
+
+    let (client, server) = session::channel::<Client>();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let (n, server) = server.recv().unwrap();
+            server.send(n.to_string()).unwrap().close();
+        });
+
+        let (s, client) = client.send(7).unwrap().recv().unwrap();
+        assert_eq!(s, "7");
+        client.close();
+    })
+    .unwrap();
+}
+
+#[test]
+fn choose_and_offer() {
+    type Client = Choose<Send<i32, End>, Recv<i32, End>>;
+
+    for &left in &[true, false] {
+        let (client, server) = session::channel::<Client>();
+
+        scope(|scope| {
+            scope.spawn(move |_| match server.offer().unwrap() {
+                Branch::Left(server) => {
+                    let (n, server) = server.recv().unwrap();
+                    assert_eq!(n, 1);
+                    server.close();
+                }
+                Branch::Right(server) => server.send(2).unwrap().close(),
+            });
+
+            if left {
+                client.left().unwrap().send(1).unwrap().close();
+            } else {
+                let (n, client) = client.right().unwrap().recv().unwrap();
+                assert_eq!(n, 2);
+                client.close();
+            }
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn nested() {
+    // The server offers to either add two numbers or negate one.
+    type Server = Offer<Recv<i32, Recv<i32, Send<i32, End>>>, Recv<i32, Send<i32, End>>>;
+
+    let (server, client) = session::channel::<Server>();
+
+    scope(|scope| {
+        scope.spawn(move |_| match server.offer().unwrap() {
+            Branch::Left(server) => {
+                let (a, server) = server.recv().unwrap();
+                let (b, server) = server.recv().unwrap();
+                server.send(a + b).unwrap().close();
+            }
+            Branch::Right(server) => {
+                let (a, server) = server.recv().unwrap();
+                server.send(-a).unwrap().close();
+            }
+        });
+
+        let client = client.left().unwrap();
+        let client = client.send(2).unwrap().send(3).unwrap();
+        let (sum, client) = client.recv().unwrap();
+        assert_eq!(sum, 5);
+        client.close();
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnected() {
+    let (client, server) = session::channel::<Send<i32, End>>();
+    drop(server);
+    assert_eq!(client.send(1).unwrap_err(), SendError(1));
+
+    let (client, server) = session::channel::<Recv<i32, End>>();
+    drop(server);
+    assert_eq!(client.recv().unwrap_err(), RecvError);
+
+    let (client, server) = session::channel::<Choose<End, End>>();
+    drop(server);
+    assert_eq!(client.left().unwrap_err(), SendError(()));
+
+    let (client, server) = session::channel::<Choose<End, End>>();
+    drop(client);
+    assert_eq!(server.offer().unwrap_err(), RecvError);
+}
@@ -0,0 +1,133 @@
+//! Tests for sharded receivers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, shard, unbounded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, Sequenced, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let shards = shard(r, 2);
+    assert_eq!(shards.len(), 2);
+    assert_eq!(shards[1].index(), 1);
+
+    s.send('a').unwrap();
+    s.send('b').unwrap();
+    s.send('c').unwrap();
+
+    assert_eq!(shards[1].try_recv(), Ok(Sequenced { seq: 0, value: 'a' }));
+    assert_eq!(shards[0].try_recv(), Ok(Sequenced { seq: 1, value: 'b' }));
+    assert_eq!(shards[1].recv(), Ok(Sequenced { seq: 2, value: 'c' }));
+    assert_eq!(shards[0].try_recv(), Err(TryRecvError::Empty));
+
+    drop(s);
+    assert_eq!(shards[0].recv(), Err(RecvError));
+    assert_eq!(shards[1].try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+#[should_panic(expected = "number of shards must be positive")]
+fn zero_shards() {
+    let (_s, r) = unbounded::<i32>();
+    shard(r, 0);
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = bounded(1);
+    let shards = shard(r, 3);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(
+            shards[2].recv_timeout(ms(50)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() >= ms(50));
+        assert_eq!(
+            shards[2].recv_timeout(ms(1000)),
+            Ok(Sequenced { seq: 0, value: 7 })
+        );
+    })
+    .unwrap();
+
+    drop(s);
+    assert_eq!(
+        shards[0].recv_timeout(ms(1000)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn parallel_restores_order() {
+    const COUNT: usize = 10_000;
+    const SHARDS: usize = 4;
+
+    let (s, r) = bounded(16);
+    let shards = shard(r, SHARDS);
+
+    let mut results = scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        let handles: Vec<_> = shards
+            .iter()
+            .map(|shard| scope.spawn(move |_| shard.iter().collect::<Vec<_>>()))
+            .collect();
+
+        let mut results = Vec::new();
+        for h in handles {
+            let part = h.join().unwrap();
+            // Each shard sees increasing sequence numbers.
+            assert!(part.windows(2).all(|w| w[0].seq < w[1].seq));
+            results.extend(part);
+        }
+        results
+    })
+    .unwrap();
+
+    results.sort_by_key(|m| m.seq);
+    for (i, m) in results.iter().enumerate() {
+        assert_eq!(m.seq, i as u64);
+        assert_eq!(m.value, i);
+    }
+}
+
+#[test]
+fn ready() {
+    let (s, r) = unbounded();
+    let (_s2, r2) = unbounded::<()>();
+    let shards = shard(r, 2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.send(1).unwrap();
+        });
+
+        let mut sel = Select::new();
+        sel.recv(&r2);
+        let oper = sel.recv(shards[1].source());
+        assert_eq!(sel.ready(), oper);
+        assert_eq!(shards[1].try_recv(), Ok(Sequenced { seq: 0, value: 1 }));
+    })
+    .unwrap();
+}
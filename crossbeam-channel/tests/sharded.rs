@@ -0,0 +1,226 @@
+//! Tests for sharded channels.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{sharded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (senders, r) = sharded::channel(3);
+    assert_eq!(senders.len(), 3);
+    for (i, s) in senders.iter().enumerate() {
+        assert_eq!(s.shard(), i);
+        assert_eq!(s.shards(), 3);
+    }
+
+    senders[1].send(1).unwrap();
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    senders[2].send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+    assert_eq!(r.capacity(), None);
+}
+
+#[test]
+#[should_panic(expected = "a sharded channel needs at least one shard")]
+fn zero_shards() {
+    sharded::channel::<i32>(0);
+}
+
+#[test]
+fn fifo_within_shard() {
+    let (senders, r) = sharded::channel(2);
+
+    for i in 0..10 {
+        senders[0].send((0, i)).unwrap();
+        senders[1].send((1, i)).unwrap();
+    }
+    assert_eq!(r.len(), 20);
+    assert_eq!(senders[0].len(), 20);
+
+    let mut next = [0, 0];
+    for (shard, i) in r.try_iter() {
+        assert_eq!(i, next[shard]);
+        next[shard] += 1;
+    }
+    assert_eq!(next, [10, 10]);
+    assert!(r.is_empty());
+    assert!(senders[1].is_empty());
+}
+
+#[test]
+fn no_starvation() {
+    let (senders, r) = sharded::channel(2);
+
+    for i in 0..10 {
+        senders[0].send(i).unwrap();
+    }
+    senders[1].send(100).unwrap();
+
+    // The second shard is visited long before the first one is drained.
+    let v: Vec<i32> = r.try_iter().take(3).collect();
+    assert!(v.contains(&100));
+}
+
+#[test]
+fn clones_share_shard() {
+    let (senders, r) = sharded::channel(4);
+
+    let clones = senders[2].clone_n(3);
+    assert_eq!(clones.len(), 3);
+    assert!(clones.iter().all(|s| s.shard() == 2));
+
+    for (i, s) in clones.iter().enumerate() {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1, 2]);
+}
+
+#[test]
+fn disconnect() {
+    let (senders, r) = sharded::channel(2);
+    let extra = senders[0].clone();
+
+    senders[0].send(1).unwrap();
+    senders[1].send(2).unwrap();
+    drop(senders);
+
+    // A clone keeps the channel connected.
+    assert!(r.recv_timeout(ms(10)).is_ok());
+    assert!(r.recv_timeout(ms(10)).is_ok());
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+    drop(extra);
+
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (senders, r) = sharded::channel(2);
+    drop(r);
+    assert_eq!(senders[0].send(1), Err(SendError(1)));
+    assert_eq!(senders[1].send(2), Err(SendError(2)));
+}
+
+#[test]
+fn wake_on_any_shard() {
+    let (senders, r) = sharded::channel(3);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+
+        thread::sleep(ms(100));
+        senders[2].send(1).unwrap();
+        thread::sleep(ms(100));
+        senders[0].send(2).unwrap();
+        thread::sleep(ms(100));
+        drop(senders);
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (senders, r) = sharded::channel(2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            senders[1].send(1).unwrap();
+        });
+
+        select! {
+            recv(r) -> msg => assert_eq!(msg, Ok(1)),
+            default(ms(1000)) => panic!(),
+        }
+    })
+    .unwrap();
+
+    senders[0].send(2).unwrap();
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r), Ok(2));
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (senders, r) = sharded::channel::<usize>(THREADS);
+
+    scope(|scope| {
+        for s in &senders {
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut handles = Vec::new();
+        for _ in 0..THREADS {
+            handles.push(scope.spawn(|_| {
+                let mut sum = 0;
+                for _ in 0..COUNT {
+                    sum += r.recv().unwrap();
+                }
+                sum
+            }));
+        }
+
+        let sum: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        assert_eq!(sum, THREADS * COUNT * (COUNT - 1) / 2);
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn drops() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (senders, r) = sharded::channel(2);
+    for _ in 0..100 {
+        senders[0].send(DropCounter).unwrap();
+        senders[1].send(DropCounter).unwrap();
+    }
+    for _ in 0..50 {
+        r.recv().unwrap();
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 50);
+
+    drop(senders);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 200);
+}
@@ -0,0 +1,201 @@
+//! Tests for signal-safe channels.
+//!
+//! Every test that raises a signal uses its own signal number, since handlers are process-wide.
+
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate libc;
+extern crate signal_hook;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{signal_safe, RecvTimeoutError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = signal_safe::channel(2);
+    assert_eq!(s.capacity(), 2);
+    assert!(s.is_empty());
+
+    s.try_send(1).unwrap();
+    s.try_send(2).unwrap();
+    assert!(s.is_full());
+    assert_eq!(s.len(), 2);
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+
+    assert_eq!(r.try_recv(), Ok(1));
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    signal_safe::channel::<i32>(0);
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = signal_safe::channel(1);
+    let s2 = s.clone();
+    drop(s);
+    s2.try_send(1).unwrap();
+    drop(s2);
+    assert_eq!(r.recv(), Ok(1));
+    assert!(r.recv().is_err());
+
+    let (s, r) = signal_safe::channel(1);
+    drop(r);
+    assert_eq!(s.try_send(1), Err(TrySendError::Disconnected(1)));
+}
+
+#[test]
+fn send_blocks_until_room() {
+    let (s, r) = signal_safe::channel(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(1));
+            assert_eq!(r.recv(), Ok(2));
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn from_handler() {
+    const COUNT: usize = 10;
+
+    let (s, r) = signal_safe::channel(COUNT);
+    let sent = Arc::new(AtomicUsize::new(0));
+    let sent2 = sent.clone();
+    unsafe {
+        signal_hook::register(signal_hook::SIGUSR1, move || {
+            let i = sent2.fetch_add(1, Ordering::SeqCst);
+            let _ = s.try_send(i);
+        })
+        .unwrap();
+    }
+
+    for _ in 0..COUNT {
+        unsafe {
+            libc::raise(signal_hook::SIGUSR1);
+        }
+    }
+    assert_eq!(sent.load(Ordering::SeqCst), COUNT);
+
+    for i in 0..COUNT {
+        assert_eq!(r.try_recv(), Ok(i));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn handler_wakes_receiver() {
+    let (s, r) = signal_safe::channel(1);
+    unsafe {
+        signal_hook::register(signal_hook::SIGUSR2, move || {
+            let _ = s.try_send(7);
+        })
+        .unwrap();
+    }
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv_timeout(ms(5000)), Ok(7));
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            unsafe {
+                libc::raise(signal_hook::SIGUSR2);
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn interrupted_receivers() {
+    const THREADS: usize = 4;
+    const SIGNALS: usize = 10_000;
+
+    // Signals are delivered to the receiving threads themselves, so the handler also runs while
+    // a receiver is in the middle of registering with or unregistering from the channel.
+    let (s, r) = signal_safe::channel(3);
+    let sent = Arc::new(AtomicUsize::new(0));
+    let sent2 = sent.clone();
+    unsafe {
+        signal_hook::register(libc::SIGURG, move || {
+            let i = sent2.load(Ordering::SeqCst);
+            if s.try_send(i).is_ok() {
+                sent2.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+    }
+
+    let threads = Mutex::new(Vec::new());
+    let received = AtomicUsize::new(0);
+    let done = AtomicBool::new(false);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                threads
+                    .lock()
+                    .unwrap()
+                    .push(unsafe { libc::pthread_self() });
+                loop {
+                    match r.recv_timeout(ms(100)) {
+                        Ok(_) => {
+                            received.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Err(RecvTimeoutError::Timeout) => {
+                            if done.load(Ordering::SeqCst) {
+                                break;
+                            }
+                        }
+                        Err(RecvTimeoutError::Disconnected) => unreachable!(),
+                    }
+                }
+            });
+        }
+
+        while threads.lock().unwrap().len() < THREADS {
+            thread::yield_now();
+        }
+        let threads = threads.lock().unwrap().clone();
+        for i in 0..SIGNALS {
+            unsafe {
+                libc::pthread_kill(threads[i % THREADS], libc::SIGURG);
+            }
+            if i % 16 == 0 {
+                thread::yield_now();
+            }
+        }
+
+        // Give the last handlers time to run before the receivers start giving up.
+        thread::sleep(ms(500));
+        done.store(true, Ordering::SeqCst);
+    })
+    .unwrap();
+
+    assert!(r.is_empty());
+    assert!(sent.load(Ordering::SeqCst) > 0);
+    assert_eq!(received.load(Ordering::SeqCst), sent.load(Ordering::SeqCst));
+}
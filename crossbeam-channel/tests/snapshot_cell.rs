@@ -0,0 +1,161 @@
+//! Tests for snapshot cells.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::panic;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::sync::SnapshotCell;
+use crossbeam_channel::{after, unbounded};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let cell = SnapshotCell::new(1);
+    assert_eq!(cell.load(), 1);
+    assert_eq!(cell.version(), 0);
+
+    cell.store(2);
+    assert_eq!(cell.load(), 2);
+    assert_eq!(cell.version(), 1);
+
+    cell.update(|x| x * 10);
+    assert_eq!(cell.load(), 20);
+    assert_eq!(cell.version(), 2);
+
+    assert_eq!(cell.into_inner(), 20);
+}
+
+#[test]
+fn get_mut() {
+    let mut cell = SnapshotCell::new(1);
+    *cell.get_mut() = 5;
+    assert_eq!(cell.load(), 5);
+    assert_eq!(cell.version(), 0);
+}
+
+#[test]
+fn no_torn_reads() {
+    const COUNT: u64 = 20_000;
+
+    let cell = SnapshotCell::new([0u64; 8]);
+
+    scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| loop {
+                let snapshot = cell.load();
+                assert!(snapshot.iter().all(|&x| x == snapshot[0]));
+                if snapshot[0] == COUNT {
+                    break;
+                }
+            });
+        }
+
+        for i in 1..=COUNT {
+            cell.store([i; 8]);
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn concurrent_updates() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    let cell = SnapshotCell::new(0);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    cell.update(|x| x + 1);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(cell.load(), THREADS * COUNT);
+    assert_eq!(cell.version(), THREADS * COUNT);
+}
+
+#[test]
+fn panic_in_update() {
+    let cell = SnapshotCell::new(1);
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        cell.update(|_| panic!());
+    }));
+    assert!(res.is_err());
+
+    assert_eq!(cell.load(), 1);
+    cell.store(2);
+    assert_eq!(cell.load(), 2);
+}
+
+#[test]
+fn watch() {
+    let cell = SnapshotCell::new(0);
+
+    let watch = cell.watch();
+    assert!(!watch.is_notified());
+    assert!(!watch.wait_timeout(ms(50)));
+
+    cell.store(1);
+    assert!(watch.is_notified());
+
+    // A new handle only sees later stores.
+    assert!(!cell.watch().is_notified());
+}
+
+#[test]
+fn watch_select() {
+    let cell = SnapshotCell::new(0);
+    let (s, r) = unbounded::<()>();
+
+    scope(|scope| {
+        let watch = cell.watch();
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            cell.store(7);
+        });
+
+        select! {
+            recv(r) -> _ => panic!(),
+            recv(watch) -> _ => assert_eq!(cell.load(), 7),
+            recv(after(ms(1000))) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+
+    drop(s);
+}
+
+#[test]
+fn watch_until() {
+    let cell = SnapshotCell::new(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 1..=100 {
+                cell.store(i);
+            }
+        });
+
+        loop {
+            let watch = cell.watch();
+            if cell.load() == 100 {
+                break;
+            }
+            watch.wait();
+        }
+    })
+    .unwrap();
+}
@@ -10,7 +10,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::{after, tick, Select, TryRecvError};
+use crossbeam_channel::{after, tick, tick_counted, Select, TryRecvError};
 use crossbeam_utils::thread::scope;
 
 fn ms(ms: u64) -> Duration {
@@ -351,3 +351,55 @@ fn fairness_duplicates() {
         assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
     }
 }
+
+#[test]
+fn counted() {
+    let start = Instant::now();
+    let r = tick_counted(ms(100));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    let first = r.recv().unwrap();
+    assert_eq!(first.count, 1);
+    assert!(first.instant - start >= ms(100));
+
+    thread::sleep(ms(350));
+    let second = r.recv().unwrap();
+    assert_eq!(second.count, 3);
+    assert!(second.instant - first.instant >= ms(100));
+
+    // The schedule restarts from the last receive.
+    let third = r.recv().unwrap();
+    assert_eq!(third.count, 1);
+}
+
+#[test]
+fn counted_from_creation() {
+    let r = tick_counted(ms(100));
+    thread::sleep(ms(250));
+    assert_eq!(r.try_recv().unwrap().count, 2);
+}
+
+#[test]
+fn counted_select() {
+    let r = tick_counted(ms(50));
+    thread::sleep(ms(180));
+
+    select! {
+        recv(r) -> msg => assert_eq!(msg.unwrap().count, 3),
+        default(ms(1000)) => panic!(),
+    }
+
+    let mut sel = Select::new();
+    let oper = sel.recv(&r);
+    let oper1 = sel.select();
+    assert_eq!(oper1.index(), oper);
+    assert_eq!(oper1.recv(&r).unwrap().count, 1);
+}
+
+#[test]
+fn counted_zero_duration() {
+    let r = tick_counted(ms(0));
+    thread::sleep(ms(10));
+    assert_eq!(r.recv().unwrap().count, 1);
+}
@@ -0,0 +1,110 @@
+//! Tests for timestamping channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{timed_bounded, timed_unbounded};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = timed_unbounded();
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv_timed().unwrap().0, 8);
+
+    assert_eq!(r.try_recv_timed(), Err(TryRecvError::Empty));
+    assert_eq!(
+        r.recv_timeout_timed(ms(100)),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn waited() {
+    let (s, r) = timed_unbounded();
+    s.send(1).unwrap();
+    thread::sleep(ms(300));
+    s.send(2).unwrap();
+
+    let (msg, waited) = r.recv_timed().unwrap();
+    assert_eq!(msg, 1);
+    assert!(waited >= ms(300));
+
+    let (msg, waited) = r.recv_timed().unwrap();
+    assert_eq!(msg, 2);
+    assert!(waited < ms(300));
+}
+
+#[test]
+fn send_errors() {
+    let (s, r) = timed_bounded(1);
+    s.send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+
+    drop(r);
+    assert_eq!(s.send(4), Err(SendError(4)));
+    assert_eq!(s.try_send(5), Err(TrySendError::Disconnected(5)));
+}
+
+#[test]
+fn blocked_send_counts() {
+    let (s, r) = timed_bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| s.send(1).unwrap());
+
+        thread::sleep(ms(300));
+        let (msg, waited) = r.recv_timed().unwrap();
+        assert_eq!(msg, 1);
+        assert!(waited >= ms(300));
+    })
+    .unwrap();
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = timed_bounded::<usize>(3);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let (n, _) = r.recv_timed().unwrap();
+                    assert!(n < COUNT);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
@@ -0,0 +1,177 @@
+//! Tests for the timer service.
+
+#[macro_use]
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{after, unbounded, Select, TimerFullError, TimerService, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let timers = TimerService::new(4);
+    let start = Instant::now();
+    let r = timers.after(ms(50)).unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.len(), 0);
+    assert_eq!(r.capacity(), Some(1));
+
+    let fired = r.recv().unwrap();
+    assert!(fired >= start + ms(50));
+    assert!(Instant::now() >= start + ms(50));
+
+    // The message is delivered only once.
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert!(r.recv_timeout(ms(50)).is_err());
+}
+
+#[test]
+fn capacity() {
+    let timers = TimerService::new(2);
+    assert_eq!(timers.capacity(), 2);
+    assert!(timers.is_empty());
+
+    let r1 = timers.after(ms(10)).unwrap();
+    let r2 = timers.after(ms(10)).unwrap();
+    assert!(timers.is_full());
+    assert_eq!(timers.after(ms(10)).unwrap_err(), TimerFullError);
+
+    // A received timer is still in use until its receiver is dropped.
+    r1.recv().unwrap();
+    assert_eq!(timers.len(), 2);
+
+    drop(r1);
+    assert_eq!(timers.len(), 1);
+    let r3 = timers.after(ms(10)).unwrap();
+    assert!(timers.is_full());
+
+    drop(r2);
+    drop(r3);
+    assert!(timers.is_empty());
+}
+
+#[test]
+fn clones_share_slot() {
+    let timers = TimerService::new(1);
+    let r1 = timers.at(Instant::now()).unwrap();
+    let r2 = r1.clone();
+    assert!(r1.same_channel(&r2));
+
+    drop(r1);
+    assert_eq!(timers.len(), 1);
+    assert!(r2.recv().is_ok());
+
+    drop(r2);
+    assert_eq!(timers.len(), 0);
+}
+
+#[test]
+fn reused_slot() {
+    let timers = TimerService::new(1);
+    let r1 = timers.at(Instant::now()).unwrap();
+    r1.recv().unwrap();
+    drop(r1);
+
+    // The recycled slot starts out fresh.
+    let r2 = timers.after(ms(50)).unwrap();
+    assert_eq!(r2.try_recv(), Err(TryRecvError::Empty));
+    assert!(r2.recv().is_ok());
+
+    let other = TimerService::new(1).at(Instant::now()).unwrap();
+    assert!(!r2.same_channel(&other));
+}
+
+#[test]
+fn next_deadline() {
+    let timers = TimerService::new(4);
+    assert_eq!(timers.next_deadline(), None);
+
+    let now = Instant::now();
+    let r1 = timers.at(now + ms(300)).unwrap();
+    let r2 = timers.at(now + ms(100)).unwrap();
+    let r3 = timers.at(now + ms(200)).unwrap();
+    assert_eq!(timers.next_deadline(), Some(now + ms(100)));
+
+    drop(r2);
+    assert_eq!(timers.next_deadline(), Some(now + ms(200)));
+
+    r3.recv().unwrap();
+    assert_eq!(timers.next_deadline(), Some(now + ms(300)));
+
+    drop(r1);
+    assert_eq!(timers.next_deadline(), None);
+    drop(r3);
+}
+
+#[test]
+fn many_reuses() {
+    let timers = TimerService::new(3);
+    let now = Instant::now();
+
+    let long = timers.at(now + ms(1000)).unwrap();
+    for i in 0..1000 {
+        let r = timers.at(now + ms(i)).unwrap();
+        drop(r);
+    }
+
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers.next_deadline(), Some(now + ms(1000)));
+    drop(long);
+}
+
+#[test]
+fn select() {
+    let timers = TimerService::new(8);
+    let (s, r) = unbounded::<i32>();
+
+    let start = Instant::now();
+    let t1 = timers.after(ms(200)).unwrap();
+    let t2 = timers.after(ms(100)).unwrap();
+
+    select! {
+        recv(r) -> _ => panic!(),
+        recv(t1) -> _ => panic!(),
+        recv(t2) -> msg => assert!(msg.unwrap() >= start + ms(100)),
+    }
+    assert!(start.elapsed() >= ms(100));
+    assert!(start.elapsed() < ms(200));
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&t1);
+    let oper2 = sel.recv(&t2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_ne!(oper.index(), oper2);
+    assert!(oper.recv(&t1).is_ok());
+    drop(s);
+}
+
+#[test]
+fn matches_after() {
+    let timers = TimerService::new(16);
+
+    scope(|scope| {
+        for i in 0..4 {
+            let timers = timers.clone();
+            scope.spawn(move |_| {
+                let start = Instant::now();
+                let t = timers.after(ms(50 * i)).unwrap();
+                let a = after(ms(50 * i));
+
+                let fired = t.recv().unwrap();
+                assert!(fired >= start + ms(50 * i));
+                assert!(a.recv().unwrap() + ms(50) > fired);
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(timers.is_empty());
+}
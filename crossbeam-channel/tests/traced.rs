@@ -0,0 +1,163 @@
+//! Tests for channels carrying tracing spans.
+
+#![cfg(feature = "tracing")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+extern crate tracing;
+extern crate tracing_core;
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crossbeam_channel::traced;
+use crossbeam_channel::{SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::with_default;
+use tracing::{Event, Metadata, Span, Subscriber};
+use tracing_core::span::Current;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+thread_local! {
+    /// Spans entered on the current thread, innermost last.
+    static STACK: RefCell<Vec<(Id, &'static Metadata<'static>)>> = RefCell::new(Vec::new());
+}
+
+/// A subscriber that only keeps track of which span is entered on each thread.
+#[derive(Clone)]
+struct Tracker {
+    next: Arc<AtomicUsize>,
+    meta: Arc<Mutex<Vec<&'static Metadata<'static>>>>,
+}
+
+impl Tracker {
+    fn new() -> Tracker {
+        Tracker {
+            next: Arc::new(AtomicUsize::new(1)),
+            meta: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl Subscriber for Tracker {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes) -> Id {
+        let mut meta = self.meta.lock().unwrap();
+        meta.push(attrs.metadata());
+        let id = self.next.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(id, meta.len());
+        Id::from_u64(id as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event) {}
+
+    fn enter(&self, id: &Id) {
+        let meta = self.meta.lock().unwrap()[id.into_u64() as usize - 1];
+        STACK.with(|s| s.borrow_mut().push((id.clone(), meta)));
+    }
+
+    fn exit(&self, id: &Id) {
+        STACK.with(|s| {
+            let popped = s.borrow_mut().pop().unwrap();
+            assert_eq!(&popped.0, id);
+        });
+    }
+
+    fn current_span(&self) -> Current {
+        STACK.with(|s| match s.borrow().last() {
+            Some(&(ref id, meta)) => Current::new(id.clone(), meta),
+            None => Current::none(),
+        })
+    }
+}
+
+#[test]
+fn propagates_span() {
+    let tracker = Tracker::new();
+    let (s, r) = traced::unbounded();
+
+    scope(|scope| {
+        let t = tracker.clone();
+        let sent = scope
+            .spawn(move |_| {
+                with_default(t, || {
+                    let span = tracing::info_span!("producer");
+                    let _enter = span.enter();
+                    s.send(1).unwrap();
+                    span.id()
+                })
+            })
+            .join()
+            .unwrap();
+
+        with_default(tracker.clone(), || {
+            assert_eq!(Span::current().id(), None);
+
+            let msg = r.recv().unwrap();
+            assert_eq!(msg.span().id(), sent);
+            assert_eq!(*msg.get(), 1);
+
+            msg.in_span(|m| {
+                assert_eq!(m, 1);
+                assert_eq!(Span::current().id(), sent);
+            });
+            assert_eq!(Span::current().id(), None);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn enter_guard() {
+    let tracker = Tracker::new();
+    let (s, r) = traced::bounded(1);
+
+    with_default(tracker, || {
+        let span = tracing::info_span!("outer");
+        let id = span.in_scope(|| {
+            s.send("msg").unwrap();
+            span.id()
+        });
+
+        let (msg, guard) = r.recv().unwrap().enter();
+        assert_eq!(msg, "msg");
+        assert_eq!(Span::current().id(), id);
+        drop(guard);
+        assert_eq!(Span::current().id(), None);
+    });
+}
+
+#[test]
+fn no_span() {
+    let (s, r) = traced::unbounded();
+    s.send(5).unwrap();
+
+    let (msg, span) = r.recv().unwrap().into_parts();
+    assert_eq!(msg, 5);
+    assert!(span.is_none());
+}
+
+#[test]
+fn errors_return_message() {
+    let (s, r) = traced::bounded(1);
+    s.try_send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(s.send_timeout(3, ms(50)), Err(SendTimeoutError::Timeout(3)));
+
+    drop(r);
+    assert_eq!(s.send(4).unwrap_err().into_inner(), 4);
+    assert_eq!(s.try_send(5), Err(TrySendError::Disconnected(5)));
+}
@@ -0,0 +1,286 @@
+//! Tests for unique receivers.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, never, tick, unbounded};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let r = r.into_unique().unwrap();
+
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn into_unique() {
+    let (_s, r) = unbounded::<i32>();
+    let r2 = r.clone();
+    let r = r.into_unique().unwrap_err();
+    let r2 = r2.into_unique().unwrap_err();
+    drop(r2);
+    assert!(r.into_unique().is_ok());
+
+    let (_s, r) = bounded::<i32>(1);
+    assert!(r.into_unique().is_ok());
+
+    let (_s, r) = bounded::<i32>(0);
+    assert!(r.into_unique().is_ok());
+
+    let r = after(ms(100));
+    let r2 = r.clone();
+    let r = r.into_unique().unwrap_err();
+    drop(r2);
+    assert!(r.into_unique().is_ok());
+
+    let r = tick(ms(100));
+    assert!(r.into_unique().is_ok());
+
+    let r = never::<i32>();
+    let _r2 = r.clone();
+    assert!(r.into_unique().is_ok());
+}
+
+#[test]
+fn into_receiver() {
+    let (s, r) = bounded(2);
+    let r = r.into_unique().unwrap().into_receiver();
+    let r2 = r.clone();
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r2.recv(), Ok(2));
+}
+
+#[test]
+fn drain_mut() {
+    let (s, r) = unbounded();
+    let mut r = r.into_unique().unwrap();
+
+    for i in 0..100 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(
+        r.drain_mut().collect::<Vec<_>>(),
+        (0..100).collect::<Vec<_>>()
+    );
+    assert_eq!(r.drain_mut().count(), 0);
+
+    s.send(100).unwrap();
+    drop(s);
+    assert_eq!(r.drain_mut().collect::<Vec<_>>(), [100]);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn len() {
+    let (s, r) = bounded(2);
+    let r = r.into_unique().unwrap();
+
+    assert_eq!(r.capacity(), Some(2));
+    assert!(r.is_empty());
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.len(), 2);
+    assert!(r.is_full());
+}
+
+#[test]
+fn mpsc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = unbounded::<usize>();
+    let r = r.into_unique().unwrap();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        drop(s);
+
+        scope.spawn(move |_| {
+            let mut sum = 0;
+            while let Ok(i) = r.recv() {
+                sum += i;
+            }
+            assert_eq!(sum, THREADS * COUNT * (COUNT - 1) / 2);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn send_to_thread() {
+    let (s, r) = bounded(0);
+    let r = r.into_unique().unwrap();
+
+    let t = thread::spawn(move || r.recv());
+    s.send(3).unwrap();
+    assert_eq!(t.join().unwrap(), Ok(3));
+}
+
+#[test]
+fn many_blocks() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = unbounded();
+    let r = r.into_unique().unwrap();
+
+    let mut next = 0;
+    for i in 0..COUNT {
+        s.send(i).unwrap();
+        if i % 3 == 0 {
+            assert_eq!(r.try_recv(), Ok(next));
+            next += 1;
+        }
+    }
+    for i in next..COUNT {
+        assert_eq!(r.recv(), Ok(i));
+    }
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn switch_receivers() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter(usize);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = unbounded();
+    for i in 0..200 {
+        s.send(DropCounter(i)).unwrap();
+    }
+
+    let mut next = 0;
+    let mut r = r;
+    for &n in &[10, 45, 3, 31] {
+        let unique = r.into_unique().unwrap();
+        for _ in 0..n {
+            assert_eq!(unique.recv().unwrap().0, next);
+            next += 1;
+        }
+
+        r = unique.into_receiver();
+        let r2 = r.clone();
+        for _ in 0..n {
+            assert_eq!(r2.recv().unwrap().0, next);
+            next += 1;
+        }
+    }
+
+    assert_eq!(DROPS.load(Ordering::SeqCst), next);
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 200);
+}
+
+#[test]
+fn into_inner() {
+    let (s, r) = bounded(10);
+    for i in 0..5 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.recv(), Ok(0));
+
+    let r = r.try_into_inner().unwrap_err();
+    let s2 = s.clone();
+    drop(s);
+    let r = r.try_into_inner().unwrap_err();
+
+    drop(s2);
+    let r2 = r.clone();
+    let r = r.try_into_inner().unwrap_err();
+
+    drop(r2);
+    assert_eq!(r.try_into_inner().unwrap(), [1, 2, 3, 4]);
+}
+
+#[test]
+fn into_inner_flavors() {
+    let (s, r) = unbounded();
+    s.send(1).unwrap();
+    drop(s);
+    assert_eq!(r.try_into_inner().unwrap(), [1]);
+
+    let (s, r) = bounded::<i32>(0);
+    drop(s);
+    assert!(r.try_into_inner().unwrap().is_empty());
+
+    assert!(never::<i32>().try_into_inner().unwrap().is_empty());
+
+    let r = after(ms(0));
+    thread::sleep(ms(10));
+    assert_eq!(r.try_into_inner().unwrap().len(), 1);
+
+    let r = tick(ms(1000));
+    let r2 = r.clone();
+    let r = r.try_into_inner().unwrap_err();
+    drop(r2);
+    assert!(r.try_into_inner().unwrap().is_empty());
+}
+
+#[test]
+fn into_inner_drops_nothing() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = unbounded();
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for _ in 0..100 {
+                s.send(DropCounter).unwrap();
+            }
+        });
+    })
+    .unwrap();
+
+    let msgs = r.try_into_inner().unwrap();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    assert_eq!(msgs.len(), 100);
+
+    drop(msgs);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 100);
+}
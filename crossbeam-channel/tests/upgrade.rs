@@ -0,0 +1,139 @@
+//! Tests for upgradable channels.
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::upgradable_bounded;
+use crossbeam_channel::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = upgradable_bounded(2);
+    assert_eq!(s.capacity(), Some(2));
+    assert!(!r.is_upgraded());
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(s.try_send(3), Err(TrySendError::Full(3)));
+
+    s.upgrade();
+    assert!(r.is_upgraded());
+    assert_eq!(r.capacity(), None);
+
+    for i in 3..10 {
+        s.try_send(i).unwrap();
+    }
+    assert_eq!(r.len(), 9);
+    assert_eq!(
+        r.iter().take(9).collect::<Vec<_>>(),
+        (1..10).collect::<Vec<_>>()
+    );
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn wakes_blocked_sender() {
+    let (s, r) = upgradable_bounded(1);
+    s.send(1).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let start = Instant::now();
+            s.send(2).unwrap();
+            assert!(start.elapsed() >= ms(100));
+        });
+
+        thread::sleep(ms(100));
+        r.upgrade();
+    })
+    .unwrap();
+
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Ok(2));
+}
+
+#[test]
+fn wakes_blocked_receiver() {
+    let (s, r) = upgradable_bounded::<i32>(1);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(100));
+            s.upgrade();
+            thread::sleep(ms(100));
+            s.send(7).unwrap();
+        });
+
+        assert_eq!(r.recv(), Ok(7));
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_timeout() {
+    let (s, r) = upgradable_bounded::<i32>(1);
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+
+    s.upgrade();
+    assert_eq!(r.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+    s.send(1).unwrap();
+    assert_eq!(r.recv_timeout(ms(50)), Ok(1));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = upgradable_bounded::<i32>(1);
+    s.send(1).unwrap();
+    drop(s);
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(r.recv(), Err(RecvError));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+
+    let (s, r) = upgradable_bounded::<i32>(1);
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    s.upgrade();
+    assert_eq!(s.send(2), Err(SendError(2)));
+}
+
+#[test]
+fn order_across_upgrade() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = upgradable_bounded::<(usize, usize)>(3);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send((t, i)).unwrap();
+                }
+            });
+        }
+
+        scope.spawn(|_| {
+            thread::sleep(ms(10));
+            s.upgrade();
+        });
+
+        let mut next = [0; THREADS];
+        for _ in 0..COUNT * THREADS {
+            let (t, i) = r.recv().unwrap();
+            assert_eq!(i, next[t]);
+            next[t] += 1;
+        }
+    })
+    .unwrap();
+
+    assert!(r.is_upgraded());
+}
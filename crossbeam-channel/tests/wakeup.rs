@@ -0,0 +1,151 @@
+//! Tests that blocked operations are woken up one at a time.
+//!
+//! Every thread in these tests performs a single blocking operation. If a thread gets woken up
+//! without being able to complete its operation, it blocks again, which the test harness counts.
+
+#![cfg(feature = "test-harness")]
+
+extern crate crossbeam_channel;
+extern crate crossbeam_utils;
+
+use crossbeam_channel::{bounded, test_harness, unbounded, Receiver, Select, Sender};
+use crossbeam_utils::thread::scope;
+
+const THREADS: usize = 8;
+
+/// Blocks `THREADS` receivers on `r`, then sends messages one at a time.
+fn receivers(s: Sender<usize>, r: Receiver<usize>) {
+    let harness = test_harness();
+    let (done_s, done_r) = unbounded();
+
+    scope(|scope| {
+        let mut ids = Vec::new();
+        for _ in 0..THREADS {
+            let r = r.clone();
+            let done_s = done_s.clone();
+            let t = scope.spawn(move |_| done_s.send(r.recv().unwrap()).unwrap());
+            harness.wait_until_blocked(t.thread().id());
+            ids.push(t.thread().id());
+        }
+
+        for i in 0..THREADS {
+            s.send(i).unwrap();
+            // Exactly one receiver completes its operation.
+            assert_eq!(done_r.recv(), Ok(i));
+        }
+
+        for id in ids {
+            assert_eq!(harness.times_blocked(id), 1);
+        }
+    })
+    .unwrap();
+}
+
+/// Blocks `THREADS` senders on a full channel, then receives messages one at a time.
+fn senders(s: Sender<usize>, r: Receiver<usize>, prefill: usize) {
+    let harness = test_harness();
+    let (done_s, done_r) = unbounded();
+
+    for i in 0..prefill {
+        s.send(i).unwrap();
+    }
+
+    scope(|scope| {
+        let mut ids = Vec::new();
+        for i in 0..THREADS {
+            let s = s.clone();
+            let done_s = done_s.clone();
+            let t = scope.spawn(move |_| {
+                s.send(prefill + i).unwrap();
+                done_s.send(()).unwrap();
+            });
+            harness.wait_until_blocked(t.thread().id());
+            ids.push(t.thread().id());
+        }
+
+        for _ in 0..THREADS {
+            r.recv().unwrap();
+            // Exactly one sender completes its operation.
+            done_r.recv().unwrap();
+        }
+
+        for id in ids {
+            assert_eq!(harness.times_blocked(id), 1);
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn array_receivers() {
+    let (s, r) = bounded(THREADS);
+    receivers(s, r);
+}
+
+#[test]
+fn array_senders() {
+    let (s, r) = bounded(1);
+    senders(s, r, 1);
+}
+
+#[test]
+fn list_receivers() {
+    let (s, r) = unbounded();
+    receivers(s, r);
+}
+
+#[test]
+fn zero_receivers() {
+    let (s, r) = bounded(0);
+    receivers(s, r);
+}
+
+#[test]
+fn zero_senders() {
+    let (s, r) = bounded(0);
+    senders(s, r, 0);
+}
+
+#[test]
+fn select_multiple_channels() {
+    let harness = test_harness();
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(0);
+    let (done_s, done_r) = unbounded();
+
+    scope(|scope| {
+        let mut ids = Vec::new();
+        for _ in 0..THREADS {
+            let (r1, r2) = (&r1, &r2);
+            let done_s = done_s.clone();
+            let t = scope.spawn(move |_| {
+                let mut sel = Select::new();
+                sel.recv(r1);
+                sel.recv(r2);
+                let oper = sel.select();
+                let msg = match oper.index() {
+                    0 => oper.recv(r1),
+                    _ => oper.recv(r2),
+                };
+                done_s.send(msg.unwrap()).unwrap();
+            });
+            harness.wait_until_blocked(t.thread().id());
+            ids.push(t.thread().id());
+        }
+
+        for i in 0..THREADS {
+            if i % 2 == 0 {
+                s1.send(i).unwrap();
+            } else {
+                s2.send(i).unwrap();
+            }
+            // Exactly one selecting thread completes its operation.
+            assert_eq!(done_r.recv(), Ok(i));
+        }
+
+        for id in ids {
+            assert_eq!(harness.times_blocked(id), 1);
+        }
+    })
+    .unwrap();
+}
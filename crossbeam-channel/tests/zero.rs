@@ -388,6 +388,50 @@ fn stress_timeout_two_threads() {
     .unwrap();
 }
 
+#[test]
+fn stress_timeout_reclaim() {
+    const COUNT: usize = 1000;
+
+    let (s, r) = bounded(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                let mut msg = i;
+                loop {
+                    match s.send_timeout(msg, ms(1)) {
+                        Ok(()) => break,
+                        Err(SendTimeoutError::Timeout(m)) => {
+                            // The message must come back intact.
+                            assert_eq!(m, i);
+                            msg = m;
+                        }
+                        Err(SendTimeoutError::Disconnected(_)) => panic!(),
+                    }
+                }
+            }
+        });
+
+        scope.spawn(|_| {
+            let mut rng = thread_rng();
+            for i in 0..COUNT {
+                loop {
+                    if rng.gen_range(0, 4) == 0 {
+                        thread::sleep(ms(1));
+                    }
+                    if let Ok(x) = r.recv_timeout(ms(1)) {
+                        assert_eq!(x, i);
+                        break;
+                    }
+                }
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+}
+
 #[test]
 fn drops() {
     static DROPS: AtomicUsize = AtomicUsize::new(0);
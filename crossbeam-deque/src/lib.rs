@@ -14,6 +14,9 @@
 //! [`Injector`] is a FIFO queue, where tasks are pushed and stolen from opposite ends. It is
 //! shared among threads and is usually the entry point for new tasks.
 //!
+//! [`PriorityInjector`] is a set of such queues, one per priority level. Tasks are always stolen
+//! from the highest-priority queue that has any.
+//!
 //! [`Worker`] has two constructors:
 //!
 //! * [`new_fifo()`] - Creates a FIFO queue, in which tasks are pushed and popped from opposite
@@ -78,6 +81,7 @@
 //! [`Worker`]: struct.Worker.html
 //! [`Stealer`]: struct.Stealer.html
 //! [`Injector`]: struct.Injector.html
+//! [`PriorityInjector`]: struct.PriorityInjector.html
 //! [`Steal::Retry`]: enum.Steal.html#variant.Retry
 //! [`new_fifo()`]: struct.Worker.html#method.new_fifo
 //! [`new_lifo()`]: struct.Worker.html#method.new_lifo
@@ -1812,6 +1816,194 @@ impl<T> fmt::Debug for Injector<T> {
     }
 }
 
+/// An injector queue with several priority lanes.
+///
+/// Every lane is a FIFO [`Injector`]. Lane 0 has the highest priority, and each following lane
+/// has a lower priority than the one before it. Steal operations always take tasks from the
+/// highest-priority lane that isn't empty, and batches are never mixed across lanes, so
+/// lower-priority tasks never get ahead of higher-priority ones.
+///
+/// If stealing from a lane fails with [`Steal::Retry`], the steal operation fails with it too
+/// instead of moving on to a lower-priority lane.
+///
+/// [`Injector`]: struct.Injector.html
+/// [`Steal::Retry`]: enum.Steal.html#variant.Retry
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_deque::{PriorityInjector, Steal};
+///
+/// let q = PriorityInjector::new(2);
+/// q.push(1, "low");
+/// q.push(0, "high");
+///
+/// assert_eq!(q.steal(), Steal::Success("high"));
+/// assert_eq!(q.steal(), Steal::Success("low"));
+/// assert_eq!(q.steal(), Steal::Empty);
+/// ```
+pub struct PriorityInjector<T> {
+    /// The lanes, from the highest priority to the lowest.
+    lanes: Box<[Injector<T>]>,
+}
+
+impl<T> PriorityInjector<T> {
+    /// Creates a new injector queue with `lanes` priority lanes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lanes` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::PriorityInjector;
+    ///
+    /// let q = PriorityInjector::<i32>::new(3);
+    /// assert_eq!(q.lanes(), 3);
+    /// ```
+    pub fn new(lanes: usize) -> PriorityInjector<T> {
+        assert!(lanes > 0, "a priority injector needs at least one lane");
+
+        PriorityInjector {
+            lanes: (0..lanes)
+                .map(|_| Injector::new())
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        }
+    }
+
+    /// Returns the number of priority lanes.
+    pub fn lanes(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// Pushes a task into the lane for `priority`, where 0 is the highest priority.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `priority` is not less than the number of lanes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::PriorityInjector;
+    ///
+    /// let q = PriorityInjector::new(2);
+    /// q.push(0, 1);
+    /// q.push(1, 2);
+    /// ```
+    pub fn push(&self, priority: usize, task: T) {
+        assert!(priority < self.lanes.len(), "priority out of range");
+        self.lanes[priority].push(task);
+    }
+
+    /// Steals a task from the highest-priority lane that isn't empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityInjector, Steal};
+    ///
+    /// let q = PriorityInjector::new(2);
+    /// q.push(1, 1);
+    /// q.push(1, 2);
+    /// q.push(0, 3);
+    ///
+    /// assert_eq!(q.steal(), Steal::Success(3));
+    /// assert_eq!(q.steal(), Steal::Success(1));
+    /// assert_eq!(q.steal(), Steal::Success(2));
+    /// ```
+    pub fn steal(&self) -> Steal<T> {
+        self.first_lane(|lane| lane.steal())
+    }
+
+    /// Steals a batch of tasks from the highest-priority lane that isn't empty and pushes them
+    /// into a worker.
+    ///
+    /// All tasks in the batch come from the same lane. How many tasks exactly will be stolen is
+    /// not specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityInjector, Worker};
+    ///
+    /// let q = PriorityInjector::new(2);
+    /// q.push(1, 1);
+    /// q.push(0, 2);
+    ///
+    /// let w = Worker::new_fifo();
+    /// let _ = q.steal_batch(&w);
+    /// assert_eq!(w.pop(), Some(2));
+    /// assert_eq!(w.pop(), None);
+    /// ```
+    pub fn steal_batch(&self, dest: &Worker<T>) -> Steal<()> {
+        self.first_lane(|lane| lane.steal_batch(dest))
+    }
+
+    /// Steals a batch of tasks from the highest-priority lane that isn't empty, pushes them into
+    /// a worker, and pops a task from that worker.
+    ///
+    /// All tasks in the batch come from the same lane. How many tasks exactly will be stolen is
+    /// not specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityInjector, Steal, Worker};
+    ///
+    /// let q = PriorityInjector::new(2);
+    /// q.push(1, 1);
+    /// q.push(0, 2);
+    ///
+    /// let w = Worker::new_fifo();
+    /// assert_eq!(q.steal_batch_and_pop(&w), Steal::Success(2));
+    /// assert_eq!(w.pop(), None);
+    /// assert_eq!(q.steal_batch_and_pop(&w), Steal::Success(1));
+    /// ```
+    pub fn steal_batch_and_pop(&self, dest: &Worker<T>) -> Steal<T> {
+        self.first_lane(|lane| lane.steal_batch_and_pop(dest))
+    }
+
+    /// Returns `true` if all lanes are empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::PriorityInjector;
+    ///
+    /// let q = PriorityInjector::new(2);
+    ///
+    /// assert!(q.is_empty());
+    /// q.push(1, 1);
+    /// assert!(!q.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lanes.iter().all(|lane| lane.is_empty())
+    }
+
+    /// Returns the outcome of `steal` on the first lane where it isn't `Steal::Empty`.
+    fn first_lane<R, F>(&self, mut steal: F) -> Steal<R>
+    where
+        F: FnMut(&Injector<T>) -> Steal<R>,
+    {
+        for lane in self.lanes.iter() {
+            match steal(lane) {
+                Steal::Empty => {}
+                res => return res,
+            }
+        }
+        Steal::Empty
+    }
+}
+
+impl<T> fmt::Debug for PriorityInjector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("PriorityInjector { .. }")
+    }
+}
+
 /// Possible outcomes of a steal operation.
 ///
 /// # Examples
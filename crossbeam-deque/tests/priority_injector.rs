@@ -0,0 +1,159 @@
+extern crate crossbeam_deque as deque;
+extern crate crossbeam_utils as utils;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+use deque::Steal::{Empty, Success};
+use deque::{PriorityInjector, Worker};
+use utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let q = PriorityInjector::new(3);
+    assert_eq!(q.steal(), Empty);
+
+    q.push(2, 1);
+    q.push(1, 2);
+    q.push(0, 3);
+    q.push(2, 4);
+    q.push(0, 5);
+
+    assert_eq!(q.steal(), Success(3));
+    assert_eq!(q.steal(), Success(5));
+    assert_eq!(q.steal(), Success(2));
+    assert_eq!(q.steal(), Success(1));
+    assert_eq!(q.steal(), Success(4));
+    assert_eq!(q.steal(), Empty);
+}
+
+#[test]
+fn is_empty() {
+    let q = PriorityInjector::new(2);
+    assert!(q.is_empty());
+
+    q.push(1, 1);
+    assert!(!q.is_empty());
+    q.push(0, 2);
+    assert!(!q.is_empty());
+
+    let _ = q.steal();
+    assert!(!q.is_empty());
+    let _ = q.steal();
+    assert!(q.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "priority out of range")]
+fn push_out_of_range() {
+    let q = PriorityInjector::new(2);
+    q.push(2, ());
+}
+
+#[test]
+#[should_panic(expected = "at least one lane")]
+fn no_lanes() {
+    PriorityInjector::<()>::new(0);
+}
+
+#[test]
+fn steal_batch_single_lane() {
+    let q = PriorityInjector::new(2);
+    for i in 0..10 {
+        q.push(1, i);
+    }
+    for i in 10..20 {
+        q.push(0, i);
+    }
+
+    // Batches never mix lanes, and the high-priority lane is drained first.
+    let w = Worker::new_fifo();
+    let mut batches = Vec::new();
+    while let Success(()) = q.steal_batch(&w) {
+        let mut batch = Vec::new();
+        while let Some(task) = w.pop() {
+            batch.push(task);
+        }
+        batches.push(batch);
+    }
+
+    for batch in &batches {
+        assert!(batch.iter().all(|&t| t < 10) || batch.iter().all(|&t| t >= 10));
+    }
+    let order = batches.concat();
+    assert_eq!(order, (10..20).chain(0..10).collect::<Vec<_>>());
+    assert!(q.is_empty());
+}
+
+#[test]
+fn steal_batch_and_pop_priority() {
+    let q = PriorityInjector::new(2);
+    for i in 0..100 {
+        q.push(1, i);
+    }
+    for i in 100..200 {
+        q.push(0, i);
+    }
+
+    // Everything from the high-priority lane comes out before anything from the low one.
+    let w = Worker::new_fifo();
+    let mut order = Vec::new();
+    loop {
+        match w.pop() {
+            Some(task) => order.push(task),
+            None => match q.steal_batch_and_pop(&w) {
+                Success(task) => order.push(task),
+                _ => break,
+            },
+        }
+    }
+
+    assert_eq!(order.len(), 200);
+    assert_eq!(order[..100], (100..200).collect::<Vec<_>>()[..]);
+    assert_eq!(order[100..], (0..100).collect::<Vec<_>>()[..]);
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+    const LANES: usize = 3;
+
+    let q = PriorityInjector::new(LANES);
+    let stolen = (0..COUNT * LANES)
+        .map(|_| AtomicUsize::new(0))
+        .collect::<Vec<_>>();
+
+    scope(|scope| {
+        for lane in 0..LANES {
+            let q = &q;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    q.push(lane, lane * COUNT + i);
+                }
+            });
+        }
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                let w = Worker::new_fifo();
+                for _ in 0..COUNT * LANES {
+                    if let Success(n) = q.steal_batch_and_pop(&w) {
+                        stolen[n].fetch_add(1, SeqCst);
+                    }
+                    while let Some(n) = w.pop() {
+                        stolen[n].fetch_add(1, SeqCst);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    while let Success(n) = q.steal() {
+        stolen[n].fetch_add(1, SeqCst);
+    }
+    for s in stolen.iter() {
+        assert_eq!(s.load(SeqCst), 1);
+    }
+}
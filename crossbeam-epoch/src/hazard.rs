@@ -0,0 +1,431 @@
+//! Hazard-pointer based memory reclamation.
+//!
+//! Epoch-based reclamation is cheap for readers, but a single thread that stays pinned for a long
+//! time stops the epoch from advancing, and garbage keeps piling up until it unpins. Hazard
+//! pointers bound the amount of garbage instead: a reader announces each pointer it is about to
+//! dereference by [`protect`]ing it, and a retired object is destructed as soon as no hazard
+//! pointer protects it, no matter what other threads are doing.
+//!
+//! The price is paid by readers, who need one hazard pointer per object they hold at a time and a
+//! full fence every time they protect a pointer.
+//!
+//! Objects are retired into a [`Domain`], which also hands out hazard pointers. Most users can
+//! use the global domain through [`hazard_pointer`] and [`retire`].
+//!
+//! # Examples
+//!
+//! ```
+//! use std::ptr;
+//! use std::sync::atomic::{AtomicPtr, Ordering};
+//! use crossbeam_epoch::hazard;
+//!
+//! let head = AtomicPtr::new(Box::into_raw(Box::new(1)));
+//!
+//! // A reader protects the object before dereferencing it.
+//! let mut hp = hazard::hazard_pointer();
+//! let p = hp.protect(&head);
+//! assert_eq!(unsafe { *p }, 1);
+//!
+//! // A writer unlinks the object and retires it. It won't be destructed while `hp` protects it.
+//! let old = head.swap(ptr::null_mut(), Ordering::AcqRel);
+//! unsafe { hazard::retire(old) };
+//! assert_eq!(unsafe { *p }, 1);
+//!
+//! hp.reset();
+//! hazard::default_domain().reclaim();
+//! ```
+//!
+//! [`protect`]: struct.HazardPointer.html#method.protect
+//! [`Domain`]: struct.Domain.html
+//! [`hazard_pointer`]: fn.hazard_pointer.html
+//! [`retire`]: fn.retire.html
+
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{self, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The number of retired objects a domain keeps, beyond twice the number of hazard pointers,
+/// before it tries to reclaim them.
+const RECLAIM_THRESHOLD: usize = 64;
+
+lazy_static! {
+    /// The global domain.
+    static ref DOMAIN: Domain = Domain::new();
+}
+
+/// Returns the global domain.
+pub fn default_domain() -> &'static Domain {
+    &DOMAIN
+}
+
+/// Acquires a hazard pointer from the global domain.
+pub fn hazard_pointer() -> HazardPointer<'static> {
+    DOMAIN.hazard_pointer()
+}
+
+/// Retires an object into the global domain, destructing it once no hazard pointer protects it.
+///
+/// The global domain is never dropped, so the object may be destructed at any later time, on any
+/// thread. That is why it must be `Send` and must not borrow anything.
+///
+/// # Safety
+///
+/// See [`Domain::retire`].
+///
+/// [`Domain::retire`]: struct.Domain.html#method.retire
+pub unsafe fn retire<T: Send + 'static>(ptr: *mut T) {
+    DOMAIN.retire(ptr)
+}
+
+/// A slot holding the pointer protected by a hazard pointer.
+struct Slot {
+    /// The protected pointer, or null.
+    ptr: AtomicPtr<u8>,
+
+    /// Set to `true` while the slot is owned by a hazard pointer.
+    active: AtomicBool,
+
+    /// The next slot in the domain.
+    next: *const Slot,
+}
+
+/// An object waiting to be destructed.
+struct Retired {
+    /// The object.
+    ptr: *mut u8,
+
+    /// Destructs the object.
+    drop: unsafe fn(*mut u8),
+}
+
+unsafe impl Send for Retired {}
+
+/// Destructs a boxed object of type `T`.
+unsafe fn drop_box<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+/// A set of hazard pointers together with the objects retired against them.
+///
+/// An object retired into a domain is only checked against the hazard pointers of that domain.
+pub struct Domain {
+    /// A linked list of slots, which only ever grows.
+    slots: AtomicPtr<Slot>,
+
+    /// The number of slots in the list.
+    slot_count: AtomicUsize,
+
+    /// Objects that were retired but not destructed yet.
+    retired: Mutex<Vec<Retired>>,
+}
+
+unsafe impl Send for Domain {}
+unsafe impl Sync for Domain {}
+
+impl Domain {
+    /// Creates a new domain.
+    pub fn new() -> Domain {
+        Domain {
+            slots: AtomicPtr::new(ptr::null_mut()),
+            slot_count: AtomicUsize::new(0),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Acquires a hazard pointer.
+    ///
+    /// Slots of dropped hazard pointers are reused, so the number of slots in the domain is the
+    /// largest number of hazard pointers that were alive at the same time.
+    pub fn hazard_pointer(&self) -> HazardPointer<'_> {
+        let mut slot = self.slots.load(Ordering::Acquire) as *const Slot;
+        while let Some(s) = unsafe { slot.as_ref() } {
+            if !s.active.load(Ordering::Relaxed)
+                && s.active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return HazardPointer { slot: s };
+            }
+            slot = s.next;
+        }
+
+        // All slots are taken, so add a new one.
+        let new = Box::into_raw(Box::new(Slot {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null(),
+        }));
+        let mut head = self.slots.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*new).next = head };
+            match self
+                .slots
+                .compare_exchange_weak(head, new, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(h) => head = h,
+            }
+        }
+        self.slot_count.fetch_add(1, Ordering::Relaxed);
+
+        HazardPointer {
+            slot: unsafe { &*new },
+        }
+    }
+
+    /// Retires an object, destructing it once no hazard pointer protects it.
+    ///
+    /// Retired objects are reclaimed in batches. The number of objects waiting in the domain is
+    /// kept proportional to the number of hazard pointers, however long they protect anything.
+    ///
+    /// The object is destructed by whichever thread happens to reclaim it, or when the domain is
+    /// dropped, so it must be `Send`.
+    ///
+    /// # Safety
+    ///
+    /// The object must have been allocated with `Box`, it must not be reachable by threads that
+    /// acquire new hazard pointers, and it must not be retired twice. Anything the object borrows
+    /// must outlive the domain.
+    pub unsafe fn retire<T: Send>(&self, ptr: *mut T) {
+        let len = {
+            let mut retired = self.retired.lock().unwrap();
+            retired.push(Retired {
+                ptr: ptr as *mut u8,
+                drop: drop_box::<T>,
+            });
+            retired.len()
+        };
+
+        if len >= 2 * self.slot_count.load(Ordering::Relaxed) + RECLAIM_THRESHOLD {
+            self.reclaim();
+        }
+    }
+
+    /// Destructs retired objects that are not protected by any hazard pointer, and returns how
+    /// many were destructed.
+    pub fn reclaim(&self) -> usize {
+        let mut retired = Vec::new();
+        mem::swap(&mut retired, &mut *self.retired.lock().unwrap());
+        if retired.is_empty() {
+            return 0;
+        }
+
+        // Pairs with the fence in `HazardPointer::protect`: either the reader sees the object
+        // unlinked, or we see its hazard pointer.
+        atomic::fence(Ordering::SeqCst);
+
+        let mut protected = Vec::new();
+        let mut slot = self.slots.load(Ordering::Acquire) as *const Slot;
+        while let Some(s) = unsafe { slot.as_ref() } {
+            let p = s.ptr.load(Ordering::Relaxed);
+            if !p.is_null() {
+                protected.push(p);
+            }
+            slot = s.next;
+        }
+        protected.sort();
+
+        let mut kept = Vec::new();
+        let mut count = 0;
+        for r in retired {
+            if protected.binary_search(&r.ptr).is_ok() {
+                kept.push(r);
+            } else {
+                unsafe { (r.drop)(r.ptr) };
+                count += 1;
+            }
+        }
+
+        if !kept.is_empty() {
+            self.retired.lock().unwrap().extend(kept);
+        }
+        count
+    }
+}
+
+impl Default for Domain {
+    fn default() -> Domain {
+        Domain::new()
+    }
+}
+
+impl Drop for Domain {
+    fn drop(&mut self) {
+        // Hazard pointers borrow the domain, so none of them can be alive anymore.
+        for r in self.retired.get_mut().unwrap().drain(..) {
+            unsafe { (r.drop)(r.ptr) };
+        }
+
+        let mut slot = *self.slots.get_mut();
+        while !slot.is_null() {
+            let s = unsafe { Box::from_raw(slot) };
+            slot = s.next as *mut Slot;
+        }
+    }
+}
+
+impl fmt::Debug for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Domain { .. }")
+    }
+}
+
+/// A hazard pointer, protecting one object at a time from being destructed.
+///
+/// Created by [`Domain::hazard_pointer`] or [`hazard_pointer`].
+///
+/// [`Domain::hazard_pointer`]: struct.Domain.html#method.hazard_pointer
+/// [`hazard_pointer`]: fn.hazard_pointer.html
+pub struct HazardPointer<'d> {
+    slot: &'d Slot,
+}
+
+impl<'d> HazardPointer<'d> {
+    /// Loads the pointer from `src` and protects the object it points to.
+    ///
+    /// The object stays protected until this hazard pointer protects something else, gets reset,
+    /// or gets dropped. Protecting a null pointer is allowed and protects nothing.
+    pub fn protect<T>(&mut self, src: &AtomicPtr<T>) -> *mut T {
+        let mut ptr = src.load(Ordering::Relaxed);
+        loop {
+            self.slot.ptr.store(ptr as *mut u8, Ordering::Relaxed);
+            atomic::fence(Ordering::SeqCst);
+
+            // If `src` still holds the pointer, the object wasn't retired before it got protected.
+            let current = src.load(Ordering::Acquire);
+            if current == ptr {
+                return ptr;
+            }
+            ptr = current;
+        }
+    }
+
+    /// Stops protecting the object.
+    pub fn reset(&mut self) {
+        self.slot.ptr.store(ptr::null_mut(), Ordering::Release);
+    }
+}
+
+impl<'d> Drop for HazardPointer<'d> {
+    fn drop(&mut self) {
+        self.reset();
+        self.slot.active.store(false, Ordering::Release);
+    }
+}
+
+impl<'d> fmt::Debug for HazardPointer<'d> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("HazardPointer { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+    use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crossbeam_utils::thread;
+
+    use super::Domain;
+
+    struct Elem(Arc<AtomicUsize>);
+
+    impl Drop for Elem {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn protected_objects_survive() {
+        let domain = Domain::new();
+        let drops = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(Box::into_raw(Box::new(Elem(drops.clone()))));
+
+        let mut hp = domain.hazard_pointer();
+        let p = hp.protect(&src);
+        unsafe { domain.retire(src.swap(ptr::null_mut(), Ordering::AcqRel)) };
+
+        assert_eq!(domain.reclaim(), 0);
+        assert_eq!(drops.load(Ordering::Relaxed), 0);
+        assert!(Arc::ptr_eq(unsafe { &(*p).0 }, &drops));
+
+        hp.reset();
+        assert_eq!(domain.reclaim(), 1);
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn slots_are_reused() {
+        let domain = Domain::new();
+        for _ in 0..10 {
+            let _a = domain.hazard_pointer();
+            let _b = domain.hazard_pointer();
+        }
+        assert_eq!(domain.slot_count.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn drop_domain() {
+        let drops = Arc::new(AtomicUsize::new(0));
+        {
+            let domain = Domain::new();
+            let _hp = domain.hazard_pointer();
+            for _ in 0..10 {
+                unsafe { domain.retire(Box::into_raw(Box::new(Elem(drops.clone())))) };
+            }
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    fn garbage_is_bounded() {
+        let domain = Domain::new();
+        let drops = Arc::new(AtomicUsize::new(0));
+
+        // A hazard pointer that stays alive doesn't hold back unrelated garbage.
+        let _idle = domain.hazard_pointer();
+        for _ in 0..1000 {
+            unsafe { domain.retire(Box::into_raw(Box::new(Elem(drops.clone())))) };
+        }
+        assert!(domain.retired.lock().unwrap().len() < 2 + super::RECLAIM_THRESHOLD);
+    }
+
+    #[test]
+    fn stress() {
+        const THREADS: usize = 4;
+        const STEPS: usize = 10_000;
+
+        let domain = Domain::new();
+        let drops = Arc::new(AtomicUsize::new(0));
+        let src = AtomicPtr::new(Box::into_raw(Box::new(Elem(drops.clone()))));
+
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let mut hp = domain.hazard_pointer();
+                    for _ in 0..STEPS {
+                        let p = hp.protect(&src);
+                        // The object must still be alive.
+                        assert!(Arc::ptr_eq(unsafe { &(*p).0 }, &drops));
+                    }
+                });
+            }
+            scope.spawn(|_| {
+                for _ in 0..STEPS {
+                    let new = Box::into_raw(Box::new(Elem(drops.clone())));
+                    let old = src.swap(new, Ordering::AcqRel);
+                    unsafe { domain.retire(old) };
+                }
+            });
+        })
+        .unwrap();
+
+        unsafe { drop(Box::from_raw(src.load(Ordering::Relaxed))) };
+        drop(domain);
+        assert_eq!(drops.load(Ordering::Relaxed), STEPS + 1);
+    }
+}
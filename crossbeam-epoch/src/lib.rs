@@ -48,11 +48,15 @@
 //! For majority of use cases, just use the default garbage collector by invoking [`pin`]. If you
 //! want to create your own garbage collector, use the [`Collector`] API.
 //!
+//! For structures that can't afford garbage piling up while a thread stays pinned, the [`hazard`]
+//! module provides hazard pointers as an alternative reclamation scheme.
+//!
 //! [`Atomic`]: struct.Atomic.html
 //! [`Collector`]: struct.Collector.html
 //! [`Shared`]: struct.Shared.html
 //! [`pin`]: fn.pin.html
 //! [`defer`]: fn.defer.html
+//! [`hazard`]: hazard/index.html
 
 #![warn(missing_docs)]
 #![warn(missing_debug_implementations)]
@@ -101,6 +105,7 @@ cfg_if! {
         extern crate lazy_static;
 
         mod default;
+        pub mod hazard;
         pub use self::default::{default_collector, is_pinned, pin};
     }
 }
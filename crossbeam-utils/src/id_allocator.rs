@@ -0,0 +1,171 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of IDs tracked by a single word.
+const BITS: usize = mem::size_of::<usize>() * 8;
+
+/// A concurrent allocator of small integer IDs.
+///
+/// The allocator hands out IDs in the range `0..capacity` and takes them back when they are
+/// freed. It is a bitset with one bit per ID, and both [`alloc`] and [`free`] are lock-free.
+///
+/// [`alloc`] prefers small IDs: the allocated IDs are kept dense, which makes them suitable as
+/// indices into slabs and slot tables. Without concurrent allocations, the smallest free ID is
+/// always returned.
+///
+/// [`alloc`]: struct.IdAllocator.html#method.alloc
+/// [`free`]: struct.IdAllocator.html#method.free
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::IdAllocator;
+///
+/// let ids = IdAllocator::new(2);
+///
+/// let a = ids.alloc().unwrap();
+/// let b = ids.alloc().unwrap();
+/// assert_eq!((a, b), (0, 1));
+/// assert_eq!(ids.alloc(), None);
+///
+/// ids.free(a);
+/// assert_eq!(ids.alloc(), Some(0));
+/// ```
+pub struct IdAllocator {
+    /// One bit per ID, set if the ID is allocated.
+    ///
+    /// Bits past the capacity in the last word are always set, so they are never handed out.
+    words: Box<[AtomicUsize]>,
+
+    /// The number of IDs.
+    capacity: usize,
+}
+
+impl IdAllocator {
+    /// Creates an allocator for IDs in the range `0..capacity`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::IdAllocator;
+    ///
+    /// let ids = IdAllocator::new(100);
+    /// assert_eq!(ids.capacity(), 100);
+    /// ```
+    pub fn new(capacity: usize) -> IdAllocator {
+        let mut words = Vec::new();
+        let mut start = 0;
+        while start < capacity {
+            let unused = if capacity - start < BITS {
+                !0 << (capacity - start)
+            } else {
+                0
+            };
+            words.push(AtomicUsize::new(unused));
+            start += BITS;
+        }
+
+        IdAllocator {
+            words: words.into_boxed_slice(),
+            capacity,
+        }
+    }
+
+    /// Allocates an ID, or returns `None` if all IDs are in use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::IdAllocator;
+    ///
+    /// let ids = IdAllocator::new(1);
+    /// assert_eq!(ids.alloc(), Some(0));
+    /// assert_eq!(ids.alloc(), None);
+    /// ```
+    pub fn alloc(&self) -> Option<usize> {
+        for (i, word) in self.words.iter().enumerate() {
+            let mut current = word.load(Ordering::Relaxed);
+            while current != !0 {
+                let bit = (!current).trailing_zeros() as usize;
+                let prev = word.fetch_or(1 << bit, Ordering::Acquire);
+                if prev & (1 << bit) == 0 {
+                    return Some(i * BITS + bit);
+                }
+                current = prev | (1 << bit);
+            }
+        }
+        None
+    }
+
+    /// Frees an ID so that it can be allocated again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range or not allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::IdAllocator;
+    ///
+    /// let ids = IdAllocator::new(4);
+    /// let id = ids.alloc().unwrap();
+    /// assert!(ids.is_allocated(id));
+    ///
+    /// ids.free(id);
+    /// assert!(!ids.is_allocated(id));
+    /// ```
+    pub fn free(&self, id: usize) {
+        assert!(id < self.capacity, "ID out of range");
+
+        let bit = 1 << (id % BITS);
+        let prev = self.words[id / BITS].fetch_and(!bit, Ordering::Release);
+        assert!(prev & bit != 0, "freeing an ID that is not allocated");
+    }
+
+    /// Returns `true` if `id` is currently allocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range.
+    pub fn is_allocated(&self, id: usize) -> bool {
+        assert!(id < self.capacity, "ID out of range");
+
+        let bit = 1 << (id % BITS);
+        self.words[id / BITS].load(Ordering::Relaxed) & bit != 0
+    }
+
+    /// Returns the number of allocated IDs.
+    ///
+    /// With concurrent allocations, the count is only a snapshot.
+    pub fn len(&self) -> usize {
+        let set = self
+            .words
+            .iter()
+            .map(|w| w.load(Ordering::Relaxed).count_ones() as usize)
+            .sum::<usize>();
+        set - (self.words.len() * BITS - self.capacity)
+    }
+
+    /// Returns `true` if no IDs are allocated.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of IDs the allocator hands out.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl fmt::Debug for IdAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdAllocator")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
@@ -15,6 +15,9 @@
 //!
 //! * [`Backoff`], for exponential backoff in spin loops.
 //! * [`CachePadded`], for padding and aligning a value to the length of a cache line.
+//! * [`IdAllocator`], for allocating small integer IDs from many threads.
+//! * [`ShardedCounter`], a counter that stays cheap to update from many threads at once.
+//! * [`SmallRng`], a small and fast pseudorandom number generator.
 //! * [`scope`], for spawning threads that borrow local variables from the stack.
 //!
 //! [`AtomicCell`]: atomic/struct.AtomicCell.html
@@ -24,6 +27,9 @@
 //! [`WaitGroup`]: sync/struct.WaitGroup.html
 //! [`Backoff`]: struct.Backoff.html
 //! [`CachePadded`]: struct.CachePadded.html
+//! [`IdAllocator`]: struct.IdAllocator.html
+//! [`ShardedCounter`]: struct.ShardedCounter.html
+//! [`SmallRng`]: struct.SmallRng.html
 //! [`scope`]: thread/fn.scope.html
 
 #![warn(missing_docs)]
@@ -53,6 +59,18 @@ pub use cache_padded::CachePadded;
 mod backoff;
 pub use backoff::Backoff;
 
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[cfg_attr(feature = "nightly", cfg(target_has_atomic = "ptr"))]
+mod id_allocator;
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[cfg_attr(feature = "nightly", cfg(target_has_atomic = "ptr"))]
+pub use id_allocator::IdAllocator;
+
+mod rng;
+#[cfg(feature = "std")]
+pub use rng::shuffle;
+pub use rng::SmallRng;
+
 cfg_if! {
     if #[cfg(feature = "std")] {
         #[macro_use]
@@ -60,5 +78,8 @@ cfg_if! {
 
         pub mod sync;
         pub mod thread;
+
+        mod sharded_counter;
+        pub use sharded_counter::ShardedCounter;
     }
 }
@@ -0,0 +1,131 @@
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::num::Wrapping;
+
+/// The seed used when no seed is given.
+const DEFAULT_SEED: u32 = 1406868647;
+
+/// A small and fast pseudorandom number generator.
+///
+/// This is the 32-bit variant of Xorshift, which is cheap enough for things like breaking ties
+/// between equally good choices, but is not suitable for cryptographic purposes.
+///
+/// Source: https://en.wikipedia.org/wiki/Xorshift
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::SmallRng;
+///
+/// let mut rng = SmallRng::with_seed(42);
+/// let i = rng.next_index(10);
+/// assert!(i < 10);
+///
+/// // Generators with the same seed produce the same numbers.
+/// let mut a = SmallRng::with_seed(7);
+/// let mut b = SmallRng::with_seed(7);
+/// assert_eq!(a.next_u32(), b.next_u32());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SmallRng {
+    state: Wrapping<u32>,
+}
+
+impl SmallRng {
+    /// Creates a generator with a fixed default seed.
+    pub fn new() -> SmallRng {
+        SmallRng::with_seed(DEFAULT_SEED)
+    }
+
+    /// Creates a generator with the given seed.
+    ///
+    /// Xorshift gets stuck at zero, so a zero seed is replaced with the default one.
+    pub fn with_seed(seed: u32) -> SmallRng {
+        let seed = if seed == 0 { DEFAULT_SEED } else { seed };
+        SmallRng {
+            state: Wrapping(seed),
+        }
+    }
+
+    /// Returns the next random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x.0
+    }
+
+    /// Returns a random index in the range `0..n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn next_index(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot pick an index in an empty range");
+        let x = self.next_u32();
+
+        // This is a fast alternative to `let j = x % n`.
+        //
+        // Author: Daniel Lemire
+        // Source: https://lemire.me/blog/2016/06/27/a-fast-alternative-to-the-modulo-reduction/
+        ((x as u64).wrapping_mul(n as u64) >> 32) as u32 as usize
+    }
+
+    /// Randomly shuffles a slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::SmallRng;
+    ///
+    /// let mut v = [1, 2, 3, 4, 5];
+    /// SmallRng::with_seed(3).shuffle(&mut v);
+    ///
+    /// v.sort();
+    /// assert_eq!(v, [1, 2, 3, 4, 5]);
+    /// ```
+    pub fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in 1..v.len() {
+            let j = self.next_index(i + 1);
+            v.swap(i, j);
+        }
+    }
+}
+
+impl Default for SmallRng {
+    fn default() -> SmallRng {
+        SmallRng::new()
+    }
+}
+
+/// Randomly shuffles a slice using a generator local to the current thread.
+///
+/// This function is only available with the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::shuffle;
+///
+/// let mut v = vec!['a', 'b', 'c'];
+/// shuffle(&mut v);
+/// assert_eq!(v.len(), 3);
+/// ```
+#[cfg(feature = "std")]
+pub fn shuffle<T>(v: &mut [T]) {
+    if v.len() <= 1 {
+        return;
+    }
+
+    thread_local! {
+        static RNG: Cell<u32> = Cell::new(DEFAULT_SEED);
+    }
+
+    let _ = RNG.try_with(|state| {
+        let mut rng = SmallRng::with_seed(state.get());
+        rng.shuffle(v);
+        state.set(rng.state.0);
+    });
+}
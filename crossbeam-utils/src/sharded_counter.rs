@@ -0,0 +1,152 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use CachePadded;
+
+/// The number of cells in a sharded counter created with `ShardedCounter::new`.
+const DEFAULT_SHARDS: usize = 16;
+
+/// A counter striped across several cells to avoid contention.
+///
+/// Every thread updates a cell of its own choosing, so threads updating the counter at the same
+/// time rarely touch the same cache line. Reading the value requires summing all cells, which
+/// makes [`sum`] slower than an update. This trade-off suits counters that are updated often and
+/// read rarely, like metrics.
+///
+/// Updates wrap around on overflow. As long as the true value is not negative, [`sum`] returns it
+/// exactly, even if some cells went below zero on their own.
+///
+/// [`sum`]: struct.ShardedCounter.html#method.sum
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::thread::scope;
+/// use crossbeam_utils::ShardedCounter;
+///
+/// let sent = ShardedCounter::new();
+///
+/// scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|_| {
+///             for _ in 0..1000 {
+///                 sent.increment();
+///             }
+///         });
+///     }
+/// })
+/// .unwrap();
+///
+/// assert_eq!(sent.sum(), 4000);
+/// ```
+pub struct ShardedCounter {
+    /// The cells, whose number is a power of two.
+    cells: Box<[CachePadded<AtomicUsize>]>,
+}
+
+impl ShardedCounter {
+    /// Creates a counter starting at zero, with a default number of cells.
+    pub fn new() -> ShardedCounter {
+        ShardedCounter::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// Creates a counter starting at zero, with at least `shards` cells.
+    ///
+    /// The number of cells is rounded up to a power of two.
+    pub fn with_shards(shards: usize) -> ShardedCounter {
+        let shards = shards.max(1).next_power_of_two();
+        ShardedCounter {
+            cells: (0..shards)
+                .map(|_| CachePadded::new(AtomicUsize::new(0)))
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        }
+    }
+
+    /// Returns the cell assigned to the current thread.
+    #[inline]
+    fn cell(&self) -> &AtomicUsize {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        thread_local! {
+            /// The index of the current thread, assigned on first use.
+            static INDEX: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let index = INDEX.try_with(|i| *i).unwrap_or(0);
+        &self.cells[index & (self.cells.len() - 1)]
+    }
+
+    /// Adds `n` to the counter.
+    #[inline]
+    pub fn add(&self, n: usize) {
+        self.cell().fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Subtracts `n` from the counter.
+    #[inline]
+    pub fn sub(&self, n: usize) {
+        self.cell().fetch_sub(n, Ordering::Relaxed);
+    }
+
+    /// Adds one to the counter.
+    #[inline]
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Subtracts one from the counter.
+    #[inline]
+    pub fn decrement(&self) {
+        self.sub(1);
+    }
+
+    /// Returns the value of the counter.
+    ///
+    /// Updates made while the cells are being summed may or may not be included.
+    pub fn sum(&self) -> usize {
+        self.cells
+            .iter()
+            .fold(0, |sum, c| sum.wrapping_add(c.load(Ordering::Relaxed)))
+    }
+
+    /// Resets the counter to zero and returns its previous value.
+    ///
+    /// Updates made while the cells are being reset are either included in the returned value or
+    /// kept in the counter, but never lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::ShardedCounter;
+    ///
+    /// let c = ShardedCounter::new();
+    /// c.add(5);
+    /// assert_eq!(c.reset(), 5);
+    /// assert_eq!(c.sum(), 0);
+    /// ```
+    pub fn reset(&self) -> usize {
+        self.cells
+            .iter()
+            .fold(0, |sum, c| sum.wrapping_add(c.swap(0, Ordering::Relaxed)))
+    }
+
+    /// Returns the number of cells.
+    pub fn shards(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> ShardedCounter {
+        ShardedCounter::new()
+    }
+}
+
+impl fmt::Debug for ShardedCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShardedCounter")
+            .field("sum", &self.sum())
+            .finish()
+    }
+}
@@ -0,0 +1,87 @@
+extern crate crossbeam_utils;
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crossbeam_utils::thread::scope;
+use crossbeam_utils::IdAllocator;
+
+#[test]
+fn smallest_first() {
+    let ids = IdAllocator::new(200);
+    for i in 0..200 {
+        assert_eq!(ids.alloc(), Some(i));
+    }
+    assert_eq!(ids.alloc(), None);
+    assert_eq!(ids.len(), 200);
+
+    ids.free(130);
+    ids.free(7);
+    assert_eq!(ids.len(), 198);
+    assert_eq!(ids.alloc(), Some(7));
+    assert_eq!(ids.alloc(), Some(130));
+    assert_eq!(ids.alloc(), None);
+}
+
+#[test]
+fn capacity() {
+    for &cap in &[0, 1, 63, 64, 65, 128] {
+        let ids = IdAllocator::new(cap);
+        assert_eq!(ids.capacity(), cap);
+        assert!(ids.is_empty());
+
+        let mut n = 0;
+        while ids.alloc().is_some() {
+            n += 1;
+        }
+        assert_eq!(n, cap);
+        assert_eq!(ids.len(), cap);
+    }
+}
+
+#[test]
+#[should_panic(expected = "not allocated")]
+fn double_free() {
+    let ids = IdAllocator::new(4);
+    let id = ids.alloc().unwrap();
+    ids.free(id);
+    ids.free(id);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn free_out_of_range() {
+    let ids = IdAllocator::new(4);
+    ids.free(4);
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 10_000;
+
+    let ids = IdAllocator::new(THREADS * 4);
+    let live = Mutex::new(HashSet::new());
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let mut mine = Vec::new();
+                    for _ in 0..4 {
+                        let id = ids.alloc().unwrap();
+                        assert!(live.lock().unwrap().insert(id));
+                        mine.push(id);
+                    }
+                    for id in mine {
+                        assert!(live.lock().unwrap().remove(&id));
+                        ids.free(id);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(ids.is_empty());
+}
@@ -0,0 +1,66 @@
+extern crate crossbeam_utils;
+
+use crossbeam_utils::{shuffle, SmallRng};
+
+#[test]
+fn deterministic() {
+    let mut a = SmallRng::with_seed(123);
+    let mut b = SmallRng::with_seed(123);
+    for _ in 0..100 {
+        assert_eq!(a.next_u32(), b.next_u32());
+    }
+
+    let mut c = a.clone();
+    assert_eq!(a.next_u32(), c.next_u32());
+}
+
+#[test]
+fn zero_seed() {
+    let mut rng = SmallRng::with_seed(0);
+    for _ in 0..100 {
+        assert_ne!(rng.next_u32(), 0);
+    }
+}
+
+#[test]
+fn next_index() {
+    let mut rng = SmallRng::new();
+    let mut seen = [0usize; 7];
+    for _ in 0..7000 {
+        seen[rng.next_index(7)] += 1;
+    }
+    for &count in &seen {
+        assert!(count > 700);
+    }
+
+    assert_eq!(rng.next_index(1), 0);
+}
+
+#[test]
+#[should_panic]
+fn next_index_empty() {
+    SmallRng::new().next_index(0);
+}
+
+#[test]
+fn shuffle_permutes() {
+    let mut rng = SmallRng::with_seed(5);
+    let mut changed = false;
+    for _ in 0..10 {
+        let mut v: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut v);
+        changed |= v != (0..20).collect::<Vec<_>>();
+        v.sort();
+        assert_eq!(v, (0..20).collect::<Vec<_>>());
+    }
+    assert!(changed);
+
+    let mut v: Vec<usize> = (0..20).collect();
+    shuffle(&mut v);
+    v.sort();
+    assert_eq!(v, (0..20).collect::<Vec<_>>());
+
+    let mut empty: [i32; 0] = [];
+    shuffle(&mut empty);
+    rng.shuffle(&mut empty);
+}
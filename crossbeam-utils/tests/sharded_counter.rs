@@ -0,0 +1,65 @@
+extern crate crossbeam_utils;
+
+use crossbeam_utils::thread::scope;
+use crossbeam_utils::ShardedCounter;
+
+#[test]
+fn sharded_counter() {
+    let c = ShardedCounter::new();
+    assert_eq!(c.sum(), 0);
+
+    c.increment();
+    c.add(10);
+    c.decrement();
+    c.sub(3);
+    assert_eq!(c.sum(), 7);
+    assert_eq!(c.reset(), 7);
+    assert_eq!(c.sum(), 0);
+
+    assert_eq!(ShardedCounter::with_shards(0).shards(), 1);
+    assert_eq!(ShardedCounter::with_shards(5).shards(), 8);
+}
+
+#[test]
+fn sharded_counter_threads() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 10_000;
+
+    let c = ShardedCounter::with_shards(4);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let c = &c;
+            scope.spawn(move |_| {
+                for _ in 0..COUNT {
+                    c.increment();
+                }
+                // Decrementing on a different thread than incrementing leaves some cells
+                // negative, which the sum must make up for.
+                if t % 2 == 0 {
+                    c.sub(COUNT / 2);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(c.sum(), THREADS * COUNT - THREADS / 2 * COUNT / 2);
+}
+
+#[test]
+fn sharded_counter_negative_cells() {
+    let c = ShardedCounter::new();
+
+    scope(|scope| {
+        scope.spawn(|_| c.add(100));
+    })
+    .unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| c.sub(40));
+    })
+    .unwrap();
+
+    assert_eq!(c.sum(), 60);
+}